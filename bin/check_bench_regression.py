@@ -0,0 +1,44 @@
+#!/usr/bin/env python3
+"""Fail when a Criterion benchmark got slower than its saved baseline.
+
+Run after `cargo bench -p allegro_cwr -- --baseline <name>`. A benchmark counts as regressed when even the lower
+bound of the confidence interval of its mean time change is above the threshold, so run-to-run noise on shared
+CI machines does not fail the check.
+
+Usage: check_bench_regression.py [criterion_dir] [threshold_percent]
+"""
+
+import glob
+import json
+import os
+import sys
+
+
+def main():
+    criterion_dir = sys.argv[1] if len(sys.argv) > 1 else 'target/criterion'
+    threshold = float(sys.argv[2]) / 100 if len(sys.argv) > 2 else 0.10
+
+    changes = sorted(glob.glob(os.path.join(criterion_dir, '**', 'change', 'estimates.json'), recursive=True))
+    if not changes:
+        print(f'No benchmark comparisons found under {criterion_dir}; run the benchmarks with --baseline first')
+        return 1
+
+    regressed = []
+    for path in changes:
+        with open(path) as f:
+            mean = json.load(f)['mean']
+        name = os.path.relpath(os.path.dirname(os.path.dirname(path)), criterion_dir)
+        change = mean['point_estimate']
+        lower_bound = mean['confidence_interval']['lower_bound']
+        print(f'{name}: {change:+.1%} (at least {lower_bound:+.1%})')
+        if lower_bound > threshold:
+            regressed.append(name)
+
+    if regressed:
+        print(f'Slower than the baseline by more than {threshold:.0%}: {", ".join(regressed)}')
+        return 1
+    return 0
+
+
+if __name__ == '__main__':
+    sys.exit(main())
@@ -0,0 +1,41 @@
+#!/usr/bin/env python3
+"""Generate the double-byte decoding tables used by allegro_cwr::encoding.
+
+Each table covers lead bytes 0x81-0xFE and trail bytes 0x40-0xFE. Entry
+(lead - 0x81) * 191 + (trail - 0x40) holds the Unicode code point as a
+little-endian u16, or 0 when the byte pair is not mapped.
+
+Usage: python3 bin/generate_encoding_tables.py
+"""
+
+import struct
+from pathlib import Path
+
+OUTPUT_DIR = Path(__file__).resolve().parent.parent / "crates" / "allegro_cwr" / "src" / "encoding"
+
+# CWR "Traditional Big5" and "Simplified GB" (GBK is a superset of GB2312)
+CODECS = {"big5.bin": "big5", "gbk.bin": "gbk"}
+
+
+def build_table(codec):
+    entries = []
+    for lead in range(0x81, 0xFF):
+        for trail in range(0x40, 0xFF):
+            try:
+                text = bytes([lead, trail]).decode(codec)
+            except UnicodeDecodeError:
+                text = ""
+            code_point = ord(text) if len(text) == 1 and ord(text) <= 0xFFFF else 0
+            entries.append(code_point)
+    return struct.pack("<%dH" % len(entries), *entries)
+
+
+def main():
+    for filename, codec in CODECS.items():
+        table = build_table(codec)
+        (OUTPUT_DIR / filename).write_bytes(table)
+        print("Wrote %s (%d bytes)" % (filename, len(table)))
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,263 @@
+//! The `parse`, `summary` and `diff` commands, which report on CWR files without converting them
+
+use std::ffi::OsString;
+use std::io::{self, Write};
+use std::process;
+
+use allegro_cwr::parser::ParseOptions;
+use allegro_cwr::{
+    CwrFile, CwrParseError, ParsedRecord, SummaryHandler, diff_cwr_files, process_cwr_reader_stream,
+    process_cwr_stream_with_options,
+};
+use allegro_cwr_cli::{
+    BaseConfig, check_log_format, create_output, get_value, init_logging_and_parse_args, status_error,
+};
+
+#[derive(Default)]
+struct Config {
+    base: BaseConfig,
+    charset_override: Option<String>,
+    output_filename: Option<String>,
+}
+
+impl Config {
+    fn parse_options(&self) -> ParseOptions {
+        ParseOptions {
+            version_hint: self.base.cwr_version,
+            charset_override: self.charset_override.clone(),
+            ..Default::default()
+        }
+    }
+
+    fn output(&self) -> Box<dyn Write> {
+        create_output(self.output_filename.as_deref()).unwrap_or_else(|e| {
+            status_error!(
+                "Error creating output file '{}': {}",
+                self.output_filename.as_deref().unwrap_or_default(),
+                e
+            );
+            process::exit(1);
+        })
+    }
+}
+
+fn parse_args(args: Vec<OsString>, print_help: fn()) -> Result<Config, String> {
+    let mut config = Config::default();
+    let mut parser = lexopt::Parser::from_args(args);
+
+    while let Ok(Some(arg)) = parser.next() {
+        match arg {
+            lexopt::Arg::Long("cwr") => {
+                let version_str = get_value(&mut parser, "cwr")?;
+                config.base.set_cwr_version(&version_str)?;
+            }
+            lexopt::Arg::Long("charset") => {
+                config.charset_override = Some(get_value(&mut parser, "charset")?);
+            }
+            lexopt::Arg::Short('o') | lexopt::Arg::Long("output") => {
+                config.output_filename = Some(get_value(&mut parser, "output")?);
+            }
+            lexopt::Arg::Value(val) => {
+                config.base.add_input_file(val.to_string_lossy().to_string())?;
+            }
+            lexopt::Arg::Short('q' | 'v') | lexopt::Arg::Long("quiet" | "verbose") => {}
+            lexopt::Arg::Long("log-format") => check_log_format(&mut parser)?,
+            lexopt::Arg::Short('h') | lexopt::Arg::Long("help") => {
+                print_help();
+                process::exit(0);
+            }
+            _ => {
+                return Err("Unknown argument".to_string());
+            }
+        }
+    }
+
+    config.base.finalize();
+    Ok(config)
+}
+
+fn init(args: Vec<OsString>, print_help: fn()) -> Config {
+    init_logging_and_parse_args(|| {
+        parse_args(args, print_help).inspect_err(|_| {
+            print_help();
+        })
+    })
+}
+
+/// A file to report on, or stdin
+enum Input<'a> {
+    File(&'a str),
+    Stdin,
+}
+
+impl Input<'_> {
+    /// Name used in the report and error messages
+    fn name(&self) -> &str {
+        match self {
+            Input::File(filename) => filename,
+            Input::Stdin => "stdin",
+        }
+    }
+
+    fn records(
+        &self, options: &ParseOptions,
+    ) -> Result<Box<dyn Iterator<Item = Result<ParsedRecord, CwrParseError>>>, CwrParseError> {
+        Ok(match self {
+            Input::File(filename) => Box::new(process_cwr_stream_with_options(filename, options)?),
+            Input::Stdin => Box::new(process_cwr_reader_stream(io::stdin().lock(), options)?),
+        })
+    }
+}
+
+/// Runs `report` on each input file, or on stdin when there are none or the input is `-`; exits non-zero if any
+/// report fails
+fn for_each_input(config: &Config, mut report: impl FnMut(&Input) -> Result<(), String>) {
+    let failed = if config.base.read_stdin {
+        report(&Input::Stdin).is_err()
+    } else {
+        let mut failed = false;
+        for filename in &config.base.input_files {
+            failed |= report(&Input::File(filename)).is_err();
+        }
+        failed
+    };
+    if failed {
+        process::exit(1);
+    }
+}
+
+/// `allegro parse`: lists parse errors and warnings by line, exiting non-zero if any line failed to parse
+pub fn parse(args: Vec<OsString>) {
+    let config = init(args, print_parse_help);
+    let mut output = config.output();
+    for_each_input(&config, |input| {
+        let name = input.name();
+        let records = input.records(&config.parse_options()).map_err(|e| {
+            status_error!("Error reading '{}': {}", name, e);
+            e.to_string()
+        })?;
+        let (mut record_count, mut warning_count, mut error_count) = (0, 0, 0);
+        for result in records {
+            match result {
+                Ok(parsed_record) => {
+                    record_count += 1;
+                    for warning in &parsed_record.warnings {
+                        warning_count += 1;
+                        let _ = writeln!(
+                            output,
+                            "{}:{}: warning: {}: {}",
+                            name,
+                            parsed_record.line_number,
+                            parsed_record.record.record_type(),
+                            warning
+                        );
+                    }
+                }
+                Err(e) => {
+                    error_count += 1;
+                    let _ = writeln!(output, "{}: error: {}", name, e);
+                }
+            }
+        }
+        let _ = writeln!(
+            output,
+            "{}: {} records, {} warnings, {} errors",
+            name,
+            allegro_cwr::format_int_with_commas(record_count),
+            allegro_cwr::format_int_with_commas(warning_count),
+            allegro_cwr::format_int_with_commas(error_count)
+        );
+        let _ = output.flush();
+        if error_count > 0 { Err(format!("{} lines failed to parse", error_count)) } else { Ok(()) }
+    });
+}
+
+/// `allegro summary`: prints a transmission summary for each file
+pub fn summary(args: Vec<OsString>) {
+    let config = init(args, print_summary_help);
+    let mut output = config.output();
+    for_each_input(&config, |input| {
+        let name = input.name();
+        let result = input
+            .records(&config.parse_options())
+            .map_err(Into::into)
+            .and_then(|records| allegro_cwr::process_records_with_handler(name, records, SummaryHandler::new()));
+        match result {
+            Ok(summary) => {
+                let _ = writeln!(output, "{}:\n{}", name, summary.report);
+                let _ = output.flush();
+                Ok(())
+            }
+            Err(e) => {
+                status_error!("Error summarizing '{}': {}", name, e);
+                Err(e.to_string())
+            }
+        }
+    });
+}
+
+/// `allegro diff`: compares the transactions of two files, exiting with 1 if they differ (like `diff`)
+pub fn diff(args: Vec<OsString>) {
+    let config = init(args, print_diff_help);
+    let [old_filename, new_filename] = config.base.input_files.as_slice() else {
+        status_error!("Error: diff takes exactly two files");
+        print_diff_help();
+        process::exit(2);
+    };
+    let read = |filename: &str| {
+        CwrFile::parse_file_with_options(filename, &config.parse_options()).unwrap_or_else(|e| {
+            status_error!("Error reading '{}': {}", filename, e);
+            process::exit(2);
+        })
+    };
+    let diff = diff_cwr_files(&read(old_filename), &read(new_filename));
+
+    let mut output = config.output();
+    if let Err(e) = writeln!(output, "{}", diff).and_then(|_| output.flush()) {
+        status_error!("Error writing diff: {}", e);
+        process::exit(2);
+    }
+    if !diff.is_empty() {
+        process::exit(1);
+    }
+}
+
+fn print_shared_options() {
+    eprintln!("Options:");
+    eprintln!("  -o, --output <file>      Write the report to a file instead of stdout");
+    eprintln!(
+        "      --cwr <version>      CWR version (2.0, 2.1, 2.2). Auto-detected from filename (.Vxx) or file content if not specified"
+    );
+    eprintln!("      --charset <charset>  Override character set when missing in HDR record (e.g., UTF-8, ASCII)");
+    eprintln!("  -q, --quiet              Only print errors");
+    eprintln!("  -v, --verbose            Also print info logging");
+    eprintln!("      --log-format <fmt>   Message format on stderr: text (default) or json");
+    eprintln!("  -h, --help               Show this help message");
+}
+
+fn print_parse_help() {
+    eprintln!("Usage: allegro parse [OPTIONS] [FILES...]");
+    eprintln!();
+    eprintln!("Parses CWR files and lists parse errors and field warnings as <file>:<line>: ...");
+    eprintln!("Exits non-zero if any line could not be parsed. Reads stdin if no files are given or the file is -.");
+    eprintln!();
+    print_shared_options();
+}
+
+fn print_summary_help() {
+    eprintln!("Usage: allegro summary [OPTIONS] [FILES...]");
+    eprintln!();
+    eprintln!("Summarizes each CWR file: sender, version, transactions by type, works, parties, territories and the");
+    eprintln!("most frequent warnings. Reads stdin if no files are given or the file is -.");
+    eprintln!();
+    print_shared_options();
+}
+
+fn print_diff_help() {
+    eprintln!("Usage: allegro diff [OPTIONS] <OLD FILE> <NEW FILE>");
+    eprintln!();
+    eprintln!("Compares transactions matched by type and submitter work or agreement number, ignoring order and");
+    eprintln!("sequence numbers. Exits with 0 if the files match, 1 if they differ and 2 on errors.");
+    eprintln!();
+    print_shared_options();
+}
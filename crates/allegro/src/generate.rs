@@ -0,0 +1,127 @@
+//! The `generate` command, which writes a synthetic CWR file for load testing and demos
+
+use std::ffi::OsString;
+use std::process;
+
+use allegro_cwr::domain_types::{CharacterSet, CwrVersion};
+use allegro_cwr_cli::{check_log_format, create_output, get_value, init_logging_and_parse_args, status, status_error};
+use allegro_cwr_obfuscate::synthetic::{SyntheticOptions, generate_synthetic_cwr};
+
+#[derive(Default)]
+struct Config {
+    options: SyntheticOptions,
+    output_filename: Option<String>,
+}
+
+fn parse_args(args: Vec<OsString>) -> Result<Config, String> {
+    let mut config = Config::default();
+    let mut parser = lexopt::Parser::from_args(args);
+
+    while let Ok(Some(arg)) = parser.next() {
+        match arg {
+            lexopt::Arg::Long("cwr") => {
+                let version_str = get_value(&mut parser, "cwr")?;
+                let mut base = allegro_cwr_cli::BaseConfig::new();
+                base.set_cwr_version(&version_str)?;
+                config.options.version = CwrVersion(base.cwr_version.unwrap_or(config.options.version.0));
+            }
+            lexopt::Arg::Long("charset") => {
+                config.options.character_set = parse_charset(&get_value(&mut parser, "charset")?)?;
+            }
+            lexopt::Arg::Short('o') | lexopt::Arg::Long("output") => {
+                config.output_filename = Some(get_value(&mut parser, "output")?);
+            }
+            lexopt::Arg::Long("works") => {
+                config.options.works = parse_count(&get_value(&mut parser, "works")?, "works")?;
+            }
+            lexopt::Arg::Long("writers") => {
+                config.options.writers_per_work = parse_count(&get_value(&mut parser, "writers")?, "writers")?;
+            }
+            lexopt::Arg::Long("territories") => {
+                config.options.territories = parse_count(&get_value(&mut parser, "territories")?, "territories")?;
+            }
+            lexopt::Arg::Long("seed") => {
+                config.options.seed = get_value(&mut parser, "seed")?;
+            }
+            lexopt::Arg::Short('q' | 'v') | lexopt::Arg::Long("quiet" | "verbose") => {}
+            lexopt::Arg::Long("log-format") => check_log_format(&mut parser)?,
+            lexopt::Arg::Short('h') | lexopt::Arg::Long("help") => {
+                print_help();
+                process::exit(0);
+            }
+            _ => {
+                return Err("Unknown argument".to_string());
+            }
+        }
+    }
+    Ok(config)
+}
+
+fn parse_count(value: &str, name: &str) -> Result<usize, String> {
+    value.parse().map_err(|_| format!("Invalid --{} '{}'. Expected a number", name, value))
+}
+
+fn parse_charset(value: &str) -> Result<CharacterSet, String> {
+    match value {
+        "ASCII" => Ok(CharacterSet::ASCII),
+        "Latin-1" | "ISO-8859-1" => Ok(CharacterSet::Latin1),
+        "UTF-8" => Ok(CharacterSet::UTF8),
+        _ => Err(format!("Invalid character set '{}'. Valid character sets: ASCII, Latin-1, UTF-8", value)),
+    }
+}
+
+/// `allegro generate`: writes a synthetic transmission of new work registrations
+pub fn generate(args: Vec<OsString>) {
+    let config = init_logging_and_parse_args(|| parse_args(args).inspect_err(|_| print_help()));
+    let output = create_output(config.output_filename.as_deref()).unwrap_or_else(|e| {
+        status_error!("Error creating output file '{}': {}", config.output_filename.as_deref().unwrap_or_default(), e);
+        process::exit(1);
+    });
+
+    match generate_synthetic_cwr(output, &config.options) {
+        Ok(totals) => status!("Wrote {} works ({} records)", totals.transactions, totals.records),
+        Err(e) => {
+            status_error!("{}", e);
+            process::exit(1);
+        }
+    }
+}
+
+fn print_help() {
+    eprintln!("Usage: allegro generate [OPTIONS]");
+    eprintln!();
+    eprintln!("Writes a synthetic CWR file of new work registrations (NWR) for load testing and demos. Titles,");
+    eprintln!("names and identifiers are fakes from the obfuscator's generators; writers and publishers recur");
+    eprintln!("across works. The same seed and options produce the same works.");
+    eprintln!();
+    eprintln!("Options:");
+    eprintln!("  -o, --output <file>         Output file path (default: stdout)");
+    eprintln!("      --works <n>             Number of works (default: 100)");
+    eprintln!("      --writers <n>           Writers per work, each with their publisher (default: 2)");
+    eprintln!("      --territories <n>       Collection territories per party; 1 is the world (default: 1)");
+    eprintln!("      --cwr <version>         CWR version to write (2.0, 2.1, 2.2; default: 2.2)");
+    eprintln!("      --charset <charset>     Character set: ASCII (default), Latin-1 or UTF-8");
+    eprintln!("      --seed <secret>         Secret mixed into generated values");
+    eprintln!("  -q, --quiet                 Only print errors");
+    eprintln!("  -v, --verbose               Also print info logging");
+    eprintln!("      --log-format <fmt>      Message format on stderr: text (default) or json");
+    eprintln!("  -h, --help                  Show this help message");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_options() {
+        let args = ["--works", "5", "--writers", "3", "--charset", "UTF-8", "--cwr", "2.1"];
+        let config = parse_args(args.iter().map(OsString::from).collect()).unwrap();
+        assert_eq!(config.options.works, 5);
+        assert_eq!(config.options.writers_per_work, 3);
+        assert_eq!(config.options.territories, 1);
+        assert_eq!(config.options.character_set, CharacterSet::UTF8);
+        assert_eq!(config.options.version, CwrVersion(2.1));
+        assert!(parse_args(vec!["--charset".into(), "EBCDIC".into()]).is_err());
+        assert!(parse_args(vec!["--works".into(), "many".into()]).is_err());
+    }
+}
@@ -0,0 +1,247 @@
+//! `allegro`: one command line tool for CWR files
+//!
+//! Each subcommand runs one of the converters or checkers (`to-json`, `to-sqlite`, `validate`, ...) or one of the
+//! built-in reports (`parse`, `summary`, `diff`). Flags given before the subcommand are passed on to it.
+
+mod commands;
+mod generate;
+mod spreadsheet;
+
+use std::ffi::OsString;
+use std::process;
+
+use allegro_cwr_cli::{BaseConfig, Direction, check_log_format, get_value};
+
+struct Command {
+    name: &'static str,
+    description: &'static str,
+    /// Whether the command takes `--charset`
+    accepts_charset: bool,
+    run: fn(Vec<OsString>),
+}
+
+const COMMANDS: &[Command] = &[
+    Command {
+        name: "parse",
+        description: "Parse CWR files and list their parse errors and warnings by line",
+        accepts_charset: true,
+        run: commands::parse,
+    },
+    Command { name: "to-json", description: "Convert CWR files to JSON", accepts_charset: false, run: to_json },
+    Command {
+        name: "from-json",
+        description: "Convert JSON files back to CWR",
+        accepts_charset: false,
+        run: from_json,
+    },
+    Command {
+        name: "to-sqlite",
+        description: "Import CWR files into a SQLite database",
+        accepts_charset: false,
+        run: to_sqlite,
+    },
+    Command {
+        name: "from-csv",
+        description: "Build CWR work registrations from a CSV or TSV spreadsheet",
+        accepts_charset: false,
+        run: spreadsheet::from_csv,
+    },
+    Command {
+        name: "generate",
+        description: "Write a synthetic CWR file for load testing and demos",
+        accepts_charset: true,
+        run: generate::generate,
+    },
+    Command {
+        name: "from-sqlite",
+        description: "Export a file from a SQLite database as CWR",
+        accepts_charset: false,
+        run: from_sqlite,
+    },
+    Command {
+        name: "validate",
+        description: "Check that CWR files round-trip, optionally with the CISAC validation rules",
+        accepts_charset: true,
+        run: allegro_cwr_validate::cli::run,
+    },
+    Command {
+        name: "obfuscate",
+        description: "Replace names and identifiers in CWR files with consistent fakes",
+        accepts_charset: false,
+        run: allegro_cwr_obfuscate::cli::run,
+    },
+    Command {
+        name: "diff",
+        description: "Compare the transactions of two CWR files",
+        accepts_charset: true,
+        run: commands::diff,
+    },
+    Command {
+        name: "summary",
+        description: "Summarize what CWR files contain (senders, transactions, parties, warnings)",
+        accepts_charset: true,
+        run: commands::summary,
+    },
+];
+
+fn to_json(args: Vec<OsString>) {
+    allegro_cwr_json::cli::run_with_direction(args, Direction::FromCwr);
+}
+
+fn from_json(args: Vec<OsString>) {
+    allegro_cwr_json::cli::run_with_direction(args, Direction::ToCwr);
+}
+
+fn to_sqlite(args: Vec<OsString>) {
+    allegro_cwr_sqlite::cli::run_with_direction(args, Direction::FromCwr);
+}
+
+fn from_sqlite(args: Vec<OsString>) {
+    allegro_cwr_sqlite::cli::run_with_direction(args, Direction::ToCwr);
+}
+
+/// Flags given before the subcommand
+#[derive(Default)]
+struct SharedFlags {
+    cwr_version: Option<String>,
+    charset: Option<String>,
+    output_filename: Option<String>,
+}
+
+impl SharedFlags {
+    /// The shared flags as arguments for `command`, ahead of its own arguments so those take precedence
+    fn command_args(&self, command: &Command, args: Vec<OsString>) -> Result<Vec<OsString>, String> {
+        let mut command_args = Vec::new();
+        if let Some(version) = &self.cwr_version {
+            command_args.extend(["--cwr".into(), version.into()]);
+        }
+        if let Some(charset) = &self.charset {
+            if !command.accepts_charset {
+                return Err(format!("'{}' does not support --charset", command.name));
+            }
+            command_args.extend(["--charset".into(), charset.into()]);
+        }
+        if let Some(output_filename) = &self.output_filename {
+            command_args.extend(["--output".into(), output_filename.into()]);
+        }
+        command_args.extend(args);
+        Ok(command_args)
+    }
+}
+
+fn parse_command_line(args: Vec<OsString>) -> Result<(&'static Command, Vec<OsString>), String> {
+    let mut flags = SharedFlags::default();
+    let mut parser = lexopt::Parser::from_args(args);
+
+    while let Some(arg) = parser.next().map_err(|e| e.to_string())? {
+        match arg {
+            lexopt::Arg::Long("cwr") => {
+                let version_str = get_value(&mut parser, "cwr")?;
+                BaseConfig::new().set_cwr_version(&version_str)?;
+                flags.cwr_version = Some(version_str);
+            }
+            lexopt::Arg::Long("charset") => {
+                flags.charset = Some(get_value(&mut parser, "charset")?);
+            }
+            lexopt::Arg::Short('o') | lexopt::Arg::Long("output") => {
+                flags.output_filename = Some(get_value(&mut parser, "output")?);
+            }
+            lexopt::Arg::Short('q' | 'v') | lexopt::Arg::Long("quiet" | "verbose") => {}
+            lexopt::Arg::Long("log-format") => check_log_format(&mut parser)?,
+            lexopt::Arg::Short('h') | lexopt::Arg::Long("help") => {
+                print_help();
+                process::exit(0);
+            }
+            lexopt::Arg::Value(name) => {
+                let name = name.to_string_lossy();
+                let command = COMMANDS
+                    .iter()
+                    .find(|command| command.name == name)
+                    .ok_or_else(|| format!("Unknown command '{}'", name))?;
+                let args = parser.raw_args().map_err(|e| e.to_string())?.collect();
+                return Ok((command, flags.command_args(command, args)?));
+            }
+            _ => {
+                return Err("Unknown argument".to_string());
+            }
+        }
+    }
+    Err("Missing command".to_string())
+}
+
+fn main() {
+    match parse_command_line(std::env::args_os().skip(1).collect()) {
+        Ok((command, args)) => (command.run)(args),
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            eprintln!();
+            print_help();
+            process::exit(1);
+        }
+    }
+}
+
+fn print_help() {
+    eprintln!("Usage: allegro [SHARED OPTIONS] <COMMAND> [OPTIONS] [FILES...]");
+    eprintln!();
+    eprintln!("Commands:");
+    for command in COMMANDS {
+        eprintln!("  {:<12} {}", command.name, command.description);
+    }
+    eprintln!();
+    eprintln!("Shared options (also accepted after the command):");
+    eprintln!("  -o, --output <file>      Output file path");
+    eprintln!(
+        "      --cwr <version>      CWR version (2.0, 2.1, 2.2). Auto-detected from filename (.Vxx) or file content if not specified"
+    );
+    eprintln!(
+        "      --charset <charset>  Override character set when missing in HDR record (parse, validate, diff, summary); character set to write (generate)"
+    );
+    eprintln!("  -q, --quiet              Only print errors");
+    eprintln!("  -v, --verbose            Also print info logging");
+    eprintln!("      --log-format <fmt>   Message format on stderr: text (default) or json");
+    eprintln!("  -h, --help               Show this help message");
+    eprintln!();
+    eprintln!("Run 'allegro <COMMAND> --help' for the options of a command.");
+    eprintln!();
+    eprintln!("Examples:");
+    eprintln!("  allegro parse input.V21                      # List parse errors and warnings");
+    eprintln!("  allegro --cwr 2.1 to-json input.cwr          # Convert CWR to JSON as version 2.1");
+    eprintln!("  allegro to-sqlite -o works.db *.V22          # Import files into one database");
+    eprintln!("  allegro from-sqlite --file-id 2 works.db     # Export a file from the database");
+    eprintln!("  allegro diff old.V21 new.V21                 # Compare two transmissions");
+    eprintln!("  allegro from-csv --sender-id 123456789 --sender-name \"ACME MUSIC\" -o out.V22 works.csv");
+    eprintln!("  allegro generate --works 100000 -o load.V22  # Synthetic file for load testing");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<OsString> {
+        args.iter().map(OsString::from).collect()
+    }
+
+    #[test]
+    fn test_shared_flags_are_passed_to_the_command() {
+        let (command, command_args) =
+            parse_command_line(args(&["--cwr", "2.1", "-o", "out.json", "to-json", "--grouped", "in.V21"])).unwrap();
+        assert_eq!(command.name, "to-json");
+        assert_eq!(command_args, args(&["--cwr", "2.1", "--output", "out.json", "--grouped", "in.V21"]));
+
+        let (command, command_args) = parse_command_line(args(&["--charset", "UTF-8", "diff", "a", "b"])).unwrap();
+        assert_eq!(command.name, "diff");
+        assert_eq!(command_args, args(&["--charset", "UTF-8", "a", "b"]));
+    }
+
+    #[test]
+    fn test_command_line_errors() {
+        assert_eq!(parse_command_line(args(&["convert"])).err().unwrap(), "Unknown command 'convert'");
+        assert_eq!(parse_command_line(args(&["--cwr", "2.1"])).err().unwrap(), "Missing command");
+        assert!(parse_command_line(args(&["--cwr", "3.0", "parse"])).is_err());
+        assert_eq!(
+            parse_command_line(args(&["--charset", "UTF-8", "to-json"])).err().unwrap(),
+            "'to-json' does not support --charset"
+        );
+    }
+}
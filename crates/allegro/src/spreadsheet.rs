@@ -0,0 +1,209 @@
+//! The `from-csv` command, which builds work registrations from a spreadsheet saved as CSV or TSV
+
+use std::ffi::OsString;
+use std::fs::File;
+use std::io::{self, Read};
+use std::process;
+
+use allegro_cwr::domain_types::{CharacterSet, CwrVersion, SenderType};
+use allegro_cwr::spreadsheet::new_header;
+use allegro_cwr::{ColumnMapping, SpreadsheetError, SpreadsheetOptions, spreadsheet_to_cwr};
+use allegro_cwr_cli::{
+    STDIN_FILENAME, check_log_format, create_output, get_value, init_logging_and_parse_args, status, status_error,
+};
+
+const DEFAULT_VERSION: f32 = 2.2;
+
+#[derive(Default)]
+struct Config {
+    input_filename: Option<String>,
+    output_filename: Option<String>,
+    mapping_filename: Option<String>,
+    delimiter: Option<char>,
+    cwr_version: Option<f32>,
+    sender_type: Option<SenderType>,
+    sender_id: Option<String>,
+    sender_name: Option<String>,
+}
+
+fn parse_args(args: Vec<OsString>) -> Result<Config, String> {
+    let mut config = Config::default();
+    let mut parser = lexopt::Parser::from_args(args);
+
+    while let Ok(Some(arg)) = parser.next() {
+        match arg {
+            lexopt::Arg::Long("cwr") => {
+                let version_str = get_value(&mut parser, "cwr")?;
+                let mut base = allegro_cwr_cli::BaseConfig::new();
+                base.set_cwr_version(&version_str)?;
+                config.cwr_version = base.cwr_version;
+            }
+            lexopt::Arg::Short('o') | lexopt::Arg::Long("output") => {
+                config.output_filename = Some(get_value(&mut parser, "output")?);
+            }
+            lexopt::Arg::Long("mapping") => {
+                config.mapping_filename = Some(get_value(&mut parser, "mapping")?);
+            }
+            lexopt::Arg::Long("delimiter") => {
+                config.delimiter = Some(parse_delimiter(&get_value(&mut parser, "delimiter")?)?);
+            }
+            lexopt::Arg::Long("sender-type") => {
+                config.sender_type = Some(parse_sender_type(&get_value(&mut parser, "sender-type")?)?);
+            }
+            lexopt::Arg::Long("sender-id") => {
+                config.sender_id = Some(get_value(&mut parser, "sender-id")?);
+            }
+            lexopt::Arg::Long("sender-name") => {
+                config.sender_name = Some(get_value(&mut parser, "sender-name")?);
+            }
+            lexopt::Arg::Value(val) if config.input_filename.is_none() => {
+                config.input_filename = Some(val.to_string_lossy().to_string());
+            }
+            lexopt::Arg::Short('q' | 'v') | lexopt::Arg::Long("quiet" | "verbose") => {}
+            lexopt::Arg::Long("log-format") => check_log_format(&mut parser)?,
+            lexopt::Arg::Short('h') | lexopt::Arg::Long("help") => {
+                print_help();
+                process::exit(0);
+            }
+            _ => {
+                return Err("Unknown argument".to_string());
+            }
+        }
+    }
+
+    if config.sender_id.is_none() || config.sender_name.is_none() {
+        return Err("--sender-id and --sender-name are required".to_string());
+    }
+    Ok(config)
+}
+
+fn parse_delimiter(value: &str) -> Result<char, String> {
+    match value {
+        "tab" | "\\t" => Ok('\t'),
+        _ => {
+            let mut chars = value.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Ok(c),
+                _ => Err(format!("Invalid delimiter '{}'. Use a single character or 'tab'", value)),
+            }
+        }
+    }
+}
+
+fn parse_sender_type(value: &str) -> Result<SenderType, String> {
+    match value.to_ascii_uppercase().as_str() {
+        "PB" => Ok(SenderType::Publisher),
+        "SO" => Ok(SenderType::Society),
+        "WR" => Ok(SenderType::Writer),
+        "AA" => Ok(SenderType::AdministrativeAgency),
+        _ => Err(format!("Invalid sender type '{}'. Valid types: PB, SO, WR, AA", value)),
+    }
+}
+
+/// Tab for `.tsv` and `.tab` files, comma otherwise
+fn default_delimiter(input_filename: Option<&str>) -> char {
+    let extension = input_filename.and_then(|name| name.rsplit_once('.')).map(|(_, ext)| ext.to_ascii_lowercase());
+    if matches!(extension.as_deref(), Some("tsv" | "tab")) { '\t' } else { ',' }
+}
+
+fn read_mapping(filename: &str) -> Result<ColumnMapping, String> {
+    let json = std::fs::read_to_string(filename).map_err(|e| format!("Error reading mapping '{}': {}", filename, e))?;
+    serde_json::from_str(&json).map_err(|e| format!("Invalid mapping '{}': {}", filename, e))
+}
+
+/// `allegro from-csv`: writes a CWR file registering the works of a spreadsheet, exiting non-zero if any row or
+/// work is invalid
+pub fn from_csv(args: Vec<OsString>) {
+    let config = init_logging_and_parse_args(|| parse_args(args).inspect_err(|_| print_help()));
+    let columns = match config.mapping_filename.as_deref().map(read_mapping).transpose() {
+        Ok(columns) => columns.unwrap_or_default(),
+        Err(e) => {
+            status_error!("{}", e);
+            process::exit(1);
+        }
+    };
+    let version = CwrVersion(config.cwr_version.unwrap_or(DEFAULT_VERSION));
+    let options = SpreadsheetOptions {
+        columns,
+        delimiter: config.delimiter.unwrap_or_else(|| default_delimiter(config.input_filename.as_deref())),
+        version: version.clone(),
+        character_set: CharacterSet::ASCII,
+    };
+    let hdr = new_header(
+        config.sender_type.clone().unwrap_or_default(),
+        config.sender_id.as_deref().unwrap_or_default(),
+        config.sender_name.as_deref().unwrap_or_default(),
+        version,
+    );
+
+    let input: Box<dyn Read> = match config.input_filename.as_deref().filter(|filename| *filename != STDIN_FILENAME) {
+        Some(filename) => match File::open(filename) {
+            Ok(file) => Box::new(file),
+            Err(e) => {
+                status_error!("Error opening '{}': {}", filename, e);
+                process::exit(1);
+            }
+        },
+        None => Box::new(io::stdin()),
+    };
+    let output = create_output(config.output_filename.as_deref()).unwrap_or_else(|e| {
+        status_error!("Error creating output file '{}': {}", config.output_filename.as_deref().unwrap_or_default(), e);
+        process::exit(1);
+    });
+
+    match spreadsheet_to_cwr(input, output, &hdr, &options) {
+        Ok(totals) => status!("Wrote {} works ({} records)", totals.transactions, totals.records),
+        Err(SpreadsheetError::Invalid(problems)) => {
+            for problem in problems {
+                status_error!("{}", problem);
+            }
+            process::exit(1);
+        }
+        Err(e) => {
+            status_error!("{}", e);
+            process::exit(1);
+        }
+    }
+}
+
+fn print_help() {
+    eprintln!("Usage: allegro from-csv --sender-id <id> --sender-name <name> [OPTIONS] [FILE]");
+    eprintln!();
+    eprintln!("Builds a CWR file of new work registrations (NWR) from a CSV or TSV spreadsheet with one row per");
+    eprintln!("writer or publisher of a work. Rows with the same submitter work number belong to one work.");
+    eprintln!("Nothing is written if any row or work is invalid. Reads stdin if no file is given or the file is -.");
+    eprintln!();
+    eprintln!("Columns (header names can be changed with --mapping):");
+    eprintln!("  work_title, submitter_work_num, party_type (writer/publisher), interested_party_num, name");
+    eprintln!("  Optional: iswc, first_name, role, ipi_name_num, pr_society, mr_society, sr_society, pr_share,");
+    eprintln!("            mr_share, sr_share (percent), territory (TIS code), publisher (IP number for PWR)");
+    eprintln!();
+    eprintln!("Options:");
+    eprintln!("  -o, --output <file>         Output file path (default: stdout)");
+    eprintln!("      --cwr <version>         CWR version to write (2.0, 2.1, 2.2; default: 2.2)");
+    eprintln!("      --mapping <file>        JSON object of column names to spreadsheet headers,");
+    eprintln!("                              e.g. {{\"work_title\": \"Song Title\", \"pr_share\": \"PR %\"}}");
+    eprintln!("      --delimiter <char>      Field separator (default: tab for .tsv/.tab files, comma otherwise)");
+    eprintln!("      --sender-type <type>    HDR sender type: PB, SO, WR or AA (default: PB)");
+    eprintln!("      --sender-id <id>        HDR sender ID (IPI name number or society code)");
+    eprintln!("      --sender-name <name>    HDR sender name");
+    eprintln!("  -q, --quiet                 Only print errors");
+    eprintln!("  -v, --verbose               Also print info logging");
+    eprintln!("      --log-format <fmt>      Message format on stderr: text (default) or json");
+    eprintln!("  -h, --help                  Show this help message");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delimiter_options() {
+        assert_eq!(parse_delimiter("tab"), Ok('\t'));
+        assert_eq!(parse_delimiter(";"), Ok(';'));
+        assert!(parse_delimiter(";;").is_err());
+        assert_eq!(default_delimiter(Some("catalog.TSV")), '\t');
+        assert_eq!(default_delimiter(Some("catalog.csv")), ',');
+        assert_eq!(default_delimiter(None), ',');
+    }
+}
@@ -0,0 +1,84 @@
+//! Parse and write throughput over a generated transmission, or over the file in `ALLEGRO_BENCH_FILE`
+//!
+//! Run with `cargo bench -p allegro_cwr`. To catch ingestion regressions, save a baseline before a change with
+//! `-- --save-baseline main` and compare against it afterwards with `-- --baseline main`; Criterion reports any
+//! significant slowdown. CI does this for every pull request against its base branch and fails when
+//! `bin/check_bench_regression.py` finds a benchmark more than 10% slower.
+
+use allegro_cwr::domain_types::{CharacterSet, CwrVersion};
+use allegro_cwr::parser::ParseOptions;
+use allegro_cwr::records::{GrhRecord, HdrRecord};
+use allegro_cwr::{
+    CwrFile, CwrRegistry, PublisherBuilder, Shares, WorkBuilder, WriterBuilder, process_cwr_bytes_stream,
+};
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use std::hint::black_box;
+use std::time::Duration;
+
+const WORKS: u32 = 20_000;
+
+/// A transmission of `works` NWR transactions with two publishers, a writer and an alternate title each
+fn generated_transmission(works: u32) -> Result<Vec<u8>, String> {
+    let (hdr, _) =
+        HdrRecord::parse("HDRPB285606836WARNER CHAPPELL MUSIC PUBLISHING LTD         01.102022122112541120221221");
+    let (grh, _) = GrhRecord::parse("GRHNWR0000102.100000000000  ");
+    let mut records = vec![CwrRegistry::Hdr(hdr), CwrRegistry::Grh(grh)];
+    for n in 0..works {
+        let work = WorkBuilder::new(&format!("BENCHMARK WORK {}", n), &format!("SW{:010}", n))
+            .iswc(&format!("T{:010}", n))
+            .publisher(
+                PublisherBuilder::new(&format!("P{:08}", n), "ORIGINAL PUBLISHER")
+                    .ownership(Shares::new(5000, 10000, 10000))
+                    .societies("021", "021", "021")
+                    .territory(2136, Shares::new(5000, 10000, 10000)),
+            )
+            .publisher(PublisherBuilder::new("000000002", "SUB PUBLISHER").territory(2136, Shares::default()))
+            .writer(
+                WriterBuilder::new(&format!("W{:08}", n), "SMITH")
+                    .first_name("JANE")
+                    .designation("CA")
+                    .ownership(Shares::new(5000, 0, 0))
+                    .societies("021", "", "")
+                    .territory(2136, Shares::new(5000, 0, 0))
+                    .represented_by(&format!("P{:08}", n)),
+            )
+            .alternate_title("ANOTHER TITLE", allegro_cwr::domain_types::TitleType::AlternativeTitle);
+        records.extend(work.build(n).map_err(|e| e.to_string())?);
+    }
+
+    let mut bytes = Vec::new();
+    allegro_cwr::write_cwr_file(records, &mut bytes, CwrVersion(2.1), CharacterSet::ASCII)
+        .map_err(|e| e.to_string())?;
+    Ok(bytes)
+}
+
+fn input() -> Vec<u8> {
+    match std::env::var("ALLEGRO_BENCH_FILE") {
+        Ok(filename) => std::fs::read(&filename).unwrap_or_else(|e| panic!("Cannot read '{}': {}", filename, e)),
+        Err(_) => generated_transmission(WORKS).expect("generating the benchmark transmission"),
+    }
+}
+
+fn throughput(c: &mut Criterion) {
+    let bytes = input();
+    let options = ParseOptions::default();
+    let file = CwrFile::parse_bytes(&bytes, &options).expect("parsing the benchmark transmission");
+    let mut output = Vec::with_capacity(bytes.len());
+    let records = file.write(&mut output).expect("writing the benchmark transmission").records;
+
+    let mut group = c.benchmark_group("throughput");
+    group.sample_size(20).measurement_time(Duration::from_secs(10)).throughput(Throughput::Elements(records.into()));
+    group.bench_function("parse", |b| {
+        b.iter(|| process_cwr_bytes_stream(black_box(&bytes), &options).unwrap().map(black_box).count())
+    });
+    group.bench_function("write", |b| {
+        b.iter(|| {
+            output.clear();
+            file.write(black_box(&mut output)).unwrap()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, throughput);
+criterion_main!(benches);
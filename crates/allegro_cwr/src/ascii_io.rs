@@ -1,23 +1,68 @@
-use crate::error::CwrParseError;
+use crate::error::{CwrParseError, ParseErrorCode, RecordParseError};
 use crate::parsing::CwrFieldParse;
-use crate::util::get_cwr_version;
+use crate::util::{VersionDetection, detect_cwr_version};
 use std::io::{BufRead, BufReader, Read, Write};
 
 fn should_validate_ascii(character_set: &Option<crate::domain_types::CharacterSet>) -> bool {
     use crate::domain_types::CharacterSet;
     match character_set {
         None | Some(CharacterSet::ASCII) => true,
-        Some(CharacterSet::UTF8) | Some(CharacterSet::Unicode) => false,
+        Some(CharacterSet::Latin1) | Some(CharacterSet::UTF8) | Some(CharacterSet::Unicode) => false,
         Some(CharacterSet::TraditionalBig5) | Some(CharacterSet::SimplifiedGb) => false,
         Some(CharacterSet::Unknown(_)) => true, // Be conservative with unknown sets
     }
 }
 
+/// Reads one line terminated by LF, CRLF or a lone CR (as produced by some mainframe exports) into `buf`,
+/// including the terminator. A final line without a terminator is returned as is; 0 means end of input.
+pub fn read_cwr_line<R: BufRead>(reader: &mut R, buf: &mut Vec<u8>) -> std::io::Result<usize> {
+    let mut total = 0;
+    loop {
+        let available = match reader.fill_buf() {
+            Ok(available) => available,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        if available.is_empty() {
+            return Ok(total);
+        }
+
+        let Some(pos) = available.iter().position(|&b| b == b'\n' || b == b'\r') else {
+            let len = available.len();
+            buf.extend_from_slice(available);
+            reader.consume(len);
+            total += len;
+            continue;
+        };
+        let is_cr = available[pos] == b'\r';
+        buf.extend_from_slice(&available[..=pos]);
+        reader.consume(pos + 1);
+        total += pos + 1;
+
+        // The LF of a CRLF pair may only arrive with the next buffer fill
+        if is_cr && reader.fill_buf()?.first() == Some(&b'\n') {
+            buf.push(b'\n');
+            reader.consume(1);
+            total += 1;
+        }
+        return Ok(total);
+    }
+}
+
+/// Removes the terminator [`read_cwr_line`] leaves on a line
+pub fn trim_line_terminator(line: &mut Vec<u8>) {
+    while line.last().is_some_and(|&b| b == b'\n' || b == b'\r') {
+        line.pop();
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct CwrHeaderInfo {
     pub header_line: String,
     pub version: f32,
     pub character_set: Option<crate::domain_types::CharacterSet>,
+    /// The versions the hint, filename and HDR record name, and how `version` was chosen
+    pub version_detection: VersionDetection,
 }
 
 pub struct AsciiStreamSniffer<R: Read> {
@@ -31,32 +76,23 @@ impl<R: Read> AsciiStreamSniffer<R> {
     }
 
     fn read_and_validate_header_line(&mut self) -> Result<String, CwrParseError> {
-        let mut first_line = String::new();
-        let bytes_read = self.inner.read_line(&mut first_line)?;
+        let mut line_bytes = Vec::new();
+        let bytes_read = read_cwr_line(&mut self.inner, &mut line_bytes)?;
 
         if bytes_read == 0 {
             return Err(CwrParseError::InvalidHeader { found_bytes: vec![] });
         }
-
-        let line_bytes = first_line.as_bytes();
+        trim_line_terminator(&mut line_bytes);
 
         // Check for BOM at start of file
-        let (bom_detected, content_start) = self.detect_bom(line_bytes);
-        if bom_detected.is_some() {
+        let (bom_detected, content_start) = self.detect_bom(&line_bytes);
+        if let Some(bom_type) = &bom_detected {
             // Log the BOM detection but continue parsing
-            eprintln!("BOM detected in CWR file: {} (CWR files should be ASCII only)", bom_detected.as_ref().unwrap());
+            tracing::warn!("BOM detected in CWR file: {} (CWR files should be ASCII only)", bom_type);
         }
 
-        // Get the line content after any BOM
-        let content_bytes = &line_bytes[content_start..];
-        let line_content = if content_start > 0 {
-            std::str::from_utf8(content_bytes)
-                .map_err(|_| CwrParseError::BadFormat("Invalid UTF-8 after BOM".to_string()))?
-        } else {
-            &first_line
-        };
-
         // Validate ASCII in header line (after BOM removal)
+        let content_bytes = &line_bytes[content_start..];
         for (pos, byte) in content_bytes.iter().enumerate() {
             if *byte > 127 {
                 return Err(CwrParseError::NonAsciiInput {
@@ -67,14 +103,15 @@ impl<R: Read> AsciiStreamSniffer<R> {
             }
         }
 
-        let line = line_content.trim_end_matches('\n').trim_end_matches('\r');
+        // All bytes are ASCII at this point
+        let line = String::from_utf8_lossy(content_bytes);
         if !line.starts_with("HDR") {
             return Err(CwrParseError::InvalidHeader {
                 found_bytes: line.chars().take(3).collect::<String>().into_bytes(),
             });
         }
 
-        Ok(line.to_string())
+        Ok(line.into_owned())
     }
 
     fn detect_bom(&self, bytes: &[u8]) -> (Option<String>, usize) {
@@ -96,12 +133,13 @@ impl<R: Read> AsciiStreamSniffer<R> {
     pub fn validate_and_detect_version(
         &mut self, filename: &str, cli_version: Option<f32>,
     ) -> Result<&CwrHeaderInfo, CwrParseError> {
-        if self.cached_header_info.is_some() {
-            return Ok(self.cached_header_info.as_ref().unwrap());
+        if let Some(ref header_info) = self.cached_header_info {
+            return Ok(header_info);
         }
 
         let line = self.read_and_validate_header_line()?;
-        let version = get_cwr_version(filename, &line, cli_version)?;
+        let version_detection = detect_cwr_version(filename, &line, cli_version)?;
+        let version = version_detection.chosen;
 
         // Extract character set from HDR record if version >= 2.1
         let character_set = if version >= 2.1 && line.len() >= 101 {
@@ -120,7 +158,7 @@ impl<R: Read> AsciiStreamSniffer<R> {
             None
         };
 
-        let header_info = CwrHeaderInfo { header_line: line, version, character_set };
+        let header_info = CwrHeaderInfo { header_line: line, version, character_set, version_detection };
 
         self.cached_header_info = Some(header_info);
         Ok(self.cached_header_info.as_ref().unwrap())
@@ -155,7 +193,29 @@ impl<R: Read> AsciiLineReader<R> {
     }
 
     pub fn lines(self) -> impl Iterator<Item = Result<String, CwrParseError>> {
-        AsciiLineIterator { buf_reader: self.buf_reader, line_num: 0, character_set: self.character_set }
+        let character_set = self.character_set.clone().unwrap_or_default();
+        self.byte_lines().enumerate().map(move |(idx, line_result)| {
+            let bytes = line_result?;
+            crate::encoding::decode(&bytes, &character_set).map(|text| text.into_owned()).map_err(|e| {
+                RecordParseError::new(
+                    ParseErrorCode::InvalidEncoding,
+                    format!("Line {}: {} for character set {}", idx + 1, e, character_set.as_str()),
+                )
+                .with_line_number(idx + 1)
+                .with_columns(e.position..e.position + 1)
+                .into()
+            })
+        })
+    }
+
+    /// Yields each line as raw bytes in the file's character set, without the line terminator
+    pub fn byte_lines(self) -> impl Iterator<Item = Result<Vec<u8>, CwrParseError>> {
+        AsciiLineIterator {
+            buf_reader: self.buf_reader,
+            line_num: 0,
+            character_set: self.character_set,
+            line_capacity: 0,
+        }
     }
 }
 
@@ -163,6 +223,8 @@ struct AsciiLineIterator<R: Read> {
     buf_reader: BufReader<R>,
     line_num: usize,
     character_set: Option<crate::domain_types::CharacterSet>,
+    /// Capacity for the next line, from the longest line so far, so most lines are read without reallocating
+    line_capacity: usize,
 }
 
 impl<R: Read> AsciiLineIterator<R> {
@@ -184,20 +246,18 @@ impl<R: Read> AsciiLineIterator<R> {
 }
 
 impl<R: Read> Iterator for AsciiLineIterator<R> {
-    type Item = Result<String, CwrParseError>;
+    type Item = Result<Vec<u8>, CwrParseError>;
 
     fn next(&mut self) -> Option<Self::Item> {
         self.line_num += 1;
-        let mut line = String::new();
+        let mut line_bytes = Vec::with_capacity(self.line_capacity);
 
-        match self.buf_reader.read_line(&mut line) {
+        match read_cwr_line(&mut self.buf_reader, &mut line_bytes) {
             Ok(0) => None, // EOF
             Ok(_) => {
-                let line_bytes = line.as_bytes();
-
                 // Check for BOM on first line only (silently handle it)
                 let content_start = if self.line_num == 1 {
-                    let (_, bom_bytes) = self.detect_bom(line_bytes);
+                    let (_, bom_bytes) = self.detect_bom(&line_bytes);
                     bom_bytes
                 } else {
                     0
@@ -217,18 +277,10 @@ impl<R: Read> Iterator for AsciiLineIterator<R> {
                     }
                 }
 
-                // Get content after BOM (if any) and remove trailing newlines
-                let line_content = if content_start > 0 {
-                    match std::str::from_utf8(content_bytes) {
-                        Ok(s) => s,
-                        Err(_) => return Some(Err(CwrParseError::BadFormat("Invalid UTF-8 after BOM".to_string()))),
-                    }
-                } else {
-                    &line
-                };
-
-                let trimmed = line_content.trim_end_matches('\n').trim_end_matches('\r');
-                Some(Ok(trimmed.to_string()))
+                self.line_capacity = self.line_capacity.max(line_bytes.len());
+                line_bytes.drain(..content_start);
+                trim_line_terminator(&mut line_bytes);
+                Some(Ok(line_bytes))
             }
             Err(e) => Some(Err(CwrParseError::Io(e))),
         }
@@ -259,11 +311,64 @@ impl<W: Write> AsciiWriter<W> {
             }
         }
 
-        // Write as UTF-8 bytes + \r\n (UTF-8 is backwards compatible with ASCII)
-        self.inner.write_all(utf8_line.as_bytes())?;
+        let bytes = match &self.character_set {
+            Some(character_set) => crate::encoding::encode(utf8_line, character_set),
+            None => utf8_line.as_bytes().to_vec(),
+        };
+        self.inner.write_all(&bytes)?;
+        self.inner.write_all(b"\r\n")?;
+        Ok(())
+    }
+
+    /// Writes a line that is already encoded in the writer's character set (e.g. from `to_cwr_record_bytes`)
+    pub fn write_bytes_line(&mut self, line: &[u8]) -> Result<(), CwrParseError> {
+        if should_validate_ascii(&self.character_set)
+            && let Some(position) = line.iter().position(|b| !b.is_ascii())
+        {
+            let text = String::from_utf8_lossy(&line[position..]);
+            let char = text.chars().next().unwrap_or(char::REPLACEMENT_CHARACTER);
+            return Err(CwrParseError::NonAsciiOutput { char, position });
+        }
+
+        self.inner.write_all(line)?;
         self.inner.write_all(b"\r\n")?;
         Ok(())
     }
+
+    /// Writes a record in the writer's character set straight into the underlying writer, truncating and
+    /// logging values longer than their field. Writers that only allow ASCII encode the record as ASCII, so
+    /// characters outside it are written as `?`. Record types that do not exist in `version` are refused.
+    pub fn write_record(
+        &mut self, record: &crate::CwrRegistry, version: &crate::domain_types::CwrVersion,
+    ) -> Result<(), CwrParseError> {
+        let mut truncations = Vec::new();
+        self.write_record_with(record, version, crate::parsing::FieldOverflow::Truncate, &mut truncations)?;
+        for truncation in &truncations {
+            tracing::warn!("{}", truncation);
+        }
+        Ok(())
+    }
+
+    /// Like [`Self::write_record`], handling values longer than their field as `overflow` says. Truncated values
+    /// are appended to `truncations`. Record types that do not exist in `version` are refused.
+    pub fn write_record_with(
+        &mut self, record: &crate::CwrRegistry, version: &crate::domain_types::CwrVersion,
+        overflow: crate::parsing::FieldOverflow, truncations: &mut Vec<crate::parsing::FieldTruncation>,
+    ) -> Result<(), CwrParseError> {
+        let character_set = match &self.character_set {
+            Some(character_set) if !should_validate_ascii(&self.character_set) => character_set,
+            _ => &crate::domain_types::CharacterSet::ASCII,
+        };
+        record.check_version(version.0)?;
+        record.write_cwr_record_with(&mut self.inner, version, character_set, overflow, truncations)?;
+        self.inner.write_all(b"\r\n")?;
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> Result<(), CwrParseError> {
+        self.inner.flush()?;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -367,4 +472,55 @@ mod tests {
         let written = String::from_utf8(output).unwrap();
         assert_eq!(written, "ASCII LINE\r\n");
     }
+
+    #[test]
+    fn test_write_record_streams_the_record_line() {
+        let (alt, _) = crate::records::AltRecord::parse(
+            "ALT0000000200000326BABY CAN T YOU SEE                                          AT  ",
+        );
+        let version = crate::domain_types::CwrVersion(2.2);
+        let record = crate::CwrRegistry::Alt(alt);
+
+        let mut output = Vec::new();
+        let mut writer = AsciiWriter::with_character_set(&mut output, Some(CharacterSet::ASCII));
+        writer.write_record(&record, &version).unwrap();
+
+        let mut expected = record.to_cwr_record_bytes(&version, &CharacterSet::ASCII);
+        expected.extend_from_slice(b"\r\n");
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn test_line_endings_are_handled_uniformly() {
+        for data in
+            ["HDR01\nGRH02\nTRL03\n", "HDR01\r\nGRH02\r\nTRL03", "HDR01\rGRH02\rTRL03\r", "HDR01\rGRH02\r\nTRL03"]
+        {
+            let reader = AsciiLineReader::new(Cursor::new(data.as_bytes()));
+            let lines: Vec<String> = reader.lines().collect::<Result<_, _>>().unwrap();
+            assert_eq!(lines, ["HDR01", "GRH02", "TRL03"], "input {:?}", data);
+        }
+    }
+
+    #[test]
+    fn test_header_sniffer_with_cr_only_line_endings() {
+        let mut sniffer = AsciiStreamSniffer::new(Cursor::new(b"HDR01\rGRH02\r".to_vec()));
+        assert!(sniffer.validate_cwr_header().is_ok());
+        let mut sniffer = AsciiStreamSniffer::new(Cursor::new(b"GRH02\rHDR01\r".to_vec()));
+        assert!(matches!(sniffer.validate_cwr_header(), Err(CwrParseError::InvalidHeader { .. })));
+    }
+
+    #[test]
+    fn test_big5_lines_round_trip_byte_exact() {
+        let data = b"HDR01\r\nNAT\xA7\x40\xA6\xB1\xAE\x61\r\n";
+        let reader = AsciiLineReader::with_character_set(Cursor::new(&data[..]), Some(CharacterSet::TraditionalBig5));
+        let lines: Vec<String> = reader.lines().collect::<Result<_, _>>().unwrap();
+        assert_eq!(lines[1], "NAT作曲家");
+
+        let mut output = Vec::new();
+        let mut writer = AsciiWriter::with_character_set(&mut output, Some(CharacterSet::TraditionalBig5));
+        for line in &lines {
+            writer.write_line(line).unwrap();
+        }
+        assert_eq!(output, data);
+    }
 }
@@ -0,0 +1,239 @@
+//! Async counterpart of [`CwrHandler`](crate::CwrHandler) for handlers that write to async sinks
+//!
+//! The driver is runtime-agnostic: records are parsed synchronously and each handler call is awaited in turn,
+//! so a handler can await its database or HTTP client without spawning a task per record.
+
+use crate::cwr_handler::{Boundary, DriverStep, HandlerDriver, options_for_handler};
+use crate::parsing::CwrWarning;
+use crate::{
+    CwrParseError, ErrorMode, HandlerReport, ParseOptions, ParsedRecord, ProcessingSummary, process_cwr_bytes_stream,
+};
+use std::future::Future;
+use tracing::Instrument;
+
+/// Trait for handling CWR records with async sinks
+pub trait AsyncCwrHandler {
+    type Error: std::error::Error;
+
+    /// Process a single parsed CWR record
+    fn process_record(&mut self, record: ParsedRecord) -> impl Future<Output = Result<(), Self::Error>> + Send;
+
+    /// Handle a parsing error (e.g., log it, count it, etc.)
+    fn handle_parse_error(
+        &mut self, line_number: usize, error: &CwrParseError,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send;
+
+    /// Record type codes this handler processes; see [`CwrHandler::interested_in`](crate::CwrHandler::interested_in)
+    fn interested_in(&self) -> Option<&'static [&'static str]> {
+        None
+    }
+
+    /// Handle warnings from a successfully parsed record (optional override)
+    fn handle_warnings(
+        &mut self, line_number: usize, record_type: &str, warnings: &[CwrWarning<'static>],
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send {
+        let _ = (line_number, record_type, warnings);
+        async { Ok(()) }
+    }
+
+    /// Called before the GRH record that opens a group
+    fn begin_group(&mut self, header: &ParsedRecord) -> impl Future<Output = Result<(), Self::Error>> + Send {
+        let _ = header;
+        async { Ok(()) }
+    }
+
+    /// Called after the GRT record that closes a group, or when a group ends without one
+    fn end_group(&mut self) -> impl Future<Output = Result<(), Self::Error>> + Send {
+        async { Ok(()) }
+    }
+
+    /// Called before the header record (record sequence 0) that opens a transaction
+    fn begin_transaction(&mut self, header: &ParsedRecord) -> impl Future<Output = Result<(), Self::Error>> + Send {
+        let _ = header;
+        async { Ok(()) }
+    }
+
+    /// Called once the last record of a transaction has been processed, before the next transaction or group
+    /// boundary record
+    fn end_transaction(&mut self) -> impl Future<Output = Result<(), Self::Error>> + Send {
+        async { Ok(()) }
+    }
+
+    /// Finalize processing (e.g., commit transaction, flush uploads, etc.)
+    fn finalize(&mut self) -> impl Future<Output = Result<(), Self::Error>> + Send;
+
+    /// Generate a report of the processing results
+    fn get_report(&self) -> String;
+
+    /// Typed counterpart of [`get_report`](AsyncCwrHandler::get_report); see
+    /// [`CwrHandler::report`](crate::CwrHandler::report)
+    fn report(&self) -> HandlerReport {
+        HandlerReport::Text { report: self.get_report() }
+    }
+}
+
+/// Processes a CWR file held in memory with an async handler, awaiting each handler call in order. Read the
+/// file with the runtime's own I/O (e.g. `tokio::fs::read`) so the reading does not block the executor.
+pub async fn process_cwr_bytes_with_async_handler<H: AsyncCwrHandler>(
+    bytes: &[u8], handler: H,
+) -> Result<ProcessingSummary, Box<dyn std::error::Error + Send + Sync>>
+where
+    H::Error: Send + Sync + 'static,
+{
+    process_cwr_bytes_with_async_handler_and_options(bytes, handler, &ParseOptions::default()).await
+}
+
+/// [`process_cwr_bytes_with_async_handler`], reading the records with the given options; the options'
+/// [`error_mode`](ParseOptions::error_mode) decides whether processing stops at the first error
+pub async fn process_cwr_bytes_with_async_handler_and_options<H: AsyncCwrHandler>(
+    bytes: &[u8], handler: H, options: &ParseOptions,
+) -> Result<ProcessingSummary, Box<dyn std::error::Error + Send + Sync>>
+where
+    H::Error: Send + Sync + 'static,
+{
+    let options = options_for_handler(options, handler.interested_in());
+    let records = process_cwr_bytes_stream(bytes, &options)?;
+    process_records_with_async_handler("<bytes>", records, handler, options.error_mode).await
+}
+
+async fn process_records_with_async_handler<H: AsyncCwrHandler>(
+    source: &str, mut records: impl Iterator<Item = Result<ParsedRecord, CwrParseError>>, mut handler: H,
+    mode: ErrorMode,
+) -> Result<ProcessingSummary, Box<dyn std::error::Error + Send + Sync>>
+where
+    H::Error: Send + Sync + 'static,
+{
+    let mut driver = HandlerDriver::new(source, mode, handler.interested_in());
+    let file_span = driver.span().clone();
+
+    async move {
+        while let Some(result) = driver.group_span().in_scope(|| records.next()) {
+            let step = driver.step(result);
+            let group_span = driver.group_span().clone();
+            async {
+                match step {
+                    DriverStep::Skip => {}
+                    DriverStep::Record { record, abort, boundaries, ends_group } => {
+                        if !record.warnings.is_empty() {
+                            handler
+                                .handle_warnings(record.line_number, record.record.record_type(), &record.warnings)
+                                .await?;
+                        }
+                        if let Some(abort) = abort {
+                            return Err(Box::new(abort) as Box<dyn std::error::Error + Send + Sync>);
+                        }
+                        for boundary in boundaries {
+                            match boundary {
+                                Boundary::BeginGroup => handler.begin_group(&record).await?,
+                                Boundary::EndGroup => handler.end_group().await?,
+                                Boundary::BeginTransaction => handler.begin_transaction(&record).await?,
+                                Boundary::EndTransaction => handler.end_transaction().await?,
+                            }
+                        }
+                        handler.process_record(record).await?;
+                        if ends_group {
+                            handler.end_group().await?;
+                        }
+                    }
+                    DriverStep::ParseError { line_number, error, abort } => {
+                        handler.handle_parse_error(line_number, &error).await?;
+                        if let Some(abort) = abort {
+                            return Err(Box::new(abort));
+                        }
+                    }
+                }
+                Ok(())
+            }
+            .instrument(group_span)
+            .await?;
+        }
+
+        for boundary in driver.finish() {
+            match boundary {
+                Boundary::EndTransaction => handler.end_transaction().await?,
+                _ => handler.end_group().await?,
+            }
+        }
+        handler.finalize().await?;
+        Ok(driver.into_summary(handler.get_report(), handler.report()))
+    }
+    .instrument(file_span)
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::pin::pin;
+    use std::task::{Context, Poll, Waker};
+
+    /// Polls a future that never waits on I/O to completion
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = pin!(future);
+        let mut context = Context::from_waker(Waker::noop());
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+                return output;
+            }
+        }
+    }
+
+    #[derive(Default)]
+    struct CollectingHandler {
+        records: Vec<String>,
+        transactions: usize,
+    }
+
+    impl AsyncCwrHandler for CollectingHandler {
+        type Error = std::io::Error;
+
+        async fn process_record(&mut self, record: ParsedRecord) -> Result<(), Self::Error> {
+            self.records.push(record.record.record_type().to_string());
+            Ok(())
+        }
+
+        async fn handle_parse_error(&mut self, _line_number: usize, _error: &CwrParseError) -> Result<(), Self::Error> {
+            Ok(())
+        }
+
+        async fn end_transaction(&mut self) -> Result<(), Self::Error> {
+            self.transactions += 1;
+            Ok(())
+        }
+
+        async fn finalize(&mut self) -> Result<(), Self::Error> {
+            Ok(())
+        }
+
+        fn get_report(&self) -> String {
+            format!("{} records, {} transactions", self.records.len(), self.transactions)
+        }
+    }
+
+    #[test]
+    fn test_async_handler_driver() {
+        let content = "HDRPB285606836WARNER CHAPPELL MUSIC PUBLISHING LTD         01.102022122112541120221221\n\
+                       GRHNWR0000102.100000000000  \n\
+                       NWR0000000000000000Test Song                                               SW0000000001        SER        Y       ORI\n\
+                       GRT000010000000100000003\nTRL000000010000000100000005\n";
+        let summary =
+            block_on(process_cwr_bytes_with_async_handler(content.as_bytes(), CollectingHandler::default())).unwrap();
+        assert_eq!(summary.report, "5 records, 1 transactions");
+        assert_eq!(summary.records_processed, 5);
+
+        let bad_line = content.replacen("NWR0000000000000000", "XYZ0000000000000000", 1);
+        let summary =
+            block_on(process_cwr_bytes_with_async_handler(bad_line.as_bytes(), CollectingHandler::default())).unwrap();
+        assert_eq!(summary.errors, 1);
+        assert_eq!(summary.skipped_lines.to_string(), "3");
+
+        let options = ParseOptions { error_mode: ErrorMode::FailFast, ..Default::default() };
+        let error = block_on(process_cwr_bytes_with_async_handler_and_options(
+            bad_line.as_bytes(),
+            CollectingHandler::default(),
+            &options,
+        ))
+        .unwrap_err();
+        assert_eq!(error.downcast_ref::<crate::ProcessingAborted>().unwrap().line_number, Some(3));
+    }
+}
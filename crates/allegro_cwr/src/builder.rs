@@ -0,0 +1,602 @@
+//! Fluent construction of work registration transactions
+//!
+//! [`WorkBuilder`] collects a work with its publishers, writers, collection territories and alternate titles, then
+//! [`WorkBuilder::build`] validates them and produces the records in transaction order. Record sequence numbers,
+//! publisher chain numbers, SPT/SWT territory sequence numbers and PWR links are filled in automatically.
+
+use crate::cwr_registry::CwrRegistry;
+use crate::domain_types::*;
+use crate::lookups::tis_codes::get_territory_name;
+use crate::records::*;
+use std::fmt;
+
+/// PR, MR and SR shares in hundredths of a percent (10000 = 100%)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Shares {
+    pub pr: u16,
+    pub mr: u16,
+    pub sr: u16,
+}
+
+impl Shares {
+    pub fn new(pr: u16, mr: u16, sr: u16) -> Self {
+        Shares { pr, mr, sr }
+    }
+
+    fn each(&self) -> [(&'static str, u16); 3] {
+        [("PR", self.pr), ("MR", self.mr), ("SR", self.sr)]
+    }
+}
+
+fn share(value: u16) -> Option<OwnershipShare> {
+    Some(OwnershipShare(value))
+}
+
+fn society(code: &str) -> Option<SocietyCode> {
+    let code = code.trim();
+    (!code.is_empty()).then(|| SocietyCode(crate::parsing::intern(code)))
+}
+
+/// Problems found by [`WorkBuilder::build`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransactionBuildError {
+    pub problems: Vec<String>,
+}
+
+impl fmt::Display for TransactionBuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid transaction: {}", self.problems.join("; "))
+    }
+}
+
+impl std::error::Error for TransactionBuildError {}
+
+#[derive(Debug, Clone)]
+struct Territory {
+    tis_code: u16,
+    shares: Shares,
+    included: bool,
+}
+
+impl Territory {
+    fn problems(&self, party: &str, problems: &mut Vec<String>) {
+        if get_territory_name(self.tis_code).is_none() {
+            problems.push(format!("{}: unknown TIS territory code {}", party, self.tis_code));
+        }
+        for (right, value) in self.shares.each() {
+            if OwnershipShare(value).exceeds_full() {
+                problems
+                    .push(format!("{}: {} collection share in territory {} exceeds 100%", party, right, self.tis_code));
+            }
+        }
+    }
+}
+
+/// A publisher controlled by the submitter (SPU), with its collection territories (SPT)
+#[derive(Debug, Clone)]
+pub struct PublisherBuilder {
+    spu: SpuRecord,
+    territories: Vec<Territory>,
+}
+
+impl PublisherBuilder {
+    /// An original publisher (type `E`) with no shares
+    pub fn new(interested_party_num: &str, name: &str) -> Self {
+        PublisherBuilder {
+            spu: SpuRecord {
+                record_type: crate::parsing::intern("SPU"),
+                transaction_sequence_num: Number(0),
+                record_sequence_num: Number(0),
+                publisher_sequence_num: PublisherSequenceNumber(1),
+                interested_party_num: Some(interested_party_num.to_string()),
+                publisher_name: Some(name.to_string()),
+                publisher_unknown_indicator: None,
+                publisher_type: Some(PublisherType::OriginalPublisher),
+                tax_id_num: None,
+                publisher_ipi_name_num: None,
+                submitter_agreement_number: None,
+                pr_affiliation_society_num: None,
+                pr_ownership_share: None,
+                mr_society: None,
+                mr_ownership_share: None,
+                sr_society: None,
+                sr_ownership_share: None,
+                special_agreements_indicator: None,
+                first_recording_refusal_ind: None,
+                filler: None,
+                publisher_ipi_base_number: None,
+                international_standard_agreement_code: None,
+                society_assigned_agreement_number: None,
+                agreement_type: None,
+                usa_license_ind: None,
+            },
+            territories: Vec::new(),
+        }
+    }
+
+    /// Publisher role. Original publishers (`E`) and acquirers (`AQ`) start a new publisher chain; every other
+    /// role joins the chain of the publisher added before it.
+    pub fn publisher_type(mut self, publisher_type: PublisherType) -> Self {
+        self.spu.publisher_type = Some(publisher_type);
+        self
+    }
+
+    pub fn ownership(mut self, shares: Shares) -> Self {
+        self.spu.pr_ownership_share = share(shares.pr);
+        self.spu.mr_ownership_share = share(shares.mr);
+        self.spu.sr_ownership_share = share(shares.sr);
+        self
+    }
+
+    /// PR, MR and SR societies; empty codes are left blank
+    pub fn societies(mut self, pr: &str, mr: &str, sr: &str) -> Self {
+        self.spu.pr_affiliation_society_num = society(pr);
+        self.spu.mr_society = society(mr);
+        self.spu.sr_society = society(sr);
+        self
+    }
+
+    pub fn ipi_name_num(mut self, ipi_name_num: &str) -> Self {
+        self.spu.publisher_ipi_name_num = Some(IpiNameNumber(ipi_name_num.to_string()));
+        self
+    }
+
+    /// Adds a territory the publisher collects the given shares in (SPT)
+    pub fn territory(mut self, tis_code: u16, shares: Shares) -> Self {
+        self.territories.push(Territory { tis_code, shares, included: true });
+        self
+    }
+
+    /// Adds a territory excluded from the publisher's collection (SPT with indicator `E`)
+    pub fn excluded_territory(mut self, tis_code: u16) -> Self {
+        self.territories.push(Territory { tis_code, shares: Shares::default(), included: false });
+        self
+    }
+
+    /// Sets any other SPU field
+    pub fn configure(mut self, configure: impl FnOnce(&mut SpuRecord)) -> Self {
+        configure(&mut self.spu);
+        self
+    }
+
+    fn interested_party_num(&self) -> &str {
+        self.spu.interested_party_num.as_deref().map_or("", str::trim)
+    }
+
+    fn ownership_shares(&self) -> ShareTotals {
+        self.spu.ownership_shares()
+    }
+
+    fn starts_chain(&self) -> bool {
+        matches!(self.spu.publisher_type, None | Some(PublisherType::OriginalPublisher | PublisherType::Acquirer))
+    }
+}
+
+/// A writer controlled by the submitter (SWR), with its collection territories (SWT) and the publishers
+/// representing it (PWR)
+#[derive(Debug, Clone)]
+pub struct WriterBuilder {
+    swr: SwrRecord,
+    territories: Vec<Territory>,
+    publishers: Vec<String>,
+}
+
+impl WriterBuilder {
+    pub fn new(interested_party_num: &str, last_name: &str) -> Self {
+        WriterBuilder {
+            swr: SwrRecord {
+                record_type: crate::parsing::intern("SWR"),
+                transaction_sequence_num: Number(0),
+                record_sequence_num: Number(0),
+                interested_party_num: Some(interested_party_num.to_string()),
+                writer_last_name: Some(last_name.to_string()),
+                writer_first_name: None,
+                writer_unknown_indicator: None,
+                writer_designation_code: None,
+                tax_id_num: None,
+                writer_ipi_name_num: None,
+                pr_affiliation_society_num: None,
+                pr_ownership_share: None,
+                mr_society: None,
+                mr_ownership_share: None,
+                sr_society: None,
+                sr_ownership_share: None,
+                reversionary_indicator: None,
+                first_recording_refusal_ind: None,
+                work_for_hire_indicator: None,
+                filler: None,
+                writer_ipi_base_number: None,
+                personal_number: None,
+                usa_license_ind: None,
+            },
+            territories: Vec::new(),
+            publishers: Vec::new(),
+        }
+    }
+
+    pub fn first_name(mut self, first_name: &str) -> Self {
+        self.swr.writer_first_name = Some(first_name.to_string());
+        self
+    }
+
+    /// Writer designation code (e.g. `CA` composer/author, `C` composer, `A` author)
+    pub fn designation(mut self, designation_code: &str) -> Self {
+        self.swr.writer_designation_code = Some(WriterDesignation(designation_code.to_string()));
+        self
+    }
+
+    pub fn ownership(mut self, shares: Shares) -> Self {
+        self.swr.pr_ownership_share = share(shares.pr);
+        self.swr.mr_ownership_share = share(shares.mr);
+        self.swr.sr_ownership_share = share(shares.sr);
+        self
+    }
+
+    /// PR, MR and SR societies; empty codes are left blank
+    pub fn societies(mut self, pr: &str, mr: &str, sr: &str) -> Self {
+        self.swr.pr_affiliation_society_num = society(pr);
+        self.swr.mr_society = society(mr);
+        self.swr.sr_society = society(sr);
+        self
+    }
+
+    pub fn ipi_name_num(mut self, ipi_name_num: &str) -> Self {
+        self.swr.writer_ipi_name_num = Some(IpiNameNumber(ipi_name_num.to_string()));
+        self
+    }
+
+    /// Adds a territory the writer collects the given shares in (SWT)
+    pub fn territory(mut self, tis_code: u16, shares: Shares) -> Self {
+        self.territories.push(Territory { tis_code, shares, included: true });
+        self
+    }
+
+    /// Adds a territory excluded from the writer's collection (SWT with indicator `E`)
+    pub fn excluded_territory(mut self, tis_code: u16) -> Self {
+        self.territories.push(Territory { tis_code, shares: Shares::default(), included: false });
+        self
+    }
+
+    /// Links the writer to a publisher of the work (PWR), by the publisher's interested party number
+    pub fn represented_by(mut self, publisher_interested_party_num: &str) -> Self {
+        self.publishers.push(publisher_interested_party_num.trim().to_string());
+        self
+    }
+
+    /// Sets any other SWR field
+    pub fn configure(mut self, configure: impl FnOnce(&mut SwrRecord)) -> Self {
+        configure(&mut self.swr);
+        self
+    }
+
+    fn interested_party_num(&self) -> &str {
+        self.swr.interested_party_num.as_deref().map_or("", str::trim)
+    }
+
+    fn ownership_shares(&self) -> ShareTotals {
+        self.swr.ownership_shares()
+    }
+}
+
+/// Builds a work registration transaction (NWR or REV) record by record
+#[derive(Debug, Clone)]
+pub struct WorkBuilder {
+    nwr: NwrRecord,
+    publishers: Vec<PublisherBuilder>,
+    writers: Vec<WriterBuilder>,
+    alternate_titles: Vec<AltRecord>,
+}
+
+impl WorkBuilder {
+    /// A new registration (NWR) of an unclassified (`UNC`) original (`ORI`) work
+    pub fn new(work_title: &str, submitter_work_num: &str) -> Self {
+        WorkBuilder {
+            nwr: NwrRecord {
+                record_type: crate::parsing::intern("NWR"),
+                transaction_sequence_num: Number(0),
+                record_sequence_num: Number(0),
+                work_title: work_title.to_string(),
+                language_code: None,
+                submitter_work_num: submitter_work_num.to_string(),
+                iswc: None,
+                copyright_date: None,
+                copyright_number: None,
+                musical_work_distribution_category: MusicalWorkDistributionCategory("UNC".to_string()),
+                duration: None,
+                recorded_indicator: Flag::Unknown,
+                text_music_relationship: None,
+                composite_type: None,
+                version_type: VersionType("ORI".to_string()),
+                excerpt_type: None,
+                music_arrangement: None,
+                lyric_adaptation: None,
+                contact_name: None,
+                contact_id: None,
+                cwr_work_type: None,
+                grand_rights_ind: None,
+                composite_component_count: None,
+                date_of_publication_of_printed_edition: None,
+                exceptional_clause: None,
+                opus_number: None,
+                catalogue_number: None,
+                priority_flag: None,
+            },
+            publishers: Vec::new(),
+            writers: Vec::new(),
+            alternate_titles: Vec::new(),
+        }
+    }
+
+    /// Sends the work as a revision (REV) instead of a new registration
+    pub fn revision(mut self) -> Self {
+        self.nwr.record_type = crate::parsing::intern("REV");
+        self
+    }
+
+    pub fn iswc(mut self, iswc: &str) -> Self {
+        self.nwr.iswc = Some(iswc.to_string());
+        self
+    }
+
+    /// Sets any other NWR field
+    pub fn configure(mut self, configure: impl FnOnce(&mut NwrRecord)) -> Self {
+        configure(&mut self.nwr);
+        self
+    }
+
+    pub fn publisher(mut self, publisher: PublisherBuilder) -> Self {
+        self.publishers.push(publisher);
+        self
+    }
+
+    pub fn writer(mut self, writer: WriterBuilder) -> Self {
+        self.writers.push(writer);
+        self
+    }
+
+    pub fn alternate_title(mut self, title: &str, title_type: TitleType) -> Self {
+        self.alternate_titles.push(AltRecord {
+            record_type: crate::parsing::intern("ALT"),
+            transaction_sequence_num: Number(0),
+            record_sequence_num: Number(0),
+            alternate_title: title.to_string(),
+            title_type,
+            language_code: None,
+        });
+        self
+    }
+
+    /// Validates the work and returns its records in transaction order, numbered as transaction
+    /// `transaction_sequence_num`
+    pub fn build(self, transaction_sequence_num: u32) -> Result<Vec<CwrRegistry>, TransactionBuildError> {
+        let mut problems = self.problems();
+        let mut records = Vec::new();
+
+        let mut nwr = self.nwr;
+        collect_critical(&nwr.record_type.clone(), nwr.validate(), &mut problems);
+        records.push(CwrRegistry::Nwr(nwr));
+
+        let mut chains: Vec<(String, u8)> = Vec::new();
+        let mut chain = 0u8;
+        for publisher in self.publishers {
+            if chain == 0 || publisher.starts_chain() {
+                chain = chain.saturating_add(1);
+            }
+            let interested_party_num = publisher.interested_party_num().to_string();
+            chains.push((interested_party_num.clone(), chain));
+
+            let mut spu = publisher.spu;
+            spu.publisher_sequence_num = PublisherSequenceNumber(chain);
+            collect_critical("SPU", spu.validate(), &mut problems);
+            records.push(CwrRegistry::Spu(spu));
+
+            for (index, territory) in publisher.territories.iter().enumerate() {
+                let mut spt = SptRecord {
+                    record_type: crate::parsing::intern("SPT"),
+                    transaction_sequence_num: Number(0),
+                    record_sequence_num: Number(0),
+                    interested_party_num: interested_party_num.clone(),
+                    constant: String::new(),
+                    pr_collection_share: share(territory.shares.pr),
+                    mr_collection_share: share(territory.shares.mr),
+                    sr_collection_share: share(territory.shares.sr),
+                    inclusion_exclusion_indicator: inclusion(territory.included),
+                    tis_numeric_code: TisNumericCode(territory.tis_code),
+                    shares_change: None,
+                    sequence_num: Some(Number(index as u32 + 1)),
+                };
+                collect_critical("SPT", spt.validate(), &mut problems);
+                records.push(CwrRegistry::Spt(spt));
+            }
+        }
+
+        for writer in self.writers {
+            let interested_party_num = writer.interested_party_num().to_string();
+            let mut swr = writer.swr;
+            collect_critical("SWR", swr.validate(), &mut problems);
+            records.push(CwrRegistry::Swr(swr));
+
+            for (index, territory) in writer.territories.iter().enumerate() {
+                let mut swt = SwtRecord {
+                    record_type: crate::parsing::intern("SWT"),
+                    transaction_sequence_num: Number(0),
+                    record_sequence_num: Number(0),
+                    interested_party_num: Some(interested_party_num.clone()),
+                    pr_collection_share: share(territory.shares.pr),
+                    mr_collection_share: share(territory.shares.mr),
+                    sr_collection_share: share(territory.shares.sr),
+                    inclusion_exclusion_indicator: inclusion(territory.included),
+                    tis_numeric_code: TisNumericCode(territory.tis_code),
+                    shares_change: None,
+                    sequence_num: Some(Number(index as u32 + 1)),
+                };
+                collect_critical("SWT", swt.validate(), &mut problems);
+                records.push(CwrRegistry::Swt(swt));
+            }
+
+            for publisher_ip in &writer.publishers {
+                let Some((_, chain)) = chains.iter().find(|(ip, _)| ip == publisher_ip) else {
+                    continue;
+                };
+                let publisher_name = records.iter().find_map(|record| match record {
+                    CwrRegistry::Spu(spu)
+                        if spu.interested_party_num.as_deref().map(str::trim) == Some(publisher_ip) =>
+                    {
+                        spu.publisher_name.clone()
+                    }
+                    _ => None,
+                });
+                let mut pwr = PwrRecord {
+                    record_type: crate::parsing::intern("PWR"),
+                    transaction_sequence_num: Number(0),
+                    record_sequence_num: Number(0),
+                    publisher_ip_num: Some(publisher_ip.clone()),
+                    publisher_name,
+                    submitter_agreement_number: None,
+                    society_assigned_agreement_number: None,
+                    writer_ip_num: Some(interested_party_num.clone()),
+                    publisher_sequence_num: Some(PublisherSequenceNumber(*chain)),
+                };
+                collect_critical("PWR", pwr.validate(), &mut problems);
+                records.push(CwrRegistry::Pwr(pwr));
+            }
+        }
+
+        for mut alt in self.alternate_titles {
+            collect_critical("ALT", alt.validate(), &mut problems);
+            records.push(CwrRegistry::Alt(alt));
+        }
+
+        if !problems.is_empty() {
+            return Err(TransactionBuildError { problems });
+        }
+        for (record_sequence_num, record) in records.iter_mut().enumerate() {
+            record.set_sequence_numbers(transaction_sequence_num, record_sequence_num as u32);
+        }
+        Ok(records)
+    }
+
+    /// Checks that need the whole transaction: identification, share totals and PWR links
+    fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.nwr.work_title.trim().is_empty() {
+            problems.push("Work title is required".to_string());
+        }
+        if self.nwr.submitter_work_num.trim().is_empty() {
+            problems.push("Submitter work number is required".to_string());
+        }
+        if self.writers.is_empty() {
+            problems.push("At least one writer is required".to_string());
+        }
+
+        let mut totals = ShareTotals::default();
+        let parties = self
+            .publishers
+            .iter()
+            .map(|p| ("Publisher", p.interested_party_num(), p.ownership_shares(), &p.territories))
+            .chain(
+                self.writers.iter().map(|w| ("Writer", w.interested_party_num(), w.ownership_shares(), &w.territories)),
+            );
+        for (role, interested_party_num, shares, territories) in parties {
+            let party = format!("{} '{}'", role, interested_party_num);
+            if interested_party_num.is_empty() {
+                problems.push(format!("{} interested party number is required", role));
+            }
+            totals = totals + shares;
+            for territory in territories {
+                territory.problems(&party, &mut problems);
+            }
+        }
+        for (right, total) in totals.exceeding_full() {
+            problems.push(format!("{} ownership shares total {}, more than 100%", right, total));
+        }
+
+        for writer in &self.writers {
+            for publisher_ip in &writer.publishers {
+                if !self.publishers.iter().any(|p| p.interested_party_num() == publisher_ip) {
+                    problems.push(format!(
+                        "Writer '{}' is linked to publisher '{}', which is not on the work",
+                        writer.interested_party_num(),
+                        publisher_ip
+                    ));
+                }
+            }
+        }
+        problems
+    }
+}
+
+fn inclusion(included: bool) -> InclusionExclusionIndicator {
+    if included { InclusionExclusionIndicator::Included } else { InclusionExclusionIndicator::Excluded }
+}
+
+fn collect_critical(record_type: &str, warnings: Vec<CwrWarning<'static>>, problems: &mut Vec<String>) {
+    problems.extend(
+        warnings
+            .into_iter()
+            .filter(CwrWarning::is_critical)
+            .map(|w| format!("{}: {}: {}", record_type, w.field_title, w.description)),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn work() -> WorkBuilder {
+        WorkBuilder::new("TEST WORK TITLE", "SW0000000001")
+            .publisher(
+                PublisherBuilder::new("000000001", "ACME MUSIC")
+                    .ownership(Shares::new(5000, 10000, 10000))
+                    .societies("021", "021", "021")
+                    .territory(2136, Shares::new(5000, 10000, 10000)),
+            )
+            .publisher(
+                PublisherBuilder::new("000000002", "ACME ADMIN")
+                    .publisher_type(PublisherType::Administrator)
+                    .territory(2136, Shares::default()),
+            )
+            .writer(
+                WriterBuilder::new("000000003", "SMITH")
+                    .first_name("JANE")
+                    .designation("CA")
+                    .ownership(Shares::new(5000, 0, 0))
+                    .societies("021", "", "")
+                    .territory(2136, Shares::new(5000, 0, 0))
+                    .represented_by("000000001"),
+            )
+            .alternate_title("ANOTHER TITLE", TitleType::AlternativeTitle)
+    }
+
+    #[test]
+    fn test_build_assigns_sequences_and_links() {
+        let records = work().build(4).unwrap();
+
+        let types: Vec<&str> = records.iter().map(CwrRegistry::record_type).collect();
+        assert_eq!(types, ["NWR", "SPU", "SPT", "SPU", "SPT", "SWR", "SWT", "PWR", "ALT"]);
+        let sequences: Vec<(u32, u32)> = records.iter().filter_map(CwrRegistry::sequence_numbers).collect();
+        assert_eq!(sequences, (0..9).map(|n| (4, n)).collect::<Vec<_>>());
+
+        let CwrRegistry::Spu(administrator) = &records[3] else { panic!("expected SPU") };
+        assert_eq!(administrator.publisher_sequence_num, PublisherSequenceNumber(1));
+        let CwrRegistry::Pwr(pwr) = &records[7] else { panic!("expected PWR") };
+        assert_eq!(pwr.publisher_name.as_deref(), Some("ACME MUSIC"));
+        assert_eq!(pwr.writer_ip_num.as_deref(), Some("000000003"));
+        assert_eq!(pwr.publisher_sequence_num, Some(PublisherSequenceNumber(1)));
+        let CwrRegistry::Swt(swt) = &records[6] else { panic!("expected SWT") };
+        assert_eq!(swt.sequence_num, Some(Number(1)));
+    }
+
+    #[test]
+    fn test_build_reports_problems() {
+        let error = work()
+            .writer(WriterBuilder::new("000000004", "JONES").ownership(Shares::new(1000, 0, 0)).represented_by("X"))
+            .publisher(PublisherBuilder::new("000000005", "OTHER").territory(9999, Shares::default()))
+            .build(0)
+            .unwrap_err();
+
+        assert!(error.problems.iter().any(|p| p.starts_with("PR ownership shares total 110.00%")), "{}", error);
+        assert!(error.problems.iter().any(|p| p.contains("unknown TIS territory code 9999")), "{}", error);
+        assert!(error.problems.iter().any(|p| p.contains("linked to publisher 'X'")), "{}", error);
+    }
+}
@@ -0,0 +1,142 @@
+//! Heuristic character set detection for CWR files
+//!
+//! Many files in the wild leave the HDR character set field blank, or declare ASCII while carrying accented or
+//! CJK names. Rather than rejecting those lines, the parser samples the raw bytes and, when the content clearly
+//! does not match the declaration, continues with the detected character set and reports a warning.
+
+use crate::domain_types::CharacterSet;
+use std::io::Read;
+
+/// Number of bytes sampled from the start of a file for detection
+pub const DETECTION_SAMPLE_SIZE: usize = 64 * 1024;
+
+const UTF8_BOM: &[u8] = &[0xEF, 0xBB, 0xBF];
+
+/// Guesses the character set of a byte sample.
+///
+/// Returns `None` when the sample is plain ASCII (any declared character set fits). Otherwise checks, in order:
+/// a UTF-8 BOM, valid UTF-8 multibyte sequences, Big5/GB double-byte patterns, and finally falls back to Latin-1.
+/// A multibyte sequence cut off at the end of the sample is not held against UTF-8.
+pub fn detect_character_set(sample: &[u8]) -> Option<CharacterSet> {
+    if sample.starts_with(UTF8_BOM) {
+        return Some(CharacterSet::UTF8);
+    }
+    if sample.is_ascii() {
+        return None;
+    }
+    match std::str::from_utf8(sample) {
+        Ok(_) => return Some(CharacterSet::UTF8),
+        Err(e) if e.error_len().is_none() => return Some(CharacterSet::UTF8),
+        Err(_) => {}
+    }
+    Some(detect_double_byte(sample).unwrap_or(CharacterSet::Latin1))
+}
+
+/// Reads up to [`DETECTION_SAMPLE_SIZE`] bytes from `reader` and runs [`detect_character_set`] on them
+pub fn detect_character_set_from_reader<R: Read>(reader: R) -> std::io::Result<Option<CharacterSet>> {
+    let mut sample = Vec::with_capacity(DETECTION_SAMPLE_SIZE);
+    reader.take(DETECTION_SAMPLE_SIZE as u64).read_to_end(&mut sample)?;
+    Ok(detect_character_set(&sample))
+}
+
+/// Returns true when content detected as `detected` can be read under the `declared` character set as is
+pub fn is_compatible(declared: &Option<CharacterSet>, detected: &CharacterSet) -> bool {
+    match (declared, detected) {
+        (Some(CharacterSet::UTF8 | CharacterSet::Unicode), CharacterSet::UTF8) => true,
+        (Some(CharacterSet::Latin1), CharacterSet::Latin1) => true,
+        // Big5 and GB share their byte ranges, so a declared double-byte set is trusted over the guess
+        (
+            Some(CharacterSet::TraditionalBig5 | CharacterSet::SimplifiedGb),
+            CharacterSet::TraditionalBig5 | CharacterSet::SimplifiedGb,
+        ) => true,
+        _ => false,
+    }
+}
+
+/// Scans the sample as a double-byte encoding. Every byte above 0x80 must start a well-formed pair, and most
+/// pairs must look like CJK text: a high trail byte, or part of a run of consecutive pairs. Isolated high bytes
+/// followed by ASCII letters are far more likely accented Latin-1 characters.
+fn detect_double_byte(sample: &[u8]) -> Option<CharacterSet> {
+    let mut pairs = 0usize;
+    let mut cjk_like_pairs = 0usize;
+    let mut previous_pair_end = None;
+    let mut big5_evidence = false;
+    let mut gbk_evidence = false;
+
+    let mut i = 0;
+    while i < sample.len() {
+        let lead = sample[i];
+        if lead < 0x80 {
+            i += 1;
+            continue;
+        }
+        if !(0x81..=0xFE).contains(&lead) {
+            return None;
+        }
+        let Some(&trail) = sample.get(i + 1) else {
+            // Pair cut off by the end of the sample
+            break;
+        };
+        match trail {
+            0x40..=0x7E => big5_evidence = true,
+            0xA1..=0xFE => {}
+            0x80..=0xA0 => gbk_evidence = true,
+            _ => return None,
+        }
+        if lead <= 0xA0 {
+            // Lead bytes below 0xA1 only occur in the GBK extension of GB
+            gbk_evidence = true;
+        }
+
+        let in_run = previous_pair_end == Some(i) || sample.get(i + 2).is_some_and(|&next| next >= 0x81);
+        if trail >= 0xA1 || in_run {
+            cjk_like_pairs += 1;
+        }
+        pairs += 1;
+        previous_pair_end = Some(i + 2);
+        i += 2;
+    }
+
+    if pairs == 0 || cjk_like_pairs * 2 < pairs {
+        return None;
+    }
+    if big5_evidence && !gbk_evidence { Some(CharacterSet::TraditionalBig5) } else { Some(CharacterSet::SimplifiedGb) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_ascii() {
+        assert_eq!(detect_character_set(b"HDRPB123456789PUBLISHER"), None);
+    }
+
+    #[test]
+    fn test_detect_utf8() {
+        assert_eq!(detect_character_set(b"\xEF\xBB\xBFHDRPB"), Some(CharacterSet::UTF8));
+        assert_eq!(detect_character_set("SWRMüller café".as_bytes()), Some(CharacterSet::UTF8));
+        // Multibyte sequence truncated by the sample boundary
+        assert_eq!(detect_character_set(&"NAT作曲".as_bytes()[..7]), Some(CharacterSet::UTF8));
+    }
+
+    #[test]
+    fn test_detect_latin1() {
+        assert_eq!(detect_character_set(b"SWRM\xFCller caf\xE9 "), Some(CharacterSet::Latin1));
+    }
+
+    #[test]
+    fn test_detect_big5_and_gb() {
+        // 作曲家 in Big5 and GB2312
+        assert_eq!(detect_character_set(b"NAT\xA7\x40\xA6\xB1\xAE\x61  "), Some(CharacterSet::TraditionalBig5));
+        assert_eq!(detect_character_set(b"NAT\xD7\xF7\xC7\xFA\xBC\xD2  "), Some(CharacterSet::SimplifiedGb));
+    }
+
+    #[test]
+    fn test_is_compatible() {
+        assert!(is_compatible(&Some(CharacterSet::Unicode), &CharacterSet::UTF8));
+        assert!(is_compatible(&Some(CharacterSet::TraditionalBig5), &CharacterSet::SimplifiedGb));
+        assert!(!is_compatible(&None, &CharacterSet::UTF8));
+        assert!(!is_compatible(&Some(CharacterSet::ASCII), &CharacterSet::Latin1));
+    }
+}
@@ -0,0 +1,96 @@
+//! Gzip-compressed input
+//!
+//! Archived CWR deliveries are often gzipped. Compression is detected from the first bytes of the content rather
+//! than the file name, so `.gz` files, renamed files and gzipped stdin are all read the same way. Concatenated
+//! gzip members (as written by `cat a.gz b.gz`) are read as one stream.
+
+use std::borrow::Cow;
+#[cfg(feature = "fs")]
+use std::fs::File;
+use std::io::{self, Read};
+
+/// The two bytes every gzip member starts with
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Whether `bytes` start like gzip-compressed data
+pub fn is_gzip(bytes: &[u8]) -> bool {
+    bytes.starts_with(&GZIP_MAGIC)
+}
+
+/// Opens a file for reading, decompressing it on the fly when its content is gzipped
+#[cfg(feature = "fs")]
+pub fn open_input(filename: &str) -> io::Result<Box<dyn Read + Send>> {
+    Ok(Box::new(decompressing(File::open(filename)?)?))
+}
+
+/// Returns the content of `bytes`, decompressed when it is gzipped and borrowed unchanged otherwise
+pub fn decompress(bytes: &[u8]) -> io::Result<Cow<'_, [u8]>> {
+    if !is_gzip(bytes) {
+        return Ok(Cow::Borrowed(bytes));
+    }
+    let mut content = Vec::new();
+    decompressing(bytes)?.read_to_end(&mut content)?;
+    Ok(Cow::Owned(content))
+}
+
+/// The content of a reader that may be gzipped, with the bytes read to sniff the compression put back in front
+pub(crate) enum Decompressing<R: Read> {
+    Plain(io::Chain<io::Cursor<Vec<u8>>, R>),
+    #[cfg(feature = "gzip")]
+    Gzip(flate2::read::MultiGzDecoder<io::Chain<io::Cursor<Vec<u8>>, R>>),
+}
+
+impl<R: Read> Read for Decompressing<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Decompressing::Plain(reader) => reader.read(buf),
+            #[cfg(feature = "gzip")]
+            Decompressing::Gzip(reader) => reader.read(buf),
+        }
+    }
+}
+
+/// Wraps `reader` so its content is decompressed on the fly when it is gzipped; only the magic bytes are read
+pub(crate) fn decompressing<R: Read>(mut reader: R) -> io::Result<Decompressing<R>> {
+    let mut magic = [0u8; 2];
+    let mut len = 0;
+    while len < magic.len() {
+        match reader.read(&mut magic[len..])? {
+            0 => break,
+            read => len += read,
+        }
+    }
+    let prefix = io::Cursor::new(magic[..len].to_vec()).chain(reader);
+    if !is_gzip(&magic[..len]) {
+        return Ok(Decompressing::Plain(prefix));
+    }
+    #[cfg(feature = "gzip")]
+    return Ok(Decompressing::Gzip(flate2::read::MultiGzDecoder::new(prefix)));
+    #[cfg(not(feature = "gzip"))]
+    Err(io::Error::new(io::ErrorKind::Unsupported, "Input is gzip-compressed; enable the gzip feature to read it"))
+}
+
+#[cfg(all(test, feature = "gzip"))]
+mod tests {
+    use super::*;
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+    use std::io::Write;
+
+    fn gzip(content: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(content).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn test_decompress() {
+        let plain = b"HDRPB000000001ACME MUSIC";
+        assert!(matches!(decompress(plain).unwrap(), Cow::Borrowed(_)));
+
+        let mut members = gzip(&plain[..10]);
+        members.extend(gzip(&plain[10..]));
+        assert!(is_gzip(&members));
+        assert_eq!(decompress(&members).unwrap().as_ref(), plain);
+    }
+}
@@ -0,0 +1,338 @@
+//! Conversion of a parsed file to another CWR version
+//!
+//! Every record is written in the target version's layout and read back, so fields the target version lacks are
+//! dropped and fields it lays out differently are moved or cut. Fields new in the target version are defaulted
+//! where the file holds the answer (HDR version, GRH version number, PWR publisher sequence number). Every change
+//! that loses or invents data is reported.
+
+use crate::cwr_registry::{CwrRegistry, parse_line_by_record_type};
+use crate::document::{CwrFile, CwrTransaction};
+use crate::domain_types::{CharacterSet, CwrVersion, CwrVersionNumber};
+use crate::error::CwrParseError;
+use crate::parsing::{CwrLine, FieldOverflow};
+use std::fmt;
+
+/// What happened to a record or field during conversion
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConversionChangeKind {
+    /// The record type does not exist in the target version
+    DroppedRecord,
+    /// The field does not exist in the target version and had a value
+    DroppedField,
+    /// The field is narrower in the target version
+    TruncatedField,
+    /// The field is new in the target version and was filled in
+    DefaultedField,
+}
+
+impl ConversionChangeKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConversionChangeKind::DroppedRecord => "dropped record",
+            ConversionChangeKind::DroppedField => "dropped field",
+            ConversionChangeKind::TruncatedField => "truncated field",
+            ConversionChangeKind::DefaultedField => "defaulted field",
+        }
+    }
+}
+
+/// One change made by [`convert_version`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConversionChange {
+    pub kind: ConversionChangeKind,
+    pub record_type: String,
+    /// Submitter work number of the transaction, when the record belongs to a work
+    pub submitter_work_num: Option<String>,
+    pub field_name: Option<&'static str>,
+    /// The value before conversion
+    pub original: Option<String>,
+    /// The value after conversion
+    pub converted: Option<String>,
+}
+
+impl fmt::Display for ConversionChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.record_type, self.kind.as_str())?;
+        if let Some(field_name) = self.field_name {
+            write!(f, " {}", field_name)?;
+        }
+        if let Some(work) = &self.submitter_work_num {
+            write!(f, " (work {})", work)?;
+        }
+        match (&self.original, &self.converted) {
+            (Some(original), Some(converted)) => write!(f, ": '{}' -> '{}'", original, converted),
+            (Some(original), None) => write!(f, ": '{}'", original),
+            (None, Some(converted)) => write!(f, ": set to '{}'", converted),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+/// Everything [`convert_version`] changed
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConversionReport {
+    pub source_version: f32,
+    pub target_version: f32,
+    pub changes: Vec<ConversionChange>,
+}
+
+impl ConversionReport {
+    /// Changes that lost data: dropped records and fields, and truncated values
+    pub fn lossy_changes(&self) -> impl Iterator<Item = &ConversionChange> {
+        self.changes.iter().filter(|change| change.kind != ConversionChangeKind::DefaultedField)
+    }
+}
+
+impl fmt::Display for ConversionReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Converted CWR {:.1} to {:.1}: {} changes ({} lossy)",
+            self.source_version,
+            self.target_version,
+            self.changes.len(),
+            self.lossy_changes().count()
+        )?;
+        for change in &self.changes {
+            write!(f, "\n  {}", change)?;
+        }
+        Ok(())
+    }
+}
+
+/// Rewrites `file` in place for `target_version`, returning what was changed
+pub fn convert_version(file: &mut CwrFile, target_version: f32) -> Result<ConversionReport, CwrParseError> {
+    let source_version = file.version.0;
+    let mut report = ConversionReport { source_version, target_version, changes: Vec::new() };
+    let character_set = file.character_set.clone();
+    let converter = Converter { source_version, target_version, character_set: &character_set };
+
+    let header = CwrRegistry::Hdr(file.header.clone());
+    if let Some(CwrRegistry::Hdr(mut hdr)) = converter.convert(&header, None, &mut report)? {
+        if target_version >= 2.2 && hdr.version.is_none() {
+            hdr.version = Some(CwrVersion(target_version));
+            report.changes.push(defaulted("HDR", None, "version", format!("{:.1}", target_version)));
+        }
+        file.header = hdr;
+    }
+
+    for group in &mut file.groups {
+        group.header.version_number = CwrVersionNumber(format!("{:05.2}", target_version));
+        for transaction in &mut group.transactions {
+            let submitter_work_num = transaction.submitter_work_num().map(str::to_string);
+            let mut records = Vec::with_capacity(transaction.records.len());
+            for record in &transaction.records {
+                if let Some(converted) = converter.convert(record, submitter_work_num.as_deref(), &mut report)? {
+                    records.push(converted);
+                }
+            }
+            transaction.records = records;
+            if target_version >= 2.2 && source_version < 2.2 {
+                default_publisher_sequence_numbers(transaction, submitter_work_num.as_deref(), &mut report);
+            }
+        }
+        group.transactions.retain(|transaction| !transaction.records.is_empty());
+    }
+
+    file.version = CwrVersion(target_version);
+    Ok(report)
+}
+
+/// Reads, converts and writes a CWR file, returning what was changed
+#[cfg(feature = "fs")]
+pub fn convert_cwr_file(
+    input_filename: &str, output_filename: &str, target_version: f32, version_hint: Option<f32>,
+) -> Result<ConversionReport, CwrParseError> {
+    let options = crate::parser::ParseOptions { version_hint, ..Default::default() };
+    let mut file = CwrFile::parse_file_with_options(input_filename, &options)?;
+    let report = convert_version(&mut file, target_version)?;
+    file.write(std::io::BufWriter::new(std::fs::File::create(output_filename)?))?;
+    tracing::info!(file = input_filename, "{}", report);
+    Ok(report)
+}
+
+struct Converter<'a> {
+    source_version: f32,
+    target_version: f32,
+    character_set: &'a CharacterSet,
+}
+
+impl Converter<'_> {
+    /// The record in the target version's layout, or `None` when the target version has no such record
+    fn convert(
+        &self, record: &CwrRegistry, submitter_work_num: Option<&str>, report: &mut ConversionReport,
+    ) -> Result<Option<CwrRegistry>, CwrParseError> {
+        let record_type = record.record_type().to_string();
+        let change = |kind, field_name, original, converted| ConversionChange {
+            kind,
+            record_type: record_type.clone(),
+            submitter_work_num: submitter_work_num.map(str::to_string),
+            field_name,
+            original,
+            converted,
+        };
+
+        if record.check_version(self.target_version).is_err() {
+            report.changes.push(change(ConversionChangeKind::DroppedRecord, None, None, None));
+            return Ok(None);
+        }
+
+        let source = record.to_cwr_record_bytes(&CwrVersion(self.source_version), self.character_set);
+        for spec in record.field_specs() {
+            if spec.min_version.is_none_or(|min| min <= self.target_version)
+                || record_type == "HDR" && spec.name == "version"
+            {
+                continue;
+            }
+            let (start, len) = spec.layout_for(self.source_version);
+            let value = source.get(start..(start + len).min(source.len())).unwrap_or_default();
+            let value = crate::encoding::decode_lossy(value, self.character_set);
+            if !value.trim().is_empty() {
+                let original = Some(value.trim().to_string());
+                report.changes.push(change(ConversionChangeKind::DroppedField, Some(spec.name), original, None));
+            }
+        }
+
+        let mut truncations = Vec::new();
+        let bytes = record.write_cwr_record_bytes(
+            &CwrVersion(self.target_version),
+            self.character_set,
+            FieldOverflow::Truncate,
+            &mut truncations,
+        )?;
+        for truncation in truncations {
+            report.changes.push(change(
+                ConversionChangeKind::TruncatedField,
+                Some(truncation.field_name),
+                Some(truncation.original),
+                Some(truncation.truncated),
+            ));
+        }
+
+        let line = CwrLine::with_character_set(&bytes, self.character_set);
+        let (converted, _) = parse_line_by_record_type(&record_type, &line, Some(self.target_version))?;
+        Ok(Some(converted))
+    }
+}
+
+fn defaulted(
+    record_type: &str, submitter_work_num: Option<&str>, field_name: &'static str, value: String,
+) -> ConversionChange {
+    ConversionChange {
+        kind: ConversionChangeKind::DefaultedField,
+        record_type: record_type.to_string(),
+        submitter_work_num: submitter_work_num.map(str::to_string),
+        field_name: Some(field_name),
+        original: None,
+        converted: Some(value),
+    }
+}
+
+/// Fills in the PWR publisher sequence number (new in 2.2) from the SPU of the same publisher
+fn default_publisher_sequence_numbers(
+    transaction: &mut CwrTransaction, submitter_work_num: Option<&str>, report: &mut ConversionReport,
+) {
+    let chains: Vec<(String, crate::domain_types::PublisherSequenceNumber)> = transaction
+        .records
+        .iter()
+        .filter_map(|record| match record {
+            CwrRegistry::Spu(spu) => {
+                Some((spu.interested_party_num.as_deref()?.trim().to_string(), spu.publisher_sequence_num.clone()))
+            }
+            _ => None,
+        })
+        .collect();
+    for record in &mut transaction.records {
+        if let CwrRegistry::Pwr(pwr) = record
+            && pwr.publisher_sequence_num.is_none()
+            && let Some(publisher_ip) = pwr.publisher_ip_num.as_deref().map(str::trim)
+            && let Some((_, chain)) = chains.iter().find(|(ip, _)| ip == publisher_ip)
+        {
+            pwr.publisher_sequence_num = Some(chain.clone());
+            report.changes.push(defaulted("PWR", submitter_work_num, "publisher_sequence_num", chain.0.to_string()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::ParseOptions;
+
+    const HDR_22: &str = "HDRPB285606836WARNER CHAPPELL MUSIC PUBLISHING LTD         01.102022122112541120221221               2.2001ALLEGRO                       1.0                           ";
+
+    fn nwr(submitter_work_num: &str) -> String {
+        format!("NWR0000000000000000{:<60}  {:<14}     SER        Y       ORI", "TEST WORK TITLE", submitter_work_num)
+    }
+
+    fn spu() -> String {
+        format!("SPU000000000000000101{:<9}{:<45} E {:<34}021050000210500002105000 N", "P00000001", "ACME MUSIC", "")
+    }
+
+    fn pwr(publisher_sequence_num: &str) -> String {
+        format!(
+            "PWR0000000000000002{:<9}{:<45}{:<28}W00000001{}",
+            "P00000001", "ACME MUSIC", "", publisher_sequence_num
+        )
+    }
+
+    #[test]
+    fn test_downgrade_reports_dropped_fields() {
+        let content = [
+            HDR_22.to_string(),
+            "GRHNWR0000102.200000000000  ".to_string(),
+            nwr("SW0000000001"),
+            spu(),
+            pwr("01"),
+            "GRT000010000000100000005".to_string(),
+            "TRL000000010000000100000007".to_string(),
+        ]
+        .join("\r\n");
+        let mut file = CwrFile::parse_bytes(content.as_bytes(), &ParseOptions::default()).unwrap();
+        assert_eq!(file.version.0, 2.2);
+
+        let report = convert_version(&mut file, 2.1).unwrap();
+        let dropped: Vec<(&str, Option<&str>)> =
+            report.lossy_changes().map(|change| (change.record_type.as_str(), change.field_name)).collect();
+        assert!(dropped.contains(&("HDR", Some("software_package"))), "{}", report);
+        assert!(dropped.contains(&("PWR", Some("publisher_sequence_num"))), "{}", report);
+        assert!(!dropped.contains(&("HDR", Some("version"))), "{}", report);
+
+        let mut output = Vec::new();
+        file.write(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines[0].trim_end().len(), 86, "{}", lines[0]);
+        assert!(lines[1].starts_with("GRHNWR0000102.10"), "{}", lines[1]);
+    }
+
+    #[test]
+    fn test_upgrade_defaults_new_fields() {
+        let content = [
+            "HDRPB285606836WARNER CHAPPELL MUSIC PUBLISHING LTD         01.102022122112541120221221".to_string(),
+            "GRHNWR0000102.100000000000  ".to_string(),
+            nwr("SW0000000001"),
+            spu(),
+            pwr(""),
+            "GRT000010000000100000005".to_string(),
+            "TRL000000010000000100000007".to_string(),
+        ]
+        .join("\r\n");
+        let options = ParseOptions { version_hint: Some(2.1), ..Default::default() };
+        let mut file = CwrFile::parse_bytes(content.as_bytes(), &options).unwrap();
+
+        let report = convert_version(&mut file, 2.2).unwrap();
+        assert_eq!(report.lossy_changes().count(), 0, "{}", report);
+        let defaulted: Vec<(&str, Option<&str>)> =
+            report.changes.iter().map(|change| (change.record_type.as_str(), change.field_name)).collect();
+        assert_eq!(defaulted, [("HDR", Some("version")), ("PWR", Some("publisher_sequence_num"))]);
+
+        let mut output = Vec::new();
+        file.write(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines[0].get(101..104), Some("2.2"), "{}", lines[0]);
+        assert!(lines[1].starts_with("GRHNWR0000102.20"), "{}", lines[1]);
+        assert!(lines[4].ends_with("01"), "{}", lines[4]);
+    }
+}
@@ -1,4 +1,10 @@
-use crate::{CwrParseError, ParsedRecord};
+use crate::cwr_registry::CwrRegistry;
+use crate::domain_types::CharacterSet;
+use crate::parsing::CwrWarning;
+use crate::{CwrParseError, ParseOptions, ParsedRecord, TransmissionMetrics, TransmissionSummary};
+use std::collections::BTreeMap;
+use std::ops::RangeInclusive;
+use std::time::Duration;
 
 /// Trait for handling CWR records during processing
 pub trait CwrHandler {
@@ -10,18 +16,593 @@ pub trait CwrHandler {
     /// Handle a parsing error (e.g., log it, count it, etc.)
     fn handle_parse_error(&mut self, line_number: usize, error: &CwrParseError) -> Result<(), Self::Error>;
 
+    /// Record type codes (e.g. `["NWR", "SWR"]`) this handler processes. Records of other types are skipped,
+    /// when reading a file before they are parsed, so extract-only handlers run faster. HDR, GRH, GRT and TRL
+    /// records are always processed. `None` (the default) processes every record.
+    fn interested_in(&self) -> Option<&'static [&'static str]> {
+        None
+    }
+
     /// Handle warnings from a successfully parsed record (optional override)
     fn handle_warnings(
-        &mut self, line_number: usize, record_type: &str, warnings: &[String],
+        &mut self, line_number: usize, record_type: &str, warnings: &[CwrWarning<'static>],
     ) -> Result<(), Self::Error> {
         // Default implementation does nothing - handlers can override to store warnings
         let _ = (line_number, record_type, warnings);
         Ok(())
     }
 
+    /// Called before the GRH record that opens a group
+    fn begin_group(&mut self, header: &ParsedRecord) -> Result<(), Self::Error> {
+        let _ = header;
+        Ok(())
+    }
+
+    /// Called after the GRT record that closes a group, or when a group ends without one
+    fn end_group(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Called before the header record (record sequence 0) that opens a transaction
+    fn begin_transaction(&mut self, header: &ParsedRecord) -> Result<(), Self::Error> {
+        let _ = header;
+        Ok(())
+    }
+
+    /// Called once the last record of a transaction has been processed, before the next transaction or group
+    /// boundary record
+    fn end_transaction(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
     /// Finalize processing (e.g., commit transaction, close files, etc.)
     fn finalize(&mut self) -> Result<(), Self::Error>;
 
     /// Generate a report of the processing results
     fn get_report(&self) -> String;
+
+    /// Typed counterpart of [`get_report`](CwrHandler::get_report), serializable for aggregating the results of
+    /// many files. Defaults to the text report.
+    fn report(&self) -> HandlerReport {
+        HandlerReport::Text { report: self.get_report() }
+    }
+}
+
+/// What a handler did, by kind of handler; serialized with a `handler` tag (e.g. `{"handler": "output", ...}`)
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+#[serde(tag = "handler", rename_all = "snake_case")]
+pub enum HandlerReport {
+    Summary(TransmissionSummary),
+    Metrics(TransmissionMetrics),
+    /// Handlers that write the records out, e.g. as JSON or to a database
+    Output(OutputReport),
+    /// The reports of both handlers of a [`crate::TeeHandler`]
+    Tee {
+        first: Box<HandlerReport>,
+        second: Box<HandlerReport>,
+    },
+    /// Handlers without a typed report
+    Text {
+        report: String,
+    },
+}
+
+impl Default for HandlerReport {
+    fn default() -> Self {
+        HandlerReport::Text { report: String::new() }
+    }
+}
+
+impl HandlerReport {
+    /// The output report of this handler, or of the first handler of a tee that has one
+    pub fn output(&self) -> Option<&OutputReport> {
+        match self {
+            HandlerReport::Output(output) => Some(output),
+            HandlerReport::Tee { first, second } => first.output().or_else(|| second.output()),
+            _ => None,
+        }
+    }
+}
+
+/// What a handler that writes records out did
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize)]
+pub struct OutputReport {
+    /// Output format, e.g. `json` or `sqlite`
+    pub format: String,
+    /// Where the records went (e.g. the database file), when the handler knows
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub destination: Option<String>,
+    pub records: usize,
+    pub errors: usize,
+}
+
+/// True when a handler subscribed to `interested_in` processes records of `record_type`
+pub(crate) fn is_interested(interested_in: Option<&[&str]>, record_type: &str) -> bool {
+    interested_in
+        .is_none_or(|codes| matches!(record_type, "HDR" | "GRH" | "GRT" | "TRL") || codes.contains(&record_type))
+}
+
+/// Parse options that skip the record types a handler is not interested in, unless `options` already restricts
+/// the record types
+pub(crate) fn options_for_handler(options: &ParseOptions, interested_in: Option<&[&str]>) -> ParseOptions {
+    let mut options = options.clone();
+    if options.record_types.is_none() {
+        options.record_types = interested_in.map(|codes| codes.iter().map(|code| code.to_string()).collect());
+    }
+    options
+}
+
+/// What processing with a handler does when a line cannot be parsed or a record has a critical warning
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ErrorMode {
+    /// Report the error to the handler and carry on with the next line; the lines skipped are logged at the end
+    #[default]
+    CollectAll,
+    /// Stop at the first such line with a [`ProcessingAborted`] error, without finalizing the handler
+    FailFast,
+}
+
+/// Processing stopped at a parse error or critical warning in [`ErrorMode::FailFast`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcessingAborted {
+    /// Line of the error, when known
+    pub line_number: Option<usize>,
+    pub message: String,
+}
+
+impl std::fmt::Display for ProcessingAborted {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.line_number {
+            Some(line_number) => write!(f, "Processing stopped at line {}: {}", line_number, self.message),
+            None => write!(f, "Processing stopped: {}", self.message),
+        }
+    }
+}
+
+impl std::error::Error for ProcessingAborted {}
+
+/// What processing a file with a handler did, alongside the handler's own report
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct ProcessingSummary {
+    /// The handler's [report](CwrHandler::get_report)
+    pub report: String,
+    /// The handler's [typed report](CwrHandler::report)
+    pub handler_report: HandlerReport,
+    /// Records passed to the handler
+    pub records_processed: usize,
+    /// Lines that could not be parsed
+    pub errors: usize,
+    /// Warnings on the records passed to the handler, by record type
+    pub warnings_by_record_type: BTreeMap<String, usize>,
+    /// Version the records were read as, when any record was parsed
+    pub cwr_version: Option<f32>,
+    pub character_set: Option<CharacterSet>,
+    /// Time spent processing; `None` on targets without a clock, such as wasm32-unknown-unknown
+    pub duration: Option<Duration>,
+    /// Lines that were not processed because they could not be parsed
+    pub skipped_lines: SkippedLines,
+}
+
+impl ProcessingSummary {
+    /// Total number of warnings
+    pub fn warnings(&self) -> usize {
+        self.warnings_by_record_type.values().sum()
+    }
+}
+
+/// Lines that were not processed, merged into ranges of consecutive lines
+#[derive(Debug, Default, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(transparent)]
+pub struct SkippedLines {
+    ranges: Vec<RangeInclusive<usize>>,
+}
+
+impl SkippedLines {
+    pub fn ranges(&self) -> &[RangeInclusive<usize>] {
+        &self.ranges
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    pub(crate) fn add(&mut self, line_number: usize) {
+        match self.ranges.last_mut() {
+            Some(range) if range.contains(&line_number) => {}
+            Some(range) if *range.end() + 1 == line_number => *range = *range.start()..=line_number,
+            _ => self.ranges.push(line_number..=line_number),
+        }
+    }
+}
+
+/// e.g. `4, 9-11`
+impl std::fmt::Display for SkippedLines {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, range) in self.ranges.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            if range.start() == range.end() {
+                write!(f, "{}", range.start())?;
+            } else {
+                write!(f, "{}-{}", range.start(), range.end())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Group or transaction boundary reported to a handler around the records it processes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Boundary {
+    BeginGroup,
+    EndGroup,
+    BeginTransaction,
+    EndTransaction,
+}
+
+/// Boundaries reported before a record
+pub(crate) type Boundaries = std::iter::Flatten<std::array::IntoIter<Option<Boundary>, 3>>;
+
+/// Tracks open groups and transactions so the boundary callbacks are always balanced
+#[derive(Debug, Default)]
+pub(crate) struct BoundaryTracker {
+    in_group: bool,
+    in_transaction: bool,
+}
+
+impl BoundaryTracker {
+    /// Boundaries to report before `record` is processed, in order
+    pub(crate) fn before_record(&mut self, record: &ParsedRecord) -> Boundaries {
+        let mut boundaries = [None; 3];
+        match record.record.sequence_numbers() {
+            Some((_, 0)) => {
+                boundaries[0] = self.close_transaction();
+                self.in_transaction = true;
+                boundaries[1] = Some(Boundary::BeginTransaction);
+            }
+            Some(_) => {}
+            None => {
+                boundaries[0] = self.close_transaction();
+                match record.record.record_type() {
+                    "GRH" => {
+                        boundaries[1] = self.close_group();
+                        self.in_group = true;
+                        boundaries[2] = Some(Boundary::BeginGroup);
+                    }
+                    "GRT" => {}
+                    _ => boundaries[1] = self.close_group(),
+                }
+            }
+        }
+        boundaries.into_iter().flatten()
+    }
+
+    /// Returns true when a record of this type closes the open group (`end_group` follows its processing)
+    pub(crate) fn after_record(&mut self, record_type: &str) -> bool {
+        record_type == "GRT" && self.close_group().is_some()
+    }
+
+    /// Transaction and group still open at the end of the file (only `End` boundaries)
+    pub(crate) fn finish(&mut self) -> impl Iterator<Item = Boundary> + use<> {
+        [self.close_transaction(), self.close_group()].into_iter().flatten()
+    }
+
+    fn close_transaction(&mut self) -> Option<Boundary> {
+        std::mem::take(&mut self.in_transaction).then_some(Boundary::EndTransaction)
+    }
+
+    fn close_group(&mut self) -> Option<Boundary> {
+        std::mem::take(&mut self.in_group).then_some(Boundary::EndGroup)
+    }
+}
+
+/// What a handler driver does with the next record or parse error
+// Each step is matched as soon as it is made, so boxing the record would only add an allocation per record
+#[allow(clippy::large_enum_variant)]
+pub(crate) enum DriverStep {
+    /// A record the handler is not interested in
+    Skip,
+    /// Report the record's warnings, if any, then stop with `abort` when set. Otherwise report `boundaries`,
+    /// process the record, and end the group after it when `ends_group`.
+    Record { record: ParsedRecord, abort: Option<ProcessingAborted>, boundaries: Boundaries, ends_group: bool },
+    /// Report the error to the handler, then stop with `abort` when set
+    ParseError { line_number: usize, error: CwrParseError, abort: Option<ProcessingAborted> },
+}
+
+/// Bookkeeping shared by the sync and async handler drivers, which differ only in how they call the handler
+pub(crate) struct HandlerDriver {
+    span: tracing::Span,
+    group_span: tracing::Span,
+    group_ended: bool,
+    mode: ErrorMode,
+    interested_in: Option<&'static [&'static str]>,
+    boundaries: BoundaryTracker,
+    summary: ProcessingSummary,
+    last_line_number: usize,
+    #[cfg(not(target_arch = "wasm32"))]
+    started: std::time::Instant,
+}
+
+impl HandlerDriver {
+    pub(crate) fn new(source: &str, mode: ErrorMode, interested_in: Option<&'static [&'static str]>) -> Self {
+        HandlerDriver {
+            span: tracing::info_span!("cwr_file", file = source),
+            group_span: tracing::Span::none(),
+            group_ended: false,
+            mode,
+            interested_in,
+            boundaries: BoundaryTracker::default(),
+            summary: ProcessingSummary::default(),
+            last_line_number: 0,
+            #[cfg(not(target_arch = "wasm32"))]
+            started: std::time::Instant::now(),
+        }
+    }
+
+    /// Span of the file being processed, the parent of the group spans
+    pub(crate) fn span(&self) -> &tracing::Span {
+        &self.span
+    }
+
+    /// Span of the group the last record belongs to, or a disabled span outside groups. Handler calls and parsing
+    /// run inside it, so their events carry the group.
+    pub(crate) fn group_span(&self) -> &tracing::Span {
+        &self.group_span
+    }
+
+    pub(crate) fn step(&mut self, result: Result<ParsedRecord, CwrParseError>) -> DriverStep {
+        if std::mem::take(&mut self.group_ended) {
+            self.group_span = tracing::Span::none();
+        }
+        match result {
+            Ok(record) => self.record(record),
+            Err(error) => self.parse_error(error),
+        }
+    }
+
+    fn record(&mut self, record: ParsedRecord) -> DriverStep {
+        self.last_line_number = record.line_number;
+        let record_type = record.record.record_type();
+        if !is_interested(self.interested_in, record_type) {
+            return DriverStep::Skip;
+        }
+        let boundaries = self.boundaries.before_record(&record);
+        for boundary in boundaries.clone() {
+            match (boundary, &record.record) {
+                (Boundary::EndGroup, _) => self.group_span = tracing::Span::none(),
+                (Boundary::BeginGroup, CwrRegistry::Grh(header)) => {
+                    self.group_span = tracing::info_span!(
+                        parent: &self.span,
+                        "cwr_group",
+                        group_id = header.group_id.0,
+                        transaction_type = header.transaction_type.as_str()
+                    );
+                }
+                _ => {}
+            }
+        }
+        self.group_span.in_scope(|| {
+            for warning in &record.warnings {
+                tracing::debug!(line = record.line_number, record_type, "{}", warning);
+            }
+        });
+        if self.summary.cwr_version.is_none() {
+            self.summary.cwr_version = Some(record.context.cwr_version);
+            self.summary.character_set = record.context.character_set.clone();
+        }
+        if !record.warnings.is_empty() {
+            *self.summary.warnings_by_record_type.entry(record_type.to_string()).or_default() += record.warnings.len();
+        }
+        let abort = match self.mode {
+            ErrorMode::FailFast => {
+                record.warnings.iter().find(|warning| warning.is_critical()).map(|warning| ProcessingAborted {
+                    line_number: Some(record.line_number),
+                    message: format!("{}: {}", record_type, warning),
+                })
+            }
+            ErrorMode::CollectAll => None,
+        };
+        let ends_group = self.boundaries.after_record(record_type);
+        self.group_ended = ends_group;
+        self.summary.records_processed += 1;
+        DriverStep::Record { record, abort, boundaries, ends_group }
+    }
+
+    fn parse_error(&mut self, error: CwrParseError) -> DriverStep {
+        let line_number = error.record_error().and_then(|err| err.line_number);
+        self.last_line_number = line_number.unwrap_or(self.last_line_number + 1);
+        self.summary.errors += 1;
+        let abort = match self.mode {
+            ErrorMode::FailFast => Some(ProcessingAborted { line_number, message: error.to_string() }),
+            ErrorMode::CollectAll => {
+                if let Some(line_number) = line_number {
+                    self.summary.skipped_lines.add(line_number);
+                }
+                None
+            }
+        };
+        DriverStep::ParseError { line_number: self.last_line_number, error, abort }
+    }
+
+    /// Transaction and group still open at the end of the records
+    pub(crate) fn finish(&mut self) -> impl Iterator<Item = Boundary> + use<> {
+        self.boundaries.finish()
+    }
+
+    /// The summary of a completed run, carrying the handler's reports
+    pub(crate) fn into_summary(mut self, report: String, handler_report: HandlerReport) -> ProcessingSummary {
+        tracing::info!(
+            "Processing complete: {} records processed, {} errors",
+            self.summary.records_processed,
+            self.summary.errors
+        );
+        if !self.summary.skipped_lines.is_empty() {
+            tracing::warn!("Lines skipped: {}", self.summary.skipped_lines);
+        }
+        self.summary.report = report;
+        self.summary.handler_report = handler_report;
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.summary.duration = Some(self.started.elapsed());
+        }
+        self.summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct RecordingHandler {
+        events: Vec<String>,
+        interested_in: Option<&'static [&'static str]>,
+    }
+
+    impl CwrHandler for RecordingHandler {
+        type Error = std::io::Error;
+
+        fn interested_in(&self) -> Option<&'static [&'static str]> {
+            self.interested_in
+        }
+
+        fn process_record(&mut self, record: ParsedRecord) -> Result<(), Self::Error> {
+            self.events.push(record.record.record_type().to_string());
+            Ok(())
+        }
+
+        fn handle_parse_error(&mut self, line_number: usize, _error: &CwrParseError) -> Result<(), Self::Error> {
+            self.events.push(format!("error@{}", line_number));
+            Ok(())
+        }
+
+        fn begin_group(&mut self, _header: &ParsedRecord) -> Result<(), Self::Error> {
+            self.events.push("begin_group".to_string());
+            Ok(())
+        }
+
+        fn end_group(&mut self) -> Result<(), Self::Error> {
+            self.events.push("end_group".to_string());
+            Ok(())
+        }
+
+        fn begin_transaction(&mut self, _header: &ParsedRecord) -> Result<(), Self::Error> {
+            self.events.push("begin_transaction".to_string());
+            Ok(())
+        }
+
+        fn end_transaction(&mut self) -> Result<(), Self::Error> {
+            self.events.push("end_transaction".to_string());
+            Ok(())
+        }
+
+        fn finalize(&mut self) -> Result<(), Self::Error> {
+            Ok(())
+        }
+
+        fn get_report(&self) -> String {
+            self.events.join(",")
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn test_boundary_callbacks_are_balanced() {
+        let nwr = "NWR0000000000000000Test Song                                               SW0000000001        SER        Y       ORI";
+        let alt = "ALT0000000000000001ALTERNATE TITLE                                             ";
+        let content = format!(
+            "HDRPB285606836WARNER CHAPPELL MUSIC PUBLISHING LTD         01.102022122112541120221221\n\
+             GRHNWR0000102.100000000000  \n{}\n{}\n{}\nGRT000010000000200000006\nTRL000000010000000200000008\n",
+            nwr,
+            alt,
+            nwr.replacen("0000000000000000", "0000000100000000", 1)
+        );
+        let path = std::env::temp_dir().join(format!("boundary_test_{:?}.V21", std::thread::current().id()));
+        std::fs::write(&path, content).unwrap();
+        let report =
+            crate::process_cwr_with_handler(path.to_str().unwrap(), RecordingHandler::default()).unwrap().report;
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(
+            report,
+            "HDR,begin_group,GRH,begin_transaction,NWR,ALT,end_transaction,begin_transaction,NWR,end_transaction,\
+             GRT,end_group,TRL"
+        );
+    }
+
+    #[test]
+    fn test_handler_only_gets_the_record_types_it_is_interested_in() {
+        let nwr = "NWR0000000000000000Test Song                                               SW0000000001        SER        Y       ORI";
+        let content = format!(
+            "HDRPB285606836WARNER CHAPPELL MUSIC PUBLISHING LTD         01.102022122112541120221221\n\
+             GRHNWR0000102.100000000000  \n{}\nALT0000000000000001\n{}\nGRT000010000000200000006\n\
+             TRL000000010000000200000008\n",
+            nwr,
+            nwr.replacen("0000000000000000", "0000000100000000", 1)
+        );
+        let handler = RecordingHandler { interested_in: Some(&["NWR"]), ..Default::default() };
+        let report = crate::process_cwr_bytes(content.as_bytes(), handler).unwrap().report;
+
+        // The malformed ALT line is skipped without being parsed, so it reports no error
+        assert_eq!(
+            report,
+            "HDR,begin_group,GRH,begin_transaction,NWR,end_transaction,begin_transaction,NWR,end_transaction,\
+             GRT,end_group,TRL"
+        );
+    }
+
+    #[test]
+    fn test_error_modes() {
+        let nwr = "NWR0000000000000000Test Song                                               SW0000000001        SER        Y       ORI";
+        let content = format!(
+            "HDRPB285606836WARNER CHAPPELL MUSIC PUBLISHING LTD         01.102022122112541120221221\n\
+             GRHNWR0000102.100000000000  \nXYZ0000000000000000\n{}\nXYZ0000000000000001\n\
+             GRT000010000000200000006\nTRL000000010000000200000008\n",
+            nwr
+        );
+        let run = |mode| {
+            let records = crate::process_cwr_bytes_stream(content.as_bytes(), &ParseOptions::default()).unwrap();
+            crate::process_records_with_handler_and_mode("<test>", records, RecordingHandler::default(), mode)
+        };
+
+        let summary = run(ErrorMode::CollectAll).unwrap();
+        assert_eq!(
+            summary.report,
+            "HDR,begin_group,GRH,error@3,begin_transaction,NWR,error@5,end_transaction,GRT,end_group,TRL"
+        );
+        assert_eq!((summary.records_processed, summary.errors), (5, 2));
+        assert_eq!(summary.skipped_lines.to_string(), "3, 5");
+        assert_eq!(summary.cwr_version, Some(2.1));
+        assert_eq!(summary.warnings_by_record_type.get("NWR"), Some(&5));
+        assert_eq!(summary.warnings(), 6);
+
+        let error = run(ErrorMode::FailFast).unwrap_err();
+        let aborted = error.downcast_ref::<ProcessingAborted>().unwrap();
+        assert_eq!(aborted.line_number, Some(3));
+
+        // Records the handler is not interested in still count as lines
+        let records = crate::process_cwr_bytes_stream(content.as_bytes(), &ParseOptions::default()).unwrap();
+        let handler = RecordingHandler { interested_in: Some(&["ALT"]), ..Default::default() };
+        let summary = crate::process_records_with_handler("<test>", records, handler).unwrap();
+        assert_eq!(summary.report, "HDR,begin_group,GRH,error@3,error@5,GRT,end_group,TRL");
+    }
+
+    #[test]
+    fn test_skipped_lines() {
+        let mut skipped = SkippedLines::default();
+        assert!(skipped.is_empty());
+        for line_number in [4, 9, 10, 10, 11, 20] {
+            skipped.add(line_number);
+        }
+        assert_eq!(skipped.to_string(), "4, 9-11, 20");
+    }
+
+    #[test]
+    fn test_process_cwr_bytes_with_handler() {
+        let content = "HDRPB285606836WARNER CHAPPELL MUSIC PUBLISHING LTD         01.102022122112541120221221\n\
+                       GRHNWR0000102.100000000000  \nGRT000010000000000000002\nTRL000000010000000000000004\n";
+        let report = crate::process_cwr_bytes(content.as_bytes(), RecordingHandler::default()).unwrap().report;
+        assert_eq!(report, "HDR,begin_group,GRH,GRT,end_group,TRL");
+    }
 }
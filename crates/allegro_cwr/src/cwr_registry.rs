@@ -1,11 +1,16 @@
-use crate::error::CwrParseError;
+use crate::error::{CwrParseError, ParseErrorCode, RecordParseError};
+use crate::parsing::{CwrLine, CwrWarning};
+use crate::records::FieldKind;
 use crate::records::*;
 use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::ops::Range;
 use std::sync::LazyLock;
 
-type ParseResult = Result<(CwrRegistry, Vec<String>), CwrParseError>;
-type ParseFunction = fn(&str) -> ParseResult;
-type ParserMap = HashMap<&'static str, ParseFunction>;
+type ParseResult = Result<(CwrRegistry, Vec<CwrWarning<'static>>), CwrParseError>;
+type ParseFunction = fn(&CwrLine<'_>, Option<f32>) -> ParseResult;
+type VersionRangeFunction = fn() -> (Option<f32>, Option<f32>);
+type ParserMap = HashMap<&'static str, (ParseFunction, VersionRangeFunction)>;
 
 /// Enum containing all possible parsed CWR record types.
 /// Note: This represents the record types we parse INTO, not the input codes.
@@ -127,18 +132,682 @@ impl CwrRegistry {
             CwrRegistry::Xrf(record) => record.to_cwr_record_bytes(cwr_version, character_set),
         }
     }
+
+    /// Convert this registry record to bytes, refusing record types that do not exist in the target version
+    pub fn try_to_cwr_record_bytes(
+        &self, cwr_version: &crate::domain_types::CwrVersion, character_set: &crate::domain_types::CharacterSet,
+    ) -> Result<Vec<u8>, CwrParseError> {
+        match self {
+            CwrRegistry::Hdr(record) => record.try_to_cwr_record_bytes(cwr_version, character_set),
+            CwrRegistry::Grh(record) => record.try_to_cwr_record_bytes(cwr_version, character_set),
+            CwrRegistry::Grt(record) => record.try_to_cwr_record_bytes(cwr_version, character_set),
+            CwrRegistry::Trl(record) => record.try_to_cwr_record_bytes(cwr_version, character_set),
+            CwrRegistry::Agr(record) => record.try_to_cwr_record_bytes(cwr_version, character_set),
+            CwrRegistry::Nwr(record) => record.try_to_cwr_record_bytes(cwr_version, character_set),
+            CwrRegistry::Ack(record) => record.try_to_cwr_record_bytes(cwr_version, character_set),
+            CwrRegistry::Ter(record) => record.try_to_cwr_record_bytes(cwr_version, character_set),
+            CwrRegistry::Ipa(record) => record.try_to_cwr_record_bytes(cwr_version, character_set),
+            CwrRegistry::Npa(record) => record.try_to_cwr_record_bytes(cwr_version, character_set),
+            CwrRegistry::Spu(record) => record.try_to_cwr_record_bytes(cwr_version, character_set),
+            CwrRegistry::Npn(record) => record.try_to_cwr_record_bytes(cwr_version, character_set),
+            CwrRegistry::Spt(record) => record.try_to_cwr_record_bytes(cwr_version, character_set),
+            CwrRegistry::Swr(record) => record.try_to_cwr_record_bytes(cwr_version, character_set),
+            CwrRegistry::Nwn(record) => record.try_to_cwr_record_bytes(cwr_version, character_set),
+            CwrRegistry::Swt(record) => record.try_to_cwr_record_bytes(cwr_version, character_set),
+            CwrRegistry::Pwr(record) => record.try_to_cwr_record_bytes(cwr_version, character_set),
+            CwrRegistry::Alt(record) => record.try_to_cwr_record_bytes(cwr_version, character_set),
+            CwrRegistry::Nat(record) => record.try_to_cwr_record_bytes(cwr_version, character_set),
+            CwrRegistry::Ewt(record) => record.try_to_cwr_record_bytes(cwr_version, character_set),
+            CwrRegistry::Ver(record) => record.try_to_cwr_record_bytes(cwr_version, character_set),
+            CwrRegistry::Per(record) => record.try_to_cwr_record_bytes(cwr_version, character_set),
+            CwrRegistry::Npr(record) => record.try_to_cwr_record_bytes(cwr_version, character_set),
+            CwrRegistry::Rec(record) => record.try_to_cwr_record_bytes(cwr_version, character_set),
+            CwrRegistry::Orn(record) => record.try_to_cwr_record_bytes(cwr_version, character_set),
+            CwrRegistry::Ins(record) => record.try_to_cwr_record_bytes(cwr_version, character_set),
+            CwrRegistry::Ind(record) => record.try_to_cwr_record_bytes(cwr_version, character_set),
+            CwrRegistry::Com(record) => record.try_to_cwr_record_bytes(cwr_version, character_set),
+            CwrRegistry::Msg(record) => record.try_to_cwr_record_bytes(cwr_version, character_set),
+            CwrRegistry::Net(record) => record.try_to_cwr_record_bytes(cwr_version, character_set),
+            CwrRegistry::Now(record) => record.try_to_cwr_record_bytes(cwr_version, character_set),
+            CwrRegistry::Ari(record) => record.try_to_cwr_record_bytes(cwr_version, character_set),
+            CwrRegistry::Xrf(record) => record.try_to_cwr_record_bytes(cwr_version, character_set),
+        }
+    }
+
+    /// Convert this registry record to bytes, handling values longer than their field as `overflow` says.
+    /// Truncated values are appended to `truncations`.
+    pub fn write_cwr_record_bytes(
+        &self, cwr_version: &crate::domain_types::CwrVersion, character_set: &crate::domain_types::CharacterSet,
+        overflow: crate::parsing::FieldOverflow, truncations: &mut Vec<crate::parsing::FieldTruncation>,
+    ) -> Result<Vec<u8>, CwrParseError> {
+        match self {
+            CwrRegistry::Hdr(record) => {
+                record.write_cwr_record_bytes(cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Grh(record) => {
+                record.write_cwr_record_bytes(cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Grt(record) => {
+                record.write_cwr_record_bytes(cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Trl(record) => {
+                record.write_cwr_record_bytes(cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Agr(record) => {
+                record.write_cwr_record_bytes(cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Nwr(record) => {
+                record.write_cwr_record_bytes(cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Ack(record) => {
+                record.write_cwr_record_bytes(cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Ter(record) => {
+                record.write_cwr_record_bytes(cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Ipa(record) => {
+                record.write_cwr_record_bytes(cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Npa(record) => {
+                record.write_cwr_record_bytes(cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Spu(record) => {
+                record.write_cwr_record_bytes(cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Npn(record) => {
+                record.write_cwr_record_bytes(cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Spt(record) => {
+                record.write_cwr_record_bytes(cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Swr(record) => {
+                record.write_cwr_record_bytes(cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Nwn(record) => {
+                record.write_cwr_record_bytes(cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Swt(record) => {
+                record.write_cwr_record_bytes(cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Pwr(record) => {
+                record.write_cwr_record_bytes(cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Alt(record) => {
+                record.write_cwr_record_bytes(cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Nat(record) => {
+                record.write_cwr_record_bytes(cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Ewt(record) => {
+                record.write_cwr_record_bytes(cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Ver(record) => {
+                record.write_cwr_record_bytes(cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Per(record) => {
+                record.write_cwr_record_bytes(cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Npr(record) => {
+                record.write_cwr_record_bytes(cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Rec(record) => {
+                record.write_cwr_record_bytes(cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Orn(record) => {
+                record.write_cwr_record_bytes(cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Ins(record) => {
+                record.write_cwr_record_bytes(cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Ind(record) => {
+                record.write_cwr_record_bytes(cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Com(record) => {
+                record.write_cwr_record_bytes(cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Msg(record) => {
+                record.write_cwr_record_bytes(cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Net(record) => {
+                record.write_cwr_record_bytes(cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Now(record) => {
+                record.write_cwr_record_bytes(cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Ari(record) => {
+                record.write_cwr_record_bytes(cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Xrf(record) => {
+                record.write_cwr_record_bytes(cwr_version, character_set, overflow, truncations)
+            }
+        }
+    }
+
+    /// Write this registry record, without a line terminator, straight into `writer`
+    pub fn write_cwr_record<W: std::io::Write>(
+        &self, writer: &mut W, cwr_version: &crate::domain_types::CwrVersion,
+        character_set: &crate::domain_types::CharacterSet,
+    ) -> Result<(), CwrParseError> {
+        match self {
+            CwrRegistry::Hdr(record) => record.write_cwr_record(writer, cwr_version, character_set),
+            CwrRegistry::Grh(record) => record.write_cwr_record(writer, cwr_version, character_set),
+            CwrRegistry::Grt(record) => record.write_cwr_record(writer, cwr_version, character_set),
+            CwrRegistry::Trl(record) => record.write_cwr_record(writer, cwr_version, character_set),
+            CwrRegistry::Agr(record) => record.write_cwr_record(writer, cwr_version, character_set),
+            CwrRegistry::Nwr(record) => record.write_cwr_record(writer, cwr_version, character_set),
+            CwrRegistry::Ack(record) => record.write_cwr_record(writer, cwr_version, character_set),
+            CwrRegistry::Ter(record) => record.write_cwr_record(writer, cwr_version, character_set),
+            CwrRegistry::Ipa(record) => record.write_cwr_record(writer, cwr_version, character_set),
+            CwrRegistry::Npa(record) => record.write_cwr_record(writer, cwr_version, character_set),
+            CwrRegistry::Spu(record) => record.write_cwr_record(writer, cwr_version, character_set),
+            CwrRegistry::Npn(record) => record.write_cwr_record(writer, cwr_version, character_set),
+            CwrRegistry::Spt(record) => record.write_cwr_record(writer, cwr_version, character_set),
+            CwrRegistry::Swr(record) => record.write_cwr_record(writer, cwr_version, character_set),
+            CwrRegistry::Nwn(record) => record.write_cwr_record(writer, cwr_version, character_set),
+            CwrRegistry::Swt(record) => record.write_cwr_record(writer, cwr_version, character_set),
+            CwrRegistry::Pwr(record) => record.write_cwr_record(writer, cwr_version, character_set),
+            CwrRegistry::Alt(record) => record.write_cwr_record(writer, cwr_version, character_set),
+            CwrRegistry::Nat(record) => record.write_cwr_record(writer, cwr_version, character_set),
+            CwrRegistry::Ewt(record) => record.write_cwr_record(writer, cwr_version, character_set),
+            CwrRegistry::Ver(record) => record.write_cwr_record(writer, cwr_version, character_set),
+            CwrRegistry::Per(record) => record.write_cwr_record(writer, cwr_version, character_set),
+            CwrRegistry::Npr(record) => record.write_cwr_record(writer, cwr_version, character_set),
+            CwrRegistry::Rec(record) => record.write_cwr_record(writer, cwr_version, character_set),
+            CwrRegistry::Orn(record) => record.write_cwr_record(writer, cwr_version, character_set),
+            CwrRegistry::Ins(record) => record.write_cwr_record(writer, cwr_version, character_set),
+            CwrRegistry::Ind(record) => record.write_cwr_record(writer, cwr_version, character_set),
+            CwrRegistry::Com(record) => record.write_cwr_record(writer, cwr_version, character_set),
+            CwrRegistry::Msg(record) => record.write_cwr_record(writer, cwr_version, character_set),
+            CwrRegistry::Net(record) => record.write_cwr_record(writer, cwr_version, character_set),
+            CwrRegistry::Now(record) => record.write_cwr_record(writer, cwr_version, character_set),
+            CwrRegistry::Ari(record) => record.write_cwr_record(writer, cwr_version, character_set),
+            CwrRegistry::Xrf(record) => record.write_cwr_record(writer, cwr_version, character_set),
+        }
+    }
+
+    /// Write this registry record into `writer`, handling values longer than their field as `overflow` says.
+    /// Truncated values are appended to `truncations`.
+    pub fn write_cwr_record_with<W: std::io::Write>(
+        &self, writer: &mut W, cwr_version: &crate::domain_types::CwrVersion,
+        character_set: &crate::domain_types::CharacterSet, overflow: crate::parsing::FieldOverflow,
+        truncations: &mut Vec<crate::parsing::FieldTruncation>,
+    ) -> Result<(), CwrParseError> {
+        match self {
+            CwrRegistry::Hdr(record) => {
+                record.write_cwr_record_with(writer, cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Grh(record) => {
+                record.write_cwr_record_with(writer, cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Grt(record) => {
+                record.write_cwr_record_with(writer, cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Trl(record) => {
+                record.write_cwr_record_with(writer, cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Agr(record) => {
+                record.write_cwr_record_with(writer, cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Nwr(record) => {
+                record.write_cwr_record_with(writer, cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Ack(record) => {
+                record.write_cwr_record_with(writer, cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Ter(record) => {
+                record.write_cwr_record_with(writer, cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Ipa(record) => {
+                record.write_cwr_record_with(writer, cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Npa(record) => {
+                record.write_cwr_record_with(writer, cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Spu(record) => {
+                record.write_cwr_record_with(writer, cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Npn(record) => {
+                record.write_cwr_record_with(writer, cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Spt(record) => {
+                record.write_cwr_record_with(writer, cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Swr(record) => {
+                record.write_cwr_record_with(writer, cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Nwn(record) => {
+                record.write_cwr_record_with(writer, cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Swt(record) => {
+                record.write_cwr_record_with(writer, cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Pwr(record) => {
+                record.write_cwr_record_with(writer, cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Alt(record) => {
+                record.write_cwr_record_with(writer, cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Nat(record) => {
+                record.write_cwr_record_with(writer, cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Ewt(record) => {
+                record.write_cwr_record_with(writer, cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Ver(record) => {
+                record.write_cwr_record_with(writer, cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Per(record) => {
+                record.write_cwr_record_with(writer, cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Npr(record) => {
+                record.write_cwr_record_with(writer, cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Rec(record) => {
+                record.write_cwr_record_with(writer, cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Orn(record) => {
+                record.write_cwr_record_with(writer, cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Ins(record) => {
+                record.write_cwr_record_with(writer, cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Ind(record) => {
+                record.write_cwr_record_with(writer, cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Com(record) => {
+                record.write_cwr_record_with(writer, cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Msg(record) => {
+                record.write_cwr_record_with(writer, cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Net(record) => {
+                record.write_cwr_record_with(writer, cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Now(record) => {
+                record.write_cwr_record_with(writer, cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Ari(record) => {
+                record.write_cwr_record_with(writer, cwr_version, character_set, overflow, truncations)
+            }
+            CwrRegistry::Xrf(record) => {
+                record.write_cwr_record_with(writer, cwr_version, character_set, overflow, truncations)
+            }
+        }
+    }
+
+    /// Length of this record type in the given CWR version: the end of its last field present in that version
+    pub fn record_length(&self, version: f32) -> usize {
+        self.field_specs()
+            .iter()
+            .filter(|spec| spec.min_version.is_none_or(|min| version >= min))
+            .map(|spec| {
+                let (start, len) = spec.layout_for(version);
+                start + len
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Every field of this record with its name, title and stringified value, in declaration order
+    pub fn fields(&self) -> Fields<'_> {
+        match self {
+            CwrRegistry::Hdr(record) => record.fields(),
+            CwrRegistry::Grh(record) => record.fields(),
+            CwrRegistry::Grt(record) => record.fields(),
+            CwrRegistry::Trl(record) => record.fields(),
+            CwrRegistry::Agr(record) => record.fields(),
+            CwrRegistry::Nwr(record) => record.fields(),
+            CwrRegistry::Ack(record) => record.fields(),
+            CwrRegistry::Ter(record) => record.fields(),
+            CwrRegistry::Ipa(record) => record.fields(),
+            CwrRegistry::Npa(record) => record.fields(),
+            CwrRegistry::Spu(record) => record.fields(),
+            CwrRegistry::Npn(record) => record.fields(),
+            CwrRegistry::Spt(record) => record.fields(),
+            CwrRegistry::Swr(record) => record.fields(),
+            CwrRegistry::Nwn(record) => record.fields(),
+            CwrRegistry::Swt(record) => record.fields(),
+            CwrRegistry::Pwr(record) => record.fields(),
+            CwrRegistry::Alt(record) => record.fields(),
+            CwrRegistry::Nat(record) => record.fields(),
+            CwrRegistry::Ewt(record) => record.fields(),
+            CwrRegistry::Ver(record) => record.fields(),
+            CwrRegistry::Per(record) => record.fields(),
+            CwrRegistry::Npr(record) => record.fields(),
+            CwrRegistry::Rec(record) => record.fields(),
+            CwrRegistry::Orn(record) => record.fields(),
+            CwrRegistry::Ins(record) => record.fields(),
+            CwrRegistry::Ind(record) => record.fields(),
+            CwrRegistry::Com(record) => record.fields(),
+            CwrRegistry::Msg(record) => record.fields(),
+            CwrRegistry::Net(record) => record.fields(),
+            CwrRegistry::Now(record) => record.fields(),
+            CwrRegistry::Ari(record) => record.fields(),
+            CwrRegistry::Xrf(record) => record.fields(),
+        }
+    }
+
+    /// One line per field present in `version`: spec title, 1-based column range and value as it would be
+    /// written, for inspecting a record without the CWR specification at hand
+    pub fn describe(&self, version: f32) -> String {
+        let line = self
+            .to_cwr_record_bytes(&crate::domain_types::CwrVersion(version), &crate::domain_types::CharacterSet::UTF8);
+        let line = String::from_utf8_lossy(&line);
+        let specs: Vec<&FieldSpec> =
+            self.field_specs().iter().filter(|spec| spec.min_version.is_none_or(|min| version >= min)).collect();
+        let title_width = specs.iter().map(|spec| spec.title.len()).max().unwrap_or(0);
+
+        specs
+            .iter()
+            .map(|spec| {
+                let (start, len) = spec.layout_for(version);
+                let value: String = line.chars().skip(start).take(len).collect();
+                let columns = format!("{}-{}", start + 1, start + len);
+                format!("{:<title_width$}  {:>7}  '{}'", spec.title, columns, value.trim_end())
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Layout of every field in this record
+    pub fn field_specs(&self) -> &'static [FieldSpec] {
+        match self {
+            CwrRegistry::Hdr(_) => HdrRecord::FIELD_SPECS,
+            CwrRegistry::Grh(_) => GrhRecord::FIELD_SPECS,
+            CwrRegistry::Grt(_) => GrtRecord::FIELD_SPECS,
+            CwrRegistry::Trl(_) => TrlRecord::FIELD_SPECS,
+            CwrRegistry::Agr(_) => AgrRecord::FIELD_SPECS,
+            CwrRegistry::Nwr(_) => NwrRecord::FIELD_SPECS,
+            CwrRegistry::Ack(_) => AckRecord::FIELD_SPECS,
+            CwrRegistry::Ter(_) => TerRecord::FIELD_SPECS,
+            CwrRegistry::Ipa(_) => IpaRecord::FIELD_SPECS,
+            CwrRegistry::Npa(_) => NpaRecord::FIELD_SPECS,
+            CwrRegistry::Spu(_) => SpuRecord::FIELD_SPECS,
+            CwrRegistry::Npn(_) => NpnRecord::FIELD_SPECS,
+            CwrRegistry::Spt(_) => SptRecord::FIELD_SPECS,
+            CwrRegistry::Swr(_) => SwrRecord::FIELD_SPECS,
+            CwrRegistry::Nwn(_) => NwnRecord::FIELD_SPECS,
+            CwrRegistry::Swt(_) => SwtRecord::FIELD_SPECS,
+            CwrRegistry::Pwr(_) => PwrRecord::FIELD_SPECS,
+            CwrRegistry::Alt(_) => AltRecord::FIELD_SPECS,
+            CwrRegistry::Nat(_) => NatRecord::FIELD_SPECS,
+            CwrRegistry::Ewt(_) => EwtRecord::FIELD_SPECS,
+            CwrRegistry::Ver(_) => VerRecord::FIELD_SPECS,
+            CwrRegistry::Per(_) => PerRecord::FIELD_SPECS,
+            CwrRegistry::Npr(_) => NprRecord::FIELD_SPECS,
+            CwrRegistry::Rec(_) => RecRecord::FIELD_SPECS,
+            CwrRegistry::Orn(_) => OrnRecord::FIELD_SPECS,
+            CwrRegistry::Ins(_) => InsRecord::FIELD_SPECS,
+            CwrRegistry::Ind(_) => IndRecord::FIELD_SPECS,
+            CwrRegistry::Com(_) => ComRecord::FIELD_SPECS,
+            CwrRegistry::Msg(_) => MsgRecord::FIELD_SPECS,
+            CwrRegistry::Net(_) => NetRecord::FIELD_SPECS,
+            CwrRegistry::Now(_) => NowRecord::FIELD_SPECS,
+            CwrRegistry::Ari(_) => AriRecord::FIELD_SPECS,
+            CwrRegistry::Xrf(_) => XrfRecord::FIELD_SPECS,
+        }
+    }
+
+    /// Zero-based byte range of a field within the line in the given CWR version
+    pub fn field_columns(&self, field_name: &str, version: f32) -> Option<Range<usize>> {
+        let spec = self.field_specs().iter().find(|spec| spec.name == field_name)?;
+        let (start, len) = spec.layout_for(version);
+        Some(start..start + len)
+    }
+
+    /// CWR version range this record type exists in, as `(min_version, max_version)`
+    pub fn version_range(&self) -> (Option<f32>, Option<f32>) {
+        match self {
+            CwrRegistry::Hdr(_) => HdrRecord::version_range(),
+            CwrRegistry::Grh(_) => GrhRecord::version_range(),
+            CwrRegistry::Grt(_) => GrtRecord::version_range(),
+            CwrRegistry::Trl(_) => TrlRecord::version_range(),
+            CwrRegistry::Agr(_) => AgrRecord::version_range(),
+            CwrRegistry::Nwr(_) => NwrRecord::version_range(),
+            CwrRegistry::Ack(_) => AckRecord::version_range(),
+            CwrRegistry::Ter(_) => TerRecord::version_range(),
+            CwrRegistry::Ipa(_) => IpaRecord::version_range(),
+            CwrRegistry::Npa(_) => NpaRecord::version_range(),
+            CwrRegistry::Spu(_) => SpuRecord::version_range(),
+            CwrRegistry::Npn(_) => NpnRecord::version_range(),
+            CwrRegistry::Spt(_) => SptRecord::version_range(),
+            CwrRegistry::Swr(_) => SwrRecord::version_range(),
+            CwrRegistry::Nwn(_) => NwnRecord::version_range(),
+            CwrRegistry::Swt(_) => SwtRecord::version_range(),
+            CwrRegistry::Pwr(_) => PwrRecord::version_range(),
+            CwrRegistry::Alt(_) => AltRecord::version_range(),
+            CwrRegistry::Nat(_) => NatRecord::version_range(),
+            CwrRegistry::Ewt(_) => EwtRecord::version_range(),
+            CwrRegistry::Ver(_) => VerRecord::version_range(),
+            CwrRegistry::Per(_) => PerRecord::version_range(),
+            CwrRegistry::Npr(_) => NprRecord::version_range(),
+            CwrRegistry::Rec(_) => RecRecord::version_range(),
+            CwrRegistry::Orn(_) => OrnRecord::version_range(),
+            CwrRegistry::Ins(_) => InsRecord::version_range(),
+            CwrRegistry::Ind(_) => IndRecord::version_range(),
+            CwrRegistry::Com(_) => ComRecord::version_range(),
+            CwrRegistry::Msg(_) => MsgRecord::version_range(),
+            CwrRegistry::Net(_) => NetRecord::version_range(),
+            CwrRegistry::Now(_) => NowRecord::version_range(),
+            CwrRegistry::Ari(_) => AriRecord::version_range(),
+            CwrRegistry::Xrf(_) => XrfRecord::version_range(),
+        }
+    }
+
+    /// Whether two records hold the same values, ignoring formatting noise: trailing spaces, all-zero dates
+    /// against blank ones, and the case of code fields (see [`crate::records::FieldKind`])
+    pub fn semantically_eq(&self, other: &CwrRegistry) -> bool {
+        self.semantic_values().eq(other.semantic_values())
+    }
+
+    /// Hashes the record consistently with [`Self::semantically_eq`]
+    pub fn semantic_hash<H: Hasher>(&self, state: &mut H) {
+        for value in self.semantic_values() {
+            value.hash(state);
+        }
+    }
+
+    /// The field values [`Self::semantically_eq`] compares, in field order
+    pub(crate) fn semantic_values(&self) -> impl Iterator<Item = String> + '_ {
+        self.fields().zip(self.field_specs()).map(|(field, spec)| match spec.kind {
+            FieldKind::Text => field.value,
+            FieldKind::Date if field.value.bytes().all(|b| b == b'0') => String::new(),
+            FieldKind::Date => field.value,
+            FieldKind::Code => field.value.to_uppercase(),
+        })
+    }
+
+    /// Metadata about this record's type code
+    pub fn info(&self) -> Option<RecordTypeInfo> {
+        record_type_info(self.record_type())
+    }
+
+    /// Returns an error if this record type does not exist in the given CWR version
+    pub fn check_version(&self, version: f32) -> Result<(), CwrParseError> {
+        let (min_version, max_version) = self.version_range();
+        crate::records::check_record_version(self.record_type(), version, min_version, max_version)
+    }
+
+    /// PR, MR and SR ownership shares of SPU/OPU and SWR/OWR records
+    pub fn ownership_shares(&self) -> Option<crate::domain_types::ShareTotals> {
+        match self {
+            CwrRegistry::Spu(record) => Some(record.ownership_shares()),
+            CwrRegistry::Swr(record) => Some(record.ownership_shares()),
+            _ => None,
+        }
+    }
+
+    /// Returns (transaction sequence #, record sequence #), or None for control records (HDR/GRH/GRT/TRL)
+    pub fn sequence_numbers(&self) -> Option<(u32, u32)> {
+        match self {
+            CwrRegistry::Hdr(_) | CwrRegistry::Grh(_) | CwrRegistry::Grt(_) | CwrRegistry::Trl(_) => None,
+            CwrRegistry::Agr(record) => Some((record.transaction_sequence_num.0, record.record_sequence_num.0)),
+            CwrRegistry::Nwr(record) => Some((record.transaction_sequence_num.0, record.record_sequence_num.0)),
+            CwrRegistry::Ack(record) => Some((record.transaction_sequence_num.0, record.record_sequence_num.0)),
+            CwrRegistry::Ter(record) => Some((record.transaction_sequence_num.0, record.record_sequence_num.0)),
+            CwrRegistry::Ipa(record) => Some((record.transaction_sequence_num.0, record.record_sequence_num.0)),
+            CwrRegistry::Npa(record) => Some((record.transaction_sequence_num.0, record.record_sequence_num.0)),
+            CwrRegistry::Spu(record) => Some((record.transaction_sequence_num.0, record.record_sequence_num.0)),
+            CwrRegistry::Npn(record) => Some((record.transaction_sequence_num.0, record.record_sequence_num.0)),
+            CwrRegistry::Spt(record) => Some((record.transaction_sequence_num.0, record.record_sequence_num.0)),
+            CwrRegistry::Swr(record) => Some((record.transaction_sequence_num.0, record.record_sequence_num.0)),
+            CwrRegistry::Nwn(record) => Some((record.transaction_sequence_num.0, record.record_sequence_num.0)),
+            CwrRegistry::Swt(record) => Some((record.transaction_sequence_num.0, record.record_sequence_num.0)),
+            CwrRegistry::Pwr(record) => Some((record.transaction_sequence_num.0, record.record_sequence_num.0)),
+            CwrRegistry::Alt(record) => Some((record.transaction_sequence_num.0, record.record_sequence_num.0)),
+            CwrRegistry::Nat(record) => Some((record.transaction_sequence_num.0, record.record_sequence_num.0)),
+            CwrRegistry::Ewt(record) => Some((record.transaction_sequence_num.0, record.record_sequence_num.0)),
+            CwrRegistry::Ver(record) => Some((record.transaction_sequence_num.0, record.record_sequence_num.0)),
+            CwrRegistry::Per(record) => Some((record.transaction_sequence_num.0, record.record_sequence_num.0)),
+            CwrRegistry::Npr(record) => Some((record.transaction_sequence_num.0, record.record_sequence_num.0)),
+            CwrRegistry::Rec(record) => Some((record.transaction_sequence_num.0, record.record_sequence_num.0)),
+            CwrRegistry::Orn(record) => Some((record.transaction_sequence_num.0, record.record_sequence_num.0)),
+            CwrRegistry::Ins(record) => Some((record.transaction_sequence_num.0, record.record_sequence_num.0)),
+            CwrRegistry::Ind(record) => Some((record.transaction_sequence_num.0, record.record_sequence_num.0)),
+            CwrRegistry::Com(record) => Some((record.transaction_sequence_num.0, record.record_sequence_num.0)),
+            CwrRegistry::Msg(record) => Some((record.transaction_sequence_num.0, record.record_sequence_num.0)),
+            CwrRegistry::Net(record) => Some((record.transaction_sequence_num.0, record.record_sequence_num.0)),
+            CwrRegistry::Now(record) => Some((record.transaction_sequence_num.0, record.record_sequence_num.0)),
+            CwrRegistry::Ari(record) => Some((record.transaction_sequence_num.0, record.record_sequence_num.0)),
+            CwrRegistry::Xrf(record) => Some((record.transaction_sequence_num.0, record.record_sequence_num.0)),
+        }
+    }
+
+    /// Sets the transaction and record sequence numbers. Control records (HDR/GRH/GRT/TRL) are left unchanged.
+    pub fn set_sequence_numbers(&mut self, transaction_sequence_num: u32, record_sequence_num: u32) {
+        use crate::domain_types::Number;
+        match self {
+            CwrRegistry::Hdr(_) | CwrRegistry::Grh(_) | CwrRegistry::Grt(_) | CwrRegistry::Trl(_) => {}
+            CwrRegistry::Agr(record) => {
+                record.transaction_sequence_num = Number(transaction_sequence_num);
+                record.record_sequence_num = Number(record_sequence_num);
+            }
+            CwrRegistry::Nwr(record) => {
+                record.transaction_sequence_num = Number(transaction_sequence_num);
+                record.record_sequence_num = Number(record_sequence_num);
+            }
+            CwrRegistry::Ack(record) => {
+                record.transaction_sequence_num = Number(transaction_sequence_num);
+                record.record_sequence_num = Number(record_sequence_num);
+            }
+            CwrRegistry::Ter(record) => {
+                record.transaction_sequence_num = Number(transaction_sequence_num);
+                record.record_sequence_num = Number(record_sequence_num);
+            }
+            CwrRegistry::Ipa(record) => {
+                record.transaction_sequence_num = Number(transaction_sequence_num);
+                record.record_sequence_num = Number(record_sequence_num);
+            }
+            CwrRegistry::Npa(record) => {
+                record.transaction_sequence_num = Number(transaction_sequence_num);
+                record.record_sequence_num = Number(record_sequence_num);
+            }
+            CwrRegistry::Spu(record) => {
+                record.transaction_sequence_num = Number(transaction_sequence_num);
+                record.record_sequence_num = Number(record_sequence_num);
+            }
+            CwrRegistry::Npn(record) => {
+                record.transaction_sequence_num = Number(transaction_sequence_num);
+                record.record_sequence_num = Number(record_sequence_num);
+            }
+            CwrRegistry::Spt(record) => {
+                record.transaction_sequence_num = Number(transaction_sequence_num);
+                record.record_sequence_num = Number(record_sequence_num);
+            }
+            CwrRegistry::Swr(record) => {
+                record.transaction_sequence_num = Number(transaction_sequence_num);
+                record.record_sequence_num = Number(record_sequence_num);
+            }
+            CwrRegistry::Nwn(record) => {
+                record.transaction_sequence_num = Number(transaction_sequence_num);
+                record.record_sequence_num = Number(record_sequence_num);
+            }
+            CwrRegistry::Swt(record) => {
+                record.transaction_sequence_num = Number(transaction_sequence_num);
+                record.record_sequence_num = Number(record_sequence_num);
+            }
+            CwrRegistry::Pwr(record) => {
+                record.transaction_sequence_num = Number(transaction_sequence_num);
+                record.record_sequence_num = Number(record_sequence_num);
+            }
+            CwrRegistry::Alt(record) => {
+                record.transaction_sequence_num = Number(transaction_sequence_num);
+                record.record_sequence_num = Number(record_sequence_num);
+            }
+            CwrRegistry::Nat(record) => {
+                record.transaction_sequence_num = Number(transaction_sequence_num);
+                record.record_sequence_num = Number(record_sequence_num);
+            }
+            CwrRegistry::Ewt(record) => {
+                record.transaction_sequence_num = Number(transaction_sequence_num);
+                record.record_sequence_num = Number(record_sequence_num);
+            }
+            CwrRegistry::Ver(record) => {
+                record.transaction_sequence_num = Number(transaction_sequence_num);
+                record.record_sequence_num = Number(record_sequence_num);
+            }
+            CwrRegistry::Per(record) => {
+                record.transaction_sequence_num = Number(transaction_sequence_num);
+                record.record_sequence_num = Number(record_sequence_num);
+            }
+            CwrRegistry::Npr(record) => {
+                record.transaction_sequence_num = Number(transaction_sequence_num);
+                record.record_sequence_num = Number(record_sequence_num);
+            }
+            CwrRegistry::Rec(record) => {
+                record.transaction_sequence_num = Number(transaction_sequence_num);
+                record.record_sequence_num = Number(record_sequence_num);
+            }
+            CwrRegistry::Orn(record) => {
+                record.transaction_sequence_num = Number(transaction_sequence_num);
+                record.record_sequence_num = Number(record_sequence_num);
+            }
+            CwrRegistry::Ins(record) => {
+                record.transaction_sequence_num = Number(transaction_sequence_num);
+                record.record_sequence_num = Number(record_sequence_num);
+            }
+            CwrRegistry::Ind(record) => {
+                record.transaction_sequence_num = Number(transaction_sequence_num);
+                record.record_sequence_num = Number(record_sequence_num);
+            }
+            CwrRegistry::Com(record) => {
+                record.transaction_sequence_num = Number(transaction_sequence_num);
+                record.record_sequence_num = Number(record_sequence_num);
+            }
+            CwrRegistry::Msg(record) => {
+                record.transaction_sequence_num = Number(transaction_sequence_num);
+                record.record_sequence_num = Number(record_sequence_num);
+            }
+            CwrRegistry::Net(record) => {
+                record.transaction_sequence_num = Number(transaction_sequence_num);
+                record.record_sequence_num = Number(record_sequence_num);
+            }
+            CwrRegistry::Now(record) => {
+                record.transaction_sequence_num = Number(transaction_sequence_num);
+                record.record_sequence_num = Number(record_sequence_num);
+            }
+            CwrRegistry::Ari(record) => {
+                record.transaction_sequence_num = Number(transaction_sequence_num);
+                record.record_sequence_num = Number(record_sequence_num);
+            }
+            CwrRegistry::Xrf(record) => {
+                record.transaction_sequence_num = Number(transaction_sequence_num);
+                record.record_sequence_num = Number(record_sequence_num);
+            }
+        }
+    }
 }
 
 use crate::records::CwrRecord;
 
 fn register_record<T: CwrRecord + 'static>(map: &mut ParserMap) {
-    let parser_fn = |line: &str| -> ParseResult {
-        let result = T::from_cwr_line(line)?;
+    let parser_fn = |line: &CwrLine<'_>, version: Option<f32>| -> ParseResult {
+        let result = T::from_cwr_source(line, version)?;
         Ok((result.record.into_registry(), result.warnings))
     };
 
     for &code in T::record_codes() {
-        map.insert(code, parser_fn);
+        map.insert(code, (parser_fn as ParseFunction, T::version_range as VersionRangeFunction));
     }
 }
 
@@ -184,11 +853,28 @@ static RECORD_PARSERS: LazyLock<ParserMap> = LazyLock::new(|| {
 });
 
 pub fn parse_by_record_type(record_type: &str, line: &str) -> ParseResult {
-    let parser_fn = RECORD_PARSERS
-        .get(record_type)
-        .ok_or_else(|| CwrParseError::BadFormat(format!("Unrecognized record type '{}'", record_type)))?;
+    parse_by_record_type_with_version(record_type, line, None)
+}
+
+/// Parses a line using the field layout of the given CWR version
+pub fn parse_by_record_type_with_version(record_type: &str, line: &str, version: Option<f32>) -> ParseResult {
+    parse_line_by_record_type(record_type, &CwrLine::new(line), version)
+}
 
-    parser_fn(line)
+/// Parses a source line (possibly in a non-UTF-8 character set) using the field layout of the given CWR version
+pub fn parse_line_by_record_type(record_type: &str, line: &CwrLine<'_>, version: Option<f32>) -> ParseResult {
+    let (parser_fn, _) = RECORD_PARSERS.get(record_type).ok_or_else(|| {
+        RecordParseError::new(ParseErrorCode::UnknownRecordType, format!("Unrecognized record type '{}'", record_type))
+            .with_columns(0..3)
+            .with_value(record_type)
+    })?;
+
+    parser_fn(line, version)
+}
+
+/// Returns true if `code` is a record type code this library can parse
+pub fn is_record_type_code(code: &str) -> bool {
+    RECORD_PARSERS.contains_key(code)
 }
 
 pub fn get_all_record_type_codes() -> Vec<&'static str> {
@@ -197,10 +883,174 @@ pub fn get_all_record_type_codes() -> Vec<&'static str> {
     codes
 }
 
+/// A record compared and hashed with [`CwrRegistry::semantically_eq`], so sets and maps treat records that only
+/// differ in formatting as the same record
+#[derive(Debug, Clone, Copy)]
+pub struct SemanticRecord<'a>(pub &'a CwrRegistry);
+
+impl PartialEq for SemanticRecord<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.semantically_eq(other.0)
+    }
+}
+
+impl Eq for SemanticRecord<'_> {}
+
+impl Hash for SemanticRecord<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.semantic_hash(state);
+    }
+}
+
+/// Where a record type sits in a transmission
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub enum RecordRole {
+    /// HDR, GRH, GRT and TRL
+    Control,
+    /// Starts a transaction: NWR, REV, ISW, EXC, AGR or ACK
+    TransactionHeader,
+    /// Belongs to the transaction started before it
+    Detail,
+}
+
+/// Metadata about a record type code, from [`record_type_info`]
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize)]
+pub struct RecordTypeInfo {
+    pub code: &'static str,
+    /// Name of the record type in the CWR specification
+    pub description: &'static str,
+    pub role: RecordRole,
+    /// First CWR version containing this record type, if not present in all versions
+    pub min_version: Option<f32>,
+    /// Last CWR version containing this record type, if it was withdrawn
+    pub max_version: Option<f32>,
+    /// Record types this record belongs to and follows, with records of the same parent in between: a
+    /// publisher's territories follow its SPU, a work's publishers follow the work header. Empty for control
+    /// records and transaction headers.
+    pub parents: &'static [&'static str],
+}
+
+impl RecordTypeInfo {
+    pub fn is_transaction_header(&self) -> bool {
+        self.role == RecordRole::TransactionHeader
+    }
+
+    pub fn is_detail(&self) -> bool {
+        self.role == RecordRole::Detail
+    }
+
+    /// Whether the record type exists in the given CWR version
+    pub fn exists_in_version(&self, version: f32) -> bool {
+        self.min_version.is_none_or(|min| version >= min) && self.max_version.is_none_or(|max| version <= max)
+    }
+}
+
+const WORK_HEADERS: &[&str] = &["NWR", "REV", "ISW", "EXC"];
+
+/// Code, description, role and parents of every record type code
+const RECORD_TYPES: &[(&str, &str, RecordRole, &[&str])] = &[
+    ("HDR", "Transmission header", RecordRole::Control, &[]),
+    ("GRH", "Group header", RecordRole::Control, &[]),
+    ("GRT", "Group trailer", RecordRole::Control, &[]),
+    ("TRL", "Transmission trailer", RecordRole::Control, &[]),
+    ("AGR", "Agreement supporting work registration", RecordRole::TransactionHeader, &[]),
+    ("NWR", "New work registration", RecordRole::TransactionHeader, &[]),
+    ("REV", "Revised registration", RecordRole::TransactionHeader, &[]),
+    ("ISW", "Notification of ISWC assigned to a work", RecordRole::TransactionHeader, &[]),
+    ("EXC", "Existing work in conflict with a work registration", RecordRole::TransactionHeader, &[]),
+    ("ACK", "Acknowledgement of transaction", RecordRole::TransactionHeader, &[]),
+    ("TER", "Territory in agreement", RecordRole::Detail, &["AGR"]),
+    ("IPA", "Interested party of agreement", RecordRole::Detail, &["AGR"]),
+    ("NPA", "Non-Roman alphabet agreement party name", RecordRole::Detail, &["IPA"]),
+    ("SPU", "Publisher controlled by submitter", RecordRole::Detail, WORK_HEADERS),
+    ("OPU", "Other publisher", RecordRole::Detail, WORK_HEADERS),
+    ("NPN", "Non-Roman alphabet publisher name", RecordRole::Detail, &["SPU", "OPU"]),
+    ("SPT", "Publisher territory of control", RecordRole::Detail, &["SPU"]),
+    ("OPT", "Publisher non-controlled collection", RecordRole::Detail, &["OPU"]),
+    ("SWR", "Writer controlled by submitter", RecordRole::Detail, WORK_HEADERS),
+    ("OWR", "Other writer", RecordRole::Detail, WORK_HEADERS),
+    ("NWN", "Non-Roman alphabet writer name", RecordRole::Detail, &["SWR", "OWR"]),
+    ("SWT", "Writer territory of control", RecordRole::Detail, &["SWR"]),
+    ("OWT", "Other writer collection", RecordRole::Detail, &["OWR"]),
+    ("PWR", "Publisher for writer", RecordRole::Detail, &["SWR", "OWR"]),
+    ("ALT", "Alternate title", RecordRole::Detail, WORK_HEADERS),
+    ("NAT", "Non-Roman alphabet title", RecordRole::Detail, WORK_HEADERS),
+    ("EWT", "Entire work title for excerpts", RecordRole::Detail, WORK_HEADERS),
+    ("VER", "Original work title for versions", RecordRole::Detail, WORK_HEADERS),
+    ("PER", "Performing artist", RecordRole::Detail, WORK_HEADERS),
+    ("NPR", "Performance data in non-Roman alphabet", RecordRole::Detail, WORK_HEADERS),
+    ("REC", "Recording detail", RecordRole::Detail, WORK_HEADERS),
+    ("ORN", "Work origin", RecordRole::Detail, WORK_HEADERS),
+    ("INS", "Instrumentation summary", RecordRole::Detail, WORK_HEADERS),
+    ("IND", "Instrumentation detail", RecordRole::Detail, WORK_HEADERS),
+    ("COM", "Component", RecordRole::Detail, WORK_HEADERS),
+    ("MSG", "Message", RecordRole::Detail, &["ACK"]),
+    ("NET", "Non-Roman alphabet entire work title for excerpts", RecordRole::Detail, &["EWT"]),
+    ("NCT", "Non-Roman alphabet title for components", RecordRole::Detail, &["COM"]),
+    ("NVT", "Non-Roman alphabet original title for versions", RecordRole::Detail, &["VER"]),
+    ("NOW", "Non-Roman alphabet other writer name", RecordRole::Detail, &["EWT", "VER", "COM"]),
+    ("ARI", "Additional related information", RecordRole::Detail, WORK_HEADERS),
+    ("XRF", "Work ID cross reference", RecordRole::Detail, WORK_HEADERS),
+];
+
+/// Metadata about a record type code this library can parse (e.g. `"REV"` or `"OPT"`)
+pub fn record_type_info(code: &str) -> Option<RecordTypeInfo> {
+    let &(code, description, role, parents) = RECORD_TYPES.iter().find(|(c, ..)| *c == code)?;
+    let (_, version_range) = RECORD_PARSERS.get(code)?;
+    let (min_version, max_version) = version_range();
+    Some(RecordTypeInfo { code, description, role, min_version, max_version, parents })
+}
+
+/// Metadata about every record type code, in transmission order: control records, transaction headers, details
+pub fn all_record_type_info() -> impl Iterator<Item = RecordTypeInfo> {
+    RECORD_TYPES.iter().filter_map(|(code, ..)| record_type_info(code))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_semantically_eq() {
+        let parse = |line: &str| parse_by_record_type(&line[0..3], line).unwrap().0;
+        let alt = parse("ALT0000000100000002ANOTHER TITLE                                               AT  ");
+        let lower = parse("ALT0000000100000002ANOTHER TITLE                                               at");
+        let renamed = parse("ALT0000000100000002Another Title                                               AT");
+        assert!(alt.semantically_eq(&lower));
+        assert!(!alt.semantically_eq(&renamed));
+
+        let agr = |end_date: &str| {
+            let line = "AGR00000001000000011234567890123               AA20231201                N        N                00001                 ";
+            parse(&format!("{}{:<8}{}", &line[..57], end_date, &line[65..]))
+        };
+        assert!(agr("").semantically_eq(&agr("00000000")));
+        assert!(!agr("").semantically_eq(&agr("20241231")));
+
+        let set: std::collections::HashSet<SemanticRecord> =
+            [&alt, &lower, &renamed].into_iter().map(SemanticRecord).collect();
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn test_record_type_info() {
+        assert_eq!(all_record_type_info().count(), get_all_record_type_codes().len());
+        let spt = record_type_info("OPT").unwrap();
+        assert!(spt.is_detail());
+        assert_eq!(spt.parents, ["OPU"]);
+        assert!(record_type_info("REV").unwrap().is_transaction_header());
+        assert_eq!(record_type_info("TRL").unwrap().role, RecordRole::Control);
+
+        let xrf = record_type_info("XRF").unwrap();
+        assert_eq!((xrf.min_version, xrf.max_version), XrfRecord::version_range());
+        assert!(!xrf.exists_in_version(2.1) && xrf.exists_in_version(2.2));
+        assert!(record_type_info("XYZ").is_none());
+
+        // Every parent is itself a known record type
+        for info in all_record_type_info() {
+            assert!(info.parents.iter().all(|parent| record_type_info(parent).is_some()), "{}", info.code);
+        }
+    }
+
     #[test]
     fn test_get_all_record_type_codes() {
         let codes = get_all_record_type_codes();
@@ -236,6 +1086,62 @@ mod tests {
         assert_eq!(record.record_type(), "REV");
     }
 
+    #[test]
+    fn test_write_overlong_field() {
+        use crate::domain_types::{CharacterSet, CwrVersion};
+        use crate::parsing::FieldOverflow;
+
+        let line = "ALT0000000100000002ANOTHER TITLE                                               AT  ";
+        let (mut record, _) = parse_by_record_type("ALT", line).unwrap();
+        if let CwrRegistry::Alt(alt) = &mut record {
+            alt.alternate_title = "X".repeat(65);
+        }
+        let version = CwrVersion(2.2);
+        let expected_len = record.to_cwr_record_bytes(&version, &CharacterSet::ASCII).len();
+        assert_eq!(expected_len, line.len());
+
+        let mut truncations = Vec::new();
+        let bytes = record
+            .write_cwr_record_bytes(&version, &CharacterSet::ASCII, FieldOverflow::Truncate, &mut truncations)
+            .unwrap();
+        assert_eq!(bytes.len(), line.len());
+        assert_eq!(truncations.len(), 1);
+        assert_eq!((truncations[0].field_name, truncations[0].width), ("alternate_title", 60));
+        assert_eq!(truncations[0].truncated, "X".repeat(60));
+
+        let error = record
+            .write_cwr_record_bytes(&version, &CharacterSet::ASCII, FieldOverflow::Error, &mut Vec::new())
+            .unwrap_err();
+        assert!(matches!(error, CwrParseError::FieldTooLong { field_name: "alternate_title", width: 60, .. }));
+    }
+
+    #[test]
+    fn test_describe_lists_fields_with_positions() {
+        let line = "ALT0000000100000002ANOTHER TITLE                                               AT  ";
+        let (record, _) = parse_by_record_type("ALT", line).unwrap();
+        let description = record.describe(2.2);
+
+        let lines: Vec<&str> = description.lines().collect();
+        assert_eq!(lines.len(), 6);
+        assert!(lines[0].starts_with("Always 'ALT'") && lines[0].ends_with("1-3  'ALT'"));
+        assert!(lines[3].starts_with("Alternate title") && lines[3].ends_with("20-79  'ANOTHER TITLE'"));
+        assert!(lines[4].ends_with("80-81  'AT'"));
+    }
+
+    #[test]
+    fn test_fields_yields_name_title_and_value() {
+        let line = "ALT0000000100000002ANOTHER TITLE                                               AT  ";
+        let (record, _) = parse_by_record_type("ALT", line).unwrap();
+        let fields: Vec<FieldValue> = record.fields().collect();
+
+        assert_eq!(fields.len(), record.field_specs().len());
+        assert_eq!(fields[3].name, "alternate_title");
+        assert_eq!(fields[3].title, "Alternate title");
+        assert_eq!(fields[3].value, "ANOTHER TITLE");
+        assert_eq!(fields[1].value, "00000001");
+        assert_eq!(fields[5].value, "");
+    }
+
     #[test]
     fn test_parse_by_record_type_unknown() {
         let line = "UNKSOME_UNKNOWN_RECORD_TYPE";
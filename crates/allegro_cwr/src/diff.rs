@@ -0,0 +1,213 @@
+//! Transaction-level comparison of two CWR files
+//!
+//! Transactions are matched by type and submitter key (work number for registrations and ACKs, agreement number for
+//! AGR), so reordering and renumbering are not reported. Matched transactions are compared record by record with
+//! sequence numbers blanked, ignoring formatting differences (see [`CwrRegistry::semantically_eq`]).
+
+use crate::cwr_registry::CwrRegistry;
+use crate::document::{CwrFile, CwrTransaction};
+use crate::encoding::decode_lossy;
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransactionChange {
+    Added,
+    Removed,
+    /// Records only in the old file and records only in the new file, as CWR lines
+    Changed {
+        removed: Vec<String>,
+        added: Vec<String>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransactionDiff {
+    /// Transaction type and submitter key, e.g. `NWR SW0000000001`
+    pub key: String,
+    pub change: TransactionChange,
+}
+
+/// Differences between two files, in the order of the new file followed by removed transactions
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CwrDiff {
+    pub transactions: Vec<TransactionDiff>,
+}
+
+impl CwrDiff {
+    pub fn is_empty(&self) -> bool {
+        self.transactions.is_empty()
+    }
+
+    fn count(&self, matches: impl Fn(&TransactionChange) -> bool) -> usize {
+        self.transactions.iter().filter(|diff| matches(&diff.change)).count()
+    }
+}
+
+impl fmt::Display for CwrDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for diff in &self.transactions {
+            match &diff.change {
+                TransactionChange::Added => writeln!(f, "+ {}", diff.key)?,
+                TransactionChange::Removed => writeln!(f, "- {}", diff.key)?,
+                TransactionChange::Changed { removed, added } => {
+                    writeln!(f, "~ {}", diff.key)?;
+                    for line in removed {
+                        writeln!(f, "    - {}", line)?;
+                    }
+                    for line in added {
+                        writeln!(f, "    + {}", line)?;
+                    }
+                }
+            }
+        }
+        write!(
+            f,
+            "{} added, {} removed, {} changed",
+            self.count(|change| *change == TransactionChange::Added),
+            self.count(|change| *change == TransactionChange::Removed),
+            self.count(|change| matches!(change, TransactionChange::Changed { .. }))
+        )
+    }
+}
+
+/// Compares the transactions of two files
+pub fn diff_cwr_files(old: &CwrFile, new: &CwrFile) -> CwrDiff {
+    let mut unmatched: HashMap<String, Vec<Vec<RecordLine>>> = HashMap::new();
+    let mut old_keys = Vec::new();
+    for (index, transaction) in old.transactions().enumerate() {
+        let key = transaction_key(transaction, index);
+        unmatched.entry(key.clone()).or_default().push(record_lines(old, transaction));
+        old_keys.push(key);
+    }
+    // Pair duplicate keys in file order
+    for candidates in unmatched.values_mut() {
+        candidates.reverse();
+    }
+
+    let mut diff = CwrDiff::default();
+    for (index, transaction) in new.transactions().enumerate() {
+        let key = transaction_key(transaction, index);
+        let added_lines = record_lines(new, transaction);
+        let change = match unmatched.get_mut(&key).and_then(Vec::pop) {
+            None => Some(TransactionChange::Added),
+            Some(old_lines) => {
+                let removed = lines_missing_from(&old_lines, &added_lines);
+                let added = lines_missing_from(&added_lines, &old_lines);
+                (!removed.is_empty() || !added.is_empty()).then_some(TransactionChange::Changed { removed, added })
+            }
+        };
+        if let Some(change) = change {
+            diff.transactions.push(TransactionDiff { key, change });
+        }
+    }
+    for key in old_keys {
+        if unmatched.get_mut(&key).and_then(Vec::pop).is_some() {
+            diff.transactions.push(TransactionDiff { key, change: TransactionChange::Removed });
+        }
+    }
+    diff
+}
+
+fn transaction_key(transaction: &CwrTransaction, index: usize) -> String {
+    let transaction_type = transaction.transaction_type().unwrap_or_default();
+    let submitter_key = match transaction.header() {
+        Some(CwrRegistry::Agr(agr)) => Some(agr.submitter_agreement_number.trim()),
+        _ => transaction.submitter_work_num(),
+    };
+    match submitter_key.filter(|key| !key.is_empty()) {
+        Some(key) => format!("{} {}", transaction_type, key),
+        None => format!("{} #{}", transaction_type, index + 1),
+    }
+}
+
+/// A record as its CWR line, for display, and the values it is compared by
+struct RecordLine {
+    line: String,
+    values: Vec<String>,
+}
+
+fn record_lines(file: &CwrFile, transaction: &CwrTransaction) -> Vec<RecordLine> {
+    transaction
+        .records
+        .iter()
+        .map(|record| {
+            let mut record = record.clone();
+            record.set_sequence_numbers(0, 0);
+            let bytes = record.to_cwr_record_bytes(&file.version, &file.character_set);
+            let line = decode_lossy(&bytes, &file.character_set).trim_end().to_string();
+            RecordLine { line, values: record.semantic_values().collect() }
+        })
+        .collect()
+}
+
+/// Lines of records in `lines` without a semantically equal record in `other`, counting repeated records
+fn lines_missing_from(lines: &[RecordLine], other: &[RecordLine]) -> Vec<String> {
+    let mut available: HashMap<&[String], usize> = HashMap::new();
+    for record in other {
+        *available.entry(&record.values).or_default() += 1;
+    }
+    lines
+        .iter()
+        .filter(|record| match available.get_mut(record.values.as_slice()) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                false
+            }
+            _ => true,
+        })
+        .map(|record| record.line.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::ParseOptions;
+
+    fn nwr(transaction_seq: u32, title: &str, submitter_work_num: &str) -> String {
+        format!("NWR{:08}00000000{:<60}  {:<14}     SER        Y       ORI", transaction_seq, title, submitter_work_num)
+    }
+
+    fn file(transactions: &[String]) -> CwrFile {
+        let mut lines = vec![
+            "HDRPB285606836WARNER CHAPPELL MUSIC PUBLISHING LTD         01.102022122112541120221221".to_string(),
+            "GRHNWR0000102.100000000000  ".to_string(),
+        ];
+        lines.extend_from_slice(transactions);
+        lines.push("GRT000010000000000000000".to_string());
+        lines.push("TRL000000010000000000000000".to_string());
+        CwrFile::parse_bytes(lines.join("\r\n").as_bytes(), &ParseOptions::default()).unwrap()
+    }
+
+    #[test]
+    fn test_diff_matches_by_work_number() {
+        let old = file(&[nwr(0, "FIRST", "SW1"), nwr(1, "SECOND", "SW2"), nwr(2, "THIRD", "SW3")]);
+        let new = file(&[nwr(0, "THIRD", "SW3"), nwr(1, "SECOND RENAMED", "SW2"), nwr(2, "FOURTH", "SW4")]);
+
+        let diff = diff_cwr_files(&old, &new);
+        let changes: Vec<(&str, &TransactionChange)> =
+            diff.transactions.iter().map(|diff| (diff.key.as_str(), &diff.change)).collect();
+        assert_eq!(changes.len(), 3, "{}", diff);
+        assert!(matches!(changes[0], ("NWR SW2", TransactionChange::Changed { removed, added })
+            if removed[0].contains("SECOND ") && added[0].contains("SECOND RENAMED")));
+        assert_eq!(changes[1], ("NWR SW4", &TransactionChange::Added));
+        assert_eq!(changes[2], ("NWR SW1", &TransactionChange::Removed));
+        assert!(diff.to_string().ends_with("1 added, 1 removed, 1 changed"));
+
+        assert!(diff_cwr_files(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn test_diff_ignores_code_case() {
+        let with_category = |category: &str| {
+            let mut file = file(&[nwr(0, "FIRST", "SW1")]);
+            if let Some(work) = file.transactions_mut().next().and_then(CwrTransaction::work_mut) {
+                work.musical_work_distribution_category.0 = category.to_string();
+            }
+            file
+        };
+        assert!(diff_cwr_files(&with_category("SER"), &with_category("ser")).is_empty());
+        assert!(!diff_cwr_files(&with_category("SER"), &with_category("POP")).is_empty());
+    }
+}
@@ -0,0 +1,413 @@
+//! In-memory model of a whole CWR file, for small to medium files where random access is more convenient than
+//! streaming handlers
+//!
+//! [`CwrFile`] holds the header, the groups with their transactions, and the trailer. [`CwrFile::write`] regenerates
+//! control records and sequence numbers, so transactions can be added, removed or edited freely.
+
+use crate::cwr_registry::CwrRegistry;
+use crate::domain_types::{
+    CharacterSet, CwrVersion, GroupCount, GroupId, PublisherSequenceNumber, PublisherType, RecordCount, ShareTotals,
+    TransactionCount,
+};
+use crate::error::CwrParseError;
+use crate::parser::{ParseOptions, ParsedRecord, process_cwr_bytes_stream};
+use crate::records::{GrhRecord, GrtRecord, HdrRecord, NwrRecord, TrlRecord};
+use crate::transmission_writer::{TransmissionTotals, write_cwr_file};
+use std::io::Write;
+
+/// One transaction: the transaction header record followed by its detail records
+#[derive(Debug, Clone)]
+pub struct CwrTransaction {
+    pub records: Vec<CwrRegistry>,
+}
+
+impl CwrTransaction {
+    /// The transaction header record (NWR, REV, AGR, ACK, ...)
+    pub fn header(&self) -> Option<&CwrRegistry> {
+        self.records.first()
+    }
+
+    pub fn transaction_type(&self) -> Option<&str> {
+        self.header().map(CwrRegistry::record_type)
+    }
+
+    /// The work registration header of NWR, REV, ISW and EXC transactions
+    pub fn work(&self) -> Option<&NwrRecord> {
+        match self.header() {
+            Some(CwrRegistry::Nwr(nwr)) => Some(nwr),
+            _ => None,
+        }
+    }
+
+    pub fn work_mut(&mut self) -> Option<&mut NwrRecord> {
+        match self.records.first_mut() {
+            Some(CwrRegistry::Nwr(nwr)) => Some(nwr),
+            _ => None,
+        }
+    }
+
+    /// The submitter work number of a work registration, or of the work an ACK acknowledges
+    pub fn submitter_work_num(&self) -> Option<&str> {
+        match self.header()? {
+            CwrRegistry::Nwr(nwr) => Some(nwr.submitter_work_num.trim()),
+            CwrRegistry::Ack(ack) => ack.submitter_creation_num.as_deref().map(str::trim),
+            _ => None,
+        }
+    }
+
+    /// Ownership share totals across the transaction's publishers and writers, controlled or not
+    pub fn ownership_share_totals(&self) -> ShareTotals {
+        self.records.iter().filter_map(CwrRegistry::ownership_shares).sum()
+    }
+
+    /// Puts the detail records into specification order and renumbers their record sequence numbers (see
+    /// [`crate::normalize_record_order`]). Returns whether any record moved.
+    pub fn normalize_order(&mut self) -> bool {
+        crate::normalize::normalize_record_order(&mut self.records)
+    }
+
+    /// Detail records of the given record type (e.g. all `SWR` records)
+    pub fn records_of_type<'a>(&'a self, record_type: &'a str) -> impl Iterator<Item = &'a CwrRegistry> + 'a {
+        self.records.iter().filter(move |record| record.record_type() == record_type)
+    }
+}
+
+/// A group: its GRH, its transactions and the GRT read from the file
+#[derive(Debug, Clone)]
+pub struct CwrGroup {
+    pub header: GrhRecord,
+    pub transactions: Vec<CwrTransaction>,
+    /// The GRT as read; [`CwrFile::write`] writes a regenerated one
+    pub trailer: Option<GrtRecord>,
+}
+
+/// A whole CWR file held in memory
+#[derive(Debug, Clone)]
+pub struct CwrFile {
+    pub header: HdrRecord,
+    pub groups: Vec<CwrGroup>,
+    /// The TRL as read; [`CwrFile::write`] writes a regenerated one
+    pub trailer: Option<TrlRecord>,
+    pub version: CwrVersion,
+    pub character_set: CharacterSet,
+}
+
+impl CwrFile {
+    /// Reads a CWR file, failing on the first line that cannot be parsed
+    #[cfg(feature = "fs")]
+    pub fn parse_file(input_filename: &str) -> Result<Self, CwrParseError> {
+        Self::parse_file_with_options(input_filename, &ParseOptions::default())
+    }
+
+    #[cfg(feature = "fs")]
+    pub fn parse_file_with_options(input_filename: &str, options: &ParseOptions) -> Result<Self, CwrParseError> {
+        Self::from_records(crate::parser::process_cwr_stream_with_options(input_filename, options)?)
+    }
+
+    /// Reads a CWR file already held in memory, failing on the first line that cannot be parsed
+    pub fn parse_bytes(bytes: &[u8], options: &ParseOptions) -> Result<Self, CwrParseError> {
+        Self::from_records(process_cwr_bytes_stream(bytes, options)?)
+    }
+
+    fn from_records(records: impl Iterator<Item = Result<ParsedRecord, CwrParseError>>) -> Result<Self, CwrParseError> {
+        let mut file: Option<CwrFile> = None;
+        for result in records {
+            let parsed_record = result?;
+            let line_number = parsed_record.line_number;
+            let record = parsed_record.record;
+
+            let Some(file) = file.as_mut() else {
+                let CwrRegistry::Hdr(header) = record else {
+                    return Err(CwrParseError::BadFormat(format!(
+                        "Line {}: expected an HDR record, found {}",
+                        line_number,
+                        record.record_type()
+                    )));
+                };
+                file = Some(CwrFile {
+                    header,
+                    groups: Vec::new(),
+                    trailer: None,
+                    version: CwrVersion(parsed_record.context.cwr_version),
+                    character_set: parsed_record.context.character_set.unwrap_or(CharacterSet::ASCII),
+                });
+                continue;
+            };
+
+            match record {
+                CwrRegistry::Hdr(_) => {
+                    return Err(CwrParseError::BadFormat(format!("Line {}: second HDR record", line_number)));
+                }
+                CwrRegistry::Grh(header) => {
+                    file.groups.push(CwrGroup { header, transactions: Vec::new(), trailer: None });
+                }
+                CwrRegistry::Grt(trailer) => {
+                    if let Some(group) = file.groups.last_mut() {
+                        group.trailer = Some(trailer);
+                    }
+                }
+                CwrRegistry::Trl(trailer) => file.trailer = Some(trailer),
+                record => {
+                    let Some(group) = file.groups.last_mut() else {
+                        return Err(CwrParseError::BadFormat(format!(
+                            "Line {}: {} record outside of a group",
+                            line_number,
+                            record.record_type()
+                        )));
+                    };
+                    if record.record_type() == group.header.transaction_type.as_str() {
+                        group.transactions.push(CwrTransaction { records: vec![record] });
+                    } else if let Some(transaction) = group.transactions.last_mut() {
+                        transaction.records.push(record);
+                    } else {
+                        return Err(CwrParseError::BadFormat(format!(
+                            "Line {}: {} record before the first transaction header",
+                            line_number,
+                            record.record_type()
+                        )));
+                    }
+                }
+            }
+        }
+        file.ok_or_else(|| CwrParseError::BadFormat("File has no records".to_string()))
+    }
+
+    /// Writes the file with regenerated GRT/TRL records and sequence numbers, in its own version and character set.
+    /// Records whose type does not exist in that version are refused; [`crate::convert_version`] drops them.
+    pub fn write<W: Write>(&self, writer: W) -> Result<TransmissionTotals, CwrParseError> {
+        let records =
+            std::iter::once(CwrRegistry::Hdr(self.header.clone())).chain(self.groups.iter().flat_map(|group| {
+                std::iter::once(CwrRegistry::Grh(group.header.clone()))
+                    .chain(group.transactions.iter().flat_map(|transaction| transaction.records.iter().cloned()))
+            }));
+        write_cwr_file(records, writer, self.version.clone(), self.character_set.clone())
+    }
+
+    /// Puts the detail records of every transaction into specification order, returning how many transactions
+    /// had records out of order
+    pub fn normalize_order(&mut self) -> usize {
+        self.transactions_mut().map(CwrTransaction::normalize_order).filter(|&moved| moved).count()
+    }
+
+    /// All transactions in file order
+    pub fn transactions(&self) -> impl Iterator<Item = &CwrTransaction> {
+        self.groups.iter().flat_map(|group| group.transactions.iter())
+    }
+
+    pub fn transactions_mut(&mut self) -> impl Iterator<Item = &mut CwrTransaction> {
+        self.groups.iter_mut().flat_map(|group| group.transactions.iter_mut())
+    }
+
+    /// The first transaction for the given submitter work number (surrounding spaces are ignored)
+    pub fn find_by_work_number(&self, submitter_work_num: &str) -> Option<&CwrTransaction> {
+        let submitter_work_num = submitter_work_num.trim();
+        self.transactions().find(|transaction| transaction.submitter_work_num() == Some(submitter_work_num))
+    }
+
+    pub fn find_by_work_number_mut(&mut self, submitter_work_num: &str) -> Option<&mut CwrTransaction> {
+        let submitter_work_num = submitter_work_num.trim();
+        self.transactions_mut().find(|transaction| transaction.submitter_work_num() == Some(submitter_work_num))
+    }
+
+    /// Every transaction for the given submitter work number, e.g. an NWR and a later REV of the same work
+    pub fn transactions_for_work_number<'a>(
+        &'a self, submitter_work_num: &'a str,
+    ) -> impl Iterator<Item = &'a CwrTransaction> + 'a {
+        let submitter_work_num = submitter_work_num.trim();
+        self.transactions().filter(move |transaction| transaction.submitter_work_num() == Some(submitter_work_num))
+    }
+
+    /// Renumbers everything after transactions or records were added, removed or reordered: group ids from 1,
+    /// each group's transactions with [`renumber_sequences`], and the GRT and TRL counts (the records are
+    /// created when missing). [`Self::write`] does this on the way out; this keeps the file in memory consistent.
+    pub fn renumber_sequences(&mut self) {
+        let mut transactions = 0;
+        let mut records = 0;
+        for (index, group) in self.groups.iter_mut().enumerate() {
+            let group_id = GroupId(index as u32 + 1);
+            group.header.group_id = group_id.clone();
+            renumber_sequences(&mut group.transactions);
+
+            let group_records: usize = group.transactions.iter().map(|transaction| transaction.records.len()).sum();
+            let trailer = group.trailer.get_or_insert_with(|| GrtRecord {
+                record_type: crate::parsing::intern("GRT"),
+                group_id: group_id.clone(),
+                transaction_count: TransactionCount(0),
+                record_count: RecordCount(0),
+                currency_indicator: None,
+                total_monetary_value: None,
+            });
+            trailer.group_id = group_id;
+            trailer.transaction_count = TransactionCount(group.transactions.len() as u32);
+            trailer.record_count = RecordCount(group_records as u32 + 2);
+            transactions += group.transactions.len();
+            records += group_records + 2;
+        }
+
+        let trailer = self.trailer.get_or_insert_with(|| TrlRecord {
+            record_type: crate::parsing::intern("TRL"),
+            group_count: GroupCount(0),
+            transaction_count: TransactionCount(0),
+            record_count: RecordCount(0),
+        });
+        trailer.group_count = GroupCount(self.groups.len() as u32);
+        trailer.transaction_count = TransactionCount(transactions as u32);
+        trailer.record_count = RecordCount(records as u32 + 2);
+    }
+
+    /// Removes the transactions `keep` returns false for, dropping groups left empty; returns how many were removed
+    pub fn retain_transactions(&mut self, mut keep: impl FnMut(&CwrTransaction) -> bool) -> usize {
+        let before: usize = self.groups.iter().map(|group| group.transactions.len()).sum();
+        for group in &mut self.groups {
+            group.transactions.retain(&mut keep);
+        }
+        self.groups.retain(|group| !group.transactions.is_empty());
+        before - self.groups.iter().map(|group| group.transactions.len()).sum::<usize>()
+    }
+}
+
+/// Renumbers the transactions of one group: transaction sequence numbers from 0, record sequence numbers from 0
+/// within each transaction, and publisher sequence numbers from 1 for each chain of SPU/OPU records (a chain
+/// starts at an original publisher or acquirer). PWR records follow their publisher to its new chain number.
+pub fn renumber_sequences(transactions: &mut [CwrTransaction]) {
+    for (transaction_sequence, transaction) in transactions.iter_mut().enumerate() {
+        renumber_publisher_chains(&mut transaction.records);
+        for (record_sequence, record) in transaction.records.iter_mut().enumerate() {
+            record.set_sequence_numbers(transaction_sequence as u32, record_sequence as u32);
+        }
+    }
+}
+
+fn renumber_publisher_chains(records: &mut [CwrRegistry]) {
+    // Interested party number, old and new chain number of each publisher
+    let mut chains: Vec<(Option<String>, u8, u8)> = Vec::new();
+    let mut chain = 0u8;
+    for record in records.iter_mut() {
+        if let CwrRegistry::Spu(spu) = record {
+            let starts_chain =
+                matches!(spu.publisher_type, None | Some(PublisherType::OriginalPublisher | PublisherType::Acquirer));
+            if chain == 0 || starts_chain {
+                chain = chain.saturating_add(1);
+            }
+            let interested_party_num = spu.interested_party_num.as_deref().map(|ip| ip.trim().to_string());
+            chains.push((interested_party_num, spu.publisher_sequence_num.0, chain));
+            spu.publisher_sequence_num = PublisherSequenceNumber(chain);
+        }
+    }
+
+    for record in records {
+        if let CwrRegistry::Pwr(pwr) = record
+            && let Some(PublisherSequenceNumber(old)) = pwr.publisher_sequence_num
+        {
+            let publisher_ip = pwr.publisher_ip_num.as_deref().map(str::trim);
+            let new = chains
+                .iter()
+                .find(|(ip, _, _)| publisher_ip.is_some() && ip.as_deref() == publisher_ip)
+                .or_else(|| chains.iter().find(|(_, chain, _)| *chain == old))
+                .map(|(_, _, new)| *new);
+            if let Some(new) = new {
+                pwr.publisher_sequence_num = Some(PublisherSequenceNumber(new));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn nwr(transaction_seq: u32, submitter_work_num: &str) -> String {
+        format!(
+            "NWR{:08}00000000{:<60}  {:<14}     SER        Y       ORI",
+            transaction_seq, "TEST WORK TITLE", submitter_work_num
+        )
+    }
+
+    fn sample() -> String {
+        [
+            "HDRPB285606836WARNER CHAPPELL MUSIC PUBLISHING LTD         01.102022122112541120221221".to_string(),
+            "GRHNWR0000102.100000000000  ".to_string(),
+            nwr(0, "SW0000000001"),
+            "ALT0000000000000001ANOTHER TITLE                                               AT  ".to_string(),
+            nwr(1, "SW0000000002"),
+            "GRT000010000000200000005".to_string(),
+            "TRL000000010000000200000007".to_string(),
+        ]
+        .join("\r\n")
+    }
+
+    #[test]
+    fn test_parse_find_and_write() {
+        let mut file = CwrFile::parse_bytes(sample().as_bytes(), &ParseOptions::default()).unwrap();
+        assert_eq!(file.groups.len(), 1);
+        assert_eq!(file.transactions().count(), 2);
+        assert!(file.trailer.is_some());
+
+        let first = file.find_by_work_number(" SW0000000001 ").unwrap();
+        assert_eq!(first.records.len(), 2);
+        assert_eq!(first.records_of_type("ALT").count(), 1);
+        assert!(file.find_by_work_number("SW0000000009").is_none());
+
+        if let Some(work) = file.find_by_work_number_mut("SW0000000002").and_then(CwrTransaction::work_mut) {
+            work.work_title = "RENAMED".to_string();
+        }
+        assert_eq!(file.retain_transactions(|t| t.submitter_work_num() != Some("SW0000000001")), 1);
+
+        let mut output = Vec::new();
+        let totals = file.write(&mut output).unwrap();
+        assert_eq!(totals, TransmissionTotals { groups: 1, transactions: 1, records: 5 });
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert!(lines[2].starts_with("NWR0000000000000000RENAMED"), "{}", lines[2]);
+        assert!(lines[3].starts_with("GRT000010000000100000003"), "{}", lines[3]);
+    }
+
+    #[test]
+    fn test_parse_rejects_orphan_detail() {
+        let content = sample().replace(&nwr(0, "SW0000000001"), "");
+        let result = CwrFile::parse_bytes(content.replacen("\r\n\r\n", "\r\n", 1).as_bytes(), &ParseOptions::default());
+        let error = result.unwrap_err().to_string();
+        assert!(error.contains("ALT record before the first transaction header"), "{}", error);
+    }
+
+    #[test]
+    fn test_renumber_sequences() {
+        let mut file = CwrFile::parse_bytes(sample().as_bytes(), &ParseOptions::default()).unwrap();
+        file.retain_transactions(|t| t.submitter_work_num() != Some("SW0000000001"));
+        let work = crate::builder::WorkBuilder::new("NEW WORK", "SW0000000003")
+            .publisher(
+                crate::builder::PublisherBuilder::new("000000001", "ACME MUSIC")
+                    .ownership(crate::builder::Shares::new(5000, 0, 0)),
+            )
+            .writer(
+                crate::builder::WriterBuilder::new("000000002", "SMITH")
+                    .ownership(crate::builder::Shares::new(5000, 0, 0))
+                    .represented_by("000000001"),
+            );
+        let mut records = work.build(7).unwrap();
+        for record in &mut records {
+            match record {
+                CwrRegistry::Spu(spu) => spu.publisher_sequence_num = PublisherSequenceNumber(4),
+                CwrRegistry::Pwr(pwr) => pwr.publisher_sequence_num = Some(PublisherSequenceNumber(4)),
+                _ => {}
+            }
+        }
+        file.groups[0].transactions.insert(0, CwrTransaction { records });
+        file.renumber_sequences();
+
+        let sequences: Vec<_> =
+            file.transactions().flat_map(|t| &t.records).filter_map(|r| r.sequence_numbers()).collect();
+        assert_eq!(sequences, [(0, 0), (0, 1), (0, 2), (0, 3), (1, 0)]);
+        let new_work = file.find_by_work_number("SW0000000003").unwrap();
+        assert!(new_work.records.iter().all(|record| match record {
+            CwrRegistry::Spu(spu) => spu.publisher_sequence_num.0 == 1,
+            CwrRegistry::Pwr(pwr) => pwr.publisher_sequence_num == Some(PublisherSequenceNumber(1)),
+            _ => true,
+        }));
+
+        let grt = file.groups[0].trailer.as_ref().unwrap();
+        assert_eq!((grt.transaction_count.0, grt.record_count.0), (2, 7));
+        let trl = file.trailer.as_ref().unwrap();
+        assert_eq!((trl.group_count.0, trl.transaction_count.0, trl.record_count.0), (1, 2, 9));
+    }
+}
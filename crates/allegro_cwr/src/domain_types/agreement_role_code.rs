@@ -12,6 +12,8 @@ pub enum AgreementRoleCode {
     #[default]
     Assignor,
     Acquirer,
+    /// Code not defined by the spec, kept as found so it is written back unchanged
+    Unknown(String),
 }
 
 impl AgreementRoleCode {
@@ -19,6 +21,7 @@ impl AgreementRoleCode {
         match self {
             AgreementRoleCode::Assignor => "AS",
             AgreementRoleCode::Acquirer => "AC",
+            AgreementRoleCode::Unknown(code) => code,
         }
     }
 }
@@ -47,10 +50,10 @@ impl CwrFieldParse for AgreementRoleCode {
                         field_title,
                         source_str: Cow::Owned(source.to_string()),
                         level: WarningLevel::Critical,
-                        description: format!("Invalid agreement role code '{}', must be AS or AC", trimmed),
+                        description: format!("Invalid agreement role code '{}', must be AS or AC", trimmed).into(),
                     });
                 }
-                (AgreementRoleCode::Assignor, warnings)
+                (AgreementRoleCode::Unknown(source.trim_end().to_string()), warnings)
             }
         }
     }
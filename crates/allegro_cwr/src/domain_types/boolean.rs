@@ -7,6 +7,8 @@ pub enum Boolean {
     Yes,
     #[default]
     No,
+    /// Code not defined by the spec, kept as found so it is written back unchanged
+    Unknown(String),
 }
 
 impl Boolean {
@@ -14,6 +16,7 @@ impl Boolean {
         match self {
             Boolean::Yes => "Y",
             Boolean::No => "N",
+            Boolean::Unknown(code) => code,
         }
     }
 }
@@ -38,9 +41,9 @@ impl CwrFieldParse for Boolean {
                     field_title,
                     source_str: Cow::Owned(source.to_string()),
                     level: WarningLevel::Warning,
-                    description: format!("Invalid Yes/No value '{}', defaulting to No", trimmed),
+                    description: format!("Invalid Yes/No value '{}'", trimmed).into(),
                 }];
-                (Boolean::No, warnings)
+                (Boolean::Unknown(source.trim_end().to_string()), warnings)
             }
         }
     }
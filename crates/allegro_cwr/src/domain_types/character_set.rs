@@ -8,6 +8,8 @@ use std::borrow::Cow;
 pub enum CharacterSet {
     #[default]
     ASCII,
+    /// ISO-8859-1, the single-byte Latin subset accepted by many societies
+    Latin1,
     TraditionalBig5,
     SimplifiedGb,
     UTF8,
@@ -19,6 +21,7 @@ impl CharacterSet {
     pub fn as_str(&self) -> &str {
         match self {
             CharacterSet::ASCII => "ASCII",
+            CharacterSet::Latin1 => "Latin-1",
             CharacterSet::TraditionalBig5 => "Traditional Big5",
             CharacterSet::SimplifiedGb => "Simplified GB",
             CharacterSet::UTF8 => "UTF-8",
@@ -41,6 +44,7 @@ impl CwrFieldParse for CharacterSet {
         let trimmed = source.trim();
         match trimmed {
             "ASCII" => (CharacterSet::ASCII, vec![]),
+            "Latin-1" | "ISO-8859-1" => (CharacterSet::Latin1, vec![]),
             "Traditional Big5" => (CharacterSet::TraditionalBig5, vec![]),
             "Simplified GB" => (CharacterSet::SimplifiedGb, vec![]),
             "UTF-8" => (CharacterSet::UTF8, vec![]),
@@ -51,9 +55,9 @@ impl CwrFieldParse for CharacterSet {
                     field_title,
                     source_str: Cow::Owned(source.to_string()),
                     level: WarningLevel::Info,
-                    description: format!("Unknown character set '{}', treating as custom", trimmed),
+                    description: format!("Unknown character set '{}', treating as custom", trimmed).into(),
                 }];
-                (CharacterSet::Unknown(trimmed.to_string()), warnings)
+                (CharacterSet::Unknown(source.trim_end().to_string()), warnings)
             }
         }
     }
@@ -33,7 +33,7 @@ impl CwrFieldParse for CompositeComponentCount {
                     field_title,
                     source_str: Cow::Owned(source.to_string()),
                     level: WarningLevel::Warning,
-                    description: format!("Invalid composite component count format: {}", trimmed),
+                    description: format!("Invalid composite component count format: {}", trimmed).into(),
                 }];
                 (CompositeComponentCount(0), warnings)
             }
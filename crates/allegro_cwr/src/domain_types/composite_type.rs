@@ -35,7 +35,7 @@ impl CwrFieldParse for CompositeType {
                 field_title,
                 source_str: Cow::Owned(source.to_string()),
                 level: WarningLevel::Warning,
-                description: format!("Composite Type '{}' not found in lookup table", trimmed),
+                description: format!("Composite Type '{}' not found in lookup table", trimmed).into(),
             });
         }
 
@@ -35,7 +35,7 @@ impl CwrFieldParse for CurrencyCode {
                 field_title,
                 source_str: Cow::Owned(source.to_string()),
                 level: WarningLevel::Warning,
-                description: format!("Currency code '{}' not found in ISO 4217 table", trimmed),
+                description: format!("Currency code '{}' not found in ISO 4217 table", trimmed).into(),
             });
         }
 
@@ -33,7 +33,7 @@ impl CwrFieldParse for CwrRevision {
                     field_title,
                     source_str: Cow::Owned(source.to_string()),
                     level: WarningLevel::Warning,
-                    description: format!("Invalid revision number format: {}", trimmed),
+                    description: format!("Invalid revision number format: {}", trimmed).into(),
                 }];
                 (CwrRevision(0), warnings)
             }
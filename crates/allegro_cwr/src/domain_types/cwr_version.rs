@@ -37,7 +37,7 @@ impl CwrFieldParse for CwrVersion {
                     field_title,
                     source_str: Cow::Owned(source.to_string()),
                     level: WarningLevel::Warning,
-                    description: format!("Invalid version number format: {}", trimmed),
+                    description: format!("Invalid version number format: {}", trimmed).into(),
                 }];
                 (CwrVersion(2.1), warnings)
             }
@@ -34,7 +34,7 @@ impl CwrFieldParse for CwrVersionNumber {
                 field_title,
                 source_str: Cow::Owned(source.to_string()),
                 level: WarningLevel::Warning,
-                description: format!("Invalid version format '{}', expected format like '02.10'", trimmed),
+                description: format!("Invalid version format '{}', expected format like '02.10'", trimmed).into(),
             });
         }
 
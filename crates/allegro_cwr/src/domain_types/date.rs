@@ -25,6 +25,28 @@ impl Date {
     }
 }
 
+#[cfg(feature = "chrono")]
+impl Date {
+    /// Number of days from `self` to `other` (negative if `other` is earlier)
+    pub fn days_until(&self, other: &Date) -> i64 {
+        (other.0 - self.0).num_days()
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl From<NaiveDate> for Date {
+    fn from(date: NaiveDate) -> Self {
+        Date(date)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl From<Date> for NaiveDate {
+    fn from(date: Date) -> Self {
+        date.0
+    }
+}
+
 impl CwrFieldWrite for Date {
     fn to_cwr_field_bytes(&self, width: usize, character_set: &CharacterSet) -> Vec<u8> {
         format_text_to_cwr_bytes(&self.as_str(), width, character_set)
@@ -42,7 +64,7 @@ impl CwrFieldParse for Date {
                 field_title,
                 source_str: Cow::Owned(source.to_string()),
                 level: WarningLevel::Warning,
-                description: format!("Date should be 8 characters YYYYMMDD, got {}", trimmed.len()),
+                description: format!("Date should be 8 characters YYYYMMDD, got {}", trimmed.len()).into(),
             }];
             return (Date(NaiveDate::from_ymd_opt(1900, 1, 1).unwrap()), warnings);
         }
@@ -55,7 +77,7 @@ impl CwrFieldParse for Date {
                     field_title,
                     source_str: Cow::Owned(source.to_string()),
                     level: WarningLevel::Warning,
-                    description: format!("Invalid date format: {}", trimmed),
+                    description: format!("Invalid date format: {}", trimmed).into(),
                 }];
                 (Date(NaiveDate::from_ymd_opt(1900, 1, 1).unwrap()), warnings)
             }
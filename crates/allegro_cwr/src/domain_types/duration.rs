@@ -0,0 +1,186 @@
+//! Duration type for CWR parsing
+
+use crate::domain_types::CharacterSet;
+use crate::parsing::{CwrFieldParse, CwrFieldWrite, CwrWarning, WarningLevel, format_text_to_cwr_bytes};
+use std::borrow::Cow;
+use std::fmt;
+
+/// Duration HHMMSS (up to 99:59:59), held as a number of seconds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, serde::Serialize, serde::Deserialize)]
+pub struct Duration(pub u32);
+
+impl Duration {
+    /// The longest duration an HHMMSS field can hold
+    pub const MAX: Duration = Duration(99 * 3600 + 59 * 60 + 59);
+
+    /// Returns `None` if hours exceed 99 or minutes or seconds exceed 59
+    pub fn from_hms(hours: u32, minutes: u32, seconds: u32) -> Option<Self> {
+        if minutes > 59 || seconds > 59 || hours > 99 {
+            return None;
+        }
+        Some(Duration(hours * 3600 + minutes * 60 + seconds))
+    }
+
+    pub fn total_seconds(&self) -> u32 {
+        self.0
+    }
+
+    pub fn hours(&self) -> u32 {
+        self.0 / 3600
+    }
+
+    pub fn minutes(&self) -> u32 {
+        self.0 / 60 % 60
+    }
+
+    pub fn seconds(&self) -> u32 {
+        self.0 % 60
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn as_str(&self) -> String {
+        format!("{:02}{:02}{:02}", self.hours(), self.minutes(), self.seconds())
+    }
+
+    /// Returns `None` if the sum exceeds [`Duration::MAX`]
+    pub fn checked_add(self, other: Duration) -> Option<Duration> {
+        self.0.checked_add(other.0).filter(|&sum| sum <= Self::MAX.0).map(Duration)
+    }
+
+    pub fn checked_sub(self, other: Duration) -> Option<Duration> {
+        self.0.checked_sub(other.0).map(Duration)
+    }
+
+    /// Adds, capping the result at [`Duration::MAX`]
+    pub fn saturating_add(self, other: Duration) -> Duration {
+        Duration(self.0.saturating_add(other.0).min(Self::MAX.0))
+    }
+}
+
+impl fmt::Display for Duration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02}:{:02}:{:02}", self.hours(), self.minutes(), self.seconds())
+    }
+}
+
+impl std::iter::Sum for Duration {
+    fn sum<I: Iterator<Item = Duration>>(iter: I) -> Self {
+        iter.fold(Duration(0), Duration::saturating_add)
+    }
+}
+
+impl From<Duration> for std::time::Duration {
+    fn from(duration: Duration) -> Self {
+        std::time::Duration::from_secs(duration.0 as u64)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl From<Duration> for chrono::TimeDelta {
+    fn from(duration: Duration) -> Self {
+        chrono::TimeDelta::seconds(duration.0 as i64)
+    }
+}
+
+/// Fails for negative deltas and deltas longer than [`Duration::MAX`]; sub-second precision is dropped
+#[cfg(feature = "chrono")]
+impl TryFrom<chrono::TimeDelta> for Duration {
+    type Error = String;
+
+    fn try_from(delta: chrono::TimeDelta) -> Result<Self, Self::Error> {
+        let seconds = delta.num_seconds();
+        if (0..=Duration::MAX.0 as i64).contains(&seconds) {
+            Ok(Duration(seconds as u32))
+        } else {
+            Err(format!("Duration of {} seconds is outside 000000-995959", seconds))
+        }
+    }
+}
+
+impl CwrFieldWrite for Duration {
+    fn to_cwr_field_bytes(&self, width: usize, character_set: &CharacterSet) -> Vec<u8> {
+        format_text_to_cwr_bytes(&self.as_str(), width, character_set)
+    }
+}
+
+impl CwrFieldParse for Duration {
+    fn parse_cwr_field(
+        source: &str, field_name: &'static str, field_title: &'static str,
+    ) -> (Self, Vec<CwrWarning<'static>>) {
+        let trimmed = source.trim();
+        let warning = |description: String| CwrWarning {
+            field_name,
+            field_title,
+            source_str: Cow::Owned(source.to_string()),
+            level: WarningLevel::Warning,
+            description: description.into(),
+        };
+        if trimmed.len() != 6 || !trimmed.chars().all(|c| c.is_ascii_digit()) {
+            let description = format!("Duration should be 6 digits HHMMSS, got '{}'", trimmed);
+            return (Duration(0), vec![warning(description)]);
+        }
+
+        let part = |range: std::ops::Range<usize>| trimmed.get(range).and_then(|s| s.parse::<u32>().ok()).unwrap_or(0);
+        match Duration::from_hms(part(0..2), part(2..4), part(4..6)) {
+            Some(duration) => (duration, vec![]),
+            None => (
+                Duration(0),
+                vec![warning(format!("Invalid duration, minutes and seconds must be below 60: {}", trimmed))],
+            ),
+        }
+    }
+}
+
+impl CwrFieldParse for Option<Duration> {
+    fn parse_cwr_field(
+        source: &str, field_name: &'static str, field_title: &'static str,
+    ) -> (Self, Vec<CwrWarning<'static>>) {
+        if source.trim().is_empty() {
+            (None, vec![])
+        } else {
+            let (duration, warnings) = Duration::parse_cwr_field(source, field_name, field_title);
+            (Some(duration), warnings)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_duration_parse_write_and_arithmetic() {
+        let (duration, warnings) = Duration::parse_cwr_field("013005", "duration", "Duration");
+        assert!(warnings.is_empty());
+        assert_eq!(duration, Duration::from_hms(1, 30, 5).unwrap());
+        assert_eq!(duration.total_seconds(), 5405);
+        assert_eq!(duration.to_string(), "01:30:05");
+        assert_eq!(duration.to_cwr_field_bytes(6, &CharacterSet::ASCII), b"013005");
+
+        let (long, warnings) = Duration::parse_cwr_field("990000", "duration", "Duration");
+        assert!(warnings.is_empty());
+        assert_eq!(long.hours(), 99);
+        assert_eq!(long.checked_add(Duration::from_hms(1, 0, 0).unwrap()), None);
+        assert_eq!([long, long].into_iter().sum::<Duration>(), Duration::MAX);
+        assert_eq!(duration.checked_sub(Duration(5)), Some(Duration::from_hms(1, 30, 0).unwrap()));
+
+        let (invalid, warnings) = Duration::parse_cwr_field("006100", "duration", "Duration");
+        assert!(invalid.is_zero());
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(Option::<Duration>::parse_cwr_field("      ", "duration", "Duration").0, None);
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_duration_chrono_conversions() {
+        let duration = Duration::from_hms(0, 3, 30).unwrap();
+        let delta: chrono::TimeDelta = duration.into();
+        assert_eq!(delta.num_seconds(), 210);
+        assert_eq!(Duration::try_from(delta), Ok(duration));
+        assert!(Duration::try_from(chrono::TimeDelta::seconds(-1)).is_err());
+        assert!(Duration::try_from(chrono::TimeDelta::hours(100)).is_err());
+    }
+}
@@ -50,7 +50,7 @@ impl CwrFieldParse for Ean {
                 field_title,
                 source_str: Cow::Owned(source.to_string()),
                 level: WarningLevel::Warning,
-                description: format!("EAN '{}' should be exactly 13 digits", trimmed),
+                description: format!("EAN '{}' should be exactly 13 digits", trimmed).into(),
             });
         }
 
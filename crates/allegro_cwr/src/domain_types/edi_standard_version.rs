@@ -33,7 +33,7 @@ impl CwrFieldParse for EdiStandardVersion {
                 field_title,
                 source_str: Cow::Owned(source.to_string()),
                 level: WarningLevel::Critical,
-                description: format!("Invalid EDI standard version '{}', must be '01.10'", trimmed),
+                description: format!("Invalid EDI standard version '{}', must be '01.10'", trimmed).into(),
             }];
             (EdiStandardVersion("01.10".to_string()), warnings)
         }
@@ -44,7 +44,7 @@ impl CwrFieldParse for ExcerptType {
                 field_title,
                 source_str: Cow::Owned(source.to_string()),
                 level: WarningLevel::Warning,
-                description: format!("Excerpt Type code '{}' not found in lookup table", trimmed),
+                description: format!("Excerpt Type code '{}' not found in lookup table", trimmed).into(),
             });
         }
 
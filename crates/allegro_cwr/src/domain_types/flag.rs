@@ -10,6 +10,9 @@ pub enum Flag {
     No,
     #[default]
     Unknown,
+    /// Code not defined by the spec, kept as found so it is written back unchanged. Named `Other` because
+    /// `Unknown` is the spec's own "U" value
+    Other(String),
 }
 
 impl Flag {
@@ -18,6 +21,7 @@ impl Flag {
             Flag::Yes => "Y",
             Flag::No => "N",
             Flag::Unknown => "U",
+            Flag::Other(code) => code,
         }
     }
 }
@@ -43,9 +47,9 @@ impl CwrFieldParse for Flag {
                     field_title,
                     source_str: Cow::Owned(source.to_string()),
                     level: WarningLevel::Warning,
-                    description: format!("Invalid Y/N/U flag value '{}', defaulting to No", trimmed),
+                    description: format!("Invalid Y/N/U flag value '{}'", trimmed).into(),
                 }];
-                (Flag::No, warnings)
+                (Flag::Other(source.trim_end().to_string()), warnings)
             }
         }
     }
@@ -33,7 +33,7 @@ impl CwrFieldParse for GroupCount {
                     field_title,
                     source_str: Cow::Owned(source.to_string()),
                     level: WarningLevel::Warning,
-                    description: format!("Invalid group count format: {}", trimmed),
+                    description: format!("Invalid group count format: {}", trimmed).into(),
                 }];
                 (GroupCount(0), warnings)
             }
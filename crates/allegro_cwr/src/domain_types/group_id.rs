@@ -33,7 +33,7 @@ impl CwrFieldParse for GroupId {
                     field_title,
                     source_str: Cow::Owned(source.to_string()),
                     level: WarningLevel::Warning,
-                    description: format!("Invalid group ID format: {}", trimmed),
+                    description: format!("Invalid group ID format: {}", trimmed).into(),
                 }];
                 (GroupId(0), warnings)
             }
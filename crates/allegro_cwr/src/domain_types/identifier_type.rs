@@ -14,14 +14,17 @@ pub enum IdentifierType {
     Work,
     /// Version identifier
     Version,
+    /// Code not defined by the spec, kept as found so it is written back unchanged
+    Unknown(String),
 }
 
 impl IdentifierType {
-    pub fn as_str(&self) -> &'static str {
+    pub fn as_str(&self) -> &str {
         match self {
             IdentifierType::Title => "T",
             IdentifierType::Work => "W",
             IdentifierType::Version => "V",
+            IdentifierType::Unknown(code) => code,
         }
     }
 
@@ -59,9 +62,10 @@ impl CwrFieldParse for IdentifierType {
                     description: format!(
                         "Identifier Type '{}' not valid. Expected: T (Title), W (Work), V (Version)",
                         trimmed
-                    ),
+                    )
+                    .into(),
                 });
-                (IdentifierType::default(), warnings)
+                (IdentifierType::Unknown(source.trim_end().to_string()), warnings)
             }
         }
     }
@@ -12,6 +12,8 @@ pub enum InclusionExclusionIndicator {
     #[default]
     Included,
     Excluded,
+    /// Code not defined by the spec, kept as found so it is written back unchanged
+    Unknown(String),
 }
 
 impl InclusionExclusionIndicator {
@@ -19,6 +21,7 @@ impl InclusionExclusionIndicator {
         match self {
             InclusionExclusionIndicator::Included => "I",
             InclusionExclusionIndicator::Excluded => "E",
+            InclusionExclusionIndicator::Unknown(code) => code,
         }
     }
 }
@@ -43,9 +46,9 @@ impl CwrFieldParse for InclusionExclusionIndicator {
                     field_title,
                     source_str: Cow::Owned(source.to_string()),
                     level: WarningLevel::Critical,
-                    description: format!("Invalid inclusion/exclusion indicator '{}', must be I or E", trimmed),
+                    description: format!("Invalid inclusion/exclusion indicator '{}', must be I or E", trimmed).into(),
                 }];
-                (InclusionExclusionIndicator::Included, warnings)
+                (InclusionExclusionIndicator::Unknown(source.trim_end().to_string()), warnings)
             }
         }
     }
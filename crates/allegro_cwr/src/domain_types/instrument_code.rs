@@ -24,7 +24,8 @@ impl CwrFieldParse for InstrumentCode {
     fn parse_cwr_field(
         source: &str, field_name: &'static str, field_title: &'static str,
     ) -> (Self, Vec<CwrWarning<'static>>) {
-        use crate::lookups::instrument_codes::is_valid_instrument_code;
+        use crate::lookups::instrument_codes::{is_valid_instrument_code, suggest_instrument_codes};
+        use crate::lookups::suggestion_suffix;
 
         let trimmed = source.trim();
         let mut warnings = vec![];
@@ -35,7 +36,12 @@ impl CwrFieldParse for InstrumentCode {
                 field_title,
                 source_str: Cow::Owned(source.to_string()),
                 level: WarningLevel::Warning,
-                description: format!("Instrument Code '{}' not found in lookup table", trimmed),
+                description: format!(
+                    "Instrument Code '{}' not found in lookup table{}",
+                    trimmed,
+                    suggestion_suffix(&suggest_instrument_codes(trimmed))
+                )
+                .into(),
             });
         }
 
@@ -26,10 +26,12 @@ pub enum IntendedPurpose {
     Theatre,
     /// Video
     Video,
+    /// Code not defined by the spec, kept as found so it is written back unchanged
+    Unknown(String),
 }
 
 impl IntendedPurpose {
-    pub fn as_str(&self) -> &'static str {
+    pub fn as_str(&self) -> &str {
         match self {
             IntendedPurpose::Commercial => "COM",
             IntendedPurpose::Film => "FIL",
@@ -40,6 +42,7 @@ impl IntendedPurpose {
             IntendedPurpose::Television => "TEL",
             IntendedPurpose::Theatre => "THR",
             IntendedPurpose::Video => "VID",
+            IntendedPurpose::Unknown(code) => code,
         }
     }
 
@@ -75,8 +78,8 @@ impl CwrFieldParse for IntendedPurpose {
         match IntendedPurpose::from_str(trimmed) {
             Some(intended_purpose) => (intended_purpose, warnings),
             None => {
-                warnings.push(CwrWarning { field_name, field_title, source_str: Cow::Owned(source.to_string()), level: WarningLevel::Warning, description: format!("Intended Purpose '{}' not found in lookup table. Expected: COM, FIL, GEN, LIB, MUL, RAD, TEL, THR, VID", trimmed) });
-                (IntendedPurpose::default(), warnings)
+                warnings.push(CwrWarning { field_name, field_title, source_str: Cow::Owned(source.to_string()), level: WarningLevel::Warning, description: format!("Intended Purpose '{}' not found in lookup table. Expected: COM, FIL, GEN, LIB, MUL, RAD, TEL, THR, VID", trimmed).into() });
+                (IntendedPurpose::Unknown(source.trim_end().to_string()), warnings)
             }
         }
     }
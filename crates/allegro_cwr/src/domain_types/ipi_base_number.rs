@@ -51,7 +51,8 @@ impl CwrFieldParse for IpiBaseNumber {
                 field_title,
                 source_str: Cow::Owned(source.to_string()),
                 level: WarningLevel::Warning,
-                description: format!("IPI Base Number '{}' should be exactly 13 alphanumeric characters", trimmed),
+                description: format!("IPI Base Number '{}' should be exactly 13 alphanumeric characters", trimmed)
+                    .into(),
             });
         }
 
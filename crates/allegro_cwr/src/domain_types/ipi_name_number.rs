@@ -51,7 +51,7 @@ impl CwrFieldParse for IpiNameNumber {
                 field_title,
                 source_str: Cow::Owned(source.to_string()),
                 level: WarningLevel::Warning,
-                description: format!("IPI Name Number '{}' should be exactly 11 digits", trimmed),
+                description: format!("IPI Name Number '{}' should be exactly 11 digits", trimmed).into(),
             });
         }
 
@@ -56,7 +56,8 @@ impl CwrFieldParse for Isrc {
                 description: format!(
                     "ISRC '{}' should be exactly 12 alphanumeric characters (CCXXXYYNNNNN format)",
                     trimmed
-                ),
+                )
+                .into(),
             });
         }
 
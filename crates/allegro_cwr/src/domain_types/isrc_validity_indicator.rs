@@ -14,14 +14,17 @@ pub enum IsrcValidityIndicator {
     Invalid,
     /// The ISRC is invalid
     IsrcInvalid,
+    /// Code not defined by the spec, kept as found so it is written back unchanged
+    Unknown(String),
 }
 
 impl IsrcValidityIndicator {
-    pub fn as_str(&self) -> &'static str {
+    pub fn as_str(&self) -> &str {
         match self {
             IsrcValidityIndicator::Valid => "Y",
             IsrcValidityIndicator::Invalid => "U",
             IsrcValidityIndicator::IsrcInvalid => "N",
+            IsrcValidityIndicator::Unknown(code) => code,
         }
     }
 
@@ -59,9 +62,10 @@ impl CwrFieldParse for IsrcValidityIndicator {
                     description: format!(
                         "ISRC Validity Indicator '{}' not found in lookup table. Expected: Y, U, N",
                         trimmed
-                    ),
+                    )
+                    .into(),
                 });
-                (IsrcValidityIndicator::default(), warnings)
+                (IsrcValidityIndicator::Unknown(source.trim_end().to_string()), warnings)
             }
         }
     }
@@ -3,15 +3,25 @@
 use crate::domain_types::CharacterSet;
 use crate::parsing::{CwrFieldParse, CwrFieldWrite, CwrWarning, WarningLevel, format_text_to_cwr_bytes};
 use std::borrow::Cow;
+use std::sync::Arc;
 
 /// CIS Language code (2 characters)
 #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, Default)]
-pub struct LanguageCode(pub String);
+pub struct LanguageCode(pub Arc<str>);
 
 impl LanguageCode {
     pub fn as_str(&self) -> &str {
         &self.0
     }
+
+    /// Returns true if the code is in the CIS Language Code table
+    pub fn is_known(&self) -> bool {
+        crate::lookups::language_codes::is_valid_language_code(&self.0)
+    }
+
+    pub fn description(&self) -> Option<&'static str> {
+        crate::lookups::language_codes::get_language_description(&self.0)
+    }
 }
 
 impl std::ops::Deref for LanguageCode {
@@ -34,20 +44,35 @@ impl CwrFieldParse for LanguageCode {
     ) -> (Self, Vec<CwrWarning<'static>>) {
         use crate::lookups::language_codes::is_valid_language_code;
 
-        let trimmed = source.trim().to_uppercase();
+        let trimmed = source.trim();
+        let trimmed = if trimmed.bytes().any(|b| b.is_ascii_lowercase()) {
+            Cow::Owned(trimmed.to_uppercase())
+        } else {
+            Cow::Borrowed(trimmed)
+        };
         let mut warnings = vec![];
 
-        if !is_valid_language_code(&trimmed) {
+        let description: Option<Cow<'static, str>> = if trimmed.is_empty() {
+            Some("Language code is blank".into())
+        } else if trimmed.len() != 2 || !trimmed.chars().all(|c| c.is_ascii_alphabetic()) {
+            Some(format!("Language code '{}' is not a two-letter code", trimmed).into())
+        } else if !is_valid_language_code(&trimmed) {
+            Some(format!("Language code '{}' not found in CIS Language Code table", trimmed).into())
+        } else {
+            None
+        };
+
+        if let Some(description) = description {
             warnings.push(CwrWarning {
                 field_name,
                 field_title,
                 source_str: Cow::Owned(source.to_string()),
                 level: WarningLevel::Warning,
-                description: format!("Language code '{}' not found in CIS Language Code table", trimmed),
+                description,
             });
         }
 
-        (LanguageCode(trimmed), warnings)
+        (LanguageCode(crate::parsing::intern(&trimmed)), warnings)
     }
 }
 
@@ -64,3 +89,44 @@ impl CwrFieldParse for Option<LanguageCode> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_language_code() {
+        let (result, warnings) = LanguageCode::parse_cwr_field("en", "test_field", "Test Field");
+        assert_eq!(result.as_str(), "EN");
+        assert!(warnings.is_empty());
+        assert!(result.is_known());
+        assert_eq!(result.description(), Some("English"));
+    }
+
+    #[test]
+    fn test_unknown_language_code() {
+        let (result, warnings) = LanguageCode::parse_cwr_field("QQ", "test_field", "Test Field");
+        assert_eq!(result.as_str(), "QQ");
+        assert!(!result.is_known());
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].description.contains("not found in CIS Language Code table"));
+    }
+
+    #[test]
+    fn test_malformed_language_code() {
+        let (_, warnings) = LanguageCode::parse_cwr_field("E1", "test_field", "Test Field");
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].description.contains("not a two-letter code"));
+    }
+
+    #[test]
+    fn test_blank_language_code() {
+        let (_, warnings) = LanguageCode::parse_cwr_field("  ", "test_field", "Test Field");
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].description, "Language code is blank");
+
+        let (result, warnings) = Option::<LanguageCode>::parse_cwr_field("  ", "test_field", "Test Field");
+        assert!(result.is_none());
+        assert!(warnings.is_empty());
+    }
+}
@@ -43,7 +43,7 @@ impl CwrFieldParse for LanguageDialect {
                 field_title,
                 source_str: Cow::Owned(source.to_string()),
                 level: WarningLevel::Warning,
-                description: format!("Language dialect code '{}' not found in ISO 639-2 table", trimmed),
+                description: format!("Language dialect code '{}' not found in ISO 639-2 table", trimmed).into(),
             });
         }
 
@@ -44,7 +44,7 @@ impl CwrFieldParse for LyricAdaptation {
                 field_title,
                 source_str: Cow::Owned(source.to_string()),
                 level: WarningLevel::Warning,
-                description: format!("Lyric Adaptation code '{}' not found in lookup table", trimmed),
+                description: format!("Lyric Adaptation code '{}' not found in lookup table", trimmed).into(),
             });
         }
 
@@ -44,7 +44,7 @@ impl CwrFieldParse for MediaType {
                 field_title,
                 source_str: Cow::Owned(source.to_string()),
                 level: WarningLevel::Warning,
-                description: format!("Media Type '{}' not found in lookup table", trimmed),
+                description: format!("Media Type '{}' not found in lookup table", trimmed).into(),
             });
         }
 
@@ -14,14 +14,17 @@ pub enum MessageLevel {
     Group,
     /// Transaction level message
     Transaction,
+    /// Code not defined by the spec, kept as found so it is written back unchanged
+    Unknown(String),
 }
 
 impl MessageLevel {
-    pub fn as_str(&self) -> &'static str {
+    pub fn as_str(&self) -> &str {
         match self {
             MessageLevel::Record => "R",
             MessageLevel::Group => "G",
             MessageLevel::Transaction => "T",
+            MessageLevel::Unknown(code) => code,
         }
     }
 
@@ -59,9 +62,10 @@ impl CwrFieldParse for MessageLevel {
                     description: format!(
                         "Message Level '{}' not valid. Expected: R (Record), G (Group), T (Transaction)",
                         trimmed
-                    ),
+                    )
+                    .into(),
                 });
-                (MessageLevel::default(), warnings)
+                (MessageLevel::Unknown(source.trim_end().to_string()), warnings)
             }
         }
     }
@@ -14,14 +14,17 @@ pub enum MessageType {
     Warning,
     /// Fatal message
     Fatal,
+    /// Code not defined by the spec, kept as found so it is written back unchanged
+    Unknown(String),
 }
 
 impl MessageType {
-    pub fn as_str(&self) -> &'static str {
+    pub fn as_str(&self) -> &str {
         match self {
             MessageType::Error => "E",
             MessageType::Warning => "W",
             MessageType::Fatal => "F",
+            MessageType::Unknown(code) => code,
         }
     }
 
@@ -59,9 +62,10 @@ impl CwrFieldParse for MessageType {
                     description: format!(
                         "Message Type '{}' not valid. Expected: E (Error), W (Warning), F (Fatal)",
                         trimmed
-                    ),
+                    )
+                    .into(),
                 });
-                (MessageType::default(), warnings)
+                (MessageType::Unknown(source.trim_end().to_string()), warnings)
             }
         }
     }
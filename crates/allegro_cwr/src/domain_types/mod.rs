@@ -14,6 +14,7 @@ mod cwr_revision;
 mod cwr_version;
 mod cwr_version_number;
 mod date;
+mod duration;
 mod ean;
 mod edi_standard_version;
 mod excerpt_type;
@@ -83,6 +84,7 @@ pub use cwr_revision::*;
 pub use cwr_version::*;
 pub use cwr_version_number::*;
 pub use date::*;
+pub use duration::*;
 pub use ean::*;
 pub use edi_standard_version::*;
 pub use excerpt_type::*;
@@ -46,7 +46,7 @@ impl CwrFieldParse for MonetaryValue {
                     field_title,
                     source_str: Cow::Owned(source.to_string()),
                     level: WarningLevel::Warning,
-                    description: format!("Invalid monetary value format: {}", trimmed),
+                    description: format!("Invalid monetary value format: {}", trimmed).into(),
                 }];
                 (MonetaryValue(0), warnings)
             }
@@ -44,7 +44,7 @@ impl CwrFieldParse for MusicArrangement {
                 field_title,
                 source_str: Cow::Owned(source.to_string()),
                 level: WarningLevel::Warning,
-                description: format!("Music Arrangement code '{}' not found in lookup table", trimmed),
+                description: format!("Music Arrangement code '{}' not found in lookup table", trimmed).into(),
             });
         }
 
@@ -35,7 +35,8 @@ impl CwrFieldParse for MusicalWorkDistributionCategory {
                 field_title,
                 source_str: Cow::Owned(source.to_string()),
                 level: WarningLevel::Warning,
-                description: format!("Musical Work Distribution Category '{}' not found in lookup table", trimmed),
+                description: format!("Musical Work Distribution Category '{}' not found in lookup table", trimmed)
+                    .into(),
             });
         }
 
@@ -51,7 +51,7 @@ impl CwrFieldParse for Number {
                     field_title,
                     source_str: Cow::Owned(source.to_string()),
                     level: WarningLevel::Warning,
-                    description: format!("Invalid number format: {}", trimmed),
+                    description: format!("Invalid number format: {}", trimmed).into(),
                 }];
                 (Number(0), warnings)
             }
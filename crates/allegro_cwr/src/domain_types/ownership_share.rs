@@ -5,12 +5,24 @@ use crate::parsing::{
     CwrFieldParse, CwrFieldWrite, CwrNumericField, CwrWarning, WarningLevel, format_number, string_to_cwr_bytes,
 };
 use std::borrow::Cow;
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign};
 
-/// Ownership share (0-100.00% represented as 0-10000)
-#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, Default)]
+/// Ownership or collection share in basis points (hundredths of a percent, 10000 = 100.00%)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize, serde::Deserialize, Default)]
 pub struct OwnershipShare(pub u16);
 
 impl OwnershipShare {
+    pub const ZERO: OwnershipShare = OwnershipShare(0);
+    /// 100.00%
+    pub const FULL: OwnershipShare = OwnershipShare(10000);
+
+    /// Rounds to the nearest basis point; `None` outside 0-100%
+    pub fn from_percentage(percentage: f64) -> Option<Self> {
+        (0.0..=100.0).contains(&percentage).then(|| OwnershipShare((percentage * 100.0).round() as u16))
+    }
+
     pub fn as_str(&self) -> String {
         format!("{:05}", self.0)
     }
@@ -18,6 +30,109 @@ impl OwnershipShare {
     pub fn as_percentage(&self) -> f32 {
         self.0 as f32 / 100.0
     }
+
+    pub fn basis_points(&self) -> u16 {
+        self.0
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Exactly 100%
+    pub fn is_full(&self) -> bool {
+        *self == Self::FULL
+    }
+
+    /// More than 100%, which no single share or share total may be
+    pub fn exceeds_full(&self) -> bool {
+        *self > Self::FULL
+    }
+
+    /// The share still unallocated, or `None` if more than 100% is allocated
+    pub fn remainder(&self) -> Option<OwnershipShare> {
+        Self::FULL.0.checked_sub(self.0).map(OwnershipShare)
+    }
+
+    pub fn checked_add(self, other: OwnershipShare) -> Option<OwnershipShare> {
+        self.0.checked_add(other.0).map(OwnershipShare)
+    }
+
+    pub fn checked_sub(self, other: OwnershipShare) -> Option<OwnershipShare> {
+        self.0.checked_sub(other.0).map(OwnershipShare)
+    }
+}
+
+/// Saturates at `u16::MAX`, so a sum over 100% stays detectable with [`OwnershipShare::exceeds_full`]
+impl Add for OwnershipShare {
+    type Output = OwnershipShare;
+
+    fn add(self, other: OwnershipShare) -> OwnershipShare {
+        OwnershipShare(self.0.saturating_add(other.0))
+    }
+}
+
+impl AddAssign for OwnershipShare {
+    fn add_assign(&mut self, other: OwnershipShare) {
+        *self = *self + other;
+    }
+}
+
+impl Sum for OwnershipShare {
+    fn sum<I: Iterator<Item = OwnershipShare>>(iter: I) -> Self {
+        iter.fold(OwnershipShare::ZERO, Add::add)
+    }
+}
+
+impl<'a> Sum<&'a OwnershipShare> for OwnershipShare {
+    fn sum<I: Iterator<Item = &'a OwnershipShare>>(iter: I) -> Self {
+        iter.copied().sum()
+    }
+}
+
+/// Formats as a percentage, e.g. `33.33%`
+impl fmt::Display for OwnershipShare {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{:02}%", self.0 / 100, self.0 % 100)
+    }
+}
+
+/// PR, MR and SR shares of one party, or their totals across a work
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ShareTotals {
+    pub pr: OwnershipShare,
+    pub mr: OwnershipShare,
+    pub sr: OwnershipShare,
+}
+
+impl ShareTotals {
+    pub fn new(pr: Option<OwnershipShare>, mr: Option<OwnershipShare>, sr: Option<OwnershipShare>) -> Self {
+        ShareTotals { pr: pr.unwrap_or_default(), mr: mr.unwrap_or_default(), sr: sr.unwrap_or_default() }
+    }
+
+    /// `("PR", share)`, `("MR", share)` and `("SR", share)`
+    pub fn each(&self) -> [(&'static str, OwnershipShare); 3] {
+        [("PR", self.pr), ("MR", self.mr), ("SR", self.sr)]
+    }
+
+    /// The rights whose total is over 100%
+    pub fn exceeding_full(&self) -> impl Iterator<Item = (&'static str, OwnershipShare)> + use<> {
+        self.each().into_iter().filter(|(_, share)| share.exceeds_full())
+    }
+}
+
+impl Add for ShareTotals {
+    type Output = ShareTotals;
+
+    fn add(self, other: ShareTotals) -> ShareTotals {
+        ShareTotals { pr: self.pr + other.pr, mr: self.mr + other.mr, sr: self.sr + other.sr }
+    }
+}
+
+impl Sum for ShareTotals {
+    fn sum<I: Iterator<Item = ShareTotals>>(iter: I) -> Self {
+        iter.fold(ShareTotals::default(), Add::add)
+    }
 }
 
 impl CwrFieldWrite for OwnershipShare {
@@ -45,7 +160,7 @@ impl CwrFieldParse for OwnershipShare {
                     field_title,
                     source_str: Cow::Owned(source.to_string()),
                     level: WarningLevel::Warning,
-                    description: format!("Ownership share {} exceeds maximum 10000 (100.00%)", num),
+                    description: format!("Ownership share {} exceeds maximum 10000 (100.00%)", num).into(),
                 }];
                 (OwnershipShare(0), warnings)
             }
@@ -55,7 +170,7 @@ impl CwrFieldParse for OwnershipShare {
                     field_title,
                     source_str: Cow::Owned(source.to_string()),
                     level: WarningLevel::Warning,
-                    description: format!("Invalid ownership share format: {}", trimmed),
+                    description: format!("Invalid ownership share format: {}", trimmed).into(),
                 }];
                 (OwnershipShare(0), warnings)
             }
@@ -76,3 +191,31 @@ impl CwrFieldParse for Option<OwnershipShare> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_share_arithmetic_and_formatting() {
+        let third = OwnershipShare(3333);
+        assert_eq!(third.to_string(), "33.33%");
+        assert_eq!(OwnershipShare::from_percentage(33.333), Some(third));
+        assert_eq!(OwnershipShare::from_percentage(100.5), None);
+
+        let total: OwnershipShare = [third, third, OwnershipShare(3334)].iter().sum();
+        assert!(total.is_full());
+        assert_eq!(third.remainder(), Some(OwnershipShare(6667)));
+        assert!((total + OwnershipShare(1)).exceeds_full());
+        assert_eq!(OwnershipShare(u16::MAX) + third, OwnershipShare(u16::MAX));
+
+        let totals: ShareTotals = [
+            ShareTotals::new(Some(OwnershipShare(5000)), None, Some(OwnershipShare(10000))),
+            ShareTotals::new(Some(OwnershipShare(5000)), Some(OwnershipShare(10000)), Some(OwnershipShare(1))),
+        ]
+        .into_iter()
+        .sum();
+        assert!(totals.pr.is_full());
+        assert_eq!(totals.exceeding_full().collect::<Vec<_>>(), [("SR", OwnershipShare(10001))]);
+    }
+}
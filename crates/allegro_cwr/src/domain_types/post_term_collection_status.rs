@@ -13,6 +13,8 @@ pub enum PostTermCollectionStatus {
     None,
     Original,
     Designated,
+    /// Code not defined by the spec, kept as found so it is written back unchanged
+    Unknown(String),
 }
 
 impl PostTermCollectionStatus {
@@ -21,6 +23,7 @@ impl PostTermCollectionStatus {
             PostTermCollectionStatus::None => "N",
             PostTermCollectionStatus::Original => "O",
             PostTermCollectionStatus::Designated => "D",
+            PostTermCollectionStatus::Unknown(code) => code,
         }
     }
 }
@@ -46,9 +49,10 @@ impl CwrFieldParse for PostTermCollectionStatus {
                     field_title,
                     source_str: Cow::Owned(source.to_string()),
                     level: WarningLevel::Critical,
-                    description: format!("Invalid post-term collection status '{}', must be N, O, or D", trimmed),
+                    description: format!("Invalid post-term collection status '{}', must be N, O, or D", trimmed)
+                        .into(),
                 }];
-                (PostTermCollectionStatus::None, warnings)
+                (PostTermCollectionStatus::Unknown(source.trim_end().to_string()), warnings)
             }
         }
     }
@@ -13,6 +13,8 @@ pub enum PriorRoyaltyStatus {
     None,
     Acquired,
     Designated,
+    /// Code not defined by the spec, kept as found so it is written back unchanged
+    Unknown(String),
 }
 
 impl PriorRoyaltyStatus {
@@ -21,6 +23,7 @@ impl PriorRoyaltyStatus {
             PriorRoyaltyStatus::None => "N",
             PriorRoyaltyStatus::Acquired => "A",
             PriorRoyaltyStatus::Designated => "D",
+            PriorRoyaltyStatus::Unknown(code) => code,
         }
     }
 }
@@ -46,9 +49,9 @@ impl CwrFieldParse for PriorRoyaltyStatus {
                     field_title,
                     source_str: Cow::Owned(source.to_string()),
                     level: WarningLevel::Critical,
-                    description: format!("Invalid prior royalty status '{}', must be N, A, or D", trimmed),
+                    description: format!("Invalid prior royalty status '{}', must be N, A, or D", trimmed).into(),
                 }];
-                (PriorRoyaltyStatus::None, warnings)
+                (PriorRoyaltyStatus::Unknown(source.trim_end().to_string()), warnings)
             }
         }
     }
@@ -33,7 +33,7 @@ impl CwrFieldParse for PublisherSequenceNumber {
                     field_title,
                     source_str: Cow::Owned(source.to_string()),
                     level: WarningLevel::Warning,
-                    description: format!("Publisher sequence number {} out of valid range 1-99", num),
+                    description: format!("Publisher sequence number {} out of valid range 1-99", num).into(),
                 }];
                 (PublisherSequenceNumber(1), warnings)
             }
@@ -43,7 +43,7 @@ impl CwrFieldParse for PublisherSequenceNumber {
                     field_title,
                     source_str: Cow::Owned(source.to_string()),
                     level: WarningLevel::Warning,
-                    description: format!("Invalid publisher sequence number format: {}", trimmed),
+                    description: format!("Invalid publisher sequence number format: {}", trimmed).into(),
                 }];
                 (PublisherSequenceNumber(1), warnings)
             }
@@ -16,6 +16,8 @@ pub enum PublisherType {
     OriginalPublisher,
     SubstitutedPublisher,
     SubPublisher,
+    /// Code not defined by the spec, kept as found so it is written back unchanged
+    Unknown(String),
 }
 
 impl PublisherType {
@@ -27,6 +29,7 @@ impl PublisherType {
             PublisherType::OriginalPublisher => "E",
             PublisherType::SubstitutedPublisher => "ES",
             PublisherType::SubPublisher => "SE",
+            PublisherType::Unknown(code) => code,
         }
     }
 }
@@ -44,17 +47,15 @@ impl CwrFieldParse for PublisherType {
         use crate::lookups::publisher_types::is_valid_publisher_type;
 
         let trimmed = source.trim();
-        let default_type = PublisherType::Acquirer;
-
         if !is_valid_publisher_type(trimmed) {
             let warnings = vec![CwrWarning {
                 field_name,
                 field_title,
                 source_str: Cow::Owned(source.to_string()),
                 level: WarningLevel::Warning,
-                description: format!("Invalid publisher type '{}', defaulting to AQ", trimmed),
+                description: format!("Invalid publisher type '{}'", trimmed).into(),
             }];
-            return (default_type, warnings);
+            return (PublisherType::Unknown(source.trim_end().to_string()), warnings);
         }
 
         match trimmed {
@@ -64,7 +65,7 @@ impl CwrFieldParse for PublisherType {
             "E" => (PublisherType::OriginalPublisher, vec![]),
             "ES" => (PublisherType::SubstitutedPublisher, vec![]),
             "SE" => (PublisherType::SubPublisher, vec![]),
-            _ => (default_type, vec![]),
+            _ => (PublisherType::Unknown(source.trim_end().to_string()), vec![]),
         }
     }
 }
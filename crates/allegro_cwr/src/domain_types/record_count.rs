@@ -33,7 +33,7 @@ impl CwrFieldParse for RecordCount {
                     field_title,
                     source_str: Cow::Owned(source.to_string()),
                     level: WarningLevel::Warning,
-                    description: format!("Invalid record count format: {}", trimmed),
+                    description: format!("Invalid record count format: {}", trimmed).into(),
                 }];
                 (RecordCount(0), warnings)
             }
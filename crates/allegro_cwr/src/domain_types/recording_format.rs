@@ -14,6 +14,9 @@ pub enum RecordingFormat {
     Stereo,
     Mono,
     Quadrophonic,
+    /// Code not defined by the spec, kept as found so it is written back unchanged. Named `Other` because
+    /// `Unknown` is the spec's own "U" value
+    Other(String),
 }
 
 impl RecordingFormat {
@@ -23,6 +26,7 @@ impl RecordingFormat {
             RecordingFormat::Stereo => "S",
             RecordingFormat::Mono => "M",
             RecordingFormat::Quadrophonic => "Q",
+            RecordingFormat::Other(code) => code,
         }
     }
 }
@@ -49,9 +53,9 @@ impl CwrFieldParse for RecordingFormat {
                     field_title,
                     source_str: Cow::Owned(source.to_string()),
                     level: WarningLevel::Warning,
-                    description: format!("Invalid recording format '{}', defaulting to Unknown", trimmed),
+                    description: format!("Invalid recording format '{}'", trimmed).into(),
                 }];
-                (RecordingFormat::Unknown, warnings)
+                (RecordingFormat::Other(source.trim_end().to_string()), warnings)
             }
         }
     }
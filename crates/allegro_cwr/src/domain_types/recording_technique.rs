@@ -13,6 +13,9 @@ pub enum RecordingTechnique {
     Unknown,
     Analog,
     Digital,
+    /// Code not defined by the spec, kept as found so it is written back unchanged. Named `Other` because
+    /// `Unknown` is the spec's own "U" value
+    Other(String),
 }
 
 impl RecordingTechnique {
@@ -21,6 +24,7 @@ impl RecordingTechnique {
             RecordingTechnique::Unknown => "U",
             RecordingTechnique::Analog => "A",
             RecordingTechnique::Digital => "D",
+            RecordingTechnique::Other(code) => code,
         }
     }
 }
@@ -46,9 +50,9 @@ impl CwrFieldParse for RecordingTechnique {
                     field_title,
                     source_str: Cow::Owned(source.to_string()),
                     level: WarningLevel::Warning,
-                    description: format!("Invalid recording technique '{}', defaulting to Unknown", trimmed),
+                    description: format!("Invalid recording technique '{}'", trimmed).into(),
                 }];
-                (RecordingTechnique::Unknown, warnings)
+                (RecordingTechnique::Other(source.trim_end().to_string()), warnings)
             }
         }
     }
@@ -4,19 +4,22 @@ use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
 use std::fmt;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
 pub enum SalesManufactureClause {
     #[default]
     Sales,
     Manufacture,
+    /// Code not defined by the spec, kept as found so it is written back unchanged
+    Unknown(String),
 }
 
 impl SalesManufactureClause {
     #[must_use]
-    pub fn as_str(&self) -> &'static str {
+    pub fn as_str(&self) -> &str {
         match self {
             SalesManufactureClause::Sales => "S",
             SalesManufactureClause::Manufacture => "M",
+            SalesManufactureClause::Unknown(code) => code,
         }
     }
 }
@@ -48,11 +51,12 @@ impl CwrFieldParse for SalesManufactureClause {
                     source_str: Cow::Owned(source.to_string()),
                     level: WarningLevel::Warning,
                     description: format!(
-                        "Invalid sales/manufacture clause '{}'. Must be 'S' (Sales) or 'M' (Manufacture), defaulting to 'S'",
+                        "Invalid sales/manufacture clause '{}'. Must be 'S' (Sales) or 'M' (Manufacture)",
                         trimmed
-                    ),
+                    )
+                    .into(),
                 }];
-                (SalesManufactureClause::Sales, warnings)
+                (SalesManufactureClause::Unknown(source.trim_end().to_string()), warnings)
             }
         }
     }
@@ -105,15 +109,15 @@ mod tests {
     #[test]
     fn test_parse_invalid() {
         let (result, warnings) = SalesManufactureClause::parse_cwr_field("X", "test", "test");
-        assert_eq!(result, SalesManufactureClause::Sales);
+        assert_eq!(result, SalesManufactureClause::Unknown("X".to_string()));
         assert_eq!(warnings.len(), 1);
 
         let (result, warnings) = SalesManufactureClause::parse_cwr_field("T", "test", "test");
-        assert_eq!(result, SalesManufactureClause::Sales);
+        assert_eq!(result, SalesManufactureClause::Unknown("T".to_string()));
         assert_eq!(warnings.len(), 1);
 
         let (result, warnings) = SalesManufactureClause::parse_cwr_field("12", "test", "test");
-        assert_eq!(result, SalesManufactureClause::Sales);
+        assert_eq!(result, SalesManufactureClause::Unknown("12".to_string()));
         assert_eq!(warnings.len(), 1);
     }
 
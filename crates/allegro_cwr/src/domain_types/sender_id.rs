@@ -35,7 +35,7 @@ impl CwrFieldParse for SenderId {
                 field_title,
                 source_str: Cow::Owned(source.to_string()),
                 level: WarningLevel::Critical,
-                description: "Sender ID is required".to_string(),
+                description: "Sender ID is required".into(),
             }];
             return (SenderId(String::new()), warnings);
         }
@@ -56,7 +56,8 @@ impl CwrFieldParse for SenderId {
                     description: format!(
                         "Sender ID '{}' not found in society codes table - may be invalid for SO sender type",
                         trimmed
-                    ),
+                    )
+                    .into(),
                 });
             }
         }
@@ -71,7 +72,8 @@ impl CwrFieldParse for SenderId {
                     description: format!(
                         "Sender ID '{}' not found in transmitter codes table - may be a custom code",
                         trimmed
-                    ),
+                    )
+                    .into(),
                 });
             }
         }
@@ -84,7 +86,7 @@ impl CwrFieldParse for SenderId {
                     field_title,
                     source_str: Cow::Owned(source.to_string()),
                     level: WarningLevel::Warning,
-                    description: format!("IPI number '{}' is longer than standard 11 digits", trimmed),
+                    description: format!("IPI number '{}' is longer than standard 11 digits", trimmed).into(),
                 });
             }
         }
@@ -32,7 +32,7 @@ impl CwrFieldParse for SenderName {
                 field_title,
                 source_str: Cow::Owned(source.to_string()),
                 level: WarningLevel::Critical,
-                description: "Sender name is required".to_string(),
+                description: "Sender name is required".into(),
             }];
             return (SenderName(String::new()), warnings);
         }
@@ -45,7 +45,7 @@ impl CwrFieldParse for SenderName {
                 field_title,
                 source_str: Cow::Owned(source.to_string()),
                 level: WarningLevel::Warning,
-                description: format!("Sender name '{}' exceeds maximum length of 45 characters", trimmed),
+                description: format!("Sender name '{}' exceeds maximum length of 45 characters", trimmed).into(),
             });
         }
 
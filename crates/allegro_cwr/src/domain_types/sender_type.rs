@@ -14,6 +14,8 @@ pub enum SenderType {
     AdministrativeAgency,
     /// For IPNN > 9 digits, this contains the leading digits
     NumericPrefix(String),
+    /// Code not defined by the spec, kept as found so it is written back unchanged
+    Unknown(String),
 }
 
 impl SenderType {
@@ -24,6 +26,7 @@ impl SenderType {
             SenderType::Writer => "WR",
             SenderType::AdministrativeAgency => "AA",
             SenderType::NumericPrefix(s) => s,
+            SenderType::Unknown(code) => code,
         }
     }
 }
@@ -56,9 +59,10 @@ impl CwrFieldParse for SenderType {
                     description: format!(
                         "Invalid sender type '{}', must be PB, SO, WR, AA, or 2-digit numeric prefix",
                         trimmed
-                    ),
+                    )
+                    .into(),
                 }];
-                (SenderType::Publisher, warnings)
+                (SenderType::Unknown(source.trim_end().to_string()), warnings)
             }
         }
     }
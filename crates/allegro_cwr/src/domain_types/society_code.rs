@@ -3,10 +3,11 @@
 use crate::domain_types::CharacterSet;
 use crate::parsing::{CwrFieldParse, CwrFieldWrite, CwrWarning, WarningLevel, format_text_to_cwr_bytes};
 use std::borrow::Cow;
+use std::sync::Arc;
 
 /// Society Code (3 characters)
 #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, Default)]
-pub struct SocietyCode(pub String);
+pub struct SocietyCode(pub Arc<str>);
 
 impl SocietyCode {
     pub fn as_str(&self) -> &str {
@@ -43,11 +44,11 @@ impl CwrFieldParse for SocietyCode {
                 field_title,
                 source_str: Cow::Owned(source.to_string()),
                 level: WarningLevel::Warning,
-                description: format!("Society Code '{}' not found in lookup table", trimmed),
+                description: format!("Society Code '{}' not found in lookup table", trimmed).into(),
             });
         }
 
-        (SocietyCode(trimmed.to_string()), warnings)
+        (SocietyCode(crate::parsing::intern(trimmed)), warnings)
     }
 }
 
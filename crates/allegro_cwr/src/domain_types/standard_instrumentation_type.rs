@@ -33,7 +33,10 @@ impl CwrFieldParse for StandardInstrumentationType {
     fn parse_cwr_field(
         source: &str, field_name: &'static str, field_title: &'static str,
     ) -> (Self, Vec<CwrWarning<'static>>) {
-        use crate::lookups::standard_instrumentations::is_valid_standard_instrumentation;
+        use crate::lookups::standard_instrumentations::{
+            is_valid_standard_instrumentation, suggest_standard_instrumentations,
+        };
+        use crate::lookups::suggestion_suffix;
 
         let trimmed = source.trim();
         let mut warnings = vec![];
@@ -44,7 +47,12 @@ impl CwrFieldParse for StandardInstrumentationType {
                 field_title,
                 source_str: Cow::Owned(source.to_string()),
                 level: WarningLevel::Warning,
-                description: format!("Standard Instrumentation Type '{}' not found in lookup table", trimmed),
+                description: format!(
+                    "Standard Instrumentation Type '{}' not found in lookup table{}",
+                    trimmed,
+                    suggestion_suffix(&suggest_standard_instrumentations(trimmed))
+                )
+                .into(),
             });
         }
 
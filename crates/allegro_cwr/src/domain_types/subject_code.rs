@@ -22,10 +22,12 @@ pub enum SubjectCode {
     Gema,
     /// SACEM - The Sacem submitter is controlling the entire work
     Sacem,
+    /// Code not defined by the spec, kept as found so it is written back unchanged
+    Unknown(String),
 }
 
 impl SubjectCode {
-    pub fn as_str(&self) -> &'static str {
+    pub fn as_str(&self) -> &str {
         match self {
             SubjectCode::DirectLicensing => "DL",
             SubjectCode::ShareChange => "SC",
@@ -34,6 +36,7 @@ impl SubjectCode {
             SubjectCode::RequestedWork => "RQ",
             SubjectCode::Gema => "GW",
             SubjectCode::Sacem => "EW",
+            SubjectCode::Unknown(code) => code,
         }
     }
 
@@ -75,9 +78,10 @@ impl CwrFieldParse for SubjectCode {
                     description: format!(
                         "Subject Code '{}' not found in lookup table. Expected: DL, SC, DW, IQ, RQ, GW, EW",
                         trimmed
-                    ),
+                    )
+                    .into(),
                 });
-                (SubjectCode::default(), warnings)
+                (SubjectCode::Unknown(source.trim_end().to_string()), warnings)
             }
         }
     }
@@ -44,7 +44,7 @@ impl CwrFieldParse for TextMusicRelationship {
                 field_title,
                 source_str: Cow::Owned(source.to_string()),
                 level: WarningLevel::Warning,
-                description: format!("Text Music Relationship code '{}' not found in lookup table", trimmed),
+                description: format!("Text Music Relationship code '{}' not found in lookup table", trimmed).into(),
             });
         }
 
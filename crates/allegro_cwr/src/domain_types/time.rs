@@ -24,6 +24,20 @@ impl Time {
     }
 }
 
+#[cfg(feature = "chrono")]
+impl From<NaiveTime> for Time {
+    fn from(time: NaiveTime) -> Self {
+        Time(time)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl From<Time> for NaiveTime {
+    fn from(time: Time) -> Self {
+        time.0
+    }
+}
+
 impl CwrFieldWrite for Time {
     fn to_cwr_field_bytes(&self, width: usize, character_set: &CharacterSet) -> Vec<u8> {
         format_text_to_cwr_bytes(&self.as_str(), width, character_set)
@@ -41,7 +55,7 @@ impl CwrFieldParse for Time {
                 field_title,
                 source_str: Cow::Owned(source.to_string()),
                 level: WarningLevel::Warning,
-                description: format!("Time should be 6 characters HHMMSS, got {}", trimmed.len()),
+                description: format!("Time should be 6 characters HHMMSS, got {}", trimmed.len()).into(),
             }];
             return (Time(NaiveTime::from_hms_opt(0, 0, 0).unwrap()), warnings);
         }
@@ -54,7 +68,7 @@ impl CwrFieldParse for Time {
                     field_title,
                     source_str: Cow::Owned(source.to_string()),
                     level: WarningLevel::Warning,
-                    description: format!("Invalid time format: {}", trimmed),
+                    description: format!("Invalid time format: {}", trimmed).into(),
                 }];
                 (Time(NaiveTime::from_hms_opt(0, 0, 0).unwrap()), warnings)
             }
@@ -38,7 +38,7 @@ impl CwrFieldParse for TisNumericCode {
                         field_title,
                         source_str: Cow::Owned(source.to_string()),
                         level: WarningLevel::Warning,
-                        description: format!("TIS code '{}' not found in territory table", code_str),
+                        description: format!("TIS code '{}' not found in territory table", code_str).into(),
                     });
                 } else if !is_valid_tis_code(num) {
                     warnings.push(CwrWarning {
@@ -46,7 +46,7 @@ impl CwrFieldParse for TisNumericCode {
                         field_title,
                         source_str: Cow::Owned(source.to_string()),
                         level: WarningLevel::Warning,
-                        description: format!("TIS code '{}' is marked as unusable in territory table", code_str),
+                        description: format!("TIS code '{}' is marked as unusable in territory table", code_str).into(),
                     });
                 }
 
@@ -58,7 +58,7 @@ impl CwrFieldParse for TisNumericCode {
                     field_title,
                     source_str: Cow::Owned(source.to_string()),
                     level: WarningLevel::Warning,
-                    description: format!("Invalid TIS numeric code format: {}", trimmed),
+                    description: format!("Invalid TIS numeric code format: {}", trimmed).into(),
                 }];
                 (TisNumericCode(0), warnings)
             }
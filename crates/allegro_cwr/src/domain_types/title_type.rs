@@ -32,6 +32,8 @@ pub enum TitleType {
     OriginalTitleWithNationalCharacters,
     /// An alternatice work title in it's original language, using 'accented ' National characters
     AlternativeTitleWithNationalCharacters,
+    /// Code not defined by the spec, kept as found so it is written back unchanged
+    Unknown(String),
 }
 
 impl TitleType {
@@ -48,6 +50,7 @@ impl TitleType {
             TitleType::ExtraSearchTitle => "ET",
             TitleType::OriginalTitleWithNationalCharacters => "OL",
             TitleType::AlternativeTitleWithNationalCharacters => "AL",
+            TitleType::Unknown(code) => code,
         }
     }
 }
@@ -81,9 +84,9 @@ impl CwrFieldParse for TitleType {
                     field_title,
                     source_str: Cow::Owned(source.to_string()),
                     level: WarningLevel::Warning,
-                    description: format!("Unknown title type '{}', defaulting to OT", trimmed),
+                    description: format!("Unknown title type '{}'", trimmed).into(),
                 }];
-                (TitleType::OriginalTitle, warnings)
+                (TitleType::Unknown(source.trim_end().to_string()), warnings)
             }
         }
     }
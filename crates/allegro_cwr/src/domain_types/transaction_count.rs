@@ -33,7 +33,7 @@ impl CwrFieldParse for TransactionCount {
                     field_title,
                     source_str: Cow::Owned(source.to_string()),
                     level: WarningLevel::Warning,
-                    description: format!("Invalid transaction count format: {}", trimmed),
+                    description: format!("Invalid transaction count format: {}", trimmed).into(),
                 }];
                 (TransactionCount(0), warnings)
             }
@@ -35,7 +35,7 @@ impl CwrFieldParse for TransactionStatus {
                 field_title,
                 source_str: Cow::Owned(source.to_string()),
                 level: WarningLevel::Warning,
-                description: format!("Transaction Status '{}' not found in lookup table", trimmed),
+                description: format!("Transaction Status '{}' not found in lookup table", trimmed).into(),
             });
         }
 
@@ -16,6 +16,8 @@ pub enum TransactionType {
     ACK,
     ISW,
     EXC,
+    /// Code not defined by the spec, kept as found so it is written back unchanged
+    Unknown(String),
 }
 
 impl TransactionType {
@@ -27,6 +29,7 @@ impl TransactionType {
             TransactionType::ACK => "ACK",
             TransactionType::ISW => "ISW",
             TransactionType::EXC => "EXC",
+            TransactionType::Unknown(code) => code,
         }
     }
 }
@@ -58,9 +61,10 @@ impl CwrFieldParse for TransactionType {
                     description: format!(
                         "Invalid transaction type '{}', must be NWR, REV, AGR, ACK, ISW, or EXC",
                         trimmed
-                    ),
+                    )
+                    .into(),
                 }];
-                (TransactionType::NWR, warnings)
+                (TransactionType::Unknown(source.trim_end().to_string()), warnings)
             }
         }
     }
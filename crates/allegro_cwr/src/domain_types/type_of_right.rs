@@ -16,15 +16,18 @@ pub enum TypeOfRight {
     Synchronisation,
     /// All Rights
     All,
+    /// Code not defined by the spec, kept as found so it is written back unchanged
+    Unknown(String),
 }
 
 impl TypeOfRight {
-    pub fn as_str(&self) -> &'static str {
+    pub fn as_str(&self) -> &str {
         match self {
             TypeOfRight::Mechanical => "MEC",
             TypeOfRight::Performing => "PER",
             TypeOfRight::Synchronisation => "SYN",
             TypeOfRight::All => "ALL",
+            TypeOfRight::Unknown(code) => code,
         }
     }
 
@@ -63,9 +66,10 @@ impl CwrFieldParse for TypeOfRight {
                     description: format!(
                         "Type of Right '{}' not found in lookup table. Expected: MEC, PER, SYN",
                         trimmed
-                    ),
+                    )
+                    .into(),
                 });
-                (TypeOfRight::default(), warnings)
+                (TypeOfRight::Unknown(source.trim_end().to_string()), warnings)
             }
         }
     }
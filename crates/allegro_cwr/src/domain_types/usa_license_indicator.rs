@@ -35,7 +35,7 @@ impl CwrFieldParse for UsaLicenseIndicator {
                 field_title,
                 source_str: Cow::Owned(source.to_string()),
                 level: WarningLevel::Warning,
-                description: format!("USA License Indicator '{}' not found in lookup table", trimmed),
+                description: format!("USA License Indicator '{}' not found in lookup table", trimmed).into(),
             });
         }
 
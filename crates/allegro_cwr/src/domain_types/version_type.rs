@@ -35,7 +35,7 @@ impl CwrFieldParse for VersionType {
                 field_title,
                 source_str: Cow::Owned(source.to_string()),
                 level: WarningLevel::Warning,
-                description: format!("Version Type '{}' not found in lookup table", trimmed),
+                description: format!("Version Type '{}' not found in lookup table", trimmed).into(),
             });
         }
 
@@ -33,7 +33,7 @@ impl CwrFieldParse for WorksCount {
                     field_title,
                     source_str: Cow::Owned(source.to_string()),
                     level: WarningLevel::Warning,
-                    description: format!("Invalid works count format: {}", trimmed),
+                    description: format!("Invalid works count format: {}", trimmed).into(),
                 }];
                 (WorksCount(0), warnings)
             }
@@ -35,7 +35,7 @@ impl CwrFieldParse for WriterDesignation {
                 field_title,
                 source_str: Cow::Owned(source.to_string()),
                 level: WarningLevel::Warning,
-                description: format!("Writer Designation '{}' not found in lookup table", trimmed),
+                description: format!("Writer Designation '{}' not found in lookup table", trimmed).into(),
             });
         }
 
@@ -4,19 +4,22 @@ use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
 use std::fmt;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
 pub enum WriterPosition {
     #[default]
     First,
     Second,
+    /// Code not defined by the spec, kept as found so it is written back unchanged
+    Unknown(String),
 }
 
 impl WriterPosition {
     #[must_use]
-    pub fn as_str(&self) -> &'static str {
+    pub fn as_str(&self) -> &str {
         match self {
             WriterPosition::First => "1",
             WriterPosition::Second => "2",
+            WriterPosition::Unknown(code) => code,
         }
     }
 }
@@ -47,12 +50,9 @@ impl CwrFieldParse for WriterPosition {
                     field_title,
                     source_str: Cow::Owned(source.to_string()),
                     level: WarningLevel::Warning,
-                    description: format!(
-                        "Invalid writer position '{}'. Must be '1' or '2', defaulting to '1'",
-                        trimmed
-                    ),
+                    description: format!("Invalid writer position '{}'. Must be '1' or '2'", trimmed).into(),
                 }];
-                (WriterPosition::First, warnings)
+                (WriterPosition::Unknown(source.trim_end().to_string()), warnings)
             }
         }
     }
@@ -105,15 +105,15 @@ mod tests {
     #[test]
     fn test_parse_invalid() {
         let (result, warnings) = WriterPosition::parse_cwr_field("0", "test", "test");
-        assert_eq!(result, WriterPosition::First);
+        assert_eq!(result, WriterPosition::Unknown("0".to_string()));
         assert_eq!(warnings.len(), 1);
 
         let (result, warnings) = WriterPosition::parse_cwr_field("3", "test", "test");
-        assert_eq!(result, WriterPosition::First);
+        assert_eq!(result, WriterPosition::Unknown("3".to_string()));
         assert_eq!(warnings.len(), 1);
 
         let (result, warnings) = WriterPosition::parse_cwr_field("A", "test", "test");
-        assert_eq!(result, WriterPosition::First);
+        assert_eq!(result, WriterPosition::Unknown("A".to_string()));
         assert_eq!(warnings.len(), 1);
     }
 
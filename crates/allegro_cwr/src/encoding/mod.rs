@@ -0,0 +1,216 @@
+//! Encoding and decoding for the CWR character sets
+//!
+//! ASCII, UTF-8 and Unicode text is stored as UTF-8, Latin-1 maps each byte to the code point of the same value,
+//! and Traditional Big5 / Simplified GB use double-byte tables generated by `bin/generate_encoding_tables.py`.
+//! Characters that cannot be represented in the target character set are written as `?`.
+
+use crate::domain_types::CharacterSet;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+const FIRST_LEAD: u8 = 0x81;
+const FIRST_TRAIL: u8 = 0x40;
+const TRAILS_PER_LEAD: usize = 0xFF - FIRST_TRAIL as usize;
+
+static BIG5: DoubleByteTable = DoubleByteTable::new(include_bytes!("big5.bin"));
+static GBK: DoubleByteTable = DoubleByteTable::new(include_bytes!("gbk.bin"));
+
+static BIG5_ENCODER: LazyLock<HashMap<char, [u8; 2]>> = LazyLock::new(|| BIG5.encoder());
+static GBK_ENCODER: LazyLock<HashMap<char, [u8; 2]>> = LazyLock::new(|| GBK.encoder());
+
+/// Byte sequence that is not valid in the character set being decoded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodeError {
+    /// Offset of the first invalid byte
+    pub position: usize,
+}
+
+impl std::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid byte sequence at position {}", self.position)
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// Lead/trail byte table mapping double-byte sequences to BMP code points (0 = unmapped)
+struct DoubleByteTable {
+    data: &'static [u8],
+}
+
+impl DoubleByteTable {
+    const fn new(data: &'static [u8]) -> Self {
+        Self { data }
+    }
+
+    fn decode_pair(&self, lead: u8, trail: u8) -> Option<char> {
+        if lead < FIRST_LEAD || lead == 0xFF || trail < FIRST_TRAIL || trail == 0xFF {
+            return None;
+        }
+        let index = (lead - FIRST_LEAD) as usize * TRAILS_PER_LEAD + (trail - FIRST_TRAIL) as usize;
+        let code_point = u16::from_le_bytes([*self.data.get(index * 2)?, *self.data.get(index * 2 + 1)?]);
+        if code_point == 0 { None } else { char::from_u32(code_point as u32) }
+    }
+
+    /// Builds the reverse mapping; where several byte pairs decode to one character, the lowest pair wins
+    fn encoder(&self) -> HashMap<char, [u8; 2]> {
+        let mut map = HashMap::new();
+        for lead in FIRST_LEAD..0xFF {
+            for trail in FIRST_TRAIL..0xFF {
+                if let Some(ch) = self.decode_pair(lead, trail) {
+                    map.entry(ch).or_insert([lead, trail]);
+                }
+            }
+        }
+        map
+    }
+
+    /// Decodes the bytes, either failing on or replacing (with U+FFFD) invalid sequences
+    fn decode(&self, bytes: &[u8], lossy: bool) -> Result<String, DecodeError> {
+        let mut text = String::with_capacity(bytes.len());
+        let mut i = 0;
+        while i < bytes.len() {
+            let byte = bytes[i];
+            if byte < 0x80 {
+                text.push(byte as char);
+                i += 1;
+                continue;
+            }
+            match bytes.get(i + 1).and_then(|&trail| self.decode_pair(byte, trail)) {
+                Some(ch) => {
+                    text.push(ch);
+                    i += 2;
+                }
+                None if lossy => {
+                    text.push(char::REPLACEMENT_CHARACTER);
+                    i += 1;
+                }
+                None => return Err(DecodeError { position: i }),
+            }
+        }
+        Ok(text)
+    }
+}
+
+fn double_byte_table(
+    character_set: &CharacterSet,
+) -> Option<(&'static DoubleByteTable, &'static HashMap<char, [u8; 2]>)> {
+    match character_set {
+        CharacterSet::TraditionalBig5 => Some((&BIG5, &BIG5_ENCODER)),
+        CharacterSet::SimplifiedGb => Some((&GBK, &GBK_ENCODER)),
+        _ => None,
+    }
+}
+
+/// Returns true when text in this character set is stored as UTF-8, so byte offsets in a decoded line match
+/// the byte offsets in the file
+pub fn is_utf8_compatible(character_set: &CharacterSet) -> bool {
+    !matches!(character_set, CharacterSet::Latin1 | CharacterSet::TraditionalBig5 | CharacterSet::SimplifiedGb)
+}
+
+/// ASCII reads the same in every supported character set, so it can be borrowed instead of decoded
+fn ascii_text(bytes: &[u8]) -> Option<&str> {
+    if bytes.is_ascii() { std::str::from_utf8(bytes).ok() } else { None }
+}
+
+/// Decodes bytes in the given character set, failing on the first invalid byte sequence
+pub fn decode<'a>(bytes: &'a [u8], character_set: &CharacterSet) -> Result<Cow<'a, str>, DecodeError> {
+    if let Some(text) = ascii_text(bytes) {
+        return Ok(Cow::Borrowed(text));
+    }
+    if let Some((table, _)) = double_byte_table(character_set) {
+        return table.decode(bytes, false).map(Cow::Owned);
+    }
+    match character_set {
+        CharacterSet::Latin1 => Ok(Cow::Owned(bytes.iter().map(|&b| b as char).collect())),
+        _ => std::str::from_utf8(bytes).map(Cow::Borrowed).map_err(|e| DecodeError { position: e.valid_up_to() }),
+    }
+}
+
+/// Decodes bytes in the given character set, replacing invalid sequences with U+FFFD
+pub fn decode_lossy<'a>(bytes: &'a [u8], character_set: &CharacterSet) -> Cow<'a, str> {
+    if let Some(text) = ascii_text(bytes) {
+        return Cow::Borrowed(text);
+    }
+    if let Some((table, _)) = double_byte_table(character_set) {
+        return Cow::Owned(table.decode(bytes, true).unwrap_or_default());
+    }
+    match character_set {
+        CharacterSet::Latin1 => Cow::Owned(bytes.iter().map(|&b| b as char).collect()),
+        _ => String::from_utf8_lossy(bytes),
+    }
+}
+
+/// Appends the encoding of `ch` in the given character set to `out`, using `?` for unmappable characters
+pub fn encode_char(ch: char, character_set: &CharacterSet, out: &mut Vec<u8>) {
+    if ch.is_ascii() {
+        out.push(ch as u8);
+        return;
+    }
+    if let Some((_, encoder)) = double_byte_table(character_set) {
+        match encoder.get(&ch) {
+            Some(pair) => out.extend_from_slice(pair),
+            None => out.push(b'?'),
+        }
+        return;
+    }
+    match character_set {
+        CharacterSet::ASCII => out.push(b'?'),
+        CharacterSet::Latin1 => out.push(u8::try_from(ch as u32).unwrap_or(b'?')),
+        _ => {
+            let mut buf = [0u8; 4];
+            out.extend_from_slice(ch.encode_utf8(&mut buf).as_bytes());
+        }
+    }
+}
+
+/// Encodes text in the given character set, using `?` for unmappable characters
+pub fn encode(text: &str, character_set: &CharacterSet) -> Vec<u8> {
+    if text.is_ascii() {
+        return text.as_bytes().to_vec();
+    }
+    let mut out = Vec::with_capacity(text.len());
+    for ch in text.chars() {
+        encode_char(ch, character_set, &mut out);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_big5_round_trip() {
+        let bytes = b"NAT\xA7\x40\xA6\xB1\xAE\x61  ";
+        let text = decode(bytes, &CharacterSet::TraditionalBig5).unwrap();
+        assert_eq!(text, "NAT作曲家  ");
+        assert_eq!(encode(&text, &CharacterSet::TraditionalBig5), bytes);
+    }
+
+    #[test]
+    fn test_gb_round_trip() {
+        let bytes = b"NAT\xD7\xF7\xC7\xFA\xBC\xD2";
+        let text = decode(bytes, &CharacterSet::SimplifiedGb).unwrap();
+        assert_eq!(text, "NAT作曲家");
+        assert_eq!(encode(&text, &CharacterSet::SimplifiedGb), bytes);
+    }
+
+    #[test]
+    fn test_latin1_round_trip() {
+        let bytes = b"M\xFCller caf\xE9";
+        let text = decode(bytes, &CharacterSet::Latin1).unwrap();
+        assert_eq!(text, "Müller café");
+        assert_eq!(encode(&text, &CharacterSet::Latin1), bytes);
+        assert_eq!(encode("作", &CharacterSet::Latin1), b"?");
+    }
+
+    #[test]
+    fn test_invalid_sequences() {
+        assert_eq!(decode(b"AB\xA7", &CharacterSet::TraditionalBig5), Err(DecodeError { position: 2 }));
+        assert_eq!(decode(b"A\xFF", &CharacterSet::UTF8), Err(DecodeError { position: 1 }));
+        assert_eq!(decode_lossy(b"\xA7\x40\xFFA", &CharacterSet::TraditionalBig5), "作\u{FFFD}A");
+        assert_eq!(encode("café", &CharacterSet::ASCII), b"caf?");
+    }
+}
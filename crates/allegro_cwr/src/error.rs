@@ -1,14 +1,158 @@
 use std::io;
+use std::ops::Range;
 
 /// Core parsing errors - focused on parsing issues only
 #[derive(Debug)]
 pub enum CwrParseError {
     Io(io::Error),
     BadFormat(String),
-    NonAsciiInput { line_num: usize, byte_pos: usize, byte_value: u8 },
-    NonAsciiOutput { char: char, position: usize },
-    InvalidHeader { found_bytes: Vec<u8> },
-    BomDetected { bom_type: String },
+    /// A line that could not be parsed into a record, with the location and value at fault
+    Record(Box<RecordParseError>),
+    NonAsciiInput {
+        line_num: usize,
+        byte_pos: usize,
+        byte_value: u8,
+    },
+    NonAsciiOutput {
+        char: char,
+        position: usize,
+    },
+    InvalidHeader {
+        found_bytes: Vec<u8>,
+    },
+    BomDetected {
+        bom_type: String,
+    },
+    UnsupportedRecordVersion {
+        record_type: String,
+        version: f32,
+        min_version: Option<f32>,
+        max_version: Option<f32>,
+    },
+    /// A value longer than its field, refused by [`crate::parsing::FieldOverflow::Error`]
+    FieldTooLong {
+        record_type: String,
+        field_name: &'static str,
+        width: usize,
+        value: String,
+    },
+}
+
+/// Classification of a line that could not be parsed into a record
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ParseErrorCode {
+    /// Blank or whitespace-only line
+    EmptyLine,
+    /// Line too short to hold a record type
+    LineTooShort,
+    /// Record type code not known to the parser
+    UnknownRecordType,
+    /// Bytes that are not valid in the file's character set
+    InvalidEncoding,
+    /// Record type that does not exist in the file's CWR version
+    RecordNotInVersion,
+    /// Field value severe enough to reject the record
+    InvalidFieldValue,
+}
+
+impl ParseErrorCode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ParseErrorCode::EmptyLine => "empty_line",
+            ParseErrorCode::LineTooShort => "line_too_short",
+            ParseErrorCode::UnknownRecordType => "unknown_record_type",
+            ParseErrorCode::InvalidEncoding => "invalid_encoding",
+            ParseErrorCode::RecordNotInVersion => "record_not_in_version",
+            ParseErrorCode::InvalidFieldValue => "invalid_field_value",
+        }
+    }
+}
+
+impl std::fmt::Display for ParseErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Details of a line that could not be parsed into a record
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordParseError {
+    pub code: ParseErrorCode,
+    pub line_number: Option<usize>,
+    pub record_type: Option<String>,
+    pub field_name: Option<&'static str>,
+    /// Zero-based byte range of the offending field or bytes within the line
+    pub columns: Option<Range<usize>>,
+    pub value: Option<String>,
+    pub message: String,
+}
+
+impl RecordParseError {
+    pub fn new(code: ParseErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            line_number: None,
+            record_type: None,
+            field_name: None,
+            columns: None,
+            value: None,
+            message: message.into(),
+        }
+    }
+
+    pub fn with_line_number(mut self, line_number: usize) -> Self {
+        self.line_number = Some(line_number);
+        self
+    }
+
+    pub fn with_record_type(mut self, record_type: impl Into<String>) -> Self {
+        self.record_type = Some(record_type.into());
+        self
+    }
+
+    pub fn with_field(mut self, field_name: &'static str, columns: Range<usize>) -> Self {
+        self.field_name = Some(field_name);
+        self.columns = Some(columns);
+        self
+    }
+
+    pub fn with_columns(mut self, columns: Range<usize>) -> Self {
+        self.columns = Some(columns);
+        self
+    }
+
+    pub fn with_value(mut self, value: impl Into<String>) -> Self {
+        self.value = Some(value.into());
+        self
+    }
+}
+
+impl std::fmt::Display for RecordParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for RecordParseError {}
+
+impl From<RecordParseError> for CwrParseError {
+    fn from(err: RecordParseError) -> CwrParseError {
+        CwrParseError::Record(Box::new(err))
+    }
+}
+
+impl CwrParseError {
+    /// The structured details when this error concerns a single line
+    pub fn record_error(&self) -> Option<&RecordParseError> {
+        match self {
+            CwrParseError::Record(err) => Some(err),
+            _ => None,
+        }
+    }
+
+    pub fn code(&self) -> Option<ParseErrorCode> {
+        self.record_error().map(|err| err.code)
+    }
 }
 
 #[derive(Debug)]
@@ -28,6 +172,7 @@ impl std::fmt::Display for CwrParseError {
         match self {
             CwrParseError::Io(err) => write!(f, "IO Error: {}", err),
             CwrParseError::BadFormat(msg) => write!(f, "{}", msg),
+            CwrParseError::Record(err) => write!(f, "{}", err),
             CwrParseError::NonAsciiInput { line_num, byte_pos, byte_value } => {
                 write!(f, "Non-ASCII byte 0x{:02X} at line {}, position {}", byte_value, line_num, byte_pos)
             }
@@ -40,6 +185,22 @@ impl std::fmt::Display for CwrParseError {
             CwrParseError::BomDetected { bom_type } => {
                 write!(f, "BOM detected: {} (CWR files should be ASCII only)", bom_type)
             }
+            CwrParseError::UnsupportedRecordVersion { record_type, version, min_version, max_version } => {
+                write!(f, "{} record is not valid in CWR version {:.1}", record_type, version)?;
+                match (min_version, max_version) {
+                    (Some(min), Some(max)) => write!(f, " (valid in versions {:.1} to {:.1})", min, max),
+                    (Some(min), None) => write!(f, " (requires version {:.1} or later)", min),
+                    (None, Some(max)) => write!(f, " (valid up to version {:.1})", max),
+                    (None, None) => Ok(()),
+                }
+            }
+            CwrParseError::FieldTooLong { record_type, field_name, width, value } => {
+                write!(
+                    f,
+                    "{} {}: '{}' is longer than the field width of {} bytes",
+                    record_type, field_name, value, width
+                )
+            }
         }
     }
 }
@@ -49,10 +210,13 @@ impl std::error::Error for CwrParseError {
         match self {
             CwrParseError::Io(err) => Some(err),
             CwrParseError::BadFormat(_)
+            | CwrParseError::Record(_)
             | CwrParseError::NonAsciiInput { .. }
             | CwrParseError::NonAsciiOutput { .. }
             | CwrParseError::InvalidHeader { .. }
-            | CwrParseError::BomDetected { .. } => None,
+            | CwrParseError::BomDetected { .. }
+            | CwrParseError::UnsupportedRecordVersion { .. }
+            | CwrParseError::FieldTooLong { .. } => None,
         }
     }
 }
@@ -0,0 +1,181 @@
+//! Filtering CWR files down to a subset of transactions
+//!
+//! Kept transactions are rewritten with regenerated GRH/GRT/TRL control records and renumbered sequence numbers,
+//! so the output is a structurally valid transmission. Groups left without transactions are dropped.
+
+use crate::cwr_registry::CwrRegistry;
+use crate::domain_types::{CharacterSet, CwrVersion};
+use crate::error::CwrParseError;
+use crate::parser::process_cwr_stream_with_version;
+use crate::records::GrhRecord;
+use crate::transmission_writer::TransmissionWriter;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use tracing::info;
+
+/// Counts reported after filtering a file
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FilterSummary {
+    pub transactions_read: usize,
+    pub transactions_kept: usize,
+}
+
+/// Writes the transactions of `input_filename` for which `predicate` returns true to `output_filename`.
+/// The predicate receives all records of a transaction, starting with the transaction header record.
+pub fn filter_cwr_file<P>(
+    input_filename: &str, predicate: P, output_filename: &str,
+) -> Result<FilterSummary, CwrParseError>
+where
+    P: FnMut(&[CwrRegistry]) -> bool,
+{
+    let output = BufWriter::new(File::create(output_filename)?);
+    filter_cwr_file_to_writer(input_filename, None, predicate, output)
+}
+
+/// Writes the transactions of `input_filename` for which `predicate` returns true to `output`
+pub fn filter_cwr_file_to_writer<P, W>(
+    input_filename: &str, version_hint: Option<f32>, mut predicate: P, output: W,
+) -> Result<FilterSummary, CwrParseError>
+where
+    P: FnMut(&[CwrRegistry]) -> bool,
+    W: Write,
+{
+    rewrite_transactions(input_filename, version_hint, |_, _, records| predicate(records), output)
+}
+
+/// Writes the transactions of `input_filename` for which `select` returns true to `output`. `select` receives the
+/// source group ID, a copy of the group header and the transaction's records, and may modify the latter two
+/// before they are written.
+pub(crate) fn rewrite_transactions<S, W>(
+    input_filename: &str, version_hint: Option<f32>, mut select: S, output: W,
+) -> Result<FilterSummary, CwrParseError>
+where
+    S: FnMut(u32, &mut GrhRecord, &mut Vec<CwrRegistry>) -> bool,
+    W: Write,
+{
+    let mut summary = FilterSummary::default();
+    let mut output = Some(output);
+    let mut writer: Option<TransmissionWriter<W>> = None;
+    let mut source_group: Option<(u32, GrhRecord)> = None;
+    let mut transaction: Vec<CwrRegistry> = Vec::new();
+    let mut transaction_group: Option<(u32, GrhRecord)> = None;
+
+    for result in process_cwr_stream_with_version(input_filename, version_hint)? {
+        let parsed_record = result?;
+
+        match parsed_record.record {
+            CwrRegistry::Hdr(record) => {
+                let version = CwrVersion(parsed_record.context.cwr_version);
+                let character_set = parsed_record.context.character_set.clone().unwrap_or(CharacterSet::ASCII);
+                if let Some(output) = output.take() {
+                    writer = Some(TransmissionWriter::start(output, &record, version, character_set)?);
+                }
+            }
+            CwrRegistry::Grh(record) => source_group = Some((record.group_id.0, record)),
+            // Control totals are regenerated for the filtered output
+            CwrRegistry::Grt(_) | CwrRegistry::Trl(_) => {}
+            record => {
+                let Some((source_group_id, grh)) = source_group.as_ref() else {
+                    return Err(CwrParseError::BadFormat(format!(
+                        "Line {}: {} record outside of a group",
+                        parsed_record.line_number,
+                        record.record_type()
+                    )));
+                };
+
+                if record.record_type() == grh.transaction_type.as_str() {
+                    if let (Some(writer), Some((group_id, group_grh))) = (writer.as_mut(), transaction_group.take()) {
+                        flush_transaction(writer, &mut transaction, group_id, &group_grh, &mut select, &mut summary)?;
+                    }
+                    transaction_group = Some((*source_group_id, grh.clone()));
+                } else if transaction_group.is_none() {
+                    return Err(CwrParseError::BadFormat(format!(
+                        "Line {}: {} record before the first transaction header",
+                        parsed_record.line_number,
+                        record.record_type()
+                    )));
+                }
+                transaction.push(record);
+            }
+        }
+    }
+
+    let Some(mut writer) = writer else {
+        return Err(CwrParseError::BadFormat("File has no HDR record".to_string()));
+    };
+    if let Some((group_id, group_grh)) = transaction_group.take() {
+        flush_transaction(&mut writer, &mut transaction, group_id, &group_grh, &mut select, &mut summary)?;
+    }
+    writer.finish()?;
+
+    info!(
+        file = input_filename,
+        "Kept {} of {} transactions from '{}'", summary.transactions_kept, summary.transactions_read, input_filename
+    );
+    Ok(summary)
+}
+
+fn flush_transaction<S, W>(
+    writer: &mut TransmissionWriter<W>, transaction: &mut Vec<CwrRegistry>, source_group_id: u32, grh: &GrhRecord,
+    select: &mut S, summary: &mut FilterSummary,
+) -> Result<(), CwrParseError>
+where
+    S: FnMut(u32, &mut GrhRecord, &mut Vec<CwrRegistry>) -> bool,
+    W: Write,
+{
+    summary.transactions_read += 1;
+    let mut grh = grh.clone();
+    if select(source_group_id, &mut grh, transaction) {
+        summary.transactions_kept += 1;
+        writer.start_transaction(source_group_id, &grh)?;
+        for mut record in transaction.drain(..) {
+            writer.write_detail(&mut record)?;
+        }
+    }
+    transaction.clear();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HDR: &str = "HDRPB123456789BMI MUSIC                                    01.1020050101120000200501010              2.2  1DEV MUSIC SOF";
+    const AGR: &str = "AGR00000001000000011234567890123               AA20231201                N        N                00001                ";
+
+    #[test]
+    fn test_filter_transactions() {
+        let mut lines = vec![HDR.to_string(), "GRHAGR0000102.20            ".to_string()];
+        for transaction in 0..3 {
+            let agreement_number = format!("AGREEMENT{:05}", transaction);
+            lines.push(format!("AGR{:08}00000000{}{}", transaction, agreement_number, &AGR[33..]));
+            lines.push(format!("TER{:08}00000001I2840", transaction));
+        }
+        lines.push("GRT000010000000300000008".to_string());
+        lines.push("TRL000010000000300000010".to_string());
+
+        let input = std::env::temp_dir().join(format!("filter_test_{:?}.V22", std::thread::current().id()));
+        std::fs::write(&input, lines.join("\r\n")).unwrap();
+
+        let mut output = Vec::new();
+        let result = filter_cwr_file_to_writer(
+            input.to_str().unwrap(),
+            None,
+            |records| match records.first() {
+                Some(CwrRegistry::Agr(agr)) => agr.submitter_agreement_number.ends_with('2'),
+                _ => false,
+            },
+            &mut output,
+        );
+        std::fs::remove_file(&input).ok();
+
+        assert_eq!(result.unwrap(), FilterSummary { transactions_read: 3, transactions_kept: 1 });
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 6);
+        assert!(lines[2].starts_with("AGR0000000000000000AGREEMENT00002"));
+        assert!(lines[3].starts_with("TER0000000000000001"));
+        assert!(lines[4].starts_with("GRT000010000000100000004"));
+        assert!(lines[5].starts_with("TRL000010000000100000006"));
+    }
+}
@@ -0,0 +1,411 @@
+//! Generating spec-valid records for property tests and fuzzing, behind the `arbitrary` feature
+//!
+//! Every record type and [`CwrRegistry`] implement [`arbitrary::Arbitrary`]. A record is generated the way a file
+//! would supply it: each field gets text that its type parses without warnings, laid out at the newest CWR version
+//! the record exists in, and the line is parsed with the record's own parser. Combinations the record's validator
+//! rejects are generated again, so a generated record round-trips through serialization without warnings.
+
+use crate::cwr_registry::CwrRegistry;
+use crate::domain_types::*;
+use crate::lookups;
+use crate::records::*;
+use arbitrary::{Arbitrary, Unstructured};
+
+/// Valid text for a field of this type
+pub trait ArbitraryField {
+    /// Text of at most `width` characters that parses as this type without warnings
+    fn arbitrary_text(u: &mut Unstructured<'_>, width: usize) -> arbitrary::Result<String>;
+}
+
+/// Generates the text of one field, given its width
+pub type FieldGenerator = fn(&mut Unstructured<'_>, usize) -> arbitrary::Result<String>;
+
+/// Candidates tried for a field before settling for the last one, and for a whole record before giving up
+const FIELD_ATTEMPTS: usize = 8;
+const RECORD_ATTEMPTS: usize = 32;
+
+/// Text for a `T` field that `T` parses without warnings; used by the generated [`Arbitrary`] implementations
+pub fn field_text<T: ArbitraryField + CwrFieldParse>(
+    u: &mut Unstructured<'_>, width: usize,
+) -> arbitrary::Result<String> {
+    let mut text = T::arbitrary_text(u, width)?;
+    for _ in 1..FIELD_ATTEMPTS {
+        if T::parse_cwr_field(&format_text(&text, width), "", "").1.is_empty() {
+            break;
+        }
+        text = T::arbitrary_text(u, width)?;
+    }
+    Ok(text)
+}
+
+/// A field that is not generated: the record type, which [`arbitrary_record`] fills in, and fields the parser skips
+pub fn blank_text(_u: &mut Unstructured<'_>, _width: usize) -> arbitrary::Result<String> {
+    Ok(String::new())
+}
+
+/// Generates a record of type `R` from `generators`, one per field in declaration order
+pub fn arbitrary_record<R: CwrRecord>(u: &mut Unstructured<'_>, generators: &[FieldGenerator]) -> arbitrary::Result<R> {
+    let version = record_version::<R>();
+    for _ in 0..RECORD_ATTEMPTS {
+        let line = arbitrary_line::<R>(u, generators, version)?;
+        if let Ok(parsed) = R::from_cwr_line_with_version(&line, Some(version))
+            && parsed.warnings.is_empty()
+        {
+            return Ok(parsed.record);
+        }
+        if u.is_empty() {
+            break;
+        }
+    }
+    Err(arbitrary::Error::IncorrectFormat)
+}
+
+/// The CWR version generated records of type `R` are laid out for: the newest one the record exists in
+pub fn record_version<R: CwrRecord>() -> f32 {
+    R::version_range().1.unwrap_or(2.2)
+}
+
+fn arbitrary_line<R: CwrRecord>(
+    u: &mut Unstructured<'_>, generators: &[FieldGenerator], version: f32,
+) -> arbitrary::Result<String> {
+    let mut line: Vec<u8> = Vec::new();
+    for (spec, generate) in R::field_specs().iter().zip(generators) {
+        if spec.min_version.is_some_and(|min| version < min) {
+            continue;
+        }
+        let (start, len) = spec.layout_for(version);
+        let text = if spec.name == "record_type" {
+            u.choose(R::record_codes())?.to_string()
+        } else if let Some(text) = named_text(u, spec, len)? {
+            text
+        } else {
+            generate(u, len)?
+        };
+        if line.len() < start + len {
+            line.resize(start + len, b' ');
+        }
+        let text = format_text(&text, len);
+        line[start..start + len].copy_from_slice(&text.as_bytes()[..len]);
+    }
+    Ok(String::from_utf8_lossy(&line).into_owned())
+}
+
+/// Text for fields whose type allows more than the field holds, such as ISWCs and IP numbers kept as plain text
+fn named_text(u: &mut Unstructured<'_>, spec: &FieldSpec, width: usize) -> arbitrary::Result<Option<String>> {
+    let name = spec.name;
+    let known = name.contains("iswc") || name.ends_with("interested_party_num") || name.starts_with("year_of_");
+    if !known {
+        return Ok(None);
+    }
+    if spec.optional && u.ratio(1, 3)? {
+        return Ok(Some(String::new()));
+    }
+    let text = if name.contains("iswc") {
+        format!("T{}", digits(u, 10)?)
+    } else if name.starts_with("year_of_") {
+        number_text(u, width, 1900, 2100)?
+    } else {
+        digits(u, width.min(9))?
+    };
+    Ok(Some(text))
+}
+
+impl<'a> Arbitrary<'a> for CwrRegistry {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(match u.int_in_range(0..=32u8)? {
+            0 => CwrRegistry::Hdr(u.arbitrary()?),
+            1 => CwrRegistry::Grh(u.arbitrary()?),
+            2 => CwrRegistry::Grt(u.arbitrary()?),
+            3 => CwrRegistry::Trl(u.arbitrary()?),
+            4 => CwrRegistry::Agr(u.arbitrary()?),
+            5 => CwrRegistry::Nwr(u.arbitrary()?),
+            6 => CwrRegistry::Ack(u.arbitrary()?),
+            7 => CwrRegistry::Ter(u.arbitrary()?),
+            8 => CwrRegistry::Ipa(u.arbitrary()?),
+            9 => CwrRegistry::Npa(u.arbitrary()?),
+            10 => CwrRegistry::Spu(u.arbitrary()?),
+            11 => CwrRegistry::Npn(u.arbitrary()?),
+            12 => CwrRegistry::Spt(u.arbitrary()?),
+            13 => CwrRegistry::Swr(u.arbitrary()?),
+            14 => CwrRegistry::Nwn(u.arbitrary()?),
+            15 => CwrRegistry::Swt(u.arbitrary()?),
+            16 => CwrRegistry::Pwr(u.arbitrary()?),
+            17 => CwrRegistry::Alt(u.arbitrary()?),
+            18 => CwrRegistry::Nat(u.arbitrary()?),
+            19 => CwrRegistry::Ewt(u.arbitrary()?),
+            20 => CwrRegistry::Ver(u.arbitrary()?),
+            21 => CwrRegistry::Per(u.arbitrary()?),
+            22 => CwrRegistry::Npr(u.arbitrary()?),
+            23 => CwrRegistry::Rec(u.arbitrary()?),
+            24 => CwrRegistry::Orn(u.arbitrary()?),
+            25 => CwrRegistry::Ins(u.arbitrary()?),
+            26 => CwrRegistry::Ind(u.arbitrary()?),
+            27 => CwrRegistry::Com(u.arbitrary()?),
+            28 => CwrRegistry::Msg(u.arbitrary()?),
+            29 => CwrRegistry::Net(u.arbitrary()?),
+            30 => CwrRegistry::Now(u.arbitrary()?),
+            31 => CwrRegistry::Ari(u.arbitrary()?),
+            _ => CwrRegistry::Xrf(u.arbitrary()?),
+        })
+    }
+}
+
+/// One of `codes` that fits in `width`, picked in a stable order so the same input always gives the same code
+fn choose_code<'c>(
+    u: &mut Unstructured<'_>, width: usize, codes: impl IntoIterator<Item = &'c str>,
+) -> arbitrary::Result<String> {
+    let mut codes: Vec<&str> = codes.into_iter().filter(|code| code.len() <= width).collect();
+    codes.sort_unstable();
+    codes.dedup();
+    Ok(u.choose(&codes)?.to_string())
+}
+
+/// A zero-padded number up to `max` that fits in `width`
+fn number_text(u: &mut Unstructured<'_>, width: usize, min: u64, max: u64) -> arbitrary::Result<String> {
+    let max = max.min(10u64.saturating_pow(width.min(19) as u32).saturating_sub(1));
+    Ok(format!("{:0width$}", u.int_in_range(min.min(max)..=max)?, width = width))
+}
+
+fn digits(u: &mut Unstructured<'_>, len: usize) -> arbitrary::Result<String> {
+    (0..len).map(|_| Ok(char::from(u.int_in_range(b'0'..=b'9')?))).collect()
+}
+
+fn letters(u: &mut Unstructured<'_>, len: usize) -> arbitrary::Result<String> {
+    (0..len).map(|_| Ok(char::from(u.int_in_range(b'A'..=b'Z')?))).collect()
+}
+
+/// Free text starting with a letter, such as a title or name
+fn text(u: &mut Unstructured<'_>, width: usize) -> arbitrary::Result<String> {
+    const CHARS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789 &'-.,";
+    if width == 0 {
+        return Ok(String::new());
+    }
+    let len = u.int_in_range(1..=width)?;
+    let mut text = letters(u, 1)?;
+    for _ in 1..len {
+        text.push(char::from(*u.choose(CHARS)?));
+    }
+    Ok(text)
+}
+
+impl<T: ArbitraryField> ArbitraryField for Option<T> {
+    fn arbitrary_text(u: &mut Unstructured<'_>, width: usize) -> arbitrary::Result<String> {
+        if u.ratio(1, 3)? { Ok(String::new()) } else { T::arbitrary_text(u, width) }
+    }
+}
+
+macro_rules! text_fields {
+    ($($ty:ty),* $(,)?) => {
+        $(impl ArbitraryField for $ty {
+            fn arbitrary_text(u: &mut Unstructured<'_>, width: usize) -> arbitrary::Result<String> {
+                text(u, width)
+            }
+        })*
+    };
+}
+
+macro_rules! number_fields {
+    ($($ty:ty => $min:expr, $max:expr);* $(;)?) => {
+        $(impl ArbitraryField for $ty {
+            fn arbitrary_text(u: &mut Unstructured<'_>, width: usize) -> arbitrary::Result<String> {
+                number_text(u, width, $min, $max)
+            }
+        })*
+    };
+}
+
+macro_rules! code_fields {
+    ($($ty:ty => $codes:expr);* $(;)?) => {
+        $(impl ArbitraryField for $ty {
+            fn arbitrary_text(u: &mut Unstructured<'_>, width: usize) -> arbitrary::Result<String> {
+                choose_code(u, width, $codes)
+            }
+        })*
+    };
+}
+
+text_fields!(String, NonRomanAlphabet);
+
+number_fields! {
+    Number => 0, u32::MAX as u64;
+    GroupCount => 0, u32::MAX as u64;
+    GroupId => 0, u32::MAX as u64;
+    RecordCount => 0, u32::MAX as u64;
+    TransactionCount => 0, u32::MAX as u64;
+    WorksCount => 0, u32::MAX as u64;
+    CwrRevision => 0, u32::MAX as u64;
+    MonetaryValue => 0, u64::MAX;
+    CompositeComponentCount => 1, u16::MAX as u64;
+    OwnershipShare => 0, 10000;
+    PublisherSequenceNumber => 1, 99;
+}
+
+code_fields! {
+    AgreementRoleCode => ["AS", "AC"];
+    Boolean => ["Y", "N"];
+    CharacterSet => ["ASCII", "Latin-1", "Traditional Big5", "Simplified GB", "UTF-8", "Unicode"];
+    CwrVersion => ["2.0", "2.1", "2.2"];
+    CwrVersionNumber => ["02.00", "02.10", "02.20"];
+    EdiStandardVersion => ["01.10"];
+    Flag => ["Y", "N", "U"];
+    IdentifierType => ["T", "W", "V"];
+    InclusionExclusionIndicator => ["I", "E"];
+    IntendedPurpose => ["COM", "FIL", "GEN", "LIB", "MUL", "RAD", "TEL", "THR", "VID"];
+    IsrcValidityIndicator => ["Y", "U", "N"];
+    MessageLevel => ["R", "G", "T"];
+    MessageType => ["E", "W", "F"];
+    PostTermCollectionStatus => ["N", "O", "D"];
+    PriorRoyaltyStatus => ["N", "A", "D"];
+    PublisherType => lookups::publisher_types::get_all_publisher_type_codes();
+    RecordingFormat => ["U", "S", "M", "Q"];
+    RecordingTechnique => ["U", "A", "D"];
+    SalesManufactureClause => ["S", "M"];
+    SenderType => ["PB", "SO", "WR", "AA"];
+    SubjectCode => ["DL", "SC", "DW", "IQ", "RQ", "GW", "EW"];
+    TitleType => ["AT", "TE", "FT", "IT", "OT", "TT", "PT", "RT", "ET", "OL", "AL"];
+    TransactionType => ["NWR", "REV", "AGR", "ACK", "ISW", "EXC"];
+    TypeOfRight => ["MEC", "PER", "SYN", "ALL"];
+    WriterPosition => ["1", "2"];
+    AgreementType => lookups::agreement_types::get_all_agreement_types();
+    CompositeType => lookups::composite_types::get_all_composite_types();
+    CurrencyCode => lookups::currency_codes::get_all_currency_codes();
+    ExcerptType => lookups::excerpt_types::get_all_excerpt_types();
+    InstrumentCode => lookups::instrument_codes::get_all_instrument_codes();
+    LanguageCode => lookups::language_codes::get_all_language_codes();
+    LanguageDialect => lookups::language_dialect_codes::get_all_language_dialect_codes();
+    LyricAdaptation => lookups::lyric_adaptations::get_all_lyric_adaptations();
+    MediaType => lookups::media_types::get_all_media_types();
+    MusicArrangement => lookups::music_arrangements::get_all_music_arrangements();
+    MusicalWorkDistributionCategory =>
+        lookups::musical_work_distribution_categories::get_all_musical_work_distribution_categories();
+    StandardInstrumentationType => lookups::standard_instrumentations::get_all_standard_instrumentations();
+    TextMusicRelationship => lookups::text_music_relationships::get_all_text_music_relationships();
+    TransactionStatus => lookups::transaction_statuses::get_all_transaction_statuses();
+    UsaLicenseIndicator => lookups::usa_license_indicators::get_all_usa_license_indicators();
+    VersionType => lookups::version_types::get_all_version_types();
+    WorkType => lookups::work_types::get_all_work_types();
+    WriterDesignation => lookups::writer_designations::get_all_writer_designations();
+}
+
+impl ArbitraryField for SocietyCode {
+    fn arbitrary_text(u: &mut Unstructured<'_>, width: usize) -> arbitrary::Result<String> {
+        let mut numbers: Vec<u16> = lookups::society_codes::SOCIETY_CODES_BY_NUMBER.keys().copied().collect();
+        numbers.sort_unstable();
+        Ok(format!("{:0width$}", u.choose(&numbers)?, width = width.min(3)))
+    }
+}
+
+impl ArbitraryField for SenderId {
+    fn arbitrary_text(u: &mut Unstructured<'_>, width: usize) -> arbitrary::Result<String> {
+        number_text(u, width, 1, u32::MAX as u64)
+    }
+}
+
+impl ArbitraryField for SenderName {
+    fn arbitrary_text(u: &mut Unstructured<'_>, width: usize) -> arbitrary::Result<String> {
+        text(u, width.min(45))
+    }
+}
+
+impl ArbitraryField for TisNumericCode {
+    fn arbitrary_text(u: &mut Unstructured<'_>, width: usize) -> arbitrary::Result<String> {
+        let mut codes = lookups::tis_codes::get_all_valid_tis_codes();
+        codes.sort_unstable();
+        Ok(format!("{:0width$}", u.choose(&codes)?, width = width))
+    }
+}
+
+impl ArbitraryField for Date {
+    fn arbitrary_text(u: &mut Unstructured<'_>, _width: usize) -> arbitrary::Result<String> {
+        let (year, month, day): (u16, u8, u8) =
+            (u.int_in_range(1900..=2020)?, u.int_in_range(1..=12)?, u.int_in_range(1..=28)?);
+        Ok(format!("{:04}{:02}{:02}", year, month, day))
+    }
+}
+
+impl ArbitraryField for Time {
+    fn arbitrary_text(u: &mut Unstructured<'_>, _width: usize) -> arbitrary::Result<String> {
+        let (hours, minutes, seconds): (u8, u8, u8) =
+            (u.int_in_range(0..=23)?, u.int_in_range(0..=59)?, u.int_in_range(0..=59)?);
+        Ok(format!("{:02}{:02}{:02}", hours, minutes, seconds))
+    }
+}
+
+impl ArbitraryField for Duration {
+    fn arbitrary_text(u: &mut Unstructured<'_>, _width: usize) -> arbitrary::Result<String> {
+        let (hours, minutes, seconds): (u8, u8, u8) =
+            (u.int_in_range(0..=99)?, u.int_in_range(0..=59)?, u.int_in_range(0..=59)?);
+        Ok(format!("{:02}{:02}{:02}", hours, minutes, seconds))
+    }
+}
+
+impl ArbitraryField for IpiNameNumber {
+    fn arbitrary_text(u: &mut Unstructured<'_>, _width: usize) -> arbitrary::Result<String> {
+        digits(u, 11)
+    }
+}
+
+impl ArbitraryField for IpiBaseNumber {
+    fn arbitrary_text(u: &mut Unstructured<'_>, _width: usize) -> arbitrary::Result<String> {
+        Ok(format!("I{}", digits(u, 12)?))
+    }
+}
+
+impl ArbitraryField for Isrc {
+    fn arbitrary_text(u: &mut Unstructured<'_>, _width: usize) -> arbitrary::Result<String> {
+        Ok(format!("{}{}{}", letters(u, 2)?, letters(u, 3)?, digits(u, 7)?))
+    }
+}
+
+impl ArbitraryField for Ean {
+    fn arbitrary_text(u: &mut Unstructured<'_>, _width: usize) -> arbitrary::Result<String> {
+        digits(u, 13)
+    }
+}
+
+/// Generates a value from `seed` alone, for tests that want a spread of generated records without a fuzzer. The
+/// same seed always gives the same value.
+pub fn from_seed<T: for<'a> Arbitrary<'a>>(seed: u64) -> arbitrary::Result<T> {
+    // xorshift64, which never leaves zero
+    let mut state = seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1;
+    let bytes: Vec<u8> = (0..SEED_BYTES)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state >> 32) as u8
+        })
+        .collect();
+    T::arbitrary(&mut Unstructured::new(&bytes))
+}
+
+/// Bytes of input [`from_seed`] generates from, enough for the longest records and a few retries
+const SEED_BYTES: usize = 4096;
+
+#[cfg(test)]
+pub(crate) fn check_round_trip<R>()
+where
+    R: CwrRecord + for<'a> Arbitrary<'a> + Clone + PartialEq + std::fmt::Debug,
+{
+    const SAMPLES: u64 = 64;
+    let version = record_version::<R>();
+    let mut generated = 0;
+    for seed in 0..SAMPLES {
+        let Ok(record) = from_seed::<R>(seed) else {
+            continue;
+        };
+        generated += 1;
+        let line = record.clone().into_registry().to_cwr_record_bytes(&CwrVersion(version), &CharacterSet::ASCII);
+        let line = String::from_utf8(line).unwrap();
+        let parsed = R::from_cwr_line_with_version(&line, Some(version)).unwrap();
+        assert!(parsed.warnings.is_empty(), "{}: {:?}", line, parsed.warnings);
+        assert_eq!(parsed.record, record, "{}", line);
+    }
+    assert!(
+        generated * 4 >= SAMPLES * 3,
+        "only {} of {} {} records generated",
+        generated,
+        SAMPLES,
+        R::record_codes()[0]
+    );
+}
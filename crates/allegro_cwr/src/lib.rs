@@ -4,14 +4,39 @@
 //! see the `allegro_cwr_sqlite` crate. For JSON output, see the `allegro_cwr_json` crate.
 
 mod ascii_io;
+pub mod async_handler;
+pub mod builder;
+pub mod charset_detect;
+pub mod compression;
+pub mod convert;
 mod cwr_handler;
 pub mod cwr_registry;
+pub mod diff;
+pub mod document;
 pub mod domain_types;
+pub mod encoding;
 pub mod error;
+#[cfg(feature = "fs")]
+pub mod filter;
+#[cfg(feature = "arbitrary")]
+pub mod generate;
 pub mod lookups;
+pub mod metrics;
+pub mod normalize;
 pub mod parser;
 pub mod parsing;
+pub mod pipeline;
 pub mod records;
+mod recovery;
+#[cfg(feature = "fs")]
+pub mod resubmit;
+#[cfg(feature = "fs")]
+pub mod split;
+pub mod spreadsheet;
+pub mod summary;
+pub mod tee;
+pub mod territories;
+pub mod transmission_writer;
 pub mod util;
 
 #[derive(Debug, Clone)]
@@ -40,23 +65,61 @@ impl std::str::FromStr for OutputFormat {
 }
 
 // Re-export commonly used items
-pub use crate::ascii_io::{AsciiLineReader, AsciiStreamSniffer, AsciiWriter, CwrHeaderInfo};
-pub use crate::cwr_registry::{CwrRegistry, get_all_record_type_codes};
-pub use crate::error::CwrParseError;
+pub use crate::ascii_io::{
+    AsciiLineReader, AsciiStreamSniffer, AsciiWriter, CwrHeaderInfo, read_cwr_line, trim_line_terminator,
+};
+pub use crate::builder::{PublisherBuilder, Shares, TransactionBuildError, WorkBuilder, WriterBuilder};
+#[cfg(feature = "fs")]
+pub use crate::convert::convert_cwr_file;
+pub use crate::convert::{ConversionChange, ConversionChangeKind, ConversionReport, convert_version};
+pub use crate::cwr_registry::{
+    CwrRegistry, RecordRole, RecordTypeInfo, SemanticRecord, all_record_type_info, get_all_record_type_codes,
+    record_type_info,
+};
+pub use crate::diff::{CwrDiff, TransactionChange, TransactionDiff, diff_cwr_files};
+pub use crate::document::{CwrFile, CwrGroup, CwrTransaction, renumber_sequences};
+pub use crate::error::{CwrParseError, ParseErrorCode, RecordParseError};
+#[cfg(feature = "fs")]
+pub use crate::filter::{FilterSummary, filter_cwr_file, filter_cwr_file_to_writer};
+pub use crate::metrics::{MetricsHandler, TransmissionMetrics};
+pub use crate::normalize::normalize_record_order;
 pub use crate::parser::{
-    ParsedRecord, ParsingContext, is_cwr_file, process_cwr_stream, process_cwr_stream_with_version,
+    ParseOptions, ParsedRecord, ParsingContext, is_cwr_bytes, parse_cwr_line, process_cwr_bytes_stream,
+    process_cwr_reader_stream,
+};
+#[cfg(feature = "fs")]
+pub use crate::parser::{
+    is_cwr_file, process_cwr_stream, process_cwr_stream_with_options, process_cwr_stream_with_version,
     process_cwr_stream_with_version_and_charset,
 };
+pub use crate::pipeline::{Pipeline, PipelineContext, PipelineStream, RecordTransform};
 pub use crate::records::*;
-pub use crate::util::{extract_version_from_filename, format_int_with_commas};
+#[cfg(feature = "fs")]
+pub use crate::resubmit::{ResubmitOptions, ResubmitSummary, build_resubmission, build_resubmission_to_writer};
+#[cfg(feature = "fs")]
+pub use crate::split::{split_cwr_file, split_cwr_file_with};
+pub use crate::spreadsheet::{ColumnMapping, SpreadsheetError, SpreadsheetOptions, spreadsheet_to_cwr};
+pub use crate::summary::{SummaryHandler, TransmissionSummary};
+pub use crate::tee::{TeeError, TeeHandler};
+pub use crate::transmission_writer::{TransmissionTotals, TransmissionWriter, write_cwr_file, write_cwr_file_with};
+pub use crate::util::{
+    VersionDetection, VersionSource, detect_cwr_version, extract_version_from_filename, format_int_with_commas,
+};
 
-pub use cwr_handler::CwrHandler;
-use log::info;
+pub use async_handler::{
+    AsyncCwrHandler, process_cwr_bytes_with_async_handler, process_cwr_bytes_with_async_handler_and_options,
+};
+use cwr_handler::{Boundary, DriverStep, HandlerDriver};
+pub use cwr_handler::{
+    CwrHandler, ErrorMode, HandlerReport, OutputReport, ProcessingAborted, ProcessingSummary, SkippedLines,
+};
 
-/// Generic function to process CWR file with any handler that implements CwrHandler trait
+/// Generic function to process CWR file with any handler that implements CwrHandler trait. The summary carries
+/// the handler's report along with the record, error and warning counts.
+#[cfg(feature = "fs")]
 pub fn process_cwr_with_handler<H: CwrHandler>(
     input_filename: &str, handler: H,
-) -> Result<String, Box<dyn std::error::Error>>
+) -> Result<ProcessingSummary, Box<dyn std::error::Error>>
 where
     H::Error: 'static,
 {
@@ -64,38 +127,118 @@ where
 }
 
 /// Generic function to process CWR file with any handler that implements CwrHandler trait and optional version hint
+#[cfg(feature = "fs")]
 pub fn process_cwr_with_handler_and_version<H: CwrHandler>(
-    input_filename: &str, mut handler: H, version_hint: Option<f32>,
-) -> Result<String, Box<dyn std::error::Error>>
+    input_filename: &str, handler: H, version_hint: Option<f32>,
+) -> Result<ProcessingSummary, Box<dyn std::error::Error>>
+where
+    H::Error: 'static,
+{
+    let options = ParseOptions { version_hint, ..Default::default() };
+    process_cwr_with_handler_and_options(input_filename, handler, &options)
+}
+
+/// Generic function to process CWR file with any handler that implements CwrHandler trait, reading it with the
+/// given options (e.g. to keep the raw source lines)
+#[cfg(feature = "fs")]
+pub fn process_cwr_with_handler_and_options<H: CwrHandler>(
+    input_filename: &str, handler: H, options: &ParseOptions,
+) -> Result<ProcessingSummary, Box<dyn std::error::Error>>
+where
+    H::Error: 'static,
+{
+    let options = cwr_handler::options_for_handler(options, handler.interested_in());
+    let records = process_cwr_stream_with_options(input_filename, &options)?;
+    process_records_with_handler_and_mode(input_filename, records, handler, options.error_mode)
+}
+
+/// Processes a CWR file read from `reader` (e.g. stdin) with any handler, reading it with the given options.
+/// `source` names the input in log messages.
+pub fn process_cwr_reader_with_handler_and_options<R: std::io::Read, H: CwrHandler>(
+    source: &str, reader: R, handler: H, options: &ParseOptions,
+) -> Result<ProcessingSummary, Box<dyn std::error::Error>>
+where
+    H::Error: 'static,
+{
+    let options = cwr_handler::options_for_handler(options, handler.interested_in());
+    let records = process_cwr_reader_stream(reader, &options)?;
+    process_records_with_handler_and_mode(source, records, handler, options.error_mode)
+}
+
+/// Processes a CWR file held in memory (e.g. an upload) with any handler, without touching the filesystem
+pub fn process_cwr_bytes<H: CwrHandler>(
+    bytes: &[u8], handler: H,
+) -> Result<ProcessingSummary, Box<dyn std::error::Error>>
+where
+    H::Error: 'static,
+{
+    let options = cwr_handler::options_for_handler(&ParseOptions::default(), handler.interested_in());
+    process_records_with_handler("<bytes>", process_cwr_bytes_stream(bytes, &options)?, handler)
+}
+
+/// Runs records that are already parsed, or rebuilt from another format, through a handler as if they were read
+/// from a CWR file. `source` names them in log messages.
+pub fn process_records_with_handler<H: CwrHandler>(
+    source: &str, records: impl Iterator<Item = Result<ParsedRecord, CwrParseError>>, handler: H,
+) -> Result<ProcessingSummary, Box<dyn std::error::Error>>
+where
+    H::Error: 'static,
+{
+    process_records_with_handler_and_mode(source, records, handler, ErrorMode::CollectAll)
+}
+
+/// [`process_records_with_handler`], stopping at the first parse error or critical warning in
+/// [`ErrorMode::FailFast`]
+pub fn process_records_with_handler_and_mode<H: CwrHandler>(
+    source: &str, mut records: impl Iterator<Item = Result<ParsedRecord, CwrParseError>>, mut handler: H,
+    mode: ErrorMode,
+) -> Result<ProcessingSummary, Box<dyn std::error::Error>>
 where
     H::Error: 'static,
 {
-    let mut processed_count = 0;
-    let mut error_count = 0;
-
-    for result in process_cwr_stream_with_version(input_filename, version_hint)? {
-        match result {
-            Ok(parsed_record) => {
-                // Handle warnings if any
-                if !parsed_record.warnings.is_empty() {
-                    handler.handle_warnings(
-                        parsed_record.line_number,
-                        parsed_record.record.record_type(),
-                        &parsed_record.warnings,
-                    )?;
+    let mut driver = HandlerDriver::new(source, mode, handler.interested_in());
+    let _file = driver.span().clone().entered();
+
+    while let Some(result) = driver.group_span().in_scope(|| records.next()) {
+        let step = driver.step(result);
+        let _group = driver.group_span().enter();
+        match step {
+            DriverStep::Skip => {}
+            DriverStep::Record { record, abort, boundaries, ends_group } => {
+                if !record.warnings.is_empty() {
+                    handler.handle_warnings(record.line_number, record.record.record_type(), &record.warnings)?;
+                }
+                if let Some(abort) = abort {
+                    return Err(Box::new(abort));
+                }
+                for boundary in boundaries {
+                    match boundary {
+                        Boundary::BeginGroup => handler.begin_group(&record)?,
+                        Boundary::EndGroup => handler.end_group()?,
+                        Boundary::BeginTransaction => handler.begin_transaction(&record)?,
+                        Boundary::EndTransaction => handler.end_transaction()?,
+                    }
+                }
+                handler.process_record(record)?;
+                if ends_group {
+                    handler.end_group()?;
                 }
-                handler.process_record(parsed_record)?;
-                processed_count += 1;
             }
-            Err(parse_error) => {
-                handler.handle_parse_error(processed_count + error_count + 1, &parse_error)?;
-                error_count += 1;
+            DriverStep::ParseError { line_number, error, abort } => {
+                handler.handle_parse_error(line_number, &error)?;
+                if let Some(abort) = abort {
+                    return Err(Box::new(abort));
+                }
             }
         }
     }
 
+    for boundary in driver.finish() {
+        match boundary {
+            Boundary::EndTransaction => handler.end_transaction()?,
+            _ => handler.end_group()?,
+        }
+    }
     handler.finalize()?;
-
-    info!("Processing complete: {} records processed, {} errors", processed_count, error_count);
-    Ok(handler.get_report())
+    Ok(driver.into_summary(handler.get_report(), handler.report()))
 }
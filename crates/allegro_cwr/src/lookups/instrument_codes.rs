@@ -1,18 +1,159 @@
-//! Instrument Code validation
+//! Instrument Code lookup table
+
+use crate::lookups::near_misses;
+use crate::lookups::registry::{LookupRegistry, LookupTable, override_contains};
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+
+/// Instrument codes mapping
+pub static INSTRUMENT_CODES: Lazy<HashMap<&'static str, &'static str>> = Lazy::new(|| {
+    let mut m = HashMap::new();
+    m.insert("ACC", "Accordion");
+    m.insert("ACL", "Alto Clarinet");
+    m.insert("AFL", "Alto Flute");
+    m.insert("AHN", "Alto Horn");
+    m.insert("ALP", "Alphorn");
+    m.insert("ALT", "Alto Voice");
+    m.insert("AMP", "Amplified Instrument");
+    m.insert("ARC", "Archlute");
+    m.insert("ASX", "Alto Saxophone");
+    m.insert("BAN", "Banjo");
+    m.insert("BAR", "Baritone Voice");
+    m.insert("BAS", "Bass Voice");
+    m.insert("BCL", "Bass Clarinet");
+    m.insert("BDR", "Bass Drum");
+    m.insert("BFL", "Bass Flute");
+    m.insert("BHN", "Basset Horn");
+    m.insert("BON", "Bongos");
+    m.insert("BRD", "Bodhran");
+    m.insert("BSN", "Bassoon");
+    m.insert("BSX", "Baritone Saxophone");
+    m.insert("BTB", "Bass Trombone");
+    m.insert("BUG", "Bugle");
+    m.insert("CAB", "Cabasa");
+    m.insert("CAL", "Calliope");
+    m.insert("CEL", "Celesta");
+    m.insert("CHI", "Chimes");
+    m.insert("CLA", "Clarinet");
+    m.insert("CLV", "Clavichord");
+    m.insert("CON", "Congas");
+    m.insert("COR", "Cornet");
+    m.insert("CRO", "Crotales");
+    m.insert("CYM", "Cymbals");
+    m.insert("DBL", "Double Bass");
+    m.insert("DIG", "Didgeridoo");
+    m.insert("DRM", "Drums");
+    m.insert("DSN", "Contrabassoon");
+    m.insert("DUL", "Dulcimer");
+    m.insert("EFX", "Electronic Effects");
+    m.insert("EGT", "Electric Guitar");
+    m.insert("EHN", "English Horn");
+    m.insert("EKB", "Electric Keyboard");
+    m.insert("EOR", "Electric Organ");
+    m.insert("EPF", "Electric Piano");
+    m.insert("ESX", "Electric Saxophone");
+    m.insert("ETB", "Electric Bass");
+    m.insert("ETR", "Electric Trumpet");
+    m.insert("EUP", "Euphonium");
+    m.insert("FEM", "Female Voice");
+    m.insert("FID", "Fiddle");
+    m.insert("FIF", "Fife");
+    m.insert("FLG", "Flugelhorn");
+    m.insert("FLT", "Flute");
+    m.insert("FRH", "French Horn");
+    m.insert("GIT", "Acoustic Guitar");
+    m.insert("GLO", "Glockenspiel");
+    m.insert("GON", "Gong");
+    m.insert("GUI", "Guiro");
+    m.insert("HAR", "Harp");
+    m.insert("HCA", "Harmonica");
+    m.insert("HCL", "Hand Claps");
+    m.insert("HDR", "Hand Drum");
+    m.insert("HPS", "Harpsichord");
+    m.insert("HRN", "Horn");
+    m.insert("KAZ", "Kazoo");
+    m.insert("KEY", "Keyboard");
+    m.insert("MAL", "Mallets");
+    m.insert("MAN", "Mandolin");
+    m.insert("MAR", "Marimba");
+    m.insert("MIC", "Microphone");
+    m.insert("MIN", "Minimoog");
+    m.insert("MIX", "Mixed Ensemble");
+    m.insert("MOD", "Modular Synthesizer");
+    m.insert("NAR", "Narrator");
+    m.insert("OBO", "Oboe");
+    m.insert("OCA", "Ocarina");
+    m.insert("OCL", "Ophicleide");
+    m.insert("ONM", "Ondes Martenot");
+    m.insert("ORC", "Orchestra");
+    m.insert("ORG", "Organ");
+    m.insert("OTH", "Other Instrument");
+    m.insert("PAN", "Pan Pipes");
+    m.insert("PER", "Percussion");
+    m.insert("PFC", "Pianoforte");
+    m.insert("PIA", "Piano");
+    m.insert("PIC", "Piccolo");
+    m.insert("PNO", "Piano");
+    m.insert("REC", "Recorder");
+    m.insert("SAX", "Saxophone");
+    m.insert("SHN", "Shakuhachi");
+    m.insert("SIT", "Sitar");
+    m.insert("SNR", "Snare Drum");
+    m.insert("SOP", "Soprano Voice");
+    m.insert("STB", "Steel Drums");
+    m.insert("STG", "Strings");
+    m.insert("SUS", "Suspended Cymbal");
+    m.insert("SYN", "Synthesizer");
+    m.insert("TAM", "Tambourine");
+    m.insert("TBL", "Tabla");
+    m.insert("TBN", "Trombone");
+    m.insert("TEN", "Tenor Voice");
+    m.insert("TIM", "Timbales");
+    m.insert("TOY", "Toy Piano");
+    m.insert("TRG", "Triangle");
+    m.insert("TRM", "Theremin");
+    m.insert("TRP", "Trumpet");
+    m.insert("TSX", "Tenor Saxophone");
+    m.insert("TUB", "Tuba");
+    m.insert("TYM", "Timpani");
+    m.insert("UKU", "Ukulele");
+    m.insert("VIB", "Vibraphone");
+    m.insert("VIO", "Violin");
+    m.insert("VLA", "Viola");
+    m.insert("VLC", "Cello");
+    m.insert("VLN", "Violin");
+    m.insert("VOC", "Voice");
+    m.insert("WAH", "Wah-wah Pedal");
+    m.insert("WHI", "Whistle");
+    m.insert("WOO", "Woodblock");
+    m.insert("XYL", "Xylophone");
+    m
+});
 
 /// Checks if an instrument code is valid according to the CWR specification
 pub fn is_valid_instrument_code(code: &str) -> bool {
-    INSTRUMENT_CODES.contains(&code)
+    if let Some(valid) = override_contains(LookupTable::InstrumentCodes, code) {
+        return valid;
+    }
+    INSTRUMENT_CODES.contains_key(code)
+}
+
+/// Gets the description for an instrument code
+pub fn get_instrument_description(code: &str) -> Option<String> {
+    if let Some(registry) = LookupRegistry::active()
+        && registry.has_table(LookupTable::InstrumentCodes)
+    {
+        return registry.description(LookupTable::InstrumentCodes, code).map(str::to_string);
+    }
+    INSTRUMENT_CODES.get(code).map(|description| description.to_string())
 }
 
-/// Valid instrument codes from the CWR specification
-const INSTRUMENT_CODES: &[&str] = &[
-    "ACC", "ALP", "ACL", "AFL", "AHN", "ARC", "ASX", "ALT", "AMP", "BAN", "BAR", "BAS", "BCL", "BDR", "BFL", "BHN",
-    "BON", "BRD", "BSN", "BSX", "BTB", "BUG", "CAB", "CAL", "CEL", "CHI", "CLA", "CLV", "CON", "COR", "CRO", "CYM",
-    "DBL", "DIG", "DRM", "DSN", "DUL", "EFX", "EGT", "EHN", "EKB", "EOR", "EPF", "ESX", "ETB", "ETR", "EUP", "FEM",
-    "FID", "FIF", "FLG", "FLT", "FRH", "GIT", "GLO", "GON", "GUI", "HAR", "HCA", "HCL", "HDR", "HPS", "HRN", "KAZ",
-    "KEY", "MAL", "MAN", "MAR", "MIC", "MIN", "MIX", "MOD", "NAR", "OBO", "OCA", "OCL", "ONM", "ORC", "ORG", "OTH",
-    "PAN", "PER", "PFC", "PIA", "PIC", "REC", "SAX", "SHN", "SIT", "SNR", "SOP", "STB", "STG", "SUS", "SYN", "TAM",
-    "TBL", "TBN", "TEN", "TIM", "TOY", "TRG", "TRM", "TRP", "TSX", "TUB", "TYM", "UKU", "VIB", "VIO", "VLA", "VOC",
-    "WAH", "WHI", "WOO", "XYL",
-];
+/// Gets all valid instrument codes
+pub fn get_all_instrument_codes() -> Vec<&'static str> {
+    INSTRUMENT_CODES.keys().copied().collect()
+}
+
+/// Built-in instrument codes that differ from `code` by a single character
+pub fn suggest_instrument_codes(code: &str) -> Vec<&'static str> {
+    near_misses(code, INSTRUMENT_CODES.keys().copied())
+}
@@ -14,13 +14,81 @@ pub mod media_types;
 pub mod music_arrangements;
 pub mod musical_work_distribution_categories;
 pub mod publisher_types;
+pub mod registry;
 pub mod society_codes;
 pub mod society_members;
 pub mod standard_instrumentations;
 pub mod text_music_relationships;
 pub mod tis_codes;
+pub mod tis_hierarchy;
 pub mod transaction_statuses;
 pub mod usa_license_indicators;
 pub mod version_types;
 pub mod work_types;
 pub mod writer_designations;
+
+/// Maximum number of suggestions returned by [`near_misses`]
+const MAX_SUGGESTIONS: usize = 5;
+
+/// Candidates within one edit (substitution, insertion, deletion or swap of adjacent characters) of `code`,
+/// compared case-insensitively and returned in alphabetical order
+pub fn near_misses<'a>(code: &str, candidates: impl IntoIterator<Item = &'a str>) -> Vec<&'a str> {
+    let code: Vec<char> = code.trim().to_uppercase().chars().collect();
+    if code.is_empty() {
+        return Vec::new();
+    }
+    let mut matches: Vec<&'a str> = candidates
+        .into_iter()
+        .filter(|candidate| {
+            let candidate: Vec<char> = candidate.to_uppercase().chars().collect();
+            candidate != code && within_one_edit(&code, &candidate)
+        })
+        .collect();
+    matches.sort_unstable();
+    matches.truncate(MAX_SUGGESTIONS);
+    matches
+}
+
+/// Formats suggestions for a warning message, e.g. " (did you mean PIA or PIC?)"; empty when there are none
+pub fn suggestion_suffix(suggestions: &[&str]) -> String {
+    match suggestions {
+        [] => String::new(),
+        [only] => format!(" (did you mean {}?)", only),
+        [rest @ .., last] => format!(" (did you mean {} or {}?)", rest.join(", "), last),
+    }
+}
+
+fn within_one_edit(a: &[char], b: &[char]) -> bool {
+    match a.len().abs_diff(b.len()) {
+        0 => {
+            let differences: Vec<usize> = (0..a.len()).filter(|&i| a[i] != b[i]).collect();
+            match differences.as_slice() {
+                [_] => true,
+                [i, j] => *j == i + 1 && a[*i] == b[*j] && a[*j] == b[*i],
+                _ => false,
+            }
+        }
+        1 => {
+            let (short, long) = if a.len() < b.len() { (a, b) } else { (b, a) };
+            let prefix = short.iter().zip(long).take_while(|(x, y)| x == y).count();
+            short[prefix..] == long[prefix + 1..]
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_near_misses() {
+        let candidates = ["PIA", "PIC", "PNO", "VIO", "ORC"];
+        assert_eq!(near_misses("PIX", candidates), ["PIA", "PIC"]);
+        assert_eq!(near_misses("pno", candidates), Vec::<&str>::new());
+        assert_eq!(near_misses("IPA", candidates), ["PIA"]);
+        assert_eq!(near_misses("OR", candidates), ["ORC"]);
+        assert!(near_misses("XYZ", candidates).is_empty());
+        assert_eq!(suggestion_suffix(&["PIA", "PIC", "PNO"]), " (did you mean PIA, PIC or PNO?)");
+    }
+}
@@ -0,0 +1,235 @@
+//! Runtime overrides for built-in lookup tables
+//!
+//! CISAC publishes updated society, TIS, instrument and work type tables more often than this crate
+//! is released. A `LookupRegistry` holds replacement tables loaded from TSV/CSV files; once installed,
+//! the `is_valid_*` functions of the corresponding lookup modules consult it instead of the built-in data.
+//!
+//! ```no_run
+//! # #[cfg(feature = "fs")] {
+//! use allegro_cwr::lookups::registry::{LookupRegistry, LookupTable};
+//!
+//! let mut registry = LookupRegistry::new();
+//! registry.load_file(LookupTable::SocietyCodes, "societies.csv").unwrap();
+//! registry.install();
+//! # }
+//! ```
+
+use crate::error::CwrParseError;
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::io::BufRead;
+#[cfg(feature = "fs")]
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+
+/// Lookup tables that can be overridden at runtime
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LookupTable {
+    SocietyCodes,
+    TisCodes,
+    InstrumentCodes,
+    WorkTypes,
+}
+
+impl LookupTable {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LookupTable::SocietyCodes => "society_codes",
+            LookupTable::TisCodes => "tis_codes",
+            LookupTable::InstrumentCodes => "instrument_codes",
+            LookupTable::WorkTypes => "work_types",
+        }
+    }
+}
+
+/// Replacement lookup tables, keyed by code with an optional description/name
+#[derive(Debug, Clone, Default)]
+pub struct LookupRegistry {
+    tables: HashMap<LookupTable, HashMap<String, String>>,
+}
+
+static ACTIVE_REGISTRY: Lazy<RwLock<Option<Arc<LookupRegistry>>>> = Lazy::new(|| RwLock::new(None));
+static HAS_ACTIVE_REGISTRY: AtomicBool = AtomicBool::new(false);
+
+impl LookupRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds (or replaces) a single entry in the given table
+    pub fn insert(&mut self, table: LookupTable, code: &str, description: &str) {
+        self.tables.entry(table).or_default().insert(normalize_code(table, code), description.trim().to_string());
+    }
+
+    /// Loads a table from a TSV (`.tsv`/`.tab`) or CSV file. See [`LookupRegistry::load_reader`].
+    #[cfg(feature = "fs")]
+    pub fn load_file<P: AsRef<Path>>(&mut self, table: LookupTable, path: P) -> Result<usize, CwrParseError> {
+        let path = path.as_ref();
+        let delimiter = match path.extension().and_then(|e| e.to_str()).map(|e| e.to_ascii_lowercase()).as_deref() {
+            Some("tsv") | Some("tab") => '\t',
+            _ => ',',
+        };
+        let file = std::fs::File::open(path)?;
+        self.load_reader(table, std::io::BufReader::new(file), delimiter)
+    }
+
+    /// Loads a table from delimited text, replacing any previously loaded entries for that table.
+    ///
+    /// If the first row contains a `Code` column header, that column supplies the codes and the first
+    /// `Name`/`Description` column supplies descriptions. Otherwise the first column is the code and the
+    /// second column (if any) the description. Returns the number of entries loaded.
+    pub fn load_reader<R: BufRead>(
+        &mut self, table: LookupTable, reader: R, delimiter: char,
+    ) -> Result<usize, CwrParseError> {
+        let mut entries = HashMap::new();
+        let mut code_column = 0;
+        let mut description_column = Some(1);
+
+        for (index, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line = line.trim_end_matches('\r');
+            if line.trim().is_empty() {
+                continue;
+            }
+            let fields = split_delimited(line, delimiter);
+
+            if index == 0
+                && let Some(code_idx) = fields.iter().position(|f| f.eq_ignore_ascii_case("code"))
+            {
+                code_column = code_idx;
+                description_column = fields.iter().position(|f| {
+                    f.eq_ignore_ascii_case("name")
+                        || f.eq_ignore_ascii_case("description")
+                        || f.eq_ignore_ascii_case("society name")
+                        || f.eq_ignore_ascii_case("territory name")
+                });
+                continue;
+            }
+
+            let Some(code) = fields.get(code_column).filter(|c| !c.is_empty()) else {
+                return Err(CwrParseError::BadFormat(format!(
+                    "{} lookup: missing code on line {}",
+                    table.as_str(),
+                    index + 1
+                )));
+            };
+            let description = description_column.and_then(|i| fields.get(i)).cloned().unwrap_or_default();
+            entries.insert(normalize_code(table, code), description);
+        }
+
+        let count = entries.len();
+        self.tables.insert(table, entries);
+        Ok(count)
+    }
+
+    /// True if this registry overrides the given table
+    pub fn has_table(&self, table: LookupTable) -> bool {
+        self.tables.contains_key(&table)
+    }
+
+    /// Checks a code against an overridden table. Returns `None` if the table is not overridden.
+    pub fn contains(&self, table: LookupTable, code: &str) -> Option<bool> {
+        let entries = self.tables.get(&table)?;
+        let code = normalize_code(table, code);
+        if entries.contains_key(&code) {
+            return Some(true);
+        }
+        // Society fields may carry either the numeric code or the society name
+        Some(table == LookupTable::SocietyCodes && entries.values().any(|name| name.eq_ignore_ascii_case(&code)))
+    }
+
+    /// Gets the description for a code in an overridden table
+    pub fn description(&self, table: LookupTable, code: &str) -> Option<&str> {
+        self.tables.get(&table)?.get(&normalize_code(table, code)).map(String::as_str)
+    }
+
+    /// Makes this registry the active one used by the lookup functions
+    pub fn install(self) {
+        if let Ok(mut active) = ACTIVE_REGISTRY.write() {
+            *active = Some(Arc::new(self));
+            HAS_ACTIVE_REGISTRY.store(true, Ordering::Release);
+        }
+    }
+
+    /// Reverts all lookups to the built-in tables
+    pub fn uninstall() {
+        if let Ok(mut active) = ACTIVE_REGISTRY.write() {
+            *active = None;
+            HAS_ACTIVE_REGISTRY.store(false, Ordering::Release);
+        }
+    }
+
+    /// Returns the currently installed registry, if any
+    pub fn active() -> Option<Arc<LookupRegistry>> {
+        if !HAS_ACTIVE_REGISTRY.load(Ordering::Acquire) {
+            return None;
+        }
+        ACTIVE_REGISTRY.read().ok().and_then(|active| active.clone())
+    }
+}
+
+/// Checks a code against the installed registry. Returns `None` when the built-in table should be used.
+pub(crate) fn override_contains(table: LookupTable, code: &str) -> Option<bool> {
+    LookupRegistry::active().and_then(|registry| registry.contains(table, code))
+}
+
+fn normalize_code(table: LookupTable, code: &str) -> String {
+    let code = code.trim();
+    match table {
+        // Numeric codes compare by value so "021" and "21" match
+        LookupTable::SocietyCodes | LookupTable::TisCodes => match code.parse::<u16>() {
+            Ok(num) => num.to_string(),
+            Err(_) => code.to_string(),
+        },
+        LookupTable::InstrumentCodes | LookupTable::WorkTypes => code.to_uppercase(),
+    }
+}
+
+fn split_delimited(line: &str, delimiter: char) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                current.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            c if c == delimiter && !in_quotes => fields.push(std::mem::take(&mut current).trim().to_string()),
+            c => current.push(c),
+        }
+    }
+    fields.push(current.trim().to_string());
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_csv_with_header() {
+        let data = "Name,Code,Definition\n\"Big Orchestra, Large\",BOL,\nKazoo,KZO,\n";
+        let mut registry = LookupRegistry::new();
+        let count = registry.load_reader(LookupTable::InstrumentCodes, data.as_bytes(), ',').unwrap();
+        assert_eq!(count, 2);
+        assert_eq!(registry.contains(LookupTable::InstrumentCodes, "bol"), Some(true));
+        assert_eq!(registry.contains(LookupTable::InstrumentCodes, "ACC"), Some(false));
+        assert_eq!(registry.description(LookupTable::InstrumentCodes, "BOL"), Some("Big Orchestra, Large"));
+        assert_eq!(registry.contains(LookupTable::WorkTypes, "JZ"), None);
+    }
+
+    #[test]
+    fn test_load_tsv_without_header() {
+        let data = "021\tBMI\n010\tASCAP\n";
+        let mut registry = LookupRegistry::new();
+        registry.load_reader(LookupTable::SocietyCodes, data.as_bytes(), '\t').unwrap();
+        assert_eq!(registry.contains(LookupTable::SocietyCodes, "21"), Some(true));
+        assert_eq!(registry.contains(LookupTable::SocietyCodes, "BMI"), Some(true));
+        assert_eq!(registry.contains(LookupTable::SocietyCodes, "052"), Some(false));
+    }
+}
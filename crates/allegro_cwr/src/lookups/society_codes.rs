@@ -1,5 +1,6 @@
 //! Society codes lookup table from CWR specification
 
+use crate::lookups::registry::{LookupTable, override_contains};
 use once_cell::sync::Lazy;
 use std::collections::HashMap;
 
@@ -410,6 +411,10 @@ pub static SOCIETY_CODES_BY_NUMBER: Lazy<HashMap<u16, &'static str>> =
 /// Validates a society code exists in the lookup table
 /// Accepts both society name strings and numeric codes (as strings with leading zeros)
 pub fn is_valid_society_code(code: &str) -> bool {
+    if let Some(valid) = override_contains(LookupTable::SocietyCodes, code) {
+        return valid;
+    }
+
     // First try as society name
     if SOCIETY_CODES.contains_key(code) {
         return true;
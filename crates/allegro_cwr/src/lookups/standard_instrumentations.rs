@@ -1,5 +1,6 @@
 //! Standard Instrumentation lookup table
 
+use crate::lookups::near_misses;
 use once_cell::sync::Lazy;
 use std::collections::HashMap;
 
@@ -73,3 +74,8 @@ pub fn get_standard_instrumentation_description(code: &str) -> Option<&'static s
 pub fn get_all_standard_instrumentations() -> Vec<&'static str> {
     STANDARD_INSTRUMENTATIONS.keys().copied().collect()
 }
+
+/// Standard instrumentation codes that differ from `code` by a single character
+pub fn suggest_standard_instrumentations(code: &str) -> Vec<&'static str> {
+    near_misses(code, STANDARD_INSTRUMENTATIONS.keys().copied())
+}
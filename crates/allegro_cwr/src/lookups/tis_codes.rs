@@ -7,6 +7,7 @@
 //! - Usability indicators
 //! - Territory hierarchy relationships
 
+use crate::lookups::registry::{LookupTable, override_contains};
 use chrono::{NaiveDate, NaiveDateTime};
 use once_cell::sync::Lazy;
 use std::collections::HashMap;
@@ -3519,6 +3520,9 @@ pub static TIS_TERRITORIES: Lazy<HashMap<u16, TisTerritory>> = Lazy::new(|| {
 
 /// Validates a CISAC TIS numeric code (checks existence and current validity)
 pub fn is_valid_tis_code(code: u16) -> bool {
+    if let Some(valid) = override_contains(LookupTable::TisCodes, &code.to_string()) {
+        return valid;
+    }
     TIS_TERRITORIES.get(&code).is_some_and(|territory| territory.is_valid_at(None))
 }
 
@@ -3549,5 +3553,8 @@ pub fn get_all_tis_codes() -> Vec<u16> {
 
 /// Checks if a TIS code exists in the territory table (regardless of usability)
 pub fn territory_exists(code: u16) -> bool {
+    if let Some(exists) = override_contains(LookupTable::TisCodes, &code.to_string()) {
+        return exists;
+    }
     TIS_TERRITORIES.contains_key(&code)
 }
@@ -0,0 +1,492 @@
+//! CISAC TIS territory hierarchy
+//!
+//! Parent/child relationships between TIS territory groups (e.g. 2136 WORLD, 2100 AFRICA) and the
+//! territories they contain, with the validity period of each relationship. Generated from the
+//! official CISAC TIS "Territory hierarchy" table.
+
+use chrono::NaiveDate;
+
+/// A single parent/child link in the TIS hierarchy
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TisHierarchyLink {
+    pub parent: u16,
+    pub child: u16,
+    /// Validity start as YYYYMMDD
+    pub validity_start: u32,
+    /// Validity end as YYYYMMDD
+    pub validity_end: u32,
+}
+
+impl TisHierarchyLink {
+    /// Check if this link is valid on the given date
+    pub fn is_valid_at(&self, date: NaiveDate) -> bool {
+        let key = date_key(date);
+        key >= self.validity_start && key <= self.validity_end
+    }
+}
+
+fn date_key(date: NaiveDate) -> u32 {
+    use chrono::Datelike;
+    date.year().max(0) as u32 * 10000 + date.month() * 100 + date.day()
+}
+
+const fn link(parent: u16, child: u16, validity_start: u32, validity_end: u32) -> TisHierarchyLink {
+    TisHierarchyLink { parent, child, validity_start, validity_end }
+}
+
+/// All TIS hierarchy links, sorted by parent then child
+pub static TIS_HIERARCHY: &[TisHierarchyLink] = &[
+    link(2100, 24, 10000101, 39991231),
+    link(2100, 72, 10000101, 39991231),
+    link(2100, 108, 10000101, 39991231),
+    link(2100, 120, 10000101, 39991231),
+    link(2100, 132, 10000101, 39991231),
+    link(2100, 140, 10000101, 39991231),
+    link(2100, 148, 10000101, 39991231),
+    link(2100, 174, 10000101, 39991231),
+    link(2100, 178, 10000101, 39991231),
+    link(2100, 180, 10000101, 39991231),
+    link(2100, 204, 10000101, 39991231),
+    link(2100, 226, 10000101, 39991231),
+    link(2100, 230, 10000101, 19930523),
+    link(2100, 231, 19930524, 39991231),
+    link(2100, 232, 19930524, 39991231),
+    link(2100, 262, 10000101, 39991231),
+    link(2100, 266, 10000101, 39991231),
+    link(2100, 270, 10000101, 39991231),
+    link(2100, 288, 10000101, 39991231),
+    link(2100, 324, 10000101, 39991231),
+    link(2100, 384, 10000101, 39991231),
+    link(2100, 404, 10000101, 39991231),
+    link(2100, 426, 10000101, 39991231),
+    link(2100, 430, 10000101, 39991231),
+    link(2100, 450, 10000101, 39991231),
+    link(2100, 454, 10000101, 39991231),
+    link(2100, 466, 10000101, 39991231),
+    link(2100, 478, 10000101, 39991231),
+    link(2100, 480, 10000101, 39991231),
+    link(2100, 508, 10000101, 39991231),
+    link(2100, 516, 10000101, 39991231),
+    link(2100, 562, 10000101, 39991231),
+    link(2100, 566, 10000101, 39991231),
+    link(2100, 624, 10000101, 39991231),
+    link(2100, 646, 10000101, 39991231),
+    link(2100, 678, 10000101, 39991231),
+    link(2100, 686, 10000101, 39991231),
+    link(2100, 690, 10000101, 39991231),
+    link(2100, 694, 10000101, 39991231),
+    link(2100, 706, 10000101, 39991231),
+    link(2100, 710, 10000101, 39991231),
+    link(2100, 716, 10000101, 39991231),
+    link(2100, 728, 20110709, 39991231),
+    link(2100, 729, 20110709, 39991231),
+    link(2100, 732, 10000101, 39991231),
+    link(2100, 736, 10000101, 20110708),
+    link(2100, 748, 10000101, 39991231),
+    link(2100, 768, 10000101, 39991231),
+    link(2100, 800, 10000101, 39991231),
+    link(2100, 834, 10000101, 39991231),
+    link(2100, 854, 10000101, 39991231),
+    link(2100, 894, 10000101, 39991231),
+    link(2100, 2128, 10000101, 39991231),
+    link(2101, 2102, 10000101, 39991231),
+    link(2101, 2134, 10000101, 39991231),
+    link(2102, 2113, 10000101, 39991231),
+    link(2102, 2129, 10000101, 39991231),
+    link(2102, 2132, 10000101, 39991231),
+    link(2103, 28, 10000101, 39991231),
+    link(2103, 52, 10000101, 39991231),
+    link(2103, 192, 10000101, 39991231),
+    link(2103, 212, 10000101, 39991231),
+    link(2103, 214, 10000101, 39991231),
+    link(2103, 308, 10000101, 39991231),
+    link(2103, 332, 10000101, 39991231),
+    link(2103, 388, 10000101, 39991231),
+    link(2103, 630, 10000101, 39991231),
+    link(2103, 659, 10000101, 39991231),
+    link(2103, 662, 10000101, 39991231),
+    link(2103, 670, 10000101, 39991231),
+    link(2103, 780, 10000101, 39991231),
+    link(2104, 36, 19891107, 39991231),
+    link(2104, 96, 19891107, 39991231),
+    link(2104, 124, 19891107, 39991231),
+    link(2104, 152, 19940101, 39991231),
+    link(2104, 156, 19910101, 39991231),
+    link(2104, 158, 19910101, 39991231),
+    link(2104, 344, 19910101, 39991231),
+    link(2104, 360, 19891107, 39991231),
+    link(2104, 392, 19891107, 39991231),
+    link(2104, 410, 19891107, 39991231),
+    link(2104, 446, 19991220, 39991231),
+    link(2104, 458, 19891107, 39991231),
+    link(2104, 484, 19930101, 39991231),
+    link(2104, 554, 19891107, 39991231),
+    link(2104, 598, 19930101, 39991231),
+    link(2104, 604, 19980101, 39991231),
+    link(2104, 608, 19891107, 39991231),
+    link(2104, 630, 19891107, 39991231),
+    link(2104, 643, 19980101, 39991231),
+    link(2104, 702, 19891107, 39991231),
+    link(2104, 704, 19980101, 39991231),
+    link(2104, 764, 19891107, 39991231),
+    link(2104, 840, 19891107, 39991231),
+    link(2105, 96, 19840101, 39991231),
+    link(2105, 104, 19970723, 39991231),
+    link(2105, 116, 19990430, 39991231),
+    link(2105, 360, 19670808, 39991231),
+    link(2105, 418, 19970723, 39991231),
+    link(2105, 458, 19670808, 39991231),
+    link(2105, 608, 19670808, 39991231),
+    link(2105, 702, 19670808, 39991231),
+    link(2105, 704, 19950728, 39991231),
+    link(2105, 764, 19670808, 39991231),
+    link(2106, 31, 19920101, 39991231),
+    link(2106, 51, 19920101, 39991231),
+    link(2106, 156, 10000101, 39991231),
+    link(2106, 158, 10000101, 39991231),
+    link(2106, 196, 10000101, 39991231),
+    link(2106, 268, 19920101, 39991231),
+    link(2106, 344, 10000101, 39991231),
+    link(2106, 376, 10000101, 39991231),
+    link(2106, 392, 10000101, 39991231),
+    link(2106, 398, 19920101, 39991231),
+    link(2106, 408, 10000101, 39991231),
+    link(2106, 410, 10000101, 39991231),
+    link(2106, 417, 19920101, 39991231),
+    link(2106, 422, 10000101, 39991231),
+    link(2106, 446, 19991220, 39991231),
+    link(2106, 760, 10000101, 39991231),
+    link(2106, 762, 19920101, 39991231),
+    link(2106, 792, 10000101, 39991231),
+    link(2106, 795, 19920101, 39991231),
+    link(2106, 860, 19920101, 39991231),
+    link(2106, 2125, 10000101, 39991231),
+    link(2106, 2133, 10000101, 39991231),
+    link(2107, 36, 10000101, 39991231),
+    link(2107, 90, 10000101, 39991231),
+    link(2107, 242, 10000101, 39991231),
+    link(2107, 540, 10000101, 39991231),
+    link(2107, 548, 10000101, 39991231),
+    link(2107, 554, 10000101, 39991231),
+    link(2108, 8, 10000101, 39991231),
+    link(2108, 70, 19920101, 39991231),
+    link(2108, 100, 10000101, 39991231),
+    link(2108, 191, 19920101, 39991231),
+    link(2108, 300, 10000101, 39991231),
+    link(2108, 499, 20060603, 39991231),
+    link(2108, 642, 10000101, 39991231),
+    link(2108, 688, 20060603, 39991231),
+    link(2108, 705, 19920101, 39991231),
+    link(2108, 807, 19920101, 39991231),
+    link(2108, 890, 10000101, 19911231),
+    link(2108, 891, 19920101, 20060602),
+    link(2109, 233, 19920101, 39991231),
+    link(2109, 428, 19920101, 39991231),
+    link(2109, 440, 19920101, 39991231),
+    link(2110, 56, 19480101, 39991231),
+    link(2110, 442, 19480101, 39991231),
+    link(2110, 528, 19480101, 39991231),
+    link(2111, 372, 10000101, 39991231),
+    link(2111, 826, 10000101, 39991231),
+    link(2112, 28, 10000101, 39991231),
+    link(2112, 44, 10000101, 39991231),
+    link(2112, 52, 10000101, 39991231),
+    link(2112, 212, 10000101, 39991231),
+    link(2112, 308, 10000101, 39991231),
+    link(2112, 388, 10000101, 39991231),
+    link(2112, 659, 10000101, 39991231),
+    link(2112, 662, 10000101, 39991231),
+    link(2112, 670, 10000101, 39991231),
+    link(2112, 780, 10000101, 39991231),
+    link(2113, 84, 10000101, 39991231),
+    link(2113, 188, 10000101, 39991231),
+    link(2113, 222, 10000101, 39991231),
+    link(2113, 320, 10000101, 39991231),
+    link(2113, 340, 10000101, 39991231),
+    link(2113, 558, 10000101, 39991231),
+    link(2113, 591, 10000101, 39991231),
+    link(2114, 28, 19810101, 39991231),
+    link(2114, 44, 19730101, 39991231),
+    link(2114, 52, 19660101, 39991231),
+    link(2114, 84, 19810101, 39991231),
+    link(2114, 124, 19310101, 39991231),
+    link(2114, 212, 19780101, 39991231),
+    link(2114, 296, 19790101, 39991231),
+    link(2114, 308, 19740101, 39991231),
+    link(2114, 328, 19660101, 39991231),
+    link(2114, 388, 19620101, 39991231),
+    link(2114, 470, 19640101, 39991231),
+    link(2114, 520, 19680101, 39991231),
+    link(2114, 598, 19750101, 39991231),
+    link(2114, 659, 19830101, 39991231),
+    link(2114, 662, 19790101, 39991231),
+    link(2114, 670, 19790101, 39991231),
+    link(2114, 776, 19700101, 39991231),
+    link(2114, 780, 19620101, 39991231),
+    link(2114, 798, 19780101, 39991231),
+    link(2114, 826, 19310101, 39991231),
+    link(2114, 882, 19700101, 39991231),
+    link(2114, 2115, 19310101, 39991231),
+    link(2114, 2116, 19310101, 39991231),
+    link(2114, 2117, 19310101, 39991231),
+    link(2115, 72, 19660101, 39991231),
+    link(2115, 120, 19951101, 39991231),
+    link(2115, 270, 19650101, 39991231),
+    link(2115, 288, 19570101, 39991231),
+    link(2115, 404, 19630101, 39991231),
+    link(2115, 426, 19660101, 39991231),
+    link(2115, 454, 19640101, 39991231),
+    link(2115, 480, 19680101, 39991231),
+    link(2115, 508, 19951112, 39991231),
+    link(2115, 516, 19900101, 39991231),
+    link(2115, 566, 19600101, 39991231),
+    link(2115, 690, 19760101, 39991231),
+    link(2115, 694, 19610101, 39991231),
+    link(2115, 710, 19940601, 39991231),
+    link(2115, 716, 19800101, 20031206),
+    link(2115, 748, 19680101, 39991231),
+    link(2115, 800, 19620101, 39991231),
+    link(2115, 834, 19610101, 39991231),
+    link(2115, 894, 19640101, 39991231),
+    link(2116, 50, 19720101, 39991231),
+    link(2116, 96, 19840101, 39991231),
+    link(2116, 144, 19480101, 39991231),
+    link(2116, 196, 19610101, 39991231),
+    link(2116, 344, 19310101, 19970630),
+    link(2116, 356, 19470101, 39991231),
+    link(2116, 458, 19570101, 39991231),
+    link(2116, 462, 19820101, 39991231),
+    link(2116, 586, 19890101, 39991231),
+    link(2116, 702, 19650101, 39991231),
+    link(2117, 36, 19310101, 39991231),
+    link(2117, 90, 19780101, 39991231),
+    link(2117, 242, 19970101, 39991231),
+    link(2117, 548, 19800101, 39991231),
+    link(2117, 554, 19310101, 39991231),
+    link(2118, 31, 19920101, 39991231),
+    link(2118, 51, 19920101, 39991231),
+    link(2118, 112, 19920101, 39991231),
+    link(2118, 268, 19940101, 20090817),
+    link(2118, 398, 19920101, 39991231),
+    link(2118, 417, 19920101, 39991231),
+    link(2118, 498, 19940415, 39991231),
+    link(2118, 643, 19920101, 39991231),
+    link(2118, 762, 19920101, 39991231),
+    link(2118, 795, 19920101, 39991231),
+    link(2118, 804, 19920101, 20180519),
+    link(2118, 860, 19920101, 39991231),
+    link(2119, 8, 10000101, 39991231),
+    link(2119, 100, 10000101, 39991231),
+    link(2119, 112, 19920101, 39991231),
+    link(2119, 200, 10000101, 19921231),
+    link(2119, 203, 19930101, 39991231),
+    link(2119, 233, 19920101, 39991231),
+    link(2119, 278, 10000101, 19901002),
+    link(2119, 348, 10000101, 39991231),
+    link(2119, 428, 19920101, 39991231),
+    link(2119, 440, 19920101, 39991231),
+    link(2119, 498, 19920101, 39991231),
+    link(2119, 616, 10000101, 39991231),
+    link(2119, 642, 10000101, 39991231),
+    link(2119, 643, 19920101, 39991231),
+    link(2119, 703, 19930101, 39991231),
+    link(2119, 804, 19920101, 39991231),
+    link(2119, 810, 10000101, 19911231),
+    link(2120, 352, 10000101, 39991231),
+    link(2120, 470, 10000101, 39991231),
+    link(2120, 2111, 10000101, 39991231),
+    link(2120, 2122, 10000101, 39991231),
+    link(2121, 40, 19940101, 39991231),
+    link(2121, 56, 19940101, 39991231),
+    link(2121, 100, 20070101, 39991231),
+    link(2121, 196, 20040501, 39991231),
+    link(2121, 203, 20040501, 39991231),
+    link(2121, 208, 19940101, 39991231),
+    link(2121, 233, 20040501, 39991231),
+    link(2121, 246, 19940101, 39991231),
+    link(2121, 250, 19940101, 39991231),
+    link(2121, 276, 19940101, 39991231),
+    link(2121, 300, 19940101, 39991231),
+    link(2121, 348, 20040501, 39991231),
+    link(2121, 352, 19940101, 39991231),
+    link(2121, 372, 19940101, 39991231),
+    link(2121, 380, 19940101, 39991231),
+    link(2121, 428, 20040501, 39991231),
+    link(2121, 438, 19940101, 39991231),
+    link(2121, 440, 20040101, 39991231),
+    link(2121, 442, 19940101, 39991231),
+    link(2121, 470, 20040501, 39991231),
+    link(2121, 528, 19940101, 39991231),
+    link(2121, 578, 19940101, 39991231),
+    link(2121, 616, 20040501, 39991231),
+    link(2121, 620, 19940101, 39991231),
+    link(2121, 642, 20070101, 39991231),
+    link(2121, 703, 20040501, 39991231),
+    link(2121, 705, 20040501, 39991231),
+    link(2121, 724, 19940101, 39991231),
+    link(2121, 752, 19940101, 39991231),
+    link(2121, 826, 19940101, 20200131),
+    link(2122, 20, 10000101, 39991231),
+    link(2122, 40, 10000101, 39991231),
+    link(2122, 56, 10000101, 39991231),
+    link(2122, 70, 19920101, 39991231),
+    link(2122, 191, 19920101, 39991231),
+    link(2122, 246, 10000101, 39991231),
+    link(2122, 250, 10000101, 39991231),
+    link(2122, 276, 19901003, 39991231),
+    link(2122, 280, 10000101, 19901002),
+    link(2122, 300, 10000101, 39991231),
+    link(2122, 336, 10000101, 39991231),
+    link(2122, 380, 10000101, 39991231),
+    link(2122, 438, 10000101, 39991231),
+    link(2122, 442, 10000101, 39991231),
+    link(2122, 492, 10000101, 39991231),
+    link(2122, 499, 20060603, 39991231),
+    link(2122, 528, 10000101, 39991231),
+    link(2122, 620, 10000101, 39991231),
+    link(2122, 674, 10000101, 39991231),
+    link(2122, 688, 20060603, 39991231),
+    link(2122, 705, 19920101, 39991231),
+    link(2122, 724, 10000101, 39991231),
+    link(2122, 756, 10000101, 39991231),
+    link(2122, 807, 19920101, 39991231),
+    link(2122, 890, 10000101, 19911231),
+    link(2122, 891, 19920101, 20060602),
+    link(2122, 2119, 10000101, 39991231),
+    link(2122, 2131, 10000101, 39991231),
+    link(2123, 40, 19950101, 39991231),
+    link(2123, 56, 19580101, 39991231),
+    link(2123, 100, 20070101, 39991231),
+    link(2123, 191, 20130701, 39991231),
+    link(2123, 196, 20040501, 39991231),
+    link(2123, 203, 20040501, 39991231),
+    link(2123, 208, 19730101, 39991231),
+    link(2123, 233, 20040501, 39991231),
+    link(2123, 246, 19950101, 39991231),
+    link(2123, 250, 19580101, 39991231),
+    link(2123, 276, 19901003, 39991231),
+    link(2123, 280, 19580101, 19901002),
+    link(2123, 300, 19810101, 39991231),
+    link(2123, 348, 20040501, 39991231),
+    link(2123, 372, 19730101, 39991231),
+    link(2123, 380, 19580101, 39991231),
+    link(2123, 428, 20040501, 39991231),
+    link(2123, 440, 20040501, 39991231),
+    link(2123, 442, 19580101, 39991231),
+    link(2123, 470, 20040501, 39991231),
+    link(2123, 528, 19580101, 39991231),
+    link(2123, 616, 20040501, 39991231),
+    link(2123, 620, 19860101, 39991231),
+    link(2123, 642, 20070101, 39991231),
+    link(2123, 703, 20040501, 39991231),
+    link(2123, 705, 20040401, 39991231),
+    link(2123, 724, 19860101, 39991231),
+    link(2123, 752, 19950101, 39991231),
+    link(2123, 826, 19730101, 20200131),
+    link(2124, 40, 10000101, 39991231),
+    link(2124, 276, 19901003, 39991231),
+    link(2124, 280, 10000101, 19901002),
+    link(2124, 756, 10000101, 39991231),
+    link(2125, 4, 10000101, 39991231),
+    link(2125, 48, 10000101, 39991231),
+    link(2125, 50, 10000101, 39991231),
+    link(2125, 64, 10000101, 39991231),
+    link(2125, 144, 10000101, 39991231),
+    link(2125, 356, 10000101, 39991231),
+    link(2125, 364, 10000101, 39991231),
+    link(2125, 368, 10000101, 39991231),
+    link(2125, 400, 10000101, 39991231),
+    link(2125, 414, 10000101, 39991231),
+    link(2125, 462, 10000101, 39991231),
+    link(2125, 496, 10000101, 39991231),
+    link(2125, 512, 10000101, 39991231),
+    link(2125, 524, 10000101, 39991231),
+    link(2125, 586, 10000101, 39991231),
+    link(2125, 634, 10000101, 39991231),
+    link(2125, 682, 10000101, 39991231),
+    link(2125, 720, 10000101, 19900521),
+    link(2125, 784, 10000101, 39991231),
+    link(2125, 886, 10000101, 19900521),
+    link(2125, 887, 19900522, 39991231),
+    link(2126, 124, 19940101, 39991231),
+    link(2126, 484, 19940101, 39991231),
+    link(2126, 630, 19940101, 39991231),
+    link(2126, 840, 19940101, 39991231),
+    link(2127, 208, 10000101, 39991231),
+    link(2127, 246, 10000101, 39991231),
+    link(2127, 352, 10000101, 39991231),
+    link(2127, 578, 10000101, 39991231),
+    link(2127, 752, 10000101, 39991231),
+    link(2128, 12, 10000101, 39991231),
+    link(2128, 434, 10000101, 39991231),
+    link(2128, 504, 10000101, 39991231),
+    link(2128, 788, 10000101, 39991231),
+    link(2128, 818, 10000101, 39991231),
+    link(2129, 124, 10000101, 39991231),
+    link(2129, 484, 10000101, 39991231),
+    link(2129, 840, 10000101, 39991231),
+    link(2130, 258, 10000101, 39991231),
+    link(2130, 296, 10000101, 39991231),
+    link(2130, 520, 10000101, 39991231),
+    link(2130, 583, 10000101, 39991231),
+    link(2130, 584, 10000101, 39991231),
+    link(2130, 585, 10000101, 39991231),
+    link(2130, 598, 10000101, 39991231),
+    link(2130, 776, 10000101, 39991231),
+    link(2130, 798, 10000101, 39991231),
+    link(2130, 882, 10000101, 39991231),
+    link(2130, 2107, 10000101, 39991231),
+    link(2131, 208, 10000101, 39991231),
+    link(2131, 578, 10000101, 39991231),
+    link(2131, 752, 10000101, 39991231),
+    link(2132, 32, 10000101, 39991231),
+    link(2132, 68, 10000101, 39991231),
+    link(2132, 76, 10000101, 39991231),
+    link(2132, 152, 10000101, 39991231),
+    link(2132, 170, 10000101, 39991231),
+    link(2132, 218, 10000101, 39991231),
+    link(2132, 328, 10000101, 39991231),
+    link(2132, 600, 10000101, 39991231),
+    link(2132, 604, 10000101, 39991231),
+    link(2132, 740, 10000101, 39991231),
+    link(2132, 858, 10000101, 39991231),
+    link(2132, 862, 10000101, 39991231),
+    link(2133, 96, 10000101, 39991231),
+    link(2133, 104, 10000101, 39991231),
+    link(2133, 116, 10000101, 39991231),
+    link(2133, 360, 10000101, 39991231),
+    link(2133, 418, 10000101, 39991231),
+    link(2133, 458, 10000101, 39991231),
+    link(2133, 608, 10000101, 39991231),
+    link(2133, 626, 20020520, 39991231),
+    link(2133, 702, 10000101, 39991231),
+    link(2133, 704, 10000101, 39991231),
+    link(2133, 764, 10000101, 39991231),
+    link(2134, 44, 10000101, 39991231),
+    link(2134, 2103, 10000101, 39991231),
+    link(2136, 2100, 10000101, 39991231),
+    link(2136, 2101, 10000101, 39991231),
+    link(2136, 2106, 10000101, 39991231),
+    link(2136, 2120, 10000101, 39991231),
+    link(2136, 2130, 10000101, 39991231),
+];
+
+/// Gets the direct children of a TIS territory group valid on the given date
+pub fn get_children_at(parent: u16, date: NaiveDate) -> Vec<u16> {
+    links_for_parent(parent).filter(|l| l.is_valid_at(date)).map(|l| l.child).collect()
+}
+
+/// Gets the direct parents of a TIS territory valid on the given date
+pub fn get_parents_at(child: u16, date: NaiveDate) -> Vec<u16> {
+    TIS_HIERARCHY.iter().filter(|l| l.child == child && l.is_valid_at(date)).map(|l| l.parent).collect()
+}
+
+/// Checks if a TIS code has any children in the hierarchy (regardless of date)
+pub fn is_territory_group(code: u16) -> bool {
+    links_for_parent(code).next().is_some()
+}
+
+fn links_for_parent(parent: u16) -> impl Iterator<Item = &'static TisHierarchyLink> {
+    let start = TIS_HIERARCHY.partition_point(|l| l.parent < parent);
+    TIS_HIERARCHY[start..].iter().take_while(move |l| l.parent == parent)
+}
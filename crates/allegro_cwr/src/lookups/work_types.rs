@@ -1,5 +1,6 @@
 //! Work types lookup table
 
+use crate::lookups::registry::{LookupTable, override_contains};
 use once_cell::sync::Lazy;
 use std::collections::HashMap;
 
@@ -41,6 +42,9 @@ pub static WORK_TYPES: Lazy<HashMap<&'static str, &'static str>> = Lazy::new(||
 /// Validates a work type code
 #[must_use]
 pub fn is_valid_work_type(code: &str) -> bool {
+    if let Some(valid) = override_contains(LookupTable::WorkTypes, code) {
+        return valid;
+    }
     WORK_TYPES.contains_key(code)
 }
 
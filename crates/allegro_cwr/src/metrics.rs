@@ -0,0 +1,269 @@
+//! Aggregate metrics over a CWR transmission, serializable for monitoring across deliveries
+
+use crate::cwr_handler::{CwrHandler, HandlerReport};
+use crate::cwr_registry::CwrRegistry;
+use crate::domain_types::{CharacterSet, Date, OwnershipShare};
+use crate::error::CwrParseError;
+use crate::parser::ParsedRecord;
+use crate::util::format_int_with_commas;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Counts of shares falling in each quarter of the 0-100% range
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShareBuckets {
+    pub zero: usize,
+    pub up_to_25: usize,
+    pub up_to_50: usize,
+    pub up_to_75: usize,
+    pub up_to_100: usize,
+}
+
+/// Distribution of one share field, in hundredths of a percent (10000 = 100%)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ShareDistribution {
+    pub count: usize,
+    pub min: u16,
+    pub max: u16,
+    pub total: u64,
+    pub buckets: ShareBuckets,
+}
+
+impl ShareDistribution {
+    fn add(&mut self, share: u16) {
+        if self.count == 0 || share < self.min {
+            self.min = share;
+        }
+        self.max = self.max.max(share);
+        self.count += 1;
+        self.total += u64::from(share);
+        let bucket = match share {
+            0 => &mut self.buckets.zero,
+            1..=2500 => &mut self.buckets.up_to_25,
+            2501..=5000 => &mut self.buckets.up_to_50,
+            5001..=7500 => &mut self.buckets.up_to_75,
+            _ => &mut self.buckets.up_to_100,
+        };
+        *bucket += 1;
+    }
+
+    /// Average share in hundredths of a percent
+    pub fn mean(&self) -> f64 {
+        if self.count == 0 { 0.0 } else { self.total as f64 / self.count as f64 }
+    }
+}
+
+/// Earliest and latest value of one date field
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DateRange {
+    pub earliest: NaiveDate,
+    pub latest: NaiveDate,
+}
+
+/// Metrics gathered by [`MetricsHandler`]. Map keys are record types, or the record type and field name for
+/// share and date metrics (e.g. `SWR pr_ownership_share`).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TransmissionMetrics {
+    pub record_count: usize,
+    pub parse_error_count: usize,
+    pub warning_count: usize,
+    pub record_types: BTreeMap<String, usize>,
+    pub warnings_by_record_type: BTreeMap<String, usize>,
+    pub shares: BTreeMap<String, ShareDistribution>,
+    pub date_ranges: BTreeMap<String, DateRange>,
+    /// Records per character set declared in the header (ASCII when none is declared)
+    pub character_sets: BTreeMap<String, usize>,
+}
+
+impl TransmissionMetrics {
+    fn add_share(&mut self, record_type: &str, field: &str, share: Option<&OwnershipShare>) {
+        if let Some(share) = share {
+            self.shares.entry(format!("{} {}", record_type, field)).or_default().add(share.0);
+        }
+    }
+
+    fn add_date(&mut self, record_type: &str, field: &str, date: Option<&Date>) {
+        let Some(Date(date)) = date else {
+            return;
+        };
+        self.date_ranges
+            .entry(format!("{} {}", record_type, field))
+            .and_modify(|range| {
+                range.earliest = range.earliest.min(*date);
+                range.latest = range.latest.max(*date);
+            })
+            .or_insert(DateRange { earliest: *date, latest: *date });
+    }
+}
+
+impl fmt::Display for TransmissionMetrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let count = |n: usize| format_int_with_commas(n as i64);
+
+        writeln!(f, "Transmission metrics:")?;
+        writeln!(f, "  Records: {}", count(self.record_count))?;
+        writeln!(f, "  Parse errors: {}", count(self.parse_error_count))?;
+        writeln!(f, "  Warnings: {}", count(self.warning_count))?;
+        writeln!(f, "  Record types:")?;
+        for (record_type, n) in &self.record_types {
+            writeln!(f, "    {:<5} {:>10}", record_type, count(*n))?;
+        }
+        if !self.shares.is_empty() {
+            writeln!(f, "  Shares (count, min, mean, max %):")?;
+            for (field, shares) in &self.shares {
+                writeln!(
+                    f,
+                    "    {:<26} {:>8} {:>7.2} {:>7.2} {:>7.2}",
+                    field,
+                    count(shares.count),
+                    f64::from(shares.min) / 100.0,
+                    shares.mean() / 100.0,
+                    f64::from(shares.max) / 100.0
+                )?;
+            }
+        }
+        if !self.date_ranges.is_empty() {
+            writeln!(f, "  Date ranges:")?;
+            for (field, range) in &self.date_ranges {
+                writeln!(f, "    {:<26} {} to {}", field, range.earliest, range.latest)?;
+            }
+        }
+        write!(f, "  Character sets:")?;
+        for (charset, n) in &self.character_sets {
+            write!(f, "\n    {:<12} {:>10}", charset, count(*n))?;
+        }
+        Ok(())
+    }
+}
+
+/// Computes [`TransmissionMetrics`] while records stream past
+#[derive(Debug, Default)]
+pub struct MetricsHandler {
+    metrics: TransmissionMetrics,
+}
+
+impl MetricsHandler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds one record to the metrics
+    pub fn observe(&mut self, parsed_record: &ParsedRecord) {
+        let metrics = &mut self.metrics;
+        let record_type = parsed_record.record.record_type();
+
+        metrics.record_count += 1;
+        *metrics.record_types.entry(record_type.to_string()).or_insert(0) += 1;
+        let charset = parsed_record.context.character_set.as_ref().unwrap_or(&CharacterSet::ASCII);
+        *metrics.character_sets.entry(charset.as_str().to_string()).or_insert(0) += 1;
+        if !parsed_record.warnings.is_empty() {
+            metrics.warning_count += parsed_record.warnings.len();
+            *metrics.warnings_by_record_type.entry(record_type.to_string()).or_insert(0) +=
+                parsed_record.warnings.len();
+        }
+
+        match &parsed_record.record {
+            CwrRegistry::Hdr(hdr) => {
+                metrics.add_date(record_type, "creation_date", Some(&hdr.creation_date));
+                metrics.add_date(record_type, "transmission_date", Some(&hdr.transmission_date));
+            }
+            CwrRegistry::Agr(agr) => {
+                metrics.add_date(record_type, "agreement_start_date", Some(&agr.agreement_start_date));
+                metrics.add_date(record_type, "agreement_end_date", agr.agreement_end_date.as_ref());
+            }
+            CwrRegistry::Nwr(nwr) => metrics.add_date(record_type, "copyright_date", nwr.copyright_date.as_ref()),
+            CwrRegistry::Spu(spu) => {
+                metrics.add_share(record_type, "pr_ownership_share", spu.pr_ownership_share.as_ref());
+                metrics.add_share(record_type, "mr_ownership_share", spu.mr_ownership_share.as_ref());
+                metrics.add_share(record_type, "sr_ownership_share", spu.sr_ownership_share.as_ref());
+            }
+            CwrRegistry::Swr(swr) => {
+                metrics.add_share(record_type, "pr_ownership_share", swr.pr_ownership_share.as_ref());
+                metrics.add_share(record_type, "mr_ownership_share", swr.mr_ownership_share.as_ref());
+                metrics.add_share(record_type, "sr_ownership_share", swr.sr_ownership_share.as_ref());
+            }
+            CwrRegistry::Spt(spt) => {
+                metrics.add_share(record_type, "pr_collection_share", spt.pr_collection_share.as_ref());
+                metrics.add_share(record_type, "mr_collection_share", spt.mr_collection_share.as_ref());
+                metrics.add_share(record_type, "sr_collection_share", spt.sr_collection_share.as_ref());
+            }
+            CwrRegistry::Swt(swt) => {
+                metrics.add_share(record_type, "pr_collection_share", swt.pr_collection_share.as_ref());
+                metrics.add_share(record_type, "mr_collection_share", swt.mr_collection_share.as_ref());
+                metrics.add_share(record_type, "sr_collection_share", swt.sr_collection_share.as_ref());
+            }
+            _ => {}
+        }
+    }
+
+    /// The metrics of the records observed so far
+    pub fn metrics(&self) -> &TransmissionMetrics {
+        &self.metrics
+    }
+
+    pub fn into_metrics(self) -> TransmissionMetrics {
+        self.metrics
+    }
+}
+
+impl CwrHandler for MetricsHandler {
+    type Error = std::convert::Infallible;
+
+    fn process_record(&mut self, record: ParsedRecord) -> Result<(), Self::Error> {
+        self.observe(&record);
+        Ok(())
+    }
+
+    fn handle_parse_error(&mut self, _line_number: usize, _error: &CwrParseError) -> Result<(), Self::Error> {
+        self.metrics.parse_error_count += 1;
+        Ok(())
+    }
+
+    fn finalize(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn get_report(&self) -> String {
+        self.metrics.to_string()
+    }
+
+    fn report(&self) -> HandlerReport {
+        HandlerReport::Metrics(self.metrics.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_share_distribution() {
+        let mut shares = ShareDistribution::default();
+        for share in [0, 2500, 5000, 10000] {
+            shares.add(share);
+        }
+        assert_eq!((shares.count, shares.min, shares.max, shares.total), (4, 0, 10000, 17500));
+        assert_eq!(shares.buckets, ShareBuckets { zero: 1, up_to_25: 1, up_to_50: 1, up_to_75: 0, up_to_100: 1 });
+        assert_eq!(shares.mean(), 4375.0);
+    }
+
+    #[test]
+    fn test_metrics_handler() {
+        let content = "HDRPB285606836WARNER CHAPPELL MUSIC PUBLISHING LTD         01.102022122112541120221221\n\
+                       GRHNWR0000102.100000000000  \nGRT000010000000000000002\nTRL000000010000000000000004\n";
+        let records = crate::process_cwr_bytes_stream(content.as_bytes(), &crate::ParseOptions::default()).unwrap();
+        let mut handler = MetricsHandler::new();
+        for record in records {
+            handler.observe(&record.unwrap());
+        }
+        let metrics = handler.into_metrics();
+
+        assert_eq!(metrics.record_count, 4);
+        assert_eq!(metrics.record_types.get("GRH"), Some(&1));
+        assert_eq!(metrics.character_sets.get("ASCII"), Some(&4));
+        let transmission = metrics.date_ranges.get("HDR transmission_date").unwrap();
+        assert_eq!(transmission.earliest, NaiveDate::from_ymd_opt(2022, 12, 21).unwrap());
+    }
+}
@@ -0,0 +1,189 @@
+//! Putting the detail records of a transaction back into the order the specification requires
+//!
+//! Some exporters write a work's records grouped by type (all SPTs after all SPUs) or in no particular order.
+//! Records that belong to the record before them (a publisher's NPN and territories, a writer's NWN, territories
+//! and PWRs, the non-Roman title and names of an EWT/VER/COM) move with it, so normalizing only moves whole
+//! blocks and sorts records within a block. Records of the same kind keep their relative order.
+
+use crate::cwr_registry::{CwrRegistry, record_type_info};
+
+/// Where a record type goes in its transaction
+#[derive(Clone, Copy)]
+enum Slot {
+    /// Starts a block, placed by rank
+    Block(u8),
+    /// Belongs to the preceding block when it starts with one of the record's parents (see
+    /// [`crate::RecordTypeInfo::parents`]), placed by rank within it
+    Follower(u8),
+}
+
+/// NWR/REV/ISW/EXC: publishers with their territories, writers with theirs and their publishers, then the
+/// optional records in specification order
+fn work_slot(record_type: &str) -> Slot {
+    match record_type {
+        "SPU" => Slot::Block(0),
+        "OPU" => Slot::Block(1),
+        "SWR" => Slot::Block(2),
+        "OWR" => Slot::Block(3),
+        "ALT" => Slot::Block(4),
+        "NAT" => Slot::Block(5),
+        "EWT" => Slot::Block(6),
+        "VER" => Slot::Block(7),
+        "PER" => Slot::Block(8),
+        "NPR" => Slot::Block(9),
+        "REC" => Slot::Block(10),
+        "ORN" => Slot::Block(11),
+        "INS" => Slot::Block(12),
+        "IND" => Slot::Block(13),
+        "COM" => Slot::Block(14),
+        "ARI" => Slot::Block(15),
+        "XRF" => Slot::Block(16),
+        "NPN" | "NWN" | "NET" | "NCT" | "NVT" => Slot::Follower(1),
+        "SPT" | "OPT" | "SWT" | "OWT" | "NOW" => Slot::Follower(2),
+        "PWR" => Slot::Follower(3),
+        _ => Slot::Block(17),
+    }
+}
+
+/// AGR: territories, then the interested parties with their non-Roman names
+fn agreement_slot(record_type: &str) -> Slot {
+    match record_type {
+        "TER" => Slot::Block(0),
+        "IPA" => Slot::Block(1),
+        "NPA" => Slot::Follower(1),
+        _ => Slot::Block(2),
+    }
+}
+
+struct Block {
+    rank: u8,
+    leader: String,
+    /// Rank within the block and position in the transaction of each record
+    records: Vec<(u8, usize)>,
+}
+
+/// Reorders the detail records of one transaction (header first) into specification order and renumbers their
+/// record sequence numbers from the header's, keeping its transaction sequence number. Work registrations
+/// (NWR/REV/ISW/EXC) and agreements are reordered; other transactions are only renumbered. Returns whether any
+/// record moved.
+pub fn normalize_record_order(records: &mut Vec<CwrRegistry>) -> bool {
+    let Some(header) = records.first() else {
+        return false;
+    };
+    let transaction_sequence = header.sequence_numbers().map_or(0, |(transaction, _)| transaction);
+    let slot: Option<fn(&str) -> Slot> = match header {
+        CwrRegistry::Nwr(_) => Some(work_slot),
+        CwrRegistry::Agr(_) => Some(agreement_slot),
+        _ => None,
+    };
+
+    let mut moved = false;
+    if let Some(slot) = slot {
+        let mut blocks: Vec<Block> = Vec::new();
+        for (index, record) in records.iter().enumerate().skip(1) {
+            let record_type = record.record_type();
+            match slot(record_type) {
+                Slot::Block(rank) => {
+                    blocks.push(Block { rank, leader: record_type.to_string(), records: vec![(0, index)] })
+                }
+                Slot::Follower(rank) => {
+                    let parents = record_type_info(record_type).map_or(&[][..], |info| info.parents);
+                    match blocks.last_mut() {
+                        Some(block) if parents.contains(&block.leader.as_str()) => block.records.push((rank, index)),
+                        // Without its parent the record is placed where the parent would go
+                        _ => {
+                            let parent_rank = match parents.first().map(|parent| slot(parent)) {
+                                Some(Slot::Block(rank)) => rank,
+                                _ => u8::MAX,
+                            };
+                            blocks.push(Block {
+                                rank: parent_rank,
+                                leader: record_type.to_string(),
+                                records: vec![(rank, index)],
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        blocks.sort_by_key(|block| block.rank);
+        let mut order = vec![0];
+        for mut block in blocks {
+            block.records.sort_by_key(|(rank, _)| *rank);
+            order.extend(block.records.into_iter().map(|(_, index)| index));
+        }
+        moved = order.iter().enumerate().any(|(position, index)| position != *index);
+        if moved {
+            let mut taken: Vec<Option<CwrRegistry>> = std::mem::take(records).into_iter().map(Some).collect();
+            *records = order.into_iter().filter_map(|index| taken[index].take()).collect();
+        }
+    }
+
+    for (record_sequence, record) in records.iter_mut().enumerate() {
+        record.set_sequence_numbers(transaction_sequence, record_sequence as u32);
+    }
+    moved
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::{PublisherBuilder, Shares, WorkBuilder, WriterBuilder};
+    use crate::domain_types::{CharacterSet, CwrVersion, TitleType};
+
+    fn lines(records: &[CwrRegistry]) -> Vec<String> {
+        records
+            .iter()
+            .map(|record| {
+                String::from_utf8(record.to_cwr_record_bytes(&CwrVersion(2.2), &CharacterSet::ASCII)).unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_normalize_work_order() {
+        let work = WorkBuilder::new("FIRST WORK", "SW0000000001")
+            .publisher(
+                PublisherBuilder::new("000000001", "ACME MUSIC")
+                    .ownership(Shares::new(5000, 10000, 10000))
+                    .territory(2136, Shares::new(5000, 10000, 10000)),
+            )
+            .writer(
+                WriterBuilder::new("000000002", "SMITH")
+                    .ownership(Shares::new(5000, 0, 0))
+                    .territory(2136, Shares::new(5000, 0, 0))
+                    .represented_by("000000001"),
+            )
+            .alternate_title("OTHER TITLE", TitleType::AlternativeTitle);
+        let mut expected = work.build(3).unwrap();
+        let types: Vec<&str> = expected.iter().map(CwrRegistry::record_type).collect();
+        assert_eq!(types, ["NWR", "SPU", "SPT", "SWR", "SWT", "PWR", "ALT"]);
+        normalize_record_order(&mut expected);
+
+        // ALT first, writer before publisher, PWR before SWT
+        let mut records: Vec<CwrRegistry> =
+            [0, 6, 3, 5, 4, 1, 2].into_iter().map(|index| expected[index].clone()).collect();
+        assert!(normalize_record_order(&mut records));
+        assert_eq!(lines(&records), lines(&expected));
+        assert_eq!(records[6].sequence_numbers(), Some((3, 6)));
+        assert!(!normalize_record_order(&mut records));
+    }
+
+    #[test]
+    fn test_orphan_territory_goes_with_publishers() {
+        let work = WorkBuilder::new("FIRST WORK", "SW0000000001")
+            .publisher(
+                PublisherBuilder::new("000000001", "ACME MUSIC")
+                    .ownership(Shares::new(5000, 0, 0))
+                    .territory(2136, Shares::new(5000, 0, 0)),
+            )
+            .writer(WriterBuilder::new("000000002", "SMITH").ownership(Shares::new(5000, 0, 0)));
+        let built = work.build(0).unwrap();
+        // SWR, SPT, SPU: the SPT comes before its SPU and stays before it
+        let mut records = vec![built[0].clone(), built[3].clone(), built[2].clone(), built[1].clone()];
+        assert!(normalize_record_order(&mut records));
+        let types: Vec<&str> = records.iter().map(CwrRegistry::record_type).collect();
+        assert_eq!(types, ["NWR", "SPT", "SPU", "SWR"]);
+    }
+}
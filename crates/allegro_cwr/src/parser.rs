@@ -1,13 +1,21 @@
 use crate::ascii_io::{AsciiLineReader, AsciiStreamSniffer};
+use crate::charset_detect::DETECTION_SAMPLE_SIZE;
+#[cfg(feature = "fs")]
+use crate::compression::open_input;
+use crate::compression::{decompress, decompressing};
 use crate::cwr_registry::CwrRegistry;
-use crate::error::CwrParseError;
-use log::{error, info};
-use std::fs::File;
-
-#[cfg(test)]
+use crate::domain_types::CharacterSet;
+use crate::error::{CwrParseError, ParseErrorCode, RecordParseError};
+use crate::parsing::{CwrLine, CwrWarning, Interner, WarningLevel};
+use crate::recovery::{TransactionTracker, find_record_prefix};
+use crate::util::{VersionDetection, VersionSource};
+use std::collections::VecDeque;
+use std::io::{self, Read};
+use std::ops::Range;
+use tracing::{error, info, warn};
+
+#[cfg(all(test, feature = "fs"))]
 use crate::util::get_cwr_version;
-#[cfg(test)]
-use std::io;
 
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct ParsingContext {
@@ -22,111 +30,512 @@ pub struct ParsedRecord {
     pub line_number: usize,
     pub record: CwrRegistry,
     pub context: ParsingContext,
-    pub warnings: Vec<String>,
+    /// Field and record warnings; serialized as their messages
+    pub warnings: Vec<CwrWarning<'static>>,
+    /// The source line as received (decoded, without the line ending); set when
+    /// [`ParseOptions::keep_raw_lines`] is enabled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub raw_line: Option<String>,
+    /// The source bytes of the record exactly as read (without the line ending), for byte-exact auditing or
+    /// re-emitting an untouched record; set when [`ParseOptions::keep_raw_bytes`] is enabled
+    #[serde(skip)]
+    pub raw_bytes: Option<Vec<u8>>,
+}
+
+impl ParsedRecord {
+    /// Zero-based byte range, within the record, of the field a warning is about; `None` for warnings about the
+    /// record as a whole. [`CwrLine::char_columns`] converts it to a character range.
+    pub fn warning_columns(&self, warning: &CwrWarning) -> Option<Range<usize>> {
+        self.record.field_columns(warning.field_name, self.context.cwr_version)
+    }
 }
 
 /// Checks if a file is a CWR file by validating the header
-/// Returns true if the file starts with "HDR", false otherwise
+/// Returns true if the file starts with "HDR" (after decompressing gzipped content), false otherwise
 /// Returns an error if the file cannot be read or has invalid content
+#[cfg(feature = "fs")]
 pub fn is_cwr_file(filename: &str) -> Result<bool, CwrParseError> {
-    let file = File::open(filename)?;
-    let mut sniffer = AsciiStreamSniffer::new(file);
+    starts_with_cwr_header(open_input(filename)?)
+}
+
+/// Like [`is_cwr_file`], for content held in memory (e.g. read from stdin)
+pub fn is_cwr_bytes(bytes: &[u8]) -> Result<bool, CwrParseError> {
+    starts_with_cwr_header(decompress(bytes)?.as_ref())
+}
 
-    match sniffer.validate_cwr_header() {
+fn starts_with_cwr_header<R: io::Read>(reader: R) -> Result<bool, CwrParseError> {
+    match AsciiStreamSniffer::new(reader).validate_cwr_header() {
         Ok(()) => Ok(true),
         Err(CwrParseError::InvalidHeader { .. }) => Ok(false),
         Err(e) => Err(e),
     }
 }
 
-/// Parses a single CWR line and returns the parsed record
-fn parse_cwr_line(line: &str, line_number: usize, context: &ParsingContext) -> Result<ParsedRecord, CwrParseError> {
-    let record_type = line
-        .get(0..3)
-        .ok_or_else(|| CwrParseError::BadFormat(format!("Line {} is too short (less than 3 chars)", line_number)))?;
+/// Parses a single record line outside of a file, e.g. one received from a queue. The line is the decoded text;
+/// field widths are counted in bytes of `character_set`.
+pub fn parse_cwr_line(
+    line: &str, version: f32, character_set: &CharacterSet,
+) -> Result<(CwrRegistry, Vec<CwrWarning<'static>>), CwrParseError> {
+    let encoded;
+    let line = if line.is_ascii() || crate::encoding::is_utf8_compatible(character_set) {
+        CwrLine::new(line)
+    } else {
+        encoded = crate::encoding::encode(line, character_set);
+        CwrLine::with_character_set(&encoded, character_set)
+    };
+    parse_record(&line, version)
+}
+
+fn parse_record(line: &CwrLine<'_>, version: f32) -> Result<(CwrRegistry, Vec<CwrWarning<'static>>), CwrParseError> {
+    if line.len() < 3 {
+        return Err(RecordParseError::new(ParseErrorCode::LineTooShort, "Line is too short (less than 3 chars)").into());
+    }
+    let record_type = line.field(0, 3);
+    let (record, warnings) = crate::cwr_registry::parse_line_by_record_type(&record_type, line, Some(version))?;
+    record.check_version(version).map_err(|e| {
+        RecordParseError::new(ParseErrorCode::RecordNotInVersion, e.to_string()).with_record_type(record_type.as_ref())
+    })?;
+    Ok((record, warnings))
+}
 
-    let (record, warnings) = crate::cwr_registry::parse_by_record_type(record_type, line)?;
+/// Parses a line of a file and returns the parsed record
+fn parse_line_in_context(
+    line: &CwrLine<'_>, line_number: usize, context: &ParsingContext,
+) -> Result<ParsedRecord, CwrParseError> {
+    if line.len() < 3 {
+        return Err(line_too_short_error(line_number));
+    }
+    let (record, warnings) = parse_record(line, context.cwr_version).map_err(|e| match e {
+        CwrParseError::Record(mut err) => {
+            if err.code == ParseErrorCode::RecordNotInVersion {
+                err.message = format!("Line {}: {}", line_number, err.message);
+            }
+            err.line_number.get_or_insert(line_number);
+            CwrParseError::Record(err)
+        }
+        other => other,
+    })?;
 
-    Ok(ParsedRecord { line_number, record, context: context.clone(), warnings })
+    Ok(ParsedRecord { line_number, record, context: context.clone(), warnings, raw_line: None, raw_bytes: None })
 }
 
-/// Returns an iterator that processes CWR lines and yields parsed records
-pub fn process_cwr_stream(
-    input_filename: &str,
-) -> Result<impl Iterator<Item = Result<ParsedRecord, CwrParseError>>, CwrParseError> {
-    process_cwr_stream_with_version(input_filename, None)
+/// Samples the file content and replaces a blank or mismatched HDR character set with the detected one.
+/// Returns the warning to report on the HDR record when the character set was replaced.
+fn resolve_character_set(
+    sample: impl io::Read, header_info: &mut crate::ascii_io::CwrHeaderInfo,
+) -> Result<Option<String>, CwrParseError> {
+    let Some(detected) = crate::charset_detect::detect_character_set_from_reader(sample)? else {
+        return Ok(None);
+    };
+    if crate::charset_detect::is_compatible(&header_info.character_set, &detected) {
+        return Ok(None);
+    }
+
+    let declared = match &header_info.character_set {
+        Some(charset) => format!("declares '{}'", charset.as_str()),
+        None => "has no character set".to_string(),
+    };
+    let message = format!(
+        "Character set: HDR {} but the file content looks like {}; reading it as {}",
+        declared,
+        detected.as_str(),
+        detected.as_str()
+    );
+    warn!("{}", message);
+    header_info.character_set = Some(detected);
+    Ok(Some(message))
 }
 
-/// Returns an iterator that processes CWR lines and yields parsed records with optional version hint and character set override
-pub fn process_cwr_stream_with_version_and_charset(
-    input_filename: &str, version_hint: Option<f32>, charset_override: Option<&str>,
-) -> Result<impl Iterator<Item = Result<ParsedRecord, CwrParseError>>, CwrParseError> {
-    // Validate header and detect version in one operation!
-    let file = File::open(input_filename)?;
-    let mut sniffer = AsciiStreamSniffer::new(file);
-    let mut header_info = match sniffer.validate_and_detect_version(input_filename, version_hint) {
-        Err(CwrParseError::InvalidHeader { found_bytes }) if found_bytes.is_empty() => {
-            return Err(CwrParseError::BadFormat("File is empty".to_string()));
+fn empty_line_error(line_number: usize) -> CwrParseError {
+    RecordParseError::new(ParseErrorCode::EmptyLine, format!("Line {} is empty", line_number))
+        .with_line_number(line_number)
+        .into()
+}
+
+fn line_too_short_error(line_number: usize) -> CwrParseError {
+    RecordParseError::new(
+        ParseErrorCode::LineTooShort,
+        format!("Line {} is too short (less than 3 chars)", line_number),
+    )
+    .with_line_number(line_number)
+    .into()
+}
+
+/// Shifts the columns of an error found in a segment starting at `start`, noting where parsing resumed
+fn resynchronized_error(parse_err: CwrParseError, start: usize, resumed_at: Option<usize>) -> CwrParseError {
+    match parse_err {
+        CwrParseError::Record(mut err) => {
+            err.columns = err.columns.map(|columns| columns.start + start..columns.end + start);
+            if let Some(resumed_at) = resumed_at {
+                err.message = format!("{}; resynchronized at column {}", err.message, resumed_at + 1);
+            }
+            CwrParseError::Record(err)
         }
-        Err(CwrParseError::InvalidHeader { found_bytes }) => {
-            return Err(CwrParseError::BadFormat(format!(
-                "File does not start with HDR record. Found: {:?}",
-                String::from_utf8_lossy(&found_bytes)
-            )));
+        other => match resumed_at {
+            Some(resumed_at) => {
+                CwrParseError::BadFormat(format!("{}; resynchronized at column {}", other, resumed_at + 1))
+            }
+            None => other,
+        },
+    }
+}
+
+/// Iterator over the parsed records of a file. Adds the character set detection warning (if any) to the HDR record
+/// and, when enabled, holds back one record so blank lines at the end of the file can be reported on it.
+struct RecordStream<I> {
+    lines: I,
+    line_number: usize,
+    context: ParsingContext,
+    character_set: CharacterSet,
+    /// Warnings about the header as a whole, reported on the HDR record
+    header_warnings: Vec<CwrWarning<'static>>,
+    version_detection: VersionDetection,
+    skip_trailing_blank_lines: bool,
+    keep_raw_lines: bool,
+    keep_raw_bytes: bool,
+    min_warning_level: Option<WarningLevel>,
+    raise_warnings_to: Option<WarningLevel>,
+    record_types: Option<Vec<String>>,
+    /// Set in recovery mode
+    tracker: Option<TransactionTracker>,
+    blank_lines: Vec<usize>,
+    ready: VecDeque<Result<ParsedRecord, CwrParseError>>,
+    last: Option<Result<ParsedRecord, CwrParseError>>,
+    /// Shares the short codes of this file's records
+    interner: Interner,
+}
+
+impl<I: Iterator<Item = Result<Vec<u8>, CwrParseError>>> RecordStream<I> {
+    fn new(lines: I, header: HeaderFindings, options: &ParseOptions) -> Self {
+        let HeaderFindings { context, warnings: header_warnings, version_detection } = header;
+        let character_set = context.character_set.clone().unwrap_or_default();
+        Self {
+            lines,
+            line_number: 0,
+            context,
+            character_set,
+            header_warnings,
+            version_detection,
+            skip_trailing_blank_lines: options.skip_trailing_blank_lines,
+            keep_raw_lines: options.keep_raw_lines,
+            keep_raw_bytes: options.keep_raw_bytes,
+            min_warning_level: options.min_warning_level.clone(),
+            raise_warnings_to: options.raise_warnings_to.clone(),
+            record_types: options.record_types.clone(),
+            tracker: options.recover_malformed_lines.then(TransactionTracker::default),
+            blank_lines: Vec::new(),
+            ready: VecDeque::new(),
+            last: None,
+            interner: Interner::default(),
         }
-        Err(e) => return Err(e),
-        Ok(info) => info.clone(),
-    };
+    }
 
-    // Override character set if provided
-    if let Some(charset_str) = charset_override {
-        use crate::parsing::CwrFieldParse;
-        let (charset_opt, _) = <Option<crate::domain_types::CharacterSet>>::parse_cwr_field(
-            charset_str,
-            "character_set_override",
-            "Character set override",
-        );
-        header_info.character_set = charset_opt;
-        info!("Character set overridden to: {:?}", header_info.character_set);
+    /// False for lines of a record type outside `record_types`; these are skipped before parsing. Lines without
+    /// a record type code are parsed so their errors are reported.
+    fn is_wanted(&self, bytes: &[u8]) -> bool {
+        let (Some(record_types), Some(code)) = (&self.record_types, bytes.get(..3)) else {
+            return true;
+        };
+        !code.iter().all(u8::is_ascii_uppercase)
+            || matches!(code, b"HDR" | b"GRH" | b"GRT" | b"TRL")
+            || record_types.iter().any(|record_type| record_type.as_bytes() == code)
     }
 
-    let cwr_version = header_info.version;
-    info!("Determined CWR version: {}", cwr_version);
+    fn parse_line(&mut self, line_number: usize, bytes: &[u8]) -> Result<ParsedRecord, CwrParseError> {
+        if bytes.iter().all(u8::is_ascii_whitespace) {
+            return Err(empty_line_error(line_number));
+        }
+        if bytes.len() < 3 {
+            return Err(line_too_short_error(line_number));
+        }
 
-    let context = ParsingContext { cwr_version, file_id: 0, character_set: header_info.character_set.clone() };
+        let mut parsed = if crate::encoding::is_utf8_compatible(&self.character_set) {
+            let text = std::str::from_utf8(bytes).map_err(|e| {
+                let position = e.valid_up_to();
+                RecordParseError::new(
+                    ParseErrorCode::InvalidEncoding,
+                    format!(
+                        "Line {}: invalid byte sequence at position {} for character set {}",
+                        line_number,
+                        position,
+                        self.character_set.as_str()
+                    ),
+                )
+                .with_line_number(line_number)
+                .with_columns(position..position + 1)
+            })?;
+            parse_line_in_context(&CwrLine::new(text), line_number, &self.context)?
+        } else {
+            parse_line_in_context(&CwrLine::with_character_set(bytes, &self.character_set), line_number, &self.context)?
+        };
+        if !self.header_warnings.is_empty() {
+            parsed.warnings.splice(0..0, std::mem::take(&mut self.header_warnings));
+        }
+        if let CwrRegistry::Grh(grh) = &parsed.record
+            && let Ok(grh_version) = grh.version_number.as_str().trim().parse::<f32>()
+            && format!("{:.1}", grh_version) != format!("{:.1}", self.context.cwr_version)
+        {
+            let detection = self.version_detection.with_source(VersionSource::GrhVersion, grh_version);
+            parsed.warnings.push(CwrWarning::record(WarningLevel::Warning, detection.to_string()));
+        }
+        if self.keep_raw_lines {
+            parsed.raw_line = Some(crate::encoding::decode_lossy(bytes, &self.character_set).into_owned());
+        }
+        if self.keep_raw_bytes {
+            parsed.raw_bytes = Some(bytes.to_vec());
+        }
+        Ok(parsed)
+    }
 
-    // Create a new reader for the full iteration with character set context
-    let file = File::open(input_filename)?;
-    let reader = AsciiLineReader::with_character_set(file, header_info.character_set.clone());
-
-    Ok(reader.lines().enumerate().map(move |(idx, line_result)| {
-        let line_number = idx + 1;
-        match line_result {
-            Ok(line) => {
-                if line.is_empty() || line.trim().is_empty() {
-                    Err(CwrParseError::BadFormat(format!("Line {} is empty", line_number)))
-                } else if line.len() < 3 {
-                    Err(CwrParseError::BadFormat(format!("Line {} is too short (less than 3 chars)", line_number)))
-                } else {
-                    parse_cwr_line(&line, line_number, &context)
+    /// Parses every record found on a line, resynchronizing on the next record prefix after unparseable data
+    fn recover_line(&mut self, line_number: usize, bytes: &[u8]) -> Vec<Result<ParsedRecord, CwrParseError>> {
+        let mut items = Vec::with_capacity(1);
+        let mut start = 0;
+        loop {
+            let segment = &bytes[start..];
+            let next = match self.parse_line(line_number, segment) {
+                Ok(mut parsed) => {
+                    // Records run together when a line break is lost; the next one starts after the last field
+                    let record_length = parsed.record.record_length(self.context.cwr_version);
+                    let next =
+                        if segment.len() > record_length { find_record_prefix(segment, record_length) } else { None };
+                    if let Some(offset) = next {
+                        if let Some(raw_bytes) = parsed.raw_bytes.as_mut() {
+                            raw_bytes.truncate(offset);
+                        }
+                        if parsed.raw_line.is_some() {
+                            parsed.raw_line = Some(
+                                crate::encoding::decode_lossy(&segment[..offset], &self.character_set).into_owned(),
+                            );
+                        }
+                    }
+                    if start > 0 {
+                        parsed.warnings.push(CwrWarning::record(
+                            WarningLevel::Warning,
+                            format!("Record starts at column {} of the line", start + 1),
+                        ));
+                    }
+                    if let Some(tracker) = self.tracker.as_mut() {
+                        tracker.record_parsed(&mut parsed);
+                    }
+                    items.push(Ok(parsed));
+                    next
+                }
+                Err(parse_err) => {
+                    if let Some(tracker) = self.tracker.as_mut() {
+                        tracker.line_malformed(segment, line_number);
+                    }
+                    let next = find_record_prefix(segment, 1);
+                    let parse_err = resynchronized_error(parse_err, start, next.map(|offset| start + offset));
+                    error!(line = line_number, "Parse error at line {}: {}", line_number, parse_err);
+                    items.push(Err(parse_err));
+                    next
                 }
+            };
+            match next {
+                Some(offset) => start += offset,
+                None => return items,
+            }
+        }
+    }
+}
+
+impl<I: Iterator<Item = Result<Vec<u8>, CwrParseError>>> Iterator for RecordStream<I> {
+    type Item = Result<ParsedRecord, CwrParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (item, interner) = std::mem::take(&mut self.interner).scope(|| self.next_record());
+        self.interner = interner;
+        let mut item = item?;
+        if let Ok(record) = &mut item {
+            self.adjust_warnings(&mut record.warnings);
+        }
+        Some(item)
+    }
+}
+
+impl<I: Iterator<Item = Result<Vec<u8>, CwrParseError>>> RecordStream<I> {
+    /// Drops warnings below `min_warning_level` and raises the rest to `raise_warnings_to`
+    fn adjust_warnings(&self, warnings: &mut Vec<CwrWarning<'static>>) {
+        if let Some(min_level) = &self.min_warning_level {
+            warnings.retain(|warning| warning.level >= *min_level);
+        }
+        if let Some(raised_level) = &self.raise_warnings_to {
+            for warning in warnings.iter_mut().filter(|warning| warning.level < *raised_level) {
+                warning.level = raised_level.clone();
+            }
+        }
+    }
+
+    fn next_record(&mut self) -> Option<Result<ParsedRecord, CwrParseError>> {
+        loop {
+            if let Some(item) = self.ready.pop_front() {
+                return Some(item);
             }
-            Err(parse_err) => {
-                error!("Parse error at line {}: {}", line_number, parse_err);
-                Err(parse_err)
+
+            let Some(line_result) = self.lines.next() else {
+                let mut last = self.last.take()?;
+                if let Some(first_blank) = self.blank_lines.first() {
+                    let message = format!(
+                        "Skipped {} blank line(s) at the end of the file, starting at line {}",
+                        self.blank_lines.len(),
+                        first_blank
+                    );
+                    warn!("{}", message);
+                    if let Ok(record) = &mut last {
+                        record.warnings.push(CwrWarning::record(WarningLevel::Warning, message));
+                    }
+                }
+                return Some(last);
+            };
+            self.line_number += 1;
+            let line_number = self.line_number;
+
+            // In recovery mode one line can hold several records; all but the last go out first
+            let (earlier, item) = match line_result {
+                Ok(bytes) if self.skip_trailing_blank_lines && bytes.iter().all(u8::is_ascii_whitespace) => {
+                    self.blank_lines.push(line_number);
+                    continue;
+                }
+                Ok(bytes) if !self.is_wanted(&bytes) => continue,
+                Ok(bytes) if self.tracker.is_some() => {
+                    let mut items = self.recover_line(line_number, &bytes);
+                    let item = items.pop().unwrap_or_else(|| Err(empty_line_error(line_number)));
+                    (items, item)
+                }
+                Ok(bytes) => (Vec::new(), self.parse_line(line_number, &bytes)),
+                Err(parse_err) => {
+                    error!(line = line_number, "Parse error at line {}: {}", line_number, parse_err);
+                    (Vec::new(), Err(parse_err))
+                }
+            };
+            if !self.skip_trailing_blank_lines {
+                if earlier.is_empty() {
+                    return Some(item);
+                }
+                self.ready.extend(earlier);
+                self.ready.push_back(item);
+                continue;
             }
+
+            // Blank lines followed by more content were not trailing after all
+            self.ready.extend(self.last.take());
+            self.ready.extend(self.blank_lines.drain(..).map(|n| Err(empty_line_error(n))));
+            self.ready.extend(earlier);
+            self.last = Some(item);
         }
-    }))
+    }
+}
+
+/// Options controlling how a CWR file is read
+#[derive(Debug, Clone, Default)]
+pub struct ParseOptions {
+    /// CWR version to use instead of auto-detecting it from the filename and header
+    pub version_hint: Option<f32>,
+    /// Character set to use instead of the HDR declaration (disables character set detection)
+    pub charset_override: Option<String>,
+    /// Skip blank or whitespace-only lines at the end of the file, reporting a warning on the last record
+    /// instead of a parse error per line
+    pub skip_trailing_blank_lines: bool,
+    /// After an unparseable line, resynchronize on the next recognizable record prefix and flag records whose
+    /// group or transaction header could not be parsed
+    pub recover_malformed_lines: bool,
+    /// Keep the source line on each [`ParsedRecord`] (`raw_line`) so handlers can store what was received
+    pub keep_raw_lines: bool,
+    /// Keep the undecoded source bytes on each [`ParsedRecord`] (`raw_bytes`); in recovery mode these cover only
+    /// the record itself when several records run together on one line
+    pub keep_raw_bytes: bool,
+    /// Drop warnings below this level before records reach handlers, e.g. `Warning` to ignore `Info` warnings
+    pub min_warning_level: Option<WarningLevel>,
+    /// Raise the warnings that are kept to at least this level, e.g. `Critical` to treat every warning as critical
+    pub raise_warnings_to: Option<WarningLevel>,
+    /// Only parse records with these type codes (e.g. `NWR`, `SWR`); other lines are skipped without being parsed.
+    /// HDR, GRH, GRT and TRL records are always parsed.
+    pub record_types: Option<Vec<String>>,
+    /// Whether processing with a handler stops at the first parse error or critical warning, or reports them all
+    pub error_mode: crate::cwr_handler::ErrorMode,
+}
+
+/// Returns an iterator that processes CWR lines and yields parsed records
+#[cfg(feature = "fs")]
+pub fn process_cwr_stream(
+    input_filename: &str,
+) -> Result<impl Iterator<Item = Result<ParsedRecord, CwrParseError>>, CwrParseError> {
+    process_cwr_stream_with_version(input_filename, None)
+}
+
+/// Returns an iterator that processes CWR lines and yields parsed records with optional version hint and character set override
+#[cfg(feature = "fs")]
+pub fn process_cwr_stream_with_version_and_charset(
+    input_filename: &str, version_hint: Option<f32>, charset_override: Option<&str>,
+) -> Result<impl Iterator<Item = Result<ParsedRecord, CwrParseError>>, CwrParseError> {
+    let options =
+        ParseOptions { version_hint, charset_override: charset_override.map(str::to_string), ..Default::default() };
+    process_cwr_stream_with_options(input_filename, &options)
 }
 
 /// Returns an iterator that processes CWR lines and yields parsed records with optional version hint
+#[cfg(feature = "fs")]
 pub fn process_cwr_stream_with_version(
     input_filename: &str, version_hint: Option<f32>,
 ) -> Result<impl Iterator<Item = Result<ParsedRecord, CwrParseError>>, CwrParseError> {
+    process_cwr_stream_with_options(input_filename, &ParseOptions { version_hint, ..Default::default() })
+}
+
+/// Returns an iterator that processes CWR lines and yields parsed records using the given options
+#[cfg(feature = "fs")]
+pub fn process_cwr_stream_with_options(
+    input_filename: &str, options: &ParseOptions,
+) -> Result<impl Iterator<Item = Result<ParsedRecord, CwrParseError>> + use<>, CwrParseError> {
+    let header = read_header(input_filename, || open_input(input_filename), options)?;
+
+    // Create a new reader for the full iteration with character set context
+    let reader = AsciiLineReader::with_character_set(open_input(input_filename)?, header.context.character_set.clone());
+    Ok(RecordStream::new(reader.byte_lines(), header, options))
+}
+
+/// Returns an iterator over the records of a CWR file already held in memory (e.g. an upload). The version is
+/// taken from `options.version_hint` or detected from the HDR record. Gzipped content is decompressed first.
+pub fn process_cwr_bytes_stream<'a>(
+    bytes: &'a [u8], options: &ParseOptions,
+) -> Result<impl Iterator<Item = Result<ParsedRecord, CwrParseError>> + use<'a>, CwrParseError> {
+    let bytes = decompress(bytes)?;
+    let header = read_header("", || Ok(bytes.as_ref()), options)?;
+    let reader = AsciiLineReader::with_character_set(io::Cursor::new(bytes), header.context.character_set.clone());
+    Ok(RecordStream::new(reader.byte_lines(), header, options))
+}
+
+/// Returns an iterator over the records of a CWR file read from `reader` (e.g. a browser stream or a network
+/// body). Only the start of the input is buffered, since the header is read more than once before parsing starts;
+/// the rest is streamed, and gzipped input is decompressed on the fly.
+pub fn process_cwr_reader_stream<R: io::Read>(
+    reader: R, options: &ParseOptions,
+) -> Result<impl Iterator<Item = Result<ParsedRecord, CwrParseError>> + use<R>, CwrParseError> {
+    let mut reader = decompressing(reader)?;
+    // The character set detection sample is the longest read of the header passes, and it covers the HDR line
+    let mut head = Vec::new();
+    reader.by_ref().take(DETECTION_SAMPLE_SIZE as u64).read_to_end(&mut head)?;
+    let header = read_header("", || Ok(head.as_slice()), options)?;
+    let reader =
+        AsciiLineReader::with_character_set(io::Cursor::new(head).chain(reader), header.context.character_set.clone());
+    Ok(RecordStream::new(reader.byte_lines(), header, options))
+}
+
+/// What reading the header settled: the parsing context, and how the version and character set were chosen
+struct HeaderFindings {
+    context: ParsingContext,
+    warnings: Vec<CwrWarning<'static>>,
+    version_detection: VersionDetection,
+}
+
+/// Validates the HDR record and settles the version and character set; `open` is called for each pass over the
+/// start of the input
+fn read_header<R: io::Read>(
+    input_filename: &str, mut open: impl FnMut() -> io::Result<R>, options: &ParseOptions,
+) -> Result<HeaderFindings, CwrParseError> {
     // Validate header and detect version in one operation!
-    let file = File::open(input_filename)?;
-    let mut sniffer = AsciiStreamSniffer::new(file);
-    let header_info = match sniffer.validate_and_detect_version(input_filename, version_hint) {
+    let mut sniffer = AsciiStreamSniffer::new(open()?);
+    let mut header_info = match sniffer.validate_and_detect_version(input_filename, options.version_hint) {
         Err(CwrParseError::InvalidHeader { found_bytes }) if found_bytes.is_empty() => {
             return Err(CwrParseError::BadFormat("File is empty".to_string()));
         }
@@ -140,39 +549,36 @@ pub fn process_cwr_stream_with_version(
         Ok(info) => info.clone(),
     };
 
+    // An explicit override is trusted as is
+    let charset_warning = if let Some(charset_str) = options.charset_override.as_deref() {
+        use crate::parsing::CwrFieldParse;
+        let (charset_opt, _) =
+            <Option<CharacterSet>>::parse_cwr_field(charset_str, "character_set_override", "Character set override");
+        header_info.character_set = charset_opt;
+        info!(file = input_filename, "Character set overridden to: {:?}", header_info.character_set);
+        None
+    } else {
+        resolve_character_set(open()?, &mut header_info)?
+    };
+
     let cwr_version = header_info.version;
-    info!("Determined CWR version: {}", cwr_version);
+    info!(file = input_filename, version = %cwr_version, "Determined CWR version: {}", cwr_version);
 
     let context = ParsingContext { cwr_version, file_id: 0, character_set: header_info.character_set.clone() };
-
-    // Create a new reader for the full iteration with character set context
-    let file = File::open(input_filename)?;
-    let reader = AsciiLineReader::with_character_set(file, header_info.character_set.clone());
-
-    Ok(reader.lines().enumerate().map(move |(idx, line_result)| {
-        let line_number = idx + 1;
-        match line_result {
-            Ok(line) => {
-                if line.is_empty() || line.trim().is_empty() {
-                    Err(CwrParseError::BadFormat(format!("Line {} is empty", line_number)))
-                } else if line.len() < 3 {
-                    Err(CwrParseError::BadFormat(format!("Line {} is too short (less than 3 chars)", line_number)))
-                } else {
-                    parse_cwr_line(&line, line_number, &context)
-                }
-            }
-            Err(parse_err) => {
-                error!("Parse error at line {}: {}", line_number, parse_err);
-                Err(parse_err)
-            }
-        }
-    }))
+    let version_detection = header_info.version_detection;
+    let mut warnings: Vec<CwrWarning<'static>> =
+        charset_warning.into_iter().map(|message| CwrWarning::record(WarningLevel::Warning, message)).collect();
+    if version_detection.has_conflict() {
+        warnings.push(CwrWarning::record(WarningLevel::Warning, version_detection.to_string()));
+    }
+    Ok(HeaderFindings { context, warnings, version_detection })
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "fs"))]
 mod tests {
     use super::*;
     use std::fs;
+    use std::fs::File;
     use std::io::Write;
 
     #[test]
@@ -282,30 +688,119 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_detect_cwr_version_reports_conflict() {
+        use crate::util::detect_cwr_version;
+
+        let mut hdr_line =
+            "HDRPB285606836WARNER CHAPPELL MUSIC PUBLISHING LTD         01.102022122112541120221221".to_string();
+        while hdr_line.len() < 101 {
+            hdr_line.push(' ');
+        }
+        hdr_line.push_str("2.2 ");
+
+        let detection = detect_cwr_version("CW060001EMI_044.V21", &hdr_line, None).unwrap();
+        assert!(detection.has_conflict());
+        assert_eq!((detection.chosen, detection.chosen_from), (2.1, VersionSource::Filename));
+        assert_eq!(
+            detection.to_string(),
+            "CWR versions disagree (filename 2.1, HDR version field 2.2, HDR EDI standard version 01.10); \
+             parsing as 2.1 from the filename"
+        );
+
+        let agreeing = detect_cwr_version("CW060001EMI_044.V22", &hdr_line, None).unwrap();
+        assert!(!agreeing.has_conflict());
+    }
+
+    #[test]
+    fn test_grh_version_conflict_warning() {
+        let content = "HDRPB285606836WARNER CHAPPELL MUSIC PUBLISHING LTD         01.102022122112541120221221\n\
+                       GRHNWR0000102.100000000000  \nGRT000010000000000000002\nTRL000000010000000000000004\n";
+        let options = ParseOptions { version_hint: Some(2.2), ..Default::default() };
+        let records: Vec<ParsedRecord> =
+            process_cwr_bytes_stream(content.as_bytes(), &options).unwrap().map(Result::unwrap).collect();
+
+        assert!(
+            records[0].warnings.iter().all(|w| !w.description.starts_with("CWR versions disagree")),
+            "{:?}",
+            records[0]
+        );
+        assert!(
+            records[1].warnings.iter().any(|w| w.description.contains("version option 2.2")
+                && w.description.contains("GRH version number 2.1")
+                && w.description.ends_with("parsing as 2.2 from the version option")),
+            "{:?}",
+            records[1].warnings
+        );
+    }
+
     #[test]
     fn test_parse_cwr_line_too_short() {
         let context = ParsingContext { cwr_version: 2.2, file_id: 0, character_set: None };
-        let result = parse_cwr_line("AB", 1, &context);
+        let result = parse_line_in_context(&"AB".into(), 1, &context);
         assert!(result.is_err());
         match result {
-            Err(CwrParseError::BadFormat(msg)) => {
-                assert_eq!(msg, "Line 1 is too short (less than 3 chars)");
+            Err(CwrParseError::Record(err)) => {
+                assert_eq!(err.code, ParseErrorCode::LineTooShort);
+                assert_eq!(err.line_number, Some(1));
+                assert_eq!(err.message, "Line 1 is too short (less than 3 chars)");
             }
-            _ => panic!("Expected BadFormat error"),
+            _ => panic!("Expected Record error"),
         }
     }
 
     #[test]
     fn test_parse_cwr_line_unknown_record_type() {
         let context = ParsingContext { cwr_version: 2.2, file_id: 0, character_set: None };
-        let result = parse_cwr_line("XYZ00000001000000012005010112000000001000000001NWR", 1, &context);
+        let result = parse_line_in_context(&"XYZ00000001000000012005010112000000001000000001NWR".into(), 1, &context);
         assert!(result.is_err());
         match result {
-            Err(CwrParseError::BadFormat(msg)) => {
-                assert_eq!(msg, "Unrecognized record type 'XYZ'");
+            Err(CwrParseError::Record(err)) => {
+                assert_eq!(err.code, ParseErrorCode::UnknownRecordType);
+                assert_eq!(err.line_number, Some(1));
+                assert_eq!(err.columns, Some(0..3));
+                assert_eq!(err.value.as_deref(), Some("XYZ"));
+                assert_eq!(err.message, "Unrecognized record type 'XYZ'");
             }
-            _ => panic!("Expected BadFormat error"),
+            _ => panic!("Expected Record error"),
+        }
+    }
+
+    #[test]
+    fn test_parse_cwr_line_record_not_in_version() {
+        let line = "XRF0000000100000001ISWT1234567890123WY";
+        let context = ParsingContext { cwr_version: 2.1, file_id: 0, character_set: None };
+        match parse_line_in_context(&line.into(), 7, &context) {
+            Err(CwrParseError::Record(err)) => {
+                assert_eq!(err.code, ParseErrorCode::RecordNotInVersion);
+                assert_eq!(err.record_type.as_deref(), Some("XRF"));
+                assert_eq!(
+                    err.message,
+                    "Line 7: XRF record is not valid in CWR version 2.1 (requires version 2.2 or later)"
+                );
+            }
+            _ => panic!("Expected Record error"),
+        }
+
+        let context = ParsingContext { cwr_version: 2.2, file_id: 0, character_set: None };
+        assert!(parse_line_in_context(&line.into(), 7, &context).is_ok());
+    }
+
+    #[test]
+    fn test_parse_single_line() {
+        let line = "ALT0000000200000326BABY CAN T YOU SEE                                          AT  ";
+        let (record, warnings) = parse_cwr_line(line, 2.2, &CharacterSet::ASCII).unwrap();
+        assert_eq!(record.record_type(), "ALT");
+        assert!(warnings.is_empty(), "{:?}", warnings);
+
+        match parse_cwr_line("XRF0000000100000001ISWT1234567890123WY", 2.1, &CharacterSet::ASCII) {
+            Err(CwrParseError::Record(err)) => {
+                assert_eq!(err.code, ParseErrorCode::RecordNotInVersion);
+                assert_eq!(err.line_number, None);
+            }
+            other => panic!("Expected Record error, got {:?}", other),
         }
+        assert!(parse_cwr_line("AB", 2.2, &CharacterSet::ASCII).is_err());
     }
 
     #[test]
@@ -313,7 +808,7 @@ mod tests {
         let context = ParsingContext { cwr_version: 2.0, file_id: 0, character_set: None };
         // Real HDR line from TestSample.V21
         let line = "HDRPB285606836WARNER CHAPPELL MUSIC PUBLISHING LTD         01.102022122112541120221221";
-        let result = parse_cwr_line(line, 1, &context);
+        let result = parse_line_in_context(&line.into(), 1, &context);
         assert!(result.is_ok());
         let parsed = result.unwrap();
         assert_eq!(parsed.line_number, 1);
@@ -327,7 +822,7 @@ mod tests {
         use chrono::{NaiveDate, NaiveTime};
 
         let hdr = HdrRecord {
-            record_type: "HDR".to_string(),
+            record_type: crate::parsing::intern("HDR"),
             sender_type: SenderType::NumericPrefix("01".to_string()),
             sender_id: SenderId("BMI".to_string()),
             sender_name: SenderName("BMI MUSIC".to_string()),
@@ -415,6 +910,28 @@ mod tests {
         fs::remove_file(&temp_file).ok();
     }
 
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_process_cwr_stream_gzipped() {
+        let content = "HDRPB285606836WARNER CHAPPELL MUSIC PUBLISHING LTD         01.102022122112541120221221\nGRHNWR0000102.100000000000  \nTRL00000002000000022022122100";
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(content.as_bytes()).unwrap();
+        let gzipped = encoder.finish().unwrap();
+
+        // Detected from the content, whatever the file is called
+        let temp_file = create_temp_cwr_file("").unwrap();
+        fs::write(&temp_file, &gzipped).unwrap();
+        assert!(is_cwr_file(&temp_file).unwrap());
+        let records: Vec<_> = process_cwr_stream(&temp_file).unwrap().collect();
+        fs::remove_file(&temp_file).ok();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0].as_ref().unwrap().context.cwr_version, 2.1);
+
+        let options = ParseOptions::default();
+        assert_eq!(process_cwr_bytes_stream(&gzipped, &options).unwrap().count(), 3);
+        assert_eq!(process_cwr_reader_stream(gzipped.as_slice(), &options).unwrap().count(), 3);
+    }
+
     #[test]
     fn test_process_cwr_stream_empty_line() {
         let content = "HDRPB285606836WARNER CHAPPELL MUSIC PUBLISHING LTD         01.102022122112541120221221\n\nTRL00000002000000022022122100                                                                                                                                                                                                                                                                                                                                                                                   ";
@@ -430,10 +947,11 @@ mod tests {
         assert!(records[2].is_ok());
 
         match &records[1] {
-            Err(CwrParseError::BadFormat(msg)) => {
-                assert_eq!(msg, "Line 2 is empty");
+            Err(err) => {
+                assert_eq!(err.code(), Some(ParseErrorCode::EmptyLine));
+                assert_eq!(err.to_string(), "Line 2 is empty");
             }
-            _ => panic!("Expected BadFormat error for empty line"),
+            _ => panic!("Expected error for empty line"),
         }
 
         fs::remove_file(&temp_file).ok();
@@ -512,8 +1030,10 @@ mod tests {
     }
 
     #[test]
-    fn test_process_cwr_stream_ascii_charset_rejects_non_ascii() {
-        // Create a CWR file with ASCII character set and attempt to include non-ASCII data
+    fn test_process_cwr_stream_ascii_charset_with_utf8_content_is_detected() {
+        use crate::domain_types::CharacterSet;
+
+        // A file declaring ASCII but carrying UTF-8 data is read as UTF-8 with a warning on the HDR
         let mut hdr_line =
             "HDRPB285606836WARNER CHAPPELL MUSIC PUBLISHING LTD         01.102022122112541120221221".to_string();
         // Pad to position 86 (character set field)
@@ -535,20 +1055,269 @@ mod tests {
         let records: Vec<_> = result.unwrap().collect();
         assert_eq!(records.len(), 3);
 
-        // First record (HDR) should be OK
+        let hdr = records[0].as_ref().unwrap();
+        assert_eq!(hdr.context.character_set, Some(CharacterSet::UTF8));
+        assert!(hdr.warnings[0].description.contains("HDR declares 'ASCII'"));
+
+        // Second line is no longer rejected for its encoding, only for its unknown record type
+        assert!(!matches!(records[1], Err(CwrParseError::NonAsciiInput { .. })));
+        assert!(records[2].is_ok());
+
+        fs::remove_file(&temp_file).ok();
+    }
+
+    #[test]
+    fn test_process_cwr_stream_detects_latin1_without_declared_charset() {
+        use crate::domain_types::CharacterSet;
+
+        let hdr_line = "HDRPB285606836WARNER CHAPPELL MUSIC PUBLISHING LTD         01.102022122112541120221221";
+        let mut content = format!("{}\nGRHNWR0000102.100000000000  \n", hdr_line).into_bytes();
+        content.extend_from_slice(b"TRL0000000200000002M\xFCller caf\xE9");
+        let temp_file = std::env::temp_dir().join(format!("latin1_test_{:?}.cwr", std::thread::current().id()));
+        fs::write(&temp_file, &content).unwrap();
+
+        let records: Vec<_> = process_cwr_stream(temp_file.to_str().unwrap()).unwrap().collect();
+        fs::remove_file(&temp_file).ok();
+
+        let hdr = records[0].as_ref().unwrap();
+        assert_eq!(hdr.context.character_set, Some(CharacterSet::Latin1));
+        assert!(hdr.warnings[0].description.contains("has no character set"));
+        assert!(records[1].as_ref().unwrap().warnings.iter().all(|w| !w.description.starts_with("Character set")));
+    }
+
+    const TRAILING_HDR: &str = "HDRPB285606836WARNER CHAPPELL MUSIC PUBLISHING LTD         01.102022122112541120221221";
+    const TRAILING_TRL: &str = "TRL000000020000000200000003";
+
+    fn parse_with_options(
+        name: &str, content: &str, options: &ParseOptions,
+    ) -> Vec<Result<ParsedRecord, CwrParseError>> {
+        let path = std::env::temp_dir().join(format!("{}_{:?}.cwr", name, std::thread::current().id()));
+        fs::write(&path, content).unwrap();
+        let records = process_cwr_stream_with_options(path.to_str().unwrap(), options).unwrap().collect();
+        fs::remove_file(&path).ok();
+        records
+    }
+
+    #[test]
+    fn test_cr_only_line_endings() {
+        let content = format!("{}\rGRHNWR0000102.100000000000  \r{}\r", TRAILING_HDR, TRAILING_TRL);
+        let records = parse_with_options("cr_only", &content, &ParseOptions::default());
+        assert_eq!(records.len(), 3);
+        assert!(records.iter().all(|r| r.is_ok()));
+    }
+
+    #[test]
+    fn test_trailing_blank_lines() {
+        let content = format!("{}\r\n{}\r\n\r\n   \r\n", TRAILING_HDR, TRAILING_TRL);
+
+        // By default every blank line is a parse error
+        let records = parse_with_options("trailing_default", &content, &ParseOptions::default());
+        assert_eq!(records.len(), 4);
+        assert!(records[2].is_err() && records[3].is_err());
+
+        let options = ParseOptions { skip_trailing_blank_lines: true, ..Default::default() };
+        let records = parse_with_options("trailing_skip", &content, &options);
+        assert_eq!(records.len(), 2);
+        let trl = records[1].as_ref().unwrap();
+        assert!(
+            trl.warnings
+                .iter()
+                .any(|w| w.description.contains("Skipped 2 blank line(s)") && w.description.contains("line 3"))
+        );
+    }
+
+    #[test]
+    fn test_blank_lines_before_content_are_still_errors() {
+        let content = format!("{}\n\n{}\n", TRAILING_HDR, TRAILING_TRL);
+        let options = ParseOptions { skip_trailing_blank_lines: true, ..Default::default() };
+        let records = parse_with_options("blank_middle", &content, &options);
+        assert_eq!(records.len(), 3);
         assert!(records[0].is_ok());
+        assert!(
+            matches!(&records[1], Err(e) if e.code() == Some(ParseErrorCode::EmptyLine) && e.to_string() == "Line 2 is empty")
+        );
+        assert_eq!(records[2].as_ref().unwrap().line_number, 3);
+    }
 
-        // Second record should fail due to non-ASCII characters
-        assert!(records[1].is_err());
-        if let Err(CwrParseError::NonAsciiInput { line_num, byte_pos: _, byte_value: _ }) = &records[1] {
-            assert_eq!(*line_num, 2);
-        } else {
-            panic!("Expected NonAsciiInput error");
+    const RECOVERY_NWR: &str = "NWR0000000000000000Test Song                                               SW0000000001        SER        Y       ORI";
+
+    fn recovery_alt(record_sequence: u32) -> String {
+        format!("{:<83}", format!("ALT00000000{:08}ALTERNATE TITLE", record_sequence))
+    }
+
+    fn recovery_options() -> ParseOptions {
+        ParseOptions { recover_malformed_lines: true, ..Default::default() }
+    }
+
+    #[test]
+    fn test_recovery_splits_records_run_together() {
+        let content = format!(
+            "{}\nGRHNWR0000102.100000000000  \n{}\n{}{}\n{}\n",
+            TRAILING_HDR,
+            RECOVERY_NWR,
+            recovery_alt(1),
+            recovery_alt(2),
+            TRAILING_TRL
+        );
+
+        let records = parse_with_options("recovery_default", &content, &ParseOptions::default());
+        assert_eq!(records.len(), 5);
+
+        let records = parse_with_options("recovery_joined", &content, &recovery_options());
+        assert_eq!(records.len(), 6);
+        let second = records[4].as_ref().unwrap();
+        assert_eq!(second.line_number, 4);
+        assert_eq!(second.record.sequence_numbers(), Some((0, 2)));
+        assert!(second.warnings.iter().any(|w| w.description == "Record starts at column 84 of the line"));
+
+        let options = ParseOptions { keep_raw_bytes: true, ..recovery_options() };
+        let records = parse_with_options("recovery_raw_bytes", &content, &options);
+        assert_eq!(records[3].as_ref().unwrap().raw_bytes, Some(recovery_alt(1).into_bytes()));
+        assert_eq!(records[4].as_ref().unwrap().raw_bytes, Some(recovery_alt(2).into_bytes()));
+
+        let options = ParseOptions { keep_raw_lines: true, ..recovery_options() };
+        let records = parse_with_options("recovery_raw_lines", &content, &options);
+        assert_eq!(records[3].as_ref().unwrap().raw_line, Some(recovery_alt(1)));
+        assert_eq!(records[4].as_ref().unwrap().raw_line, Some(recovery_alt(2)));
+    }
+
+    #[test]
+    fn test_recovery_skips_garbage_before_a_record() {
+        let content = format!("{}\ngarbage{}\n{}\n", TRAILING_HDR, recovery_alt(1), TRAILING_TRL);
+        let records = parse_with_options("recovery_garbage", &content, &recovery_options());
+        assert_eq!(records.len(), 4);
+        assert!(
+            matches!(&records[1], Err(e) if e.code() == Some(ParseErrorCode::UnknownRecordType) && e.to_string().ends_with("resynchronized at column 8"))
+        );
+        assert_eq!(records[2].as_ref().unwrap().record.record_type(), "ALT");
+    }
+
+    #[test]
+    fn test_recovery_flags_records_of_a_broken_transaction() {
+        let broken_header = RECOVERY_NWR.replacen("NWR", "NW?", 1);
+        let content = format!(
+            "{}\nGRHNWR0000102.100000000000  \n{}\n{}\n{}\n{}\n",
+            TRAILING_HDR,
+            broken_header,
+            recovery_alt(1),
+            RECOVERY_NWR.replacen("0000000000000000", "0000000100000000", 1),
+            TRAILING_TRL
+        );
+        let records = parse_with_options("recovery_broken", &content, &recovery_options());
+        assert!(records[2].is_err());
+        let detail = records[3].as_ref().unwrap();
+        assert!(detail.warnings.contains(&CwrWarning::record(
+            WarningLevel::Warning,
+            "Record belongs to a transaction whose header at line 3 could not be parsed"
+        )));
+        assert!(records[4].as_ref().unwrap().warnings.iter().all(|w| !w.description.contains("could not be parsed")));
+    }
+
+    #[test]
+    fn test_process_cwr_bytes_stream() {
+        let content = format!("{}\nGRHNWR0000102.100000000000  \n{}\n", TRAILING_HDR, TRAILING_TRL);
+        let records: Vec<_> = process_cwr_bytes_stream(content.as_bytes(), &ParseOptions::default()).unwrap().collect();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[2].as_ref().unwrap().record.record_type(), "TRL");
+
+        assert!(process_cwr_bytes_stream(b"", &ParseOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_record_stream_interns_per_parse() {
+        let content = format!("{}\nGRHNWR0000102.100000000000  \nGRHREV0000202.100000000000  \n", TRAILING_HDR);
+        let record_types = || -> Vec<std::sync::Arc<str>> {
+            process_cwr_bytes_stream(content.as_bytes(), &ParseOptions::default())
+                .unwrap()
+                .filter_map(|record| match record.unwrap().record {
+                    CwrRegistry::Grh(grh) => Some(grh.record_type),
+                    _ => None,
+                })
+                .collect()
+        };
+        let first = record_types();
+        assert!(std::sync::Arc::ptr_eq(&first[0], &first[1]));
+        assert!(!std::sync::Arc::ptr_eq(&first[0], &record_types()[0]));
+    }
+
+    #[test]
+    fn test_keep_raw_lines() {
+        let content = format!("{}\r\nGRHNWR0000102.100000000000  \r\n{}\r\n", TRAILING_HDR, TRAILING_TRL);
+        let records: Vec<_> = process_cwr_bytes_stream(content.as_bytes(), &ParseOptions::default()).unwrap().collect();
+        assert!(records[1].as_ref().unwrap().raw_line.is_none());
+
+        let options = ParseOptions { keep_raw_lines: true, ..Default::default() };
+        let records: Vec<_> = process_cwr_bytes_stream(content.as_bytes(), &options).unwrap().collect();
+        assert_eq!(records[1].as_ref().unwrap().raw_line.as_deref(), Some("GRHNWR0000102.100000000000  "));
+        assert_eq!(records[0].as_ref().unwrap().raw_line.as_deref(), Some(TRAILING_HDR));
+    }
+
+    #[test]
+    fn test_keep_raw_bytes() {
+        let content = format!("{}\r\nGRHNWR0000102.100000000000  \r\n{}\r\n", TRAILING_HDR, TRAILING_TRL);
+        let records: Vec<_> = process_cwr_bytes_stream(content.as_bytes(), &ParseOptions::default()).unwrap().collect();
+        assert!(records[1].as_ref().unwrap().raw_bytes.is_none());
+
+        let options = ParseOptions { keep_raw_bytes: true, ..Default::default() };
+        let records: Vec<_> = process_cwr_bytes_stream(content.as_bytes(), &options).unwrap().collect();
+        let grh = records[1].as_ref().unwrap();
+        assert_eq!(grh.raw_bytes.as_deref(), Some(&b"GRHNWR0000102.100000000000  "[..]));
+        assert!(grh.raw_line.is_none());
+    }
+
+    #[test]
+    fn test_warning_level_options() {
+        let content = format!("{}\nGRHZZZ0000102.200000000000  \n{}\n", TRAILING_HDR, TRAILING_TRL);
+        let grh_levels = |options: &ParseOptions| -> Vec<WarningLevel> {
+            let grh = process_cwr_bytes_stream(content.as_bytes(), options).unwrap().nth(1).unwrap().unwrap();
+            grh.warnings.into_iter().map(|warning| warning.level).collect()
+        };
+
+        let grh =
+            process_cwr_bytes_stream(content.as_bytes(), &ParseOptions::default()).unwrap().nth(1).unwrap().unwrap();
+        let warning = grh.warnings.iter().find(|warning| warning.field_name == "transaction_type").unwrap();
+        assert_eq!(grh.warning_columns(warning), Some(3..6));
+        assert!(
+            grh.warnings.iter().filter(|warning| !warning.is_field_warning()).all(|w| grh.warning_columns(w).is_none())
+        );
+
+        let levels = grh_levels(&ParseOptions::default());
+        assert!(levels.contains(&WarningLevel::Warning) && levels.contains(&WarningLevel::Critical));
+
+        let options = ParseOptions { min_warning_level: Some(WarningLevel::Critical), ..Default::default() };
+        assert_eq!(grh_levels(&options), vec![WarningLevel::Critical]);
+
+        let options = ParseOptions { raise_warnings_to: Some(WarningLevel::Critical), ..Default::default() };
+        assert_eq!(grh_levels(&options), vec![WarningLevel::Critical; levels.len()]);
+    }
+
+    #[test]
+    fn test_process_cwr_reader_stream() {
+        let content = format!("{}\nGRHNWR0000102.100000000000  \n{}\n", TRAILING_HDR, TRAILING_TRL);
+        let reader = io::Cursor::new(content.into_bytes());
+        let records: Vec<_> = process_cwr_reader_stream(reader, &ParseOptions::default()).unwrap().collect();
+        assert_eq!(records.len(), 3);
+        assert!(records.iter().all(Result::is_ok));
+    }
+    #[test]
+    fn test_process_cwr_reader_stream_reads_lazily() {
+        struct Counting<R>(R, std::rc::Rc<std::cell::Cell<usize>>);
+        impl<R: io::Read> io::Read for Counting<R> {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                let read = self.0.read(buf)?;
+                self.1.set(self.1.get() + read);
+                Ok(read)
+            }
         }
 
-        // Third record should be OK
-        assert!(records[2].is_ok());
+        let groups = "GRHNWR0000102.100000000000  \n".repeat(10_000);
+        let content = format!("{}\n{}{}\n", TRAILING_HDR, groups, TRAILING_TRL);
+        let consumed = std::rc::Rc::new(std::cell::Cell::new(0));
+        let reader = Counting(io::Cursor::new(content.as_bytes()), consumed.clone());
 
-        fs::remove_file(&temp_file).ok();
+        let records = process_cwr_reader_stream(reader, &ParseOptions::default()).unwrap();
+        assert!(consumed.get() <= DETECTION_SAMPLE_SIZE, "read {} bytes before parsing", consumed.get());
+        assert_eq!(records.count(), 10_002);
+        assert_eq!(consumed.get(), content.len());
     }
 }
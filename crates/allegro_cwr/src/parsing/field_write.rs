@@ -21,40 +21,24 @@ pub fn format_text(value: &str, width: usize) -> String {
 /// Convert a string to bytes according to the specified character set
 /// This is the core function that ensures proper encoding for CWR output
 pub fn string_to_cwr_bytes(value: &str, character_set: &CharacterSet) -> Vec<u8> {
-    match character_set {
-        CharacterSet::ASCII => {
-            // For ASCII, convert each character to ASCII bytes, replacing non-ASCII with '?'
-            let mut bytes = Vec::new();
-            for ch in value.chars() {
-                if ch.is_ascii() {
-                    bytes.push(ch as u8);
-                } else {
-                    // Replace non-ASCII characters with '?' for ASCII compatibility
-                    bytes.push(b'?');
-                }
-            }
-            bytes
-        }
-        CharacterSet::UTF8 => {
-            // For UTF-8, use the string's natural UTF-8 encoding
-            value.as_bytes().to_vec()
-        }
-        CharacterSet::TraditionalBig5 | CharacterSet::SimplifiedGb | CharacterSet::Unicode => {
-            // For other character sets, fall back to UTF-8 for now
-            // In a real implementation, you'd use proper encoding libraries
-            string_to_cwr_bytes(value, &CharacterSet::UTF8)
-        }
-        CharacterSet::Unknown(_) => {
-            // For unknown character sets, default to UTF-8
-            string_to_cwr_bytes(value, &CharacterSet::UTF8)
-        }
-    }
+    crate::encoding::encode(value, character_set)
 }
 
-/// Convert a formatted text field to CWR bytes with proper width and character set handling
+/// Convert a formatted text field to CWR bytes with proper width and character set handling.
+/// Padding is applied to the encoded bytes, since field widths count bytes of the target character set.
 pub fn format_text_to_cwr_bytes(value: &str, width: usize, character_set: &CharacterSet) -> Vec<u8> {
-    let formatted_string = format_text(value, width);
-    string_to_cwr_bytes(&formatted_string, character_set)
+    let mut bytes = Vec::with_capacity(width.max(value.len()));
+    if value.is_ascii() {
+        bytes.extend_from_slice(value.as_bytes());
+    } else {
+        for ch in value.chars() {
+            crate::encoding::encode_char(ch, character_set, &mut bytes);
+        }
+    }
+    if bytes.len() < width {
+        bytes.resize(width, b' ');
+    }
+    bytes
 }
 
 /// Trait for numeric fields that need zero-padding to a specific width
@@ -74,15 +58,101 @@ impl CwrFieldWrite for String {
     }
 }
 
+impl CwrFieldWrite for std::sync::Arc<str> {
+    fn to_cwr_field_bytes(&self, width: usize, character_set: &CharacterSet) -> Vec<u8> {
+        format_text_to_cwr_bytes(self, width, character_set)
+    }
+}
+
 // Option<T> fields: Always space-padded when None, regardless of T's type
 impl<T: CwrFieldWrite> CwrFieldWrite for Option<T> {
     fn to_cwr_field_bytes(&self, width: usize, character_set: &CharacterSet) -> Vec<u8> {
         match self {
             Some(val) => val.to_cwr_field_bytes(width, character_set),
-            None => {
-                let empty_string = format!("{:width$}", "", width = width);
-                string_to_cwr_bytes(&empty_string, character_set)
-            }
+            None => vec![b' '; width],
+        }
+    }
+}
+
+/// What to do when a value is longer than its field when writing
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FieldOverflow {
+    /// Cut the value to the field width and report a [`FieldTruncation`]
+    #[default]
+    Truncate,
+    /// Refuse to write the record
+    Error,
+}
+
+/// What to do with a record whose type does not exist in the CWR version being written (e.g. XRF in a 2.1 file)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UnsupportedRecord {
+    /// Refuse to write the record
+    #[default]
+    Error,
+    /// Leave the record out and log a warning
+    Drop,
+}
+
+/// A value cut to fit its field when writing a record
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldTruncation {
+    pub record_type: String,
+    pub field_name: &'static str,
+    pub width: usize,
+    pub original: String,
+    pub truncated: String,
+}
+
+impl std::fmt::Display for FieldTruncation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {}: '{}' is longer than {} bytes, written as '{}'",
+            self.record_type, self.field_name, self.original, self.width, self.truncated
+        )
+    }
+}
+
+/// Cuts encoded field bytes to `width` without splitting a character, padding with spaces if the last character
+/// did not fit. Returns the original and truncated values when the field was too long.
+pub fn fit_field_bytes(
+    bytes: Vec<u8>, width: usize, character_set: &CharacterSet,
+) -> (Vec<u8>, Option<(String, String)>) {
+    if bytes.len() <= width {
+        return (bytes, None);
+    }
+    let original = crate::encoding::decode_lossy(&bytes, character_set).into_owned();
+    let mut fitted = Vec::with_capacity(width);
+    let mut truncated = String::new();
+    let mut encoded = Vec::new();
+    for ch in original.chars() {
+        encoded.clear();
+        crate::encoding::encode_char(ch, character_set, &mut encoded);
+        if fitted.len() + encoded.len() > width {
+            break;
         }
+        fitted.extend_from_slice(&encoded);
+        truncated.push(ch);
+    }
+    fitted.resize(width, b' ');
+    (fitted, Some((original, truncated)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fit_field_bytes() {
+        assert_eq!(fit_field_bytes(b"ABC".to_vec(), 5, &CharacterSet::ASCII), (b"ABC".to_vec(), None));
+        let (bytes, truncation) = fit_field_bytes(b"ABCDEF".to_vec(), 4, &CharacterSet::ASCII);
+        assert_eq!(bytes, b"ABCD");
+        assert_eq!(truncation, Some(("ABCDEF".to_string(), "ABCD".to_string())));
+
+        // A two-byte character that would straddle the field end is dropped and the gap padded
+        let (bytes, truncation) = fit_field_bytes("ABCÉ".as_bytes().to_vec(), 4, &CharacterSet::UTF8);
+        assert_eq!(bytes, b"ABC ");
+        assert_eq!(truncation, Some(("ABCÉ".to_string(), "ABC".to_string())));
     }
 }
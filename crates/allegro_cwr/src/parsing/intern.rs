@@ -0,0 +1,73 @@
+//! Shared copies of short, frequently repeated field values such as record types and society codes
+
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// Values longer than this are unlikely to repeat and are not interned
+const MAX_INTERNED_LEN: usize = 4;
+
+/// Upper bound on distinct interned values, so malformed input can't grow the table without limit
+const MAX_INTERNED_VALUES: usize = 4096;
+
+thread_local! {
+    /// The interner of the parse running on this thread, if any
+    static CURRENT: RefCell<Option<Interner>> = const { RefCell::new(None) };
+}
+
+/// The values interned while parsing one file. Each record stream owns one, so parses share nothing and take no
+/// locks.
+#[derive(Debug, Default)]
+pub(crate) struct Interner {
+    values: HashSet<Arc<str>>,
+}
+
+impl Interner {
+    /// Runs `parse` with [`intern`] sharing values through this interner, and returns the interner with them
+    pub(crate) fn scope<T>(self, parse: impl FnOnce() -> T) -> (T, Interner) {
+        let previous = CURRENT.replace(Some(self));
+        let result = parse();
+        let interner = CURRENT.replace(previous).unwrap_or_default();
+        (result, interner)
+    }
+
+    fn intern(&mut self, value: &str) -> Arc<str> {
+        if let Some(shared) = self.values.get(value) {
+            return Arc::clone(shared);
+        }
+        let shared: Arc<str> = Arc::from(value);
+        if self.values.len() < MAX_INTERNED_VALUES {
+            self.values.insert(Arc::clone(&shared));
+        }
+        shared
+    }
+}
+
+/// Returns a shared copy of `value`, so parsing millions of records holds one allocation per distinct code.
+/// Outside a record stream (e.g. when building records) the value is copied.
+pub fn intern(value: &str) -> Arc<str> {
+    if value.len() > MAX_INTERNED_LEN {
+        return Arc::from(value);
+    }
+    CURRENT.with_borrow_mut(|current| match current {
+        Some(interner) => interner.intern(value),
+        None => Arc::from(value),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_intern_shares_short_values() {
+        let ((nwr, again), interner) = Interner::default().scope(|| (intern("NWR"), intern("NWR")));
+        assert!(Arc::ptr_eq(&nwr, &again));
+        assert_eq!(interner.values.len(), 1);
+        assert!(!Arc::ptr_eq(&intern("NWR"), &nwr));
+
+        let ((long, again), _) = Interner::default().scope(|| (intern("A LONG TITLE"), intern("A LONG TITLE")));
+        assert!(!Arc::ptr_eq(&long, &again));
+        assert_eq!(&*intern("021"), "021");
+    }
+}
@@ -0,0 +1,87 @@
+//! Source line handed to the record parsers
+//!
+//! CWR field positions are byte offsets in the file's own character set. Lines in a UTF-8 compatible character
+//! set are sliced directly; Latin-1, Big5 and GB lines keep their original bytes and each field is decoded on its
+//! own, so multibyte characters never shift the columns that follow them.
+
+use crate::domain_types::CharacterSet;
+use std::borrow::Cow;
+use std::ops::Range;
+
+#[derive(Debug, Clone, Copy)]
+pub struct CwrLine<'a> {
+    bytes: &'a [u8],
+    character_set: Option<&'a CharacterSet>,
+}
+
+impl<'a> CwrLine<'a> {
+    /// A line that is already UTF-8 text
+    pub fn new(line: &'a str) -> Self {
+        Self { bytes: line.as_bytes(), character_set: None }
+    }
+
+    /// A line holding the raw bytes of the file in the given character set
+    pub fn with_character_set(bytes: &'a [u8], character_set: &'a CharacterSet) -> Self {
+        Self { bytes, character_set: Some(character_set) }
+    }
+
+    /// Length of the line in bytes of its character set
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// Decoded text of the byte range `start..end` (clamped to the line length)
+    pub fn field(&self, start: usize, end: usize) -> Cow<'a, str> {
+        let end = end.min(self.bytes.len());
+        let Some(bytes) = self.bytes.get(start..end) else {
+            return Cow::Borrowed("");
+        };
+        match self.character_set {
+            Some(character_set) => crate::encoding::decode_lossy(bytes, character_set),
+            None => String::from_utf8_lossy(bytes),
+        }
+    }
+
+    /// Character range matching the byte range `columns`, for highlighting a field in the decoded line
+    pub fn char_columns(&self, columns: Range<usize>) -> Range<usize> {
+        let start = self.field(0, columns.start).chars().count();
+        start..start + self.field(columns.start, columns.end).chars().count()
+    }
+
+    /// Decoded text of the whole line
+    pub fn text(&self) -> Cow<'a, str> {
+        self.field(0, self.bytes.len())
+    }
+}
+
+impl<'a> From<&'a str> for CwrLine<'a> {
+    fn from(line: &'a str) -> Self {
+        Self::new(line)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fields_keep_byte_columns() {
+        // 作曲家 in Big5 takes six bytes, so the following field starts at byte 9
+        let bytes = b"NAT\xA7\x40\xA6\xB1\xAE\x61ZH";
+        let line = CwrLine::with_character_set(bytes, &CharacterSet::TraditionalBig5);
+        assert_eq!(line.field(3, 9), "作曲家");
+        assert_eq!(line.field(9, 11), "ZH");
+        assert_eq!(line.field(9, 20), "ZH");
+        assert_eq!(line.field(20, 30), "");
+        assert_eq!(line.char_columns(3..9), 3..6);
+        assert_eq!(line.char_columns(9..11), 6..8);
+    }
+}
@@ -2,9 +2,14 @@
 
 mod field_parse;
 mod field_write;
+mod intern;
+mod line;
 mod warning;
 pub mod warning_level;
 
 pub use field_parse::*;
 pub use field_write::*;
+pub(crate) use intern::Interner;
+pub use intern::intern;
+pub use line::CwrLine;
 pub use warning::*;
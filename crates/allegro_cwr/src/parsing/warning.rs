@@ -2,26 +2,95 @@
 
 use std::borrow::Cow;
 
-/// Warning levels for CWR parsing
-#[derive(Debug, Clone, PartialEq)]
+/// Warning levels for CWR parsing, ordered from least to most severe
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum WarningLevel {
     Info,
     Warning,
     Critical,
 }
 
-/// Warning generated during CWR parsing
+impl WarningLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WarningLevel::Info => "INFO",
+            WarningLevel::Warning => "WARNING",
+            WarningLevel::Critical => "CRITICAL",
+        }
+    }
+}
+
+/// Warning generated during CWR parsing. Warnings about the record as a whole (not one field) have an empty
+/// `field_name` and `field_title`.
 #[derive(Debug, Clone, PartialEq)]
 pub struct CwrWarning<'a> {
     pub field_name: &'static str,
     pub field_title: &'static str,
     pub source_str: Cow<'a, str>,
     pub level: WarningLevel,
-    pub description: String,
+    /// Borrowed for fixed messages, so only warnings that quote values allocate
+    pub description: Cow<'static, str>,
+}
+
+impl CwrWarning<'static> {
+    /// A warning about the record as a whole rather than one of its fields
+    pub fn record(level: WarningLevel, description: impl Into<Cow<'static, str>>) -> Self {
+        CwrWarning {
+            field_name: "",
+            field_title: "",
+            source_str: Cow::Borrowed(""),
+            level,
+            description: description.into(),
+        }
+    }
 }
 
 impl CwrWarning<'_> {
     pub fn is_critical(&self) -> bool {
         matches!(self.level, WarningLevel::Critical)
     }
+
+    pub fn is_field_warning(&self) -> bool {
+        !self.field_name.is_empty()
+    }
+}
+
+/// `<field title>: <description>`, or just the description for record-level warnings
+impl std::fmt::Display for CwrWarning<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.field_title.is_empty() {
+            write!(f, "{}", self.description)
+        } else {
+            write!(f, "{}: {}", self.field_title, self.description)
+        }
+    }
+}
+
+/// Serialized as its message, so JSON output keeps a list of strings
+impl serde::Serialize for CwrWarning<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_warning_messages() {
+        let field_warning = CwrWarning {
+            field_name: "language_code",
+            field_title: "Language code",
+            source_str: Cow::Borrowed("QQ"),
+            level: WarningLevel::Warning,
+            description: "Language code 'QQ' not found".into(),
+        };
+        assert!(field_warning.is_field_warning());
+        assert_eq!(field_warning.to_string(), "Language code: Language code 'QQ' not found");
+
+        let record_warning = CwrWarning::record(WarningLevel::Info, "Record starts at column 84 of the line");
+        assert!(!record_warning.is_field_warning());
+        assert_eq!(record_warning.to_string(), "Record starts at column 84 of the line");
+    }
 }
@@ -0,0 +1,216 @@
+//! Chaining record transforms (obfuscate, rewrite, check, ...) over a single streaming pass
+//!
+//! A [`Pipeline`] runs each parsed record through its transforms in order. A transform may change the record,
+//! or return `None` to drop it; dropped records are not seen by later transforms. Parse errors pass through
+//! untouched. The output stream can go to a handler, a writer (with regenerated control records and sequence
+//! numbers) or anywhere else an iterator of parsed records is accepted.
+
+use crate::cwr_handler::CwrHandler;
+use crate::domain_types::{CharacterSet, CwrVersion};
+use crate::error::CwrParseError;
+use crate::parser::{ParsedRecord, ParsingContext};
+use crate::transmission_writer::{TransmissionTotals, write_cwr_file};
+use std::io::Write;
+
+/// State shared by all transforms of a pipeline
+#[derive(Debug, Clone, Default)]
+pub struct PipelineContext {
+    /// Parsing context of the first record, i.e. the file's version and character set
+    pub parsing: Option<ParsingContext>,
+    pub records_read: usize,
+    pub records_dropped: usize,
+}
+
+/// One step of a [`Pipeline`]: returns the (possibly changed) record, or `None` to drop it
+pub trait RecordTransform {
+    fn transform(&mut self, record: ParsedRecord, context: &mut PipelineContext) -> Option<ParsedRecord>;
+}
+
+impl<F> RecordTransform for F
+where
+    F: FnMut(ParsedRecord, &mut PipelineContext) -> Option<ParsedRecord>,
+{
+    fn transform(&mut self, record: ParsedRecord, context: &mut PipelineContext) -> Option<ParsedRecord> {
+        self(record, context)
+    }
+}
+
+/// Record transforms applied in order
+#[derive(Default)]
+pub struct Pipeline<'a> {
+    transforms: Vec<Box<dyn RecordTransform + 'a>>,
+    context: PipelineContext,
+}
+
+impl<'a> Pipeline<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a transform after the existing ones
+    pub fn then(mut self, transform: impl RecordTransform + 'a) -> Self {
+        self.transforms.push(Box::new(transform));
+        self
+    }
+
+    /// Runs a single record through all transforms
+    pub fn apply(&mut self, record: ParsedRecord) -> Option<ParsedRecord> {
+        self.context.records_read += 1;
+        if self.context.parsing.is_none() {
+            self.context.parsing = Some(record.context.clone());
+        }
+        let mut record = Some(record);
+        for transform in &mut self.transforms {
+            record = record.and_then(|record| transform.transform(record, &mut self.context));
+        }
+        if record.is_none() {
+            self.context.records_dropped += 1;
+        }
+        record
+    }
+
+    /// Lazily runs a stream of parsed records through the pipeline
+    pub fn stream<I>(self, records: I) -> PipelineStream<'a, I::IntoIter>
+    where
+        I: IntoIterator<Item = Result<ParsedRecord, CwrParseError>>,
+    {
+        PipelineStream { pipeline: self, records: records.into_iter() }
+    }
+
+    /// Runs the records through the pipeline into `handler`, returning the processing summary with its report
+    pub fn run_with_handler<I, H>(
+        self, records: I, handler: H,
+    ) -> Result<crate::ProcessingSummary, Box<dyn std::error::Error>>
+    where
+        I: IntoIterator<Item = Result<ParsedRecord, CwrParseError>>,
+        H: CwrHandler,
+        H::Error: 'static,
+    {
+        crate::process_records_with_handler("<pipeline>", self.stream(records), handler)
+    }
+
+    /// Runs the records through the pipeline and writes the result as a transmission in the first record's
+    /// version and character set, with regenerated GRT/TRL records and sequence numbers (see [`write_cwr_file`]).
+    /// Stops at the first parse error.
+    pub fn write<I, W>(self, records: I, writer: W) -> Result<TransmissionTotals, CwrParseError>
+    where
+        I: IntoIterator<Item = Result<ParsedRecord, CwrParseError>>,
+        W: Write,
+    {
+        let mut stream = self.stream(records);
+        let first = match stream.next() {
+            Some(record) => record?,
+            None => return Err(CwrParseError::BadFormat("Transmission has no records".to_string())),
+        };
+        let version = CwrVersion(first.context.cwr_version);
+        let character_set = first.context.character_set.clone().unwrap_or(CharacterSet::ASCII);
+
+        let mut error = None;
+        let records = std::iter::once(first.record).chain(stream.map_while(|result| match result {
+            Ok(parsed_record) => Some(parsed_record.record),
+            Err(e) => {
+                error = Some(e);
+                None
+            }
+        }));
+        let totals = write_cwr_file(records, writer, version, character_set)?;
+        match error {
+            Some(e) => Err(e),
+            None => Ok(totals),
+        }
+    }
+}
+
+/// Iterator over the records left after a [`Pipeline`]
+pub struct PipelineStream<'a, I> {
+    pipeline: Pipeline<'a>,
+    records: I,
+}
+
+impl<'a, I> PipelineStream<'a, I> {
+    pub fn context(&self) -> &PipelineContext {
+        &self.pipeline.context
+    }
+}
+
+impl<I: Iterator<Item = Result<ParsedRecord, CwrParseError>>> Iterator for PipelineStream<'_, I> {
+    type Item = Result<ParsedRecord, CwrParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.records.next()? {
+                Ok(record) => {
+                    if let Some(record) = self.pipeline.apply(record) {
+                        return Some(Ok(record));
+                    }
+                }
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cwr_registry::CwrRegistry;
+
+    fn records() -> impl Iterator<Item = Result<ParsedRecord, CwrParseError>> {
+        let nwr = |transaction: u32, title: &str, work_num: &str| {
+            format!("NWR{:08}00000000{:<60}SW{}        SER        Y       ORI", transaction, title, work_num)
+        };
+        let content = format!(
+            "HDRPB285606836WARNER CHAPPELL MUSIC PUBLISHING LTD         01.102022122112541120221221\n\
+             GRHNWR0000102.100000000000  \n{}\n{:<80}\n{}\nGRT000010000000200000006\nTRL000000010000000200000008\n",
+            nwr(0, "First Song", "0000000001"),
+            "ALT0000000000000001ALTERNATE TITLE",
+            nwr(1, "Second Song", "0000000002")
+        );
+        crate::process_cwr_bytes_stream(content.as_bytes(), &crate::ParseOptions::default())
+            .unwrap()
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    #[test]
+    fn test_pipeline_transforms_and_drops_records() {
+        let drop_alternate_titles =
+            |record: ParsedRecord, _: &mut PipelineContext| (record.record.record_type() != "ALT").then_some(record);
+        let upper_case_titles = |mut record: ParsedRecord, _: &mut PipelineContext| {
+            if let CwrRegistry::Nwr(nwr) = &mut record.record {
+                nwr.work_title = nwr.work_title.to_uppercase();
+            }
+            Some(record)
+        };
+
+        let mut stream = Pipeline::new().then(drop_alternate_titles).then(upper_case_titles).stream(records());
+        let titles: Vec<String> = stream
+            .by_ref()
+            .filter_map(|result| match result.unwrap().record {
+                CwrRegistry::Nwr(nwr) => Some(nwr.work_title.trim_end().to_string()),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(titles, ["FIRST SONG", "SECOND SONG"]);
+        assert_eq!(stream.context().records_read, 7);
+        assert_eq!(stream.context().records_dropped, 1);
+        assert_eq!(stream.context().parsing.as_ref().map(|context| context.cwr_version), Some(2.1));
+    }
+
+    #[test]
+    fn test_pipeline_write_regenerates_control_records() {
+        let drop_second_work = |record: ParsedRecord, _: &mut PipelineContext| match &record.record {
+            CwrRegistry::Nwr(nwr) if nwr.work_title.starts_with("Second") => None,
+            _ => Some(record),
+        };
+
+        let mut output = Vec::new();
+        let totals = Pipeline::new().then(drop_second_work).write(records(), &mut output).unwrap();
+
+        assert_eq!((totals.groups, totals.transactions), (1, 1));
+        let output = String::from_utf8(output).unwrap();
+        assert!(!output.contains("Second Song"));
+        assert!(output.contains("ALTERNATE TITLE"));
+    }
+}
@@ -1,13 +1,14 @@
 use crate::domain_types::*;
 use allegro_cwr_derive::CwrRecord;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 /// ACK - Acknowledgement of Transaction Record
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, CwrRecord)]
 #[cwr(validator = ack_custom_validate, test_data = "ACK0000000100000001200501011200000000100000001NWRTEST WORK TITLE                                          SW123456789012345678                    20050102AS   ")]
 pub struct AckRecord {
     #[cwr(title = "Always 'ACK'", start = 0, len = 3)]
-    pub record_type: String,
+    pub record_type: Arc<str>,
 
     #[cwr(title = "Transaction sequence number", start = 3, len = 8)]
     pub transaction_sequence_num: Number,
@@ -59,7 +60,7 @@ fn ack_custom_validate(record: &mut AckRecord) -> Vec<CwrWarning<'static>> {
             field_title: "Creation title (conditional)",
             source_str: std::borrow::Cow::Borrowed(""),
             level: WarningLevel::Critical,
-            description: "Creation Title is required when ACK responds to NWR or REV transaction".to_string(),
+            description: "Creation Title is required when ACK responds to NWR or REV transaction".into(),
         });
     }
 
@@ -73,7 +74,7 @@ fn ack_custom_validate(record: &mut AckRecord) -> Vec<CwrWarning<'static>> {
             field_title: "Submitter creation number (conditional)",
             source_str: std::borrow::Cow::Borrowed(""),
             level: WarningLevel::Critical,
-            description: "Submitter Creation Number is required when ACK responds to a transaction".to_string(),
+            description: "Submitter Creation Number is required when ACK responds to a transaction".into(),
         });
     }
 
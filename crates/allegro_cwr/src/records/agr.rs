@@ -1,13 +1,14 @@
 use crate::domain_types::*;
 use allegro_cwr_derive::CwrRecord;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 /// AGR - Agreement Transaction Record
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, CwrRecord)]
 #[cwr(validator = agr_custom_validate, test_data = "AGR00000001000000011234567890123               AA20231201                N        N                00001                 ")]
 pub struct AgrRecord {
     #[cwr(title = "Always 'AGR'", start = 0, len = 3)]
-    pub record_type: String,
+    pub record_type: Arc<str>,
 
     #[cwr(title = "Transaction sequence number", start = 3, len = 8)]
     pub transaction_sequence_num: Number,
@@ -77,8 +78,7 @@ fn agr_custom_validate(record: &mut AgrRecord) -> Vec<CwrWarning<'static>> {
             field_title: "Prior royalty start date YYYYMMDD (conditional)",
             source_str: std::borrow::Cow::Borrowed(""),
             level: WarningLevel::Critical,
-            description: "Prior Royalty Start Date is required when Prior Royalty Status is 'D' (Designated)"
-                .to_string(),
+            description: "Prior Royalty Start Date is required when Prior Royalty Status is 'D' (Designated)".into(),
         });
     }
 
@@ -92,54 +92,50 @@ fn agr_custom_validate(record: &mut AgrRecord) -> Vec<CwrWarning<'static>> {
             source_str: std::borrow::Cow::Borrowed(""),
             level: WarningLevel::Critical,
             description:
-                "Post-term Collection End Date is required when Post-term Collection Status is 'D' (Designated)"
-                    .to_string(),
+                "Post-term Collection End Date is required when Post-term Collection Status is 'D' (Designated)".into(),
         });
     }
 
     // Business rule: Date validations
     if let (start_date, Some(end_date)) =
         (&record.agreement_start_date.0, &record.agreement_end_date.as_ref().map(|d| &d.0))
+        && *end_date < start_date
     {
-        if *end_date < start_date {
-            warnings.push(CwrWarning {
-                field_name: "agreement_end_date",
-                field_title: "Agreement end date YYYYMMDD (optional)",
-                source_str: std::borrow::Cow::Owned(end_date.format("%Y%m%d").to_string()),
-                level: WarningLevel::Critical,
-                description: "Agreement End Date must be >= Agreement Start Date".to_string(),
-            });
-        }
+        warnings.push(CwrWarning {
+            field_name: "agreement_end_date",
+            field_title: "Agreement end date YYYYMMDD (optional)",
+            source_str: std::borrow::Cow::Owned(end_date.format("%Y%m%d").to_string()),
+            level: WarningLevel::Critical,
+            description: "Agreement End Date must be >= Agreement Start Date".into(),
+        });
     }
 
     // Business rule: Retention End Date must be >= Agreement End Date
     if let (Some(end_date), Some(retention_date)) =
         (&record.agreement_end_date.as_ref().map(|d| &d.0), &record.retention_end_date.as_ref().map(|d| &d.0))
+        && retention_date < end_date
     {
-        if retention_date < end_date {
-            warnings.push(CwrWarning {
-                field_name: "retention_end_date",
-                field_title: "Retention end date YYYYMMDD (optional)",
-                source_str: std::borrow::Cow::Owned(retention_date.format("%Y%m%d").to_string()),
-                level: WarningLevel::Critical,
-                description: "Retention End Date must be >= Agreement End Date".to_string(),
-            });
-        }
+        warnings.push(CwrWarning {
+            field_name: "retention_end_date",
+            field_title: "Retention end date YYYYMMDD (optional)",
+            source_str: std::borrow::Cow::Owned(retention_date.format("%Y%m%d").to_string()),
+            level: WarningLevel::Critical,
+            description: "Retention End Date must be >= Agreement End Date".into(),
+        });
     }
 
     // Business rule: Prior Royalty Start Date must be < Agreement Start Date
     if let (Some(prior_date), start_date) =
         (&record.prior_royalty_start_date.as_ref().map(|d| &d.0), &record.agreement_start_date.0)
+        && *prior_date >= start_date
     {
-        if *prior_date >= start_date {
-            warnings.push(CwrWarning {
-                field_name: "prior_royalty_start_date",
-                field_title: "Prior royalty start date YYYYMMDD (conditional)",
-                source_str: std::borrow::Cow::Owned(prior_date.format("%Y%m%d").to_string()),
-                level: WarningLevel::Critical,
-                description: "Prior Royalty Start Date must be < Agreement Start Date".to_string(),
-            });
-        }
+        warnings.push(CwrWarning {
+            field_name: "prior_royalty_start_date",
+            field_title: "Prior royalty start date YYYYMMDD (conditional)",
+            source_str: std::borrow::Cow::Owned(prior_date.format("%Y%m%d").to_string()),
+            level: WarningLevel::Critical,
+            description: "Prior Royalty Start Date must be < Agreement Start Date".into(),
+        });
     }
 
     // TODO: Additional business rules requiring broader context:
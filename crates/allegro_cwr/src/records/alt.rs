@@ -1,13 +1,14 @@
 use crate::domain_types::*;
 use allegro_cwr_derive::CwrRecord;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 /// ALT - Alternate Title Record
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, CwrRecord)]
 #[cwr(validator = alt_custom_validate, test_data = "ALT0000000200000326BABY CAN T YOU SEE                                          AT  ")]
 pub struct AltRecord {
     #[cwr(title = "Always 'ALT'", start = 0, len = 3)]
-    pub record_type: String,
+    pub record_type: Arc<str>,
 
     #[cwr(title = "Transaction sequence number", start = 3, len = 8)]
     pub transaction_sequence_num: Number,
@@ -36,7 +37,7 @@ fn alt_custom_validate(record: &mut AltRecord) -> Vec<CwrWarning<'static>> {
             field_title: "Alternate title",
             source_str: std::borrow::Cow::Borrowed(""),
             level: WarningLevel::Critical,
-            description: "Alternate title cannot be empty".to_string(),
+            description: "Alternate title cannot be empty".into(),
         });
     }
 
@@ -53,13 +54,12 @@ fn alt_custom_validate(record: &mut AltRecord) -> Vec<CwrWarning<'static>> {
             field_title: "Language code (conditional)",
             source_str: std::borrow::Cow::Borrowed(""),
             level: WarningLevel::Critical,
-            description: "Language code is required for translated and transliterated titles".to_string(),
+            description: "Language code is required for translated and transliterated titles".into(),
         });
     }
 
     // TODO: Additional business rules requiring broader context:
     // - Must follow a NWR/REV record (requires parsing context)
-    // - Language codes must be valid ISO 639-1 or ISO 639-2 codes (requires lookup table)
     // - Alternate titles should not duplicate the original work title (requires cross-record validation)
     // - Fictional titles (FT) and Original titles (OT) are v2.1+ features (requires version validation)
 
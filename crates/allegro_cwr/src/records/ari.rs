@@ -1,13 +1,14 @@
 use crate::domain_types::*;
 use allegro_cwr_derive::CwrRecord;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 /// ARI - Additional Related Information Record
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, CwrRecord)]
 #[cwr(validator = ari_custom_validate, test_data = "ARI0000000100000001021              ALL  Additional related information note for the work                                                                                                                ")]
 pub struct AriRecord {
     #[cwr(title = "Always 'ARI'", start = 0, len = 3)]
-    pub record_type: String,
+    pub record_type: Arc<str>,
 
     #[cwr(title = "Transaction sequence number", start = 3, len = 8)]
     pub transaction_sequence_num: Number,
@@ -36,13 +37,13 @@ fn ari_custom_validate(record: &mut AriRecord) -> Vec<CwrWarning<'static>> {
     let mut warnings = Vec::new();
 
     // Validate record type
-    if record.record_type != "ARI" {
+    if &*record.record_type != "ARI" {
         warnings.push(CwrWarning {
             field_name: "record_type",
             field_title: "Always 'ARI'",
-            source_str: std::borrow::Cow::Owned(record.record_type.clone()),
+            source_str: std::borrow::Cow::Owned(record.record_type.to_string()),
             level: WarningLevel::Critical,
-            description: "Record type must be 'ARI'".to_string(),
+            description: "Record type must be 'ARI'".into(),
         });
     }
 
@@ -55,7 +56,7 @@ fn ari_custom_validate(record: &mut AriRecord) -> Vec<CwrWarning<'static>> {
             field_title: "Society number",
             source_str: std::borrow::Cow::Owned(record.society_num.as_str().to_string()),
             level: WarningLevel::Critical,
-            description: "Society number must be 3 numeric digits".to_string(),
+            description: "Society number must be 3 numeric digits".into(),
         });
     }
 
@@ -70,7 +71,7 @@ fn ari_custom_validate(record: &mut AriRecord) -> Vec<CwrWarning<'static>> {
             field_title: "Work number (conditional)",
             source_str: std::borrow::Cow::Borrowed(""),
             level: WarningLevel::Critical,
-            description: "At least one of work number, subject code, or note must be provided".to_string(),
+            description: "At least one of work number, subject code, or note must be provided".into(),
         });
     }
 
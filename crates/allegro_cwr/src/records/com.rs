@@ -1,6 +1,7 @@
 use crate::domain_types::*;
 use allegro_cwr_derive::CwrRecord;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 /// COM - Composite Component Record
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, CwrRecord)]
@@ -10,7 +11,7 @@ use serde::{Deserialize, Serialize};
 )]
 pub struct ComRecord {
     #[cwr(title = "Always 'COM'", start = 0, len = 3)]
-    pub record_type: String,
+    pub record_type: Arc<str>,
 
     #[cwr(title = "Transaction sequence number", start = 3, len = 8)]
     pub transaction_sequence_num: Number,
@@ -28,7 +29,7 @@ pub struct ComRecord {
     pub submitter_work_num: Option<String>,
 
     #[cwr(title = "Duration HHMMSS (optional)", start = 104, len = 6)]
-    pub duration: Option<Time>,
+    pub duration: Option<Duration>,
 
     #[cwr(title = "Writer 1 last name", start = 110, len = 45)]
     pub writer_1_last_name: String,
@@ -66,7 +67,7 @@ fn com_custom_validate(record: &mut ComRecord) -> Vec<CwrWarning<'static>> {
             field_title: "Title",
             source_str: std::borrow::Cow::Borrowed(""),
             level: WarningLevel::Critical,
-            description: "Component title cannot be empty".to_string(),
+            description: "Component title cannot be empty".into(),
         });
     }
 
@@ -77,7 +78,7 @@ fn com_custom_validate(record: &mut ComRecord) -> Vec<CwrWarning<'static>> {
             field_title: "Writer 1 last name",
             source_str: std::borrow::Cow::Borrowed(""),
             level: WarningLevel::Critical,
-            description: "Writer 1 last name cannot be empty".to_string(),
+            description: "Writer 1 last name cannot be empty".into(),
         });
     }
 
@@ -90,7 +91,7 @@ fn com_custom_validate(record: &mut ComRecord) -> Vec<CwrWarning<'static>> {
                 field_title: "ISWC of component (optional)",
                 source_str: std::borrow::Cow::Owned(iswc.clone()),
                 level: WarningLevel::Warning,
-                description: "ISWC should be exactly 11 characters (T-NNNNNNNN-C format)".to_string(),
+                description: "ISWC should be exactly 11 characters (T-NNNNNNNN-C format)".into(),
             });
         }
     }
@@ -1,13 +1,14 @@
 use crate::domain_types::*;
 use allegro_cwr_derive::CwrRecord;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 /// Starts a new group of transactions within a CWR transmission.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, CwrRecord)]
 #[cwr(validator = grh_custom_validate, test_data = "GRHAGR0000102.20            ")]
 pub struct GrhRecord {
     #[cwr(title = "Always 'GRH'", start = 0, len = 3)]
-    pub record_type: String,
+    pub record_type: Arc<str>,
 
     #[cwr(title = "Transaction type code", start = 3, len = 3)]
     pub transaction_type: TransactionType,
@@ -42,7 +43,8 @@ fn grh_custom_validate(record: &mut GrhRecord) -> Vec<CwrWarning<'static>> {
             description: format!(
                 "Version number '{}' is not a valid CWR version (expected: 02.00, 02.10, or 02.20)",
                 version_str
-            ),
+            )
+            .into(),
         });
     }
 
@@ -54,7 +56,7 @@ fn grh_custom_validate(record: &mut GrhRecord) -> Vec<CwrWarning<'static>> {
             field_title: "Group identifier within the transmission",
             source_str: std::borrow::Cow::Owned(record.group_id.as_str()),
             level: WarningLevel::Critical,
-            description: "Group ID must start at 1, not 0".to_string(),
+            description: "Group ID must start at 1, not 0".into(),
         });
     }
 
@@ -1,13 +1,14 @@
 use crate::domain_types::*;
 use allegro_cwr_derive::CwrRecord;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 /// Marks the end of a group and contains summary counts for that group.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, CwrRecord)]
 #[cwr(validator = grt_custom_validate, test_data = "GRT000010000001400000365             ")]
 pub struct GrtRecord {
     #[cwr(title = "Always 'GRT'", start = 0, len = 3)]
-    pub record_type: String,
+    pub record_type: Arc<str>,
 
     #[cwr(title = "Group ID", start = 3, len = 5)]
     pub group_id: GroupId,
@@ -33,16 +34,17 @@ fn grt_custom_validate(record: &mut GrtRecord) -> Vec<CwrWarning<'static>> {
 
     // Business rule: Currency Indicator is mandatory if Total Monetary Value is provided
     // Note: These fields are marked as "ignored for CWR" in spec but validate relationship if present
-    if let Some(monetary_value) = &record.total_monetary_value {
-        if monetary_value.0 > 0 && record.currency_indicator.is_none() {
-            warnings.push(CwrWarning {
+    if let Some(monetary_value) = &record.total_monetary_value
+        && monetary_value.0 > 0
+        && record.currency_indicator.is_none()
+    {
+        warnings.push(CwrWarning {
                 field_name: "currency_indicator",
                 field_title: "Currency indicator (conditional)",
                 source_str: std::borrow::Cow::Borrowed(""),
                 level: WarningLevel::Warning,
-                description: "Currency Indicator should be provided when Total Monetary Value is present (though both fields are ignored for CWR processing)".to_string(),
+                description: "Currency Indicator should be provided when Total Monetary Value is present (though both fields are ignored for CWR processing)".into(),
             });
-        }
     }
 
     // Business rule: Group ID must match the preceding GRH record
@@ -1,13 +1,14 @@
 use crate::domain_types::*;
 use allegro_cwr_derive::CwrRecord;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 /// Contains information about the sender and the transmission itself.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, CwrRecord)]
 #[cwr(validator = hdr_custom_validate, test_data = "HDRPB123456789BMI MUSIC                                    01.1020050101120000200501010              2.2  1DEV MUSIC SOFTWARE VERSION 1.0  MUSIC PACKAGE VERSION 2.0   ")]
 pub struct HdrRecord {
     #[cwr(title = "Always 'HDR'", start = 0, len = 3)]
-    pub record_type: String,
+    pub record_type: Arc<str>,
 
     #[cwr(title = "Sender type", start = 3, len = 2)]
     pub sender_type: SenderType,
@@ -70,7 +71,8 @@ fn hdr_custom_validate(record: &mut HdrRecord) -> Vec<CwrWarning<'static>> {
                     description: format!(
                         "Sender ID should be numeric IPI for sender type {}",
                         record.sender_type.as_str()
-                    ),
+                    )
+                    .into(),
                 });
             } else if sender_id_str.len() < 9 || sender_id_str.len() > 11 {
                 warnings.push(CwrWarning {
@@ -78,7 +80,7 @@ fn hdr_custom_validate(record: &mut HdrRecord) -> Vec<CwrWarning<'static>> {
                     field_title: "Sender ID",
                     source_str: std::borrow::Cow::Owned(sender_id_str.to_string()),
                     level: WarningLevel::Warning,
-                    description: format!("IPI should be 9-11 digits, got {} digits", sender_id_str.len()),
+                    description: format!("IPI should be 9-11 digits, got {} digits", sender_id_str.len()).into(),
                 });
             }
 
@@ -90,7 +92,7 @@ fn hdr_custom_validate(record: &mut HdrRecord) -> Vec<CwrWarning<'static>> {
                     field_title: "Sender name",
                     source_str: std::borrow::Cow::Owned(sender_name_str.to_string()),
                     level: WarningLevel::Critical,
-                    description: "Sender name is required for Publisher/Administrative Agency/Writer".to_string(),
+                    description: "Sender name is required for Publisher/Administrative Agency/Writer".into(),
                 });
             }
         }
@@ -111,23 +113,26 @@ fn hdr_custom_validate(record: &mut HdrRecord) -> Vec<CwrWarning<'static>> {
                         description: format!(
                             "Sender ID '{}' not found in society codes or transmitter codes tables",
                             sender_id_str
-                        ),
+                        )
+                        .into(),
                     });
                 } else {
                     // It's a valid transmitter code, check if name matches
-                    if let Some(expected_name) = get_society_name_for_transmitter(sender_id_str) {
-                        if !sender_name_str.eq_ignore_ascii_case(expected_name) && !sender_name_str.is_empty() {
-                            warnings.push(CwrWarning {
-                                field_name: "sender_name",
-                                field_title: "Sender name",
-                                source_str: std::borrow::Cow::Owned(sender_name_str.to_string()),
-                                level: WarningLevel::Warning,
-                                description: format!(
-                                    "Sender name '{}' does not match expected name '{}' for transmitter code '{}'",
-                                    sender_name_str, expected_name, sender_id_str
-                                ),
-                            });
-                        }
+                    if let Some(expected_name) = get_society_name_for_transmitter(sender_id_str)
+                        && !sender_name_str.eq_ignore_ascii_case(expected_name)
+                        && !sender_name_str.is_empty()
+                    {
+                        warnings.push(CwrWarning {
+                            field_name: "sender_name",
+                            field_title: "Sender name",
+                            source_str: std::borrow::Cow::Owned(sender_name_str.to_string()),
+                            level: WarningLevel::Warning,
+                            description: format!(
+                                "Sender name '{}' does not match expected name '{}' for transmitter code '{}'",
+                                sender_name_str, expected_name, sender_id_str
+                            )
+                            .into(),
+                        });
                     }
                 }
             } else {
@@ -138,7 +143,7 @@ fn hdr_custom_validate(record: &mut HdrRecord) -> Vec<CwrWarning<'static>> {
                         field_title: "Sender name",
                         source_str: std::borrow::Cow::Owned(sender_name_str.to_string()),
                         level: WarningLevel::Warning,
-                        description: "Sender name should be provided for society sender type".to_string(),
+                        description: "Sender name should be provided for society sender type".into(),
                     });
                 } else {
                     // Check if sender_name matches the society code
@@ -148,7 +153,7 @@ fn hdr_custom_validate(record: &mut HdrRecord) -> Vec<CwrWarning<'static>> {
                             field_title: "Sender name",
                             source_str: std::borrow::Cow::Owned(sender_name_str.to_string()),
                             level: WarningLevel::Info,
-                            description: format!("Sender name '{}' does not match society code format - may be organization display name", sender_name_str),
+                            description: format!("Sender name '{}' does not match society code format - may be organization display name", sender_name_str).into(),
                         });
                     }
                 }
@@ -166,8 +171,7 @@ fn hdr_custom_validate(record: &mut HdrRecord) -> Vec<CwrWarning<'static>> {
                     field_title: "Sender ID",
                     source_str: std::borrow::Cow::Owned(combined_id.clone()),
                     level: WarningLevel::Critical,
-                    description: "Combined sender type prefix + sender ID must be numeric for IPNN > 9 digits"
-                        .to_string(),
+                    description: "Combined sender type prefix + sender ID must be numeric for IPNN > 9 digits".into(),
                 });
             } else if combined_id.len() < 10 || combined_id.len() > 12 {
                 warnings.push(CwrWarning {
@@ -175,7 +179,7 @@ fn hdr_custom_validate(record: &mut HdrRecord) -> Vec<CwrWarning<'static>> {
                     field_title: "Sender ID",
                     source_str: std::borrow::Cow::Owned(combined_id),
                     level: WarningLevel::Warning,
-                    description: "Combined IPI should be 10-12 digits for numeric prefix case".to_string(),
+                    description: "Combined IPI should be 10-12 digits for numeric prefix case".into(),
                 });
             }
 
@@ -185,10 +189,12 @@ fn hdr_custom_validate(record: &mut HdrRecord) -> Vec<CwrWarning<'static>> {
                     field_title: "Sender name",
                     source_str: std::borrow::Cow::Owned(sender_name_str.to_string()),
                     level: WarningLevel::Critical,
-                    description: "Sender name is required for numeric prefix IPI".to_string(),
+                    description: "Sender name is required for numeric prefix IPI".into(),
                 });
             }
         }
+        // Already reported when the sender type was parsed
+        SenderType::Unknown(_) => {}
     }
 
     warnings
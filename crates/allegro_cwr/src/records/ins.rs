@@ -1,13 +1,14 @@
 use crate::domain_types::*;
 use allegro_cwr_derive::CwrRecord;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 /// INS - Instrumentation Summary Record
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, CwrRecord)]
 #[cwr(validator = ins_custom_validate, test_data = "INS000000010000000104 ORCHFULL ORCHESTRA WITH STRINGS AND BRASS SECTION    ")]
 pub struct InsRecord {
     #[cwr(title = "Always 'INS'", start = 0, len = 3)]
-    pub record_type: String,
+    pub record_type: Arc<str>,
 
     #[cwr(title = "Transaction sequence number", start = 3, len = 8)]
     pub transaction_sequence_num: Number,
@@ -15,7 +16,7 @@ pub struct InsRecord {
     #[cwr(title = "Record sequence number", start = 11, len = 8)]
     pub record_sequence_num: Number,
 
-    #[cwr(title = "Number of voices (optional)", start = 19, len = 3)]
+    #[cwr(title = "Number of voices (optional)", start = 19, len = 3, validate = validate_number_of_voices)]
     pub number_of_voices: Option<Number>,
 
     #[cwr(title = "Standard instrumentation type (conditional)", start = 22, len = 3)]
@@ -30,31 +31,18 @@ fn ins_custom_validate(record: &mut InsRecord) -> Vec<CwrWarning<'static>> {
     let mut warnings = Vec::new();
 
     // Validate record type
-    if record.record_type != "INS" {
+    if &*record.record_type != "INS" {
         warnings.push(CwrWarning {
             field_name: "record_type",
             field_title: "Always 'INS'",
-            source_str: std::borrow::Cow::Owned(record.record_type.clone()),
+            source_str: std::borrow::Cow::Owned(record.record_type.to_string()),
             level: WarningLevel::Critical,
-            description: "Record type must be 'INS'".to_string(),
+            description: "Record type must be 'INS'".into(),
         });
     }
 
     // Validate transaction sequence number is numeric
     // Validate record sequence number is numeric
-    // Validate number of voices if present
-    if let Some(ref voices) = record.number_of_voices {
-        if voices.0 == 0 {
-            warnings.push(CwrWarning {
-                field_name: "number_of_voices",
-                field_title: "Number of voices (optional)",
-                source_str: std::borrow::Cow::Owned(voices.to_string()),
-                level: WarningLevel::Warning,
-                description: "Number of voices should be greater than 0 if specified".to_string(),
-            });
-        }
-    }
-
     // Standard instrumentation type validation is now handled by the StandardInstrumentationType domain type
 
     // Conditional validation: at least one of standard_instrumentation_type or instrumentation_description must be present
@@ -66,10 +54,45 @@ fn ins_custom_validate(record: &mut InsRecord) -> Vec<CwrWarning<'static>> {
             field_title: "Standard instrumentation type (conditional)",
             source_str: std::borrow::Cow::Borrowed(""),
             level: WarningLevel::Critical,
-            description: "Either standard instrumentation type or instrumentation description must be provided"
-                .to_string(),
+            description: "Either standard instrumentation type or instrumentation description must be provided".into(),
         });
     }
 
     warnings
 }
+
+fn validate_number_of_voices(
+    value: &Option<Number>, field_name: &'static str, field_title: &'static str,
+) -> Vec<CwrWarning<'static>> {
+    // Validate number of voices if present
+    if let Some(voices) = value
+        && voices.0 == 0
+    {
+        return vec![CwrWarning {
+            field_name,
+            field_title,
+            source_str: std::borrow::Cow::Owned(voices.to_string()),
+            level: WarningLevel::Warning,
+            description: "Number of voices should be greater than 0 if specified".into(),
+        }];
+    }
+    Vec::new()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_field_validator_warnings_are_merged() {
+        let (_, warnings) =
+            InsRecord::parse("INS0000000100000001000ORC                                                  ");
+        assert!(warnings.iter().any(|w| w.field_name == "number_of_voices"
+            && w.field_title == "Number of voices (optional)"
+            && w.description == "Number of voices should be greater than 0 if specified"));
+
+        let (_, warnings) =
+            InsRecord::parse("INS0000000100000001004ORC                                                  ");
+        assert!(warnings.iter().all(|w| w.field_name != "number_of_voices"));
+    }
+}
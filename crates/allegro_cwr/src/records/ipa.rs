@@ -1,13 +1,14 @@
 use crate::domain_types::*;
 use allegro_cwr_derive::CwrRecord;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 /// IPA - Interested Party of Agreement Record
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, CwrRecord)]
 #[cwr(validator = ipa_custom_validate, test_data = "IPA0000000100000001AS                        123456789JONES                                                                      BMI01000   00000   00000")]
 pub struct IpaRecord {
     #[cwr(title = "Always 'IPA'", start = 0, len = 3)]
-    pub record_type: String,
+    pub record_type: Arc<str>,
 
     #[cwr(title = "Transaction sequence number", start = 3, len = 8)]
     pub transaction_sequence_num: Number,
@@ -67,7 +68,7 @@ fn ipa_custom_validate(record: &mut IpaRecord) -> Vec<CwrWarning<'static>> {
             field_title: "PR share (conditional)",
             source_str: std::borrow::Cow::Borrowed(""),
             level: WarningLevel::Critical,
-            description: "At least one of PR, MR, or SR share must be > 0".to_string(),
+            description: "At least one of PR, MR, or SR share must be > 0".into(),
         });
     }
 
@@ -81,7 +82,7 @@ fn ipa_custom_validate(record: &mut IpaRecord) -> Vec<CwrWarning<'static>> {
             field_title: "PR affiliation society (conditional)",
             source_str: std::borrow::Cow::Borrowed(""),
             level: WarningLevel::Critical,
-            description: "PR affiliation society is required when PR share > 0".to_string(),
+            description: "PR affiliation society is required when PR share > 0".into(),
         });
     }
 
@@ -94,7 +95,7 @@ fn ipa_custom_validate(record: &mut IpaRecord) -> Vec<CwrWarning<'static>> {
             field_title: "MR affiliation society (conditional)",
             source_str: std::borrow::Cow::Borrowed(""),
             level: WarningLevel::Critical,
-            description: "MR affiliation society is required when MR share > 0".to_string(),
+            description: "MR affiliation society is required when MR share > 0".into(),
         });
     }
 
@@ -107,21 +108,21 @@ fn ipa_custom_validate(record: &mut IpaRecord) -> Vec<CwrWarning<'static>> {
             field_title: "SR affiliation society (conditional)",
             source_str: std::borrow::Cow::Borrowed(""),
             level: WarningLevel::Critical,
-            description: "SR affiliation society is required when SR share > 0".to_string(),
+            description: "SR affiliation society is required when SR share > 0".into(),
         });
     }
 
     // Business rule: Writer first name only allowed for OS/OG agreements with assignor role
     if matches!(record.agreement_role_code, AgreementRoleCode::Acquirer)
-        && record.interested_party_writer_first_name.is_some()
-        && !record.interested_party_writer_first_name.as_ref().is_none_or(|s| s.trim().is_empty())
+        && let Some(first_name) = record.interested_party_writer_first_name.as_ref()
+        && !first_name.trim().is_empty()
     {
         warnings.push(CwrWarning {
             field_name: "interested_party_writer_first_name",
             field_title: "Interested party writer first name (optional)",
-            source_str: std::borrow::Cow::Owned(record.interested_party_writer_first_name.as_ref().unwrap().clone()),
+            source_str: std::borrow::Cow::Owned(first_name.clone()),
             level: WarningLevel::Warning,
-            description: "Writer first name typically only used for OS/OG agreements with assignor role".to_string(),
+            description: "Writer first name typically only used for OS/OG agreements with assignor role".into(),
         });
     }
 
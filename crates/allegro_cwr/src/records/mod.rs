@@ -9,7 +9,7 @@ use crate::error::CwrParseError;
 #[derive(Debug)]
 pub struct ParseResult<T> {
     pub record: T,
-    pub warnings: Vec<String>,
+    pub warnings: Vec<crate::parsing::CwrWarning<'static>>,
 }
 
 /// Trait for getting the record type from any record instance
@@ -17,6 +17,115 @@ pub trait RecordType {
     fn record_type(&self) -> &str;
 }
 
+/// Layout metadata for a single record field, generated by the `CwrRecord` derive
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize)]
+pub struct FieldSpec {
+    /// Rust field name (e.g., `work_title`)
+    pub name: &'static str,
+    /// Human-readable title from the CWR specification
+    pub title: &'static str,
+    /// Zero-based start position in the line
+    pub start: usize,
+    /// Field width in characters
+    pub len: usize,
+    /// First CWR version containing this field, if not present in all versions
+    pub min_version: Option<f32>,
+    /// Whether the field may be blank
+    pub optional: bool,
+    pub kind: FieldKind,
+    /// Position overrides for older versions, sorted by `max_version`
+    pub layouts: &'static [FieldLayout],
+}
+
+/// What a field holds, for comparisons that ignore formatting (see [`CwrRegistry::semantically_eq`])
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub enum FieldKind {
+    /// Free text: titles, names, identifiers without a fixed format
+    Text,
+    /// A `YYYYMMDD` date; all zeros is the same as blank
+    Date,
+    /// Any other typed value (lookup codes, flags, numbers); compared case-insensitively
+    Code,
+}
+
+/// Field position used for CWR versions up to and including `max_version`
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize)]
+pub struct FieldLayout {
+    pub max_version: f32,
+    pub start: usize,
+    pub len: usize,
+}
+
+impl FieldSpec {
+    /// Returns the field's `(start, len)` in the given CWR version
+    pub fn layout_for(&self, version: f32) -> (usize, usize) {
+        self.layouts
+            .iter()
+            .find(|layout| version <= layout.max_version)
+            .map_or((self.start, self.len), |layout| (layout.start, layout.len))
+    }
+}
+
+/// A field of a record with its stringified value: the value as it would be written, with trailing spaces removed
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct FieldValue {
+    pub name: &'static str,
+    pub title: &'static str,
+    pub value: String,
+}
+
+/// Field text by position in `FIELD_SPECS`, generated by the `CwrRecord` derive
+pub(crate) trait FieldText {
+    fn field_text(&self, index: usize) -> String;
+}
+
+/// Iterator over the fields of a record, in declaration order; see [`CwrRegistry::fields`]
+pub struct Fields<'a> {
+    record: &'a dyn FieldText,
+    specs: std::slice::Iter<'static, FieldSpec>,
+    index: usize,
+}
+
+impl<'a> Fields<'a> {
+    pub(crate) fn new(record: &'a dyn FieldText, specs: &'static [FieldSpec]) -> Self {
+        Self { record, specs: specs.iter(), index: 0 }
+    }
+}
+
+impl Iterator for Fields<'_> {
+    type Item = FieldValue;
+
+    fn next(&mut self) -> Option<FieldValue> {
+        let spec = self.specs.next()?;
+        let value = self.record.field_text(self.index);
+        self.index += 1;
+        Some(FieldValue { name: spec.name, title: spec.title, value })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.specs.size_hint()
+    }
+}
+
+impl ExactSizeIterator for Fields<'_> {}
+
+/// Returns an error if a record type does not exist in the given CWR version
+pub fn check_record_version(
+    record_type: &str, version: f32, min_version: Option<f32>, max_version: Option<f32>,
+) -> Result<(), CwrParseError> {
+    let too_old = min_version.is_some_and(|min| version < min);
+    let too_new = max_version.is_some_and(|max| version > max);
+    if too_old || too_new {
+        return Err(CwrParseError::UnsupportedRecordVersion {
+            record_type: record_type.to_string(),
+            version,
+            min_version,
+            max_version,
+        });
+    }
+    Ok(())
+}
+
 /// Trait that all CWR record types must implement
 pub trait CwrRecord {
     /// The 3-character record type codes this record handles
@@ -27,6 +136,30 @@ pub trait CwrRecord {
     where
         Self: Sized;
 
+    /// Parse a CWR line using the field layout of the given CWR version
+    fn from_cwr_line_with_version(line: &str, version: Option<f32>) -> Result<ParseResult<Self>, CwrParseError>
+    where
+        Self: Sized;
+
+    /// Parse a source line whose fields are decoded from its own character set
+    fn from_cwr_source(
+        line: &crate::parsing::CwrLine<'_>, version: Option<f32>,
+    ) -> Result<ParseResult<Self>, CwrParseError>
+    where
+        Self: Sized;
+
+    /// Layout of every field in this record
+    /// (Auto-generated by derive macro as `FIELD_SPECS`)
+    fn field_specs() -> &'static [FieldSpec]
+    where
+        Self: Sized;
+
+    /// CWR version range this record type exists in, as `(min_version, max_version)`
+    /// (Auto-generated by derive macro from `#[cwr(min_version = ..., max_version = ...)]`)
+    fn version_range() -> (Option<f32>, Option<f32>)
+    where
+        Self: Sized;
+
     /// Convert this record into the registry enum variant
     fn into_registry(self) -> CwrRegistry;
 
@@ -109,6 +242,60 @@ pub use xrf::XrfRecord;
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::domain_types::TransactionType;
+
+    #[test]
+    fn test_record_version_range() {
+        assert_eq!(XrfRecord::version_range(), (Some(2.2), None));
+        assert_eq!(HdrRecord::version_range(), (None, None));
+
+        let line = "XRF0000000100000001ISWT1234567890123WY";
+        let record = XrfRecord::from_cwr_line(line).unwrap().record;
+        let character_set = crate::domain_types::CharacterSet::ASCII;
+        assert!(record.try_to_cwr_record_bytes(&crate::domain_types::CwrVersion(2.2), &character_set).is_ok());
+
+        let err = record.try_to_cwr_record_bytes(&crate::domain_types::CwrVersion(2.1), &character_set).unwrap_err();
+        assert_eq!(err.to_string(), "XRF record is not valid in CWR version 2.1 (requires version 2.2 or later)");
+    }
+
+    #[test]
+    fn test_field_specs() {
+        let specs = TerRecord::field_specs();
+        assert_eq!(specs.len(), TerRecord::FIELD_SPECS.len());
+        assert_eq!(specs[0].name, "record_type");
+        assert_eq!((specs[0].start, specs[0].len), (0, 3));
+
+        let pwr_writer = PwrRecord::FIELD_SPECS.iter().find(|f| f.name == "writer_ip_num").unwrap();
+        assert_eq!((pwr_writer.start, pwr_writer.len), (101, 9));
+        assert_eq!(pwr_writer.min_version, Some(2.1));
+        assert!(pwr_writer.optional);
+
+        // Fields are contiguous and non-overlapping
+        for record_specs in [HdrRecord::FIELD_SPECS, NwrRecord::FIELD_SPECS, SwrRecord::FIELD_SPECS] {
+            for pair in record_specs.windows(2) {
+                assert!(pair[0].start + pair[0].len <= pair[1].start, "{} overlaps {}", pair[0].name, pair[1].name);
+            }
+        }
+    }
+
+    #[test]
+    fn test_field_layout_for_version() {
+        const LAYOUTS: &[FieldLayout] =
+            &[FieldLayout { max_version: 2.0, start: 90, len: 5 }, FieldLayout { max_version: 2.1, start: 95, len: 9 }];
+        let spec = FieldSpec {
+            name: "example",
+            title: "Example",
+            start: 100,
+            len: 9,
+            min_version: None,
+            optional: true,
+            kind: FieldKind::Text,
+            layouts: LAYOUTS,
+        };
+        assert_eq!(spec.layout_for(2.0), (90, 5));
+        assert_eq!(spec.layout_for(2.1), (95, 9));
+        assert_eq!(spec.layout_for(2.2), (100, 9));
+    }
 
     #[test]
     fn test_hdr_record_trait() {
@@ -142,10 +329,68 @@ mod tests {
         assert_eq!(registry.record_type(), "NWR");
     }
 
+    #[test]
+    fn test_critical_warnings_reject_only_standalone_lines() {
+        // Cut off before the required fields at the end of the record
+        let line = format!("NWR0000000100000001{:<60}SW0000000001", "CRITICAL SONG");
+        assert!(NwrRecord::from_cwr_line(&line).is_err());
+
+        let parsed = <NwrRecord as CwrRecord>::from_cwr_line(&line).unwrap();
+        assert_eq!(parsed.record.work_title.trim_end(), "CRITICAL SONG");
+        assert!(parsed.warnings.iter().any(|w| w.is_critical()));
+
+        // Field values that happen to mention "Critical" do not reject a complete record
+        let line = format!(
+            "{:<260}",
+            format!("NWR0000000100000001{:<60}SW0000000001        SER        Y       ORI", "Critical Song")
+        );
+        assert!(NwrRecord::from_cwr_line(&line).is_ok());
+    }
+
     #[test]
     fn test_spu_record_trait() {
         // Test that SpuRecord implements CwrRecord trait with multiple codes
         let codes = SpuRecord::record_codes();
         assert_eq!(codes, &["SPU", "OPU"]);
     }
+
+    #[test]
+    fn test_shared_code_roles() {
+        let line = "REV0000000100000001Test Song                                               SW0000000001        SER        Y       ORI                                                                                                                                               ";
+        let nwr = NwrRecord::from_cwr_line(line).unwrap().record;
+        assert_eq!(&*nwr.record_type, "REV");
+        assert_eq!(nwr.transaction_type(), TransactionType::REV);
+        assert!(nwr.is_revision());
+
+        let spu_line = "SPU0000000100000001011234567890PUBLISHER NAME                             N AS1234567890123456789    BMI  50.00000000000000000000000000000  N N                                                            ";
+        assert!(SpuRecord::from_cwr_line(spu_line).unwrap().record.is_controlled());
+        let opu = SpuRecord::from_cwr_line(&format!("OPU{}", &spu_line[3..])).unwrap().record;
+        assert_eq!(&*opu.record_type, "OPU");
+        assert!(!opu.is_controlled());
+
+        let owr_line = "OWR0000000000000226WOMA     WOMACK                                       BOBBY                          CA00000000000033188001021050000990000009900000 N                           B";
+        assert!(!SwrRecord::from_cwr_line(owr_line).unwrap().record.is_controlled());
+        let opt_line = "OPT0000000000000002ABKC           025000750000000I0840N001";
+        assert!(!SptRecord::from_cwr_line(opt_line).unwrap().record.is_controlled());
+        let swt_line = "SWT0000000000000227WOMA     050000000000000I2100N001";
+        assert!(SwtRecord::from_cwr_line(swt_line).unwrap().record.is_controlled());
+    }
+
+    #[test]
+    fn test_unknown_codes_round_trip() {
+        use crate::domain_types::{CharacterSet, CwrVersion, Flag, PublisherType};
+
+        let mut line = "SPU0000000100000001011234567890PUBLISHER NAME                             N AS1234567890123456789    BMI  50.00000000000000000000000000000  N N                                                            ".to_string();
+        line.replace_range(76..78, "ZZ");
+        line.replace_range(136.., "XZ");
+
+        let result = SpuRecord::from_cwr_line(&line).unwrap();
+        assert_eq!(result.record.publisher_type, Some(PublisherType::Unknown("ZZ".to_string())));
+        assert_eq!(result.record.special_agreements_indicator, Some(Flag::Other("X".to_string())));
+        assert_eq!(result.record.first_recording_refusal_ind, Some(Flag::Other("Z".to_string())));
+        assert!(result.warnings.iter().any(|w| w.contains("publisher type 'ZZ'")));
+
+        let bytes = result.record.to_cwr_record_bytes(&CwrVersion(2.2), &CharacterSet::ASCII);
+        assert_eq!(String::from_utf8(bytes).unwrap().trim_end(), line.trim_end());
+    }
 }
@@ -1,13 +1,14 @@
 use crate::domain_types::*;
 use allegro_cwr_derive::CwrRecord;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 /// MSG - Message Record
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, CwrRecord)]
 #[cwr(validator = msg_custom_validate, test_data = "MSG0000000100000001E00000002NWRR001Record rejected due to invalid format                                                                                                                            ")]
 pub struct MsgRecord {
     #[cwr(title = "Always 'MSG'", start = 0, len = 3)]
-    pub record_type: String,
+    pub record_type: Arc<str>,
 
     #[cwr(title = "Transaction sequence number", start = 3, len = 8)]
     pub transaction_sequence_num: Number,
@@ -39,13 +40,13 @@ fn msg_custom_validate(record: &mut MsgRecord) -> Vec<CwrWarning<'static>> {
     let mut warnings = Vec::new();
 
     // Validate record type
-    if record.record_type != "MSG" {
+    if &*record.record_type != "MSG" {
         warnings.push(CwrWarning {
             field_name: "record_type",
             field_title: "Always 'MSG'",
-            source_str: std::borrow::Cow::Owned(record.record_type.clone()),
+            source_str: std::borrow::Cow::Owned(record.record_type.to_string()),
             level: WarningLevel::Critical,
-            description: "Record type must be 'MSG'".to_string(),
+            description: "Record type must be 'MSG'".into(),
         });
     }
 
@@ -61,7 +62,7 @@ fn msg_custom_validate(record: &mut MsgRecord) -> Vec<CwrWarning<'static>> {
             field_title: "Record type",
             source_str: std::borrow::Cow::Owned(record.record_type_field.clone()),
             level: WarningLevel::Critical,
-            description: "Record type field must be exactly 3 characters".to_string(),
+            description: "Record type field must be exactly 3 characters".into(),
         });
     }
     // TODO: Validate against known CWR record types
@@ -75,7 +76,7 @@ fn msg_custom_validate(record: &mut MsgRecord) -> Vec<CwrWarning<'static>> {
             field_title: "Validation number",
             source_str: std::borrow::Cow::Owned(record.validation_number.clone()),
             level: WarningLevel::Critical,
-            description: "Validation number must be exactly 3 characters".to_string(),
+            description: "Validation number must be exactly 3 characters".into(),
         });
     }
 
@@ -86,7 +87,7 @@ fn msg_custom_validate(record: &mut MsgRecord) -> Vec<CwrWarning<'static>> {
             field_title: "Message text",
             source_str: std::borrow::Cow::Owned(record.message_text.clone()),
             level: WarningLevel::Critical,
-            description: "Message text cannot be empty".to_string(),
+            description: "Message text cannot be empty".into(),
         });
     }
 
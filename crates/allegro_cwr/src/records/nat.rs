@@ -1,6 +1,7 @@
 use crate::domain_types::*;
 use allegro_cwr_derive::CwrRecord;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 /// NAT - Non-Roman Alphabet Title Record
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, CwrRecord)]
@@ -10,7 +11,7 @@ use serde::{Deserialize, Serialize};
 )]
 pub struct NatRecord {
     #[cwr(title = "Always 'NAT'", start = 0, len = 3)]
-    pub record_type: String,
+    pub record_type: Arc<str>,
 
     #[cwr(title = "Transaction sequence number", start = 3, len = 8)]
     pub transaction_sequence_num: Number,
@@ -33,13 +34,13 @@ fn nat_custom_validate(record: &mut NatRecord) -> Vec<CwrWarning<'static>> {
     let mut warnings = Vec::new();
 
     // Validate record type
-    if record.record_type != "NAT" {
+    if &*record.record_type != "NAT" {
         warnings.push(CwrWarning {
             field_name: "record_type",
             field_title: "Always 'NAT'",
-            source_str: std::borrow::Cow::Owned(record.record_type.clone()),
+            source_str: std::borrow::Cow::Owned(record.record_type.to_string()),
             level: WarningLevel::Critical,
-            description: "Record type must be 'NAT'".to_string(),
+            description: "Record type must be 'NAT'".into(),
         });
     }
 
@@ -52,21 +53,22 @@ fn nat_custom_validate(record: &mut NatRecord) -> Vec<CwrWarning<'static>> {
             field_title: "Title",
             source_str: std::borrow::Cow::Owned(record.title.as_str().to_string()),
             level: WarningLevel::Critical,
-            description: "Title cannot be empty".to_string(),
+            description: "Title cannot be empty".into(),
         });
     }
 
     // Validate language code format if present (ISO 639-1)
-    if let Some(ref lang_code) = record.language_code {
-        if !lang_code.as_str().trim().is_empty() && lang_code.as_str().len() != 2 {
-            warnings.push(CwrWarning {
-                field_name: "language_code",
-                field_title: "Language code (optional)",
-                source_str: std::borrow::Cow::Owned(lang_code.as_str().to_string()),
-                level: WarningLevel::Warning,
-                description: "Language code should be 2 characters (ISO 639-1)".to_string(),
-            });
-        }
+    if let Some(ref lang_code) = record.language_code
+        && !lang_code.as_str().trim().is_empty()
+        && lang_code.as_str().len() != 2
+    {
+        warnings.push(CwrWarning {
+            field_name: "language_code",
+            field_title: "Language code (optional)",
+            source_str: std::borrow::Cow::Owned(lang_code.as_str().to_string()),
+            level: WarningLevel::Warning,
+            description: "Language code should be 2 characters (ISO 639-1)".into(),
+        });
     }
 
     // Note: TitleType validation is handled by the domain type parser
@@ -1,6 +1,7 @@
 use crate::domain_types::*;
 use allegro_cwr_derive::CwrRecord;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 /// Also handles NCT (Non-Roman Alphabet Title for Components) and NVT (Non-Roman Alphabet Original Title for Versions)
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, CwrRecord)]
@@ -11,7 +12,7 @@ use serde::{Deserialize, Serialize};
 )]
 pub struct NetRecord {
     #[cwr(title = "'NET', 'NCT', or 'NVT'", start = 0, len = 3)]
-    pub record_type: String,
+    pub record_type: Arc<str>,
 
     #[cwr(title = "Transaction sequence number", start = 3, len = 8)]
     pub transaction_sequence_num: Number,
@@ -31,15 +32,15 @@ fn net_custom_validate(record: &mut NetRecord) -> Vec<CwrWarning<'static>> {
     let mut warnings = Vec::new();
 
     // Validate record type
-    match record.record_type.as_str() {
+    match &*record.record_type {
         "NET" | "NCT" | "NVT" => {}
         _ => {
             warnings.push(CwrWarning {
                 field_name: "record_type",
                 field_title: "'NET', 'NCT', or 'NVT'",
-                source_str: std::borrow::Cow::Owned(record.record_type.clone()),
+                source_str: std::borrow::Cow::Owned(record.record_type.to_string()),
                 level: WarningLevel::Critical,
-                description: "Record type must be 'NET', 'NCT', or 'NVT'".to_string(),
+                description: "Record type must be 'NET', 'NCT', or 'NVT'".into(),
             });
         }
     }
@@ -53,21 +54,22 @@ fn net_custom_validate(record: &mut NetRecord) -> Vec<CwrWarning<'static>> {
             field_title: "Title",
             source_str: std::borrow::Cow::Owned(record.title.clone()),
             level: WarningLevel::Critical,
-            description: "Title cannot be empty".to_string(),
+            description: "Title cannot be empty".into(),
         });
     }
 
     // Validate language code format if present (ISO 639-1)
-    if let Some(ref lang_code) = record.language_code {
-        if !lang_code.as_str().trim().is_empty() && lang_code.as_str().len() != 2 {
-            warnings.push(CwrWarning {
-                field_name: "language_code",
-                field_title: "Language code (optional)",
-                source_str: std::borrow::Cow::Owned(lang_code.as_str().to_string()),
-                level: WarningLevel::Warning,
-                description: "Language code should be 2 characters (ISO 639-1)".to_string(),
-            });
-        }
+    if let Some(ref lang_code) = record.language_code
+        && !lang_code.as_str().trim().is_empty()
+        && lang_code.as_str().len() != 2
+    {
+        warnings.push(CwrWarning {
+            field_name: "language_code",
+            field_title: "Language code (optional)",
+            source_str: std::borrow::Cow::Owned(lang_code.as_str().to_string()),
+            level: WarningLevel::Warning,
+            description: "Language code should be 2 characters (ISO 639-1)".into(),
+        });
     }
 
     warnings
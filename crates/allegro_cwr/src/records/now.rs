@@ -1,6 +1,7 @@
 use crate::domain_types::*;
 use allegro_cwr_derive::CwrRecord;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 /// NOW - Non-Roman Alphabet Writer Name Record
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, CwrRecord)]
@@ -10,7 +11,7 @@ use serde::{Deserialize, Serialize};
 )]
 pub struct NowRecord {
     #[cwr(title = "Always 'NOW'", start = 0, len = 3)]
-    pub record_type: String,
+    pub record_type: Arc<str>,
 
     #[cwr(title = "Transaction sequence number", start = 3, len = 8)]
     pub transaction_sequence_num: Number,
@@ -36,13 +37,13 @@ fn now_custom_validate(record: &mut NowRecord) -> Vec<CwrWarning<'static>> {
     let mut warnings = Vec::new();
 
     // Validate record type
-    if record.record_type != "NOW" {
+    if &*record.record_type != "NOW" {
         warnings.push(CwrWarning {
             field_name: "record_type",
             field_title: "Always 'NOW'",
-            source_str: std::borrow::Cow::Owned(record.record_type.clone()),
+            source_str: std::borrow::Cow::Owned(record.record_type.to_string()),
             level: WarningLevel::Critical,
-            description: "Record type must be 'NOW'".to_string(),
+            description: "Record type must be 'NOW'".into(),
         });
     }
 
@@ -55,21 +56,22 @@ fn now_custom_validate(record: &mut NowRecord) -> Vec<CwrWarning<'static>> {
             field_title: "Writer name",
             source_str: std::borrow::Cow::Owned(record.writer_name.as_str().to_string()),
             level: WarningLevel::Critical,
-            description: "Writer name cannot be empty".to_string(),
+            description: "Writer name cannot be empty".into(),
         });
     }
 
     // Validate language code format if present (ISO 639-1)
-    if let Some(ref lang_code) = record.language_code {
-        if !lang_code.as_str().trim().is_empty() && lang_code.as_str().len() != 2 {
-            warnings.push(CwrWarning {
-                field_name: "language_code",
-                field_title: "Language code (optional)",
-                source_str: std::borrow::Cow::Owned(lang_code.as_str().to_string()),
-                level: WarningLevel::Warning,
-                description: "Language code should be 2 characters (ISO 639-1)".to_string(),
-            });
-        }
+    if let Some(ref lang_code) = record.language_code
+        && !lang_code.as_str().trim().is_empty()
+        && lang_code.as_str().len() != 2
+    {
+        warnings.push(CwrWarning {
+            field_name: "language_code",
+            field_title: "Language code (optional)",
+            source_str: std::borrow::Cow::Owned(lang_code.as_str().to_string()),
+            level: WarningLevel::Warning,
+            description: "Language code should be 2 characters (ISO 639-1)".into(),
+        });
     }
 
     // Writer position validation is now handled by the WriterPosition domain type
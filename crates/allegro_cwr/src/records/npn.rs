@@ -1,6 +1,7 @@
 use crate::domain_types::*;
 use allegro_cwr_derive::CwrRecord;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 /// NPN - Non-Roman Alphabet Publisher Name Record
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, CwrRecord)]
@@ -10,7 +11,7 @@ use serde::{Deserialize, Serialize};
 )]
 pub struct NpnRecord {
     #[cwr(title = "Always 'NPN'", start = 0, len = 3)]
-    pub record_type: String,
+    pub record_type: Arc<str>,
 
     #[cwr(title = "Transaction sequence number", start = 3, len = 8)]
     pub transaction_sequence_num: Number,
@@ -36,13 +37,13 @@ fn npn_custom_validate(record: &mut NpnRecord) -> Vec<CwrWarning<'static>> {
     let mut warnings = Vec::new();
 
     // Validate record type
-    if record.record_type != "NPN" {
+    if &*record.record_type != "NPN" {
         warnings.push(CwrWarning {
             field_name: "record_type",
             field_title: "Always 'NPN'",
-            source_str: std::borrow::Cow::Owned(record.record_type.clone()),
+            source_str: std::borrow::Cow::Owned(record.record_type.to_string()),
             level: WarningLevel::Critical,
-            description: "Record type must be 'NPN'".to_string(),
+            description: "Record type must be 'NPN'".into(),
         });
     }
 
@@ -55,7 +56,7 @@ fn npn_custom_validate(record: &mut NpnRecord) -> Vec<CwrWarning<'static>> {
             field_title: "Interested party number",
             source_str: std::borrow::Cow::Owned(record.interested_party_num.clone()),
             level: WarningLevel::Critical,
-            description: "Interested party number must be exactly 9 characters".to_string(),
+            description: "Interested party number must be exactly 9 characters".into(),
         });
     }
 
@@ -66,7 +67,7 @@ fn npn_custom_validate(record: &mut NpnRecord) -> Vec<CwrWarning<'static>> {
             field_title: "Interested party number",
             source_str: std::borrow::Cow::Owned(record.interested_party_num.clone()),
             level: WarningLevel::Warning,
-            description: "Interested party number should be numeric".to_string(),
+            description: "Interested party number should be numeric".into(),
         });
     }
 
@@ -77,21 +78,22 @@ fn npn_custom_validate(record: &mut NpnRecord) -> Vec<CwrWarning<'static>> {
             field_title: "Publisher name",
             source_str: std::borrow::Cow::Owned(record.publisher_name.as_str().to_string()),
             level: WarningLevel::Critical,
-            description: "Publisher name cannot be empty".to_string(),
+            description: "Publisher name cannot be empty".into(),
         });
     }
 
     // Validate language code format if present (ISO 639-1)
-    if let Some(ref lang_code) = record.language_code {
-        if !lang_code.as_str().trim().is_empty() && lang_code.as_str().len() != 2 {
-            warnings.push(CwrWarning {
-                field_name: "language_code",
-                field_title: "Language code (optional)",
-                source_str: std::borrow::Cow::Owned(lang_code.as_str().to_string()),
-                level: WarningLevel::Warning,
-                description: "Language code should be 2 characters (ISO 639-1)".to_string(),
-            });
-        }
+    if let Some(ref lang_code) = record.language_code
+        && !lang_code.as_str().trim().is_empty()
+        && lang_code.as_str().len() != 2
+    {
+        warnings.push(CwrWarning {
+            field_name: "language_code",
+            field_title: "Language code (optional)",
+            source_str: std::borrow::Cow::Owned(lang_code.as_str().to_string()),
+            level: WarningLevel::Warning,
+            description: "Language code should be 2 characters (ISO 639-1)".into(),
+        });
     }
 
     // Note: PublisherSequenceNumber validation is handled by the domain type parser
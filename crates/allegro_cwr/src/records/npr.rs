@@ -1,6 +1,7 @@
 use crate::domain_types::*;
 use allegro_cwr_derive::CwrRecord;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 /// NPR - Non-Roman Alphabet Performing Artist Record
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, CwrRecord)]
@@ -10,7 +11,7 @@ use serde::{Deserialize, Serialize};
 )]
 pub struct NprRecord {
     #[cwr(title = "Always 'NPR'", start = 0, len = 3)]
-    pub record_type: String,
+    pub record_type: Arc<str>,
 
     #[cwr(title = "Transaction sequence number", start = 3, len = 8)]
     pub transaction_sequence_num: Number,
@@ -45,77 +46,77 @@ fn npr_custom_validate(record: &mut NprRecord) -> Vec<CwrWarning<'static>> {
     let mut warnings = Vec::new();
 
     // Validate record type
-    if record.record_type != "NPR" {
+    if &*record.record_type != "NPR" {
         warnings.push(CwrWarning {
             field_name: "record_type",
             field_title: "Always 'NPR'",
-            source_str: std::borrow::Cow::Owned(record.record_type.clone()),
+            source_str: std::borrow::Cow::Owned(record.record_type.to_string()),
             level: WarningLevel::Critical,
-            description: "Record type must be 'NPR'".to_string(),
+            description: "Record type must be 'NPR'".into(),
         });
     }
 
     // Validate transaction sequence number is numeric
     // Validate record sequence number is numeric
     // Validate performing artist name (conditional but required if present)
-    if let Some(ref name) = record.performing_artist_name {
-        if name.as_str().trim().is_empty() {
+    if let Some(ref name) = record.performing_artist_name
+        && name.as_str().trim().is_empty()
+    {
+        warnings.push(CwrWarning {
+            field_name: "performing_artist_name",
+            field_title: "Performing artist name (conditional)",
+            source_str: std::borrow::Cow::Owned(name.as_str().to_string()),
+            level: WarningLevel::Warning,
+            description: "Performing artist name should not be empty if specified".into(),
+        });
+    }
+
+    // Validate IPI name number format if present
+    if let Some(ref ipi_name) = record.performing_artist_ipi_name_num
+        && !ipi_name.as_str().trim().is_empty()
+    {
+        if ipi_name.as_str().len() != 11 {
             warnings.push(CwrWarning {
-                field_name: "performing_artist_name",
-                field_title: "Performing artist name (conditional)",
-                source_str: std::borrow::Cow::Owned(name.as_str().to_string()),
+                field_name: "performing_artist_ipi_name_num",
+                field_title: "Performing artist IPI name number (optional)",
+                source_str: std::borrow::Cow::Owned(ipi_name.as_str().to_string()),
                 level: WarningLevel::Warning,
-                description: "Performing artist name should not be empty if specified".to_string(),
+                description: "IPI name number should be 11 characters if specified".into(),
             });
         }
-    }
-
-    // Validate IPI name number format if present
-    if let Some(ref ipi_name) = record.performing_artist_ipi_name_num {
-        if !ipi_name.as_str().trim().is_empty() {
-            if ipi_name.as_str().len() != 11 {
-                warnings.push(CwrWarning {
-                    field_name: "performing_artist_ipi_name_num",
-                    field_title: "Performing artist IPI name number (optional)",
-                    source_str: std::borrow::Cow::Owned(ipi_name.as_str().to_string()),
-                    level: WarningLevel::Warning,
-                    description: "IPI name number should be 11 characters if specified".to_string(),
-                });
-            }
-            if !ipi_name.as_str().chars().all(|c| c.is_ascii_digit()) {
-                warnings.push(CwrWarning {
-                    field_name: "performing_artist_ipi_name_num",
-                    field_title: "Performing artist IPI name number (optional)",
-                    source_str: std::borrow::Cow::Owned(ipi_name.as_str().to_string()),
-                    level: WarningLevel::Warning,
-                    description: "IPI name number should be numeric".to_string(),
-                });
-            }
+        if !ipi_name.as_str().chars().all(|c| c.is_ascii_digit()) {
+            warnings.push(CwrWarning {
+                field_name: "performing_artist_ipi_name_num",
+                field_title: "Performing artist IPI name number (optional)",
+                source_str: std::borrow::Cow::Owned(ipi_name.as_str().to_string()),
+                level: WarningLevel::Warning,
+                description: "IPI name number should be numeric".into(),
+            });
         }
     }
 
     // Validate IPI base number format if present
-    if let Some(ref ipi_base) = record.performing_artist_ipi_base_number {
-        if !ipi_base.as_str().trim().is_empty() {
-            if ipi_base.as_str().len() != 13 {
-                warnings.push(CwrWarning {
-                    field_name: "performing_artist_ipi_base_number",
-                    field_title: "Performing artist IPI base number (optional)",
-                    source_str: std::borrow::Cow::Owned(ipi_base.as_str().to_string()),
-                    level: WarningLevel::Warning,
-                    description: "IPI base number should be 13 characters if specified".to_string(),
-                });
-            }
-            // IPI base numbers are typically alphanumeric
-            if !ipi_base.as_str().chars().all(|c| c.is_ascii_alphanumeric()) {
-                warnings.push(CwrWarning {
-                    field_name: "performing_artist_ipi_base_number",
-                    field_title: "Performing artist IPI base number (optional)",
-                    source_str: std::borrow::Cow::Owned(ipi_base.as_str().to_string()),
-                    level: WarningLevel::Warning,
-                    description: "IPI base number should be alphanumeric".to_string(),
-                });
-            }
+    if let Some(ref ipi_base) = record.performing_artist_ipi_base_number
+        && !ipi_base.as_str().trim().is_empty()
+    {
+        if ipi_base.as_str().len() != 13 {
+            warnings.push(CwrWarning {
+                field_name: "performing_artist_ipi_base_number",
+                field_title: "Performing artist IPI base number (optional)",
+                source_str: std::borrow::Cow::Owned(ipi_base.as_str().to_string()),
+                level: WarningLevel::Warning,
+                description: "IPI base number should be 13 characters if specified".into(),
+            });
+        }
+        // IPI base numbers are typically alphanumeric
+        if !ipi_base.as_str().chars().all(|c| c.is_ascii_alphanumeric()) {
+            warnings.push(CwrWarning {
+                field_name: "performing_artist_ipi_base_number",
+                field_title: "Performing artist IPI base number (optional)",
+                source_str: std::borrow::Cow::Owned(ipi_base.as_str().to_string()),
+                level: WarningLevel::Warning,
+                description: "IPI base number should be alphanumeric".into(),
+            });
         }
     }
 
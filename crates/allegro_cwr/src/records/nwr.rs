@@ -1,13 +1,14 @@
 use crate::domain_types::*;
 use allegro_cwr_derive::CwrRecord;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 /// Used for NWR, REV, ISW, and EXC record types.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, CwrRecord)]
 #[cwr(codes = ["NWR", "REV", "ISW", "EXC"], validator = nwr_custom_validate, test_data = "NWR0000000100000001Test Song                                               SW0000000001        SER        Y       ORI                                                                                                                                               ")]
 pub struct NwrRecord {
     #[cwr(title = "'NWR', 'REV', 'ISW', or 'EXC'", start = 0, len = 3)]
-    pub record_type: String,
+    pub record_type: Arc<str>,
 
     #[cwr(title = "Transaction sequence number", start = 3, len = 8)]
     pub transaction_sequence_num: Number,
@@ -37,7 +38,7 @@ pub struct NwrRecord {
     pub musical_work_distribution_category: MusicalWorkDistributionCategory,
 
     #[cwr(title = "Duration HHMMSS (conditional)", start = 129, len = 6)]
-    pub duration: Option<Time>,
+    pub duration: Option<Duration>,
 
     #[cwr(title = "Recorded indicator (1 char)", start = 135, len = 1)]
     pub recorded_indicator: Flag,
@@ -91,33 +92,48 @@ pub struct NwrRecord {
     pub priority_flag: Option<Flag>,
 }
 
+impl NwrRecord {
+    /// The transaction this header opens, taken from the record code (NWR, REV, ISW or EXC)
+    pub fn transaction_type(&self) -> TransactionType {
+        match &*self.record_type {
+            "REV" => TransactionType::REV,
+            "ISW" => TransactionType::ISW,
+            "EXC" => TransactionType::EXC,
+            _ => TransactionType::NWR,
+        }
+    }
+
+    /// True when the work is being revised (REV) rather than newly registered or notified
+    pub fn is_revision(&self) -> bool {
+        &*self.record_type == "REV"
+    }
+}
+
 // Custom validation function for NWR record
 fn nwr_custom_validate(record: &mut NwrRecord) -> Vec<CwrWarning<'static>> {
     let mut warnings = Vec::new();
 
-    // Business rule: Duration required if Musical Work Distribution Category = "SER"
-    if record.musical_work_distribution_category.as_str() == "SER" && record.duration.is_none() {
+    // Business rule: Duration required (and greater than zero) if Musical Work Distribution Category = "SER"
+    if record.musical_work_distribution_category.as_str() == "SER" && record.duration.is_none_or(|d| d.is_zero()) {
         warnings.push(CwrWarning {
             field_name: "duration",
             field_title: "Duration HHMMSS (conditional)",
-            source_str: std::borrow::Cow::Borrowed(""),
+            source_str: std::borrow::Cow::Owned(record.duration.map(|d| d.as_str()).unwrap_or_default()),
             level: WarningLevel::Critical,
-            description: "Duration is required when Musical Work Distribution Category is 'SER'".to_string(),
+            description: "Duration greater than 00:00:00 is required when Musical Work Distribution Category is 'SER'"
+                .into(),
+        });
+    } else if let Some(duration) = record.duration
+        && duration.is_zero()
+    {
+        // Business rule: Duration must be > 0 if present
+        warnings.push(CwrWarning {
+            field_name: "duration",
+            field_title: "Duration HHMMSS (conditional)",
+            source_str: std::borrow::Cow::Owned(duration.as_str()),
+            level: WarningLevel::Warning,
+            description: "Duration should be greater than 00:00:00 if specified".into(),
         });
-    }
-
-    // Business rule: Duration must be > 0 if present
-    if let Some(ref duration) = record.duration {
-        let seconds = duration.duration_since_midnight();
-        if seconds == 0.0 {
-            warnings.push(CwrWarning {
-                field_name: "duration",
-                field_title: "Duration HHMMSS (conditional)",
-                source_str: std::borrow::Cow::Owned(duration.as_str()),
-                level: WarningLevel::Warning,
-                description: "Duration should be greater than 00:00:00 if specified".to_string(),
-            });
-        }
     }
 
     // Business rule: Music Arrangement required if Version Type = "MOD"
@@ -130,7 +146,7 @@ fn nwr_custom_validate(record: &mut NwrRecord) -> Vec<CwrWarning<'static>> {
                 field_title: "Music arrangement (conditional)",
                 source_str: std::borrow::Cow::Borrowed(""),
                 level: WarningLevel::Critical,
-                description: "Music Arrangement is required when Version Type is 'MOD'".to_string(),
+                description: "Music Arrangement is required when Version Type is 'MOD'".into(),
             });
         }
 
@@ -142,7 +158,7 @@ fn nwr_custom_validate(record: &mut NwrRecord) -> Vec<CwrWarning<'static>> {
                 field_title: "Lyric adaptation (conditional)",
                 source_str: std::borrow::Cow::Borrowed(""),
                 level: WarningLevel::Critical,
-                description: "Lyric Adaptation is required when Version Type is 'MOD'".to_string(),
+                description: "Lyric Adaptation is required when Version Type is 'MOD'".into(),
             });
         }
     }
@@ -160,7 +176,7 @@ fn nwr_custom_validate(record: &mut NwrRecord) -> Vec<CwrWarning<'static>> {
             level: WarningLevel::Warning,
             description:
                 "Composite Component Count should be specified when Composite Type is present (required for ASCAP)"
-                    .to_string(),
+                    .into(),
         });
     }
 
@@ -1,6 +1,7 @@
 use crate::domain_types::*;
 use allegro_cwr_derive::CwrRecord;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 /// ORN - Work Origin Record
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, CwrRecord)]
@@ -10,7 +11,7 @@ use serde::{Deserialize, Serialize};
 )]
 pub struct OrnRecord {
     #[cwr(title = "Always 'ORN'", start = 0, len = 3)]
-    pub record_type: String,
+    pub record_type: Arc<str>,
 
     #[cwr(title = "Transaction sequence number", start = 3, len = 8)]
     pub transaction_sequence_num: Number,
@@ -84,13 +85,13 @@ fn orn_custom_validate(record: &mut OrnRecord) -> Vec<CwrWarning<'static>> {
     let mut warnings = Vec::new();
 
     // Validate record type
-    if record.record_type != "ORN" {
+    if &*record.record_type != "ORN" {
         warnings.push(CwrWarning {
             field_name: "record_type",
             field_title: "Always 'ORN'",
-            source_str: std::borrow::Cow::Owned(record.record_type.clone()),
+            source_str: std::borrow::Cow::Owned(record.record_type.to_string()),
             level: WarningLevel::Critical,
-            description: "Record type must be 'ORN'".to_string(),
+            description: "Record type must be 'ORN'".into(),
         });
     }
 
@@ -103,100 +104,104 @@ fn orn_custom_validate(record: &mut OrnRecord) -> Vec<CwrWarning<'static>> {
             field_title: "Intended purpose",
             source_str: std::borrow::Cow::Owned(record.intended_purpose.as_str().to_string()),
             level: WarningLevel::Critical,
-            description: "Intended purpose must be exactly 3 characters".to_string(),
+            description: "Intended purpose must be exactly 3 characters".into(),
         });
     }
     // TODO: Validate intended_purpose against lookup table (e.g., "L" for Library, etc.)
 
     // Validate cut number is reasonable if present
-    if let Some(ref cut_num) = record.cut_number {
-        if cut_num.0 > 9999 {
-            warnings.push(CwrWarning {
-                field_name: "cut_number",
-                field_title: "Cut number (optional)",
-                source_str: std::borrow::Cow::Owned(cut_num.to_string()),
-                level: WarningLevel::Warning,
-                description: "Cut number should be a 4-digit number (0000-9999)".to_string(),
-            });
-        }
+    if let Some(ref cut_num) = record.cut_number
+        && cut_num.0 > 9999
+    {
+        warnings.push(CwrWarning {
+            field_name: "cut_number",
+            field_title: "Cut number (optional)",
+            source_str: std::borrow::Cow::Owned(cut_num.to_string()),
+            level: WarningLevel::Warning,
+            description: "Cut number should be a 4-digit number (0000-9999)".into(),
+        });
     }
 
     // Validate BLTVR is single character if present
-    if let Some(ref bltvr) = record.bltvr {
-        if !bltvr.trim().is_empty() && bltvr.len() != 1 {
-            warnings.push(CwrWarning {
-                field_name: "bltvr",
-                field_title: "BLTVR (1 char, optional, v2.1+)",
-                source_str: std::borrow::Cow::Owned(bltvr.clone()),
-                level: WarningLevel::Warning,
-                description: "BLTVR must be exactly 1 character if specified".to_string(),
-            });
-        }
+    if let Some(ref bltvr) = record.bltvr
+        && !bltvr.trim().is_empty()
+        && bltvr.len() != 1
+    {
+        warnings.push(CwrWarning {
+            field_name: "bltvr",
+            field_title: "BLTVR (1 char, optional, v2.1+)",
+            source_str: std::borrow::Cow::Owned(bltvr.clone()),
+            level: WarningLevel::Warning,
+            description: "BLTVR must be exactly 1 character if specified".into(),
+        });
     }
 
     // Validate year of production is reasonable if present
-    if let Some(ref year) = record.year_of_production {
-        if year.0 < 1900 || year.0 > 2100 {
-            warnings.push(CwrWarning {
-                field_name: "year_of_production",
-                field_title: "Year of production (optional, v2.1+)",
-                source_str: std::borrow::Cow::Owned(year.to_string()),
-                level: WarningLevel::Warning,
-                description: "Year of production should be a reasonable year (1900-2100)".to_string(),
-            });
-        }
+    if let Some(ref year) = record.year_of_production
+        && (year.0 < 1900 || year.0 > 2100)
+    {
+        warnings.push(CwrWarning {
+            field_name: "year_of_production",
+            field_title: "Year of production (optional, v2.1+)",
+            source_str: std::borrow::Cow::Owned(year.to_string()),
+            level: WarningLevel::Warning,
+            description: "Year of production should be a reasonable year (1900-2100)".into(),
+        });
     }
 
     // Validate AVI society code is reasonable if present
-    if let Some(ref avi_code) = record.avi_society_code {
-        if avi_code.0 > 999 {
-            warnings.push(CwrWarning {
-                field_name: "avi_society_code",
-                field_title: "AVI society code (optional, v2.1+)",
-                source_str: std::borrow::Cow::Owned(avi_code.to_string()),
-                level: WarningLevel::Warning,
-                description: "AVI society code should be a 3-digit number (000-999)".to_string(),
-            });
-        }
-        // TODO: Validate against AVI society code lookup table
+    if let Some(ref avi_code) = record.avi_society_code
+        && avi_code.0 > 999
+    {
+        warnings.push(CwrWarning {
+            field_name: "avi_society_code",
+            field_title: "AVI society code (optional, v2.1+)",
+            source_str: std::borrow::Cow::Owned(avi_code.to_string()),
+            level: WarningLevel::Warning,
+            description: "AVI society code should be a 3-digit number (000-999)".into(),
+        });
     }
+    // TODO: Validate against AVI society code lookup table
 
     // Validate V-ISAN check digits are single characters if present
-    if let Some(ref check_digit) = record.v_isan_check_digit_1 {
-        if !check_digit.trim().is_empty() && check_digit.len() != 1 {
-            warnings.push(CwrWarning {
-                field_name: "v_isan_check_digit_1",
-                field_title: "V-ISAN/Check Digit 1 (1 char, optional, v2.2+)",
-                source_str: std::borrow::Cow::Owned(check_digit.clone()),
-                level: WarningLevel::Warning,
-                description: "V-ISAN check digit 1 must be exactly 1 character if specified".to_string(),
-            });
-        }
+    if let Some(ref check_digit) = record.v_isan_check_digit_1
+        && !check_digit.trim().is_empty()
+        && check_digit.len() != 1
+    {
+        warnings.push(CwrWarning {
+            field_name: "v_isan_check_digit_1",
+            field_title: "V-ISAN/Check Digit 1 (1 char, optional, v2.2+)",
+            source_str: std::borrow::Cow::Owned(check_digit.clone()),
+            level: WarningLevel::Warning,
+            description: "V-ISAN check digit 1 must be exactly 1 character if specified".into(),
+        });
     }
 
-    if let Some(ref check_digit) = record.v_isan_check_digit_2 {
-        if !check_digit.trim().is_empty() && check_digit.len() != 1 {
-            warnings.push(CwrWarning {
-                field_name: "v_isan_check_digit_2",
-                field_title: "V-ISAN/Check Digit 2 (1 char, optional, v2.2+)",
-                source_str: std::borrow::Cow::Owned(check_digit.clone()),
-                level: WarningLevel::Warning,
-                description: "V-ISAN check digit 2 must be exactly 1 character if specified".to_string(),
-            });
-        }
+    if let Some(ref check_digit) = record.v_isan_check_digit_2
+        && !check_digit.trim().is_empty()
+        && check_digit.len() != 1
+    {
+        warnings.push(CwrWarning {
+            field_name: "v_isan_check_digit_2",
+            field_title: "V-ISAN/Check Digit 2 (1 char, optional, v2.2+)",
+            source_str: std::borrow::Cow::Owned(check_digit.clone()),
+            level: WarningLevel::Warning,
+            description: "V-ISAN check digit 2 must be exactly 1 character if specified".into(),
+        });
     }
 
     // Validate EIDR check digit is single character if present
-    if let Some(ref eidr_check) = record.eidr_check_digit {
-        if !eidr_check.trim().is_empty() && eidr_check.len() != 1 {
-            warnings.push(CwrWarning {
-                field_name: "eidr_check_digit",
-                field_title: "EIDR/Check Digit (1 char, optional, v2.2+)",
-                source_str: std::borrow::Cow::Owned(eidr_check.clone()),
-                level: WarningLevel::Warning,
-                description: "EIDR check digit must be exactly 1 character if specified".to_string(),
-            });
-        }
+    if let Some(ref eidr_check) = record.eidr_check_digit
+        && !eidr_check.trim().is_empty()
+        && eidr_check.len() != 1
+    {
+        warnings.push(CwrWarning {
+            field_name: "eidr_check_digit",
+            field_title: "EIDR/Check Digit (1 char, optional, v2.2+)",
+            source_str: std::borrow::Cow::Owned(eidr_check.clone()),
+            level: WarningLevel::Warning,
+            description: "EIDR check digit must be exactly 1 character if specified".into(),
+        });
     }
 
     // TODO: Add cross-field validation logic for V-ISAN and EIDR complete identifier validation
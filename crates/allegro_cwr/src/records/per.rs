@@ -1,13 +1,14 @@
 use crate::domain_types::*;
 use allegro_cwr_derive::CwrRecord;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 /// PER - Performing Artist Record
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, CwrRecord)]
 #[cwr(validator = per_custom_validate, test_data = "PER0000050400000429DEVVON TERRELL                                                                                     ")]
 pub struct PerRecord {
     #[cwr(title = "Always 'PER'", start = 0, len = 3)]
-    pub record_type: String,
+    pub record_type: Arc<str>,
 
     #[cwr(title = "Transaction sequence number", start = 3, len = 8)]
     pub transaction_sequence_num: Number,
@@ -39,7 +40,7 @@ fn per_custom_validate(record: &mut PerRecord) -> Vec<CwrWarning<'static>> {
             field_title: "Performing artist last name",
             source_str: std::borrow::Cow::Borrowed(""),
             level: WarningLevel::Critical,
-            description: "Performing artist last name cannot be empty".to_string(),
+            description: "Performing artist last name cannot be empty".into(),
         });
     }
 
@@ -1,13 +1,14 @@
 use crate::domain_types::*;
 use allegro_cwr_derive::CwrRecord;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 /// PWR - Publisher for Writer Record
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, CwrRecord)]
 #[cwr(validator = pwr_custom_validate, test_data = "PWR0000000000000325ABKC     ABKCO MUSIC INC.                                                         WOMA     01")]
 pub struct PwrRecord {
     #[cwr(title = "Always 'PWR'", start = 0, len = 3)]
-    pub record_type: String,
+    pub record_type: Arc<str>,
 
     #[cwr(title = "Transaction sequence number", start = 3, len = 8)]
     pub transaction_sequence_num: Number,
@@ -47,7 +48,7 @@ fn pwr_custom_validate(record: &mut PwrRecord) -> Vec<CwrWarning<'static>> {
             field_title: "Publisher IP number (conditional)",
             source_str: std::borrow::Cow::Borrowed(""),
             level: WarningLevel::Critical,
-            description: "Either Publisher IP Number or Publisher Name must be provided".to_string(),
+            description: "Either Publisher IP Number or Publisher Name must be provided".into(),
         });
     }
 
@@ -73,7 +74,7 @@ mod tests {
     fn test_version_aware_writing() {
         // Create a test PWR record
         let pwr = PwrRecord {
-            record_type: "PWR".to_string(),
+            record_type: crate::parsing::intern("PWR"),
             transaction_sequence_num: Number(3),
             record_sequence_num: Number(25),
             publisher_ip_num: Some("ABKC     ".to_string()),
@@ -1,6 +1,7 @@
 use crate::domain_types::*;
 use allegro_cwr_derive::CwrRecord;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 /// REC - Recording Detail Record
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, CwrRecord)]
@@ -10,7 +11,7 @@ use serde::{Deserialize, Serialize};
 )]
 pub struct RecRecord {
     #[cwr(title = "Always 'REC'", start = 0, len = 3)]
-    pub record_type: String,
+    pub record_type: Arc<str>,
 
     #[cwr(title = "Transaction sequence number", start = 3, len = 8)]
     pub transaction_sequence_num: Number,
@@ -25,7 +26,7 @@ pub struct RecRecord {
     pub constant: String,
 
     #[cwr(title = "Release duration HHMMSS (optional)", start = 87, len = 6)]
-    pub release_duration: Option<Time>,
+    pub release_duration: Option<Duration>,
 
     #[cwr(title = "Constant - spaces", start = 93, len = 5)]
     pub constant2: String,
@@ -79,15 +80,14 @@ fn rec_custom_validate(record: &mut RecRecord) -> Vec<CwrWarning<'static>> {
 
     // Business rule: Release date should not be in the future
     if let Some(ref release_date) = record.release_date {
-        let current_timestamp =
-            std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs() as i64;
+        let current_timestamp = chrono::Utc::now().timestamp();
         if release_date.to_timestamp() > current_timestamp {
             warnings.push(CwrWarning {
                 field_name: "release_date",
                 field_title: "Release date YYYYMMDD (optional)",
                 source_str: std::borrow::Cow::Owned(release_date.as_str()),
                 level: WarningLevel::Warning,
-                description: format!("Release date {} is in the future", release_date.as_str()),
+                description: format!("Release date {} is in the future", release_date.as_str()).into(),
             });
         }
     }
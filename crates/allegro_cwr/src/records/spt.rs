@@ -1,13 +1,14 @@
 use crate::domain_types::*;
 use allegro_cwr_derive::CwrRecord;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 /// SPT - Publisher Territory of Control Record (also OPT - Other Publisher Territory)
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, CwrRecord)]
 #[cwr(codes = ["SPT", "OPT"], validator = spt_custom_validate, test_data = "SPT0000000000000002ABKC           025000750000000I0840N001")]
 pub struct SptRecord {
     #[cwr(title = "'SPT' or 'OPT'", start = 0, len = 3)]
-    pub record_type: String,
+    pub record_type: Arc<str>,
 
     #[cwr(title = "Transaction sequence number", start = 3, len = 8)]
     pub transaction_sequence_num: Number,
@@ -43,6 +44,13 @@ pub struct SptRecord {
     pub sequence_num: Option<Number>,
 }
 
+impl SptRecord {
+    /// True for an SPT (territory of a controlled publisher), false for an OPT
+    pub fn is_controlled(&self) -> bool {
+        &*self.record_type == "SPT"
+    }
+}
+
 // Custom validation function for SPT record
 fn spt_custom_validate(record: &mut SptRecord) -> Vec<CwrWarning<'static>> {
     let mut warnings = Vec::new();
@@ -54,7 +62,7 @@ fn spt_custom_validate(record: &mut SptRecord) -> Vec<CwrWarning<'static>> {
             field_title: "Interested party number",
             source_str: std::borrow::Cow::Borrowed(""),
             level: WarningLevel::Critical,
-            description: "Interested party number cannot be empty".to_string(),
+            description: "Interested party number cannot be empty".into(),
         });
     }
 
@@ -69,7 +77,7 @@ fn spt_custom_validate(record: &mut SptRecord) -> Vec<CwrWarning<'static>> {
             field_title: "PR collection share (conditional)",
             source_str: std::borrow::Cow::Borrowed(""),
             level: WarningLevel::Warning,
-            description: "At least one collection share (PR, MR, or SR) should be provided".to_string(),
+            description: "At least one collection share (PR, MR, or SR) should be provided".into(),
         });
     }
 
@@ -1,13 +1,14 @@
 use crate::domain_types::*;
 use allegro_cwr_derive::CwrRecord;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 /// SPU - Publisher Controlled by Submitter Record (also OPU - Other Publisher)
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, CwrRecord)]
 #[cwr(codes = ["SPU", "OPU"], validator = spu_custom_validate, test_data = "SPU0000000100000001011234567890PUBLISHER NAME                             N AS1234567890123456789    BMI  50.00000000000000000000000000000  N N                                                            ")]
 pub struct SpuRecord {
     #[cwr(title = "'SPU' or 'OPU'", start = 0, len = 3)]
-    pub record_type: String,
+    pub record_type: Arc<str>,
 
     #[cwr(title = "Transaction sequence number", start = 3, len = 8)]
     pub transaction_sequence_num: Number,
@@ -85,6 +86,18 @@ pub struct SpuRecord {
     pub usa_license_ind: Option<UsaLicenseIndicator>,
 }
 
+impl SpuRecord {
+    /// PR, MR and SR ownership shares, with blank shares counted as zero
+    pub fn ownership_shares(&self) -> ShareTotals {
+        ShareTotals::new(self.pr_ownership_share, self.mr_ownership_share, self.sr_ownership_share)
+    }
+
+    /// True for an SPU (publisher controlled by the submitter), false for an OPU
+    pub fn is_controlled(&self) -> bool {
+        &*self.record_type == "SPU"
+    }
+}
+
 // Custom validation function for SPU record
 fn spu_custom_validate(record: &mut SpuRecord) -> Vec<CwrWarning<'static>> {
     let mut warnings = Vec::new();
@@ -95,7 +108,7 @@ fn spu_custom_validate(record: &mut SpuRecord) -> Vec<CwrWarning<'static>> {
     // If input line is longer, warn about extra characters
 
     // SPU-specific validations (vs OPU)
-    if record.record_type == "SPU" {
+    if record.is_controlled() {
         // For SPU records: Interested Party #, Publisher Name, and Publisher Type are required
         if record.interested_party_num.is_none()
             || record.interested_party_num.as_ref().is_none_or(|s| s.trim().is_empty())
@@ -105,7 +118,7 @@ fn spu_custom_validate(record: &mut SpuRecord) -> Vec<CwrWarning<'static>> {
                 field_title: "Interested party number (conditional)",
                 source_str: std::borrow::Cow::Borrowed(""),
                 level: WarningLevel::Critical,
-                description: "Interested Party Number is required for SPU records".to_string(),
+                description: "Interested Party Number is required for SPU records".into(),
             });
         }
 
@@ -115,38 +128,38 @@ fn spu_custom_validate(record: &mut SpuRecord) -> Vec<CwrWarning<'static>> {
                 field_title: "Publisher name (conditional)",
                 source_str: std::borrow::Cow::Borrowed(""),
                 level: WarningLevel::Critical,
-                description: "Publisher Name is required for SPU records".to_string(),
+                description: "Publisher Name is required for SPU records".into(),
             });
         }
 
         // Publisher Unknown Indicator must be None or Unknown for SPU records
-        if let Some(ref indicator) = record.publisher_unknown_indicator {
-            if !matches!(indicator, Flag::Unknown) {
-                warnings.push(CwrWarning {
-                    field_name: "publisher_unknown_indicator",
-                    field_title: "Publisher unknown indicator (1 char, conditional)",
-                    source_str: std::borrow::Cow::Owned(indicator.as_str().to_string()),
-                    level: WarningLevel::Critical,
-                    description: "Publisher Unknown Indicator must be blank/unknown for SPU records".to_string(),
-                });
-            }
-        }
-    }
-
-    // Ownership share validation for PR (Performance Rights) - max 50%
-    if let Some(ref pr_share) = record.pr_ownership_share {
-        if pr_share.0 > 5000 {
-            // 50.00% = 5000
+        if let Some(ref indicator) = record.publisher_unknown_indicator
+            && !matches!(indicator, Flag::Unknown)
+        {
             warnings.push(CwrWarning {
-                field_name: "pr_ownership_share",
-                field_title: "PR ownership share (conditional)",
-                source_str: std::borrow::Cow::Owned(pr_share.as_str()),
+                field_name: "publisher_unknown_indicator",
+                field_title: "Publisher unknown indicator (1 char, conditional)",
+                source_str: std::borrow::Cow::Owned(indicator.as_str().to_string()),
                 level: WarningLevel::Critical,
-                description: format!("PR ownership share {}% exceeds maximum 50.00%", pr_share.as_percentage()),
+                description: "Publisher Unknown Indicator must be blank/unknown for SPU records".into(),
             });
         }
     }
 
+    // Ownership share validation for PR (Performance Rights) - max 50%
+    if let Some(ref pr_share) = record.pr_ownership_share
+        && pr_share.0 > 5000
+    {
+        // 50.00% = 5000
+        warnings.push(CwrWarning {
+            field_name: "pr_ownership_share",
+            field_title: "PR ownership share (conditional)",
+            source_str: std::borrow::Cow::Owned(pr_share.as_str()),
+            level: WarningLevel::Critical,
+            description: format!("PR ownership share {}% exceeds maximum 50.00%", pr_share.as_percentage()).into(),
+        });
+    }
+
     // TODO: Version-specific validations
     // - Version 2.1+: Agreement Type and USA License Ind validation
     // - Version 2.2+: Enhanced IPI Name # validation for collecting publishers
@@ -1,13 +1,14 @@
 use crate::domain_types::*;
 use allegro_cwr_derive::CwrRecord;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 /// SWR - Writer Controlled by Submitter Record (also OWR - Other Writer)
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, CwrRecord)]
 #[cwr(codes = ["SWR", "OWR"], validator = swr_custom_validate, test_data = "SWR0000000000000226WOMA     WOMACK                                       BOBBY                          CA00000000000033188001021050000990000009900000 N                           B")]
 pub struct SwrRecord {
     #[cwr(title = "'SWR' or 'OWR'", start = 0, len = 3)]
-    pub record_type: String,
+    pub record_type: Arc<str>,
 
     #[cwr(title = "Transaction sequence number", start = 3, len = 8)]
     pub transaction_sequence_num: Number,
@@ -76,6 +77,18 @@ pub struct SwrRecord {
     pub usa_license_ind: Option<UsaLicenseIndicator>,
 }
 
+impl SwrRecord {
+    /// PR, MR and SR ownership shares, with blank shares counted as zero
+    pub fn ownership_shares(&self) -> ShareTotals {
+        ShareTotals::new(self.pr_ownership_share, self.mr_ownership_share, self.sr_ownership_share)
+    }
+
+    /// True for an SWR (writer controlled by the submitter), false for an OWR
+    pub fn is_controlled(&self) -> bool {
+        &*self.record_type == "SWR"
+    }
+}
+
 // Custom validation function for SWR record
 fn swr_custom_validate(record: &mut SwrRecord) -> Vec<CwrWarning<'static>> {
     let mut warnings = Vec::new();
@@ -90,7 +103,7 @@ fn swr_custom_validate(record: &mut SwrRecord) -> Vec<CwrWarning<'static>> {
             field_title: "Interested party number (conditional)",
             source_str: std::borrow::Cow::Borrowed(""),
             level: WarningLevel::Critical,
-            description: "Either Interested Party Number or Writer Last Name must be provided".to_string(),
+            description: "Either Interested Party Number or Writer Last Name must be provided".into(),
         });
     }
 
@@ -105,7 +118,7 @@ fn swr_custom_validate(record: &mut SwrRecord) -> Vec<CwrWarning<'static>> {
             field_title: "PR ownership share (optional)",
             source_str: std::borrow::Cow::Borrowed(""),
             level: WarningLevel::Warning,
-            description: "At least one ownership share (PR, MR, or SR) should be provided".to_string(),
+            description: "At least one ownership share (PR, MR, or SR) should be provided".into(),
         });
     }
 
@@ -119,7 +132,7 @@ fn swr_custom_validate(record: &mut SwrRecord) -> Vec<CwrWarning<'static>> {
             field_title: "PR affiliation society number (optional)",
             source_str: std::borrow::Cow::Borrowed(""),
             level: WarningLevel::Warning,
-            description: "PR affiliation society number should be provided when PR ownership share > 0".to_string(),
+            description: "PR affiliation society number should be provided when PR ownership share > 0".into(),
         });
     }
 
@@ -131,7 +144,7 @@ fn swr_custom_validate(record: &mut SwrRecord) -> Vec<CwrWarning<'static>> {
             field_title: "MR society (optional)",
             source_str: std::borrow::Cow::Borrowed(""),
             level: WarningLevel::Warning,
-            description: "MR society should be provided when MR ownership share > 0".to_string(),
+            description: "MR society should be provided when MR ownership share > 0".into(),
         });
     }
 
@@ -143,7 +156,7 @@ fn swr_custom_validate(record: &mut SwrRecord) -> Vec<CwrWarning<'static>> {
             field_title: "SR society (optional)",
             source_str: std::borrow::Cow::Borrowed(""),
             level: WarningLevel::Warning,
-            description: "SR society should be provided when SR ownership share > 0".to_string(),
+            description: "SR society should be provided when SR ownership share > 0".into(),
         });
     }
 
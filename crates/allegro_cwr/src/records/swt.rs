@@ -1,13 +1,14 @@
 use crate::domain_types::*;
 use allegro_cwr_derive::CwrRecord;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 /// SWT - Writer Territory of Control Record (also OWT - Other Writer Territory)
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, CwrRecord)]
 #[cwr(codes = ["SWT", "OWT"], validator = swt_custom_validate, test_data = "SWT0000000000000227WOMA     050000000000000I2100N001")]
 pub struct SwtRecord {
     #[cwr(title = "'SWT' or 'OWT'", start = 0, len = 3)]
-    pub record_type: String,
+    pub record_type: Arc<str>,
 
     #[cwr(title = "Transaction sequence number", start = 3, len = 8)]
     pub transaction_sequence_num: Number,
@@ -40,6 +41,13 @@ pub struct SwtRecord {
     pub sequence_num: Option<Number>,
 }
 
+impl SwtRecord {
+    /// True for an SWT (territory of a controlled writer), false for an OWT
+    pub fn is_controlled(&self) -> bool {
+        &*self.record_type == "SWT"
+    }
+}
+
 // Custom validation function for SWT record
 fn swt_custom_validate(record: &mut SwtRecord) -> Vec<CwrWarning<'static>> {
     let mut warnings = Vec::new();
@@ -52,7 +60,7 @@ fn swt_custom_validate(record: &mut SwtRecord) -> Vec<CwrWarning<'static>> {
             field_title: "Interested party number (conditional)",
             source_str: std::borrow::Cow::Borrowed(""),
             level: WarningLevel::Warning,
-            description: "Interested party number is typically required for writer territory records".to_string(),
+            description: "Interested party number is typically required for writer territory records".into(),
         });
     }
 
@@ -67,7 +75,7 @@ fn swt_custom_validate(record: &mut SwtRecord) -> Vec<CwrWarning<'static>> {
             field_title: "PR collection share (optional)",
             source_str: std::borrow::Cow::Borrowed(""),
             level: WarningLevel::Warning,
-            description: "At least one collection share (PR, MR, or SR) should be provided".to_string(),
+            description: "At least one collection share (PR, MR, or SR) should be provided".into(),
         });
     }
 
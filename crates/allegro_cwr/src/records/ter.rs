@@ -1,13 +1,14 @@
 use crate::domain_types::*;
 use allegro_cwr_derive::CwrRecord;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 /// TER - Territory in Agreement Record
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, CwrRecord)]
 #[cwr(validator = ter_custom_validate, test_data = "TER0000000100000001I2840")]
 pub struct TerRecord {
     #[cwr(title = "Always 'TER'", start = 0, len = 3)]
-    pub record_type: String,
+    pub record_type: Arc<str>,
 
     #[cwr(title = "Transaction sequence number", start = 3, len = 8)]
     pub transaction_sequence_num: Number,
@@ -18,28 +19,29 @@ pub struct TerRecord {
     #[cwr(title = "Inclusion/Exclusion indicator (1 char)", start = 19, len = 1)]
     pub inclusion_exclusion_indicator: InclusionExclusionIndicator,
 
-    #[cwr(title = "TIS Numeric Code", start = 20, len = 4)]
+    #[cwr(title = "TIS Numeric Code", start = 20, len = 4, validate = validate_tis_numeric_code)]
     pub tis_numeric_code: TisNumericCode,
 }
 
 // Custom validation function for TER record
-fn ter_custom_validate(record: &mut TerRecord) -> Vec<CwrWarning<'static>> {
-    let mut warnings = Vec::new();
-
+fn ter_custom_validate(_record: &mut TerRecord) -> Vec<CwrWarning<'static>> {
     // TODO: Business rules requiring broader context:
     // - Must follow an AGR or TER record (requires parsing context)
-    // - TIS Numeric Code must match an entry in the TIS lookup table (requires lookup table data)
+    Vec::new()
+}
 
+fn validate_tis_numeric_code(
+    value: &TisNumericCode, field_name: &'static str, field_title: &'static str,
+) -> Vec<CwrWarning<'static>> {
     // Basic validation: TIS code should be reasonable
-    if record.tis_numeric_code.0 > 9999 {
-        warnings.push(CwrWarning {
-            field_name: "tis_numeric_code",
-            field_title: "TIS Numeric Code",
-            source_str: std::borrow::Cow::Owned(record.tis_numeric_code.as_str()),
+    if value.0 > 9999 {
+        return vec![CwrWarning {
+            field_name,
+            field_title,
+            source_str: std::borrow::Cow::Owned(value.as_str()),
             level: WarningLevel::Warning,
-            description: "TIS Numeric Code seems unusually high, please verify".to_string(),
-        });
+            description: "TIS Numeric Code seems unusually high, please verify".into(),
+        }];
     }
-
-    warnings
+    Vec::new()
 }
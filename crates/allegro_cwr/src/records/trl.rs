@@ -1,13 +1,14 @@
 use crate::domain_types::*;
 use allegro_cwr_derive::CwrRecord;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 /// Marks the end of a CWR transmission and contains summary counts.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, CwrRecord)]
 #[cwr(validator = trl_custom_validate, test_data = "TRL000010000001400000367")]
 pub struct TrlRecord {
     #[cwr(title = "Always 'TRL'", start = 0, len = 3)]
-    pub record_type: String,
+    pub record_type: Arc<str>,
 
     #[cwr(title = "Group count", start = 3, len = 5)]
     pub group_count: GroupCount,
@@ -39,7 +40,7 @@ fn trl_custom_validate(record: &mut TrlRecord) -> Vec<CwrWarning<'static>> {
             field_title: "Group count",
             source_str: std::borrow::Cow::Owned(record.group_count.as_str()),
             level: WarningLevel::Warning,
-            description: "Group count is 0, which may indicate no content in file".to_string(),
+            description: "Group count is 0, which may indicate no content in file".into(),
         });
     }
 
@@ -49,7 +50,7 @@ fn trl_custom_validate(record: &mut TrlRecord) -> Vec<CwrWarning<'static>> {
             field_title: "Transaction count",
             source_str: std::borrow::Cow::Owned(record.transaction_count.as_str()),
             level: WarningLevel::Warning,
-            description: "Transaction count is 0, which may indicate no content in file".to_string(),
+            description: "Transaction count is 0, which may indicate no content in file".into(),
         });
     }
 
@@ -60,7 +61,7 @@ fn trl_custom_validate(record: &mut TrlRecord) -> Vec<CwrWarning<'static>> {
             field_title: "Record count",
             source_str: std::borrow::Cow::Owned(record.record_count.as_str()),
             level: WarningLevel::Critical,
-            description: "Record count must be at least 2 (HDR + TRL records)".to_string(),
+            description: "Record count must be at least 2 (HDR + TRL records)".into(),
         });
     }
 
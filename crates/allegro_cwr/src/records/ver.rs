@@ -1,6 +1,7 @@
 use crate::domain_types::*;
 use allegro_cwr_derive::CwrRecord;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 /// VER - Original Work Title for Versions Record
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, CwrRecord)]
@@ -10,7 +11,7 @@ use serde::{Deserialize, Serialize};
 )]
 pub struct VerRecord {
     #[cwr(title = "Always 'VER'", start = 0, len = 3)]
-    pub record_type: String,
+    pub record_type: Arc<str>,
 
     #[cwr(title = "Transaction sequence number", start = 3, len = 8)]
     pub transaction_sequence_num: Number,
@@ -69,7 +70,7 @@ fn ver_custom_validate(record: &mut VerRecord) -> Vec<CwrWarning<'static>> {
             field_title: "Original work title",
             source_str: std::borrow::Cow::Borrowed(""),
             level: WarningLevel::Critical,
-            description: "Original work title cannot be empty".to_string(),
+            description: "Original work title cannot be empty".into(),
         });
     }
 
@@ -82,7 +83,7 @@ fn ver_custom_validate(record: &mut VerRecord) -> Vec<CwrWarning<'static>> {
                 field_title: "ISWC of original work (optional)",
                 source_str: std::borrow::Cow::Owned(iswc.clone()),
                 level: WarningLevel::Warning,
-                description: "ISWC should be exactly 11 characters (T-NNNNNNNN-C format)".to_string(),
+                description: "ISWC should be exactly 11 characters (T-NNNNNNNN-C format)".into(),
             });
         }
     }
@@ -102,7 +103,7 @@ fn ver_custom_validate(record: &mut VerRecord) -> Vec<CwrWarning<'static>> {
             field_title: "Writer 1 last name (optional)",
             source_str: std::borrow::Cow::Borrowed(""),
             level: WarningLevel::Warning,
-            description: "Writer 1 last name should be provided when other writer 1 information is given".to_string(),
+            description: "Writer 1 last name should be provided when other writer 1 information is given".into(),
         });
     }
 
@@ -121,13 +122,12 @@ fn ver_custom_validate(record: &mut VerRecord) -> Vec<CwrWarning<'static>> {
             field_title: "Writer 2 last name (optional)",
             source_str: std::borrow::Cow::Borrowed(""),
             level: WarningLevel::Warning,
-            description: "Writer 2 last name should be provided when other writer 2 information is given".to_string(),
+            description: "Writer 2 last name should be provided when other writer 2 information is given".into(),
         });
     }
 
     // TODO: Additional business rules requiring broader context:
     // - Must follow a NWR/REV record (requires parsing context)
-    // - Language codes must be valid ISO 639-1 codes (requires lookup table)
     // - IPI Name Number must match IPI system entry if provided (requires IPI lookup)
     // - IPI Base Number must match IPI system entry if provided (requires IPI lookup)
     // - Original work should not be the same as the current work (requires cross-record validation)
@@ -1,13 +1,14 @@
 use crate::domain_types::*;
 use allegro_cwr_derive::CwrRecord;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 /// XRF - Work ID Cross Reference Record
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, CwrRecord)]
-#[cwr(validator = xrf_custom_validate, test_data = "XRF0000000100000001ISWT1234567890123WY")]
+#[cwr(min_version = 2.2, validator = xrf_custom_validate, test_data = "XRF0000000100000001ISWT1234567890123WY")]
 pub struct XrfRecord {
     #[cwr(title = "Always 'XRF'", start = 0, len = 3)]
-    pub record_type: String,
+    pub record_type: Arc<str>,
 
     #[cwr(title = "Transaction sequence number", start = 3, len = 8)]
     pub transaction_sequence_num: Number,
@@ -41,7 +42,7 @@ fn xrf_custom_validate(record: &mut XrfRecord) -> Vec<CwrWarning<'static>> {
             field_title: "Identifier",
             source_str: std::borrow::Cow::Borrowed(""),
             level: WarningLevel::Critical,
-            description: "Identifier cannot be empty".to_string(),
+            description: "Identifier cannot be empty".into(),
         });
     }
 
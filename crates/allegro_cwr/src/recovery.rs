@@ -0,0 +1,89 @@
+//! Malformed-line recovery for the record stream
+//!
+//! In recovery mode an unparseable line is reported as an error, and parsing resumes at the next recognizable
+//! record prefix on the same line (records run together when a line break is lost). The transaction state is
+//! tracked so detail records following an unparseable group or transaction header carry a warning, letting
+//! consumers treat the whole transaction consistently instead of abandoning the file.
+
+use crate::cwr_registry::is_record_type_code;
+use crate::parser::ParsedRecord;
+use crate::parsing::{CwrWarning, WarningLevel};
+
+/// Finds the first offset at or after `from` where a recognizable record starts
+pub(crate) fn find_record_prefix(bytes: &[u8], from: usize) -> Option<usize> {
+    (from..bytes.len().saturating_sub(2)).find(|&offset| looks_like_record_start(&bytes[offset..]))
+}
+
+/// A known record type code followed by the fixed prefix of that record type
+fn looks_like_record_start(bytes: &[u8]) -> bool {
+    let Some(code) = bytes.get(0..3).and_then(|code| std::str::from_utf8(code).ok()) else {
+        return false;
+    };
+    if !is_record_type_code(code) {
+        return false;
+    }
+
+    let all =
+        |range: std::ops::Range<usize>, check: fn(&u8) -> bool| bytes.get(range).is_some_and(|b| b.iter().all(check));
+    match code {
+        // Sender type
+        "HDR" => all(3..5, u8::is_ascii_uppercase),
+        // Transaction type and group ID
+        "GRH" => all(3..6, u8::is_ascii_uppercase) && all(6..11, u8::is_ascii_digit),
+        // Group ID or group count
+        "GRT" | "TRL" => all(3..8, u8::is_ascii_digit),
+        // Transaction and record sequence numbers
+        _ => all(3..19, u8::is_ascii_digit),
+    }
+}
+
+/// Group/transaction state used to flag records whose header could not be parsed
+#[derive(Debug, Default)]
+pub(crate) struct TransactionTracker {
+    broken_header: Option<(&'static str, usize)>,
+}
+
+impl TransactionTracker {
+    pub(crate) fn record_parsed(&mut self, parsed: &mut ParsedRecord) {
+        match parsed.record.sequence_numbers() {
+            // HDR, GRH, GRT and TRL open or close a group, and record sequence 0 is a transaction header
+            None | Some((_, 0)) => self.broken_header = None,
+            Some(_) => {
+                if let Some((header, line_number)) = self.broken_header {
+                    parsed.warnings.push(CwrWarning::record(
+                        WarningLevel::Warning,
+                        format!(
+                            "Record belongs to a {} whose header at line {} could not be parsed",
+                            header, line_number
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+
+    /// Notes a malformed segment; when it was a group or transaction header, the records that follow are flagged
+    pub(crate) fn line_malformed(&mut self, segment: &[u8], line_number: usize) {
+        if segment.starts_with(b"GRH") {
+            self.broken_header = Some(("group", line_number));
+        } else if segment.get(3..11).is_some_and(|b| b.iter().all(u8::is_ascii_digit))
+            && segment.get(11..19).is_some_and(|b| b.iter().all(|&c| c == b'0'))
+        {
+            self.broken_header = Some(("transaction", line_number));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_record_prefix() {
+        let line = b"garbageNWR0000000100000000TITLE";
+        assert_eq!(find_record_prefix(line, 1), Some(7));
+        assert_eq!(find_record_prefix(b"GRHNWR00001", 0), Some(0));
+        // Record code without the sequence number prefix is not a record start
+        assert_eq!(find_record_prefix(b"A TITLE WITH NWR IN IT", 1), None);
+    }
+}
@@ -0,0 +1,187 @@
+//! Resubmission files built from an original submission and its acknowledgement
+//!
+//! The transactions the ACK file reports with a selected status (rejected or in conflict by default) are copied
+//! from the original file, optionally converted from NWR to REV, into a new transmission with renumbered
+//! sequences and regenerated GRH/GRT/TRL records.
+
+use crate::cwr_registry::CwrRegistry;
+use crate::domain_types::TransactionType;
+use crate::error::CwrParseError;
+use crate::filter::{FilterSummary, rewrite_transactions};
+use crate::parser::process_cwr_stream_with_version;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use tracing::info;
+
+/// Options for [`build_resubmission`]
+#[derive(Debug, Clone)]
+pub struct ResubmitOptions {
+    /// ACK transaction statuses whose transactions are resubmitted
+    pub statuses: Vec<String>,
+    /// Resubmit NWR transactions as REV
+    pub as_revision: bool,
+    /// CWR version of the original and ACK files, instead of detecting it
+    pub version_hint: Option<f32>,
+}
+
+impl Default for ResubmitOptions {
+    fn default() -> Self {
+        ResubmitOptions { statuses: vec!["RJ".to_string(), "CO".to_string()], as_revision: false, version_hint: None }
+    }
+}
+
+/// Counts reported after building a resubmission file
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ResubmitSummary {
+    /// ACK records with one of the selected statuses
+    pub acks_selected: usize,
+    pub transactions_read: usize,
+    pub transactions_kept: usize,
+}
+
+/// Writes the transactions of `original_filename` that `ack_filename` reports with one of the selected statuses
+/// to `output_filename`
+pub fn build_resubmission(
+    original_filename: &str, ack_filename: &str, output_filename: &str, options: &ResubmitOptions,
+) -> Result<ResubmitSummary, CwrParseError> {
+    let output = BufWriter::new(File::create(output_filename)?);
+    build_resubmission_to_writer(original_filename, ack_filename, output, options)
+}
+
+/// Writes the transactions of `original_filename` that `ack_filename` reports with one of the selected statuses
+/// to `output`
+pub fn build_resubmission_to_writer<W: Write>(
+    original_filename: &str, ack_filename: &str, output: W, options: &ResubmitOptions,
+) -> Result<ResubmitSummary, CwrParseError> {
+    let selected = selected_transactions(ack_filename, options)?;
+
+    let FilterSummary { transactions_read, transactions_kept } = rewrite_transactions(
+        original_filename,
+        options.version_hint,
+        |group_id, grh, records| {
+            let Some(header) = records.first() else {
+                return false;
+            };
+            let Some((transaction_seq, _)) = header.sequence_numbers() else {
+                return false;
+            };
+            let Some(submitter_creation_num) = selected.get(&(group_id, transaction_seq)) else {
+                return false;
+            };
+            if let (Some(expected), CwrRegistry::Nwr(nwr)) = (submitter_creation_num, header)
+                && nwr.submitter_work_num.trim() != expected
+            {
+                return false;
+            }
+
+            if options.as_revision
+                && let Some(CwrRegistry::Nwr(nwr)) = records.first_mut()
+                && nwr.transaction_type() == TransactionType::NWR
+            {
+                nwr.record_type = crate::parsing::intern("REV");
+                grh.transaction_type = TransactionType::REV;
+            }
+            true
+        },
+        output,
+    )?;
+
+    let summary = ResubmitSummary { acks_selected: selected.len(), transactions_read, transactions_kept };
+    info!(
+        file = original_filename,
+        "Resubmitting {} of {} transactions from '{}' ({} selected ACKs in '{}')",
+        summary.transactions_kept,
+        summary.transactions_read,
+        original_filename,
+        summary.acks_selected,
+        ack_filename
+    );
+    Ok(summary)
+}
+
+/// Original (group ID, transaction sequence number) of every ACK with a selected status, with the submitter
+/// creation number when the ACK carries one
+fn selected_transactions(
+    ack_filename: &str, options: &ResubmitOptions,
+) -> Result<HashMap<(u32, u32), Option<String>>, CwrParseError> {
+    let mut selected = HashMap::new();
+    for result in process_cwr_stream_with_version(ack_filename, options.version_hint)? {
+        let parsed_record = result?;
+        if let CwrRegistry::Ack(ack) = parsed_record.record
+            && options.statuses.iter().any(|status| status.eq_ignore_ascii_case(ack.transaction_status.as_str()))
+        {
+            let submitter_creation_num =
+                ack.submitter_creation_num.map(|num| num.trim().to_string()).filter(|num| !num.is_empty());
+            selected.insert((ack.original_group_id.0, ack.original_transaction_sequence_num.0), submitter_creation_num);
+        }
+    }
+    Ok(selected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HDR: &str = "HDRPB285606836WARNER CHAPPELL MUSIC PUBLISHING LTD         01.102022122112541120221221";
+
+    fn nwr(transaction_seq: u32, submitter_work_num: &str) -> String {
+        format!(
+            "NWR{:08}00000000{:<60}  {:<14}     SER        Y       ORI",
+            transaction_seq, "TEST WORK TITLE", submitter_work_num
+        )
+    }
+
+    fn ack(transaction_seq: u32, original_seq: u32, submitter_work_num: &str, status: &str) -> String {
+        format!(
+            "ACK{:08}0000000020221221125411000010{:07}NWR{:<60}{:<20}{:<20}20221222{}",
+            transaction_seq, original_seq, "TEST WORK TITLE", submitter_work_num, "", status
+        )
+    }
+
+    #[test]
+    fn test_resubmit_rejected_transactions_as_revisions() {
+        let dir = std::env::temp_dir().join(format!("resubmit_test_{:?}", std::thread::current().id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let original = dir.join("original.V21");
+        let ack_file = dir.join("ack.V21");
+        let original_lines = [
+            HDR.to_string(),
+            "GRHNWR0000102.100000000000  ".to_string(),
+            nwr(0, "SW0000000001"),
+            nwr(1, "SW0000000002"),
+            nwr(2, "SW0000000003"),
+            "GRT000010000000300000005".to_string(),
+            "TRL000000010000000300000007".to_string(),
+        ];
+        std::fs::write(&original, original_lines.join("\r\n")).unwrap();
+        let ack_lines = [
+            HDR.to_string(),
+            "GRHACK0000102.100000000000  ".to_string(),
+            ack(0, 0, "SW0000000001", "AS"),
+            ack(1, 1, "SW0000000002", "RJ"),
+            ack(2, 2, "SW0000000003", "CO"),
+            "GRT000010000000300000005".to_string(),
+            "TRL000000010000000300000007".to_string(),
+        ];
+        std::fs::write(&ack_file, ack_lines.join("\r\n")).unwrap();
+
+        let mut output = Vec::new();
+        let options = ResubmitOptions { as_revision: true, ..Default::default() };
+        let result =
+            build_resubmission_to_writer(original.to_str().unwrap(), ack_file.to_str().unwrap(), &mut output, &options);
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert_eq!(result.unwrap(), ResubmitSummary { acks_selected: 2, transactions_read: 3, transactions_kept: 2 });
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 6);
+        assert!(lines[1].starts_with("GRHREV00001"), "{}", lines[1]);
+        assert!(lines[2].starts_with("REV0000000000000000TEST WORK TITLE"), "{}", lines[2]);
+        assert!(lines[2].contains("SW0000000002"));
+        assert!(lines[3].starts_with("REV0000000100000000"), "{}", lines[3]);
+        assert!(lines[3].contains("SW0000000003"));
+        assert!(lines[4].starts_with("GRT000010000000200000004"), "{}", lines[4]);
+        assert!(lines[5].starts_with("TRL000010000000200000006"), "{}", lines[5]);
+    }
+}
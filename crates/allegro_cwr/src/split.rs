@@ -0,0 +1,176 @@
+//! Splitting large CWR files into smaller, self-contained files
+//!
+//! Each output file gets a copy of the original HDR, regenerated GRH/GRT/TRL control records and renumbered
+//! transaction/record sequence numbers, so every chunk is a valid transmission on its own.
+
+use crate::cwr_registry::CwrRegistry;
+use crate::domain_types::{CharacterSet, CwrVersion};
+use crate::error::CwrParseError;
+use crate::parser::process_cwr_stream_with_version;
+use crate::records::{GrhRecord, HdrRecord};
+use crate::transmission_writer::TransmissionWriter;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use tracing::info;
+
+/// Builds the default output filename for a chunk: `name.V22` becomes `name_part001.V22`
+pub fn split_output_filename(input_filename: &str, chunk_index: usize) -> String {
+    let path = Path::new(input_filename);
+    let stem = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    let file_name = match path.extension() {
+        Some(ext) => format!("{}_part{:03}.{}", stem, chunk_index + 1, ext.to_string_lossy()),
+        None => format!("{}_part{:03}", stem, chunk_index + 1),
+    };
+    path.with_file_name(file_name).to_string_lossy().to_string()
+}
+
+/// Splits a CWR file into files holding at most `transactions_per_file` transactions each.
+/// Output files are named with [`split_output_filename`]. Returns the names of the files written.
+pub fn split_cwr_file(
+    input_filename: &str, transactions_per_file: usize, version_hint: Option<f32>,
+) -> Result<Vec<String>, CwrParseError> {
+    let mut filenames = Vec::new();
+    split_cwr_file_with(input_filename, transactions_per_file, version_hint, |chunk_index| {
+        let filename = split_output_filename(input_filename, chunk_index);
+        let file = File::create(&filename)?;
+        filenames.push(filename);
+        Ok(BufWriter::new(file))
+    })?;
+    Ok(filenames)
+}
+
+/// Splits a CWR file, asking `open_chunk` for a writer for each chunk (called with a zero-based chunk index).
+/// Returns the number of chunks written.
+pub fn split_cwr_file_with<W, F>(
+    input_filename: &str, transactions_per_file: usize, version_hint: Option<f32>, mut open_chunk: F,
+) -> Result<usize, CwrParseError>
+where
+    W: Write,
+    F: FnMut(usize) -> std::io::Result<W>,
+{
+    if transactions_per_file == 0 {
+        return Err(CwrParseError::BadFormat("Transactions per file must be greater than zero".to_string()));
+    }
+
+    let mut hdr: Option<HdrRecord> = None;
+    let mut source_group: Option<(u32, GrhRecord)> = None;
+    let mut chunk: Option<TransmissionWriter<W>> = None;
+    let mut chunk_count = 0;
+
+    for result in process_cwr_stream_with_version(input_filename, version_hint)? {
+        let parsed_record = result?;
+        let version = CwrVersion(parsed_record.context.cwr_version);
+        let character_set = parsed_record.context.character_set.clone().unwrap_or(CharacterSet::ASCII);
+
+        match parsed_record.record {
+            CwrRegistry::Hdr(record) => hdr = Some(record),
+            CwrRegistry::Grh(record) => source_group = Some((record.group_id.0, record)),
+            // Control totals are regenerated for each chunk
+            CwrRegistry::Grt(_) | CwrRegistry::Trl(_) => {}
+            mut record => {
+                let Some((source_group_id, grh)) = source_group.as_ref() else {
+                    return Err(CwrParseError::BadFormat(format!(
+                        "Line {}: {} record outside of a group",
+                        parsed_record.line_number,
+                        record.record_type()
+                    )));
+                };
+                let starts_transaction = record.record_type() == grh.transaction_type.as_str();
+
+                if starts_transaction
+                    && chunk.as_ref().is_some_and(|c| c.transaction_count() >= transactions_per_file)
+                    && let Some(finished) = chunk.take()
+                {
+                    finished.finish()?;
+                }
+
+                if chunk.is_none() {
+                    let Some(hdr) = hdr.as_ref() else {
+                        return Err(CwrParseError::BadFormat("File has no HDR record".to_string()));
+                    };
+                    let writer = open_chunk(chunk_count)?;
+                    chunk = Some(TransmissionWriter::start(writer, hdr, version, character_set)?);
+                    chunk_count += 1;
+                }
+
+                let Some(current) = chunk.as_mut() else {
+                    continue;
+                };
+                if starts_transaction {
+                    current.start_transaction(*source_group_id, grh)?;
+                } else if !current.in_group() {
+                    return Err(CwrParseError::BadFormat(format!(
+                        "Line {}: {} record before the first transaction header",
+                        parsed_record.line_number,
+                        record.record_type()
+                    )));
+                }
+                current.write_detail(&mut record)?;
+            }
+        }
+    }
+
+    if let Some(finished) = chunk.take() {
+        finished.finish()?;
+    }
+
+    info!(file = input_filename, "Split '{}' into {} files", input_filename, chunk_count);
+    Ok(chunk_count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    const HDR: &str = "HDRPB123456789BMI MUSIC                                    01.1020050101120000200501010              2.2  1DEV MUSIC SOF";
+    const AGR: &str = "AGR00000001000000011234567890123               AA20231201                N        N                00001                ";
+
+    #[test]
+    fn test_split_by_transaction_count() {
+        let mut lines = vec![HDR.to_string(), "GRHAGR0000102.20            ".to_string()];
+        for transaction in 0..3 {
+            lines.push(AGR.replacen("0000000100000001", &format!("{:08}00000000", transaction), 1));
+            lines.push(format!("TER{:08}00000001I2840", transaction));
+        }
+        lines.push("GRT000010000000300000008".to_string());
+        lines.push("TRL000010000000300000010".to_string());
+
+        let input = std::env::temp_dir().join(format!("split_test_{:?}.V22", std::thread::current().id()));
+        let mut file = File::create(&input).unwrap();
+        file.write_all(lines.join("\r\n").as_bytes()).unwrap();
+        drop(file);
+
+        let result = split_cwr_file(input.to_str().unwrap(), 2, None);
+        std::fs::remove_file(&input).ok();
+        let filenames = result.unwrap();
+        assert_eq!(filenames.len(), 2);
+
+        let contents: Vec<String> = filenames.iter().map(|f| std::fs::read_to_string(f).unwrap()).collect();
+        for filename in &filenames {
+            std::fs::remove_file(filename).ok();
+        }
+
+        let first: Vec<&str> = contents[0].lines().collect();
+        assert_eq!(first.len(), 8);
+        assert!(first[0].starts_with("HDR"));
+        assert!(first[4].starts_with("AGR0000000100000000"));
+        assert!(first[5].starts_with("TER0000000100000001"));
+        assert!(first[6].starts_with("GRT000010000000200000006"));
+        assert!(first[7].starts_with("TRL000010000000200000008"));
+
+        let second: Vec<&str> = contents[1].lines().collect();
+        assert_eq!(second.len(), 6);
+        assert!(second[1].starts_with("GRHAGR00001"));
+        assert!(second[2].starts_with("AGR0000000000000000"));
+        assert!(second[4].starts_with("GRT000010000000100000004"));
+        assert!(second[5].starts_with("TRL000010000000100000006"));
+    }
+
+    #[test]
+    fn test_split_output_filename() {
+        assert_eq!(split_output_filename("works.V22", 0), "works_part001.V22");
+        assert_eq!(split_output_filename("works", 11), "works_part012");
+    }
+}
@@ -0,0 +1,499 @@
+//! Builds a CWR transmission of work registrations from a spreadsheet saved as CSV or TSV
+//!
+//! Each row is one party of a work: a writer or a publisher with its shares. Rows with the same submitter work
+//! number make up one work, in the order the works first appear. Which column holds what is set by a
+//! [`ColumnMapping`]; the work columns only need to be filled on the first row of each work.
+
+use crate::builder::{PublisherBuilder, Shares, WorkBuilder, WriterBuilder};
+use crate::domain_types::*;
+use crate::error::CwrParseError;
+use crate::records::{GrhRecord, HdrRecord};
+use crate::transmission_writer::{TransmissionTotals, TransmissionWriter};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{Read, Write};
+
+/// Header names of the spreadsheet columns. Every column defaults to a header named after the field.
+/// `work_title`, `submitter_work_num`, `party_type`, `interested_party_num` and `name` are required; the others
+/// are used when the spreadsheet has them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ColumnMapping {
+    pub work_title: String,
+    pub submitter_work_num: String,
+    pub iswc: String,
+    /// `writer` or `publisher` (also `W`/`SWR` and `P`/`SPU`)
+    pub party_type: String,
+    pub interested_party_num: String,
+    /// Writer last name or publisher name
+    pub name: String,
+    pub first_name: String,
+    /// Writer designation code (`CA`, `C`, `A`, ...) or publisher type (`E`, `AM`, `SE`, ...)
+    pub role: String,
+    pub ipi_name_num: String,
+    pub pr_society: String,
+    pub mr_society: String,
+    pub sr_society: String,
+    /// Ownership shares in percent (`50`, `33.33`, `50%`)
+    pub pr_share: String,
+    pub mr_share: String,
+    pub sr_share: String,
+    /// TIS code of the territory the party collects its shares in
+    pub territory: String,
+    /// Interested party number of the publisher representing a writer
+    pub publisher: String,
+}
+
+impl Default for ColumnMapping {
+    fn default() -> Self {
+        ColumnMapping {
+            work_title: "work_title".to_string(),
+            submitter_work_num: "submitter_work_num".to_string(),
+            iswc: "iswc".to_string(),
+            party_type: "party_type".to_string(),
+            interested_party_num: "interested_party_num".to_string(),
+            name: "name".to_string(),
+            first_name: "first_name".to_string(),
+            role: "role".to_string(),
+            ipi_name_num: "ipi_name_num".to_string(),
+            pr_society: "pr_society".to_string(),
+            mr_society: "mr_society".to_string(),
+            sr_society: "sr_society".to_string(),
+            pr_share: "pr_share".to_string(),
+            mr_share: "mr_share".to_string(),
+            sr_share: "sr_share".to_string(),
+            territory: "territory".to_string(),
+            publisher: "publisher".to_string(),
+        }
+    }
+}
+
+/// How a spreadsheet is read and the transmission written
+#[derive(Debug, Clone)]
+pub struct SpreadsheetOptions {
+    pub columns: ColumnMapping,
+    /// Field separator: `,` for CSV, `\t` for TSV
+    pub delimiter: char,
+    pub version: CwrVersion,
+    pub character_set: CharacterSet,
+}
+
+impl Default for SpreadsheetOptions {
+    fn default() -> Self {
+        SpreadsheetOptions {
+            columns: ColumnMapping::default(),
+            delimiter: ',',
+            version: CwrVersion(2.2),
+            character_set: CharacterSet::ASCII,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum SpreadsheetError {
+    Io(std::io::Error),
+    /// Problems with the rows or the works they describe, by line or submitter work number; nothing is written
+    Invalid(Vec<String>),
+    Write(CwrParseError),
+}
+
+impl fmt::Display for SpreadsheetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpreadsheetError::Io(e) => write!(f, "Error reading spreadsheet: {}", e),
+            SpreadsheetError::Invalid(problems) => write!(f, "Invalid spreadsheet: {}", problems.join("; ")),
+            SpreadsheetError::Write(e) => write!(f, "Error writing CWR: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for SpreadsheetError {}
+
+impl From<std::io::Error> for SpreadsheetError {
+    fn from(e: std::io::Error) -> Self {
+        SpreadsheetError::Io(e)
+    }
+}
+
+impl From<CwrParseError> for SpreadsheetError {
+    fn from(e: CwrParseError) -> Self {
+        SpreadsheetError::Write(e)
+    }
+}
+
+/// An HDR for a transmission created now
+pub fn new_header(sender_type: SenderType, sender_id: &str, sender_name: &str, version: CwrVersion) -> HdrRecord {
+    let now = chrono::Utc::now().naive_utc();
+    let v22 = version.0 >= 2.2;
+    HdrRecord {
+        record_type: crate::parsing::intern("HDR"),
+        sender_type,
+        sender_id: SenderId(sender_id.to_string()),
+        sender_name: SenderName(sender_name.to_string()),
+        edi_standard_version_number: EdiStandardVersion("01.10".to_string()),
+        creation_date: Date(now.date()),
+        creation_time: Time(now.time()),
+        transmission_date: Date(now.date()),
+        character_set: None,
+        version: v22.then_some(version),
+        revision: v22.then_some(CwrRevision(1)),
+        software_package: None,
+        software_package_version: None,
+    }
+}
+
+/// Reads the spreadsheet and writes one NWR group with a transaction per work, returning the TRL totals. Every
+/// row and work is checked before anything is written; all problems found are returned together.
+pub fn spreadsheet_to_cwr<R: Read, W: Write>(
+    mut reader: R, writer: W, hdr: &HdrRecord, options: &SpreadsheetOptions,
+) -> Result<TransmissionTotals, SpreadsheetError> {
+    let mut text = String::new();
+    reader.read_to_string(&mut text)?;
+    let works = read_works(&text, options)?;
+
+    let grh = GrhRecord {
+        record_type: crate::parsing::intern("GRH"),
+        transaction_type: TransactionType::NWR,
+        group_id: GroupId(1),
+        version_number: CwrVersionNumber(format!("{:05.2}", options.version.0)),
+        batch_request: None,
+        submission_distribution_type: None,
+    };
+    let mut transmission =
+        TransmissionWriter::start(writer, hdr, options.version.clone(), options.character_set.clone())?;
+    for records in works {
+        transmission.start_transaction(1, &grh)?;
+        for mut record in records {
+            transmission.write_detail(&mut record)?;
+        }
+    }
+    Ok(transmission.finish()?)
+}
+
+fn read_works(text: &str, options: &SpreadsheetOptions) -> Result<Vec<Vec<crate::CwrRegistry>>, SpreadsheetError> {
+    let mut rows = split_rows(text.trim_start_matches('\u{feff}'), options.delimiter).into_iter();
+    let Some((_, headers)) = rows.next() else {
+        return Err(SpreadsheetError::Invalid(vec!["Spreadsheet is empty".to_string()]));
+    };
+    let columns = Columns::new(&options.columns, &headers)?;
+
+    let mut problems = Vec::new();
+    let mut works: Vec<(String, WorkBuilder)> = Vec::new();
+    let mut work_index: HashMap<String, usize> = HashMap::new();
+    for (line, cells) in rows {
+        if cells.iter().all(|cell| cell.trim().is_empty()) {
+            continue;
+        }
+        let cell = |column: Option<usize>| column.and_then(|index| cells.get(index)).map_or("", |cell| cell.trim());
+        let submitter_work_num = cell(columns.submitter_work_num);
+        if submitter_work_num.is_empty() {
+            problems.push(format!("Line {}: submitter work number is empty", line));
+            continue;
+        }
+        let index = match work_index.get(submitter_work_num) {
+            Some(&index) => index,
+            None => {
+                let mut work = WorkBuilder::new(cell(columns.work_title), submitter_work_num);
+                if !cell(columns.iswc).is_empty() {
+                    work = work.iswc(cell(columns.iswc));
+                }
+                works.push((submitter_work_num.to_string(), work));
+                work_index.insert(submitter_work_num.to_string(), works.len() - 1);
+                works.len() - 1
+            }
+        };
+
+        let party = match party(&columns, &cell) {
+            Ok(party) => party,
+            Err(problem) => {
+                problems.push(format!("Line {}: {}", line, problem));
+                continue;
+            }
+        };
+        let work = &mut works[index].1;
+        let builder = std::mem::replace(work, WorkBuilder::new("", ""));
+        *work = match party {
+            Party::Writer(writer) => builder.writer(writer),
+            Party::Publisher(publisher) => builder.publisher(publisher),
+        };
+    }
+
+    let mut transactions = Vec::new();
+    for (submitter_work_num, work) in works {
+        match work.build(0) {
+            Ok(records) => transactions.push(records),
+            Err(e) => problems.extend(e.problems.into_iter().map(|p| format!("Work '{}': {}", submitter_work_num, p))),
+        }
+    }
+    if !problems.is_empty() {
+        return Err(SpreadsheetError::Invalid(problems));
+    }
+    Ok(transactions)
+}
+
+/// Column index of each mapped header found in the spreadsheet
+struct Columns {
+    work_title: Option<usize>,
+    submitter_work_num: Option<usize>,
+    iswc: Option<usize>,
+    party_type: Option<usize>,
+    interested_party_num: Option<usize>,
+    name: Option<usize>,
+    first_name: Option<usize>,
+    role: Option<usize>,
+    ipi_name_num: Option<usize>,
+    societies: [Option<usize>; 3],
+    shares: [Option<usize>; 3],
+    territory: Option<usize>,
+    publisher: Option<usize>,
+}
+
+impl Columns {
+    fn new(mapping: &ColumnMapping, headers: &[String]) -> Result<Self, SpreadsheetError> {
+        let find = |header: &str| headers.iter().position(|h| h.trim().eq_ignore_ascii_case(header.trim()));
+        let missing: Vec<String> = [
+            &mapping.work_title,
+            &mapping.submitter_work_num,
+            &mapping.party_type,
+            &mapping.interested_party_num,
+            &mapping.name,
+        ]
+        .into_iter()
+        .filter(|header| find(header).is_none())
+        .map(|header| format!("Missing column '{}'", header))
+        .collect();
+        if !missing.is_empty() {
+            return Err(SpreadsheetError::Invalid(missing));
+        }
+        Ok(Columns {
+            work_title: find(&mapping.work_title),
+            submitter_work_num: find(&mapping.submitter_work_num),
+            iswc: find(&mapping.iswc),
+            party_type: find(&mapping.party_type),
+            interested_party_num: find(&mapping.interested_party_num),
+            name: find(&mapping.name),
+            first_name: find(&mapping.first_name),
+            role: find(&mapping.role),
+            ipi_name_num: find(&mapping.ipi_name_num),
+            societies: [find(&mapping.pr_society), find(&mapping.mr_society), find(&mapping.sr_society)],
+            shares: [find(&mapping.pr_share), find(&mapping.mr_share), find(&mapping.sr_share)],
+            territory: find(&mapping.territory),
+            publisher: find(&mapping.publisher),
+        })
+    }
+}
+
+enum Party {
+    Writer(WriterBuilder),
+    Publisher(PublisherBuilder),
+}
+
+fn party<'a>(columns: &Columns, cell: &impl Fn(Option<usize>) -> &'a str) -> Result<Party, String> {
+    let interested_party_num = cell(columns.interested_party_num);
+    let name = cell(columns.name);
+    let [pr, mr, sr] = columns.shares.map(|column| percent(cell(column)));
+    let shares = Shares::new(pr?, mr?, sr?);
+    let [pr_society, mr_society, sr_society] = columns.societies.map(cell);
+    let role = cell(columns.role);
+    let ipi_name_num = cell(columns.ipi_name_num);
+    let territory = match cell(columns.territory) {
+        "" => None,
+        code => Some(code.parse::<u16>().map_err(|_| format!("invalid territory code '{}'", code))?),
+    };
+
+    match cell(columns.party_type).to_ascii_uppercase().as_str() {
+        "WRITER" | "W" | "SWR" => {
+            let mut writer = WriterBuilder::new(interested_party_num, name)
+                .ownership(shares)
+                .societies(pr_society, mr_society, sr_society);
+            if !cell(columns.first_name).is_empty() {
+                writer = writer.first_name(cell(columns.first_name));
+            }
+            if !role.is_empty() {
+                writer = writer.designation(&role.to_ascii_uppercase());
+            }
+            if !ipi_name_num.is_empty() {
+                writer = writer.ipi_name_num(ipi_name_num);
+            }
+            if let Some(tis_code) = territory {
+                writer = writer.territory(tis_code, shares);
+            }
+            if !cell(columns.publisher).is_empty() {
+                writer = writer.represented_by(cell(columns.publisher));
+            }
+            Ok(Party::Writer(writer))
+        }
+        "PUBLISHER" | "P" | "SPU" => {
+            let mut publisher = PublisherBuilder::new(interested_party_num, name)
+                .ownership(shares)
+                .societies(pr_society, mr_society, sr_society);
+            if !role.is_empty() {
+                publisher = publisher.publisher_type(publisher_type(role)?);
+            }
+            if !ipi_name_num.is_empty() {
+                publisher = publisher.ipi_name_num(ipi_name_num);
+            }
+            if let Some(tis_code) = territory {
+                publisher = publisher.territory(tis_code, shares);
+            }
+            Ok(Party::Publisher(publisher))
+        }
+        other => Err(format!("party type '{}' is neither writer nor publisher", other)),
+    }
+}
+
+fn publisher_type(code: &str) -> Result<PublisherType, String> {
+    match code.to_ascii_uppercase().as_str() {
+        "E" => Ok(PublisherType::OriginalPublisher),
+        "AQ" => Ok(PublisherType::Acquirer),
+        "AM" => Ok(PublisherType::Administrator),
+        "PA" => Ok(PublisherType::IncomeParticipant),
+        "ES" => Ok(PublisherType::SubstitutedPublisher),
+        "SE" => Ok(PublisherType::SubPublisher),
+        _ => Err(format!("invalid publisher type '{}'", code)),
+    }
+}
+
+/// A percentage such as `50`, `33.33` or `50%` in hundredths of a percent; empty is zero
+fn percent(value: &str) -> Result<u16, String> {
+    let number = value.trim_end_matches('%').trim();
+    if number.is_empty() {
+        return Ok(0);
+    }
+    number
+        .replace(',', ".")
+        .parse::<f64>()
+        .ok()
+        .filter(|percent| (0.0..=100.0).contains(percent))
+        .map(|percent| (percent * 100.0).round() as u16)
+        .ok_or_else(|| format!("invalid share '{}', expected a percentage from 0 to 100", value))
+}
+
+/// Splits delimited text into rows of cells with the line each row starts on. Cells may be quoted with `"`, with
+/// `""` for a quote inside a quoted cell; quoted cells may contain the delimiter and line breaks.
+fn split_rows(text: &str, delimiter: char) -> Vec<(usize, Vec<String>)> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut cell = String::new();
+    let mut quoted = false;
+    let mut line = 1;
+    let mut row_line = 1;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted => {
+                if chars.peek() == Some(&'"') {
+                    chars.next();
+                    cell.push('"');
+                } else {
+                    quoted = false;
+                }
+            }
+            '"' if cell.is_empty() => quoted = true,
+            '\n' if quoted => {
+                line += 1;
+                cell.push(c);
+            }
+            '\r' if !quoted => {}
+            '\n' => {
+                row.push(std::mem::take(&mut cell));
+                rows.push((row_line, std::mem::take(&mut row)));
+                line += 1;
+                row_line = line;
+            }
+            c if c == delimiter && !quoted => row.push(std::mem::take(&mut cell)),
+            c => cell.push(c),
+        }
+    }
+    if !cell.is_empty() || !row.is_empty() {
+        row.push(cell);
+        rows.push((row_line, row));
+    }
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CwrRegistry;
+    use crate::parser::{ParseOptions, process_cwr_bytes_stream};
+
+    const SHEET: &str = "\u{feff}Song,Code,Type,IP,Name,First,Role,Society,Share,Territory,Publisher\r\n\
+        \"Love, Again\",SW1,Publisher,P1,ACME MUSIC,,E,021,50,2136,\r\n\
+        \"Love, Again\",SW1,Writer,W1,SMITH,JANE,CA,021,50%,2136,P1\r\n\
+        ,,,,,,,,,,\r\n\
+        Second Song,SW2,writer,W1,SMITH,JANE,ca,021,100,,\r\n";
+
+    fn options() -> SpreadsheetOptions {
+        let columns = ColumnMapping {
+            work_title: "Song".to_string(),
+            submitter_work_num: "Code".to_string(),
+            party_type: "Type".to_string(),
+            interested_party_num: "IP".to_string(),
+            name: "Name".to_string(),
+            first_name: "First".to_string(),
+            role: "Role".to_string(),
+            pr_society: "Society".to_string(),
+            pr_share: "Share".to_string(),
+            territory: "Territory".to_string(),
+            publisher: "Publisher".to_string(),
+            ..Default::default()
+        };
+        SpreadsheetOptions { columns, ..Default::default() }
+    }
+
+    #[test]
+    fn test_spreadsheet_to_cwr() {
+        let hdr = new_header(SenderType::Publisher, "000000001", "ACME MUSIC", CwrVersion(2.2));
+        let mut output = Vec::new();
+        let totals = spreadsheet_to_cwr(SHEET.as_bytes(), &mut output, &hdr, &options()).unwrap();
+        assert_eq!(totals, TransmissionTotals { groups: 1, transactions: 2, records: 12 });
+
+        let records: Vec<CwrRegistry> =
+            process_cwr_bytes_stream(&output, &ParseOptions::default()).unwrap().map(|r| r.unwrap().record).collect();
+        let types: Vec<&str> = records.iter().map(CwrRegistry::record_type).collect();
+        assert_eq!(types, ["HDR", "GRH", "NWR", "SPU", "SPT", "SWR", "SWT", "PWR", "NWR", "SWR", "GRT", "TRL"]);
+        let CwrRegistry::Nwr(nwr) = &records[2] else { panic!("expected NWR") };
+        assert_eq!(nwr.work_title.trim_end(), "Love, Again");
+        let CwrRegistry::Swr(swr) = &records[9] else { panic!("expected SWR") };
+        assert_eq!(swr.pr_ownership_share, Some(OwnershipShare(10000)));
+        assert_eq!(swr.writer_designation_code, Some(WriterDesignation("CA".to_string())));
+    }
+
+    #[test]
+    fn test_spreadsheet_problems() {
+        let hdr = new_header(SenderType::Publisher, "000000001", "ACME MUSIC", CwrVersion(2.2));
+        let sheet = "Song\tCode\tType\tIP\tName\tShare\n\
+            First\tSW1\tcomposer\tW1\tSMITH\t50\n\
+            First\tSW1\twriter\tW2\tJONES\t120\n\
+            \t\twriter\tW3\tBROWN\t10\n\
+            Second\tSW2\twriter\tW1\tSMITH\t60\n\
+            Second\tSW2\twriter\tW2\tJONES\t60\n";
+        let options = SpreadsheetOptions { delimiter: '\t', ..options() };
+        let Err(SpreadsheetError::Invalid(problems)) = spreadsheet_to_cwr(sheet.as_bytes(), Vec::new(), &hdr, &options)
+        else {
+            panic!("expected problems")
+        };
+        assert_eq!(problems[0], "Line 2: party type 'COMPOSER' is neither writer nor publisher");
+        assert!(problems[1].starts_with("Line 3: invalid share '120'"), "{:?}", problems);
+        assert_eq!(problems[2], "Line 4: submitter work number is empty");
+        assert!(problems[3].starts_with("Work 'SW1': At least one writer is required"), "{:?}", problems);
+        assert!(problems[4].starts_with("Work 'SW2': PR ownership shares total 120.00%"), "{:?}", problems);
+
+        let Err(SpreadsheetError::Invalid(problems)) =
+            spreadsheet_to_cwr("Title,Code\n".as_bytes(), Vec::new(), &hdr, &SpreadsheetOptions::default())
+        else {
+            panic!("expected problems")
+        };
+        assert_eq!(problems.len(), 5);
+        assert_eq!(problems[0], "Missing column 'work_title'");
+    }
+
+    #[test]
+    fn test_split_rows_quoting() {
+        let rows = split_rows("a,\"b \"\"quoted\"\"\nnext\",c\nd", ',');
+        assert_eq!(rows[0], (1, vec!["a".to_string(), "b \"quoted\"\nnext".to_string(), "c".to_string()]));
+        assert_eq!(rows[1], (3, vec!["d".to_string()]));
+    }
+}
@@ -0,0 +1,224 @@
+//! One-pass, human-readable summary of a CWR transmission, for triage before full ingestion
+
+use crate::cwr_handler::{CwrHandler, HandlerReport};
+use crate::cwr_registry::CwrRegistry;
+use crate::error::CwrParseError;
+use crate::lookups::tis_codes::get_territory_name;
+use crate::parser::ParsedRecord;
+use crate::util::format_int_with_commas;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt;
+
+/// Number of warning types listed in the report
+const TOP_WARNING_TYPES: usize = 10;
+
+/// What a transmission contains, gathered by [`SummaryHandler`]
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize)]
+pub struct TransmissionSummary {
+    pub sender_id: Option<String>,
+    pub sender_name: Option<String>,
+    pub cwr_version: Option<f32>,
+    pub record_count: usize,
+    pub group_count: usize,
+    /// Transactions per transaction type (NWR, REV, AGR, ...)
+    pub transaction_counts: BTreeMap<String, usize>,
+    /// Work registrations (NWR, REV, ISW and EXC transactions)
+    pub work_count: usize,
+    pub unique_writers: usize,
+    pub unique_publishers: usize,
+    /// TIS codes named in TER, SPT and SWT records
+    pub territories: BTreeSet<u16>,
+    /// Warning types (record type and field) with their counts, most frequent first
+    pub top_warnings: Vec<(String, usize)>,
+    pub warning_count: usize,
+    pub parse_error_count: usize,
+}
+
+impl fmt::Display for TransmissionSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let count = |n: usize| format_int_with_commas(n as i64);
+
+        writeln!(f, "Transmission summary:")?;
+        match (&self.sender_name, &self.sender_id) {
+            (Some(name), Some(id)) => writeln!(f, "  Sender: {} ({})", name, id)?,
+            _ => writeln!(f, "  Sender: unknown")?,
+        }
+        match self.cwr_version {
+            Some(version) => writeln!(f, "  CWR version: {:.1}", version)?,
+            None => writeln!(f, "  CWR version: unknown")?,
+        }
+        writeln!(f, "  Records: {}", count(self.record_count))?;
+        writeln!(f, "  Groups: {}", count(self.group_count))?;
+        let transactions: Vec<String> =
+            self.transaction_counts.iter().map(|(kind, n)| format!("{} {}", kind, count(*n))).collect();
+        if transactions.is_empty() {
+            writeln!(f, "  Transactions: none")?;
+        } else {
+            writeln!(f, "  Transactions: {}", transactions.join(", "))?;
+        }
+        writeln!(f, "  Works: {}", count(self.work_count))?;
+        writeln!(f, "  Unique writers: {}", count(self.unique_writers))?;
+        writeln!(f, "  Unique publishers: {}", count(self.unique_publishers))?;
+
+        let territories: Vec<String> = self
+            .territories
+            .iter()
+            .map(|code| match get_territory_name(*code) {
+                Some(name) => format!("{} ({})", name, code),
+                None => code.to_string(),
+            })
+            .collect();
+        if territories.is_empty() {
+            writeln!(f, "  Territories: none")?;
+        } else {
+            writeln!(f, "  Territories: {}", territories.join(", "))?;
+        }
+
+        writeln!(f, "  Parse errors: {}", count(self.parse_error_count))?;
+        write!(f, "  Warnings: {}", count(self.warning_count))?;
+        for (warning_type, n) in &self.top_warnings {
+            write!(f, "\n    {:>8}  {}", count(*n), warning_type)?;
+        }
+        Ok(())
+    }
+}
+
+/// Collects a [`TransmissionSummary`] while records stream past
+#[derive(Debug, Default)]
+pub struct SummaryHandler {
+    summary: TransmissionSummary,
+    writers: HashSet<String>,
+    publishers: HashSet<String>,
+    warnings: HashMap<String, usize>,
+}
+
+impl SummaryHandler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds one record to the summary
+    pub fn observe(&mut self, parsed_record: &ParsedRecord) {
+        let summary = &mut self.summary;
+        summary.record_count += 1;
+        summary.cwr_version.get_or_insert(parsed_record.context.cwr_version);
+
+        match &parsed_record.record {
+            CwrRegistry::Hdr(hdr) => {
+                summary.sender_id = Some(hdr.sender_id.as_str().trim().to_string());
+                summary.sender_name = Some(hdr.sender_name.as_str().trim().to_string());
+            }
+            CwrRegistry::Grh(_) => summary.group_count += 1,
+            CwrRegistry::Swr(swr) => {
+                if let Some(key) = party_key(swr.interested_party_num.as_deref(), swr.writer_last_name.as_deref()) {
+                    self.writers.insert(key);
+                }
+            }
+            CwrRegistry::Spu(spu) => {
+                if let Some(key) = party_key(spu.interested_party_num.as_deref(), spu.publisher_name.as_deref()) {
+                    self.publishers.insert(key);
+                }
+            }
+            CwrRegistry::Ter(ter) => {
+                summary.territories.insert(ter.tis_numeric_code.0);
+            }
+            CwrRegistry::Spt(spt) => {
+                summary.territories.insert(spt.tis_numeric_code.0);
+            }
+            CwrRegistry::Swt(swt) => {
+                summary.territories.insert(swt.tis_numeric_code.0);
+            }
+            _ => {}
+        }
+
+        let record_type = parsed_record.record.record_type();
+        if let Some((_, 0)) = parsed_record.record.sequence_numbers() {
+            *summary.transaction_counts.entry(record_type.to_string()).or_insert(0) += 1;
+            if matches!(parsed_record.record, CwrRegistry::Nwr(_)) {
+                summary.work_count += 1;
+            }
+        }
+
+        for warning in &parsed_record.warnings {
+            let field = if warning.is_field_warning() { warning.field_title } else { &warning.description };
+            *self.warnings.entry(format!("{} {}", record_type, field)).or_insert(0) += 1;
+            summary.warning_count += 1;
+        }
+    }
+
+    /// The summary of the records observed so far
+    pub fn summary(&self) -> TransmissionSummary {
+        let mut top_warnings: Vec<(String, usize)> = self.warnings.iter().map(|(k, n)| (k.clone(), *n)).collect();
+        top_warnings.sort_by(|(a_type, a), (b_type, b)| b.cmp(a).then_with(|| a_type.cmp(b_type)));
+        top_warnings.truncate(TOP_WARNING_TYPES);
+
+        TransmissionSummary {
+            unique_writers: self.writers.len(),
+            unique_publishers: self.publishers.len(),
+            top_warnings,
+            ..self.summary.clone()
+        }
+    }
+}
+
+/// Identifies an interested party by its number, or by name when the number is missing
+fn party_key(interested_party_num: Option<&str>, name: Option<&str>) -> Option<String> {
+    [interested_party_num, name].into_iter().flatten().map(str::trim).find(|s| !s.is_empty()).map(str::to_string)
+}
+
+impl CwrHandler for SummaryHandler {
+    type Error = std::convert::Infallible;
+
+    fn process_record(&mut self, record: ParsedRecord) -> Result<(), Self::Error> {
+        self.observe(&record);
+        Ok(())
+    }
+
+    fn handle_parse_error(&mut self, _line_number: usize, _error: &CwrParseError) -> Result<(), Self::Error> {
+        self.summary.parse_error_count += 1;
+        Ok(())
+    }
+
+    fn finalize(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn get_report(&self) -> String {
+        self.summary().to_string()
+    }
+
+    fn report(&self) -> HandlerReport {
+        HandlerReport::Summary(self.summary())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary_report() {
+        let nwr = "NWR0000000000000000Test Song                                               SW0000000001        SER        Y       ORI";
+        let content = format!(
+            "HDRPB285606836WARNER CHAPPELL MUSIC PUBLISHING LTD         01.102022122112541120221221\n\
+             GRHNWR0000102.100000000000  \n{}\n{}\nGRT000010000000200000004\nTRL000000010000000200000006\n",
+            nwr,
+            nwr.replacen("0000000000000000", "0000000100000000", 1)
+        );
+        let report = crate::process_cwr_bytes(content.as_bytes(), SummaryHandler::new()).unwrap().report;
+
+        assert!(report.contains("Sender: WARNER CHAPPELL MUSIC PUBLISHING LTD (285606836)"), "{}", report);
+        assert!(report.contains("CWR version: 2.1"), "{}", report);
+        assert!(report.contains("Groups: 1"), "{}", report);
+        assert!(report.contains("Transactions: NWR 2"), "{}", report);
+        assert!(report.contains("Works: 2"), "{}", report);
+        assert!(report.contains("Territories: none"), "{}", report);
+    }
+
+    #[test]
+    fn test_party_key_falls_back_to_name() {
+        assert_eq!(party_key(Some("  "), Some("SMITH ")), Some("SMITH".to_string()));
+        assert_eq!(party_key(Some("IP1"), Some("SMITH")), Some("IP1".to_string()));
+        assert_eq!(party_key(None, None), None);
+    }
+}
@@ -0,0 +1,172 @@
+//! Drive two handlers from a single parse pass; nest [`TeeHandler`]s for more
+
+use crate::cwr_handler::{CwrHandler, HandlerReport, is_interested};
+use crate::parsing::CwrWarning;
+use crate::{CwrParseError, ParsedRecord};
+use std::fmt;
+
+/// Passes every record, warning, error and boundary to both handlers, the first one first. Records are cloned
+/// for the first handler and moved into the second. The report is both reports, one per line.
+///
+/// Each handler only gets the record types it is [interested in](CwrHandler::interested_in). Record types are
+/// skipped before parsing only when both handlers subscribe to the same list.
+///
+/// ```no_run
+/// # #[cfg(feature = "fs")] {
+/// use allegro_cwr::{MetricsHandler, SummaryHandler, TeeHandler, process_cwr_with_handler};
+///
+/// let handler = TeeHandler::new(SummaryHandler::new(), MetricsHandler::new());
+/// let report = process_cwr_with_handler("CW220001ABC_XYZ.V22", handler).unwrap().report;
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct TeeHandler<A, B> {
+    first: A,
+    second: B,
+}
+
+impl<A: CwrHandler, B: CwrHandler> TeeHandler<A, B> {
+    pub fn new(first: A, second: B) -> Self {
+        Self { first, second }
+    }
+
+    pub fn first(&self) -> &A {
+        &self.first
+    }
+
+    pub fn second(&self) -> &B {
+        &self.second
+    }
+
+    pub fn into_inner(self) -> (A, B) {
+        (self.first, self.second)
+    }
+}
+
+/// Error from one of the handlers of a [`TeeHandler`]
+#[derive(Debug)]
+pub enum TeeError<A, B> {
+    First(A),
+    Second(B),
+}
+
+impl<A: fmt::Display, B: fmt::Display> fmt::Display for TeeError<A, B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TeeError::First(err) => err.fmt(f),
+            TeeError::Second(err) => err.fmt(f),
+        }
+    }
+}
+
+impl<A: std::error::Error + 'static, B: std::error::Error + 'static> std::error::Error for TeeError<A, B> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TeeError::First(err) => Some(err),
+            TeeError::Second(err) => Some(err),
+        }
+    }
+}
+
+impl<A: CwrHandler, B: CwrHandler> CwrHandler for TeeHandler<A, B>
+where
+    A::Error: 'static,
+    B::Error: 'static,
+{
+    type Error = TeeError<A::Error, B::Error>;
+
+    fn interested_in(&self) -> Option<&'static [&'static str]> {
+        match (self.first.interested_in(), self.second.interested_in()) {
+            (Some(first), Some(second)) if first == second => Some(first),
+            _ => None,
+        }
+    }
+
+    fn process_record(&mut self, record: ParsedRecord) -> Result<(), Self::Error> {
+        let record_type = record.record.record_type();
+        let first = is_interested(self.first.interested_in(), record_type);
+        let second = is_interested(self.second.interested_in(), record_type);
+        match (first, second) {
+            (true, true) => {
+                self.first.process_record(record.clone()).map_err(TeeError::First)?;
+                self.second.process_record(record).map_err(TeeError::Second)
+            }
+            (true, false) => self.first.process_record(record).map_err(TeeError::First),
+            (false, true) => self.second.process_record(record).map_err(TeeError::Second),
+            (false, false) => Ok(()),
+        }
+    }
+
+    fn handle_parse_error(&mut self, line_number: usize, error: &CwrParseError) -> Result<(), Self::Error> {
+        self.first.handle_parse_error(line_number, error).map_err(TeeError::First)?;
+        self.second.handle_parse_error(line_number, error).map_err(TeeError::Second)
+    }
+
+    fn handle_warnings(
+        &mut self, line_number: usize, record_type: &str, warnings: &[CwrWarning<'static>],
+    ) -> Result<(), Self::Error> {
+        if is_interested(self.first.interested_in(), record_type) {
+            self.first.handle_warnings(line_number, record_type, warnings).map_err(TeeError::First)?;
+        }
+        if is_interested(self.second.interested_in(), record_type) {
+            self.second.handle_warnings(line_number, record_type, warnings).map_err(TeeError::Second)?;
+        }
+        Ok(())
+    }
+
+    fn begin_group(&mut self, header: &ParsedRecord) -> Result<(), Self::Error> {
+        self.first.begin_group(header).map_err(TeeError::First)?;
+        self.second.begin_group(header).map_err(TeeError::Second)
+    }
+
+    fn end_group(&mut self) -> Result<(), Self::Error> {
+        self.first.end_group().map_err(TeeError::First)?;
+        self.second.end_group().map_err(TeeError::Second)
+    }
+
+    fn begin_transaction(&mut self, header: &ParsedRecord) -> Result<(), Self::Error> {
+        self.first.begin_transaction(header).map_err(TeeError::First)?;
+        self.second.begin_transaction(header).map_err(TeeError::Second)
+    }
+
+    fn end_transaction(&mut self) -> Result<(), Self::Error> {
+        self.first.end_transaction().map_err(TeeError::First)?;
+        self.second.end_transaction().map_err(TeeError::Second)
+    }
+
+    fn finalize(&mut self) -> Result<(), Self::Error> {
+        self.first.finalize().map_err(TeeError::First)?;
+        self.second.finalize().map_err(TeeError::Second)
+    }
+
+    fn get_report(&self) -> String {
+        format!("{}\n{}", self.first.get_report(), self.second.get_report())
+    }
+
+    fn report(&self) -> HandlerReport {
+        HandlerReport::Tee { first: Box::new(self.first.report()), second: Box::new(self.second.report()) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MetricsHandler, SummaryHandler};
+
+    #[test]
+    fn test_tee_handler_drives_both_handlers() {
+        let content = "HDRPB285606836WARNER CHAPPELL MUSIC PUBLISHING LTD         01.102022122112541120221221\n\
+                       GRHNWR0000102.100000000000  \nGRT000010000000000000002\nTRL000000010000000000000004\n";
+        let summary = crate::process_cwr_bytes(content.as_bytes(), SummaryHandler::new()).unwrap().report;
+        let metrics = crate::process_cwr_bytes(content.as_bytes(), MetricsHandler::new()).unwrap().report;
+
+        let handler = TeeHandler::new(SummaryHandler::new(), MetricsHandler::new());
+        let processed = crate::process_cwr_bytes(content.as_bytes(), handler).unwrap();
+        assert_eq!(processed.report, format!("{}\n{}", summary, metrics));
+        let HandlerReport::Tee { first, second } = processed.handler_report else {
+            panic!("expected a tee report");
+        };
+        assert!(matches!(*first, HandlerReport::Summary(summary) if summary.group_count == 1));
+        assert!(matches!(*second, HandlerReport::Metrics(metrics) if metrics.record_count == 4));
+    }
+}
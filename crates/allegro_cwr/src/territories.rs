@@ -0,0 +1,281 @@
+//! TIS territory expansion
+//!
+//! Expands TER/SPT/SWT include/exclude sequences into a concrete set of countries using the CISAC TIS
+//! hierarchy, and reports combinations that contradict each other (e.g. excluding a territory that was
+//! never included).
+
+use crate::domain_types::InclusionExclusionIndicator;
+use crate::lookups::tis_codes::territory_exists;
+use crate::lookups::tis_hierarchy::get_children_at;
+use crate::records::{SptRecord, SwtRecord, TerRecord};
+use chrono::NaiveDate;
+use std::collections::BTreeSet;
+
+/// TIS code for the whole world
+pub const WORLD: u16 = 2136;
+
+/// A single include/exclude entry in a territory sequence
+pub trait TerritoryEntry {
+    fn inclusion_exclusion_indicator(&self) -> &InclusionExclusionIndicator;
+    fn tis_code(&self) -> u16;
+}
+
+impl TerritoryEntry for TerRecord {
+    fn inclusion_exclusion_indicator(&self) -> &InclusionExclusionIndicator {
+        &self.inclusion_exclusion_indicator
+    }
+
+    fn tis_code(&self) -> u16 {
+        self.tis_numeric_code.0
+    }
+}
+
+impl TerritoryEntry for SptRecord {
+    fn inclusion_exclusion_indicator(&self) -> &InclusionExclusionIndicator {
+        &self.inclusion_exclusion_indicator
+    }
+
+    fn tis_code(&self) -> u16 {
+        self.tis_numeric_code.0
+    }
+}
+
+impl TerritoryEntry for SwtRecord {
+    fn inclusion_exclusion_indicator(&self) -> &InclusionExclusionIndicator {
+        &self.inclusion_exclusion_indicator
+    }
+
+    fn tis_code(&self) -> u16 {
+        self.tis_numeric_code.0
+    }
+}
+
+impl TerritoryEntry for (InclusionExclusionIndicator, u16) {
+    fn inclusion_exclusion_indicator(&self) -> &InclusionExclusionIndicator {
+        &self.0
+    }
+
+    fn tis_code(&self) -> u16 {
+        self.1
+    }
+}
+
+/// Problems detected while expanding a territory sequence. `index` is the position of the offending
+/// entry within the sequence.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TerritoryConflict {
+    /// TIS code does not exist in the territory table
+    UnknownTerritory { index: usize, tis_code: u16 },
+    /// Sequence starts with an exclusion, so there is nothing to exclude from
+    LeadingExclusion { index: usize, tis_code: u16 },
+    /// Exclusion removes no country that was previously included
+    IneffectiveExclusion { index: usize, tis_code: u16 },
+    /// Inclusion adds no country that was not already included
+    RedundantInclusion { index: usize, tis_code: u16 },
+    /// Inclusion re-adds countries removed by an earlier exclusion
+    IncludedAfterExclusion { index: usize, tis_code: u16, excluded_by: u16 },
+    /// The same TIS code is both included and excluded
+    IncludedAndExcluded { tis_code: u16 },
+}
+
+impl std::fmt::Display for TerritoryConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TerritoryConflict::UnknownTerritory { tis_code, .. } => {
+                write!(f, "TIS code {:04} not found in territory table", tis_code)
+            }
+            TerritoryConflict::LeadingExclusion { tis_code, .. } => {
+                write!(f, "Territory sequence starts with exclusion of {:04}", tis_code)
+            }
+            TerritoryConflict::IneffectiveExclusion { tis_code, .. } => {
+                write!(f, "Exclusion of {:04} does not remove any included territory", tis_code)
+            }
+            TerritoryConflict::RedundantInclusion { tis_code, .. } => {
+                write!(f, "Inclusion of {:04} is already covered by earlier inclusions", tis_code)
+            }
+            TerritoryConflict::IncludedAfterExclusion { tis_code, excluded_by, .. } => {
+                write!(f, "Inclusion of {:04} re-adds territories excluded by {:04}", tis_code, excluded_by)
+            }
+            TerritoryConflict::IncludedAndExcluded { tis_code } => {
+                write!(f, "TIS code {:04} is both included and excluded", tis_code)
+            }
+        }
+    }
+}
+
+/// Result of expanding a territory sequence
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TerritoryExpansion {
+    /// Concrete (leaf) TIS codes covered by the sequence
+    pub countries: BTreeSet<u16>,
+    pub conflicts: Vec<TerritoryConflict>,
+}
+
+impl TerritoryExpansion {
+    pub fn contains(&self, tis_code: u16) -> bool {
+        self.countries.contains(&tis_code)
+    }
+
+    pub fn has_conflicts(&self) -> bool {
+        !self.conflicts.is_empty()
+    }
+}
+
+/// Expands a single TIS code into the countries it covers today
+pub fn expand_territory(tis_code: u16) -> BTreeSet<u16> {
+    expand_territory_at(tis_code, today())
+}
+
+/// Expands a single TIS code into the countries it covers on the given date.
+/// Codes that are not groups (i.e. countries) expand to themselves.
+pub fn expand_territory_at(tis_code: u16, date: NaiveDate) -> BTreeSet<u16> {
+    let mut countries = BTreeSet::new();
+    let mut pending = vec![tis_code];
+    let mut visited = BTreeSet::new();
+
+    while let Some(code) = pending.pop() {
+        if !visited.insert(code) {
+            continue;
+        }
+        let children = get_children_at(code, date);
+        if children.is_empty() {
+            countries.insert(code);
+        } else {
+            pending.extend(children);
+        }
+    }
+
+    countries
+}
+
+/// Checks whether `outer` covers every country of `inner` today
+pub fn territory_contains(outer: u16, inner: u16) -> bool {
+    let outer_countries = expand_territory(outer);
+    expand_territory(inner).is_subset(&outer_countries)
+}
+
+/// Expands an ordered include/exclude sequence (as found in TER, SPT or SWT records) into countries,
+/// evaluated today
+pub fn expand_sequence<T: TerritoryEntry>(entries: &[T]) -> TerritoryExpansion {
+    expand_sequence_at(entries, today())
+}
+
+/// Expands an ordered include/exclude sequence into countries, evaluated on the given date.
+///
+/// Entries are applied in order: inclusions add the countries of their territory, exclusions remove
+/// them. Any contradictory combinations are reported in `conflicts`; the resulting country set is still
+/// computed so callers can decide how strict to be.
+pub fn expand_sequence_at<T: TerritoryEntry>(entries: &[T], date: NaiveDate) -> TerritoryExpansion {
+    let mut expansion = TerritoryExpansion::default();
+    let mut excluded: Vec<(u16, BTreeSet<u16>)> = Vec::new();
+    let mut included_codes = BTreeSet::new();
+
+    for (index, entry) in entries.iter().enumerate() {
+        let tis_code = entry.tis_code();
+        if !territory_exists(tis_code) {
+            expansion.conflicts.push(TerritoryConflict::UnknownTerritory { index, tis_code });
+            continue;
+        }
+
+        let countries = expand_territory_at(tis_code, date);
+        match entry.inclusion_exclusion_indicator() {
+            InclusionExclusionIndicator::Included => {
+                included_codes.insert(tis_code);
+                if let Some((excluded_by, _)) = excluded.iter().find(|(_, set)| !set.is_disjoint(&countries)) {
+                    expansion.conflicts.push(TerritoryConflict::IncludedAfterExclusion {
+                        index,
+                        tis_code,
+                        excluded_by: *excluded_by,
+                    });
+                } else if countries.is_subset(&expansion.countries) {
+                    expansion.conflicts.push(TerritoryConflict::RedundantInclusion { index, tis_code });
+                }
+                expansion.countries.extend(countries);
+            }
+            InclusionExclusionIndicator::Excluded => {
+                if index == 0 {
+                    expansion.conflicts.push(TerritoryConflict::LeadingExclusion { index, tis_code });
+                } else if expansion.countries.is_disjoint(&countries) {
+                    expansion.conflicts.push(TerritoryConflict::IneffectiveExclusion { index, tis_code });
+                }
+                expansion.countries.retain(|c| !countries.contains(c));
+                excluded.push((tis_code, countries));
+            }
+            // Already reported when the indicator was parsed; neither adds nor removes countries
+            InclusionExclusionIndicator::Unknown(_) => {}
+        }
+    }
+
+    for (tis_code, _) in &excluded {
+        if included_codes.contains(tis_code) {
+            expansion.conflicts.push(TerritoryConflict::IncludedAndExcluded { tis_code: *tis_code });
+        }
+    }
+
+    expansion
+}
+
+fn today() -> NaiveDate {
+    chrono::Utc::now().date_naive()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use InclusionExclusionIndicator::{Excluded, Included};
+
+    const USA: u16 = 840;
+    const CANADA: u16 = 124;
+    const NORTH_AMERICA: u16 = 2129;
+
+    #[test]
+    fn test_country_expands_to_itself() {
+        assert_eq!(expand_territory(USA), BTreeSet::from([USA]));
+    }
+
+    #[test]
+    fn test_world_contains_countries() {
+        let world = expand_territory(WORLD);
+        assert!(world.contains(&USA));
+        assert!(world.contains(&CANADA));
+        assert!(!world.contains(&WORLD));
+        assert!(world.len() > 200);
+    }
+
+    #[test]
+    fn test_world_excluding_usa() {
+        let expansion = expand_sequence(&[(Included, WORLD), (Excluded, USA)]);
+        assert!(!expansion.contains(USA));
+        assert!(expansion.contains(CANADA));
+        assert!(!expansion.has_conflicts());
+    }
+
+    #[test]
+    fn test_expired_hierarchy_link_respected() {
+        // East Germany (278) left Eastern Europe (2119) in 1990
+        let before = NaiveDate::from_ymd_opt(1985, 1, 1).unwrap();
+        let after = NaiveDate::from_ymd_opt(2000, 1, 1).unwrap();
+        assert!(expand_territory_at(2119, before).contains(&278));
+        assert!(!expand_territory_at(2119, after).contains(&278));
+    }
+
+    #[test]
+    fn test_detects_conflicts() {
+        let expansion = expand_sequence(&[(Excluded, USA), (Included, NORTH_AMERICA), (Included, CANADA)]);
+        assert!(expansion.conflicts.contains(&TerritoryConflict::LeadingExclusion { index: 0, tis_code: USA }));
+        assert!(expansion.conflicts.contains(&TerritoryConflict::IncludedAfterExclusion {
+            index: 1,
+            tis_code: NORTH_AMERICA,
+            excluded_by: USA
+        }));
+        assert!(expansion.conflicts.contains(&TerritoryConflict::RedundantInclusion { index: 2, tis_code: CANADA }));
+    }
+
+    #[test]
+    fn test_detects_ineffective_exclusion_and_unknown_code() {
+        let expansion = expand_sequence(&[(Included, CANADA), (Excluded, USA), (Included, 9999)]);
+        assert!(expansion.conflicts.contains(&TerritoryConflict::IneffectiveExclusion { index: 1, tis_code: USA }));
+        assert!(expansion.conflicts.contains(&TerritoryConflict::UnknownTerritory { index: 2, tis_code: 9999 }));
+        assert_eq!(expansion.countries, BTreeSet::from([CANADA]));
+    }
+}
@@ -0,0 +1,327 @@
+//! Writes a CWR transmission with regenerated control records and sequence numbers
+//!
+//! Records are written as they arrive: the HDR first, then each transaction's records after
+//! [`TransmissionWriter::start_transaction`]. Groups, GRT and TRL records and all sequence numbers are generated.
+
+use crate::ascii_io::AsciiWriter;
+use crate::cwr_registry::CwrRegistry;
+use crate::domain_types::{CharacterSet, CwrVersion, GroupCount, GroupId, RecordCount, TransactionCount};
+use crate::error::CwrParseError;
+use crate::parsing::{FieldOverflow, FieldTruncation, UnsupportedRecord};
+use crate::records::{GrhRecord, GrtRecord, HdrRecord, TrlRecord};
+use std::io::Write;
+
+/// Control totals of a written transmission, as they appear in its TRL record
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TransmissionTotals {
+    pub groups: u32,
+    pub transactions: u32,
+    /// All records including HDR and TRL
+    pub records: u32,
+}
+
+struct GroupState {
+    source_group_id: u32,
+    group_id: u32,
+    transactions: u32,
+    records: u32,
+}
+
+pub struct TransmissionWriter<W: Write> {
+    writer: AsciiWriter<W>,
+    version: CwrVersion,
+    group: Option<GroupState>,
+    groups: u32,
+    transactions: usize,
+    records: u32,
+    record_sequence: u32,
+    overflow: FieldOverflow,
+    truncations: Vec<FieldTruncation>,
+    unsupported: UnsupportedRecord,
+    dropped: Vec<String>,
+}
+
+impl<W: Write> TransmissionWriter<W> {
+    /// Writes `hdr` and returns a writer ready for the first transaction
+    pub fn start(
+        writer: W, hdr: &HdrRecord, version: CwrVersion, character_set: CharacterSet,
+    ) -> Result<Self, CwrParseError> {
+        let mut transmission = TransmissionWriter {
+            writer: AsciiWriter::with_character_set(writer, Some(character_set)),
+            version,
+            group: None,
+            groups: 0,
+            transactions: 0,
+            records: 0,
+            record_sequence: 0,
+            overflow: FieldOverflow::default(),
+            truncations: Vec::new(),
+            unsupported: UnsupportedRecord::default(),
+            dropped: Vec::new(),
+        };
+        transmission.write(&CwrRegistry::Hdr(hdr.clone()))?;
+        Ok(transmission)
+    }
+
+    /// How values longer than their field are handled from now on (truncated by default)
+    pub fn with_field_overflow(mut self, overflow: FieldOverflow) -> Self {
+        self.overflow = overflow;
+        self
+    }
+
+    /// What happens to detail records whose type does not exist in the version being written (refused by default)
+    pub fn with_unsupported_records(mut self, unsupported: UnsupportedRecord) -> Self {
+        self.unsupported = unsupported;
+        self
+    }
+
+    /// Record types of the detail records left out so far with [`UnsupportedRecord::Drop`], one per record
+    pub fn dropped_records(&self) -> &[String] {
+        &self.dropped
+    }
+
+    /// Values truncated to fit their field so far
+    pub fn truncations(&self) -> &[FieldTruncation] {
+        &self.truncations
+    }
+
+    /// Transactions started so far
+    pub fn transaction_count(&self) -> usize {
+        self.transactions
+    }
+
+    /// True once a transaction has been started and the group has not been closed yet
+    pub fn in_group(&self) -> bool {
+        self.group.is_some()
+    }
+
+    /// Starts a new transaction, opening a new group when the source group changes. `source_group_id` only has
+    /// to distinguish groups; the written groups are numbered from 1.
+    pub fn start_transaction(&mut self, source_group_id: u32, grh: &GrhRecord) -> Result<(), CwrParseError> {
+        if self.group.as_ref().is_none_or(|g| g.source_group_id != source_group_id) {
+            self.close_group()?;
+            self.groups += 1;
+            let mut grh = grh.clone();
+            grh.group_id = GroupId(self.groups);
+            self.write(&CwrRegistry::Grh(grh))?;
+            self.group = Some(GroupState { source_group_id, group_id: self.groups, transactions: 0, records: 1 });
+        }
+        if let Some(group) = self.group.as_mut() {
+            group.transactions += 1;
+        }
+        self.transactions += 1;
+        self.record_sequence = 0;
+        Ok(())
+    }
+
+    /// Writes a detail record, renumbering its transaction and record sequence numbers. A record type that does
+    /// not exist in the version being written is refused or left out, as set by [`Self::with_unsupported_records`].
+    pub fn write_detail(&mut self, record: &mut CwrRegistry) -> Result<(), CwrParseError> {
+        if let Err(e) = record.check_version(self.version.0) {
+            if self.unsupported == UnsupportedRecord::Error {
+                return Err(e);
+            }
+            tracing::warn!("{}, record left out", e);
+            self.dropped.push(record.record_type().to_string());
+            return Ok(());
+        }
+        let transaction_sequence = self.group.as_ref().map_or(0, |g| g.transactions.saturating_sub(1));
+        record.set_sequence_numbers(transaction_sequence, self.record_sequence);
+        self.record_sequence += 1;
+        if let Some(group) = self.group.as_mut() {
+            group.records += 1;
+        }
+        self.write(record)
+    }
+
+    fn close_group(&mut self) -> Result<(), CwrParseError> {
+        if let Some(group) = self.group.take() {
+            let grt = GrtRecord {
+                record_type: crate::parsing::intern("GRT"),
+                group_id: GroupId(group.group_id),
+                transaction_count: TransactionCount(group.transactions),
+                record_count: RecordCount(group.records + 1),
+                currency_indicator: None,
+                total_monetary_value: None,
+            };
+            self.write(&CwrRegistry::Grt(grt))?;
+        }
+        Ok(())
+    }
+
+    /// Closes any open group and writes the TRL with regenerated totals
+    pub fn finish(mut self) -> Result<TransmissionTotals, CwrParseError> {
+        self.close_group()?;
+        let totals = TransmissionTotals {
+            groups: self.groups,
+            transactions: self.transactions as u32,
+            records: self.records + 1,
+        };
+        let trl = TrlRecord {
+            record_type: crate::parsing::intern("TRL"),
+            group_count: GroupCount(totals.groups),
+            transaction_count: TransactionCount(totals.transactions),
+            record_count: RecordCount(totals.records),
+        };
+        self.write(&CwrRegistry::Trl(trl))?;
+        self.writer.flush()?;
+        Ok(totals)
+    }
+
+    fn write(&mut self, record: &CwrRegistry) -> Result<(), CwrParseError> {
+        let truncated = self.truncations.len();
+        self.writer.write_record_with(record, &self.version, self.overflow, &mut self.truncations)?;
+        for truncation in &self.truncations[truncated..] {
+            tracing::warn!("{}", truncation);
+        }
+        self.records += 1;
+        Ok(())
+    }
+}
+
+/// Writes a whole transmission from its records: the HDR first, then each group's GRH followed by its
+/// transactions. A record of the group's transaction type starts a new transaction. GRT and TRL records in the
+/// input are ignored; they are written with counts computed from the records actually written, and all sequence
+/// numbers are regenerated. Groups without transactions are left out. Records whose type does not exist in
+/// `version` are refused; see [`write_cwr_file_with`] to leave them out instead.
+pub fn write_cwr_file<W: Write>(
+    records: impl IntoIterator<Item = CwrRegistry>, writer: W, version: CwrVersion, character_set: CharacterSet,
+) -> Result<TransmissionTotals, CwrParseError> {
+    write_cwr_file_with(records, writer, version, character_set, UnsupportedRecord::Error)
+}
+
+/// Like [`write_cwr_file`], handling records whose type does not exist in `version` as `unsupported` says
+pub fn write_cwr_file_with<W: Write>(
+    records: impl IntoIterator<Item = CwrRegistry>, writer: W, version: CwrVersion, character_set: CharacterSet,
+    unsupported: UnsupportedRecord,
+) -> Result<TransmissionTotals, CwrParseError> {
+    let mut records = records.into_iter();
+    let hdr = match records.next() {
+        Some(CwrRegistry::Hdr(hdr)) => hdr,
+        Some(record) => {
+            return Err(CwrParseError::BadFormat(format!(
+                "Transmission must start with an HDR record, found {}",
+                record.record_type()
+            )));
+        }
+        None => return Err(CwrParseError::BadFormat("Transmission has no records".to_string())),
+    };
+
+    let mut transmission =
+        TransmissionWriter::start(writer, &hdr, version, character_set)?.with_unsupported_records(unsupported);
+    let mut group: Option<(u32, GrhRecord)> = None;
+    let mut groups_seen = 0;
+    let mut in_transaction = false;
+    for mut record in records {
+        match record {
+            CwrRegistry::Hdr(_) => {
+                return Err(CwrParseError::BadFormat("Transmission has more than one HDR record".to_string()));
+            }
+            CwrRegistry::Grh(grh) => {
+                groups_seen += 1;
+                group = Some((groups_seen, grh));
+                in_transaction = false;
+            }
+            CwrRegistry::Grt(_) | CwrRegistry::Trl(_) => {}
+            _ => {
+                let Some((group_id, grh)) = group.as_ref() else {
+                    return Err(CwrParseError::BadFormat(format!(
+                        "{} record outside of a group",
+                        record.record_type()
+                    )));
+                };
+                if record.record_type() == grh.transaction_type.as_str() {
+                    transmission.start_transaction(*group_id, grh)?;
+                    in_transaction = true;
+                } else if !in_transaction {
+                    return Err(CwrParseError::BadFormat(format!(
+                        "{} record before the first transaction header",
+                        record.record_type()
+                    )));
+                }
+                transmission.write_detail(&mut record)?;
+            }
+        }
+    }
+    transmission.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::{PublisherBuilder, Shares, WorkBuilder, WriterBuilder};
+
+    const HDR: &str = "HDRPB285606836WARNER CHAPPELL MUSIC PUBLISHING LTD         01.102022122112541120221221";
+
+    fn record(line: &str) -> CwrRegistry {
+        crate::cwr_registry::parse_by_record_type(&line[0..3], line).unwrap().0
+    }
+
+    #[test]
+    fn test_write_cwr_file_computes_totals() {
+        let mut records = vec![record(HDR), record("GRHNWR0000102.100000000000  ")];
+        for (index, title) in ["FIRST WORK", "SECOND WORK"].into_iter().enumerate() {
+            let work = WorkBuilder::new(title, &format!("SW{:010}", index))
+                .publisher(PublisherBuilder::new("000000001", "ACME MUSIC").ownership(Shares::new(5000, 0, 0)))
+                .writer(WriterBuilder::new("000000002", "SMITH").ownership(Shares::new(5000, 0, 0)));
+            records.extend(work.build(0).unwrap());
+        }
+        // Stale control records are replaced
+        records.push(record("GRT000010000009900000999"));
+
+        let mut output = Vec::new();
+        let totals = write_cwr_file(records, &mut output, CwrVersion(2.1), CharacterSet::ASCII).unwrap();
+        assert_eq!(totals, TransmissionTotals { groups: 1, transactions: 2, records: 10 });
+
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 10);
+        assert!(lines[5].starts_with("NWR0000000100000000SECOND WORK"), "{}", lines[5]);
+        assert!(lines[7].starts_with("SWR0000000100000002"), "{}", lines[7]);
+        assert!(lines[8].starts_with("GRT000010000000200000008"), "{}", lines[8]);
+        assert!(lines[9].starts_with("TRL000010000000200000010"), "{}", lines[9]);
+    }
+
+    #[test]
+    fn test_write_cwr_file_requires_hdr() {
+        let result = write_cwr_file(
+            vec![record("GRHNWR0000102.100000000000  ")],
+            Vec::new(),
+            CwrVersion(2.1),
+            CharacterSet::ASCII,
+        );
+        assert!(matches!(result, Err(CwrParseError::BadFormat(_))));
+    }
+
+    #[test]
+    fn test_unsupported_records() {
+        let mut records = vec![record(HDR), record("GRHNWR0000102.100000000000  ")];
+        let work = WorkBuilder::new("FIRST WORK", "SW0000000001")
+            .publisher(PublisherBuilder::new("000000001", "ACME MUSIC").ownership(Shares::new(5000, 0, 0)))
+            .writer(WriterBuilder::new("000000002", "SMITH").ownership(Shares::new(5000, 0, 0)));
+        records.extend(work.build(0).unwrap());
+        records.push(record("XRF0000000100000001ISWT1234567890123WY"));
+
+        let result = write_cwr_file(records.clone(), Vec::new(), CwrVersion(2.1), CharacterSet::ASCII);
+        let err = result.unwrap_err();
+        assert_eq!(err.to_string(), "XRF record is not valid in CWR version 2.1 (requires version 2.2 or later)");
+
+        let mut output = Vec::new();
+        let totals = write_cwr_file_with(
+            records.clone(),
+            &mut output,
+            CwrVersion(2.1),
+            CharacterSet::ASCII,
+            UnsupportedRecord::Drop,
+        )
+        .unwrap();
+        assert_eq!(totals, TransmissionTotals { groups: 1, transactions: 1, records: 7 });
+        assert!(!String::from_utf8(output).unwrap().contains("XRF"));
+
+        let totals = write_cwr_file(records, Vec::new(), CwrVersion(2.2), CharacterSet::ASCII).unwrap();
+        assert_eq!(totals.records, 8);
+
+        let mut writer = crate::AsciiWriter::new(Vec::new());
+        assert!(writer.write_record(&record("XRF0000000100000001ISWT1234567890123WY"), &CwrVersion(2.1)).is_err());
+    }
+}
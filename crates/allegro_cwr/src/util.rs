@@ -8,7 +8,7 @@ pub fn format_int_with_commas(num: i64) -> String {
     for (i, c) in s.chars().enumerate() {
         result.push(c);
         let pos = len - 1 - i;
-        if pos > 0 && pos % 3 == 0 {
+        if pos > 0 && pos.is_multiple_of(3) {
             result.push(',');
         }
     }
@@ -53,101 +53,165 @@ pub fn extract_version_from_filename(filename: &str) -> Option<f32> {
     }
 }
 
+/// Where a CWR version was read from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum VersionSource {
+    /// The version given by the caller (e.g. `--cwr`)
+    Hint,
+    /// The `.Vxx` extension of the filename
+    Filename,
+    /// The HDR version field (CWR 2.2+)
+    HdrVersion,
+    /// The HDR EDI standard version, `01.10` for every CWR 2.x version
+    HdrEdiVersion,
+    /// The GRH version number
+    GrhVersion,
+    /// The length of the HDR record, when nothing else names a version
+    #[default]
+    HdrLength,
+}
+
+impl VersionSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VersionSource::Hint => "version option",
+            VersionSource::Filename => "filename",
+            VersionSource::HdrVersion => "HDR version field",
+            VersionSource::HdrEdiVersion => "HDR EDI standard version",
+            VersionSource::GrhVersion => "GRH version number",
+            VersionSource::HdrLength => "HDR record length",
+        }
+    }
+}
+
+/// The EDI standard version every CWR 2.x header declares
+const EDI_STANDARD_VERSION: &str = "01.10";
+
+/// Every version a file declares, and the one chosen for parsing
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VersionDetection {
+    /// Each version found with where it came from, as written (EDI version) or as `2.1`
+    pub detected: Vec<(VersionSource, String)>,
+    pub chosen: f32,
+    pub chosen_from: VersionSource,
+}
+
+impl VersionDetection {
+    /// True when the sources name different CWR versions or the EDI standard version is not `01.10`
+    pub fn has_conflict(&self) -> bool {
+        let mut versions = self.detected.iter().filter(|(source, _)| *source != VersionSource::HdrEdiVersion);
+        let first = versions.next().map(|(_, version)| version);
+        versions.any(|(_, version)| Some(version) != first)
+            || self
+                .detected
+                .iter()
+                .any(|(source, version)| *source == VersionSource::HdrEdiVersion && version != EDI_STANDARD_VERSION)
+    }
+
+    /// A copy with one more detected version, e.g. from a GRH record
+    pub fn with_source(&self, source: VersionSource, version: f32) -> Self {
+        let mut detection = self.clone();
+        detection.detected.push((source, format!("{:.1}", version)));
+        detection
+    }
+}
+
+impl std::fmt::Display for VersionDetection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let detected: Vec<String> =
+            self.detected.iter().map(|(source, version)| format!("{} {}", source.as_str(), version)).collect();
+        write!(
+            f,
+            "CWR versions disagree ({}); parsing as {:.1} from the {}",
+            detected.join(", "),
+            self.chosen,
+            self.chosen_from.as_str()
+        )
+    }
+}
+
+/// Determines the CWR version with precedence: hint > filename > HDR version field > HDR length
 pub fn get_cwr_version(filename: &str, hdr_line: &str, cli_version: Option<f32>) -> Result<f32, CwrParseError> {
-    use log::{info, warn};
+    detect_cwr_version(filename, hdr_line, cli_version).map(|detection| detection.chosen)
+}
 
-    // Try to detect version from HDR line using enhanced heuristics
-    let hdr_detected_version = detect_version_from_hdr(hdr_line)?;
+/// Like [`get_cwr_version`], also reporting every version the hint, filename and HDR record name
+pub fn detect_cwr_version(
+    filename: &str, hdr_line: &str, cli_version: Option<f32>,
+) -> Result<VersionDetection, CwrParseError> {
+    use tracing::{info, warn};
 
-    // Try to extract version from filename
+    let hdr_version = detect_version_from_hdr(hdr_line)?;
     let filename_version = extract_version_from_filename(filename);
+    let edi_version = hdr_line.get(59..64).map(str::trim).filter(|version| !version.is_empty());
 
-    // Determine final version with precedence: CLI > filename > HDR explicit > HDR heuristics
-    match (cli_version, filename_version, hdr_detected_version) {
-        // CLI version specified
-        (Some(cli_ver), Some(filename_ver), _) => {
-            if cli_ver != filename_ver {
-                info!(
-                    "CLI specified CWR version {} but filename suggests {}. Using CLI version {}",
-                    cli_ver, filename_ver, cli_ver
-                );
-            }
-            Ok(cli_ver)
-        }
-        (Some(cli_ver), None, Some(hdr_ver)) => {
-            if cli_ver != hdr_ver {
-                warn!(
-                    "CLI specified CWR version {} but file contains explicit version {}. Using CLI version {}",
-                    cli_ver, hdr_ver, cli_ver
-                );
-            }
-            Ok(cli_ver)
-        }
-        (Some(cli_ver), None, None) => Ok(cli_ver),
-
-        // No CLI version - use filename version if available
-        (None, Some(filename_ver), Some(hdr_ver)) => {
-            if filename_ver != hdr_ver {
-                info!(
-                    "Filename suggests CWR version {} but file contains explicit version {}. Using filename version {}",
-                    filename_ver, hdr_ver, filename_ver
-                );
-            }
-            Ok(filename_ver)
-        }
-        (None, Some(filename_ver), None) => {
-            info!("Detected CWR version {} from filename", filename_ver);
-            Ok(filename_ver)
+    let mut detection = VersionDetection::default();
+    for (source, version) in [
+        (VersionSource::Hint, cli_version),
+        (VersionSource::Filename, filename_version),
+        (VersionSource::HdrVersion, hdr_version),
+    ] {
+        if let Some(version) = version {
+            detection.detected.push((source, format!("{:.1}", version)));
         }
+    }
+    if let Some(edi_version) = edi_version {
+        detection.detected.push((VersionSource::HdrEdiVersion, edi_version.to_string()));
+    }
 
-        // No CLI or filename version - use HDR version if available
-        (None, None, Some(hdr_ver)) => Ok(hdr_ver),
-
-        // Fall back to heuristics
-        (None, None, None) => {
+    let chosen = [
+        (VersionSource::Hint, cli_version),
+        (VersionSource::Filename, filename_version),
+        (VersionSource::HdrVersion, hdr_version),
+    ]
+    .into_iter()
+    .find_map(|(source, version)| version.map(|version| (source, version)));
+    (detection.chosen_from, detection.chosen) = match chosen {
+        Some(chosen) => chosen,
+        None => {
             let heuristic_version = detect_version_by_heuristics(hdr_line);
             info!("Auto-detected CWR version: {}", heuristic_version);
-            Ok(heuristic_version)
+            detection.detected.push((VersionSource::HdrLength, format!("{:.1}", heuristic_version)));
+            (VersionSource::HdrLength, heuristic_version)
         }
+    };
+
+    if detection.has_conflict() {
+        warn!("{}", detection);
     }
+    Ok(detection)
 }
 
 fn detect_version_from_hdr(hdr_line: &str) -> Result<Option<f32>, CwrParseError> {
     // Check for explicit version field at position 101-104 (CWR 2.2+)
-    if hdr_line.len() > 104 {
-        if let Some(version_str) = hdr_line.get(101..104) {
-            let trimmed = version_str.trim();
-            if !trimmed.is_empty() {
-                match trimmed.parse::<f32>() {
-                    Ok(version) => {
-                        if [2.0, 2.1, 2.2].contains(&version) {
-                            return Ok(Some(version));
-                        } else {
-                            return Err(CwrParseError::BadFormat(format!(
-                                "Invalid CWR version in header: {}",
-                                version
-                            )));
-                        }
-                    }
-                    Err(_) => {
-                        return Err(CwrParseError::BadFormat(format!(
-                            "Invalid CWR version format in header: {}",
-                            trimmed
-                        )));
+    if hdr_line.len() > 104
+        && let Some(version_str) = hdr_line.get(101..104)
+    {
+        let trimmed = version_str.trim();
+        if !trimmed.is_empty() {
+            match trimmed.parse::<f32>() {
+                Ok(version) => {
+                    if [2.0, 2.1, 2.2].contains(&version) {
+                        return Ok(Some(version));
+                    } else {
+                        return Err(CwrParseError::BadFormat(format!("Invalid CWR version in header: {}", version)));
                     }
                 }
+                Err(_) => {
+                    return Err(CwrParseError::BadFormat(format!("Invalid CWR version format in header: {}", trimmed)));
+                }
             }
         }
     }
 
     // Check for character set field presence (positions 87-89, indicates 2.1+)
-    if hdr_line.len() >= 89 {
-        if let Some(charset_field) = hdr_line.get(87..89) {
-            let trimmed = charset_field.trim();
-            if !trimmed.is_empty() {
-                // Character set field present suggests 2.1+, but we can't distinguish 2.1 from 2.2 without explicit version
-                return Ok(None); // Let heuristics handle it
-            }
+    if hdr_line.len() >= 89
+        && let Some(charset_field) = hdr_line.get(87..89)
+    {
+        let trimmed = charset_field.trim();
+        if !trimmed.is_empty() {
+            // Character set field present suggests 2.1+, but we can't distinguish 2.1 from 2.2 without explicit version
+            return Ok(None); // Let heuristics handle it
         }
     }
 
@@ -12,7 +12,7 @@ use allegro_cwr::records::pwr::PwrRecord;
 #[test]
 fn test_pwr_record_has_crlf_ending() {
     let pwr = PwrRecord {
-        record_type: "PWR".to_string(),
+        record_type: "PWR".into(),
         transaction_sequence_num: Number(1),
         record_sequence_num: Number(2),
         publisher_ip_num: Some("TESTPUB  ".to_string()),
@@ -93,7 +93,7 @@ fn test_nwr_record_has_crlf_ending() {
 fn test_multiple_records_all_have_crlf_endings() {
     // Test that multiple different record types all produce CRLF endings
     let pwr = PwrRecord {
-        record_type: "PWR".to_string(),
+        record_type: "PWR".into(),
         transaction_sequence_num: Number(1),
         record_sequence_num: Number(2),
         publisher_ip_num: Some("TESTPUB  ".to_string()),
@@ -136,7 +136,7 @@ fn test_ascii_field_still_validates() {
 
     // The record type field should still be ASCII-only validated
     // (though this test data doesn't have non-ASCII in the record type field)
-    assert_eq!(record.record_type, "NPN");
+    assert_eq!(&*record.record_type, "NPN");
     assert_eq!(record.publisher_name.as_str().trim(), "NORMAL PUBLISHER NAME");
 }
 
@@ -229,3 +229,25 @@ fn test_nwn_multibyte_field_alignment_bug() {
     let critical_warnings: Vec<_> = warnings.iter().filter(|w| w.is_critical()).collect();
     assert!(critical_warnings.is_empty(), "NWN should not have critical warnings");
 }
+
+#[test]
+fn test_nwn_big5_round_trip_is_byte_exact() {
+    use allegro_cwr::domain_types::CharacterSet;
+    use allegro_cwr::parsing::CwrLine;
+
+    // Writer last name 作曲家 is six bytes in Big5; the language code must still be read from byte 348
+    let mut line = b"NWN000000740000000627976    ".to_vec();
+    line.extend_from_slice(b"\xA7\x40\xA6\xB1\xAE\x61");
+    line.extend_from_slice(&[b' '; 154]);
+    line.extend_from_slice(&[b' '; 160]);
+    line.extend_from_slice(b"ZH");
+
+    let character_set = CharacterSet::TraditionalBig5;
+    let (record, warnings) = NwnRecord::parse_line(&CwrLine::with_character_set(&line, &character_set), None);
+    assert!(warnings.iter().all(|w| !w.is_critical()));
+    assert_eq!(record.writer_last_name.as_str().trim(), "作曲家");
+    assert_eq!(record.language_code.as_ref().map(|l| l.as_str()), Some("ZH"));
+
+    let serialized = record.to_cwr_record_bytes(&CwrVersion(2.1), &character_set);
+    assert_eq!(serialized, line);
+}
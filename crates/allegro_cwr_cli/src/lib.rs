@@ -1,15 +1,35 @@
 use std::fs;
-use std::io::{self, Read};
+use std::io::{self, Read, Write};
+use std::path::Path;
 use std::process;
-use std::time::Instant;
+use std::time::Duration;
 
-use log::{error, info};
+use tracing::info;
+
+#[macro_use]
+pub mod logging;
+pub mod watch;
+
+#[doc(hidden)]
+pub use tracing;
+
+use logging::CliLayer;
+pub use logging::{check_log_format, LogFormat, LogOptions, Verbosity, STATUS_TARGET};
+
+/// Input filename that stands for stdin, e.g. `curl ... | allegro validate -`
+pub const STDIN_FILENAME: &str = "-";
+
+/// Output filename that stands for stdout, e.g. `cwr-obfuscate -o - input.V22 | cwr-json`
+pub const STDOUT_FILENAME: &str = "-";
 
 #[derive(Default)]
 pub struct BaseConfig {
     pub input_files: Vec<String>,
     pub cwr_version: Option<f32>,
     pub read_stdin: bool,
+    /// Drop directory to watch instead of processing the input files once
+    pub watch_dir: Option<String>,
+    pub poll_interval: Option<Duration>,
 }
 
 impl BaseConfig {
@@ -30,17 +50,225 @@ impl BaseConfig {
         Ok(())
     }
 
-    pub fn add_input_file(&mut self, file: String) {
-        self.input_files.push(file);
+    /// Adds an input argument, expanding directories and `*`/`?` patterns into the CWR files they contain.
+    /// [`STDIN_FILENAME`] reads stdin, and cannot be combined with other input files.
+    pub fn add_input_file(&mut self, file: String) -> Result<(), String> {
+        if file == STDIN_FILENAME || self.read_stdin {
+            if self.read_stdin || !self.input_files.is_empty() {
+                return Err(format!("'{}' (stdin) cannot be combined with other input files", STDIN_FILENAME));
+            }
+            self.read_stdin = true;
+            return Ok(());
+        }
+        self.input_files.extend(expand_input_path(&file)?);
+        Ok(())
+    }
+
+    pub fn set_poll_interval(&mut self, seconds_str: &str) -> Result<(), String> {
+        let seconds: f64 =
+            seconds_str.parse().ok().filter(|seconds: &f64| seconds.is_finite() && *seconds > 0.0).ok_or_else(
+                || format!("Invalid poll interval '{}'. Must be a positive number of seconds", seconds_str),
+            )?;
+        self.poll_interval = Some(Duration::from_secs_f64(seconds));
+        Ok(())
     }
 
     pub fn finalize(&mut self) {
-        if self.input_files.is_empty() {
+        if self.input_files.is_empty() && self.watch_dir.is_none() {
             self.read_stdin = true;
         }
     }
 }
 
+/// Which way a bidirectional converter may run; `Auto` picks it from the input's content
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Direction {
+    #[default]
+    Auto,
+    /// Only accept CWR input
+    FromCwr,
+    /// Only accept the converter's other format as input, writing CWR
+    ToCwr,
+}
+
+impl Direction {
+    /// Fails when the detected input format does not match the requested direction
+    pub fn check(&self, is_cwr: bool, other_format: &str) -> Result<(), String> {
+        match (self, is_cwr) {
+            (Direction::FromCwr, false) => Err(format!("Expected a CWR file, found {} input", other_format)),
+            (Direction::ToCwr, true) => Err(format!("Expected {} input, found a CWR file", other_format)),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Returns true for CWR file extensions such as `.V21` or `.v22`, also when gzipped (`.V21.gz`)
+pub(crate) fn has_cwr_extension(path: &Path) -> bool {
+    let path = match (path.extension(), path.file_stem()) {
+        (Some(ext), Some(stem)) if ext.eq_ignore_ascii_case("gz") => Path::new(stem),
+        _ => path,
+    };
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.len() > 1 && ext.starts_with(['V', 'v']) && ext[1..].chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Matches a file name against a pattern where `*` matches any run of characters and `?` a single character
+fn matches_pattern(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    let mut backtrack = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c.eq_ignore_ascii_case(&name[n]) => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    backtrack = Some((star, matched + 1));
+                    p = star + 1;
+                    n = matched + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Expands a directory into its CWR files, or a pattern in the last path component into the matching files.
+/// Other arguments are returned unchanged. Results are sorted so batch output is stable.
+pub fn expand_input_path(arg: &str) -> Result<Vec<String>, String> {
+    let path = Path::new(arg);
+    let (dir, pattern) = if path.is_dir() {
+        (path, None)
+    } else {
+        match path.file_name().and_then(|name| name.to_str()) {
+            Some(name) if name.contains(['*', '?']) => {
+                let dir = path.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+                (dir, Some(name))
+            }
+            _ => return Ok(vec![arg.to_string()]),
+        }
+    };
+
+    let entries = fs::read_dir(dir).map_err(|e| format!("Cannot read directory '{}': {}", dir.display(), e))?;
+    let mut files: Vec<String> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|file| file.is_file())
+        .filter(|file| match pattern {
+            Some(pattern) => {
+                file.file_name().and_then(|name| name.to_str()).is_some_and(|name| matches_pattern(pattern, name))
+            }
+            None => has_cwr_extension(file),
+        })
+        .map(|file| file.to_string_lossy().to_string())
+        .collect();
+    if files.is_empty() {
+        return Err(format!("No CWR files found for '{}'", arg));
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Per-file results of a run over several input files
+#[derive(Debug, Default)]
+pub struct BatchSummary {
+    results: Vec<(String, Result<usize, String>)>,
+}
+
+impl BatchSummary {
+    pub fn add_success(&mut self, filename: &str, record_count: usize) {
+        self.results.push((filename.to_string(), Ok(record_count)));
+    }
+
+    pub fn add_failure(&mut self, filename: &str, error: impl std::fmt::Display) {
+        self.results.push((filename.to_string(), Err(error.to_string())));
+    }
+
+    pub fn files_processed(&self) -> usize {
+        self.results.iter().filter(|(_, result)| result.is_ok()).count()
+    }
+
+    pub fn total_records(&self) -> usize {
+        self.results.iter().filter_map(|(_, result)| result.as_ref().ok()).sum()
+    }
+
+    pub fn failed_files(&self) -> Vec<&str> {
+        self.results.iter().filter(|(_, result)| result.is_err()).map(|(name, _)| name.as_str()).collect()
+    }
+
+    /// Reports one line per file followed by the combined totals, which start with `action` (e.g. "Processed")
+    pub fn print(&self, action: &str, elapsed: Duration) {
+        let width = self.results.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+        status!("Summary:");
+        for (name, result) in &self.results {
+            match result {
+                Ok(count) => status!("  {:<width$}  {:>12} records", name, format_count(*count), width = width),
+                Err(e) => status!("  {:<width$}  FAILED: {}", name, e, width = width),
+            }
+        }
+        let failed = self.failed_files().len();
+        status!(
+            "{} {} CWR records from {} files in {:.2?}{}",
+            action,
+            format_count(self.total_records()),
+            self.files_processed(),
+            elapsed,
+            if failed > 0 { format!(" ({} failed)", failed) } else { String::new() }
+        );
+    }
+}
+
+/// Formats a count with thousands separators
+fn format_count(count: usize) -> String {
+    let digits = count.to_string();
+    let mut formatted = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            formatted.push(',');
+        }
+        formatted.push(digit);
+    }
+    formatted
+}
+
+/// Watches `dir` until interrupted, processing each new CWR file and printing its outcome
+pub fn watch_directory<F>(dir: &str, poll_interval: Option<Duration>, process: F) -> !
+where
+    F: FnMut(&str) -> Result<usize, String>,
+{
+    let result = watch::DirectoryWatcher::new(dir, poll_interval.unwrap_or(watch::DEFAULT_POLL_INTERVAL)).and_then(
+        |mut watcher| {
+            status!("Watching '{}' for CWR files (Ctrl-C to stop)", dir);
+            watcher.run(process, |watched| match &watched.result {
+                Ok(count) => status!("{}: {} records", watched.path.display(), format_count(*count)),
+                Err(e) => status_error!("{}: FAILED: {}", watched.path.display(), e),
+            })
+        },
+    );
+    if let Err(e) = result {
+        status_error!("Error watching directory '{}': {}", dir, e);
+    }
+    process::exit(1);
+}
+
+/// Default output file for a file picked up in watch mode: `<watch_dir>/done/<input name>.<extension>`, next to
+/// where the processed input is moved
+pub fn watch_output_filename(watch_dir: &str, input_filename: &str, extension: &str) -> String {
+    let name = Path::new(input_filename).file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+    generate_default_output_filename(
+        &Path::new(watch_dir).join("done").join(name.as_ref()).to_string_lossy(),
+        extension,
+    )
+}
+
 pub fn get_value(parser: &mut lexopt::Parser, arg_name: &str) -> Result<String, String> {
     parser
         .value()
@@ -48,41 +276,46 @@ pub fn get_value(parser: &mut lexopt::Parser, arg_name: &str) -> Result<String,
         .map_err(|e| format!("Missing value for --{}: {}", arg_name, e))
 }
 
-pub fn process_stdin_with_temp_file<F, T>(temp_file_prefix: &str, processor: F, start_time: Instant) -> T
-where
-    F: FnOnce(&str, Instant) -> T,
-{
+/// Reads all of stdin, for tools that go over their input more than once; exits if it cannot be read
+pub fn read_stdin() -> Vec<u8> {
     info!("Reading CWR data from stdin");
 
-    let mut buffer = String::new();
-    if let Err(e) = io::stdin().read_to_string(&mut buffer) {
-        eprintln!("Error reading from stdin: {}", e);
-        process::exit(1);
-    }
-
-    let temp_file = std::env::temp_dir().join(format!("{}.tmp", temp_file_prefix));
-    if let Err(e) = fs::write(&temp_file, &buffer) {
-        eprintln!("Error writing temporary file: {}", e);
+    // Bytes rather than text, so Latin-1 input is kept as received
+    let mut buffer = Vec::new();
+    if let Err(e) = io::stdin().read_to_end(&mut buffer) {
+        status_error!("Error reading from stdin: {}", e);
         process::exit(1);
     }
-
-    let temp_path = temp_file.to_string_lossy();
-    let result = processor(&temp_path, start_time);
-
-    let _ = fs::remove_file(&temp_file);
-    result
+    buffer
 }
 
+/// Installs the logger configured by the `--quiet`, `--verbose` and `--log-format` flags, then parses the arguments
 pub fn init_logging_and_parse_args<F, T>(parser_fn: F) -> T
 where
     F: FnOnce() -> Result<T, String>,
 {
-    env_logger::init();
+    CliLayer::new(LogOptions::from_args(std::env::args_os().skip(1)), None).init();
+    parse_args_or_exit(parser_fn)
+}
+
+/// Like [`init_logging_and_parse_args`], but events logged to `report_target` are a library's report for the user:
+/// info events are printed to stdout and warnings to stderr as plain lines, whatever `RUST_LOG` says
+pub fn init_report_logging_and_parse_args<F, T>(report_target: &'static str, parser_fn: F) -> T
+where
+    F: FnOnce() -> Result<T, String>,
+{
+    CliLayer::new(LogOptions::from_args(std::env::args_os().skip(1)), Some(report_target)).init();
+    parse_args_or_exit(parser_fn)
+}
 
+fn parse_args_or_exit<F, T>(parser_fn: F) -> T
+where
+    F: FnOnce() -> Result<T, String>,
+{
     match parser_fn() {
         Ok(config) => config,
         Err(e) => {
-            error!("Configuration error: {}", e);
+            status_error!("Configuration error: {}", e);
             process::exit(1);
         }
     }
@@ -115,6 +348,19 @@ pub fn generate_default_output_filename(input_filename: &str, extension: &str) -
     }
 }
 
+/// The output file, or `None` for stdout when `filename` is [`STDOUT_FILENAME`]
+pub fn output_file(filename: Option<&str>) -> Option<&str> {
+    filename.filter(|filename| *filename != STDOUT_FILENAME)
+}
+
+/// Creates a buffered output file, or returns stdout when there is none or it is [`STDOUT_FILENAME`]
+pub fn create_output(filename: Option<&str>) -> io::Result<Box<dyn Write>> {
+    Ok(match output_file(filename) {
+        Some(filename) => Box::new(io::BufWriter::new(fs::File::create(filename)?)),
+        None => Box::new(io::stdout()),
+    })
+}
+
 /// Determines the output filename for a given input file when processing files
 /// For single files: uses the exact filename (overwrites if exists) or None if no output specified
 /// For multiple files: finds the next available filename by incrementing the index, or generates default if no output specified
@@ -123,6 +369,8 @@ pub fn get_output_filename_for_multiple_files(
     base_output_filename: Option<&str>, input_file_count: usize, current_file_index: usize,
 ) -> Option<String> {
     match (base_output_filename, input_file_count > 1) {
+        // Every file goes to stdout
+        (Some(STDOUT_FILENAME), _) => Some(STDOUT_FILENAME.to_string()),
         (Some(base_name), true) => {
             // Multiple files with -o: use incremental naming
             Some(find_next_available_filename(base_name, current_file_index + 1))
@@ -162,6 +410,122 @@ pub fn get_output_filename_with_default_extension(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::ffi::OsString;
+
+    /// Runs `log` with a [`CliLayer`] installed and returns the lines it wrote
+    fn capture_lines(
+        options: LogOptions, report_target: Option<&'static str>, log: impl FnOnce(),
+    ) -> Vec<(logging::Stream, String)> {
+        use std::sync::{Arc, Mutex};
+        use tracing_subscriber::layer::SubscriberExt;
+        use tracing_subscriber::Layer;
+
+        let lines = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&lines);
+        let layer = CliLayer::with_writer(options, report_target, move |stream, line: &str| {
+            sink.lock().unwrap().push((stream, line.to_string()))
+        });
+        let filter = layer.filter();
+        tracing::subscriber::with_default(tracing_subscriber::registry().with(layer.with_filter(filter)), log);
+        let lines = lines.lock().unwrap().clone();
+        lines
+    }
+
+    #[test]
+    fn test_report_events_go_to_stdout() {
+        use logging::Stream;
+
+        let quiet = LogOptions { verbosity: Verbosity::Quiet, format: LogFormat::Text };
+        let lines = capture_lines(quiet, Some("allegro_cwr_validate::report"), || {
+            tracing::info!(target: "allegro_cwr_validate::report", file = "a.V21", "ROUNDTRIP PASSED");
+            tracing::warn!(target: "allegro_cwr_validate::report", "    Example from line 3:");
+            tracing::debug!(target: "allegro_cwr_validate::report", "not shown");
+            tracing::info!(target: "allegro_cwr::parser", "not shown either");
+            tracing::error!(target: "allegro_cwr::parser", "Parse error");
+        });
+        assert_eq!(
+            lines,
+            vec![
+                (Stream::Stdout, "ROUNDTRIP PASSED".to_string()),
+                (Stream::Stderr, "    Example from line 3:".to_string()),
+                (Stream::Stderr, "ERROR allegro_cwr::parser: Parse error".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_events_carry_span_fields() {
+        let options = LogOptions { verbosity: Verbosity::Verbose, format: LogFormat::Text };
+        let lines = capture_lines(options, None, || {
+            let _file = tracing::info_span!("cwr_file", file = "a.V21").entered();
+            let _group = tracing::info_span!("cwr_group", group_id = 1, transaction_type = "NWR").entered();
+            tracing::warn!(target: "allegro_cwr::parser", line = 5, record_type = "XYZ", "Unknown record");
+        });
+        assert_eq!(
+            lines[0].1,
+            " WARN cwr_file{file=a.V21}: cwr_group{group_id=1 transaction_type=NWR}: allegro_cwr::parser: \
+             Unknown record line=5 record_type=XYZ"
+        );
+
+        let options = LogOptions { verbosity: Verbosity::Verbose, format: LogFormat::Json };
+        let lines = capture_lines(options, None, || {
+            let _file = tracing::info_span!("cwr_file", file = "a.V21").entered();
+            tracing::warn!(target: "allegro_cwr::parser", line = 5, "Bad \"quote\"");
+        });
+        assert_eq!(
+            lines[0].1,
+            r#"{"level":"WARN","target":"allegro_cwr::parser","message":"Bad \"quote\"","file":"a.V21","line":5}"#
+        );
+    }
+
+    #[test]
+    fn test_log_options_from_args() {
+        let options = |args: &[&str]| LogOptions::from_args(args.iter().map(OsString::from));
+
+        assert_eq!(options(&["file.V21"]), LogOptions::default());
+        assert_eq!(options(&["-q", "file.V21"]).verbosity, Verbosity::Quiet);
+        assert_eq!(options(&["--verbose"]).verbosity, Verbosity::Verbose);
+        assert_eq!(options(&["--log-format", "json"]).format, LogFormat::Json);
+        assert_eq!(options(&["--log-format=json"]).format, LogFormat::Json);
+        assert_eq!(options(&["--log-format", "xml"]).format, LogFormat::Text);
+        assert_eq!(options(&["--", "--quiet"]).verbosity, Verbosity::Normal);
+    }
+
+    #[test]
+    fn test_quiet_keeps_status_errors_only() {
+        let quiet = LogOptions { verbosity: Verbosity::Quiet, format: LogFormat::Text };
+        let lines = capture_lines(quiet, None, || {
+            status!("Processing a.V21");
+            status_error!("Error reading file 'a.V21'");
+            tracing::warn!(target: "allegro_cwr::parser", "Lines skipped: 3");
+        });
+        let lines: Vec<_> = lines.into_iter().map(|(_, line)| line).collect();
+        assert_eq!(lines, vec!["Error reading file 'a.V21'".to_string()]);
+
+        let verbose = LogOptions { verbosity: Verbosity::Verbose, format: LogFormat::Text };
+        let lines = capture_lines(verbose, None, || {
+            status!("Processing a.V21");
+            tracing::info!(target: "allegro_cwr::parser", "Determined CWR version: 2.1");
+            tracing::debug!(target: "allegro_cwr::parser", "not shown");
+        });
+        let lines: Vec<_> = lines.into_iter().map(|(_, line)| line).collect();
+        assert_eq!(
+            lines,
+            vec!["Processing a.V21".to_string(), " INFO allegro_cwr::parser: Determined CWR version: 2.1".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_json_log_line() {
+        let json = LogOptions { verbosity: Verbosity::Normal, format: LogFormat::Json };
+        let lines = capture_lines(json, None, || {
+            status_error!("Error reading file '{}': \"bad\"\n", "a.V21");
+        });
+        assert_eq!(
+            lines[0].1,
+            r#"{"level":"ERROR","target":"allegro_cwr_cli::status","message":"Error reading file 'a.V21': \"bad\"\n"}"#
+        );
+    }
 
     #[test]
     fn test_find_next_available_filename_with_extension() {
@@ -197,4 +561,84 @@ mod tests {
         let result = find_next_available_filename(".test.json", 1);
         assert_eq!(result, ".test.1.json");
     }
+
+    #[test]
+    fn test_matches_pattern() {
+        assert!(matches_pattern("*.V21", "CW230001ABC_000.V21"));
+        assert!(matches_pattern("*.v2?", "file.V22"));
+        assert!(!matches_pattern("*.V21", "file.V22"));
+        assert!(matches_pattern("CW*ABC*", "CW230001ABC_000.V21"));
+        assert!(!matches_pattern("CW?", "CW12"));
+    }
+
+    #[test]
+    fn test_expand_input_path() {
+        let dir = std::env::temp_dir().join(format!("cli_expand_test_{:?}", std::thread::current().id()));
+        fs::create_dir_all(&dir).unwrap();
+        for name in ["b.V22", "a.V21", "c.V21.gz", "notes.txt", "notes.gz"] {
+            fs::write(dir.join(name), "").unwrap();
+        }
+        let dir_arg = dir.to_string_lossy().to_string();
+
+        let files = expand_input_path(&dir_arg).unwrap();
+        assert_eq!(
+            files,
+            vec![
+                dir.join("a.V21").to_string_lossy(),
+                dir.join("b.V22").to_string_lossy(),
+                dir.join("c.V21.gz").to_string_lossy()
+            ]
+        );
+
+        let files = expand_input_path(&dir.join("*.V22").to_string_lossy()).unwrap();
+        assert_eq!(files, vec![dir.join("b.V22").to_string_lossy()]);
+
+        assert!(expand_input_path(&dir.join("*.V20").to_string_lossy()).is_err());
+        assert_eq!(expand_input_path("plain.V21").unwrap(), vec!["plain.V21"]);
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_stdin_input_file() {
+        let mut config = BaseConfig::new();
+        config.add_input_file(STDIN_FILENAME.to_string()).unwrap();
+        assert!(config.read_stdin && config.input_files.is_empty());
+        assert!(config.add_input_file("plain.V21".to_string()).is_err());
+
+        let mut config = BaseConfig::new();
+        config.add_input_file("plain.V21".to_string()).unwrap();
+        assert!(config.add_input_file(STDIN_FILENAME.to_string()).is_err());
+    }
+
+    #[test]
+    fn test_stdout_output_file() {
+        assert_eq!(output_file(Some(STDOUT_FILENAME)), None);
+        assert_eq!(output_file(Some("out.json")), Some("out.json"));
+        assert_eq!(get_output_filename_for_multiple_files(Some(STDOUT_FILENAME), 3, 1).as_deref(), Some("-"));
+        assert_eq!(
+            get_output_filename_with_default_extension(Some(STDOUT_FILENAME), "a.V21", 3, 1, "json").as_deref(),
+            Some("-")
+        );
+    }
+
+    #[test]
+    fn test_direction_check() {
+        assert!(Direction::Auto.check(false, "SQLite").is_ok());
+        assert!(Direction::FromCwr.check(true, "SQLite").is_ok());
+        assert_eq!(Direction::FromCwr.check(false, "SQLite").unwrap_err(), "Expected a CWR file, found SQLite input");
+        assert!(Direction::ToCwr.check(true, "JSON").is_err());
+    }
+
+    #[test]
+    fn test_batch_summary_totals() {
+        let mut summary = BatchSummary::default();
+        summary.add_success("a.V21", 1200);
+        summary.add_failure("b.V21", "bad header");
+        summary.add_success("c.V22", 34);
+        assert_eq!(summary.total_records(), 1234);
+        assert_eq!(summary.files_processed(), 2);
+        assert_eq!(summary.failed_files(), vec!["b.V21"]);
+        assert_eq!(format_count(1234567), "1,234,567");
+    }
 }
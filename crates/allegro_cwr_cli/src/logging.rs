@@ -0,0 +1,346 @@
+//! Logging shared by the command line tools: `--quiet`, `--verbose` and `--log-format` handling, and the `tracing`
+//! subscriber that keeps progress and error messages on stderr so stdout only carries data
+
+use std::ffi::OsString;
+use std::fmt::{self, Write as _};
+use std::io::{self, Write};
+use std::str::FromStr;
+
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Metadata, Subscriber};
+use tracing_subscriber::filter::{filter_fn, EnvFilter, FilterExt, LevelFilter};
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+
+/// Target of progress and error messages meant for the user, logged with [`status!`](crate::status) and
+/// [`status_error!`](crate::status_error)
+pub const STATUS_TARGET: &str = "allegro_cwr_cli::status";
+
+/// Logs a progress message for the user, printed to stderr unless `--quiet` is given
+#[macro_export]
+macro_rules! status {
+    ($($arg:tt)+) => {
+        $crate::tracing::info!(target: $crate::STATUS_TARGET, $($arg)+)
+    };
+}
+
+/// Logs an error message for the user, printed to stderr even with `--quiet`
+#[macro_export]
+macro_rules! status_error {
+    ($($arg:tt)+) => {
+        $crate::tracing::error!(target: $crate::STATUS_TARGET, $($arg)+)
+    };
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Verbosity {
+    /// Only errors are printed
+    Quiet,
+    /// Progress messages and errors; other logging as configured by `RUST_LOG`
+    #[default]
+    Normal,
+    /// Progress messages and all logging at info level and above
+    Verbose,
+}
+
+/// How messages on stderr are written
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Plain lines for people
+    #[default]
+    Text,
+    /// One JSON object per line with `level`, `target`, `message` and the fields of the event and its spans
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            _ => Err(format!("Invalid log format '{}'. Valid formats: text, json", s)),
+        }
+    }
+}
+
+/// The logging flags given on the command line
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LogOptions {
+    pub verbosity: Verbosity,
+    pub format: LogFormat,
+}
+
+impl LogOptions {
+    /// Picks the logging flags out of `args` ahead of parsing, so messages about the other arguments already follow
+    /// them. Invalid values are left for the tool's own parser to report.
+    pub fn from_args(args: impl IntoIterator<Item = OsString>) -> Self {
+        let mut options = LogOptions::default();
+        let mut args = args.into_iter().map(|arg| arg.to_string_lossy().to_string());
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--" => break,
+                "-q" | "--quiet" => options.verbosity = Verbosity::Quiet,
+                "-v" | "--verbose" => options.verbosity = Verbosity::Verbose,
+                "--log-format" => {
+                    if let Some(format) = args.next().and_then(|value| value.parse().ok()) {
+                        options.format = format;
+                    }
+                }
+                _ => {
+                    if let Some(format) = arg.strip_prefix("--log-format=").and_then(|value| value.parse().ok()) {
+                        options.format = format;
+                    }
+                }
+            }
+        }
+        options
+    }
+
+    /// Filter of the events that are neither status messages nor a report
+    pub(crate) fn env_filter(&self) -> EnvFilter {
+        match self.verbosity {
+            Verbosity::Quiet => EnvFilter::new("error"),
+            Verbosity::Normal => {
+                EnvFilter::builder().with_default_directive(LevelFilter::ERROR.into()).from_env_lossy()
+            }
+            Verbosity::Verbose => EnvFilter::new("info"),
+        }
+    }
+}
+
+/// Checks the value of `--log-format`, for tools' argument parsers; the flag itself is applied by
+/// [`LogOptions::from_args`]
+pub fn check_log_format(parser: &mut lexopt::Parser) -> Result<(), String> {
+    crate::get_value(parser, "log-format")?.parse::<LogFormat>().map(|_| ())
+}
+
+/// Where a line of output goes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Stream {
+    Stdout,
+    Stderr,
+}
+
+fn print_line(stream: Stream, line: &str) {
+    match stream {
+        Stream::Stdout => {
+            let _ = writeln!(io::stdout(), "{}", line);
+        }
+        Stream::Stderr => {
+            let _ = writeln!(io::stderr(), "{}", line);
+        }
+    }
+}
+
+/// Sends status messages to stderr and, when a tool has one, its report to stdout. Other events pass the
+/// `RUST_LOG`/verbosity filter and are written to stderr with the fields of their spans (e.g. the file and group
+/// being processed).
+pub(crate) struct CliLayer<W = fn(Stream, &str)> {
+    pub(crate) options: LogOptions,
+    pub(crate) report_target: Option<&'static str>,
+    write: W,
+}
+
+impl CliLayer {
+    pub(crate) fn new(options: LogOptions, report_target: Option<&'static str>) -> Self {
+        CliLayer { options, report_target, write: print_line }
+    }
+
+    /// Installs the layer as the global subscriber; does nothing if one is already installed
+    pub(crate) fn init(self) {
+        let filter = self.filter();
+        let _ = tracing::subscriber::set_global_default(tracing_subscriber::registry().with(self.with_filter(filter)));
+    }
+}
+
+impl<W> CliLayer<W> {
+    #[cfg(test)]
+    pub(crate) fn with_writer(options: LogOptions, report_target: Option<&'static str>, write: W) -> Self {
+        CliLayer { options, report_target, write }
+    }
+
+    /// Status messages and the report whatever the verbosity, everything else as `RUST_LOG` or the verbosity says
+    pub(crate) fn filter<S: Subscriber + for<'a> LookupSpan<'a>>(
+        &self,
+    ) -> impl tracing_subscriber::layer::Filter<S> + Send + Sync + 'static {
+        let (options, report_target) = (self.options, self.report_target);
+        filter_fn(move |metadata| is_report(report_target, metadata) || is_status(options.verbosity, metadata))
+            .or(self.options.env_filter())
+    }
+}
+
+pub(crate) fn is_report(report_target: Option<&str>, metadata: &Metadata) -> bool {
+    Some(metadata.target()) == report_target && *metadata.level() <= Level::INFO
+}
+
+pub(crate) fn is_status(verbosity: Verbosity, metadata: &Metadata) -> bool {
+    metadata.target() == STATUS_TARGET
+        && match verbosity {
+            Verbosity::Quiet => *metadata.level() == Level::ERROR,
+            _ => *metadata.level() <= Level::INFO,
+        }
+}
+
+impl<S, W> Layer<S> for CliLayer<W>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    W: Fn(Stream, &str) + 'static,
+{
+    fn on_new_span(&self, attrs: &tracing::span::Attributes<'_>, id: &tracing::span::Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            let mut fields = Fields::default();
+            attrs.record(&mut fields);
+            span.extensions_mut().insert(fields);
+        }
+    }
+
+    fn on_record(&self, id: &tracing::span::Id, values: &tracing::span::Record<'_>, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            if let Some(fields) = span.extensions_mut().get_mut::<Fields>() {
+                values.record(fields);
+            }
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        let mut fields = Fields::default();
+        event.record(&mut fields);
+
+        if is_report(self.report_target, metadata) {
+            let stream = if *metadata.level() == Level::INFO { Stream::Stdout } else { Stream::Stderr };
+            (self.write)(stream, &fields.message);
+            return;
+        }
+
+        let mut spans: Vec<(&'static str, Fields)> = Vec::new();
+        if let Some(scope) = ctx.event_scope(event) {
+            for span in scope.from_root() {
+                let span_fields = span.extensions().get::<Fields>().cloned().unwrap_or_default();
+                spans.push((span.name(), span_fields));
+            }
+        }
+        let line = match self.options.format {
+            LogFormat::Json => json_line(metadata, &spans, &fields),
+            LogFormat::Text if metadata.target() == STATUS_TARGET => fields.message,
+            LogFormat::Text => text_line(metadata, &spans, &fields),
+        };
+        (self.write)(Stream::Stderr, &line);
+    }
+}
+
+/// The message and other fields of an event or span
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Fields {
+    message: String,
+    values: Vec<(&'static str, FieldValue)>,
+}
+
+#[derive(Debug, Clone)]
+enum FieldValue {
+    /// Numbers and booleans, written as they are in JSON
+    Plain(String),
+    Text(String),
+}
+
+impl Visit for Fields {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{:?}", value);
+        } else {
+            self.values.push((field.name(), FieldValue::Text(format!("{:?}", value))));
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message = value.to_string();
+        } else {
+            self.values.push((field.name(), FieldValue::Text(value.to_string())));
+        }
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.values.push((field.name(), FieldValue::Plain(value.to_string())));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.values.push((field.name(), FieldValue::Plain(value.to_string())));
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        let value =
+            if value.is_finite() { FieldValue::Plain(value.to_string()) } else { FieldValue::Text(value.to_string()) };
+        self.values.push((field.name(), value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.values.push((field.name(), FieldValue::Plain(value.to_string())));
+    }
+}
+
+/// Formats an event as `LEVEL span{field=value}:span{...}: target: message field=value`
+pub(crate) fn text_line(metadata: &Metadata, spans: &[(&'static str, Fields)], fields: &Fields) -> String {
+    let mut line = format!("{:>5} ", metadata.level());
+    for (name, span_fields) in spans {
+        line.push_str(name);
+        let mut separator = '{';
+        for (key, value) in &span_fields.values {
+            let (FieldValue::Plain(value) | FieldValue::Text(value)) = value;
+            let _ = write!(line, "{}{}={}", separator, key, value);
+            separator = ' ';
+        }
+        if separator == ' ' {
+            line.push('}');
+        }
+        line.push_str(": ");
+    }
+    let _ = write!(line, "{}: {}", metadata.target(), fields.message);
+    for (key, value) in &fields.values {
+        let (FieldValue::Plain(value) | FieldValue::Text(value)) = value;
+        let _ = write!(line, " {}={}", key, value);
+    }
+    line
+}
+
+/// Formats an event as a single line JSON object, with the fields of its spans and its own fields after the message
+pub(crate) fn json_line(metadata: &Metadata, spans: &[(&'static str, Fields)], fields: &Fields) -> String {
+    let mut line = format!(
+        "{{\"level\":\"{}\",\"target\":\"{}\",\"message\":\"{}\"",
+        metadata.level(),
+        json_escape(metadata.target()),
+        json_escape(&fields.message)
+    );
+    for (key, value) in spans.iter().flat_map(|(_, span_fields)| &span_fields.values).chain(&fields.values) {
+        match value {
+            FieldValue::Plain(value) => {
+                let _ = write!(line, ",\"{}\":{}", json_escape(key), value);
+            }
+            FieldValue::Text(value) => {
+                let _ = write!(line, ",\"{}\":\"{}\"", json_escape(key), json_escape(value));
+            }
+        }
+    }
+    line.push('}');
+    line
+}
+
+fn json_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(escaped, "\\u{:04x}", c as u32);
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
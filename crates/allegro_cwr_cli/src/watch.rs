@@ -0,0 +1,210 @@
+//! Drop-directory ingestion: poll a directory for new CWR files, process each one and move it to `done/` or
+//! `failed/`. A file is only picked up once its size is unchanged between two polls, so files still being
+//! copied into the directory are left alone. I/O errors on one file are logged and the file skipped, so the watch
+//! keeps going.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use tracing::{error, info, warn};
+
+use crate::{find_next_available_filename, has_cwr_extension};
+
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Outcome of processing one file picked up from the watched directory
+#[derive(Debug)]
+pub struct WatchedFile {
+    /// Where the file was moved to after processing, or where it was left if it could not be moved
+    pub path: PathBuf,
+    pub result: Result<usize, String>,
+}
+
+pub struct DirectoryWatcher {
+    inbox: PathBuf,
+    done_dir: PathBuf,
+    failed_dir: PathBuf,
+    poll_interval: Duration,
+    /// Sizes seen on the previous poll for files not yet processed
+    pending: HashMap<PathBuf, u64>,
+    /// Files processed but left in the directory because they could not be moved, so they are not processed again
+    handled: HashSet<PathBuf>,
+}
+
+impl DirectoryWatcher {
+    /// Watches `inbox`, creating its `done` and `failed` subdirectories if needed
+    pub fn new(inbox: impl Into<PathBuf>, poll_interval: Duration) -> io::Result<Self> {
+        let inbox = inbox.into();
+        let done_dir = inbox.join("done");
+        let failed_dir = inbox.join("failed");
+        fs::create_dir_all(&done_dir)?;
+        fs::create_dir_all(&failed_dir)?;
+        Ok(Self { inbox, done_dir, failed_dir, poll_interval, pending: HashMap::new(), handled: HashSet::new() })
+    }
+
+    pub fn done_dir(&self) -> &Path {
+        &self.done_dir
+    }
+
+    /// Scans the directory once, processing every CWR file whose size has settled since the previous scan. Only
+    /// failing to read the directory itself is an error.
+    pub fn poll<F>(&mut self, mut process: F) -> io::Result<Vec<WatchedFile>>
+    where
+        F: FnMut(&str) -> Result<usize, String>,
+    {
+        let mut ready = Vec::new();
+        let mut seen = HashMap::new();
+        let mut still_handled = HashSet::new();
+        for entry in fs::read_dir(&self.inbox)? {
+            let path = match entry {
+                Ok(entry) => entry.path(),
+                Err(e) => {
+                    warn!("Could not read an entry of '{}': {}", self.inbox.display(), e);
+                    continue;
+                }
+            };
+            if !path.is_file() || !has_cwr_extension(&path) {
+                continue;
+            }
+            if self.handled.contains(&path) {
+                still_handled.insert(path);
+                continue;
+            }
+            let size = match fs::metadata(&path) {
+                Ok(metadata) => metadata.len(),
+                Err(e) => {
+                    warn!("Skipping '{}': {}", path.display(), e);
+                    continue;
+                }
+            };
+            if self.pending.get(&path) == Some(&size) {
+                ready.push(path);
+            } else {
+                seen.insert(path, size);
+            }
+        }
+        self.pending = seen;
+        self.handled = still_handled;
+        ready.sort();
+
+        let mut processed = Vec::with_capacity(ready.len());
+        for path in ready {
+            let path_str = path.to_string_lossy().to_string();
+            info!("Processing '{}' from watched directory", path_str);
+            let result = process(&path_str);
+            let target_dir = match &result {
+                Ok(_) => &self.done_dir,
+                Err(e) => {
+                    error!("Failed to process '{}': {}", path_str, e);
+                    &self.failed_dir
+                }
+            };
+            let moved_to = match move_into(&path, target_dir) {
+                Ok(moved_to) => moved_to,
+                Err(e) if target_dir != &self.failed_dir => {
+                    error!("Could not move '{}' to '{}': {}", path_str, target_dir.display(), e);
+                    move_into(&path, &self.failed_dir).unwrap_or_else(|e| self.leave(path, e))
+                }
+                Err(e) => self.leave(path, e),
+            };
+            processed.push(WatchedFile { path: moved_to, result });
+        }
+        Ok(processed)
+    }
+
+    /// Keeps a processed file that could not be moved from being processed again on the next polls
+    fn leave(&mut self, path: PathBuf, e: io::Error) -> PathBuf {
+        error!("Could not move '{}' to '{}', leaving it in place: {}", path.display(), self.failed_dir.display(), e);
+        self.handled.insert(path.clone());
+        path
+    }
+
+    /// Polls forever, reporting each processed file to `report`
+    pub fn run<F, R>(&mut self, mut process: F, mut report: R) -> io::Result<()>
+    where
+        F: FnMut(&str) -> Result<usize, String>,
+        R: FnMut(&WatchedFile),
+    {
+        info!("Watching '{}' for CWR files", self.inbox.display());
+        loop {
+            for watched in self.poll(&mut process)? {
+                report(&watched);
+            }
+            std::thread::sleep(self.poll_interval);
+        }
+    }
+}
+
+/// Moves a file into `dir`, adding an index to the name rather than overwriting an earlier file
+fn move_into(path: &Path, dir: &Path) -> io::Result<PathBuf> {
+    let file_name = path.file_name().ok_or_else(|| io::Error::other("Path has no file name"))?;
+    let mut target = dir.join(file_name);
+    if target.exists() {
+        target = PathBuf::from(find_next_available_filename(&target.to_string_lossy(), 1));
+    }
+    fs::rename(path, &target)?;
+    Ok(target)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_poll_waits_for_stable_size_and_moves_files() {
+        let inbox = std::env::temp_dir().join(format!("cli_watch_test_{:?}", std::thread::current().id()));
+        fs::remove_dir_all(&inbox).ok();
+        let mut watcher = DirectoryWatcher::new(&inbox, Duration::ZERO).unwrap();
+        fs::write(inbox.join("good.V21"), "HDR").unwrap();
+        fs::write(inbox.join("bad.V21"), "XXX").unwrap();
+        fs::write(inbox.join("notes.txt"), "").unwrap();
+
+        let process = |path: &str| if path.ends_with("good.V21") { Ok(1) } else { Err("bad header".to_string()) };
+
+        // First sighting only records the sizes
+        assert!(watcher.poll(process).unwrap().is_empty());
+
+        let processed = watcher.poll(process).unwrap();
+        assert_eq!(processed.len(), 2);
+        assert!(inbox.join("done").join("good.V21").exists());
+        assert!(inbox.join("failed").join("bad.V21").exists());
+        assert!(inbox.join("notes.txt").exists());
+        assert!(watcher.poll(process).unwrap().is_empty());
+
+        fs::remove_dir_all(&inbox).ok();
+    }
+
+    #[test]
+    fn test_poll_does_not_process_unmovable_files_again() {
+        let inbox = std::env::temp_dir().join(format!("cli_watch_unmovable_{:?}", std::thread::current().id()));
+        fs::remove_dir_all(&inbox).ok();
+        let mut watcher = DirectoryWatcher::new(&inbox, Duration::ZERO).unwrap();
+        let mut calls = 0;
+        let mut process = |_: &str| {
+            calls += 1;
+            Ok(1)
+        };
+
+        // A processed file that cannot go to done/ goes to failed/
+        fs::write(inbox.join("good.V21"), "HDR").unwrap();
+        assert!(watcher.poll(&mut process).unwrap().is_empty());
+        fs::remove_dir(inbox.join("done")).unwrap();
+        let processed = watcher.poll(&mut process).unwrap();
+        assert_eq!(processed[0].path, inbox.join("failed").join("good.V21"));
+
+        // and stays put when that fails too, without being processed again
+        fs::remove_dir_all(inbox.join("failed")).unwrap();
+        fs::write(inbox.join("late.V21"), "HDR").unwrap();
+        assert!(watcher.poll(&mut process).unwrap().is_empty());
+        let processed = watcher.poll(&mut process).unwrap();
+        assert_eq!(processed[0].path, inbox.join("late.V21"));
+        assert!(watcher.poll(&mut process).unwrap().is_empty());
+        assert!(watcher.poll(&mut process).unwrap().is_empty());
+        assert_eq!(calls, 2);
+
+        fs::remove_dir_all(&inbox).ok();
+    }
+}
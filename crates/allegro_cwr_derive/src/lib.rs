@@ -2,12 +2,35 @@ use proc_macro::TokenStream;
 use quote::quote;
 use syn::{Data, DeriveInput, Fields, LitInt, LitStr, parse_macro_input};
 
+/// `FieldKind` variant for a field's type: free text, dates, or any other domain type (codes, numbers, flags)
+fn field_kind(ty: &syn::Type) -> proc_macro2::TokenStream {
+    let mut ty = ty;
+    let mut name = String::new();
+    while let syn::Type::Path(type_path) = ty
+        && let Some(segment) = type_path.path.segments.last()
+    {
+        name = segment.ident.to_string();
+        match &segment.arguments {
+            syn::PathArguments::AngleBracketed(args) if name == "Option" => match args.args.first() {
+                Some(syn::GenericArgument::Type(inner)) => ty = inner,
+                _ => break,
+            },
+            _ => break,
+        }
+    }
+    match name.as_str() {
+        "String" | "SenderName" | "NonRomanAlphabet" => quote! { crate::records::FieldKind::Text },
+        "Date" => quote! { crate::records::FieldKind::Date },
+        _ => quote! { crate::records::FieldKind::Code },
+    }
+}
+
 /// Check if a type is Option<T>
 fn is_option_type(ty: &syn::Type) -> bool {
-    if let syn::Type::Path(type_path) = ty {
-        if let Some(segment) = type_path.path.segments.last() {
-            return segment.ident == "Option";
-        }
+    if let syn::Type::Path(type_path) = ty
+        && let Some(segment) = type_path.path.segments.last()
+    {
+        return segment.ident == "Option";
     }
     false
 }
@@ -17,8 +40,25 @@ fn is_option_type(ty: &syn::Type) -> bool {
 /// # Attributes
 /// - `codes`: Optional array of record codes this struct handles (e.g., `["NWR", "REV"]`)
 /// - `validator`: Optional custom validation function name
+/// - `min_version` / `max_version`: Optional CWR version range the record exists in (e.g., `min_version = 2.2`).
+///   Records outside the range are rejected when parsing and refused by `try_to_cwr_record_bytes`.
 /// - `test_data`: Required test data string for auto-generated tests (put last since it's long)
 ///
+/// # Field Attributes
+/// - `title`, `start`, `len`: Field title and default position
+/// - `min_version`: Optional first CWR version containing the field
+/// - `layout(max_version = 2.1, start = ..., len = ...)`: Optional, repeatable position override for
+///   versions up to `max_version`. Parsing and `to_cwr_record_bytes` pick the layout for the file's version.
+/// - `validate`: Optional field validation function, run on the parsed value. Its warnings are merged with
+///   the parse warnings. Define it with this signature:
+///   ```rust,ignore
+///   fn my_check(value: &FieldType, field_name: &'static str, field_title: &'static str) -> Vec<CwrWarning<'static>>
+///   ```
+///
+/// The generated `FIELD_SPECS` constant describes every field's name, title, position, length,
+/// minimum version and optionality, for tooling that needs the record layout.
+/// The generated `fields()` method yields each field's name, title and stringified value.
+///
 /// # Custom Validator
 /// If you specify `validator = my_function`, define it with this exact signature:
 /// ```rust,ignore
@@ -56,17 +96,27 @@ pub fn derive_cwr_record(input: TokenStream) -> TokenStream {
     let record_codes = extract_record_codes(&input.attrs, name);
     let registry_variant = generate_registry_variant(name);
     let validator_fn = extract_validator(&input.attrs);
+    let (record_min_version, record_max_version) = extract_version_range(&input.attrs);
+    let record_min_version = quote_optional_version(record_min_version);
+    let record_max_version = quote_optional_version(record_max_version);
     let field_parsers = fields.iter().map(|field| {
         let field_name = field.ident.as_ref().unwrap();
         let field_type = &field.ty;
         let (title, start, len, skip_parse, _min_version) = extract_field_attrs(&field.attrs);
+        let layout = quote_field_layout(start, len, &extract_field_layouts(&field.attrs));
+        let field_validation = match extract_field_validator(&field.attrs) {
+            Some(validate_fn) => quote! {
+                warnings.extend(#validate_fn(&#field_name, stringify!(#field_name), #title));
+            },
+            None => quote! {},
+        };
 
         let field_name_str = field_name.to_string();
 
         if field_name_str == "record_type" {
             // For record_type field, use the actual record type from the line
             quote! {
-                let #field_name = line[0..3].to_string();
+                let #field_name = crate::parsing::intern(&line.field(0, 3));
             }
         } else if skip_parse {
             quote! {
@@ -77,27 +127,29 @@ pub fn derive_cwr_record(input: TokenStream) -> TokenStream {
             if is_optional {
                 quote! {
                     let (#field_name, field_warnings) = {
-                        let start_pos = #start;
+                        let (start_pos, field_len) = #layout;
                         if line.len() <= start_pos {
                             // For Option<T> fields: silently set to None when line doesn't reach field start
                             (None, Vec::new())
                         } else {
                             // Parse whatever content is available from start position to end of line
-                            let end_pos = (start_pos + #len).min(line.len());
-                            let field_slice = &line[start_pos..end_pos];
+                            let end_pos = (start_pos + field_len).min(line.len());
+                            let field_slice = line.field(start_pos, end_pos);
                             <#field_type as CwrFieldParse>::parse_cwr_field(
-                                field_slice,
+                                &field_slice,
                                 stringify!(#field_name),
                                 #title
                             )
                         }
                     };
                     warnings.extend(field_warnings);
+                    #field_validation
                 }
             } else {
                 quote! {
                     let (#field_name, field_warnings) = {
-                        let end = #start + #len;
+                        let (start_pos, field_len) = #layout;
+                        let end = start_pos + field_len;
                         if line.len() < end {
                             let mut warnings = vec![
                                 CwrWarning {
@@ -109,21 +161,22 @@ pub fn derive_cwr_record(input: TokenStream) -> TokenStream {
                                         "Line too short: expected at least {} characters, got {}",
                                         end,
                                         line.len()
-                                    ),
+                                    ).into(),
                                 }
                             ];
                             let default_value = <#field_type as Default>::default();
                             (default_value, warnings)
                         } else {
-                            let field_slice = &line[#start..end];
+                            let field_slice = line.field(start_pos, end);
                             <#field_type as CwrFieldParse>::parse_cwr_field(
-                                field_slice,
+                                &field_slice,
                                 stringify!(#field_name),
                                 #title
                             )
                         }
                     };
                     warnings.extend(field_warnings);
+                    #field_validation
                 }
             }
         }
@@ -134,33 +187,102 @@ pub fn derive_cwr_record(input: TokenStream) -> TokenStream {
     // Generate field writers for byte-based method
     let field_writers_bytes = fields.iter().map(|field| {
         let field_name = field.ident.as_ref().unwrap();
+        let field_name_str = field_name.to_string();
         let (_title, start, len, _skip_parse, min_version) = extract_field_attrs(&field.attrs);
+        let layout = quote_field_layout(start, len, &extract_field_layouts(&field.attrs));
+        let write_field = quote! {
+            let (start_pos, field_len) = #layout;
+            // Ensure we're at the right position
+            for _ in written..start_pos {
+                writer.write_all(b" ")?;
+            }
+            let field_bytes = <_ as crate::parsing::CwrFieldWrite>::to_cwr_field_bytes(&self.#field_name, field_len, character_set);
+            let (field_bytes, truncation) = crate::parsing::fit_field_bytes(field_bytes, field_len, character_set);
+            if let Some((original, truncated)) = truncation {
+                let record_type = crate::records::RecordType::record_type(self).to_string();
+                if overflow == crate::parsing::FieldOverflow::Error {
+                    return Err(crate::error::CwrParseError::FieldTooLong {
+                        record_type,
+                        field_name: #field_name_str,
+                        width: field_len,
+                        value: original,
+                    });
+                }
+                truncations.push(crate::parsing::FieldTruncation {
+                    record_type,
+                    field_name: #field_name_str,
+                    width: field_len,
+                    original,
+                    truncated,
+                });
+            }
+            writer.write_all(&field_bytes)?;
+            written = start_pos.max(written) + field_bytes.len();
+        };
 
         if let Some(min_ver) = min_version {
             // Version-conditional field - only write if version supports it
             quote! {
                 if version.supports_version(#min_ver) {
-                    // Ensure we're at the right position
-                    while result.len() < #start {
-                        result.push(b' ');
-                    }
-                    let field_bytes = <_ as crate::parsing::CwrFieldWrite>::to_cwr_field_bytes(&self.#field_name, #len, character_set);
-                    result.extend(field_bytes);
+                    #write_field
                 }
             }
         } else {
             // Always include this field
             quote! {
-                // Ensure we're at the right position
-                while result.len() < #start {
-                    result.push(b' ');
+                {
+                    #write_field
                 }
-                let field_bytes = <_ as crate::parsing::CwrFieldWrite>::to_cwr_field_bytes(&self.#field_name, #len, character_set);
-                result.extend(field_bytes);
             }
         }
     });
 
+    // Generate static field metadata for introspection
+    let field_specs = fields.iter().map(|field| {
+        let field_name_str = field.ident.as_ref().unwrap().to_string();
+        let (title, start, len, _skip_parse, min_version) = extract_field_attrs(&field.attrs);
+        let min_version = quote_optional_version(min_version);
+        let optional = is_option_type(&field.ty);
+        let kind = field_kind(&field.ty);
+        let layouts = extract_field_layouts(&field.attrs).into_iter().map(|(max_version, start, len)| {
+            quote! { crate::records::FieldLayout { max_version: #max_version, start: #start, len: #len } }
+        });
+        quote! {
+            crate::records::FieldSpec {
+                name: #field_name_str,
+                title: #title,
+                start: #start,
+                len: #len,
+                min_version: #min_version,
+                optional: #optional,
+                kind: #kind,
+                layouts: &[#(#layouts),*],
+            }
+        }
+    });
+
+    // Generate per-field text for the generic field iterator
+    let field_text_arms = fields.iter().enumerate().map(|(index, field)| {
+        let field_name = field.ident.as_ref().unwrap();
+        let (_title, _start, len, _skip_parse, _min_version) = extract_field_attrs(&field.attrs);
+        quote! {
+            #index => <_ as crate::parsing::CwrFieldWrite>::to_cwr_field_bytes(&self.#field_name, #len, &crate::domain_types::CharacterSet::UTF8),
+        }
+    });
+
+    // Generate the field generators for `arbitrary`, in declaration order; the record type and constant spaces are
+    // left blank
+    let field_generators = fields.iter().map(|field| {
+        let field_type = &field.ty;
+        let (_title, _start, _len, skip_parse, _min_version) = extract_field_attrs(&field.attrs);
+        let ident = field.ident.as_ref().map(|ident| ident.to_string()).unwrap_or_default();
+        if skip_parse || ident == "record_type" || ident.starts_with("constant") {
+            quote! { crate::generate::blank_text }
+        } else {
+            quote! { crate::generate::field_text::<#field_type> }
+        }
+    });
+
     let test_mod_name = quote::format_ident!("{}_generated_tests", name.to_string().to_lowercase());
 
     let validator_implementation = if let Some(validator_fn) = validator_fn {
@@ -193,13 +315,37 @@ pub fn derive_cwr_record(input: TokenStream) -> TokenStream {
                         "Critical warnings found in test data"
                     );
                 }
+
+                #[cfg(feature = "arbitrary")]
+                #[test]
+                fn test_arbitrary_round_trip() {
+                    crate::generate::check_round_trip::<#name>();
+                }
             }
         }
     };
 
     let expanded = quote! {
         impl #name {
+            /// Layout of every field in this record, in declaration order
+            pub const FIELD_SPECS: &'static [crate::records::FieldSpec] = &[#(#field_specs),*];
+
+            /// Every field with its name, title and stringified value, in declaration order
+            pub fn fields(&self) -> crate::records::Fields<'_> {
+                crate::records::Fields::new(self, Self::FIELD_SPECS)
+            }
+
             pub fn parse(line: &str) -> (Self, Vec<CwrWarning<'static>>) {
+                Self::parse_with_version(line, None)
+            }
+
+            /// Parse using the field layout of the given CWR version (the default layout when `None`)
+            pub fn parse_with_version(line: &str, layout_version: Option<f32>) -> (Self, Vec<CwrWarning<'static>>) {
+                Self::parse_line(&crate::parsing::CwrLine::new(line), layout_version)
+            }
+
+            /// Parse a source line whose fields are decoded from its own character set
+            pub fn parse_line(line: &crate::parsing::CwrLine<'_>, layout_version: Option<f32>) -> (Self, Vec<CwrWarning<'static>>) {
                 let mut warnings = Vec::new();
 
                 #(#field_parsers)*
@@ -215,44 +361,120 @@ pub fn derive_cwr_record(input: TokenStream) -> TokenStream {
                 (record, warnings)
             }
 
+            /// Structured error for the first warning that rejects the record
+            fn rejection_error(
+                record_type: &str, warnings: &[CwrWarning<'static>], layout_version: Option<f32>,
+            ) -> Option<crate::error::CwrParseError> {
+                let warning = warnings.iter().find(|w| w.is_critical())?;
+                let message = warnings.iter().map(ToString::to_string).collect::<Vec<_>>().join("; ");
+                let mut error = crate::error::RecordParseError::new(crate::error::ParseErrorCode::InvalidFieldValue, message)
+                .with_record_type(record_type)
+                .with_value(warning.source_str.to_string());
+                error.field_name = Some(warning.field_name);
+                error.columns = Self::FIELD_SPECS.iter().find(|spec| spec.name == warning.field_name).map(|spec| {
+                    let (start, len) = layout_version.map_or((spec.start, spec.len), |version| spec.layout_for(version));
+                    start..start + len
+                });
+                Some(error.into())
+            }
+
             /// Compatibility method for existing parser
             #[must_use]
             pub fn from_cwr_line(line: &str) -> Result<crate::error::CwrParseResult<Self>, crate::error::CwrParseError> {
                 // Validate record type matches what we expect
                 if line.len() < 3 {
-                    return Err(crate::error::CwrParseError::BadFormat(
-                        "Line too short to contain record type".to_string()
-                    ));
+                    return Err(crate::error::RecordParseError::new(
+                        crate::error::ParseErrorCode::LineTooShort,
+                        "Line too short to contain record type",
+                    ).into());
                 }
 
                 let (record, warnings) = Self::parse(line);
 
-                // Convert CwrWarning to String for compatibility
-                let string_warnings: Vec<String> = warnings.into_iter()
-                    .map(|w| format!("{}: {}", w.field_title, w.description))
-                    .collect();
-
                 // Check for critical errors
-                let has_critical = string_warnings.iter().any(|w| w.contains("Critical"));
-                if has_critical {
-                    return Err(crate::error::CwrParseError::BadFormat(
-                        string_warnings.join("; ")
-                    ));
+                if let Some(error) = Self::rejection_error(&record.record_type, &warnings, None) {
+                    return Err(error);
                 }
 
+                // Convert CwrWarning to String for compatibility
                 Ok(crate::error::CwrParseResult {
                     record,
-                    warnings: string_warnings,
+                    warnings: warnings.iter().map(ToString::to_string).collect(),
                 })
             }
 
             /// Generate CWR record as bytes with proper character set encoding
+            /// Values longer than their field are truncated and logged; see [`Self::write_cwr_record_bytes`]
             pub fn to_cwr_record_bytes(&self, version: &crate::domain_types::CwrVersion, character_set: &crate::domain_types::CharacterSet) -> Vec<u8> {
-                let mut result = Vec::new();
+                let mut bytes = Vec::with_capacity(Self::FIELD_SPECS.last().map_or(0, |spec| spec.start + spec.len));
+                match self.write_cwr_record(&mut bytes, version, character_set) {
+                    Ok(()) => bytes,
+                    Err(_) => Vec::new(),
+                }
+            }
+
+            /// Write the CWR record, without a line terminator, straight into `writer`
+            /// Values longer than their field are truncated and logged
+            pub fn write_cwr_record<W: std::io::Write>(
+                &self,
+                writer: &mut W,
+                version: &crate::domain_types::CwrVersion,
+                character_set: &crate::domain_types::CharacterSet,
+            ) -> Result<(), crate::error::CwrParseError> {
+                let mut truncations = Vec::new();
+                self.write_cwr_record_with(writer, version, character_set, crate::parsing::FieldOverflow::Truncate, &mut truncations)?;
+                for truncation in &truncations {
+                    tracing::warn!("{}", truncation);
+                }
+                Ok(())
+            }
+
+            /// Generate CWR record bytes, handling values longer than their field as `overflow` says. Truncated
+            /// values are appended to `truncations`.
+            pub fn write_cwr_record_bytes(
+                &self,
+                version: &crate::domain_types::CwrVersion,
+                character_set: &crate::domain_types::CharacterSet,
+                overflow: crate::parsing::FieldOverflow,
+                truncations: &mut Vec<crate::parsing::FieldTruncation>,
+            ) -> Result<Vec<u8>, crate::error::CwrParseError> {
+                let mut bytes = Vec::with_capacity(Self::FIELD_SPECS.last().map_or(0, |spec| spec.start + spec.len));
+                self.write_cwr_record_with(&mut bytes, version, character_set, overflow, truncations)?;
+                Ok(bytes)
+            }
+
+            /// Write the CWR record into `writer`, handling values longer than their field as `overflow` says.
+            /// Truncated values are appended to `truncations`.
+            pub fn write_cwr_record_with<W: std::io::Write>(
+                &self,
+                writer: &mut W,
+                version: &crate::domain_types::CwrVersion,
+                character_set: &crate::domain_types::CharacterSet,
+                overflow: crate::parsing::FieldOverflow,
+                truncations: &mut Vec<crate::parsing::FieldTruncation>,
+            ) -> Result<(), crate::error::CwrParseError> {
+                let mut written = 0;
+                let layout_version = Some(version.0);
 
                 #(#field_writers_bytes)*
 
-                result
+                Ok(())
+            }
+
+            /// Generate CWR record bytes, refusing versions this record type does not exist in
+            pub fn try_to_cwr_record_bytes(&self, version: &crate::domain_types::CwrVersion, character_set: &crate::domain_types::CharacterSet) -> Result<Vec<u8>, crate::error::CwrParseError> {
+                crate::records::check_record_version(&self.record_type, version.0, #record_min_version, #record_max_version)?;
+                Ok(self.to_cwr_record_bytes(version, character_set))
+            }
+        }
+
+        impl crate::records::FieldText for #name {
+            fn field_text(&self, index: usize) -> String {
+                let bytes = match index {
+                    #(#field_text_arms)*
+                    _ => Vec::new(),
+                };
+                String::from_utf8_lossy(&bytes).trim_end().to_string()
             }
         }
 
@@ -271,32 +493,35 @@ pub fn derive_cwr_record(input: TokenStream) -> TokenStream {
 
             #[must_use]
             fn from_cwr_line(line: &str) -> Result<crate::records::ParseResult<Self>, crate::error::CwrParseError> {
+                <Self as crate::records::CwrRecord>::from_cwr_line_with_version(line, None)
+            }
+
+            #[must_use]
+            fn from_cwr_line_with_version(line: &str, version: Option<f32>) -> Result<crate::records::ParseResult<Self>, crate::error::CwrParseError> {
+                <Self as crate::records::CwrRecord>::from_cwr_source(&crate::parsing::CwrLine::new(line), version)
+            }
+
+            #[must_use]
+            fn from_cwr_source(line: &crate::parsing::CwrLine<'_>, version: Option<f32>) -> Result<crate::records::ParseResult<Self>, crate::error::CwrParseError> {
                 // Validate record type matches what we expect
                 if line.len() < 3 {
-                    return Err(crate::error::CwrParseError::BadFormat(
-                        "Line too short to contain record type".to_string()
-                    ));
+                    return Err(crate::error::RecordParseError::new(
+                        crate::error::ParseErrorCode::LineTooShort,
+                        "Line too short to contain record type",
+                    ).into());
                 }
 
-                let (record, warnings) = Self::parse(line);
-
-                // Convert CwrWarning to String for compatibility
-                let string_warnings: Vec<String> = warnings.into_iter()
-                    .map(|w| format!("{}: {}", w.field_title, w.description))
-                    .collect();
+                // Critical warnings stay on the record; the handler's error mode decides whether they stop processing
+                let (record, warnings) = Self::parse_line(line, version);
+                Ok(crate::records::ParseResult { record, warnings })
+            }
 
-                // Check for critical errors
-                let has_critical = string_warnings.iter().any(|w| w.contains("Critical"));
-                if has_critical {
-                    return Err(crate::error::CwrParseError::BadFormat(
-                        string_warnings.join("; ")
-                    ));
-                }
+            fn field_specs() -> &'static [crate::records::FieldSpec] {
+                Self::FIELD_SPECS
+            }
 
-                Ok(crate::records::ParseResult {
-                    record,
-                    warnings: string_warnings,
-                })
+            fn version_range() -> (Option<f32>, Option<f32>) {
+                (#record_min_version, #record_max_version)
             }
 
             fn into_registry(self) -> crate::cwr_registry::CwrRegistry {
@@ -308,6 +533,13 @@ pub fn derive_cwr_record(input: TokenStream) -> TokenStream {
             }
         }
 
+        #[cfg(feature = "arbitrary")]
+        impl<'a> ::arbitrary::Arbitrary<'a> for #name {
+            fn arbitrary(u: &mut ::arbitrary::Unstructured<'a>) -> ::arbitrary::Result<Self> {
+                crate::generate::arbitrary_record(u, &[#(#field_generators),*])
+            }
+        }
+
         #_test_module
     };
 
@@ -318,10 +550,10 @@ fn extract_test_data(attrs: &[syn::Attribute]) -> Option<String> {
     for attr in attrs {
         if attr.path().is_ident("cwr") {
             let result: Result<CwrAttribute, _> = attr.parse_args();
-            if let Ok(cwr_attr) = result {
-                if let Some(test_data) = cwr_attr.test_data {
-                    return Some(test_data.value());
-                }
+            if let Ok(cwr_attr) = result
+                && let Some(test_data) = cwr_attr.test_data
+            {
+                return Some(test_data.value());
             }
         }
     }
@@ -332,26 +564,47 @@ fn extract_validator(attrs: &[syn::Attribute]) -> Option<syn::Ident> {
     for attr in attrs {
         if attr.path().is_ident("cwr") {
             let result: Result<CwrAttribute, _> = attr.parse_args();
-            if let Ok(cwr_attr) = result {
-                if let Some(validator) = cwr_attr.validator {
-                    return Some(validator);
-                }
+            if let Ok(cwr_attr) = result
+                && let Some(validator) = cwr_attr.validator
+            {
+                return Some(validator);
             }
         }
     }
     None
 }
 
+fn extract_version_range(attrs: &[syn::Attribute]) -> (Option<f32>, Option<f32>) {
+    for attr in attrs {
+        if attr.path().is_ident("cwr") {
+            let result: Result<CwrAttribute, _> = attr.parse_args();
+            if let Ok(cwr_attr) = result {
+                let min_version = cwr_attr.min_version.map(|v| v.base10_parse::<f32>().unwrap());
+                let max_version = cwr_attr.max_version.map(|v| v.base10_parse::<f32>().unwrap());
+                return (min_version, max_version);
+            }
+        }
+    }
+    (None, None)
+}
+
+fn quote_optional_version(version: Option<f32>) -> quote::__private::TokenStream {
+    match version {
+        Some(version) => quote! { Some(#version) },
+        None => quote! { None },
+    }
+}
+
 fn extract_record_codes(attrs: &[syn::Attribute], name: &syn::Ident) -> quote::__private::TokenStream {
     // First check for explicit codes attribute
     for attr in attrs {
         if attr.path().is_ident("cwr") {
             let result: Result<CwrAttribute, _> = attr.parse_args();
-            if let Ok(cwr_attr) = result {
-                if let Some(codes) = cwr_attr.codes {
-                    let code_strings: Vec<_> = codes.iter().map(|s| s.value()).collect();
-                    return quote! { &[#(#code_strings),*] };
-                }
+            if let Ok(cwr_attr) = result
+                && let Some(codes) = cwr_attr.codes
+            {
+                let code_strings: Vec<_> = codes.iter().map(|s| s.value()).collect();
+                return quote! { &[#(#code_strings),*] };
             }
         }
     }
@@ -377,6 +630,59 @@ fn generate_registry_variant(name: &syn::Ident) -> quote::__private::TokenStream
     panic!("Could not determine registry variant for struct: {}", name_str);
 }
 
+fn extract_field_validator(attrs: &[syn::Attribute]) -> Option<syn::Ident> {
+    for attr in attrs {
+        if attr.path().is_ident("cwr") {
+            let result: Result<CwrFieldAttribute, _> = attr.parse_args();
+            if let Ok(field_attr) = result {
+                return field_attr.validate;
+            }
+        }
+    }
+    None
+}
+
+/// Per-version `(max_version, start, len)` overrides, sorted by version
+fn extract_field_layouts(attrs: &[syn::Attribute]) -> Vec<(f32, usize, usize)> {
+    for attr in attrs {
+        if attr.path().is_ident("cwr") {
+            let result: Result<CwrFieldAttribute, _> = attr.parse_args();
+            if let Ok(field_attr) = result {
+                let mut layouts: Vec<(f32, usize, usize)> = field_attr
+                    .layouts
+                    .iter()
+                    .map(|layout| {
+                        (
+                            layout.max_version.base10_parse().unwrap(),
+                            layout.start.base10_parse().unwrap(),
+                            layout.len.base10_parse().unwrap(),
+                        )
+                    })
+                    .collect();
+                layouts.sort_by(|a, b| a.0.total_cmp(&b.0));
+                return layouts;
+            }
+        }
+    }
+    Vec::new()
+}
+
+/// Generates an expression evaluating to the field's `(start, len)` for `layout_version`
+fn quote_field_layout(start: usize, len: usize, layouts: &[(f32, usize, usize)]) -> quote::__private::TokenStream {
+    if layouts.is_empty() {
+        return quote! { (#start, #len) };
+    }
+    let arms = layouts.iter().map(|(max_version, start, len)| {
+        quote! { Some(v) if v <= #max_version => (#start, #len), }
+    });
+    quote! {
+        match layout_version {
+            #(#arms)*
+            _ => (#start, #len),
+        }
+    }
+}
+
 fn extract_field_attrs(attrs: &[syn::Attribute]) -> (String, usize, usize, bool, Option<f32>) {
     for attr in attrs {
         if attr.path().is_ident("cwr") {
@@ -400,6 +706,8 @@ struct CwrAttribute {
     test_data: Option<LitStr>,
     codes: Option<Vec<LitStr>>,
     validator: Option<syn::Ident>,
+    min_version: Option<syn::LitFloat>,
+    max_version: Option<syn::LitFloat>,
 }
 
 impl syn::parse::Parse for CwrAttribute {
@@ -407,6 +715,8 @@ impl syn::parse::Parse for CwrAttribute {
         let mut test_data = None;
         let mut codes = None;
         let mut validator = None;
+        let mut min_version = None;
+        let mut max_version = None;
 
         while !input.is_empty() {
             let ident: syn::Ident = input.parse()?;
@@ -428,6 +738,10 @@ impl syn::parse::Parse for CwrAttribute {
                 codes = Some(code_list);
             } else if ident == "validator" {
                 validator = Some(input.parse()?);
+            } else if ident == "min_version" {
+                min_version = Some(input.parse()?);
+            } else if ident == "max_version" {
+                max_version = Some(input.parse()?);
             } else {
                 return Err(syn::Error::new(ident.span(), "Unknown attribute"));
             }
@@ -437,7 +751,7 @@ impl syn::parse::Parse for CwrAttribute {
             }
         }
 
-        Ok(CwrAttribute { test_data, codes, validator })
+        Ok(CwrAttribute { test_data, codes, validator, min_version, max_version })
     }
 }
 
@@ -447,6 +761,45 @@ struct CwrFieldAttribute {
     len: LitInt,
     skip_parse: bool,
     min_version: Option<syn::LitFloat>,
+    layouts: Vec<CwrFieldLayout>,
+    validate: Option<syn::Ident>,
+}
+
+/// `layout(max_version = 2.1, start = ..., len = ...)`: position used for versions up to `max_version`
+struct CwrFieldLayout {
+    max_version: syn::LitFloat,
+    start: LitInt,
+    len: LitInt,
+}
+
+impl syn::parse::Parse for CwrFieldLayout {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let mut max_version = None;
+        let mut start = None;
+        let mut len = None;
+
+        while !input.is_empty() {
+            let ident: syn::Ident = input.parse()?;
+            input.parse::<syn::Token![=]>()?;
+
+            match ident.to_string().as_str() {
+                "max_version" => max_version = Some(input.parse()?),
+                "start" => start = Some(input.parse()?),
+                "len" => len = Some(input.parse()?),
+                _ => return Err(syn::Error::new(ident.span(), "Unknown layout attribute")),
+            }
+
+            if !input.is_empty() {
+                input.parse::<syn::Token![,]>()?;
+            }
+        }
+
+        Ok(CwrFieldLayout {
+            max_version: max_version.ok_or_else(|| input.error("Missing 'max_version' in layout"))?,
+            start: start.ok_or_else(|| input.error("Missing 'start' in layout"))?,
+            len: len.ok_or_else(|| input.error("Missing 'len' in layout"))?,
+        })
+    }
 }
 
 impl syn::parse::Parse for CwrFieldAttribute {
@@ -456,6 +809,8 @@ impl syn::parse::Parse for CwrFieldAttribute {
         let mut len = None;
         let mut skip_parse = false;
         let mut min_version = None;
+        let mut layouts = Vec::new();
+        let mut validate = None;
 
         while !input.is_empty() {
             let ident: syn::Ident = input.parse()?;
@@ -480,6 +835,15 @@ impl syn::parse::Parse for CwrFieldAttribute {
                     input.parse::<syn::Token![=]>()?;
                     min_version = Some(input.parse()?);
                 }
+                "validate" => {
+                    input.parse::<syn::Token![=]>()?;
+                    validate = Some(input.parse()?);
+                }
+                "layout" => {
+                    let content;
+                    syn::parenthesized!(content in input);
+                    layouts.push(content.parse()?);
+                }
                 _ => return Err(syn::Error::new(ident.span(), "Unknown field attribute")),
             }
 
@@ -494,6 +858,8 @@ impl syn::parse::Parse for CwrFieldAttribute {
             len: len.ok_or_else(|| input.error("Missing 'len' attribute"))?,
             skip_parse,
             min_version,
+            layouts,
+            validate,
         })
     }
 }
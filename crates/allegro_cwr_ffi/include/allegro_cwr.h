@@ -0,0 +1,58 @@
+/* C API for the allegro CWR (Common Works Registration) parser */
+
+#ifndef ALLEGRO_CWR_H
+#define ALLEGRO_CWR_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define CWR_OK 0
+#define CWR_END 1
+#define CWR_ERR_NULL_ARGUMENT (-1)
+#define CWR_ERR_INVALID_ARGUMENT (-2)
+#define CWR_ERR_IO (-3)
+#define CWR_ERR_BAD_FORMAT (-4)
+#define CWR_ERR_PARSE (-5)
+#define CWR_ERR_EMPTY_LINE (-10)
+#define CWR_ERR_LINE_TOO_SHORT (-11)
+#define CWR_ERR_UNKNOWN_RECORD_TYPE (-12)
+#define CWR_ERR_INVALID_ENCODING (-13)
+#define CWR_ERR_RECORD_NOT_IN_VERSION (-14)
+#define CWR_ERR_INVALID_FIELD_VALUE (-15)
+
+typedef struct CwrStream CwrStream;
+
+typedef struct CwrRecordInfo {
+    uint64_t line_number;
+    char record_type[4];
+    uint32_t warning_count;
+    float cwr_version;
+} CwrRecordInfo;
+
+/* version_hint <= 0 detects the version from the filename and HDR record */
+int32_t cwr_stream_open(const char *path, float version_hint, CwrStream **out);
+int32_t cwr_stream_open_bytes(const uint8_t *data, size_t len, float version_hint, CwrStream **out);
+
+/* CWR_OK, CWR_END, or a negative code for a line that could not be parsed (the stream can be advanced further) */
+int32_t cwr_stream_next(CwrStream *stream, CwrRecordInfo *info);
+
+/* Caller-owned; release with cwr_string_free. Null when there is no current record or field. */
+char *cwr_stream_record_json(const CwrStream *stream);
+char *cwr_stream_record_field(const CwrStream *stream, const char *field_name);
+
+/* Stream-owned; valid until the next call on the stream */
+const char *cwr_stream_last_error(const CwrStream *stream);
+
+void cwr_stream_close(CwrStream *stream);
+void cwr_string_free(char *s);
+const char *cwr_status_name(int32_t code);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* ALLEGRO_CWR_H */
@@ -0,0 +1,330 @@
+//! C API for embedding the CWR parser
+//!
+//! A stream is opened on a file or a buffer and advanced one line at a time with [`cwr_stream_next`]. The current
+//! record can then be read as JSON or field by field. The declarations are in `include/allegro_cwr.h`.
+//!
+//! Strings returned as `char *` are owned by the caller and must be released with [`cwr_string_free`]; strings
+//! returned as `const char *` are owned by the stream and stay valid until the next call on it.
+
+use allegro_cwr::{CwrParseError, ParseErrorCode, ParseOptions, ParsedRecord};
+use serde_json::{json, Value};
+use std::ffi::{c_char, CStr, CString};
+use std::ptr;
+
+pub const CWR_OK: i32 = 0;
+pub const CWR_END: i32 = 1;
+pub const CWR_ERR_NULL_ARGUMENT: i32 = -1;
+pub const CWR_ERR_INVALID_ARGUMENT: i32 = -2;
+pub const CWR_ERR_IO: i32 = -3;
+pub const CWR_ERR_BAD_FORMAT: i32 = -4;
+pub const CWR_ERR_PARSE: i32 = -5;
+pub const CWR_ERR_EMPTY_LINE: i32 = -10;
+pub const CWR_ERR_LINE_TOO_SHORT: i32 = -11;
+pub const CWR_ERR_UNKNOWN_RECORD_TYPE: i32 = -12;
+pub const CWR_ERR_INVALID_ENCODING: i32 = -13;
+pub const CWR_ERR_RECORD_NOT_IN_VERSION: i32 = -14;
+pub const CWR_ERR_INVALID_FIELD_VALUE: i32 = -15;
+
+/// Summary of the line read by [`cwr_stream_next`]
+#[repr(C)]
+#[derive(Debug, Default)]
+pub struct CwrRecordInfo {
+    pub line_number: u64,
+    /// NUL-terminated record type, e.g. `"NWR"`; empty when the line could not be parsed
+    pub record_type: [c_char; 4],
+    pub warning_count: u32,
+    /// CWR version of the file, e.g. 2.1
+    pub cwr_version: f32,
+}
+
+type Records = Box<dyn Iterator<Item = Result<ParsedRecord, CwrParseError>>>;
+
+/// Opaque parsing stream
+pub struct CwrStream {
+    records: Records,
+    current: Option<ParsedRecord>,
+    last_error: Option<CString>,
+}
+
+impl CwrStream {
+    fn new(records: Records) -> Self {
+        Self { records, current: None, last_error: None }
+    }
+
+    fn set_error(&mut self, message: impl Into<Vec<u8>>) {
+        self.last_error = CString::new(message).ok();
+    }
+}
+
+fn error_code(error: &CwrParseError) -> i32 {
+    match error {
+        CwrParseError::Io(_) => CWR_ERR_IO,
+        CwrParseError::BadFormat(_) | CwrParseError::InvalidHeader { .. } | CwrParseError::BomDetected { .. } => {
+            CWR_ERR_BAD_FORMAT
+        }
+        _ => match error.code() {
+            Some(ParseErrorCode::EmptyLine) => CWR_ERR_EMPTY_LINE,
+            Some(ParseErrorCode::LineTooShort) => CWR_ERR_LINE_TOO_SHORT,
+            Some(ParseErrorCode::UnknownRecordType) => CWR_ERR_UNKNOWN_RECORD_TYPE,
+            Some(ParseErrorCode::InvalidEncoding) => CWR_ERR_INVALID_ENCODING,
+            Some(ParseErrorCode::RecordNotInVersion) => CWR_ERR_RECORD_NOT_IN_VERSION,
+            Some(ParseErrorCode::InvalidFieldValue) => CWR_ERR_INVALID_FIELD_VALUE,
+            None => CWR_ERR_PARSE,
+        },
+    }
+}
+
+fn record_json(parsed: &ParsedRecord) -> Result<Value, serde_json::Error> {
+    Ok(json!({
+        "line_number": parsed.line_number,
+        "record_type": parsed.record.record_type(),
+        "record": serde_json::to_value(&parsed.record)?,
+        "warnings": parsed.warnings,
+    }))
+}
+
+fn into_c_string(s: String) -> *mut c_char {
+    CString::new(s).map(CString::into_raw).unwrap_or(ptr::null_mut())
+}
+
+fn open(result: Result<Records, CwrParseError>, out: *mut *mut CwrStream) -> i32 {
+    match result {
+        Ok(records) => {
+            // SAFETY: callers have checked `out` for null
+            unsafe { *out = Box::into_raw(Box::new(CwrStream::new(records))) };
+            CWR_OK
+        }
+        Err(e) => error_code(&e),
+    }
+}
+
+/// Opens a stream on a CWR file. `version_hint` overrides version detection when greater than zero.
+///
+/// # Safety
+/// `path` must be a valid NUL-terminated string and `out` a valid pointer to write the stream to.
+#[no_mangle]
+pub unsafe extern "C" fn cwr_stream_open(path: *const c_char, version_hint: f32, out: *mut *mut CwrStream) -> i32 {
+    if path.is_null() || out.is_null() {
+        return CWR_ERR_NULL_ARGUMENT;
+    }
+    let Ok(path) = CStr::from_ptr(path).to_str() else {
+        return CWR_ERR_INVALID_ARGUMENT;
+    };
+    let options = ParseOptions { version_hint: (version_hint > 0.0).then_some(version_hint), ..Default::default() };
+    open(allegro_cwr::process_cwr_stream_with_options(path, &options).map(|s| Box::new(s) as Records), out)
+}
+
+/// Opens a stream on CWR content held in memory. The bytes are copied, so the buffer may be released as soon as
+/// this returns.
+///
+/// # Safety
+/// `data` must point to `len` readable bytes and `out` must be a valid pointer to write the stream to.
+#[no_mangle]
+pub unsafe extern "C" fn cwr_stream_open_bytes(
+    data: *const u8, len: usize, version_hint: f32, out: *mut *mut CwrStream,
+) -> i32 {
+    if data.is_null() || out.is_null() {
+        return CWR_ERR_NULL_ARGUMENT;
+    }
+    let bytes = std::slice::from_raw_parts(data, len);
+    let options = ParseOptions { version_hint: (version_hint > 0.0).then_some(version_hint), ..Default::default() };
+    open(allegro_cwr::process_cwr_reader_stream(bytes, &options).map(|s| Box::new(s) as Records), out)
+}
+
+/// Reads the next line. Returns `CWR_OK` with `info` filled in when a record was parsed, `CWR_END` at the end of
+/// the stream, or a negative error code when the line could not be parsed (see [`cwr_stream_last_error`]); the
+/// stream can still be advanced after a line error.
+///
+/// # Safety
+/// `stream` must come from `cwr_stream_open*` and not have been closed; `info` may be null.
+#[no_mangle]
+pub unsafe extern "C" fn cwr_stream_next(stream: *mut CwrStream, info: *mut CwrRecordInfo) -> i32 {
+    let Some(stream) = stream.as_mut() else {
+        return CWR_ERR_NULL_ARGUMENT;
+    };
+    stream.current = None;
+    stream.last_error = None;
+    let mut record_info = CwrRecordInfo::default();
+    let code = match stream.records.next() {
+        None => CWR_END,
+        Some(Ok(parsed)) => {
+            record_info.line_number = parsed.line_number as u64;
+            for (slot, byte) in record_info.record_type.iter_mut().zip(parsed.record.record_type().bytes().take(3)) {
+                *slot = byte as c_char;
+            }
+            record_info.warning_count = parsed.warnings.len() as u32;
+            record_info.cwr_version = parsed.context.cwr_version;
+            stream.current = Some(parsed);
+            CWR_OK
+        }
+        Some(Err(e)) => {
+            if let Some(line_number) = e.record_error().and_then(|details| details.line_number) {
+                record_info.line_number = line_number as u64;
+            }
+            stream.set_error(e.to_string());
+            error_code(&e)
+        }
+    };
+    if let Some(info) = info.as_mut() {
+        *info = record_info;
+    }
+    code
+}
+
+/// Returns the current record as JSON (`line_number`, `record_type`, `record`, `warnings`), or null when there is
+/// no current record. Release with [`cwr_string_free`].
+///
+/// # Safety
+/// `stream` must come from `cwr_stream_open*` and not have been closed.
+#[no_mangle]
+pub unsafe extern "C" fn cwr_stream_record_json(stream: *const CwrStream) -> *mut c_char {
+    let Some(parsed) = stream.as_ref().and_then(|s| s.current.as_ref()) else {
+        return ptr::null_mut();
+    };
+    match record_json(parsed) {
+        Ok(value) => into_c_string(value.to_string()),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// Returns one field of the current record as text (e.g. `"work_title"`), or null when the record has no such
+/// field or the field is empty. Release with [`cwr_string_free`].
+///
+/// # Safety
+/// `stream` must come from `cwr_stream_open*` and not have been closed; `field_name` must be a valid
+/// NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn cwr_stream_record_field(stream: *const CwrStream, field_name: *const c_char) -> *mut c_char {
+    let Some(parsed) = stream.as_ref().and_then(|s| s.current.as_ref()) else {
+        return ptr::null_mut();
+    };
+    if field_name.is_null() {
+        return ptr::null_mut();
+    }
+    let Ok(field_name) = CStr::from_ptr(field_name).to_str() else {
+        return ptr::null_mut();
+    };
+    let Ok(value) = serde_json::to_value(&parsed.record) else {
+        return ptr::null_mut();
+    };
+    // Records serialize as `{"Nwr": {...}}`
+    let fields = match &value {
+        Value::Object(map) if map.len() == 1 => map.values().next(),
+        _ => None,
+    };
+    match fields.and_then(|fields| fields.get(field_name)) {
+        None | Some(Value::Null) => ptr::null_mut(),
+        Some(Value::String(s)) => into_c_string(s.clone()),
+        Some(other) => into_c_string(other.to_string()),
+    }
+}
+
+/// Returns the message for the last line error, or null. Owned by the stream; valid until the next call on it.
+///
+/// # Safety
+/// `stream` must come from `cwr_stream_open*` and not have been closed.
+#[no_mangle]
+pub unsafe extern "C" fn cwr_stream_last_error(stream: *const CwrStream) -> *const c_char {
+    stream.as_ref().and_then(|s| s.last_error.as_ref()).map_or(ptr::null(), |message| message.as_ptr())
+}
+
+/// Closes a stream. Null is ignored.
+///
+/// # Safety
+/// `stream` must come from `cwr_stream_open*` and not already have been closed.
+#[no_mangle]
+pub unsafe extern "C" fn cwr_stream_close(stream: *mut CwrStream) {
+    if !stream.is_null() {
+        drop(Box::from_raw(stream));
+    }
+}
+
+/// Releases a string returned by this library. Null is ignored.
+///
+/// # Safety
+/// `s` must have been returned by this library as `char *` and not already released.
+#[no_mangle]
+pub unsafe extern "C" fn cwr_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// Returns a static, NUL-terminated name for a status code, e.g. `"unknown_record_type"`
+#[no_mangle]
+pub extern "C" fn cwr_status_name(code: i32) -> *const c_char {
+    let name: &'static CStr = match code {
+        CWR_OK => c"ok",
+        CWR_END => c"end",
+        CWR_ERR_NULL_ARGUMENT => c"null_argument",
+        CWR_ERR_INVALID_ARGUMENT => c"invalid_argument",
+        CWR_ERR_IO => c"io",
+        CWR_ERR_BAD_FORMAT => c"bad_format",
+        CWR_ERR_EMPTY_LINE => c"empty_line",
+        CWR_ERR_LINE_TOO_SHORT => c"line_too_short",
+        CWR_ERR_UNKNOWN_RECORD_TYPE => c"unknown_record_type",
+        CWR_ERR_INVALID_ENCODING => c"invalid_encoding",
+        CWR_ERR_RECORD_NOT_IN_VERSION => c"record_not_in_version",
+        CWR_ERR_INVALID_FIELD_VALUE => c"invalid_field_value",
+        _ => c"parse",
+    };
+    name.as_ptr()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = concat!(
+        "HDRPB285606836WARNER CHAPPELL MUSIC PUBLISHING LTD         01.102022122112541120221221\n",
+        "GRHNWR0000102.100000000000  \n",
+        "XYZ garbage\n",
+        "GRT000010000000100000003\n",
+        "TRL000000020000000200000003\n",
+    );
+
+    #[test]
+    fn test_stream_over_bytes() {
+        unsafe {
+            let mut stream = ptr::null_mut();
+            assert_eq!(cwr_stream_open_bytes(SAMPLE.as_ptr(), SAMPLE.len(), 0.0, &mut stream), CWR_OK);
+
+            let mut info = CwrRecordInfo::default();
+            assert_eq!(cwr_stream_next(stream, &mut info), CWR_OK);
+            assert_eq!(CStr::from_ptr(info.record_type.as_ptr()).to_str().unwrap(), "HDR");
+            assert_eq!(info.line_number, 1);
+
+            let json = cwr_stream_record_json(stream);
+            let value: Value = serde_json::from_str(CStr::from_ptr(json).to_str().unwrap()).unwrap();
+            assert_eq!(value["record_type"], "HDR");
+            cwr_string_free(json);
+
+            let name = cwr_stream_record_field(stream, c"sender_name".as_ptr());
+            assert_eq!(CStr::from_ptr(name).to_str().unwrap(), "WARNER CHAPPELL MUSIC PUBLISHING LTD");
+            cwr_string_free(name);
+            assert!(cwr_stream_record_field(stream, c"no_such_field".as_ptr()).is_null());
+
+            assert_eq!(cwr_stream_next(stream, &mut info), CWR_OK);
+            assert_eq!(cwr_stream_next(stream, &mut info), CWR_ERR_UNKNOWN_RECORD_TYPE);
+            assert_eq!(info.line_number, 3);
+            assert!(!cwr_stream_last_error(stream).is_null());
+            assert!(cwr_stream_record_json(stream).is_null());
+
+            assert_eq!(cwr_stream_next(stream, &mut info), CWR_OK);
+            assert_eq!(cwr_stream_next(stream, ptr::null_mut()), CWR_OK);
+            assert_eq!(cwr_stream_next(stream, &mut info), CWR_END);
+            cwr_stream_close(stream);
+        }
+    }
+
+    #[test]
+    fn test_open_errors() {
+        unsafe {
+            let mut stream = ptr::null_mut();
+            assert_eq!(cwr_stream_open(ptr::null(), 0.0, &mut stream), CWR_ERR_NULL_ARGUMENT);
+            assert_eq!(cwr_stream_open_bytes(b"NOT CWR".as_ptr(), 7, 0.0, &mut stream), CWR_ERR_BAD_FORMAT);
+            assert!(stream.is_null());
+            assert_eq!(CStr::from_ptr(cwr_status_name(CWR_ERR_BAD_FORMAT)).to_str().unwrap(), "bad_format");
+        }
+    }
+}
@@ -0,0 +1,282 @@
+use std::ffi::OsString;
+use std::io;
+use std::process;
+use std::time::Instant;
+
+use crate::{FieldNaming, JsonLayout, JsonOptions};
+use allegro_cwr::parser::is_cwr_file;
+use allegro_cwr_cli::{
+    BaseConfig, BatchSummary, Direction, check_log_format, create_output, get_output_filename_with_default_extension,
+    get_value, init_logging_and_parse_args, output_file, status, status_error, watch_directory, watch_output_filename,
+};
+use tracing::info;
+
+#[derive(Default)]
+struct Config {
+    base: BaseConfig,
+    direction: Direction,
+    output_filename: Option<String>,
+    json_options: JsonOptions,
+}
+
+fn parse_args(args: Vec<OsString>) -> Result<Config, String> {
+    let mut config = Config::default();
+    let mut parser = lexopt::Parser::from_args(args);
+
+    while let Ok(Some(arg)) = parser.next() {
+        match arg {
+            lexopt::Arg::Long("cwr") => {
+                let version_str = get_value(&mut parser, "cwr")?;
+                config.base.set_cwr_version(&version_str)?;
+            }
+            lexopt::Arg::Short('o') | lexopt::Arg::Long("output") => {
+                let output_filename = get_value(&mut parser, "output")?;
+                config.output_filename = Some(output_filename);
+            }
+            lexopt::Arg::Long("grouped") => {
+                config.json_options.layout = JsonLayout::Grouped;
+            }
+            lexopt::Arg::Long("camel-case") => {
+                config.json_options.field_naming = FieldNaming::CamelCase;
+            }
+            lexopt::Arg::Long("field-titles") => {
+                config.json_options.include_field_titles = true;
+            }
+            lexopt::Arg::Long("flat") => {
+                config.json_options.flatten_domain_types = true;
+            }
+            lexopt::Arg::Long("raw-lines") => {
+                config.json_options.include_raw_lines = true;
+            }
+            lexopt::Arg::Long("structured-warnings") => {
+                config.json_options.structured_warnings = true;
+            }
+            lexopt::Arg::Long("watch") => {
+                config.base.watch_dir = Some(get_value(&mut parser, "watch")?);
+            }
+            lexopt::Arg::Long("poll-interval") => {
+                let seconds = get_value(&mut parser, "poll-interval")?;
+                config.base.set_poll_interval(&seconds)?;
+            }
+            lexopt::Arg::Value(val) => {
+                config.base.add_input_file(val.to_string_lossy().to_string())?;
+            }
+            lexopt::Arg::Short('q' | 'v') | lexopt::Arg::Long("quiet" | "verbose") => {}
+            lexopt::Arg::Long("log-format") => check_log_format(&mut parser)?,
+            lexopt::Arg::Short('h') | lexopt::Arg::Long("help") => {
+                print_help();
+                process::exit(0);
+            }
+            _ => {
+                return Err("Unknown argument".to_string());
+            }
+        }
+    }
+
+    config.base.finalize();
+    Ok(config)
+}
+
+/// Runs the command line tool with `args` (excluding the program name)
+pub fn run(args: impl IntoIterator<Item = OsString>) {
+    run_with_direction(args, Direction::Auto);
+}
+
+/// Like [`run`], but rejects input that would convert the other way than `direction`
+pub fn run_with_direction(args: impl IntoIterator<Item = OsString>, direction: Direction) {
+    let args: Vec<OsString> = args.into_iter().collect();
+    let config = init_logging_and_parse_args(|| {
+        parse_args(args).inspect_err(|_| {
+            print_help();
+        })
+    });
+    let config = Config { direction, ..config };
+
+    if let Some(watch_dir) = config.base.watch_dir.as_deref() {
+        watch_directory(watch_dir, config.base.poll_interval, |input_filename| {
+            let output_filename = watch_output_filename(watch_dir, input_filename, "json");
+            crate::process_cwr_to_json_with_options(
+                input_filename,
+                config.base.cwr_version,
+                Some(&output_filename),
+                config.json_options,
+            )
+            .map_err(|e| e.to_string())
+        });
+    }
+
+    let start_time = Instant::now();
+
+    if config.base.read_stdin {
+        process_stdin(&config, start_time);
+    } else {
+        process_files(&config, start_time);
+    }
+}
+
+fn process_stdin(config: &Config, start_time: Instant) {
+    info!("Reading CWR data from stdin");
+    let mut stdin = io::stdin().lock();
+    let is_cwr = match crate::starts_with_json(&mut stdin) {
+        Ok(is_json) => !is_json,
+        Err(e) => {
+            status_error!("Error reading from stdin: {}", e);
+            process::exit(1);
+        }
+    };
+    if let Err(e) = config.direction.check(is_cwr, "JSON") {
+        status_error!("Error processing stdin: {}", e);
+        process::exit(1);
+    }
+
+    let result = create_output(config.output_filename.as_deref()).map_err(Into::into).and_then(|output| {
+        if is_cwr {
+            crate::process_cwr_reader_to_json_to_writer(stdin, config.base.cwr_version, output, config.json_options)
+        } else {
+            crate::process_json_reader_to_cwr_to_writer(stdin, io::BufWriter::new(output))
+        }
+    });
+    let elapsed_time = start_time.elapsed();
+
+    let count = match result {
+        Ok(c) => c,
+        Err(e) => {
+            status_error!("Error processing stdin after {:.2?}: {}", elapsed_time, e);
+            process::exit(1);
+        }
+    };
+
+    status!(
+        "Successfully processed {} CWR records from stdin in {:.2?}",
+        allegro_cwr::format_int_with_commas(count as i64),
+        elapsed_time
+    );
+}
+
+fn process_files(config: &Config, start_time: Instant) {
+    let mut summary = BatchSummary::default();
+
+    for input_filename in &config.base.input_files {
+        info!("Processing CWR file: {}", input_filename);
+
+        let is_cwr = match is_cwr_file(input_filename) {
+            Ok(is_cwr) => is_cwr,
+            Err(e) => {
+                status_error!("Error reading file '{}': {}", input_filename, e);
+                summary.add_failure(input_filename, &e);
+                continue;
+            }
+        };
+        if let Err(e) = config.direction.check(is_cwr, "JSON") {
+            status_error!("Error processing file '{}': {}", input_filename, e);
+            summary.add_failure(input_filename, &e);
+            continue;
+        }
+
+        let output_filename = get_output_filename_with_default_extension(
+            config.output_filename.as_deref(),
+            input_filename,
+            config.base.input_files.len(),
+            summary.files_processed(),
+            if is_cwr { "json" } else { "cwr" },
+        );
+
+        let result = if is_cwr {
+            crate::process_cwr_to_json_with_options(
+                input_filename,
+                config.base.cwr_version,
+                output_file(output_filename.as_deref()),
+                config.json_options,
+            )
+        } else {
+            crate::process_json_to_cwr_with_version_and_output(
+                input_filename,
+                config.base.cwr_version,
+                output_file(output_filename.as_deref()),
+            )
+        };
+
+        match result {
+            Ok(count) => {
+                summary.add_success(input_filename, count);
+                info!("Processed {} records from '{}'", count, input_filename);
+            }
+            Err(e) => {
+                status_error!("Error processing file '{}': {}", input_filename, e);
+                summary.add_failure(input_filename, &e);
+            }
+        }
+    }
+
+    let elapsed_time = start_time.elapsed();
+    info!("Processing completed");
+
+    if config.base.input_files.len() == 1 {
+        let failed_files = summary.failed_files();
+        if !failed_files.is_empty() {
+            status_error!("Failed to process {} file(s): {}", failed_files.len(), failed_files.join(", "));
+            process::exit(1);
+        }
+
+        status!(
+            "Processed {} CWR records from '{}' in {:.2?}",
+            allegro_cwr::format_int_with_commas(summary.total_records() as i64),
+            &config.base.input_files[0],
+            elapsed_time
+        );
+    } else {
+        summary.print("Processed", elapsed_time);
+        if !summary.failed_files().is_empty() {
+            process::exit(1);
+        }
+    }
+}
+
+fn print_help() {
+    eprintln!("Usage: cwr-json [OPTIONS] [FILES...]");
+    eprintln!();
+    eprintln!("Arguments:");
+    eprintln!(
+        "  [FILES...]          CWR or JSON files to process. If no files specified or a file is -, reads from stdin"
+    );
+    eprintln!("                      Directories and patterns such as \'*.V21\' expand to the matching CWR files");
+    eprintln!();
+    eprintln!("Options:");
+    eprintln!(
+        "  -o, --output <file>      Output file path, or - for stdout (format auto-detected or stdout for stdin)"
+    );
+    eprintln!(
+        "      --cwr <version>      CWR version (2.0, 2.1, 2.2). Auto-detected from filename (.Vxx) or file content if not specified"
+    );
+    eprintln!("      --raw-lines          Include each record's source line as received (raw_line)");
+    eprintln!(
+        "      --structured-warnings Write each warning as an object with its level, field, columns and source text"
+    );
+    eprintln!(
+        "      --grouped            Nest records under their group and transaction (groups[].transactions[].records[])"
+    );
+    eprintln!("      --camel-case         Write JSON keys in camelCase (e.g. workTitle) instead of snake_case");
+    eprintln!("      --field-titles       Add the CWR specification title of each field to every record");
+    eprintln!(
+        "      --flat               Write field values as plain CWR text (e.g. \"PB\" rather than \"Publisher\")"
+    );
+    eprintln!("                           JSON written with these options cannot be converted back to CWR");
+    eprintln!(
+        "      --watch <dir>        Process CWR files dropped into <dir>, moving them to <dir>/done or <dir>/failed"
+    );
+    eprintln!("      --poll-interval <s>  Seconds between scans of the watched directory (default: 2)");
+    eprintln!("  -q, --quiet              Only print errors");
+    eprintln!("  -v, --verbose            Also print info logging");
+    eprintln!("      --log-format <fmt>   Message format on stderr: text (default) or json");
+    eprintln!("  -h, --help               Show this help message");
+    eprintln!();
+    eprintln!("Bidirectional converter between CWR and JSON formats.");
+    eprintln!("Input format auto-detected by content (CWR starts with 'HDR', JSON starts with '{{')");
+    eprintln!();
+    eprintln!("Examples:");
+    eprintln!("  cwr-json file.cwr                            # Convert CWR to JSON");
+    eprintln!("  cwr-json file.json                           # Convert JSON to CWR");
+    eprintln!("  cwr-json -o output.json input.cwr            # Specify output file");
+    eprintln!("  cwr-json *.cwr *.json                        # Process multiple files");
+    eprintln!("  cat input.cwr | cwr-json                     # Process from stdin");
+}
@@ -2,18 +2,43 @@
 //!
 //! This crate provides JSON output functionality for CWR records.
 
+pub mod cli;
+mod options;
+mod read;
+mod stream;
+
+pub use options::{FieldNaming, JsonLayout, JsonOptions};
+pub use read::read_json_records;
+
 use allegro_cwr::CwrRegistry;
 use serde::Deserialize;
+use std::borrow::Cow;
 use std::fs::File;
-use std::io::{self, BufReader, Write};
+use std::io::{self, BufReader, BufWriter, Write};
+use stream::{JsonStream, OrderedObject};
 
 /// JSON implementation of CwrHandler trait
 pub struct JsonHandler<W: Write> {
     output_count: usize,
     error_count: usize,
-    first_record: bool,
-    context_written: bool,
-    writer: W,
+    options: JsonOptions,
+    stream: JsonStream<W>,
+    grouped: GroupedState,
+}
+
+/// Position within the [`JsonLayout::Grouped`] document. Records outside any transaction other than the control
+/// records are buffered and written as `other_records` when their group (or the document) closes.
+#[derive(Default)]
+struct GroupedState {
+    groups_open: bool,
+    in_group: bool,
+    in_transaction: bool,
+    /// The GRH was written as the group header by `begin_group`
+    skip_group_header: bool,
+    group_trailer: Option<serde_json::Value>,
+    group_other: Vec<serde_json::Value>,
+    trailer: Option<serde_json::Value>,
+    other: Vec<serde_json::Value>,
 }
 
 impl Default for JsonHandler<io::Stdout> {
@@ -23,11 +48,159 @@ impl Default for JsonHandler<io::Stdout> {
 }
 
 impl<W: Write> JsonHandler<W> {
-    pub fn new(mut writer: W) -> Self {
-        // Start JSON object
-        writeln!(writer, "{{").expect("Failed to write to output");
+    pub fn new(writer: W) -> Self {
+        Self::with_options(writer, JsonOptions::default())
+    }
+
+    pub fn with_options(writer: W, options: JsonOptions) -> Self {
+        JsonHandler {
+            output_count: 0,
+            error_count: 0,
+            options,
+            stream: JsonStream::new(writer),
+            grouped: GroupedState::default(),
+        }
+    }
+
+    fn key(&self, name: &'static str) -> Cow<'static, str> {
+        self.options.field_naming.apply(name)
+    }
+
+    /// Writes the context once, before the first record or error
+    fn ensure_started(&mut self, cwr_version: Option<f32>, file_id: i64) -> io::Result<()> {
+        if self.stream.has_started() {
+            return Ok(());
+        }
+        self.stream.begin_object()?;
+        self.stream.entry("context", &OutputContext { naming: self.options.field_naming, cwr_version, file_id })?;
+        if self.options.layout == JsonLayout::Flat {
+            self.stream.key("records")?;
+            self.stream.begin_array()?;
+        }
+        Ok(())
+    }
+
+    fn record_entry(&self, parsed_record: &allegro_cwr::ParsedRecord) -> serde_json::Value {
+        options::record_value(parsed_record, &self.options).unwrap_or_else(|e| {
+            // Fallback to basic metadata if serialization fails
+            serde_json::json!({
+                self.key("line_number"): parsed_record.line_number,
+                self.key("record_type"): parsed_record.record.record_type(),
+                "status": "serialization_error",
+                self.key("error_message"): e.to_string(),
+            })
+        })
+    }
+
+    /// Writes a record or error entry where it belongs in the current layout
+    fn write_entry(&mut self, entry: &impl serde::Serialize, record_type: Option<&str>) -> io::Result<()> {
+        if self.options.layout == JsonLayout::Flat || self.grouped.in_transaction {
+            return self.stream.value(entry);
+        }
+
+        let value = serde_json::to_value(entry)?;
+        match record_type {
+            Some("GRH") if self.grouped.skip_group_header => self.grouped.skip_group_header = false,
+            Some("GRT") if self.grouped.in_group && self.grouped.group_trailer.is_none() => {
+                self.grouped.group_trailer = Some(value)
+            }
+            Some("HDR") if !self.grouped.groups_open && self.stream.depth() == 1 => {
+                let key = self.key("header");
+                self.stream.entry(&key, &value)?;
+            }
+            Some("TRL") if self.grouped.trailer.is_none() => self.grouped.trailer = Some(value),
+            _ if self.grouped.in_group => self.grouped.group_other.push(value),
+            _ => self.grouped.other.push(value),
+        }
+        Ok(())
+    }
+
+    /// Opens a group object in the `groups` array, with `header` (the GRH) if there is one
+    fn open_group(&mut self, header: Option<&allegro_cwr::ParsedRecord>) -> io::Result<()> {
+        if !self.grouped.groups_open {
+            let key = self.key("groups");
+            self.stream.key(&key)?;
+            self.stream.begin_array()?;
+            self.grouped.groups_open = true;
+        }
+        self.stream.begin_object()?;
+        if let Some(header) = header {
+            let entry = self.record_entry(header);
+            let key = self.key("header");
+            self.stream.entry(&key, &entry)?;
+            self.grouped.skip_group_header = true;
+        }
+        let key = self.key("transactions");
+        self.stream.key(&key)?;
+        self.stream.begin_array()?;
+        self.grouped.in_group = true;
+        Ok(())
+    }
+
+    fn close_group(&mut self) -> io::Result<()> {
+        if self.grouped.in_transaction {
+            self.close_transaction()?;
+        }
+        // transactions array
+        self.stream.end()?;
+        let other = std::mem::take(&mut self.grouped.group_other);
+        if !other.is_empty() {
+            let key = self.key("other_records");
+            self.stream.entry(&key, &other)?;
+        }
+        if let Some(trailer) = self.grouped.group_trailer.take() {
+            let key = self.key("trailer");
+            self.stream.entry(&key, &trailer)?;
+        }
+        self.stream.end()?;
+        self.grouped.in_group = false;
+        self.grouped.skip_group_header = false;
+        Ok(())
+    }
 
-        JsonHandler { output_count: 0, error_count: 0, first_record: true, context_written: false, writer }
+    fn close_transaction(&mut self) -> io::Result<()> {
+        // records array and transaction object
+        self.stream.end()?;
+        self.stream.end()?;
+        self.grouped.in_transaction = false;
+        Ok(())
+    }
+
+    fn finish_grouped(&mut self) -> io::Result<()> {
+        if self.grouped.in_group {
+            self.close_group()?;
+        }
+        if self.grouped.groups_open {
+            self.stream.end()?;
+            self.grouped.groups_open = false;
+        }
+        let other = std::mem::take(&mut self.grouped.other);
+        if !other.is_empty() {
+            let key = self.key("other_records");
+            self.stream.entry(&key, &other)?;
+        }
+        if let Some(trailer) = self.grouped.trailer.take() {
+            let key = self.key("trailer");
+            self.stream.entry(&key, &trailer)?;
+        }
+        Ok(())
+    }
+}
+
+/// The `context` object. Serialized by hand so the version is written as an `f32` (`2.1`, not `2.0999999046325684`)
+struct OutputContext {
+    naming: FieldNaming,
+    cwr_version: Option<f32>,
+    file_id: i64,
+}
+
+impl serde::Serialize for OutputContext {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
+        let mut map = serializer.serialize_map(Some(2))?;
+        map.serialize_entry(&self.naming.apply("cwr_version"), &self.cwr_version)?;
+        map.serialize_entry(&self.naming.apply("file_id"), &self.file_id)?;
+        map.end()
     }
 }
 
@@ -35,45 +208,11 @@ impl<W: Write> allegro_cwr::CwrHandler for JsonHandler<W> {
     type Error = std::io::Error;
 
     fn process_record(&mut self, parsed_record: allegro_cwr::ParsedRecord) -> Result<(), Self::Error> {
-        // Write context once at the beginning
-        if !self.context_written {
-            writeln!(self.writer, "  \"context\": {{")?;
-            writeln!(self.writer, "    \"cwr_version\": {},", parsed_record.context.cwr_version)?;
-            writeln!(self.writer, "    \"file_id\": {}", parsed_record.context.file_id)?;
-            writeln!(self.writer, "  }},")?;
-            writeln!(self.writer, "  \"records\": [")?;
-            self.context_written = true;
-        }
-
-        if !self.first_record {
-            writeln!(self.writer, ",")?;
-        }
-
-        // Create a simplified record without context
-        let record_without_context = serde_json::json!({
-            "line_number": parsed_record.line_number,
-            "record": parsed_record.record,
-            "warnings": parsed_record.warnings
-        });
-
-        match serde_json::to_string_pretty(&record_without_context) {
-            Ok(json_str) => {
-                // Indent the JSON to match our array formatting
-                let indented_json = json_str.lines().map(|line| format!("    {}", line)).collect::<Vec<_>>().join("\n");
-                write!(self.writer, "{}", indented_json)?;
-            }
-            Err(e) => {
-                // Fallback to basic metadata if serialization fails
-                writeln!(self.writer, "    {{")?;
-                writeln!(self.writer, "      \"line_number\": {},", parsed_record.line_number)?;
-                writeln!(self.writer, "      \"record_type\": \"{}\",", parsed_record.record.record_type())?;
-                writeln!(self.writer, "      \"status\": \"serialization_error\",")?;
-                writeln!(self.writer, "      \"error_message\": \"{}\"", e.to_string().replace('"', "\\\""))?;
-                write!(self.writer, "    }}")?;
-            }
-        }
+        self.ensure_started(Some(parsed_record.context.cwr_version), parsed_record.context.file_id)?;
+
+        let entry = self.record_entry(&parsed_record);
+        self.write_entry(&entry, Some(parsed_record.record.record_type()))?;
 
-        self.first_record = false;
         self.output_count += 1;
         Ok(())
     }
@@ -81,53 +220,97 @@ impl<W: Write> allegro_cwr::CwrHandler for JsonHandler<W> {
     fn handle_parse_error(
         &mut self, line_number: usize, error: &allegro_cwr::CwrParseError,
     ) -> Result<(), Self::Error> {
-        // Initialize context if this is the first thing we encounter
-        if !self.context_written {
-            writeln!(self.writer, "  \"context\": {{")?;
-            writeln!(self.writer, "    \"cwr_version\": null,")?;
-            writeln!(self.writer, "    \"file_id\": 0")?;
-            writeln!(self.writer, "  }},")?;
-            writeln!(self.writer, "  \"records\": [")?;
-            self.context_written = true;
-        }
-
-        if !self.first_record {
-            writeln!(self.writer, ",")?;
+        self.ensure_started(None, 0)?;
+
+        let mut entry = vec![
+            (self.key("line_number"), serde_json::json!(line_number)),
+            (Cow::Borrowed("status"), serde_json::json!("error")),
+        ];
+        if let Some(details) = error.record_error() {
+            entry.extend([
+                (self.key("error_code"), serde_json::json!(details.code.as_str())),
+                (self.key("record_type"), serde_json::json!(details.record_type)),
+                (self.key("field"), serde_json::json!(details.field_name)),
+                (
+                    self.key("columns"),
+                    serde_json::json!(details.columns.as_ref().map(|columns| [columns.start, columns.end])),
+                ),
+                (self.key("value"), serde_json::json!(details.value)),
+            ]);
         }
+        entry.push((self.key("error_message"), serde_json::json!(error.to_string())));
+        self.write_entry(&OrderedObject(entry), None)?;
 
-        writeln!(self.writer, "    {{")?;
-        writeln!(self.writer, "      \"line_number\": {},", line_number)?;
-        writeln!(self.writer, "      \"status\": \"error\",")?;
-        writeln!(self.writer, "      \"error_message\": \"{}\"", error.to_string().replace('"', "\\\""))?;
-        write!(self.writer, "    }}")?;
-
-        self.first_record = false;
         self.error_count += 1;
         Ok(())
     }
 
     fn handle_warnings(
-        &mut self, _line_number: usize, _record_type: &str, warnings: &[String],
+        &mut self, _line_number: usize, _record_type: &str, warnings: &[allegro_cwr::parsing::CwrWarning<'static>],
     ) -> Result<(), Self::Error> {
         // Warnings are now included in each record's warnings array, so we don't need separate warning objects
         self.error_count += warnings.len();
         Ok(())
     }
 
-    fn finalize(&mut self) -> Result<(), Self::Error> {
-        // Close records array and main object
-        if self.context_written {
-            writeln!(self.writer)?;
-            writeln!(self.writer, "  ]")?;
+    fn begin_group(&mut self, header: &allegro_cwr::ParsedRecord) -> Result<(), Self::Error> {
+        if self.options.layout != JsonLayout::Grouped {
+            return Ok(());
+        }
+        self.ensure_started(Some(header.context.cwr_version), header.context.file_id)?;
+        self.open_group(Some(header))
+    }
+
+    fn end_group(&mut self) -> Result<(), Self::Error> {
+        if self.options.layout != JsonLayout::Grouped || !self.grouped.in_group {
+            return Ok(());
+        }
+        self.close_group()
+    }
+
+    fn begin_transaction(&mut self, header: &allegro_cwr::ParsedRecord) -> Result<(), Self::Error> {
+        if self.options.layout != JsonLayout::Grouped {
+            return Ok(());
         }
-        writeln!(self.writer, "}}")?;
-        self.writer.flush()?;
+        self.ensure_started(Some(header.context.cwr_version), header.context.file_id)?;
+        // Transactions outside a GRH/GRT pair go in a group without a header
+        if !self.grouped.in_group {
+            self.open_group(None)?;
+        }
+        self.stream.begin_object()?;
+        let key = self.key("records");
+        self.stream.key(&key)?;
+        self.stream.begin_array()?;
+        self.grouped.in_transaction = true;
         Ok(())
     }
 
+    fn end_transaction(&mut self) -> Result<(), Self::Error> {
+        if self.options.layout != JsonLayout::Grouped || !self.grouped.in_transaction {
+            return Ok(());
+        }
+        self.close_transaction()
+    }
+
+    fn finalize(&mut self) -> Result<(), Self::Error> {
+        if self.options.layout == JsonLayout::Grouped && self.stream.has_started() {
+            self.finish_grouped()?;
+        }
+        self.stream.finish()
+    }
+
     fn get_report(&self) -> String {
         format!("JSON processing complete:\n  Records output: {}\n  Errors: {}", self.output_count, self.error_count)
     }
+
+    fn report(&self) -> allegro_cwr::HandlerReport {
+        allegro_cwr::HandlerReport::Output(allegro_cwr::OutputReport {
+            format: "json".to_string(),
+            destination: None,
+            records: self.output_count,
+            errors: self.error_count,
+        })
+    }
 }
 
 /// Convenience function to process CWR file and output JSON
@@ -146,27 +329,77 @@ pub fn process_cwr_to_json_with_version(
 pub fn process_cwr_to_json_with_version_and_output(
     input_filename: &str, version_hint: Option<f32>, output_filename: Option<&str>,
 ) -> Result<usize, Box<dyn std::error::Error>> {
-    let report = match output_filename {
+    process_cwr_to_json_with_options(input_filename, version_hint, output_filename, JsonOptions::default())
+}
+
+/// Convenience function to process CWR file and output JSON using the given output options
+pub fn process_cwr_to_json_with_options(
+    input_filename: &str, version_hint: Option<f32>, output_filename: Option<&str>, options: JsonOptions,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    match output_filename {
         Some(filename) => {
-            let file = std::fs::File::create(filename)?;
-            let handler = JsonHandler::new(file);
-            allegro_cwr::process_cwr_with_handler_and_version(input_filename, handler, version_hint)?
-        }
-        None => {
-            let handler = JsonHandler::new(io::stdout());
-            allegro_cwr::process_cwr_with_handler_and_version(input_filename, handler, version_hint)?
+            process_cwr_to_json_to_writer(input_filename, version_hint, std::fs::File::create(filename)?, options)
         }
-    };
+        None => process_cwr_to_json_to_writer(input_filename, version_hint, io::stdout(), options),
+    }
+}
+
+/// Converts a CWR file to JSON written to `writer` (stdout, a pipe, an in-memory buffer, ...). Returns the number
+/// of records written.
+pub fn process_cwr_to_json_to_writer<W: Write>(
+    input_filename: &str, version_hint: Option<f32>, writer: W, options: JsonOptions,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let parse_options =
+        allegro_cwr::ParseOptions { version_hint, keep_raw_lines: options.include_raw_lines, ..Default::default() };
+    let handler = JsonHandler::with_options(writer, options);
+    let summary = allegro_cwr::process_cwr_with_handler_and_options(input_filename, handler, &parse_options)?;
+    Ok(summary.handler_report.output().map_or(0, |output| output.records))
+}
+
+/// Like [`process_cwr_to_json_to_writer`], for a CWR file read from `reader` (e.g. stdin)
+pub fn process_cwr_reader_to_json_to_writer<R: io::Read, W: Write>(
+    reader: R, version_hint: Option<f32>, writer: W, options: JsonOptions,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let parse_options =
+        allegro_cwr::ParseOptions { version_hint, keep_raw_lines: options.include_raw_lines, ..Default::default() };
+    let handler = JsonHandler::with_options(writer, options);
+    let summary =
+        allegro_cwr::process_cwr_reader_with_handler_and_options("<reader>", reader, handler, &parse_options)?;
+    Ok(summary.handler_report.output().map_or(0, |output| output.records))
+}
+
+/// Runs the records of a JSON file written by [`JsonHandler`] through any handler, without converting them back to
+/// CWR lines first. Reads the same layout as [`read_json_records`].
+pub fn process_json_with_handler<H: allegro_cwr::CwrHandler>(
+    input_filename: &str, handler: H,
+) -> Result<allegro_cwr::ProcessingSummary, Box<dyn std::error::Error>>
+where
+    H::Error: 'static,
+{
+    process_json_reader_with_handler(input_filename, BufReader::new(File::open(input_filename)?), handler)
+}
 
-    // Extract count from report
-    let output_count = report
-        .lines()
-        .find(|line| line.contains("Records output:"))
-        .and_then(|line| line.split(':').nth(1))
-        .and_then(|s| s.trim().parse::<usize>().ok())
-        .unwrap_or(0);
+/// Like [`process_json_with_handler`], for JSON read from `reader` (e.g. stdin). `source` names the input in log
+/// messages.
+pub fn process_json_reader_with_handler<R: io::Read, H: allegro_cwr::CwrHandler>(
+    source: &str, reader: R, handler: H,
+) -> Result<allegro_cwr::ProcessingSummary, Box<dyn std::error::Error>>
+where
+    H::Error: 'static,
+{
+    let records = read_json_records(reader)?;
+    allegro_cwr::process_records_with_handler(source, records.into_iter(), handler)
+}
+
+/// Whether the file starts with a JSON object, as [`JsonHandler`] output does
+pub fn is_json_file(filename: &str) -> io::Result<bool> {
+    starts_with_json(&mut BufReader::new(File::open(filename)?))
+}
 
-    Ok(output_count)
+/// Whether the content of `reader` starts with a JSON object, judged from what it has buffered so nothing is
+/// consumed; lets stdin be told apart from CWR before it is read
+pub fn starts_with_json<R: io::BufRead>(reader: &mut R) -> io::Result<bool> {
+    Ok(reader.fill_buf()?.iter().find(|b| !b.is_ascii_whitespace()) == Some(&b'{'))
 }
 
 /// Structure representing the JSON format we expect to parse
@@ -192,36 +425,111 @@ struct JsonRecord {
     warnings: Option<Vec<String>>,
 }
 
-/// Convenience function to process JSON file and output CWR with optional version hint and output file
+/// Convenience function to process JSON file and output CWR with optional version hint and output file.
+/// Reads the default JSON layout only (see [`JsonOptions`]).
 pub fn process_json_to_cwr_with_version_and_output(
     input_filename: &str, _version_hint: Option<f32>, output_filename: Option<&str>,
 ) -> Result<usize, Box<dyn std::error::Error>> {
-    let file = File::open(input_filename)?;
-    let reader = BufReader::new(file);
+    let output: Box<dyn Write> = match output_filename {
+        Some(filename) => Box::new(BufWriter::new(File::create(filename)?)),
+        None => Box::new(BufWriter::new(io::stdout())),
+    };
+    process_json_to_cwr_to_writer(input_filename, output)
+}
 
+/// Converts a JSON file in the default layout back to CWR lines written to `writer`, in the version recorded in
+/// the JSON context. Returns the number of records written.
+pub fn process_json_to_cwr_to_writer<W: Write>(
+    input_filename: &str, writer: W,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    process_json_reader_to_cwr_to_writer(BufReader::new(File::open(input_filename)?), writer)
+}
+
+/// Like [`process_json_to_cwr_to_writer`], for JSON read from `reader` (e.g. stdin)
+pub fn process_json_reader_to_cwr_to_writer<R: io::Read, W: Write>(
+    reader: R, writer: W,
+) -> Result<usize, Box<dyn std::error::Error>> {
     // Parse the entire JSON file
     let json_data: JsonCwrFile = serde_json::from_reader(reader)?;
 
-    // Use the CWR version from context or fallback to hint
+    // Use the CWR version from context
     let cwr_version = allegro_cwr::domain_types::CwrVersion(json_data.context.cwr_version);
 
     // Create output writer with ASCII validation
-    let output: Box<dyn Write> = match output_filename {
-        Some(filename) => Box::new(File::create(filename)?),
-        None => Box::new(io::stdout()),
-    };
-    let mut ascii_writer = allegro_cwr::AsciiWriter::new(output);
+    let mut ascii_writer = allegro_cwr::AsciiWriter::new(writer);
 
     // Write each record as a CWR line
     let mut count = 0;
     for json_record in json_data.records {
-        let character_set = allegro_cwr::domain_types::CharacterSet::ASCII;
-        let cwr_bytes = json_record.record.to_cwr_record_bytes(&cwr_version, &character_set);
-        let cwr_line = String::from_utf8_lossy(&cwr_bytes).to_string();
-        ascii_writer.write_line(&cwr_line)?;
+        ascii_writer.write_record(&json_record.record, &cwr_version)?;
         count += 1;
     }
 
-    // AsciiWriter doesn't need explicit flush - it writes directly
+    ascii_writer.flush()?;
     Ok(count)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn convert(content: &str, options: JsonOptions) -> serde_json::Value {
+        let mut output = Vec::new();
+        allegro_cwr::process_cwr_bytes(content.as_bytes(), JsonHandler::with_options(&mut output, options)).unwrap();
+        serde_json::from_slice(&output).unwrap()
+    }
+
+    #[test]
+    fn test_grouped_layout() {
+        let nwr = |seq: u32| {
+            format!(
+                "NWR{:08}00000000Test Song                                               SW0000000001        SER        Y       ORI",
+                seq
+            )
+        };
+        let content = [
+            "HDRPB285606836WARNER CHAPPELL MUSIC PUBLISHING LTD         01.102022122112541120221221".to_string(),
+            "GRHNWR0000102.100000000000  ".to_string(),
+            nwr(0),
+            format!("{:<83}", "ALT0000000000000001ALTERNATE TITLE"),
+            nwr(1),
+            "XYZ".to_string(),
+            "GRT000010000000200000006".to_string(),
+            "TRL000000010000000200000008".to_string(),
+        ]
+        .join("\n");
+
+        let flat = convert(&content, JsonOptions::default());
+        assert_eq!(flat["records"].as_array().unwrap().len(), 8);
+
+        let grouped = convert(&content, JsonOptions { layout: JsonLayout::Grouped, ..Default::default() });
+        assert_eq!(grouped["context"]["cwr_version"], 2.1);
+        assert!(grouped["header"]["record"]["Hdr"].is_object());
+        assert!(grouped["trailer"]["record"]["Trl"].is_object());
+
+        let groups = grouped["groups"].as_array().unwrap();
+        assert_eq!(groups.len(), 1);
+        assert!(groups[0]["header"]["record"]["Grh"].is_object());
+        assert!(groups[0]["trailer"]["record"]["Grt"].is_object());
+
+        let transactions = groups[0]["transactions"].as_array().unwrap();
+        assert_eq!(transactions.len(), 2);
+        assert_eq!(transactions[0]["records"].as_array().unwrap().len(), 2);
+        // The unparseable line stays with the transaction it appeared in
+        assert_eq!(transactions[1]["records"][1]["status"], "error");
+    }
+
+    #[test]
+    fn test_typed_report() {
+        let content = "HDRPB285606836WARNER CHAPPELL MUSIC PUBLISHING LTD         01.102022122112541120221221\n\
+                       GRHNWR0000102.100000000000  \nXYZ\nGRT000010000000000000002\nTRL000000010000000000000004\n";
+        let mut output = Vec::new();
+        let handler = JsonHandler::with_options(&mut output, JsonOptions::default());
+        let summary = allegro_cwr::process_cwr_bytes(content.as_bytes(), handler).unwrap();
+        // The JSON report counts warnings as errors: the unparseable line and two warnings on the TRL
+        assert_eq!(
+            serde_json::to_value(&summary.handler_report).unwrap(),
+            serde_json::json!({"handler": "output", "format": "json", "records": 4, "errors": 3})
+        );
+    }
+}
@@ -0,0 +1,212 @@
+//! Options controlling how records are written as JSON
+
+use allegro_cwr::ParsedRecord;
+use allegro_cwr::domain_types::CwrVersion;
+use allegro_cwr::parsing::CwrWarning;
+use serde_json::{Map, Value};
+use std::borrow::Cow;
+
+/// Naming of JSON object keys
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FieldNaming {
+    /// Rust field names, e.g. `work_title` (default; required to convert the JSON back to CWR)
+    #[default]
+    SnakeCase,
+    /// e.g. `workTitle`
+    CamelCase,
+}
+
+impl FieldNaming {
+    pub fn apply<'a>(&self, name: &'a str) -> Cow<'a, str> {
+        match self {
+            FieldNaming::SnakeCase => Cow::Borrowed(name),
+            FieldNaming::CamelCase => Cow::Owned(to_camel_case(name)),
+        }
+    }
+}
+
+/// Overall shape of the JSON document
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum JsonLayout {
+    /// `records[]` in file order (default; required to convert the JSON back to CWR)
+    #[default]
+    Flat,
+    /// `header`, `groups[].transactions[].records[]` and `trailer`, with each group's GRH and GRT as its `header`
+    /// and `trailer`
+    Grouped,
+}
+
+/// JSON output options. The defaults reproduce the output that `cwr-json` reads back when converting to CWR.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct JsonOptions {
+    pub layout: JsonLayout,
+    pub field_naming: FieldNaming,
+    /// Add a `field_titles` object mapping each field to its title in the CWR specification
+    pub include_field_titles: bool,
+    /// Write every field as the text found in the CWR line (trailing spaces removed, `null` when blank) instead of
+    /// the serialized domain type, e.g. `"PB"` rather than `"Publisher"`
+    pub flatten_domain_types: bool,
+    /// Add the source line as received (`raw_line`) to every record
+    pub include_raw_lines: bool,
+    /// Write each warning as an object with its `level`, `field_name`, `columns`, `source` and `message` instead of
+    /// its message
+    pub structured_warnings: bool,
+}
+
+pub(crate) fn to_camel_case(name: &str) -> String {
+    let mut result = String::with_capacity(name.len());
+    let mut upper_next = false;
+    for c in name.chars() {
+        if c == '_' {
+            upper_next = !result.is_empty();
+        } else if upper_next {
+            result.extend(c.to_uppercase());
+            upper_next = false;
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+/// Builds the JSON object written for a record: `line_number`, `record`, `warnings` and, optionally, `field_titles`
+pub(crate) fn record_value(parsed_record: &ParsedRecord, options: &JsonOptions) -> Result<Value, serde_json::Error> {
+    let mut record = serde_json::to_value(&parsed_record.record)?;
+
+    // Records serialize as `{"Nwr": {...}}`
+    if let Some(fields) = record.as_object_mut().and_then(|m| m.values_mut().next()).and_then(Value::as_object_mut) {
+        if options.flatten_domain_types {
+            flatten_fields(parsed_record, fields);
+        }
+        if options.field_naming != FieldNaming::SnakeCase {
+            *fields = std::mem::take(fields)
+                .into_iter()
+                .map(|(name, value)| (options.field_naming.apply(&name).into_owned(), value))
+                .collect();
+        }
+    }
+
+    let naming = options.field_naming;
+    let mut value = Map::new();
+    value.insert(naming.apply("line_number").into_owned(), parsed_record.line_number.into());
+    value.insert("record".to_string(), record);
+    let warnings = if options.structured_warnings {
+        Value::Array(
+            parsed_record.warnings.iter().map(|warning| warning_value(parsed_record, warning, naming)).collect(),
+        )
+    } else {
+        serde_json::to_value(&parsed_record.warnings)?
+    };
+    value.insert("warnings".to_string(), warnings);
+    if let Some(raw_line) = &parsed_record.raw_line {
+        value.insert(naming.apply("raw_line").into_owned(), Value::from(raw_line.as_str()));
+    }
+    if options.include_field_titles {
+        let version = parsed_record.context.cwr_version;
+        let titles = parsed_record
+            .record
+            .field_specs()
+            .iter()
+            .filter(|spec| spec.min_version.is_none_or(|min| version >= min))
+            .map(|spec| (naming.apply(spec.name).into_owned(), Value::from(spec.title)))
+            .collect();
+        value.insert(naming.apply("field_titles").into_owned(), Value::Object(titles));
+    }
+    Ok(Value::Object(value))
+}
+
+/// A warning as an object; `field_name`, `columns` and `source` are `null` for warnings about the record as a whole
+fn warning_value(parsed_record: &ParsedRecord, warning: &CwrWarning, naming: FieldNaming) -> Value {
+    let columns = parsed_record.warning_columns(warning).map(|columns| serde_json::json!([columns.start, columns.end]));
+    let field_name = if warning.is_field_warning() { Value::from(warning.field_name) } else { Value::Null };
+    let source = if warning.source_str.is_empty() { Value::Null } else { Value::from(warning.source_str.as_ref()) };
+    let mut value = Map::new();
+    value.insert("level".to_string(), Value::from(warning.level.as_str()));
+    value.insert(naming.apply("field_name").into_owned(), field_name);
+    value.insert("columns".to_string(), columns.unwrap_or(Value::Null));
+    value.insert("source".to_string(), source);
+    value.insert("message".to_string(), Value::from(warning.to_string()));
+    Value::Object(value)
+}
+
+fn flatten_fields(parsed_record: &ParsedRecord, fields: &mut Map<String, Value>) {
+    let version = parsed_record.context.cwr_version;
+    let character_set = parsed_record.context.character_set.clone().unwrap_or_default();
+    let line = parsed_record.record.to_cwr_record_bytes(&CwrVersion(version), &character_set);
+    let line = String::from_utf8_lossy(&line);
+
+    for spec in parsed_record.record.field_specs() {
+        if spec.min_version.is_some_and(|min| version < min) {
+            fields.remove(spec.name);
+            continue;
+        }
+        let (start, len) = spec.layout_for(version);
+        let text: String = line.chars().skip(start).take(len).collect();
+        let text = text.trim_end();
+        let value = if text.is_empty() { Value::Null } else { Value::from(text) };
+        fields.insert(spec.name.to_string(), value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_camel_case() {
+        assert_eq!(to_camel_case("work_title"), "workTitle");
+        assert_eq!(to_camel_case("pr_ownership_share_1"), "prOwnershipShare1");
+        assert_eq!(to_camel_case("record_type"), "recordType");
+    }
+
+    #[test]
+    fn test_record_value_options() {
+        let line = "HDRPB285606836WARNER CHAPPELL MUSIC PUBLISHING LTD         01.102022122112541120221221";
+        let parsed = allegro_cwr::process_cwr_bytes_stream(line.as_bytes(), &Default::default())
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap();
+
+        let default = record_value(&parsed, &JsonOptions::default()).unwrap();
+        assert_eq!(default["record"]["Hdr"]["sender_type"], "Publisher");
+        assert!(default.get("field_titles").is_none());
+
+        let options = JsonOptions {
+            layout: JsonLayout::Flat,
+            field_naming: FieldNaming::CamelCase,
+            include_field_titles: true,
+            flatten_domain_types: true,
+            include_raw_lines: false,
+            structured_warnings: false,
+        };
+        let value = record_value(&parsed, &options).unwrap();
+        assert_eq!(value["lineNumber"], 1);
+        assert_eq!(value["record"]["Hdr"]["senderType"], "PB");
+        assert_eq!(value["record"]["Hdr"]["creationDate"], "20221221");
+        assert_eq!(value["record"]["Hdr"]["characterSet"], Value::Null);
+        assert!(value["fieldTitles"]["senderName"].is_string());
+    }
+
+    #[test]
+    fn test_structured_warnings() {
+        let content = "HDRPB285606836WARNER CHAPPELL MUSIC PUBLISHING LTD         01.102022122112541120221221\nGRHZZZ0000102.100000000000  \n";
+        let parsed = allegro_cwr::process_cwr_bytes_stream(content.as_bytes(), &Default::default())
+            .unwrap()
+            .nth(1)
+            .unwrap()
+            .unwrap();
+
+        let default = record_value(&parsed, &JsonOptions::default()).unwrap();
+        assert!(default["warnings"][0].is_string());
+
+        let options = JsonOptions { structured_warnings: true, ..Default::default() };
+        let value = record_value(&parsed, &options).unwrap();
+        let warning = &value["warnings"][0];
+        assert_eq!(warning["level"], "CRITICAL");
+        assert_eq!(warning["field_name"], "transaction_type");
+        assert_eq!(warning["source"], "ZZZ");
+        assert_eq!(warning["columns"], serde_json::json!([3, 6]));
+        assert_eq!(warning["message"], default["warnings"][0]);
+    }
+}
@@ -0,0 +1,144 @@
+//! Reading JSON written by [`crate::JsonHandler`] back into parsed records
+//!
+//! Records are rebuilt from their JSON fields rather than re-parsed from fixed-width lines, so their warnings,
+//! original lines and parse errors come through as they were when the JSON was written.
+
+use std::borrow::Cow;
+use std::io::Read;
+
+use allegro_cwr::parsing::{CwrWarning, WarningLevel};
+use allegro_cwr::{CwrParseError, CwrRegistry, ParsedRecord, ParsingContext};
+use serde::Deserialize;
+use serde_json::Value;
+
+#[derive(Deserialize)]
+struct JsonInput {
+    context: JsonInputContext,
+    #[serde(default)]
+    records: Vec<Value>,
+}
+
+#[derive(Deserialize)]
+struct JsonInputContext {
+    cwr_version: Option<f32>,
+    #[serde(default)]
+    file_id: i64,
+}
+
+#[derive(Deserialize)]
+struct JsonInputRecord {
+    line_number: usize,
+    record: CwrRegistry,
+    #[serde(default)]
+    warnings: Vec<JsonInputWarning>,
+    raw_line: Option<String>,
+}
+
+/// A warning as its message, or as the object written with [`crate::JsonOptions::structured_warnings`]
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum JsonInputWarning {
+    Message(String),
+    Structured { level: String, field_name: Option<String>, source: Option<String>, message: String },
+}
+
+#[derive(Deserialize)]
+struct JsonInputError {
+    error_message: String,
+}
+
+/// Reads the records and parse errors of a JSON document, in file order. Only the default flat layout with
+/// snake_case keys and nested domain types can be read (see [`crate::JsonOptions`]); a warning keeps its message,
+/// and its level and field when they were written as objects.
+pub fn read_json_records(reader: impl Read) -> Result<Vec<Result<ParsedRecord, CwrParseError>>, serde_json::Error> {
+    let input: JsonInput = serde_json::from_reader(reader)?;
+    let context = ParsingContext {
+        cwr_version: input.context.cwr_version.unwrap_or(2.2),
+        file_id: input.context.file_id,
+        character_set: None,
+    };
+
+    input
+        .records
+        .into_iter()
+        .map(|entry| {
+            if entry.get("status").and_then(Value::as_str) == Some("error") {
+                let error: JsonInputError = serde_json::from_value(entry)?;
+                return Ok(Err(CwrParseError::BadFormat(error.error_message)));
+            }
+            let entry: JsonInputRecord = serde_json::from_value(entry)?;
+            let mut context = context.clone();
+            if let CwrRegistry::Hdr(hdr) = &entry.record {
+                context.character_set = hdr.character_set.clone();
+            }
+            let warnings = entry.warnings.into_iter().map(|warning| warning.into_warning(&entry.record)).collect();
+            Ok(Ok(ParsedRecord {
+                line_number: entry.line_number,
+                record: entry.record,
+                context,
+                warnings,
+                raw_line: entry.raw_line,
+                raw_bytes: None,
+            }))
+        })
+        .collect()
+}
+
+impl JsonInputWarning {
+    fn into_warning(self, record: &CwrRegistry) -> CwrWarning<'static> {
+        match self {
+            JsonInputWarning::Message(message) => CwrWarning::record(WarningLevel::Warning, message),
+            JsonInputWarning::Structured { level, field_name, source, message } => {
+                let level = match level.as_str() {
+                    "INFO" => WarningLevel::Info,
+                    "CRITICAL" => WarningLevel::Critical,
+                    _ => WarningLevel::Warning,
+                };
+                let field_name = field_name
+                    .and_then(|name| record.field_specs().iter().find(|spec| spec.name == name))
+                    .map_or("", |spec| spec.name);
+                // The message already starts with the field title, so the title is left empty
+                CwrWarning {
+                    field_name,
+                    field_title: "",
+                    source_str: Cow::Owned(source.unwrap_or_default()),
+                    level,
+                    description: message.into(),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{JsonHandler, JsonOptions};
+    use allegro_cwr::domain_types::{CharacterSet, CwrVersion};
+
+    #[test]
+    fn test_generated_records_round_trip() {
+        let records: Vec<CwrRegistry> =
+            (0..300).filter_map(|seed| allegro_cwr::generate::from_seed(seed).ok()).collect();
+        let parsed = records.iter().enumerate().map(|(index, record)| {
+            Ok(ParsedRecord {
+                line_number: index + 1,
+                record: record.clone(),
+                context: ParsingContext { cwr_version: 2.2, file_id: 0, character_set: None },
+                warnings: Vec::new(),
+                raw_line: None,
+                raw_bytes: None,
+            })
+        });
+        let mut json = Vec::new();
+        let handler = JsonHandler::with_options(&mut json, JsonOptions::default());
+        allegro_cwr::process_records_with_handler("<generated>", parsed, handler).unwrap();
+
+        let read = read_json_records(json.as_slice()).unwrap();
+        assert_eq!(read.len(), records.len());
+        let line = |record: &CwrRegistry| record.to_cwr_record_bytes(&CwrVersion(2.2), &CharacterSet::ASCII);
+        for (record, read) in records.iter().zip(read) {
+            assert_eq!(line(&read.unwrap().record), line(record));
+        }
+    }
+}
@@ -0,0 +1,232 @@
+//! Incremental JSON document writer
+//!
+//! Values are serialized through serde_json as they arrive, so memory use does not grow with the file. Objects and
+//! arrays are opened and closed through the same [`PrettyFormatter`] that formats the values, which keeps the
+//! indentation consistent without buffering the document.
+
+use serde::ser::{Serialize, SerializeMap};
+use serde_json::Serializer;
+use serde_json::ser::{Formatter, PrettyFormatter};
+use std::borrow::Cow;
+use std::io::{self, Write};
+
+/// Object whose keys are written in insertion order (a `serde_json::Map` would sort them)
+pub(crate) struct OrderedObject<'a>(pub Vec<(Cow<'a, str>, serde_json::Value)>);
+
+impl Serialize for OrderedObject<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (key, value) in &self.0 {
+            map.serialize_entry(key, value)?;
+        }
+        map.end()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Container {
+    Object { first: bool },
+    Array { first: bool },
+}
+
+pub(crate) struct JsonStream<W: Write> {
+    writer: W,
+    formatter: PrettyFormatter<'static>,
+    open: Vec<Container>,
+    started: bool,
+    finished: bool,
+}
+
+impl<W: Write> JsonStream<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer, formatter: PrettyFormatter::new(), open: Vec::new(), started: false, finished: false }
+    }
+
+    pub fn has_started(&self) -> bool {
+        self.started
+    }
+
+    /// Number of objects and arrays currently open
+    pub fn depth(&self) -> usize {
+        self.open.len()
+    }
+
+    /// Opens an object, as an array element or as the value of the preceding [`JsonStream::key`]
+    pub fn begin_object(&mut self) -> io::Result<()> {
+        self.begin_element()?;
+        self.formatter.begin_object(&mut self.writer)?;
+        self.open.push(Container::Object { first: true });
+        Ok(())
+    }
+
+    /// Opens an array, as an array element or as the value of the preceding [`JsonStream::key`]
+    pub fn begin_array(&mut self) -> io::Result<()> {
+        self.begin_element()?;
+        self.formatter.begin_array(&mut self.writer)?;
+        self.open.push(Container::Array { first: true });
+        Ok(())
+    }
+
+    /// Writes a key of the innermost object; it must be followed by a value, object or array
+    pub fn key(&mut self, key: &str) -> io::Result<()> {
+        let Some(Container::Object { first }) = self.open.last_mut() else {
+            return Err(io::Error::other("JSON key written outside an object"));
+        };
+        let first = std::mem::replace(first, false);
+        self.formatter.begin_object_key(&mut self.writer, first)?;
+        self.serialize(key)?;
+        self.formatter.end_object_key(&mut self.writer)?;
+        self.formatter.begin_object_value(&mut self.writer)
+    }
+
+    /// Writes a complete value, as an array element or as the value of the preceding [`JsonStream::key`]
+    pub fn value(&mut self, value: &(impl Serialize + ?Sized)) -> io::Result<()> {
+        self.begin_element()?;
+        self.serialize(value)?;
+        self.end_element()
+    }
+
+    pub fn entry(&mut self, key: &str, value: &(impl Serialize + ?Sized)) -> io::Result<()> {
+        self.key(key)?;
+        self.value(value)
+    }
+
+    /// Closes the innermost object or array
+    pub fn end(&mut self) -> io::Result<()> {
+        match self.open.pop() {
+            Some(Container::Object { .. }) => self.formatter.end_object(&mut self.writer)?,
+            Some(Container::Array { .. }) => self.formatter.end_array(&mut self.writer)?,
+            None => return Err(io::Error::other("No open JSON object or array to close")),
+        }
+        self.end_element()
+    }
+
+    /// Closes everything still open and flushes; an empty object is written if nothing was
+    pub fn finish(&mut self) -> io::Result<()> {
+        if self.finished {
+            return Ok(());
+        }
+        if !self.started {
+            self.begin_object()?;
+        }
+        while !self.open.is_empty() {
+            self.end()?;
+        }
+        self.writer.write_all(b"\n")?;
+        self.writer.flush()?;
+        self.finished = true;
+        Ok(())
+    }
+
+    fn begin_element(&mut self) -> io::Result<()> {
+        self.started = true;
+        if let Some(Container::Array { first }) = self.open.last_mut() {
+            let first = std::mem::replace(first, false);
+            self.formatter.begin_array_value(&mut self.writer, first)?;
+        }
+        Ok(())
+    }
+
+    fn end_element(&mut self) -> io::Result<()> {
+        match self.open.last() {
+            Some(Container::Array { .. }) => self.formatter.end_array_value(&mut self.writer),
+            Some(Container::Object { .. }) => self.formatter.end_object_value(&mut self.writer),
+            None => Ok(()),
+        }
+    }
+
+    fn serialize(&mut self, value: &(impl Serialize + ?Sized)) -> io::Result<()> {
+        let mut serializer = Serializer::with_formatter(&mut self.writer, Nested(&mut self.formatter));
+        value.serialize(&mut serializer)?;
+        Ok(())
+    }
+}
+
+/// Lends the stream's formatter to a value serializer so nested values continue at the current indentation
+struct Nested<'a>(&'a mut PrettyFormatter<'static>);
+
+impl Formatter for Nested<'_> {
+    fn begin_array<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.0.begin_array(writer)
+    }
+
+    fn end_array<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.0.end_array(writer)
+    }
+
+    fn begin_array_value<W: ?Sized + Write>(&mut self, writer: &mut W, first: bool) -> io::Result<()> {
+        self.0.begin_array_value(writer, first)
+    }
+
+    fn end_array_value<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.0.end_array_value(writer)
+    }
+
+    fn begin_object<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.0.begin_object(writer)
+    }
+
+    fn end_object<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.0.end_object(writer)
+    }
+
+    fn begin_object_key<W: ?Sized + Write>(&mut self, writer: &mut W, first: bool) -> io::Result<()> {
+        self.0.begin_object_key(writer, first)
+    }
+
+    fn begin_object_value<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.0.begin_object_value(writer)
+    }
+
+    fn end_object_value<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.0.end_object_value(writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_stream_matches_pretty_serialization() {
+        let context = json!({ "cwr_version": 2.1, "file_id": 0 });
+        let records = [json!({ "line_number": 1, "warnings": ["a \"quoted\" warning"] }), json!({ "line_number": 2 })];
+
+        let mut output = Vec::new();
+        let mut stream = JsonStream::new(&mut output);
+        stream.begin_object().unwrap();
+        stream.entry("context", &context).unwrap();
+        stream.key("records").unwrap();
+        stream.begin_array().unwrap();
+        for record in &records {
+            stream.value(record).unwrap();
+        }
+        stream.begin_object().unwrap();
+        stream.entry("nested", &[1]).unwrap();
+        stream.finish().unwrap();
+
+        let expected = serde_json::to_string_pretty(&json!({
+            "context": context,
+            "records": [records[0], records[1], { "nested": [1] }],
+        }))
+        .unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), expected + "\n");
+    }
+
+    #[test]
+    fn test_empty_stream_is_an_empty_object() {
+        let mut output = Vec::new();
+        let mut stream = JsonStream::new(&mut output);
+        stream.finish().unwrap();
+        stream.finish().unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "{}\n");
+    }
+
+    #[test]
+    fn test_key_outside_object_is_an_error() {
+        let mut stream = JsonStream::new(Vec::new());
+        stream.begin_array().unwrap();
+        assert!(stream.key("records").is_err());
+    }
+}
@@ -0,0 +1,238 @@
+use std::ffi::OsString;
+use std::io;
+use std::process;
+use std::time::Instant;
+
+use allegro_cwr::parser::is_cwr_file;
+use allegro_cwr_cli::{
+    check_log_format, create_output, get_output_filename_with_default_extension, get_value,
+    init_logging_and_parse_args, output_file, status, status_error, BaseConfig, BatchSummary,
+};
+use tracing::info;
+
+#[derive(Default)]
+struct Config {
+    base: BaseConfig,
+    output_filename: Option<String>,
+    seed: Option<String>,
+    preserve_length: bool,
+    preset: crate::ObfuscationPreset,
+}
+
+fn parse_args(args: Vec<OsString>) -> Result<Config, String> {
+    let mut config = Config::default();
+    let mut parser = lexopt::Parser::from_args(args);
+
+    while let Ok(Some(arg)) = parser.next() {
+        match arg {
+            lexopt::Arg::Long("cwr") => {
+                let version_str = get_value(&mut parser, "cwr")?;
+                config.base.set_cwr_version(&version_str)?;
+            }
+            lexopt::Arg::Short('o') | lexopt::Arg::Long("output") => {
+                let output_filename = get_value(&mut parser, "output")?;
+                config.output_filename = Some(output_filename);
+            }
+            lexopt::Arg::Long("seed") => {
+                let seed = get_value(&mut parser, "seed")?;
+                config.seed = Some(seed);
+            }
+            lexopt::Arg::Long("preserve-length") => {
+                config.preserve_length = true;
+            }
+            lexopt::Arg::Long("preset") => {
+                config.preset = get_value(&mut parser, "preset")?.parse()?;
+            }
+            lexopt::Arg::Value(val) => {
+                config.base.add_input_file(val.to_string_lossy().to_string())?;
+            }
+            lexopt::Arg::Short('q' | 'v') | lexopt::Arg::Long("quiet" | "verbose") => {}
+            lexopt::Arg::Long("log-format") => check_log_format(&mut parser)?,
+            lexopt::Arg::Short('h') | lexopt::Arg::Long("help") => {
+                print_help();
+                process::exit(0);
+            }
+            _ => {
+                return Err("Unknown argument".to_string());
+            }
+        }
+    }
+
+    config.base.finalize();
+    Ok(config)
+}
+
+/// Runs the command line tool with `args` (excluding the program name)
+pub fn run(args: impl IntoIterator<Item = OsString>) {
+    let args: Vec<OsString> = args.into_iter().collect();
+    let config = init_logging_and_parse_args(|| {
+        parse_args(args).inspect_err(|_| {
+            print_help();
+        })
+    });
+
+    let start_time = Instant::now();
+
+    if config.base.read_stdin {
+        process_stdin(&config, start_time);
+    } else {
+        process_files(&config, start_time);
+    }
+}
+
+fn process_stdin(config: &Config, start_time: Instant) {
+    info!("Reading CWR data from stdin");
+    let result = create_output(config.output_filename.as_deref()).map_err(Into::into).and_then(|output| {
+        crate::process_cwr_reader_obfuscation_to_writer(
+            io::stdin().lock(),
+            output,
+            config.base.cwr_version,
+            config.seed.as_deref(),
+            config.preserve_length,
+            config.preset,
+        )
+    });
+    let elapsed_time = start_time.elapsed();
+
+    let count = match result {
+        Ok(c) => c,
+        Err(e) => {
+            status_error!("Error processing stdin after {:.2?}: {}", elapsed_time, e);
+            process::exit(1);
+        }
+    };
+
+    status!(
+        "Successfully obfuscated {} CWR records from stdin in {:.2?}",
+        allegro_cwr::format_int_with_commas(count as i64),
+        elapsed_time
+    );
+}
+
+fn process_files(config: &Config, start_time: Instant) {
+    let mut summary = BatchSummary::default();
+
+    for input_filename in &config.base.input_files {
+        info!("Processing CWR file: {}", input_filename);
+
+        let is_cwr = match is_cwr_file(input_filename) {
+            Ok(is_cwr) => is_cwr,
+            Err(e) => {
+                status_error!("Error reading file '{}': {}", input_filename, e);
+                summary.add_failure(input_filename, &e);
+                continue;
+            }
+        };
+
+        if !is_cwr {
+            status_error!("Error: File '{}' is not a CWR file. Obfuscation only works with CWR files.", input_filename);
+            summary.add_failure(input_filename, "not a CWR file");
+            continue;
+        }
+
+        let output_filename = get_output_filename_with_default_extension(
+            config.output_filename.as_deref(),
+            input_filename,
+            config.base.input_files.len(),
+            summary.files_processed(),
+            "obfuscated",
+        );
+
+        let result = match output_file(output_filename.as_deref()) {
+            Some(output_file) => crate::process_cwr_obfuscation(
+                input_filename,
+                Some(output_file),
+                config.base.cwr_version,
+                config.seed.as_deref(),
+                config.preserve_length,
+                config.preset,
+            ),
+            None => {
+                use std::io;
+                crate::process_cwr_obfuscation_to_writer(
+                    input_filename,
+                    io::stdout(),
+                    config.base.cwr_version,
+                    config.seed.as_deref(),
+                    config.preserve_length,
+                    config.preset,
+                )
+            }
+        };
+
+        match result {
+            Ok(count) => {
+                summary.add_success(input_filename, count);
+                info!("Processed {} records from '{}'", count, input_filename);
+            }
+            Err(e) => {
+                status_error!("Error processing file '{}': {}", input_filename, e);
+                summary.add_failure(input_filename, &e);
+            }
+        }
+    }
+
+    let elapsed_time = start_time.elapsed();
+    info!("Processing completed");
+
+    if config.base.input_files.len() == 1 {
+        let failed_files = summary.failed_files();
+        if !failed_files.is_empty() {
+            status_error!("Failed to process {} file(s): {}", failed_files.len(), failed_files.join(", "));
+            process::exit(1);
+        }
+
+        status!(
+            "Successfully obfuscated {} CWR records from '{}' in {:.2?}",
+            allegro_cwr::format_int_with_commas(summary.total_records() as i64),
+            &config.base.input_files[0],
+            elapsed_time
+        );
+    } else {
+        summary.print("Successfully obfuscated", elapsed_time);
+        if !summary.failed_files().is_empty() {
+            process::exit(1);
+        }
+    }
+}
+
+fn print_help() {
+    eprintln!("Usage: cwr-obfuscate [OPTIONS] [FILES...]");
+    eprintln!();
+    eprintln!("Arguments:");
+    eprintln!("  [FILES...]          CWR files to obfuscate. If no files specified or a file is -, reads from stdin");
+    eprintln!("                      Directories and patterns such as \'*.V21\' expand to the matching CWR files");
+    eprintln!();
+    eprintln!("Options:");
+    eprintln!("  -o, --output <file>      Output file path, or - for stdout (defaults to <input>.obfuscated or stdout for stdin)");
+    eprintln!("      --cwr <version>      CWR version (2.0, 2.1, 2.2). Auto-detected from filename (.Vxx) or file content if not specified");
+    eprintln!(
+        "      --seed <secret>      Secret mixed into generated values; the same seed reproduces the same mappings"
+    );
+    eprintln!("      --preserve-length    Keep obfuscated names and titles the same length as the originals");
+    eprintln!(
+        "      --preset <preset>    standard (default), or analytics: also replace names, titles and identifiers in"
+    );
+    eprintln!("                           every other record, giving each original its own fake, while keeping");
+    eprintln!("                           societies, territories, shares and dates for statistics and load tests");
+    eprintln!("  -q, --quiet              Only print errors");
+    eprintln!("  -v, --verbose            Also print info logging");
+    eprintln!("      --log-format <fmt>   Message format on stderr: text (default) or json");
+    eprintln!("  -h, --help               Show this help message");
+    eprintln!();
+    eprintln!("Privacy-preserving obfuscation of sensitive CWR data while maintaining referential integrity.");
+    eprintln!(
+        "Names, titles, IPIs, interested party numbers and work numbers are consistently mapped throughout the file."
+    );
+    eprintln!("Input format auto-detected (only CWR files are supported for obfuscation).");
+    eprintln!();
+    eprintln!("Examples:");
+    eprintln!("  cwr-obfuscate input.cwr                       # Obfuscate single CWR file");
+    eprintln!("  cwr-obfuscate *.cwr                           # Obfuscate multiple CWR files");
+    eprintln!("  cwr-obfuscate -o obfuscated.cwr input.cwr     # Specify output file");
+    eprintln!("  cwr-obfuscate --seed secret input.cwr         # Organization-specific, reproducible mappings");
+    eprintln!("  cwr-obfuscate --preserve-length input.cwr     # Byte-for-byte identical layout for parser bug repros");
+    eprintln!("  cwr-obfuscate --preset analytics input.cwr    # Keep the file useful for statistics");
+    eprintln!("  cat input.cwr | cwr-obfuscate                 # Process CWR data from stdin");
+    eprintln!("  find . -name '*.cwr' | xargs cwr-obfuscate    # Process all CWR files recursively");
+}
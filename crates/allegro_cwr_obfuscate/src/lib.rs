@@ -1,8 +1,16 @@
-use std::collections::HashMap;
+pub mod cli;
+pub mod synthetic;
+
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
-use std::io::{BufWriter, Write};
+use std::io::{BufWriter, Read, Write};
+use std::str::FromStr;
 
-use allegro_cwr::{process_cwr_stream_with_version, CwrRegistry};
+use allegro_cwr::domain_types::{CharacterSet, CwrVersion};
+use allegro_cwr::{
+    process_cwr_reader_stream, process_cwr_stream_with_version, CwrRegistry, ParseOptions, ParsedRecord,
+    PipelineContext, RecordTransform,
+};
 use rand::{Rng, SeedableRng};
 use sha2::{Digest, Sha256};
 use thiserror::Error;
@@ -13,11 +21,51 @@ pub enum ObfuscationError {
     Io(#[from] std::io::Error),
     #[error("CWR parsing error: {0}")]
     CwrParsing(String),
+    #[error("Generation error: {0}")]
+    Generation(String),
+}
+
+impl From<allegro_cwr::CwrParseError> for ObfuscationError {
+    fn from(e: allegro_cwr::CwrParseError) -> Self {
+        ObfuscationError::Generation(e.to_string())
+    }
 }
 
+/// Which values obfuscation replaces
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ObfuscationPreset {
+    /// Names, titles and identifiers of works, writers, publishers, performers and recordings
+    #[default]
+    Standard,
+    /// Names, titles and identifiers in every record, each original getting its own fake so distinct works and
+    /// parties stay distinct. Societies, territories, shares, dates, durations and the records themselves are kept,
+    /// so the output can still be counted and profiled like the original.
+    Analytics,
+}
+
+impl FromStr for ObfuscationPreset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "standard" => Ok(ObfuscationPreset::Standard),
+            "analytics" => Ok(ObfuscationPreset::Analytics),
+            _ => Err(format!("Invalid preset '{}'. Valid presets: standard, analytics", s)),
+        }
+    }
+}
+
+/// Fakes tried for a value before one not given to another value is accepted anyway
+const DISTINCT_ATTEMPTS: u32 = 64;
+
 /// Consistent obfuscation mappings for different types of data
 #[derive(Debug, Default)]
 pub struct ObfuscationMappings {
+    /// Secret mixed into every generated value, so different seeds produce different mappings
+    seed: String,
+    /// Generate names and titles with exactly the same character length as the originals
+    preserve_length: bool,
+    preset: ObfuscationPreset,
     /// Map original names to obfuscated names (publishers, writers, etc.)
     names: HashMap<String, String>,
     /// Map original titles to obfuscated titles
@@ -28,6 +76,12 @@ pub struct ObfuscationMappings {
     work_numbers: HashMap<String, String>,
     /// Map original ISWCs to obfuscated ISWCs
     iswcs: HashMap<String, String>,
+    /// Map original interested party numbers to obfuscated interested party numbers
+    interested_party_nums: HashMap<String, String>,
+    /// Map other original identifiers (agreement numbers, ISRCs, ...) to obfuscated identifiers
+    identifiers: HashMap<String, String>,
+    /// Every fake handed out, so the analytics preset can keep them distinct
+    used: HashSet<String>,
 }
 
 impl ObfuscationMappings {
@@ -35,13 +89,49 @@ impl ObfuscationMappings {
         Self::default()
     }
 
+    /// Create mappings whose generated values depend on a secret seed.
+    /// The same seed always reproduces the same mappings for the same input.
+    pub fn with_seed(seed: &str) -> Self {
+        Self { seed: seed.to_string(), ..Self::default() }
+    }
+
+    /// Keep obfuscated names and titles the same character length as the originals, so the fixed-width output
+    /// is structurally identical to the source, which matters when reproducing parser bugs.
+    pub fn with_preserve_length(mut self, preserve_length: bool) -> Self {
+        self.preserve_length = preserve_length;
+        self
+    }
+
+    /// Which values are replaced, and whether different originals always get different fakes
+    pub fn with_preset(mut self, preset: ObfuscationPreset) -> Self {
+        self.preset = preset;
+        self
+    }
+
+    pub fn preset(&self) -> ObfuscationPreset {
+        self.preset
+    }
+
     /// Get or create an obfuscated name, ensuring consistency
     pub fn obfuscate_name(&mut self, original: &str) -> String {
         if original.trim().is_empty() {
             return original.to_string();
         }
+        if let Some(fake) = self.names.get(original) {
+            return fake.clone();
+        }
 
-        self.names.entry(original.to_string()).or_insert_with(|| generate_fake_name(original)).clone()
+        let preserve_length = self.preserve_length;
+        let fake = self.distinct(original, |seed, original| {
+            let fake = generate_fake_name(seed, original);
+            if preserve_length {
+                fit_to_length(&fake, original)
+            } else {
+                fake
+            }
+        });
+        self.names.insert(original.to_string(), fake.clone());
+        fake
     }
 
     /// Get or create an obfuscated title, ensuring consistency
@@ -49,8 +139,21 @@ impl ObfuscationMappings {
         if original.trim().is_empty() {
             return original.to_string();
         }
+        if let Some(fake) = self.titles.get(original) {
+            return fake.clone();
+        }
 
-        self.titles.entry(original.to_string()).or_insert_with(|| generate_fake_title(original)).clone()
+        let preserve_length = self.preserve_length;
+        let fake = self.distinct(original, |seed, original| {
+            let fake = generate_fake_title(seed, original);
+            if preserve_length {
+                fit_to_length(&fake, original)
+            } else {
+                fake
+            }
+        });
+        self.titles.insert(original.to_string(), fake.clone());
+        fake
     }
 
     /// Get or create an obfuscated IPI number, ensuring consistency
@@ -58,17 +161,36 @@ impl ObfuscationMappings {
         if original.trim().is_empty() {
             return original.to_string();
         }
+        if let Some(fake) = self.ipis.get(original) {
+            return fake.clone();
+        }
 
-        self.ipis.entry(original.to_string()).or_insert_with(|| generate_fake_ipi(original)).clone()
+        let analytics = self.preset == ObfuscationPreset::Analytics;
+        let fake = self.distinct(original, |seed, original| match generate_fake_ipi(seed, original) {
+            fake if analytics && fake == original => generate_fake_identifier(seed, original),
+            fake => fake,
+        });
+        self.ipis.insert(original.to_string(), fake.clone());
+        fake
     }
 
-    /// Get or create an obfuscated work number, ensuring consistency
+    /// Get or create an obfuscated work number, ensuring consistency. Only numeric work numbers are replaced,
+    /// unless the preset is [`ObfuscationPreset::Analytics`].
     pub fn obfuscate_work_number(&mut self, original: &str) -> String {
         if original.trim().is_empty() {
             return original.to_string();
         }
+        if let Some(fake) = self.work_numbers.get(original) {
+            return fake.clone();
+        }
 
-        self.work_numbers.entry(original.to_string()).or_insert_with(|| generate_fake_work_number(original)).clone()
+        let analytics = self.preset == ObfuscationPreset::Analytics;
+        let fake = self.distinct(original, |seed, original| match generate_fake_work_number(seed, original) {
+            fake if analytics && fake == original => generate_fake_identifier(seed, original),
+            fake => fake,
+        });
+        self.work_numbers.insert(original.to_string(), fake.clone());
+        fake
     }
 
     /// Get or create an obfuscated ISWC, ensuring consistency
@@ -76,16 +198,77 @@ impl ObfuscationMappings {
         if original.trim().is_empty() {
             return original.to_string();
         }
+        if let Some(fake) = self.iswcs.get(original) {
+            return fake.clone();
+        }
 
-        self.iswcs.entry(original.to_string()).or_insert_with(|| generate_fake_iswc(original)).clone()
+        let fake = self.distinct(original, generate_fake_iswc);
+        self.iswcs.insert(original.to_string(), fake.clone());
+        fake
+    }
+
+    /// Get or create an obfuscated interested party number, ensuring consistency.
+    /// Padding is ignored, so the same party maps to the same number in every record that references it.
+    pub fn obfuscate_interested_party_num(&mut self, original: &str) -> String {
+        let trimmed = original.trim();
+        if trimmed.is_empty() {
+            return original.to_string();
+        }
+        if let Some(fake) = self.interested_party_nums.get(trimmed) {
+            return fake.clone();
+        }
+
+        let fake = self.distinct(trimmed, generate_fake_interested_party_num);
+        self.interested_party_nums.insert(trimmed.to_string(), fake.clone());
+        fake
+    }
+
+    /// Get or create an obfuscated identifier of any other kind, ensuring consistency. Digits are replaced by
+    /// digits and letters by letters; padding and punctuation are kept.
+    pub fn obfuscate_identifier(&mut self, original: &str) -> String {
+        if original.trim().is_empty() {
+            return original.to_string();
+        }
+        if let Some(fake) = self.identifiers.get(original) {
+            return fake.clone();
+        }
+
+        let fake = self.distinct(original, generate_fake_identifier);
+        self.identifiers.insert(original.to_string(), fake.clone());
+        fake
+    }
+
+    /// The fake `generate` makes for `original` from the seed. With the analytics preset a fake already given to
+    /// another value is regenerated from a varied seed, so distinct originals keep distinct fakes.
+    fn distinct(&mut self, original: &str, generate: impl Fn(&str, &str) -> String) -> String {
+        let mut fake = generate(&self.seed, original);
+        if self.preset != ObfuscationPreset::Analytics {
+            return fake;
+        }
+        let mut attempt = 0;
+        while self.used.contains(&fake) && fake != original && attempt < DISTINCT_ATTEMPTS {
+            attempt += 1;
+            fake = generate(&format!("{}#{}", self.seed, attempt), original);
+        }
+        self.used.insert(fake.clone());
+        fake
     }
 }
 
+/// Create a random number generator seeded from the SHA-256 of the secret seed and the original value
+fn seeded_rng(seed: &str, original: &str) -> rand::rngs::StdRng {
+    let mut hasher = Sha256::new();
+    hasher.update(seed.as_bytes());
+    hasher.update(original.as_bytes());
+    let hash = hasher.finalize();
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&hash[..8]);
+    rand::rngs::StdRng::seed_from_u64(u64::from_le_bytes(bytes))
+}
+
 /// Generate a deterministic but obfuscated name based on original
-fn generate_fake_name(original: &str) -> String {
-    let hash = Sha256::digest(original.as_bytes());
-    let seed = u64::from_le_bytes(hash[0..8].try_into().unwrap());
-    let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+fn generate_fake_name(seed: &str, original: &str) -> String {
+    let mut rng = seeded_rng(seed, original);
 
     // Common fake publisher/writer names
     let prefixes = ["FAKE", "TEST", "DEMO", "SAMPLE", "MOCK"];
@@ -99,10 +282,8 @@ fn generate_fake_name(original: &str) -> String {
 }
 
 /// Generate a deterministic but obfuscated title based on original
-fn generate_fake_title(original: &str) -> String {
-    let hash = Sha256::digest(original.as_bytes());
-    let seed = u64::from_le_bytes(hash[0..8].try_into().unwrap());
-    let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+fn generate_fake_title(seed: &str, original: &str) -> String {
+    let mut rng = seeded_rng(seed, original);
 
     // Common fake song title patterns
     let adjectives = ["DEMO", "TEST", "SAMPLE", "FAKE", "MOCK"];
@@ -115,11 +296,16 @@ fn generate_fake_title(original: &str) -> String {
     format!("{} {} {}", adjective, noun, number)
 }
 
+/// Reshape a generated value to the original's character length, keeping the original's whitespace positions
+/// and filling every other position from the generated value's characters (repeated as needed)
+fn fit_to_length(fake: &str, original: &str) -> String {
+    let mut filler = fake.chars().filter(|c| !c.is_whitespace()).cycle();
+    original.chars().map(|c| if c.is_whitespace() { c } else { filler.next().unwrap_or('X') }).collect()
+}
+
 /// Generate a deterministic but obfuscated IPI number
-fn generate_fake_ipi(original: &str) -> String {
-    let hash = Sha256::digest(original.as_bytes());
-    let seed = u64::from_le_bytes(hash[0..8].try_into().unwrap());
-    let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+fn generate_fake_ipi(seed: &str, original: &str) -> String {
+    let mut rng = seeded_rng(seed, original);
 
     // Generate fake IPI maintaining same format (11 digits for IPI Name Number)
     if original.len() == 11 && original.chars().all(|c| c.is_ascii_digit()) {
@@ -135,10 +321,8 @@ fn generate_fake_ipi(original: &str) -> String {
 }
 
 /// Generate a deterministic but obfuscated work number
-fn generate_fake_work_number(original: &str) -> String {
-    let hash = Sha256::digest(original.as_bytes());
-    let seed = u64::from_le_bytes(hash[0..8].try_into().unwrap());
-    let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+fn generate_fake_work_number(seed: &str, original: &str) -> String {
+    let mut rng = seeded_rng(seed, original);
 
     // Generate fake work number maintaining same length
     if original.chars().all(|c| c.is_ascii_digit()) {
@@ -153,10 +337,8 @@ fn generate_fake_work_number(original: &str) -> String {
 }
 
 /// Generate a deterministic but obfuscated ISWC
-fn generate_fake_iswc(original: &str) -> String {
-    let hash = Sha256::digest(original.as_bytes());
-    let seed = u64::from_le_bytes(hash[0..8].try_into().unwrap());
-    let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+fn generate_fake_iswc(seed: &str, original: &str) -> String {
+    let mut rng = seeded_rng(seed, original);
 
     // ISWC format: T-NNNNNNNN-C (T followed by 8 digits followed by check digit)
     if original.len() == 11 && original.starts_with('T') {
@@ -169,91 +351,159 @@ fn generate_fake_iswc(original: &str) -> String {
     }
 }
 
+/// Generate a deterministic but obfuscated interested party number
+fn generate_fake_interested_party_num(seed: &str, original: &str) -> String {
+    let mut rng = seeded_rng(seed, original);
+
+    // Keep the same length, and keep numeric numbers numeric
+    let chars: Vec<char> = if original.chars().all(|c| c.is_ascii_digit()) {
+        "0123456789".chars().collect()
+    } else {
+        "ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789".chars().collect()
+    };
+    (0..original.len()).map(|_| chars[rng.gen_range(0..chars.len())]).collect()
+}
+
+/// Generate a deterministic but obfuscated identifier with the original's layout: digits stay digits, letters
+/// become upper case letters and everything else is kept
+fn generate_fake_identifier(seed: &str, original: &str) -> String {
+    let mut rng = seeded_rng(seed, original);
+
+    original
+        .chars()
+        .map(|c| {
+            if c.is_ascii_digit() {
+                char::from(b'0' + rng.gen_range(0..10u8))
+            } else if c.is_ascii_alphabetic() {
+                char::from(b'A' + rng.gen_range(0..26u8))
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
 /// Process a CWR file and obfuscate sensitive information
 pub fn process_cwr_obfuscation(
-    input_path: &str, output_path: Option<&str>, cwr_version: Option<f32>,
+    input_path: &str, output_path: Option<&str>, cwr_version: Option<f32>, seed: Option<&str>, preserve_length: bool,
+    preset: ObfuscationPreset,
 ) -> Result<usize, ObfuscationError> {
     let default_output = format!("{}.obfuscated", input_path);
     let output_path = output_path.unwrap_or(&default_output);
     let output_file = File::create(output_path)?;
-    let buffered_writer = BufWriter::new(output_file);
-    let mut writer = allegro_cwr::AsciiWriter::new(buffered_writer);
+    let record_count = process_cwr_obfuscation_to_writer(
+        input_path,
+        BufWriter::new(output_file),
+        cwr_version,
+        seed,
+        preserve_length,
+        preset,
+    )?;
 
-    let mut mappings = ObfuscationMappings::new();
-    let mut record_count = 0;
-
-    // Use the allegro_cwr streaming parser
-    let record_stream = process_cwr_stream_with_version(input_path, cwr_version)
-        .map_err(|e| ObfuscationError::CwrParsing(format!("Failed to open CWR file: {}", e)))?;
-
-    for parsed_result in record_stream {
-        match parsed_result {
-            Ok(parsed_record) => {
-                // Obfuscate the record
-                let obfuscated_record = obfuscate_record(parsed_record.record, &mut mappings);
-
-                // Convert back to CWR line and write
-                let version = allegro_cwr::domain_types::CwrVersion(parsed_record.context.cwr_version);
-                let character_set = allegro_cwr::domain_types::CharacterSet::ASCII;
-                let obfuscated_bytes = obfuscated_record.to_cwr_record_bytes(&version, &character_set);
-                let obfuscated_line = String::from_utf8_lossy(&obfuscated_bytes).to_string();
-                writer
-                    .write_line(&obfuscated_line)
-                    .map_err(|e| ObfuscationError::CwrParsing(format!("ASCII writing error: {}", e)))?;
-                record_count += 1;
-            }
-            Err(e) => {
-                return Err(ObfuscationError::CwrParsing(format!("Parse error: {}", e)));
-            }
-        }
-    }
-
-    // AsciiWriter doesn't need explicit flush - it writes directly
-    println!("Successfully obfuscated {} records to '{}'", record_count, output_path);
+    tracing::info!(
+        file = input_path,
+        output = output_path,
+        "Obfuscated {} records from '{}' to '{}'",
+        record_count,
+        input_path,
+        output_path
+    );
 
     Ok(record_count)
 }
 
-/// Process CWR data and obfuscate to stdout or specified output
+/// Process CWR data and obfuscate to any writer (stdout, a file, an in-memory buffer, ...).
+/// Records are written in the character set declared by the file's HDR record.
+/// With `preserve_length`, names and titles keep their original character length; `preset` picks which values
+/// are replaced.
 pub fn process_cwr_obfuscation_to_writer<W: Write>(
-    input_path: &str, writer: W, cwr_version: Option<f32>,
+    input_path: &str, writer: W, cwr_version: Option<f32>, seed: Option<&str>, preserve_length: bool,
+    preset: ObfuscationPreset,
 ) -> Result<usize, ObfuscationError> {
-    let mut ascii_writer = allegro_cwr::AsciiWriter::new(writer);
-    let mut mappings = ObfuscationMappings::new();
-    let mut record_count = 0;
-
-    // Use the allegro_cwr streaming parser
     let record_stream = process_cwr_stream_with_version(input_path, cwr_version)
         .map_err(|e| ObfuscationError::CwrParsing(format!("Failed to open CWR file: {}", e)))?;
+    let mappings = ObfuscationMappings::with_seed(seed.unwrap_or_default())
+        .with_preserve_length(preserve_length)
+        .with_preset(preset);
+    obfuscate_records(record_stream, writer, mappings)
+}
+
+/// Like [`process_cwr_obfuscation_to_writer`], for CWR data read from `reader` (e.g. stdin)
+pub fn process_cwr_reader_obfuscation_to_writer<R: Read, W: Write>(
+    reader: R, writer: W, cwr_version: Option<f32>, seed: Option<&str>, preserve_length: bool,
+    preset: ObfuscationPreset,
+) -> Result<usize, ObfuscationError> {
+    let options = ParseOptions { version_hint: cwr_version, ..Default::default() };
+    let record_stream = process_cwr_reader_stream(reader, &options)
+        .map_err(|e| ObfuscationError::CwrParsing(format!("Failed to read CWR data: {}", e)))?;
+    let mappings = ObfuscationMappings::with_seed(seed.unwrap_or_default())
+        .with_preserve_length(preserve_length)
+        .with_preset(preset);
+    obfuscate_records(record_stream, writer, mappings)
+}
+
+/// Writes the obfuscated `record_stream` to `writer`, returning the number of records written
+fn obfuscate_records<W: Write>(
+    record_stream: impl Iterator<Item = Result<ParsedRecord, allegro_cwr::CwrParseError>>, writer: W,
+    mut mappings: ObfuscationMappings,
+) -> Result<usize, ObfuscationError> {
+    let mut ascii_writer: Option<allegro_cwr::AsciiWriter<W>> = None;
+    let mut writer = Some(writer);
+    let mut record_count = 0;
 
     for parsed_result in record_stream {
-        match parsed_result {
-            Ok(parsed_record) => {
-                // Obfuscate the record
-                let obfuscated_record = obfuscate_record(parsed_record.record, &mut mappings);
+        let parsed_record = parsed_result.map_err(|e| ObfuscationError::CwrParsing(format!("Parse error: {}", e)))?;
+        let character_set = parsed_record.context.character_set.clone().unwrap_or(CharacterSet::ASCII);
 
-                // Convert back to CWR line and write
-                let version = allegro_cwr::domain_types::CwrVersion(parsed_record.context.cwr_version);
-                let character_set = allegro_cwr::domain_types::CharacterSet::ASCII;
-                let obfuscated_bytes = obfuscated_record.to_cwr_record_bytes(&version, &character_set);
-                let obfuscated_line = String::from_utf8_lossy(&obfuscated_bytes).to_string();
-                ascii_writer
-                    .write_line(&obfuscated_line)
-                    .map_err(|e| ObfuscationError::CwrParsing(format!("ASCII writing error: {}", e)))?;
-                record_count += 1;
-            }
-            Err(e) => {
-                return Err(ObfuscationError::CwrParsing(format!("Parse error: {}", e)));
-            }
+        // The character set is only known once the HDR record has been parsed
+        if let Some(writer) = writer.take() {
+            ascii_writer = Some(allegro_cwr::AsciiWriter::with_character_set(writer, Some(character_set.clone())));
         }
+        let Some(ascii_writer) = ascii_writer.as_mut() else {
+            continue;
+        };
+
+        // Obfuscate the record
+        let obfuscated_record = obfuscate_record(parsed_record.record, &mut mappings);
+
+        // Convert back to CWR line and write
+        let version = CwrVersion(parsed_record.context.cwr_version);
+        ascii_writer
+            .write_record(&obfuscated_record, &version)
+            .map_err(|e| ObfuscationError::CwrParsing(format!("Writing error: {}", e)))?;
+        record_count += 1;
+    }
+
+    if let Some(ascii_writer) = ascii_writer.as_mut() {
+        ascii_writer.flush().map_err(|e| ObfuscationError::CwrParsing(format!("Writing error: {}", e)))?;
     }
 
-    // AsciiWriter doesn't need explicit flush - it writes directly
     Ok(record_count)
 }
 
+/// Obfuscates records in a [`allegro_cwr::Pipeline`]. The raw source line and bytes and the warnings, which may quote the
+/// original values, are dropped.
+impl RecordTransform for ObfuscationMappings {
+    fn transform(&mut self, mut record: ParsedRecord, _context: &mut PipelineContext) -> Option<ParsedRecord> {
+        record.record = obfuscate_record(record.record, self);
+        record.raw_line = None;
+        record.raw_bytes = None;
+        record.warnings.clear();
+        Some(record)
+    }
+}
+
 /// Obfuscate sensitive information in a CWR record
 fn obfuscate_record(record: CwrRegistry, mappings: &mut ObfuscationMappings) -> CwrRegistry {
+    let record = obfuscate_standard(record, mappings);
+    match mappings.preset {
+        ObfuscationPreset::Standard => record,
+        ObfuscationPreset::Analytics => obfuscate_analytics(record, mappings),
+    }
+}
+
+/// Obfuscate the values covered by [`ObfuscationPreset::Standard`]
+fn obfuscate_standard(record: CwrRegistry, mappings: &mut ObfuscationMappings) -> CwrRegistry {
     match record {
         CwrRegistry::Hdr(mut hdr) => {
             // Obfuscate sender name (it's a domain type, access the inner string)
@@ -272,6 +522,9 @@ fn obfuscate_record(record: CwrRegistry, mappings: &mut ObfuscationMappings) ->
             CwrRegistry::Nwr(nwr)
         }
         CwrRegistry::Swr(mut swr) => {
+            if let Some(ref ip_num) = swr.interested_party_num {
+                swr.interested_party_num = Some(mappings.obfuscate_interested_party_num(ip_num));
+            }
             if let Some(ref last_name) = swr.writer_last_name {
                 swr.writer_last_name = Some(mappings.obfuscate_name(last_name));
             }
@@ -289,6 +542,9 @@ fn obfuscate_record(record: CwrRegistry, mappings: &mut ObfuscationMappings) ->
             CwrRegistry::Swr(swr)
         }
         CwrRegistry::Spu(mut spu) => {
+            if let Some(ref ip_num) = spu.interested_party_num {
+                spu.interested_party_num = Some(mappings.obfuscate_interested_party_num(ip_num));
+            }
             if let Some(ref pub_name) = spu.publisher_name {
                 spu.publisher_name = Some(mappings.obfuscate_name(pub_name));
             }
@@ -302,6 +558,48 @@ fn obfuscate_record(record: CwrRegistry, mappings: &mut ObfuscationMappings) ->
             }
             CwrRegistry::Spu(spu)
         }
+        CwrRegistry::Spt(mut spt) => {
+            spt.interested_party_num = mappings.obfuscate_interested_party_num(&spt.interested_party_num);
+            CwrRegistry::Spt(spt)
+        }
+        CwrRegistry::Swt(mut swt) => {
+            if let Some(ref ip_num) = swt.interested_party_num {
+                swt.interested_party_num = Some(mappings.obfuscate_interested_party_num(ip_num));
+            }
+            CwrRegistry::Swt(swt)
+        }
+        CwrRegistry::Pwr(mut pwr) => {
+            if let Some(ref ip_num) = pwr.publisher_ip_num {
+                pwr.publisher_ip_num = Some(mappings.obfuscate_interested_party_num(ip_num));
+            }
+            if let Some(ref pub_name) = pwr.publisher_name {
+                pwr.publisher_name = Some(mappings.obfuscate_name(pub_name));
+            }
+            if let Some(ref ip_num) = pwr.writer_ip_num {
+                pwr.writer_ip_num = Some(mappings.obfuscate_interested_party_num(ip_num));
+            }
+            CwrRegistry::Pwr(pwr)
+        }
+        CwrRegistry::Ipa(mut ipa) => {
+            ipa.interested_party_num = mappings.obfuscate_interested_party_num(&ipa.interested_party_num);
+            CwrRegistry::Ipa(ipa)
+        }
+        CwrRegistry::Npn(mut npn) => {
+            npn.interested_party_num = mappings.obfuscate_interested_party_num(&npn.interested_party_num);
+            CwrRegistry::Npn(npn)
+        }
+        CwrRegistry::Nwn(mut nwn) => {
+            if let Some(ref ip_num) = nwn.interested_party_num {
+                nwn.interested_party_num = Some(mappings.obfuscate_interested_party_num(ip_num));
+            }
+            CwrRegistry::Nwn(nwn)
+        }
+        CwrRegistry::Npa(mut npa) => {
+            if let Some(ref ip_num) = npa.interested_party_num {
+                npa.interested_party_num = Some(mappings.obfuscate_interested_party_num(ip_num));
+            }
+            CwrRegistry::Npa(npa)
+        }
         CwrRegistry::Alt(mut alt) => {
             alt.alternate_title = mappings.obfuscate_title(&alt.alternate_title);
             CwrRegistry::Alt(alt)
@@ -384,6 +682,160 @@ fn obfuscate_record(record: CwrRegistry, mappings: &mut ObfuscationMappings) ->
     }
 }
 
+/// Obfuscate the names, titles and identifiers [`obfuscate_standard`] leaves, for [`ObfuscationPreset::Analytics`].
+/// Codes, shares, territories, dates and counts are not touched.
+fn obfuscate_analytics(mut record: CwrRegistry, mappings: &mut ObfuscationMappings) -> CwrRegistry {
+    fn each(value: &mut Option<String>, mut obfuscate: impl FnMut(&str) -> String) {
+        if let Some(value) = value.as_mut() {
+            *value = obfuscate(value);
+        }
+    }
+
+    match &mut record {
+        // Society senders are identified by their society code, which is kept
+        CwrRegistry::Hdr(hdr) if hdr.sender_type != allegro_cwr::domain_types::SenderType::Society => {
+            hdr.sender_id.0 = mappings.obfuscate_identifier(&hdr.sender_id.0);
+        }
+        CwrRegistry::Agr(agr) => {
+            agr.submitter_agreement_number = mappings.obfuscate_identifier(&agr.submitter_agreement_number);
+            each(&mut agr.international_standard_agreement_code, |v| mappings.obfuscate_identifier(v));
+            each(&mut agr.society_assigned_agreement_number, |v| mappings.obfuscate_identifier(v));
+        }
+        CwrRegistry::Ipa(ipa) => {
+            ipa.interested_party_last_name = mappings.obfuscate_name(&ipa.interested_party_last_name);
+            each(&mut ipa.interested_party_writer_first_name, |v| mappings.obfuscate_name(v));
+            if let Some(ipi) = ipa.interested_party_ipi_name_num.as_mut() {
+                ipi.0 = mappings.obfuscate_ipi(&ipi.0);
+            }
+            if let Some(ipi) = ipa.ipi_base_number.as_mut() {
+                ipi.0 = mappings.obfuscate_ipi(&ipi.0);
+            }
+        }
+        CwrRegistry::Spu(spu) => {
+            each(&mut spu.tax_id_num, |v| mappings.obfuscate_identifier(v));
+            each(&mut spu.submitter_agreement_number, |v| mappings.obfuscate_identifier(v));
+            each(&mut spu.international_standard_agreement_code, |v| mappings.obfuscate_identifier(v));
+            each(&mut spu.society_assigned_agreement_number, |v| mappings.obfuscate_identifier(v));
+        }
+        CwrRegistry::Swr(swr) => {
+            each(&mut swr.tax_id_num, |v| mappings.obfuscate_identifier(v));
+        }
+        CwrRegistry::Pwr(pwr) => {
+            each(&mut pwr.submitter_agreement_number, |v| mappings.obfuscate_identifier(v));
+            each(&mut pwr.society_assigned_agreement_number, |v| mappings.obfuscate_identifier(v));
+        }
+        CwrRegistry::Npn(npn) => {
+            npn.publisher_name.0 = mappings.obfuscate_name(&npn.publisher_name.0);
+        }
+        CwrRegistry::Nwn(nwn) => {
+            nwn.writer_last_name.0 = mappings.obfuscate_name(&nwn.writer_last_name.0);
+            if let Some(first_name) = nwn.writer_first_name.as_mut() {
+                first_name.0 = mappings.obfuscate_name(&first_name.0);
+            }
+        }
+        CwrRegistry::Npa(npa) => {
+            npa.interested_party_name.0 = mappings.obfuscate_name(&npa.interested_party_name.0);
+            npa.interested_party_writer_first_name.0 =
+                mappings.obfuscate_name(&npa.interested_party_writer_first_name.0);
+        }
+        CwrRegistry::Nat(nat) => {
+            nat.title.0 = mappings.obfuscate_title(&nat.title.0);
+        }
+        CwrRegistry::Net(net) => {
+            net.title = mappings.obfuscate_title(&net.title);
+        }
+        CwrRegistry::Now(now) => {
+            now.writer_name.0 = mappings.obfuscate_name(&now.writer_name.0);
+            now.writer_first_name.0 = mappings.obfuscate_name(&now.writer_first_name.0);
+        }
+        CwrRegistry::Npr(npr) => {
+            for name in
+                [npr.performing_artist_name.as_mut(), npr.performing_artist_first_name.as_mut()].into_iter().flatten()
+            {
+                name.0 = mappings.obfuscate_name(&name.0);
+            }
+            if let Some(ipi) = npr.performing_artist_ipi_name_num.as_mut() {
+                ipi.0 = mappings.obfuscate_ipi(&ipi.0);
+            }
+            if let Some(ipi) = npr.performing_artist_ipi_base_number.as_mut() {
+                ipi.0 = mappings.obfuscate_ipi(&ipi.0);
+            }
+        }
+        CwrRegistry::Ewt(ewt) => {
+            ewt.entire_work_title = mappings.obfuscate_title(&ewt.entire_work_title);
+            each(&mut ewt.iswc_of_entire_work, |v| mappings.obfuscate_iswc(v));
+            each(&mut ewt.submitter_work_num, |v| mappings.obfuscate_work_number(v));
+            for name in [
+                &mut ewt.writer_1_last_name,
+                &mut ewt.writer_1_first_name,
+                &mut ewt.writer_2_last_name,
+                &mut ewt.writer_2_first_name,
+            ] {
+                each(name, |v| mappings.obfuscate_name(v));
+            }
+            each(&mut ewt.writer_1_ipi_base_number, |v| mappings.obfuscate_ipi(v));
+            for ipi in [ewt.writer_1_ipi_name_num.as_mut(), ewt.writer_2_ipi_name_num.as_mut()].into_iter().flatten() {
+                ipi.0 = mappings.obfuscate_ipi(&ipi.0);
+            }
+            if let Some(ipi) = ewt.writer_2_ipi_base_number.as_mut() {
+                ipi.0 = mappings.obfuscate_ipi(&ipi.0);
+            }
+        }
+        CwrRegistry::Ver(ver) => {
+            ver.original_work_title = mappings.obfuscate_title(&ver.original_work_title);
+            each(&mut ver.iswc_of_original_work, |v| mappings.obfuscate_iswc(v));
+            each(&mut ver.submitter_work_num, |v| mappings.obfuscate_work_number(v));
+            for name in [
+                &mut ver.writer_1_last_name,
+                &mut ver.writer_1_first_name,
+                &mut ver.writer_2_last_name,
+                &mut ver.writer_2_first_name,
+            ] {
+                each(name, |v| mappings.obfuscate_name(v));
+            }
+            for ipi in [ver.writer_1_ipi_name_num.as_mut(), ver.writer_2_ipi_name_num.as_mut()].into_iter().flatten() {
+                ipi.0 = mappings.obfuscate_ipi(&ipi.0);
+            }
+            for ipi in
+                [ver.writer_1_ipi_base_number.as_mut(), ver.writer_2_ipi_base_number.as_mut()].into_iter().flatten()
+            {
+                ipi.0 = mappings.obfuscate_ipi(&ipi.0);
+            }
+        }
+        CwrRegistry::Orn(orn) => {
+            each(&mut orn.production_title, |v| mappings.obfuscate_title(v));
+            each(&mut orn.episode_title, |v| mappings.obfuscate_title(v));
+            for identifier in [&mut orn.cd_identifier, &mut orn.production_num, &mut orn.audio_visual_number] {
+                each(identifier, |v| mappings.obfuscate_identifier(v));
+            }
+        }
+        CwrRegistry::Ack(ack) => {
+            each(&mut ack.creation_title, |v| mappings.obfuscate_title(v));
+            // The submitter's number is the work number it acknowledges
+            each(&mut ack.submitter_creation_num, |v| mappings.obfuscate_work_number(v));
+            each(&mut ack.recipient_creation_num, |v| mappings.obfuscate_identifier(v));
+        }
+        CwrRegistry::Xrf(xrf) => {
+            xrf.identifier = mappings.obfuscate_identifier(&xrf.identifier);
+        }
+        CwrRegistry::Ari(ari) => {
+            each(&mut ari.work_num, |v| mappings.obfuscate_identifier(v));
+        }
+        CwrRegistry::Rec(rec) => {
+            each(&mut rec.release_catalog_num, |v| mappings.obfuscate_identifier(v));
+            each(&mut rec.submitter_recording_identifier, |v| mappings.obfuscate_identifier(v));
+            if let Some(isrc) = rec.isrc.as_mut() {
+                isrc.0 = mappings.obfuscate_identifier(&isrc.0);
+            }
+            if let Some(ean) = rec.ean.as_mut() {
+                ean.0 = mappings.obfuscate_identifier(&ean.0);
+            }
+        }
+        _ => {}
+    }
+    record
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -480,4 +932,276 @@ mod tests {
 
         assert_eq!(obfuscated1, obfuscated2);
     }
+
+    #[test]
+    fn test_interested_party_num_consistency() {
+        let mut mappings = ObfuscationMappings::new();
+
+        // Padded and unpadded references to the same party resolve to the same number
+        let obfuscated1 = mappings.obfuscate_interested_party_num("ABKC     ");
+        let obfuscated2 = mappings.obfuscate_interested_party_num("ABKC");
+        assert_eq!(obfuscated1, obfuscated2);
+        assert_ne!(obfuscated1, "ABKC");
+        assert_eq!(obfuscated1.len(), 4);
+
+        let numeric = mappings.obfuscate_interested_party_num("000012345");
+        assert_eq!(numeric.len(), 9);
+        assert!(numeric.chars().all(|c| c.is_ascii_digit()));
+        assert_eq!(mappings.obfuscate_interested_party_num("   "), "   ");
+    }
+
+    #[test]
+    fn test_obfuscation_to_writer() {
+        let content = [
+            "HDRPB123456789BMI MUSIC                                    01.1020050101120000200501010              2.2  1DEV MUSIC SOF",
+            "GRHAGR0000102.20            ",
+            "AGR00000001000000011234567890123               AA20231201                N        N                00001                ",
+            "IPA0000000100000002AS                        123456789JONES                                                             ",
+            "GRT000010000000100000004",
+            "TRL000010000000100000006",
+        ]
+        .join("\r\n");
+        let input = std::env::temp_dir().join(format!("obfuscate_test_{:?}.V22", std::thread::current().id()));
+        std::fs::write(&input, content).unwrap();
+
+        let mut output = Vec::new();
+        let result = process_cwr_obfuscation_to_writer(
+            input.to_str().unwrap(),
+            &mut output,
+            None,
+            None,
+            false,
+            ObfuscationPreset::Standard,
+        );
+        std::fs::remove_file(&input).ok();
+
+        assert_eq!(result.unwrap(), 6);
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(output.matches("\r\n").count(), 6);
+        assert!(!output.contains("BMI MUSIC"));
+        assert!(!output.contains("123456789JONES"));
+    }
+
+    #[test]
+    fn test_obfuscation_in_pipeline() {
+        let content = [
+            "HDRPB123456789BMI MUSIC                                    01.1020050101120000200501010              2.2  1DEV MUSIC SOF",
+            "GRHAGR0000102.20            ",
+            "AGR00000001000000011234567890123               AA20231201                N        N                00001                ",
+            "IPA0000000100000002AS                        123456789JONES                                                             ",
+            "GRT000010000000100000004",
+            "TRL000010000000100000006",
+        ]
+        .join("\r\n");
+        let records =
+            allegro_cwr::process_cwr_bytes_stream(content.as_bytes(), &allegro_cwr::ParseOptions::default()).unwrap();
+
+        let mut output = Vec::new();
+        let totals = allegro_cwr::Pipeline::new()
+            .then(ObfuscationMappings::with_seed("pipeline"))
+            .write(records, &mut output)
+            .unwrap();
+
+        assert_eq!(totals.records, 6);
+        let output = String::from_utf8(output).unwrap();
+        assert!(!output.contains("BMI MUSIC"));
+        assert!(!output.contains("123456789JONES"));
+    }
+
+    #[test]
+    fn test_preserve_length_mappings() {
+        let mut mappings = ObfuscationMappings::with_seed("repro").with_preserve_length(true);
+
+        let name = mappings.obfuscate_name("JO");
+        assert_eq!(name.len(), 2);
+        let title = mappings.obfuscate_title("A VERY LONG SONG TITLE THAT OUTGROWS ANY GENERATED TITLE");
+        assert_eq!(title.len(), 56);
+        assert_eq!(
+            title.match_indices(' ').map(|(i, _)| i).collect::<Vec<_>>(),
+            vec![1, 6, 11, 16, 22, 27, 36, 40, 50]
+        );
+        assert_ne!(title, "A VERY LONG SONG TITLE THAT OUTGROWS ANY GENERATED TITLE");
+        assert_eq!(mappings.obfuscate_name("JO"), name);
+    }
+
+    #[test]
+    fn test_preserve_length_output_matches_layout() {
+        let lines = [
+            "HDRPB123456789BMI MUSIC                                    01.1020050101120000200501010              2.2  1DEV MUSIC SOF",
+            "GRHAGR0000102.20            ",
+            "AGR00000001000000011234567890123               AA20231201                N        N                00001                ",
+            "IPA0000000100000002AS                        123456789JONES                                                             ",
+            "GRT000010000000100000004",
+            "TRL000010000000100000006",
+        ];
+        let content = lines.join("\r\n");
+        let parse = || {
+            allegro_cwr::process_cwr_bytes_stream(content.as_bytes(), &allegro_cwr::ParseOptions::default()).unwrap()
+        };
+
+        let mut plain = Vec::new();
+        allegro_cwr::Pipeline::new().write(parse(), &mut plain).unwrap();
+        let mut obfuscated = Vec::new();
+        allegro_cwr::Pipeline::new()
+            .then(ObfuscationMappings::with_seed("repro").with_preserve_length(true))
+            .write(parse(), &mut obfuscated)
+            .unwrap();
+
+        let plain = String::from_utf8(plain).unwrap();
+        let obfuscated = String::from_utf8(obfuscated).unwrap();
+        assert!(!obfuscated.contains("BMI MUSIC"));
+        assert_eq!(plain.len(), obfuscated.len());
+        // Only the characters inside values change; every space, padding included, stays in place
+        for (expected, actual) in plain.chars().zip(obfuscated.chars()) {
+            assert_eq!(expected == ' ', actual == ' ');
+        }
+    }
+
+    #[test]
+    fn test_seed_changes_mappings() {
+        let original = "12345678901";
+        let unseeded = ObfuscationMappings::new().obfuscate_ipi(original);
+        let seeded1 = ObfuscationMappings::with_seed("org-a-secret").obfuscate_ipi(original);
+        let seeded2 = ObfuscationMappings::with_seed("org-a-secret").obfuscate_ipi(original);
+        let other = ObfuscationMappings::with_seed("org-b-secret").obfuscate_ipi(original);
+
+        assert_eq!(seeded1, seeded2);
+        assert_ne!(seeded1, unseeded);
+        assert_ne!(seeded1, other);
+        assert_eq!(ObfuscationMappings::with_seed("").obfuscate_ipi(original), unseeded);
+    }
+
+    #[test]
+    fn test_analytics_preset_keeps_fakes_distinct() {
+        let mut standard = ObfuscationMappings::with_seed("stats");
+        let mut analytics = ObfuscationMappings::with_seed("stats").with_preset(ObfuscationPreset::Analytics);
+        let names: Vec<String> = (0..3000).map(|i| format!("WRITER {}", i)).collect();
+
+        let distinct = |mappings: &mut ObfuscationMappings| {
+            names.iter().map(|name| mappings.obfuscate_name(name)).collect::<HashSet<_>>().len()
+        };
+        // Random fakes from a small vocabulary collide; the analytics preset regenerates them
+        assert!(distinct(&mut standard) < names.len());
+        assert_eq!(distinct(&mut analytics), names.len());
+        assert_eq!(analytics.obfuscate_name("WRITER 7"), analytics.obfuscate_name("WRITER 7"));
+
+        let work_number = analytics.obfuscate_work_number("ABC-123");
+        assert_ne!(work_number, "ABC-123");
+        assert!(work_number.starts_with(|c: char| c.is_ascii_uppercase()) && work_number.contains('-'));
+        assert_eq!(ObfuscationMappings::new().obfuscate_work_number("ABC-123"), "ABC-123");
+    }
+
+    #[test]
+    fn test_analytics_preset_keeps_statistics() {
+        use allegro_cwr::domain_types::CharacterSet;
+        use synthetic::{generate_synthetic_cwr, SyntheticOptions};
+
+        let options = SyntheticOptions {
+            works: 60,
+            writers_per_work: 3,
+            territories: 2,
+            character_set: CharacterSet::ASCII,
+            seed: "source".to_string(),
+            ..Default::default()
+        };
+        let mut original = Vec::new();
+        generate_synthetic_cwr(&mut original, &options).unwrap();
+        let parse = |bytes: &[u8]| -> Vec<CwrRegistry> {
+            allegro_cwr::process_cwr_bytes_stream(bytes, &allegro_cwr::ParseOptions::default())
+                .unwrap()
+                .map(|record| record.unwrap().record)
+                .collect()
+        };
+
+        let mut obfuscated = Vec::new();
+        allegro_cwr::Pipeline::new()
+            .then(ObfuscationMappings::with_seed("stats").with_preset(ObfuscationPreset::Analytics))
+            .write(parse(&original).into_iter().map(|record| Ok(parsed(record))), &mut obfuscated)
+            .unwrap();
+
+        let before = parse(&original);
+        let after = parse(&obfuscated);
+        assert_eq!(before.len(), after.len());
+        for (before, after) in before.iter().zip(&after) {
+            match (before, after) {
+                (CwrRegistry::Nwr(before), CwrRegistry::Nwr(after)) => {
+                    assert_ne!(before.work_title, after.work_title);
+                    assert_ne!(before.iswc, after.iswc);
+                    assert_eq!(before.duration, after.duration);
+                }
+                (CwrRegistry::Spu(before), CwrRegistry::Spu(after)) => {
+                    assert_ne!(before.publisher_name, after.publisher_name);
+                    assert_eq!(before.pr_affiliation_society_num, after.pr_affiliation_society_num);
+                    assert_eq!(
+                        (before.pr_ownership_share, before.mr_ownership_share, before.sr_ownership_share),
+                        (after.pr_ownership_share, after.mr_ownership_share, after.sr_ownership_share)
+                    );
+                }
+                (CwrRegistry::Swt(before), CwrRegistry::Swt(after)) => {
+                    assert_eq!(before.tis_numeric_code, after.tis_numeric_code);
+                    assert_eq!(before.pr_collection_share, after.pr_collection_share);
+                }
+                (before, after) => assert_eq!(before.record_type(), after.record_type()),
+            }
+        }
+
+        let distinct = |records: &[CwrRegistry], value: fn(&CwrRegistry) -> Option<String>| {
+            records.iter().filter_map(value).collect::<HashSet<_>>().len()
+        };
+        let writer = |record: &CwrRegistry| match record {
+            CwrRegistry::Swr(swr) => swr.writer_last_name.clone(),
+            _ => None,
+        };
+        let title = |record: &CwrRegistry| match record {
+            CwrRegistry::Nwr(nwr) => Some(nwr.work_title.clone()),
+            _ => None,
+        };
+        assert_eq!(distinct(&before, writer), distinct(&after, writer));
+        assert_eq!(distinct(&before, title), distinct(&after, title));
+    }
+
+    #[test]
+    fn test_analytics_preset_covers_other_records() {
+        let content = [
+            "HDRPB123456789BMI MUSIC                                    01.1020050101120000200501010              2.2  1DEV MUSIC SOF",
+            "GRHAGR0000102.20            ",
+            "AGR00000001000000011234567890123               AA20231201                N        N                00001                ",
+            "IPA0000000100000002AS                        123456789JONES                                                             ",
+            "GRT000010000000100000004",
+            "TRL000010000000100000006",
+        ]
+        .join("\r\n");
+        let obfuscate = |preset: ObfuscationPreset| {
+            let records =
+                allegro_cwr::process_cwr_bytes_stream(content.as_bytes(), &allegro_cwr::ParseOptions::default())
+                    .unwrap();
+            let mut output = Vec::new();
+            allegro_cwr::Pipeline::new()
+                .then(ObfuscationMappings::with_seed("other").with_preset(preset))
+                .write(records, &mut output)
+                .unwrap();
+            String::from_utf8(output).unwrap()
+        };
+
+        let standard = obfuscate(ObfuscationPreset::Standard);
+        assert!(standard.contains("JONES"));
+        assert!(standard.contains("1234567890123"));
+        let analytics = obfuscate(ObfuscationPreset::Analytics);
+        assert!(!analytics.contains("JONES"));
+        assert!(!analytics.contains("1234567890123"));
+        assert!(!analytics.contains("123456789"));
+        // The agreement's dates and type are kept
+        assert!(analytics.contains("AA20231201"));
+    }
+
+    fn parsed(record: CwrRegistry) -> ParsedRecord {
+        ParsedRecord {
+            line_number: 0,
+            record,
+            context: allegro_cwr::ParsingContext { cwr_version: 2.2, file_id: 0, character_set: None },
+            warnings: Vec::new(),
+            raw_line: None,
+            raw_bytes: None,
+        }
+    }
 }
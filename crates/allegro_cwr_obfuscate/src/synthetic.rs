@@ -0,0 +1,242 @@
+//! Synthetic CWR transmissions for load testing and demos
+//!
+//! Titles, names and identifiers come from the same seeded generators the obfuscator uses, so a seed always
+//! produces the same works; only the HDR creation date and time change. Writers and their publishers are drawn from a catalogue shared by all works, so parties
+//! recur across works the way they do in a real repertoire.
+
+use std::io::Write;
+
+use allegro_cwr::domain_types::{CharacterSet, CwrVersion, Duration, SenderType, TitleType, TransactionType};
+use allegro_cwr::domain_types::{CwrVersionNumber, GroupId};
+use allegro_cwr::records::GrhRecord;
+use allegro_cwr::spreadsheet::new_header;
+use allegro_cwr::{PublisherBuilder, Shares, TransmissionTotals, TransmissionWriter, WorkBuilder, WriterBuilder};
+use rand::seq::index::sample;
+use rand::Rng;
+
+use crate::{generate_fake_name, generate_fake_title, seeded_rng, ObfuscationError, ObfuscationMappings};
+
+/// TIS codes used for collection territories, the world first
+const TERRITORIES: &[u16] = &[2136, 840, 826, 276, 250, 380, 724, 392, 36, 124, 76, 528, 752, 484];
+
+/// PR society codes given to writers and publishers
+const SOCIETIES: &[&str] = &["010", "021", "052", "035", "058", "040", "072"];
+
+const DESIGNATIONS: &[&str] = &["CA", "CA", "C", "A"];
+
+/// Words with accented letters for titles, when the character set can hold them
+const ACCENTED_WORDS: &[&str] = &["CAFÉ", "CORAZÓN", "NIÑO", "GRÜN", "ÉTÉ", "SEÑORA"];
+
+/// What to generate
+#[derive(Debug, Clone)]
+pub struct SyntheticOptions {
+    pub works: usize,
+    pub writers_per_work: usize,
+    /// Collection territories of each writer and publisher; a single territory is the world
+    pub territories: usize,
+    pub version: CwrVersion,
+    pub character_set: CharacterSet,
+    /// Secret mixed into generated values; the same seed and options produce the same works
+    pub seed: String,
+}
+
+impl Default for SyntheticOptions {
+    fn default() -> Self {
+        SyntheticOptions {
+            works: 100,
+            writers_per_work: 2,
+            territories: 1,
+            version: CwrVersion(2.2),
+            character_set: CharacterSet::ASCII,
+            seed: String::new(),
+        }
+    }
+}
+
+/// Writes a transmission of `options.works` new work registrations (NWR) in one group and returns its totals.
+/// Each work has `options.writers_per_work` writers, each represented by their original publisher; writers hold
+/// half of the performing rights and publishers the rest, with all mechanical and sync rights.
+pub fn generate_synthetic_cwr<W: Write>(
+    writer: W, options: &SyntheticOptions,
+) -> Result<TransmissionTotals, ObfuscationError> {
+    if options.writers_per_work == 0 || options.territories == 0 {
+        return Err(ObfuscationError::Generation("Works need at least one writer and one territory".to_string()));
+    }
+    if options.territories > TERRITORIES.len() {
+        return Err(ObfuscationError::Generation(format!(
+            "At most {} territories can be generated",
+            TERRITORIES.len()
+        )));
+    }
+
+    let seed = options.seed.as_str();
+    let mut mappings = ObfuscationMappings::with_seed(seed);
+    let mut rng = seeded_rng(seed, "sender");
+    let sender_id = format!("{:09}", rng.gen_range(100_000_000u32..1_000_000_000));
+    let mut hdr =
+        new_header(SenderType::Publisher, &sender_id, &generate_fake_name(seed, "sender"), options.version.clone());
+    if options.character_set != CharacterSet::ASCII {
+        hdr.character_set = Some(options.character_set.clone());
+    }
+    let grh = GrhRecord {
+        record_type: allegro_cwr::parsing::intern("GRH"),
+        transaction_type: TransactionType::NWR,
+        group_id: GroupId(1),
+        version_number: CwrVersionNumber(format!("{:05.2}", options.version.0)),
+        batch_request: None,
+        submission_distribution_type: None,
+    };
+
+    let catalogue = Catalogue::new(options);
+    let accented = matches!(options.character_set, CharacterSet::Latin1 | CharacterSet::UTF8);
+    let mut transmission =
+        TransmissionWriter::start(writer, &hdr, options.version.clone(), options.character_set.clone())?;
+    for n in 0..options.works {
+        let work = catalogue.work(n, &mut mappings, accented);
+        let records = work.build(0).map_err(|e| ObfuscationError::Generation(e.to_string()))?;
+        transmission.start_transaction(1, &grh)?;
+        for mut record in records {
+            transmission.write_detail(&mut record)?;
+        }
+    }
+    Ok(transmission.finish()?)
+}
+
+/// The writers and publishers works are drawn from
+struct Catalogue<'a> {
+    options: &'a SyntheticOptions,
+    writers: usize,
+    publishers: usize,
+}
+
+impl<'a> Catalogue<'a> {
+    fn new(options: &'a SyntheticOptions) -> Self {
+        let writers = (options.works * options.writers_per_work / 3).max(options.writers_per_work);
+        Catalogue { options, writers, publishers: (writers / 4).max(1) }
+    }
+
+    fn work(&self, n: usize, mappings: &mut ObfuscationMappings, accented: bool) -> WorkBuilder {
+        let seed = self.options.seed.as_str();
+        let key = format!("work {}", n);
+        let mut rng = seeded_rng(seed, &key);
+        let mut title = mappings.obfuscate_title(&key);
+        if accented && rng.gen_ratio(1, 4) {
+            title = format!("{} {}", ACCENTED_WORDS[rng.gen_range(0..ACCENTED_WORDS.len())], title);
+        }
+        let duration = rng.gen_range(90..=420);
+        let mut work = WorkBuilder::new(&title, &format!("SW{:010}", n))
+            .iswc(&mappings.obfuscate_iswc(&format!("T{:010}", n)))
+            .configure(|nwr| nwr.duration = Some(Duration(duration)));
+
+        let writers = self.options.writers_per_work;
+        let mut publishers: Vec<(usize, Shares)> = Vec::new();
+        let mut work_writers = Vec::new();
+        for (k, writer) in sample(&mut rng, self.writers, writers).into_iter().enumerate() {
+            let publisher = writer % self.publishers;
+            let shares = Shares::new(split(5000, writers, k), split(10000, writers, k), split(10000, writers, k));
+            match publishers.iter_mut().find(|(index, _)| *index == publisher) {
+                Some((_, total)) => {
+                    total.pr += shares.pr;
+                    total.mr += shares.mr;
+                    total.sr += shares.sr;
+                }
+                None => publishers.push((publisher, shares)),
+            }
+            work_writers.push((writer, publisher, split(5000, writers, k)));
+        }
+
+        let territories = self.territories(&mut rng);
+        for (publisher, shares) in publishers {
+            let mut builder = PublisherBuilder::new(
+                &format!("P{:08}", publisher),
+                &mappings.obfuscate_name(&format!("publisher {}", publisher)),
+            )
+            .ownership(shares)
+            .ipi_name_num(&mappings.obfuscate_ipi(&format!("1{:010}", publisher)));
+            let society = SOCIETIES[publisher % SOCIETIES.len()];
+            builder = builder.societies(society, society, society);
+            for &tis_code in &territories {
+                builder = builder.territory(tis_code, shares);
+            }
+            work = work.publisher(builder);
+        }
+        for (writer, publisher, pr_share) in work_writers {
+            let key = format!("writer {}", writer);
+            let mut builder = WriterBuilder::new(&format!("W{:08}", writer), &mappings.obfuscate_name(&key))
+                .first_name(&mappings.obfuscate_name(&format!("writer {} first name", writer)))
+                .designation(DESIGNATIONS[writer % DESIGNATIONS.len()])
+                .ownership(Shares::new(pr_share, 0, 0))
+                .societies(SOCIETIES[writer % SOCIETIES.len()], "", "")
+                .ipi_name_num(&mappings.obfuscate_ipi(&format!("2{:010}", writer)))
+                .represented_by(&format!("P{:08}", publisher));
+            for &tis_code in &territories {
+                builder = builder.territory(tis_code, Shares::new(pr_share, 0, 0));
+            }
+            work = work.writer(builder);
+        }
+
+        if rng.gen_ratio(1, 4) {
+            work = work
+                .alternate_title(&generate_fake_title(seed, &format!("alternate {}", n)), TitleType::AlternativeTitle);
+        }
+        work
+    }
+
+    /// The world when one territory is asked for, otherwise distinct countries
+    fn territories(&self, rng: &mut impl Rng) -> Vec<u16> {
+        if self.options.territories == 1 {
+            return vec![TERRITORIES[0]];
+        }
+        let countries = &TERRITORIES[1..];
+        let count = self.options.territories.min(countries.len());
+        sample(rng, countries.len(), count).into_iter().map(|index| countries[index]).collect()
+    }
+}
+
+/// Share `k` of `total` split `parts` ways, the remainder going to the first parts
+fn split(total: u16, parts: usize, k: usize) -> u16 {
+    let parts = parts as u16;
+    total / parts + u16::from((k as u16) < total % parts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use allegro_cwr::parser::ParseOptions;
+    use allegro_cwr::{process_cwr_bytes_stream, CwrRegistry};
+
+    #[test]
+    fn test_synthetic_file_parses_cleanly() {
+        let options = SyntheticOptions {
+            works: 40,
+            writers_per_work: 3,
+            territories: 3,
+            character_set: CharacterSet::UTF8,
+            seed: "demo".to_string(),
+            ..Default::default()
+        };
+        let mut output = Vec::new();
+        let totals = generate_synthetic_cwr(&mut output, &options).unwrap();
+        assert_eq!(totals.transactions, 40);
+
+        let mut again = Vec::new();
+        generate_synthetic_cwr(&mut again, &options).unwrap();
+        // Only the HDR creation date and time change
+        let lines = |bytes: &[u8]| bytes.split(|&b| b == b'\n').skip(1).map(<[u8]>::to_vec).collect::<Vec<_>>();
+        assert_eq!(lines(&output), lines(&again));
+
+        let records: Vec<_> = process_cwr_bytes_stream(&output, &ParseOptions::default())
+            .unwrap()
+            .map(|record| record.unwrap())
+            .collect();
+        assert_eq!(records.len(), totals.records as usize);
+        for record in &records {
+            assert!(record.warnings.is_empty(), "{:?}", record.warnings);
+        }
+        let count = |record_type: &str| records.iter().filter(|r| r.record.record_type() == record_type).count();
+        assert_eq!(count("SWR"), 120);
+        assert_eq!(count("SWT"), 360);
+        assert_eq!(count("PWR"), 120);
+        assert!(records.iter().any(|r| matches!(&r.record, CwrRegistry::Nwr(nwr) if !nwr.work_title.is_ascii())));
+    }
+}
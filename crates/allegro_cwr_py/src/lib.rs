@@ -0,0 +1,190 @@
+//! Python bindings for the CWR parser, validator and converters
+//!
+//! Built with maturin as the `allegro_cwr` Python module:
+//!
+//! ```python
+//! import allegro_cwr
+//!
+//! for record in allegro_cwr.parse("CW240001XXX_000.V21"):
+//!     print(record["record_type"], record.get("warnings"))
+//! ```
+//!
+//! `parse` and `parse_bytes` return iterators that parse one line per step, so large files are never held in
+//! memory as a list.
+
+use std::sync::Mutex;
+
+use allegro_cwr::{CwrParseError, ParseOptions, ParsedRecord};
+use pyo3::create_exception;
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use serde_json::{json, Value};
+
+create_exception!(allegro_cwr, CwrError, PyException, "Raised when a CWR file cannot be read, parsed or converted");
+
+fn cwr_error(e: impl std::fmt::Display) -> PyErr {
+    CwrError::new_err(e.to_string())
+}
+
+fn to_py(py: Python<'_>, value: &Value) -> PyResult<PyObject> {
+    Ok(match value {
+        Value::Null => py.None(),
+        Value::Bool(b) => b.into_pyobject(py)?.to_owned().into_any().unbind(),
+        Value::Number(n) => match (n.as_i64(), n.as_u64()) {
+            (Some(i), _) => i.into_pyobject(py)?.into_any().unbind(),
+            (None, Some(u)) => u.into_pyobject(py)?.into_any().unbind(),
+            (None, None) => n.as_f64().unwrap_or(f64::NAN).into_pyobject(py)?.into_any().unbind(),
+        },
+        Value::String(s) => s.into_pyobject(py)?.into_any().unbind(),
+        Value::Array(items) => {
+            let items = items.iter().map(|item| to_py(py, item)).collect::<PyResult<Vec<_>>>()?;
+            PyList::new(py, items)?.into_any().unbind()
+        }
+        Value::Object(map) => {
+            let dict = PyDict::new(py);
+            for (key, item) in map {
+                dict.set_item(key, to_py(py, item)?)?;
+            }
+            dict.into_any().unbind()
+        }
+    })
+}
+
+/// One dict per line: parsed records carry `record_type`, `record` and `warnings`; unparseable lines carry
+/// `error` and `code` instead
+fn record_value(result: Result<ParsedRecord, CwrParseError>) -> Result<Value, serde_json::Error> {
+    Ok(match result {
+        Ok(parsed) => json!({
+            "line_number": parsed.line_number,
+            "record_type": parsed.record.record_type(),
+            "record": serde_json::to_value(&parsed.record)?,
+            "warnings": parsed.warnings,
+        }),
+        Err(e) => json!({
+            "line_number": e.record_error().and_then(|details| details.line_number),
+            "error": e.to_string(),
+            "code": e.code().map(|code| code.as_str()),
+        }),
+    })
+}
+
+type RecordStream = Box<dyn Iterator<Item = Result<ParsedRecord, CwrParseError>> + Send>;
+
+/// Iterator over the lines of a CWR file, one dict per line, parsed as they are asked for
+#[pyclass(module = "allegro_cwr")]
+struct Records {
+    records: Mutex<RecordStream>,
+}
+
+impl Records {
+    fn new(records: impl Iterator<Item = Result<ParsedRecord, CwrParseError>> + Send + 'static) -> Self {
+        Records { records: Mutex::new(Box::new(records)) }
+    }
+}
+
+#[pymethods]
+impl Records {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&self, py: Python<'_>) -> PyResult<Option<PyObject>> {
+        let next = self.records.lock().map_err(cwr_error)?.next();
+        next.map(|result| to_py(py, &record_value(result).map_err(cwr_error)?)).transpose()
+    }
+}
+
+/// parse(path, version=None, charset=None) -> Iterator[dict]
+///
+/// Parses a CWR file into one dict per line.
+#[pyfunction]
+#[pyo3(signature = (path, version=None, charset=None))]
+fn parse(path: &str, version: Option<f32>, charset: Option<String>) -> PyResult<Records> {
+    let options = ParseOptions { version_hint: version, charset_override: charset, ..Default::default() };
+    let records = allegro_cwr::process_cwr_stream_with_options(path, &options).map_err(cwr_error)?;
+    Ok(Records::new(records))
+}
+
+/// parse_bytes(data, version=None) -> Iterator[dict]
+///
+/// Parses CWR content held in memory, e.g. a downloaded file, into one dict per line.
+#[pyfunction]
+#[pyo3(signature = (data, version=None))]
+fn parse_bytes(data: &[u8], version: Option<f32>) -> PyResult<Records> {
+    let options = ParseOptions { version_hint: version, ..Default::default() };
+    let records =
+        allegro_cwr::process_cwr_reader_stream(std::io::Cursor::new(data.to_vec()), &options).map_err(cwr_error)?;
+    Ok(Records::new(records))
+}
+
+/// validate(path, version=None, charset=None) -> dict
+///
+/// Runs parser checks, the round-trip check and the default validation rules, returning the same report as
+/// `cwr-validate --format json` for a single file.
+#[pyfunction]
+#[pyo3(signature = (path, version=None, charset=None))]
+fn validate(py: Python<'_>, path: &str, version: Option<f32>, charset: Option<&str>) -> PyResult<PyObject> {
+    let report = py
+        .allow_threads(|| {
+            let mut engine = allegro_cwr_validate::RuleEngine::with_default_rules();
+            let report = allegro_cwr_validate::build_file_report(path, version, charset, &mut engine)
+                .map_err(|e| e.to_string())?;
+            serde_json::to_value(&report).map_err(|e| e.to_string())
+        })
+        .map_err(cwr_error)?;
+    to_py(py, &report)
+}
+
+/// cwr_to_json(path, output, version=None) -> int
+///
+/// Converts a CWR file to JSON, returning the number of records written.
+#[pyfunction]
+#[pyo3(signature = (path, output, version=None))]
+fn cwr_to_json(py: Python<'_>, path: &str, output: &str, version: Option<f32>) -> PyResult<usize> {
+    py.allow_threads(|| {
+        allegro_cwr_json::process_cwr_to_json_with_version_and_output(path, version, Some(output))
+            .map_err(|e| e.to_string())
+    })
+    .map_err(cwr_error)
+}
+
+/// json_to_cwr(path, output) -> int
+///
+/// Converts a JSON file written by `cwr_to_json` back to CWR, returning the number of records written.
+#[pyfunction]
+fn json_to_cwr(py: Python<'_>, path: &str, output: &str) -> PyResult<usize> {
+    py.allow_threads(|| {
+        allegro_cwr_json::process_json_to_cwr_with_version_and_output(path, None, Some(output))
+            .map_err(|e| e.to_string())
+    })
+    .map_err(cwr_error)
+}
+
+/// cwr_to_sqlite(path, db, version=None) -> tuple[int, int]
+///
+/// Imports a CWR file into a SQLite database, returning the file id and the number of records processed.
+#[pyfunction]
+#[pyo3(signature = (path, db, version=None))]
+fn cwr_to_sqlite(py: Python<'_>, path: &str, db: &str, version: Option<f32>) -> PyResult<(i64, usize)> {
+    py.allow_threads(|| {
+        allegro_cwr_sqlite::process_cwr_to_sqlite_with_version(path, db, version)
+            .map(|(file_id, count, _)| (file_id, count))
+            .map_err(|e| e.to_string())
+    })
+    .map_err(cwr_error)
+}
+
+#[pymodule]
+#[pyo3(name = "allegro_cwr")]
+fn allegro_cwr_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("CwrError", m.py().get_type::<CwrError>())?;
+    m.add_class::<Records>()?;
+    m.add_function(wrap_pyfunction!(parse, m)?)?;
+    m.add_function(wrap_pyfunction!(parse_bytes, m)?)?;
+    m.add_function(wrap_pyfunction!(validate, m)?)?;
+    m.add_function(wrap_pyfunction!(cwr_to_json, m)?)?;
+    m.add_function(wrap_pyfunction!(json_to_cwr, m)?)?;
+    m.add_function(wrap_pyfunction!(cwr_to_sqlite, m)?)?;
+    Ok(())
+}
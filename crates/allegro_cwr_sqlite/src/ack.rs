@@ -0,0 +1,198 @@
+//! Correlation of imported ACK files with the submissions they acknowledge
+//!
+//! Each ACK transaction is matched to the NWR/REV (or ISW/EXC) transaction it answers, using the original group
+//! ID, the original transaction sequence number and, when the ACK carries one, the submitter work number. The
+//! outcome is stored per ACK in `ack_status`, so the latest status of every submitted work can be queried.
+
+use crate::error::CwrDbError;
+use rusqlite::{Connection, OptionalExtension, params};
+
+/// Counts reported after correlating an ACK file
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AckCorrelation {
+    pub matched: usize,
+    pub unmatched: usize,
+}
+
+/// Acknowledgement status of a submitted work
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkAckStatus {
+    pub submission_file_id: i64,
+    pub cwr_nwr_id: i64,
+    pub transaction_type: String,
+    pub submitter_work_num: String,
+    pub work_title: String,
+    /// Transaction status code of the most recent ACK (e.g. `AS` accepted, `RJ` rejected)
+    pub transaction_status: String,
+    pub message_count: i64,
+    pub ack_file_id: i64,
+}
+
+/// Finds the submission for every ACK record of `ack_file_id` and records the results in `ack_status`,
+/// replacing any earlier correlation of the same file
+pub fn correlate_acks(conn: &mut Connection, ack_file_id: i64) -> Result<AckCorrelation, CwrDbError> {
+    let tx = conn.transaction()?;
+    tx.execute("DELETE FROM ack_status WHERE ack_file_id = ?1", [ack_file_id])?;
+
+    let acks: Vec<(i64, String, String, String, String, String, i64)> = tx
+        .prepare(
+            "SELECT a.cwr_ack_id, a.original_group_id, a.original_transaction_sequence_num,
+                    a.original_transaction_type, COALESCE(TRIM(a.submitter_creation_num), ''), a.transaction_status,
+                    (SELECT count(*) FROM cwr_msg m WHERE m.file_id = a.file_id
+                        AND CAST(m.transaction_sequence_num AS INTEGER) = CAST(a.transaction_sequence_num AS INTEGER))
+             FROM cwr_ack a WHERE a.file_id = ?1 ORDER BY a.cwr_ack_id",
+        )?
+        .query_map([ack_file_id], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?, row.get(5)?, row.get(6)?))
+        })?
+        .collect::<Result<_, _>>()?;
+
+    let mut correlation = AckCorrelation::default();
+    {
+        let mut find_submission = tx.prepare(
+            "SELECT n.file_id, n.cwr_nwr_id
+             FROM cwr_nwr n
+             JOIN file_line nl ON nl.file_id = n.file_id AND nl.record_type = n.record_type AND nl.record_id = n.cwr_nwr_id
+             WHERE n.file_id != ?1
+               AND n.record_type = ?2
+               AND CAST(n.transaction_sequence_num AS INTEGER) = CAST(?3 AS INTEGER)
+               AND (?4 = '' OR TRIM(n.submitter_work_num) = ?4)
+               AND (SELECT CAST(g.group_id AS INTEGER)
+                    FROM file_line gl JOIN cwr_grh g ON g.cwr_grh_id = gl.record_id
+                    WHERE gl.file_id = n.file_id AND gl.record_type = 'GRH' AND gl.line_number < nl.line_number
+                    ORDER BY gl.line_number DESC LIMIT 1) = CAST(?5 AS INTEGER)
+             ORDER BY n.file_id DESC LIMIT 1",
+        )?;
+        let mut insert_status = tx.prepare(
+            "INSERT INTO ack_status (ack_file_id, cwr_ack_id, submission_file_id, cwr_nwr_id, transaction_type,
+                                     submitter_work_num, transaction_status, message_count)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        )?;
+
+        for (cwr_ack_id, group_id, transaction_seq, transaction_type, submitter_work_num, status, messages) in &acks {
+            let submission: Option<(i64, i64)> = find_submission
+                .query_row(
+                    params![ack_file_id, transaction_type, transaction_seq, submitter_work_num, group_id],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )
+                .optional()?;
+            if submission.is_some() {
+                correlation.matched += 1;
+            } else {
+                correlation.unmatched += 1;
+            }
+            insert_status.execute(params![
+                ack_file_id,
+                cwr_ack_id,
+                submission.map(|(file_id, _)| file_id),
+                submission.map(|(_, nwr_id)| nwr_id),
+                transaction_type,
+                submitter_work_num,
+                status,
+                messages,
+            ])?;
+        }
+    }
+
+    tx.commit()?;
+    Ok(correlation)
+}
+
+/// Latest acknowledgement of each work submitted in `submission_file_id`
+pub fn work_ack_statuses(conn: &Connection, submission_file_id: i64) -> Result<Vec<WorkAckStatus>, CwrDbError> {
+    let mut stmt = conn.prepare(
+        "SELECT s.submission_file_id, s.cwr_nwr_id, s.transaction_type, TRIM(n.submitter_work_num),
+                TRIM(n.work_title), s.transaction_status, s.message_count, s.ack_file_id
+         FROM ack_status s JOIN cwr_nwr n ON n.cwr_nwr_id = s.cwr_nwr_id
+         WHERE s.submission_file_id = ?1
+           AND s.ack_status_id = (SELECT max(latest.ack_status_id) FROM ack_status latest
+                                  WHERE latest.cwr_nwr_id = s.cwr_nwr_id)
+         ORDER BY s.cwr_nwr_id",
+    )?;
+    let statuses = stmt
+        .query_map([submission_file_id], |row| {
+            Ok(WorkAckStatus {
+                submission_file_id: row.get(0)?,
+                cwr_nwr_id: row.get(1)?,
+                transaction_type: row.get(2)?,
+                submitter_work_num: row.get(3)?,
+                work_title: row.get(4)?,
+                transaction_status: row.get(5)?,
+                message_count: row.get(6)?,
+                ack_file_id: row.get(7)?,
+            })
+        })?
+        .collect::<Result<_, _>>()?;
+    Ok(statuses)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ImportOptions, process_cwr_to_sqlite_with_options};
+    use tempfile::tempdir;
+
+    const HDR: &str = "HDRPB285606836WARNER CHAPPELL MUSIC PUBLISHING LTD         01.102022122112541120221221";
+
+    fn nwr(transaction_seq: u32, submitter_work_num: &str) -> String {
+        format!(
+            "NWR{:08}00000000{:<60}  {:<14}     SER        Y       ORI",
+            transaction_seq, "TEST WORK TITLE", submitter_work_num
+        )
+    }
+
+    fn ack(transaction_seq: u32, original_seq: u32, submitter_work_num: &str, status: &str) -> String {
+        format!(
+            "ACK{:08}0000000020221221125411000010{:07}NWR{:<60}{:<20}{:<20}20221222{}",
+            transaction_seq, original_seq, "TEST WORK TITLE", submitter_work_num, "", status
+        )
+    }
+
+    #[test]
+    fn test_correlate_acks_with_submission() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("acks.db");
+        let db_path = db_path.to_str().unwrap();
+        let submission_path = temp_dir.path().join("submission.V21");
+        let ack_path = temp_dir.path().join("ack.V21");
+
+        let submission = [
+            HDR.to_string(),
+            "GRHNWR0000102.100000000000  ".to_string(),
+            nwr(0, "SW0000000001"),
+            nwr(1, "SW0000000002"),
+            "GRT000010000000200000004".to_string(),
+            "TRL000000010000000200000006".to_string(),
+        ];
+        std::fs::write(&submission_path, submission.join("\r\n")).unwrap();
+        let acks = [
+            HDR.to_string(),
+            "GRHACK0000102.100000000000  ".to_string(),
+            ack(0, 0, "SW0000000001", "AS"),
+            ack(1, 1, "SW0000000002", "RJ"),
+            "MSG0000000100000001E00000000NWRR001Record rejected due to invalid format".to_string(),
+            ack(2, 7, "SW0000000009", "RJ"),
+            "GRT000010000000300000006".to_string(),
+            "TRL000000010000000300000008".to_string(),
+        ];
+        std::fs::write(&ack_path, acks.join("\r\n")).unwrap();
+
+        let options = ImportOptions::default();
+        let (submission_id, _, _) =
+            process_cwr_to_sqlite_with_options(submission_path.to_str().unwrap(), db_path, &options).unwrap();
+        let (ack_id, _, _) = process_cwr_to_sqlite_with_options(ack_path.to_str().unwrap(), db_path, &options).unwrap();
+
+        let mut conn = Connection::open(db_path).unwrap();
+        let correlation = correlate_acks(&mut conn, ack_id).unwrap();
+        assert_eq!(correlation, AckCorrelation { matched: 2, unmatched: 1 });
+        // Correlating again replaces the earlier results
+        assert_eq!(correlate_acks(&mut conn, ack_id).unwrap(), correlation);
+
+        let statuses = work_ack_statuses(&conn, submission_id).unwrap();
+        let summary: Vec<_> = statuses
+            .iter()
+            .map(|s| (s.submitter_work_num.as_str(), s.transaction_status.as_str(), s.message_count))
+            .collect();
+        assert_eq!(summary, [("SW0000000001", "AS", 0), ("SW0000000002", "RJ", 1)]);
+    }
+}
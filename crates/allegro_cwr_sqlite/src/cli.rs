@@ -0,0 +1,632 @@
+use std::ffi::OsString;
+use std::process;
+use std::time::Instant;
+
+use allegro_cwr::OutputFormat;
+use allegro_cwr::parser::is_cwr_file;
+use allegro_cwr_cli::{
+    BaseConfig, BatchSummary, Direction, STATUS_TARGET, STDOUT_FILENAME, check_log_format,
+    get_output_filename_with_default_extension, get_value, init_logging_and_parse_args, output_file, read_stdin,
+    status, status_error, watch_directory, watch_output_filename,
+};
+use tracing::info;
+
+#[derive(Default)]
+struct Config {
+    base: BaseConfig,
+    direction: Direction,
+    output_filename: Option<String>,
+    file_id: Option<i64>,
+    store_original_lines: bool,
+    on_duplicate: crate::DuplicatePolicy,
+    correlate_acks: bool,
+    auto_rev: bool,
+    title_search: bool,
+    search: Option<String>,
+    duplicates: bool,
+    schema: crate::SchemaKind,
+    /// Write SQL statements instead of a database
+    sql: bool,
+    sql_dump: crate::SqlDumpOptions,
+    /// Files imported at the same time when several go into one database
+    jobs: usize,
+}
+
+/// A file to convert, or stdin read into memory
+#[derive(Clone, Copy)]
+enum Input<'a> {
+    File(&'a str),
+    Stdin(&'a [u8]),
+}
+
+impl Input<'_> {
+    /// Name recorded in the database and used in messages
+    fn name(&self) -> &str {
+        match self {
+            Input::File(filename) => filename,
+            Input::Stdin(_) => "stdin",
+        }
+    }
+}
+
+/// What an input file holds, detected from its content
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum InputFormat {
+    Cwr,
+    /// JSON written by `cwr-json`, imported like the CWR file it came from
+    Json,
+    Sqlite,
+}
+
+impl InputFormat {
+    fn detect(input: Input) -> Result<Self, allegro_cwr::CwrParseError> {
+        let (is_cwr, is_json) = match input {
+            Input::File(filename) => (is_cwr_file(filename)?, allegro_cwr_json::is_json_file(filename)?),
+            Input::Stdin(content) => {
+                (allegro_cwr::is_cwr_bytes(content)?, allegro_cwr_json::starts_with_json(&mut &content[..])?)
+            }
+        };
+        if is_cwr {
+            Ok(InputFormat::Cwr)
+        } else if is_json {
+            Ok(InputFormat::Json)
+        } else {
+            Ok(InputFormat::Sqlite)
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            InputFormat::Cwr => "CWR",
+            InputFormat::Json => "JSON",
+            InputFormat::Sqlite => "SQLite",
+        }
+    }
+
+    /// Whether the file is imported into a database rather than exported from one
+    fn is_import(self) -> bool {
+        self != InputFormat::Sqlite
+    }
+}
+
+fn parse_args(args: Vec<OsString>) -> Result<Config, String> {
+    let mut config = Config::default();
+    let mut parser = lexopt::Parser::from_args(args);
+
+    while let Ok(Some(arg)) = parser.next() {
+        match arg {
+            lexopt::Arg::Long("cwr") => {
+                let version_str = get_value(&mut parser, "cwr")?;
+                config.base.set_cwr_version(&version_str)?;
+            }
+            lexopt::Arg::Short('o') | lexopt::Arg::Long("output") => {
+                let output_filename = get_value(&mut parser, "output")?;
+                config.output_filename = Some(output_filename);
+            }
+            lexopt::Arg::Long("file-id") => {
+                let file_id_str = get_value(&mut parser, "file-id")?;
+                let file_id: i64 = file_id_str
+                    .parse()
+                    .map_err(|_| format!("Invalid file ID '{}'. Must be a positive integer", file_id_str))?;
+
+                if file_id <= 0 {
+                    return Err(format!("Invalid file ID '{}'. Must be a positive integer", file_id));
+                }
+
+                config.file_id = Some(file_id);
+            }
+            lexopt::Arg::Long("original-lines") => {
+                config.store_original_lines = true;
+            }
+            lexopt::Arg::Long("ack") => {
+                config.correlate_acks = true;
+            }
+            lexopt::Arg::Long("auto-rev") => {
+                config.auto_rev = true;
+            }
+            lexopt::Arg::Long("schema") => {
+                config.schema = get_value(&mut parser, "schema")?.parse()?;
+            }
+            lexopt::Arg::Long("title-search") => {
+                config.title_search = true;
+            }
+            lexopt::Arg::Long("duplicates") => {
+                config.duplicates = true;
+            }
+            lexopt::Arg::Long("search") => {
+                config.search = Some(get_value(&mut parser, "search")?);
+            }
+            lexopt::Arg::Long("on-duplicate") => {
+                config.on_duplicate = get_value(&mut parser, "on-duplicate")?.parse()?;
+            }
+            lexopt::Arg::Long("sql") => {
+                config.sql = true;
+            }
+            lexopt::Arg::Long("sql-dialect") => {
+                config.sql_dump.dialect = get_value(&mut parser, "sql-dialect")?.parse()?;
+                config.sql = true;
+            }
+            lexopt::Arg::Long("first-id") => {
+                let first_id = get_value(&mut parser, "first-id")?;
+                config.sql_dump.first_id = first_id
+                    .parse()
+                    .ok()
+                    .filter(|first_id| *first_id > 0)
+                    .ok_or_else(|| format!("Invalid first ID '{}'. Must be a positive integer", first_id))?;
+            }
+            lexopt::Arg::Long("jobs") => {
+                let jobs = get_value(&mut parser, "jobs")?;
+                config.jobs = jobs
+                    .parse()
+                    .ok()
+                    .filter(|jobs| *jobs > 0)
+                    .ok_or_else(|| format!("Invalid number of jobs '{}'. Must be a positive integer", jobs))?;
+            }
+            lexopt::Arg::Long("watch") => {
+                config.base.watch_dir = Some(get_value(&mut parser, "watch")?);
+            }
+            lexopt::Arg::Long("poll-interval") => {
+                let seconds = get_value(&mut parser, "poll-interval")?;
+                config.base.set_poll_interval(&seconds)?;
+            }
+            lexopt::Arg::Value(val) => {
+                config.base.add_input_file(val.to_string_lossy().to_string())?;
+            }
+            lexopt::Arg::Short('q' | 'v') | lexopt::Arg::Long("quiet" | "verbose") => {}
+            lexopt::Arg::Long("log-format") => check_log_format(&mut parser)?,
+            lexopt::Arg::Short('h') | lexopt::Arg::Long("help") => {
+                print_help();
+                process::exit(0);
+            }
+            _ => {
+                return Err("Unknown argument".to_string());
+            }
+        }
+    }
+
+    config.base.finalize();
+    Ok(config)
+}
+
+fn get_most_recent_file_id(db_filename: &str) -> Result<i64, Box<dyn std::error::Error>> {
+    let conn = rusqlite::Connection::open(db_filename)?;
+
+    let file_id: i64 = conn
+        .query_row("SELECT file_id FROM file ORDER BY imported_on DESC LIMIT 1", [], |row| row.get(0))
+        .map_err(|e| match e {
+            rusqlite::Error::QueryReturnedNoRows => "No files found in database".to_string(),
+            _ => format!("Database error: {}", e),
+        })?;
+
+    Ok(file_id)
+}
+
+/// Runs the command line tool with `args` (excluding the program name)
+pub fn run(args: impl IntoIterator<Item = OsString>) {
+    run_with_direction(args, Direction::Auto);
+}
+
+/// Like [`run`], but rejects input that would convert the other way than `direction`
+pub fn run_with_direction(args: impl IntoIterator<Item = OsString>, direction: Direction) {
+    let args: Vec<OsString> = args.into_iter().collect();
+    let config = init_logging_and_parse_args(|| {
+        parse_args(args).inspect_err(|_| {
+            print_help();
+        })
+    });
+    let config = Config { direction, ..config };
+
+    if let Some(query) = config.search.as_deref() {
+        search(&config, query);
+        return;
+    }
+    if config.duplicates {
+        report_duplicates(&config);
+        return;
+    }
+
+    if let Some(watch_dir) = config.base.watch_dir.as_deref() {
+        watch_directory(watch_dir, config.base.poll_interval, |input_filename| {
+            let output_filename = config
+                .output_filename
+                .clone()
+                .unwrap_or_else(|| watch_output_filename(watch_dir, input_filename, "db"));
+            process_file(&config, Input::File(input_filename), InputFormat::Cwr, Some(&output_filename))
+                .map_err(|e| e.to_string())
+        });
+    }
+
+    let start_time = Instant::now();
+
+    if config.base.read_stdin {
+        process_stdin(&config, start_time);
+    } else if config.jobs > 1
+        && output_file(config.output_filename.as_deref()).is_some()
+        && config.base.input_files.len() > 1
+    {
+        process_files_in_parallel(&config, start_time);
+    } else {
+        process_files(&config, start_time);
+    }
+}
+
+fn process_stdin(config: &Config, start_time: Instant) {
+    let content = read_stdin();
+    let input = Input::Stdin(&content);
+    let format = match InputFormat::detect(input) {
+        Ok(format) => format,
+        Err(e) => {
+            status_error!("Error reading stdin: {}", e);
+            process::exit(1);
+        }
+    };
+    if let Err(e) = config.direction.check(format.is_import(), "SQLite") {
+        status_error!("Error processing stdin: {}", e);
+        process::exit(1);
+    }
+
+    let result = process_file(config, input, format, config.output_filename.as_deref());
+    let elapsed_time = start_time.elapsed();
+
+    let count = match result {
+        Ok(c) => c,
+        Err(e) => {
+            status_error!("Error processing stdin after {:.2?}: {}", elapsed_time, e);
+            process::exit(1);
+        }
+    };
+
+    status!(
+        "Successfully processed {} CWR records from stdin in {:.2?}",
+        allegro_cwr::format_int_with_commas(count as i64),
+        elapsed_time
+    );
+}
+
+fn process_files(config: &Config, start_time: Instant) {
+    let mut summary = BatchSummary::default();
+
+    for input_filename in &config.base.input_files {
+        info!("Processing CWR file: {}", input_filename);
+
+        let format = match InputFormat::detect(Input::File(input_filename)) {
+            Ok(format) => format,
+            Err(e) => {
+                status_error!("Error reading file '{}': {}", input_filename, e);
+                summary.add_failure(input_filename, &e);
+                continue;
+            }
+        };
+        if let Err(e) = config.direction.check(format.is_import(), "SQLite") {
+            status_error!("Error processing file '{}': {}", input_filename, e);
+            summary.add_failure(input_filename, &e);
+            continue;
+        }
+
+        status!("Processing input file: {} (detected format: {})", input_filename, format.name());
+
+        let output_filename = get_output_filename_with_default_extension(
+            config.output_filename.as_deref(),
+            input_filename,
+            config.base.input_files.len(),
+            summary.files_processed(),
+            match (format, config.sql) {
+                (InputFormat::Sqlite, _) => "cwr",
+                (_, true) => "sql",
+                (_, false) => "db",
+            },
+        );
+
+        let result = process_file(config, Input::File(input_filename), format, output_filename.as_deref());
+
+        match result {
+            Ok(count) => {
+                summary.add_success(input_filename, count);
+                info!("Processed {} records from '{}'", count, input_filename);
+            }
+            Err(e) => {
+                status_error!("Error processing file '{}': {}", input_filename, e);
+                summary.add_failure(input_filename, &e);
+            }
+        }
+    }
+
+    let elapsed_time = start_time.elapsed();
+    info!("Processing completed");
+
+    if config.base.input_files.len() == 1 {
+        let failed_files = summary.failed_files();
+        if !failed_files.is_empty() {
+            status_error!("Failed to process {} file(s): {}", failed_files.len(), failed_files.join(", "));
+            process::exit(1);
+        }
+
+        status!(
+            "Successfully processed {} CWR records from '{}' in {:.2?}",
+            allegro_cwr::format_int_with_commas(summary.total_records() as i64),
+            &config.base.input_files[0],
+            elapsed_time
+        );
+    } else {
+        summary.print("Successfully processed", elapsed_time);
+        if !summary.failed_files().is_empty() {
+            process::exit(1);
+        }
+    }
+}
+
+/// Imports all input files into the `--output` database, `--jobs` at a time
+fn process_files_in_parallel(config: &Config, start_time: Instant) {
+    let db_filename = config.output_filename.as_deref().unwrap_or_default();
+    let mut read_errors = Vec::new();
+    let mut cwr_files = Vec::new();
+    for input_filename in &config.base.input_files {
+        match is_cwr_file(input_filename) {
+            Ok(true) => {
+                cwr_files.push(input_filename.clone());
+                read_errors.push(None);
+            }
+            Ok(false) => read_errors.push(Some("not a CWR file".to_string())),
+            Err(e) => read_errors.push(Some(e.to_string())),
+        }
+    }
+
+    let options = crate::ImportOptions {
+        version_hint: config.base.cwr_version,
+        store_original_lines: config.store_original_lines,
+        on_duplicate: config.on_duplicate,
+        correlate_acks: config.correlate_acks,
+        title_search: config.title_search,
+        schema: config.schema,
+    };
+    let batch = match crate::parallel::import_files(&cwr_files, db_filename, &options, config.jobs) {
+        Ok(batch) => batch,
+        Err(e) => {
+            status_error!("Error importing into '{}': {}", db_filename, e);
+            process::exit(1);
+        }
+    };
+
+    let mut summary = BatchSummary::default();
+    let mut imports = batch.files.iter();
+    for (input_filename, read_error) in config.base.input_files.iter().zip(read_errors) {
+        let result = match read_error {
+            Some(e) => Err(e),
+            None => imports.next().map_or_else(
+                || Err("File was not imported".to_string()),
+                |file| file.result.as_ref().map(|imported| imported.record_count).map_err(Clone::clone),
+            ),
+        };
+        match result {
+            Ok(count) => summary.add_success(input_filename, count),
+            Err(e) => {
+                status_error!("Error processing file '{}': {}", input_filename, e);
+                summary.add_failure(input_filename, e);
+            }
+        }
+    }
+
+    summary.print("Successfully processed", start_time.elapsed());
+    if !summary.failed_files().is_empty() {
+        process::exit(1);
+    }
+}
+
+fn process_file(
+    config: &Config, input: Input, format: InputFormat, output_filename: Option<&str>,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let to_stdout = output_filename == Some(STDOUT_FILENAME);
+    let output_filename = output_file(output_filename);
+    if format == InputFormat::Json && config.sql {
+        Err("--sql needs CWR input".into())
+    } else if format == InputFormat::Cwr && config.sql {
+        let options = crate::ImportOptions {
+            version_hint: config.base.cwr_version,
+            store_original_lines: config.store_original_lines,
+            ..Default::default()
+        };
+        let writer: Box<dyn std::io::Write> = match output_filename {
+            Some(output_filename) => Box::new(std::io::BufWriter::new(std::fs::File::create(output_filename)?)),
+            None => Box::new(std::io::stdout().lock()),
+        };
+        let (count, report) = match input {
+            Input::File(input_filename) => {
+                crate::process_cwr_to_sql(input_filename, writer, &options, &config.sql_dump)?
+            }
+            Input::Stdin(content) => {
+                crate::process_cwr_bytes_to_sql(input.name(), content, writer, &options, &config.sql_dump)?
+            }
+        };
+        info!("{}", report);
+        Ok(count)
+    } else if format.is_import() && to_stdout {
+        Err("A SQLite database cannot be written to stdout; use --sql for the SQL statements".into())
+    } else if format.is_import() {
+        // CWR or JSON -> SQLite
+        let db_filename = crate::determine_db_filename(input.name(), output_filename);
+        info!("Using database filename: '{}'", db_filename);
+
+        let options = crate::ImportOptions {
+            version_hint: config.base.cwr_version,
+            store_original_lines: config.store_original_lines,
+            on_duplicate: config.on_duplicate,
+            correlate_acks: config.correlate_acks,
+            title_search: config.title_search,
+            schema: config.schema,
+        };
+        let result = match (input, format) {
+            (Input::File(input_filename), InputFormat::Json) => {
+                crate::process_json_to_sqlite_with_options(input_filename, &db_filename, &options)
+            }
+            (Input::File(input_filename), _) => {
+                crate::process_cwr_to_sqlite_with_options(input_filename, &db_filename, &options)
+            }
+            (Input::Stdin(content), InputFormat::Json) => {
+                crate::process_json_bytes_to_sqlite_with_options(input.name(), content, &db_filename, &options)
+            }
+            (Input::Stdin(content), _) => {
+                crate::process_cwr_bytes_to_sqlite_with_options(input.name(), content, &db_filename, &options)
+            }
+        };
+        match result {
+            Ok((file_id, count, report)) => {
+                status!("{}", report);
+                if config.schema == crate::SchemaKind::Raw {
+                    match crate::report::report_summary(&db_filename, file_id, OutputFormat::Sql) {
+                        Ok(summary) => print!("{}", summary),
+                        Err(e) => tracing::warn!(target: STATUS_TARGET, "Could not generate detailed report: {}", e),
+                    }
+                }
+                Ok(count)
+            }
+            Err(e) => Err(e),
+        }
+    } else {
+        // SQLite -> CWR (new functionality)
+        let Input::File(input_filename) = input else {
+            return Err("A SQLite database cannot be read from stdin; pass its file name".into());
+        };
+        // Use specified file_id or get the most recent one
+        let file_id = match config.file_id {
+            Some(id) => {
+                info!("Using specified file ID: {}", id);
+                Ok(id)
+            }
+            None => {
+                info!("No file ID specified, using most recent file from database");
+                get_most_recent_file_id(input_filename)
+            }
+        };
+
+        match file_id {
+            Ok(id) => crate::process_sqlite_to_cwr_with_options(
+                input_filename,
+                id,
+                output_filename,
+                &crate::ExportOptions { version_hint: config.base.cwr_version, registration_aware: config.auto_rev },
+            ),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// Number of matches `--search` prints per database
+const SEARCH_LIMIT: usize = 50;
+
+fn search(config: &Config, query: &str) {
+    if config.base.input_files.is_empty() {
+        status_error!("--search needs a database file");
+        process::exit(1);
+    }
+    for db_filename in &config.base.input_files {
+        match crate::search_titles(db_filename, query, SEARCH_LIMIT) {
+            Ok(matches) => {
+                for title_match in matches {
+                    println!(
+                        "{}\t{}\t{}\t{}\t{}",
+                        title_match.file_id,
+                        title_match.kind,
+                        title_match.text.trim_end(),
+                        title_match.submitter_work_num.as_deref().map(str::trim_end).unwrap_or(""),
+                        title_match.work_title.as_deref().map(str::trim_end).unwrap_or("")
+                    );
+                }
+            }
+            Err(e) => {
+                status_error!("Error searching '{}': {}", db_filename, e);
+                process::exit(1);
+            }
+        }
+    }
+}
+
+fn report_duplicates(config: &Config) {
+    if config.base.input_files.is_empty() {
+        status_error!("--duplicates needs a database file");
+        process::exit(1);
+    }
+    for db_filename in &config.base.input_files {
+        let duplicates = rusqlite::Connection::open(db_filename)
+            .map_err(crate::CwrDbError::from)
+            .and_then(|conn| crate::find_duplicate_works(&conn));
+        match duplicates {
+            Ok(duplicates) => {
+                for duplicate in &duplicates {
+                    print!("{}", duplicate);
+                }
+                println!("{}: {} conflicting works", db_filename, duplicates.len());
+            }
+            Err(e) => {
+                status_error!("Error finding duplicates in '{}': {}", db_filename, e);
+                process::exit(1);
+            }
+        }
+    }
+}
+
+fn print_help() {
+    eprintln!("Usage: cwr-sqlite [OPTIONS] [FILES...]");
+    eprintln!();
+    eprintln!("Arguments:");
+    eprintln!(
+        "  [FILES...]          CWR, cwr-json JSON or SQLite database files to process. If no files specified or a file is -, reads from stdin"
+    );
+    eprintln!("                      Directories and patterns such as \'*.V21\' expand to the matching CWR files");
+    eprintln!();
+    eprintln!("Options:");
+    eprintln!(
+        "  -o, --output <file>      Output file path (SQLite database or CWR file), or - to write CWR or --sql to stdout"
+    );
+    eprintln!(
+        "      --cwr <version>      CWR version (2.0, 2.1, 2.2). Auto-detected from filename (.Vxx) or file content if not specified"
+    );
+    eprintln!("      --file-id <id>       File ID to export from SQLite database (defaults to most recent)");
+    eprintln!("      --original-lines     Keep the verbatim source line of every record in file_line.original_line");
+    eprintln!("      --ack                Match the file's ACK transactions to works imported earlier (ack_status)");
+    eprintln!("      --auto-rev           On export, send works an ACK has accepted as REV and the rest as NWR");
+    eprintln!("      --schema <schema>    Tables to import into: raw (default, one table per record type) or");
+    eprintln!("                           normalized (work, party, work_party, shares and territories)");
+    eprintln!("      --title-search       Add a full-text index of work titles, alternate titles and performers");
+    eprintln!("      --search <query>     Search the title index of the given databases (FTS5 query syntax); prints");
+    eprintln!("                           file ID, kind, matching text, submitter work number and work title");
+    eprintln!("      --duplicates         List works registered in several imported files (same ISWC, or same title");
+    eprintln!("                           and writer IPIs) whose submitter work numbers, ISWCs or shares differ");
+    eprintln!(
+        "      --sql                Write CWR input as SQL statements (schema and INSERTs) instead of a database,"
+    );
+    eprintln!("                           to stdout unless --output is given");
+    eprintln!("      --sql-dialect <d>    Database the --sql statements are for: sqlite (default), postgres or mysql");
+    eprintln!("      --first-id <n>       Id of the first row --sql writes to each table (default: 1); raise it to");
+    eprintln!("                           load into a database that already holds rows");
+    eprintln!("      --jobs <n>           Import up to <n> files at the same time into the --output database");
+    eprintln!("      --on-duplicate <p>   When the file's content was already imported: skip (default), replace,");
+    eprintln!("                           or new-version (import again with the next file.import_version)");
+    eprintln!(
+        "      --watch <dir>        Process CWR files dropped into <dir>, moving them to <dir>/done or <dir>/failed"
+    );
+    eprintln!("      --poll-interval <s>  Seconds between scans of the watched directory (default: 2)");
+    eprintln!("  -q, --quiet              Only print errors");
+    eprintln!("  -v, --verbose            Also print info logging");
+    eprintln!("      --log-format <fmt>   Message format on stderr: text (default) or json");
+    eprintln!("  -h, --help               Show this help message");
+    eprintln!();
+    eprintln!("Bidirectional converter:");
+    eprintln!("  CWR → SQLite: cwr-sqlite file.cwr [-o output.db]");
+    eprintln!("  JSON → SQLite: cwr-sqlite file.json [-o output.db]");
+    eprintln!("  SQLite → CWR: cwr-sqlite file.db [-o output.cwr]");
+    eprintln!();
+    eprintln!("Input format auto-detected by content (CWR starts with 'HDR', JSON with '{{')");
+    eprintln!("For CWR → SQLite: creates <input_filename>.db by default, or numbered variants if it exists");
+    eprintln!("(.1.db, .2.db, etc.). Multiple files can be imported into the same database.");
+    eprintln!();
+    eprintln!("Examples:");
+    eprintln!("  cwr-sqlite input.cwr                         # Convert CWR to SQLite");
+    eprintln!("  cwr-sqlite *.cwr                             # Convert multiple CWR files");
+    eprintln!("  cwr-sqlite -o output.db input.cwr            # Specify output database");
+    eprintln!("  cwr-sqlite input.db                          # Convert SQLite to CWR");
+    eprintln!("  cwr-sqlite --file-id 123 input.db           # Convert specific file ID from SQLite");
+    eprintln!("  cat input.cwr | cwr-sqlite                   # Process CWR data from stdin");
+    eprintln!("  cwr-sqlite --title-search -o cat.db *.cwr    # Import with the title search index");
+    eprintln!("  cwr-sqlite --search 'baby can*' cat.db      # Search titles and performers");
+    eprintln!("  cwr-sqlite --duplicates cat.db              # Conflicting registrations across deliveries");
+}
@@ -1,7 +1,7 @@
 use crate::error::CwrDbError;
 use allegro_cwr_cli::find_next_available_filename;
-use log::info;
 use rusqlite::Connection;
+use tracing::info;
 
 /// Main database manager for CWR operations
 pub struct CwrDatabase {
@@ -9,9 +9,10 @@ pub struct CwrDatabase {
 }
 
 impl CwrDatabase {
-    /// Open or create a CWR database
+    /// Open or create a CWR database, refusing one written by a newer, incompatible version of this crate
     pub fn open(db_filename: &str) -> Result<Self, CwrDbError> {
         let conn = Connection::open(db_filename)?;
+        check_schema_version(&conn)?;
         Ok(CwrDatabase { connection: conn })
     }
 
@@ -43,12 +44,28 @@ pub fn determine_db_filename(input_filename: &str, output_path: Option<&str>) ->
     }
 }
 
-/// Sets up the CWR database schema
-pub fn setup_database(db_filename: &str) -> Result<(), CwrDbError> {
-    // Schema is embedded directly into the binary at compile time
-    const SCHEMA_SQL: &str = include_str!("schema.sql");
+/// The schema of a new database, embedded into the binary at compile time
+pub const SCHEMA_SQL: &str = include_str!("schema.sql");
+
+/// Version of the schema this crate writes, stored in SQLite's `user_version`. Bump it when a change to the
+/// schema cannot be read or written by earlier versions of the crate.
+pub const SCHEMA_VERSION: i64 = 1;
 
+/// Returns the database's schema version (0 for databases created before the version was stored), or an error if
+/// it is newer than [`SCHEMA_VERSION`]
+pub fn check_schema_version(conn: &Connection) -> Result<i64, CwrDbError> {
+    let version: i64 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+    if version > SCHEMA_VERSION {
+        return Err(CwrDbError::IncompatibleSchema { found: version, supported: SCHEMA_VERSION });
+    }
+    Ok(version)
+}
+
+/// Sets up the CWR database schema, upgrading databases created by earlier versions of this crate. Databases with
+/// a newer schema version are refused before anything is written.
+pub fn setup_database(db_filename: &str) -> Result<(), CwrDbError> {
     let conn = Connection::open(db_filename)?;
+    check_schema_version(&conn)?;
 
     // Check if tables already exist to avoid erroring on re-runs
     let table_count: i64 =
@@ -62,6 +79,57 @@ pub fn setup_database(db_filename: &str) -> Result<(), CwrDbError> {
     } else {
         info!("Database schema already exists, ready for import");
     }
+    upgrade_schema(&conn)?;
+    conn.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+
+    Ok(())
+}
+
+/// Columns added after the first release of the schema, as (table, column, definition)
+const ADDED_COLUMNS: &[(&str, &str, &str)] = &[
+    ("file_line", "original_line", "TEXT"),
+    ("file", "content_hash", "TEXT"),
+    ("file", "import_version", "INTEGER NOT NULL DEFAULT 1"),
+    ("error", "record_type", "VARCHAR(3)"),
+    ("error", "level", "VARCHAR(8)"),
+    ("error", "field_name", "TEXT"),
+    ("error", "source", "TEXT"),
+];
+
+/// Tables added after the first release of the schema
+const ADDED_TABLES: &str = "
+CREATE TABLE IF NOT EXISTS ack_status (
+    ack_status_id INTEGER PRIMARY KEY,
+    ack_file_id INTEGER NOT NULL,
+    cwr_ack_id INTEGER NOT NULL,
+    submission_file_id INTEGER,
+    cwr_nwr_id INTEGER,
+    transaction_type VARCHAR(3) NOT NULL,
+    submitter_work_num VARCHAR(20),
+    transaction_status VARCHAR(2) NOT NULL,
+    message_count INTEGER NOT NULL DEFAULT 0
+);
+CREATE INDEX IF NOT EXISTS idx_ack_status_ack_file ON ack_status(ack_file_id);
+CREATE INDEX IF NOT EXISTS idx_ack_status_work ON ack_status(submission_file_id, cwr_nwr_id);
+";
 
+/// Work-centric views (`v_work_writers`, `v_work_publishers`, `v_work_territories`), recreated when missing
+const VIEWS_SQL: &str = include_str!("views.sql");
+
+/// Adds tables, columns and indexes introduced after a database was created
+fn upgrade_schema(conn: &Connection) -> Result<(), CwrDbError> {
+    for (table, column, definition) in ADDED_COLUMNS {
+        let exists: i64 =
+            conn.query_row("SELECT count(*) FROM pragma_table_info(?1) WHERE name = ?2", [table, column], |row| {
+                row.get(0)
+            })?;
+        if exists == 0 {
+            info!("Adding {} column to {}", column, table);
+            conn.execute_batch(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition))?;
+        }
+    }
+    conn.execute_batch("CREATE INDEX IF NOT EXISTS idx_file_content_hash ON file(content_hash)")?;
+    conn.execute_batch(ADDED_TABLES)?;
+    conn.execute_batch(VIEWS_SQL)?;
     Ok(())
 }
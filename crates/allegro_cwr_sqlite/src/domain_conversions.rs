@@ -25,6 +25,12 @@ impl CwrToSqlString for String {
     }
 }
 
+impl CwrToSqlString for std::sync::Arc<str> {
+    fn to_sql_string(&self) -> String {
+        self.to_string()
+    }
+}
+
 impl CwrToSqlString for RecordingFormat {
     fn to_sql_string(&self) -> String {
         self.as_str().to_string()
@@ -332,6 +338,12 @@ impl CwrToSqlString for Ean {
 }
 
 // Integer conversions for numeric domain types
+impl CwrToSqlInt for Duration {
+    fn to_sql_int(&self) -> i64 {
+        self.0 as i64
+    }
+}
+
 impl CwrToSqlInt for OwnershipShare {
     fn to_sql_int(&self) -> i64 {
         self.0 as i64
@@ -729,6 +741,16 @@ impl CwrFromSqlString for CompositeComponentCount {
 }
 
 // Integer parsing implementations
+impl CwrFromSqlInt for Duration {
+    fn from_sql_int(value: i64) -> Result<Self, String> {
+        if !(0..=Duration::MAX.0 as i64).contains(&value) {
+            Err(format!("Duration value {} is out of range 0-{}", value, Duration::MAX.0))
+        } else {
+            Ok(Duration(value as u32))
+        }
+    }
+}
+
 impl CwrFromSqlInt for OwnershipShare {
     fn from_sql_int(value: i64) -> Result<Self, String> {
         if !(0..=10000).contains(&value) {
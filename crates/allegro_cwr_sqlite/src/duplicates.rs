@@ -0,0 +1,324 @@
+//! Detection of works registered in more than one imported file with conflicting details
+//!
+//! Registrations (NWR, REV, ISW and EXC outside ACK groups) are the same work when they share an ISWC, or the same
+//! normalized title and set of writer IPI name numbers. Matches chain: a work matched by ISWC to one registration
+//! and by title and writers to another links all three.
+
+use crate::error::CwrDbError;
+use rusqlite::Connection;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+
+/// Ownership shares of one publisher or writer of a registration, in hundredths of a percent
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct PartyShare {
+    /// SPU, OPU, SWR or OWR
+    pub record_type: String,
+    /// IPI name number, or the interested party number when the record has none
+    pub party: String,
+    pub pr_ownership_share: i64,
+    pub mr_ownership_share: i64,
+    pub sr_ownership_share: i64,
+}
+
+/// One registration of a work in an imported file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkRegistration {
+    pub file_id: i64,
+    pub cwr_nwr_id: i64,
+    pub submitter_work_num: String,
+    pub work_title: String,
+    pub iswc: Option<String>,
+    /// Sorted, so registrations listing the same parties in another order compare equal
+    pub shares: Vec<PartyShare>,
+    writer_ipis: BTreeSet<String>,
+}
+
+/// What differs between registrations of the same work
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum WorkConflict {
+    SubmitterWorkNum,
+    Iswc,
+    Shares,
+}
+
+impl WorkConflict {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WorkConflict::SubmitterWorkNum => "submitter work number",
+            WorkConflict::Iswc => "ISWC",
+            WorkConflict::Shares => "shares",
+        }
+    }
+}
+
+/// A work registered in several files with differing numbers or shares
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateWork {
+    /// In import order
+    pub registrations: Vec<WorkRegistration>,
+    pub conflicts: Vec<WorkConflict>,
+}
+
+impl fmt::Display for DuplicateWork {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let files: BTreeSet<i64> = self.registrations.iter().map(|r| r.file_id).collect();
+        let conflicts: Vec<&str> = self.conflicts.iter().map(WorkConflict::as_str).collect();
+        let title = self.registrations.first().map_or("", |r| r.work_title.as_str());
+        writeln!(f, "'{}' in {} files: {} differ", title, files.len(), conflicts.join(", "))?;
+        for registration in &self.registrations {
+            let shares: Vec<String> = registration
+                .shares
+                .iter()
+                .map(|s| {
+                    format!(
+                        "{} {} {}/{}/{}",
+                        s.record_type,
+                        s.party,
+                        format_share(s.pr_ownership_share),
+                        format_share(s.mr_ownership_share),
+                        format_share(s.sr_ownership_share)
+                    )
+                })
+                .collect();
+            writeln!(
+                f,
+                "  file {}  {}  {}  {}",
+                registration.file_id,
+                registration.submitter_work_num,
+                registration.iswc.as_deref().unwrap_or("-"),
+                shares.join("; ")
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn format_share(hundredths: i64) -> String {
+    format!("{}.{:02}", hundredths / 100, hundredths % 100)
+}
+
+/// Works registered in at least two files whose registrations differ in submitter work number, ISWC or shares
+pub fn find_duplicate_works(conn: &Connection) -> Result<Vec<DuplicateWork>, CwrDbError> {
+    let registrations = load_registrations(conn)?;
+
+    let mut parent: Vec<usize> = (0..registrations.len()).collect();
+    let mut first_with_key: HashMap<String, usize> = HashMap::new();
+    for (index, registration) in registrations.iter().enumerate() {
+        let iswc_key = registration.iswc.as_ref().map(|iswc| format!("iswc:{}", iswc));
+        let title_key = (!registration.writer_ipis.is_empty()).then(|| {
+            let ipis: Vec<&str> = registration.writer_ipis.iter().map(String::as_str).collect();
+            format!("title:{}|{}", normalize_title(&registration.work_title), ipis.join(","))
+        });
+        for key in [iswc_key, title_key].into_iter().flatten() {
+            match first_with_key.get(&key) {
+                Some(&other) => union(&mut parent, index, other),
+                None => {
+                    first_with_key.insert(key, index);
+                }
+            }
+        }
+    }
+
+    let mut clusters: Vec<Vec<WorkRegistration>> = Vec::new();
+    let mut cluster_of_root: HashMap<usize, usize> = HashMap::new();
+    for (index, registration) in registrations.into_iter().enumerate() {
+        let root = find(&mut parent, index);
+        match cluster_of_root.get(&root) {
+            Some(&cluster) => clusters[cluster].push(registration),
+            None => {
+                cluster_of_root.insert(root, clusters.len());
+                clusters.push(vec![registration]);
+            }
+        }
+    }
+
+    Ok(clusters
+        .into_iter()
+        .filter_map(|registrations| {
+            let files: BTreeSet<i64> = registrations.iter().map(|r| r.file_id).collect();
+            if files.len() < 2 {
+                return None;
+            }
+            let differs = |value: fn(&WorkRegistration) -> String| {
+                registrations.iter().map(value).collect::<BTreeSet<_>>().len() > 1
+            };
+            let mut conflicts = Vec::new();
+            if differs(|r| r.submitter_work_num.clone()) {
+                conflicts.push(WorkConflict::SubmitterWorkNum);
+            }
+            if differs(|r| r.iswc.clone().unwrap_or_default()) {
+                conflicts.push(WorkConflict::Iswc);
+            }
+            if differs(|r| format!("{:?}", r.shares)) {
+                conflicts.push(WorkConflict::Shares);
+            }
+            (!conflicts.is_empty()).then_some(DuplicateWork { registrations, conflicts })
+        })
+        .collect())
+}
+
+fn load_registrations(conn: &Connection) -> Result<Vec<WorkRegistration>, CwrDbError> {
+    let mut stmt = conn.prepare(
+        "SELECT n.file_id, n.cwr_nwr_id, TRIM(n.submitter_work_num), TRIM(n.work_title), NULLIF(TRIM(n.iswc), '')
+         FROM cwr_nwr n
+         JOIN file_line l ON l.file_id = n.file_id AND l.record_id = n.cwr_nwr_id
+             AND l.record_type IN ('NWR', 'REV', 'ISW', 'EXC')
+         WHERE COALESCE((SELECT g.transaction_type FROM file_line gl JOIN cwr_grh g ON g.cwr_grh_id = gl.record_id
+                         WHERE gl.file_id = l.file_id AND gl.record_type = 'GRH' AND gl.line_number < l.line_number
+                         ORDER BY gl.line_number DESC LIMIT 1), '') != 'ACK'
+         ORDER BY n.file_id, l.line_number",
+    )?;
+    let mut registrations: Vec<WorkRegistration> = stmt
+        .query_map([], |row| {
+            Ok(WorkRegistration {
+                file_id: row.get(0)?,
+                cwr_nwr_id: row.get(1)?,
+                submitter_work_num: row.get(2)?,
+                work_title: row.get(3)?,
+                iswc: row.get(4)?,
+                shares: Vec::new(),
+                writer_ipis: BTreeSet::new(),
+            })
+        })?
+        .collect::<Result<_, _>>()?;
+    let by_id: HashMap<i64, usize> = registrations.iter().enumerate().map(|(index, r)| (r.cwr_nwr_id, index)).collect();
+
+    let mut stmt = conn.prepare(
+        "SELECT cwr_nwr_id, record_type, COALESCE(NULLIF(TRIM(writer_ipi_name_num), ''), TRIM(interested_party_num), ''),
+                NULLIF(TRIM(writer_ipi_name_num), ''), CAST(pr_ownership_share AS INTEGER),
+                CAST(mr_ownership_share AS INTEGER), CAST(sr_ownership_share AS INTEGER)
+         FROM v_work_writers
+         UNION ALL
+         SELECT cwr_nwr_id, record_type, COALESCE(NULLIF(TRIM(publisher_ipi_name_num), ''), TRIM(interested_party_num), ''),
+                NULL, CAST(pr_ownership_share AS INTEGER), CAST(mr_ownership_share AS INTEGER),
+                CAST(sr_ownership_share AS INTEGER)
+         FROM v_work_publishers",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, i64>(0)?,
+            row.get::<_, Option<String>>(3)?,
+            PartyShare {
+                record_type: row.get(1)?,
+                party: row.get(2)?,
+                pr_ownership_share: row.get::<_, Option<i64>>(4)?.unwrap_or(0),
+                mr_ownership_share: row.get::<_, Option<i64>>(5)?.unwrap_or(0),
+                sr_ownership_share: row.get::<_, Option<i64>>(6)?.unwrap_or(0),
+            },
+        ))
+    })?;
+    for row in rows {
+        let (cwr_nwr_id, writer_ipi, share) = row?;
+        let Some(&index) = by_id.get(&cwr_nwr_id) else { continue };
+        let registration = &mut registrations[index];
+        if let Some(ipi) = writer_ipi {
+            registration.writer_ipis.insert(ipi);
+        }
+        registration.shares.push(share);
+    }
+    for registration in &mut registrations {
+        registration.shares.sort();
+    }
+    Ok(registrations)
+}
+
+/// Upper case letters and digits, with every run of other characters reduced to one space
+fn normalize_title(title: &str) -> String {
+    title
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_uppercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn find(parent: &mut [usize], index: usize) -> usize {
+    let mut root = index;
+    while parent[root] != root {
+        root = parent[root];
+    }
+    let mut node = index;
+    while parent[node] != root {
+        let next = parent[node];
+        parent[node] = root;
+        node = next;
+    }
+    root
+}
+
+fn union(parent: &mut [usize], a: usize, b: usize) {
+    let (root_a, root_b) = (find(parent, a), find(parent, b));
+    if root_a != root_b {
+        parent[root_a.max(root_b)] = root_a.min(root_b);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ImportOptions, process_cwr_to_sqlite_with_options};
+    use tempfile::tempdir;
+
+    const HDR: &str = "HDRPB285606836WARNER CHAPPELL MUSIC PUBLISHING LTD         01.102022122112541120221221";
+
+    fn nwr(transaction_seq: u32, title: &str, submitter_work_num: &str, iswc: &str) -> String {
+        format!(
+            "NWR{:08}00000000{:<60}  {:<14}{:<11}     SER        Y       ORI",
+            transaction_seq, title, submitter_work_num, iswc
+        )
+    }
+
+    fn swr(transaction_seq: u32, ipi: &str, pr_share: &str) -> String {
+        format!(
+            "SWR{:08}00000001{:<9}{:<45}{:<30} CA{:<9}{:<11}021{}",
+            transaction_seq, "W1", "SMITH", "JANE", "", ipi, pr_share
+        )
+    }
+
+    fn import(dir: &std::path::Path, db_path: &str, name: &str, works: &[[String; 2]]) {
+        let mut lines = vec![HDR.to_string(), "GRHNWR0000102.100000000000  ".to_string()];
+        lines.extend(works.iter().flatten().cloned());
+        let path = dir.join(name);
+        std::fs::write(&path, lines.join("\n") + "\n").unwrap();
+        process_cwr_to_sqlite_with_options(path.to_str().unwrap(), db_path, &ImportOptions::default()).unwrap();
+    }
+
+    #[test]
+    fn test_find_duplicate_works_across_files() {
+        let temp_dir = tempdir().unwrap();
+        let db_file_path = temp_dir.path().join("dups.db");
+        let db_path = db_file_path.to_str().unwrap();
+        import(
+            temp_dir.path(),
+            db_path,
+            "first.V21",
+            &[
+                [nwr(0, "LOVE SONG", "SW1", "T0345246801"), swr(0, "00014107338", "05000")],
+                [nwr(1, "SAME EVERYWHERE", "SW2", ""), swr(1, "00014107339", "10000")],
+                [nwr(2, "ONLY HERE", "SW3", ""), swr(2, "00014107340", "10000")],
+            ],
+        );
+        import(
+            temp_dir.path(),
+            db_path,
+            "second.V21",
+            &[
+                [nwr(0, "LOVE SONG (REMIX)", "XX1", "T0345246801"), swr(0, "00014107338", "06000")],
+                [nwr(1, "Same, Everywhere!", "SW2", ""), swr(1, "00014107339", "10000")],
+            ],
+        );
+
+        let conn = Connection::open(db_path).unwrap();
+        let duplicates = find_duplicate_works(&conn).unwrap();
+        assert_eq!(duplicates.len(), 1, "{:?}", duplicates);
+        let duplicate = &duplicates[0];
+        assert_eq!(duplicate.conflicts, [WorkConflict::SubmitterWorkNum, WorkConflict::Shares]);
+        let numbers: Vec<&str> = duplicate.registrations.iter().map(|r| r.submitter_work_num.as_str()).collect();
+        assert_eq!(numbers, ["SW1", "XX1"]);
+        assert_eq!(duplicate.registrations[1].shares[0].party, "00014107338");
+        assert!(duplicate.to_string().starts_with("'LOVE SONG' in 2 files: submitter work number, shares differ"));
+
+        assert_eq!(normalize_title(" Same,  everywhere! "), "SAME EVERYWHERE");
+    }
+}
@@ -5,6 +5,15 @@ pub enum CwrDbError {
     Sqlite(rusqlite::Error),
     Io(std::io::Error),
     Setup(String),
+    /// The file was already imported with the same content and [`crate::DuplicatePolicy::Skip`] was in effect
+    DuplicateFile {
+        existing_file_id: i64,
+    },
+    /// The database was written by a newer version of this crate, with schema version `found`
+    IncompatibleSchema {
+        found: i64,
+        supported: i64,
+    },
 }
 
 impl From<rusqlite::Error> for CwrDbError {
@@ -31,6 +40,15 @@ impl fmt::Display for CwrDbError {
             CwrDbError::Sqlite(err) => write!(f, "SQLite error: {}", err),
             CwrDbError::Io(err) => write!(f, "IO error: {}", err),
             CwrDbError::Setup(msg) => write!(f, "Database setup error: {}", msg),
+            CwrDbError::DuplicateFile { existing_file_id } => {
+                write!(f, "File already imported with identical content (file ID {})", existing_file_id)
+            }
+            CwrDbError::IncompatibleSchema { found, supported } => write!(
+                f,
+                "Database schema version {} is newer than the supported version {}; it was created by a newer \
+                 version of allegro",
+                found, supported
+            ),
         }
     }
 }
@@ -40,7 +58,7 @@ impl std::error::Error for CwrDbError {
         match self {
             CwrDbError::Sqlite(err) => Some(err),
             CwrDbError::Io(err) => Some(err),
-            CwrDbError::Setup(_) => None,
+            CwrDbError::Setup(_) | CwrDbError::DuplicateFile { .. } | CwrDbError::IncompatibleSchema { .. } => None,
         }
     }
 }
@@ -0,0 +1,108 @@
+//! Maintenance of databases that accumulate many imports: listing, deleting and compacting
+
+use crate::error::CwrDbError;
+use crate::operations::delete_file_rows;
+use rusqlite::{Connection, OptionalExtension};
+
+/// An imported file with its record and error counts
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileSummary {
+    pub file_id: i64,
+    pub file_path: Option<String>,
+    pub imported_on: Option<String>,
+    pub content_hash: Option<String>,
+    pub import_version: i64,
+    pub record_count: i64,
+    pub error_count: i64,
+}
+
+/// Lists every imported file, oldest first
+pub fn list_files(db_path: &str) -> Result<Vec<FileSummary>, CwrDbError> {
+    let conn = Connection::open(db_path)?;
+    let mut stmt = conn.prepare(
+        "SELECT f.file_id, f.file_path, f.imported_on, f.content_hash, f.import_version,
+                (SELECT count(*) FROM file_line l WHERE l.file_id = f.file_id),
+                (SELECT count(*) FROM error e WHERE e.file_id = f.file_id)
+         FROM file f ORDER BY f.file_id",
+    )?;
+    let files = stmt
+        .query_map([], |row| {
+            Ok(FileSummary {
+                file_id: row.get(0)?,
+                file_path: row.get(1)?,
+                imported_on: row.get(2)?,
+                content_hash: row.get(3)?,
+                import_version: row.get(4)?,
+                record_count: row.get(5)?,
+                error_count: row.get(6)?,
+            })
+        })?
+        .collect::<Result<_, _>>()?;
+    Ok(files)
+}
+
+/// Deletes a file with all its records, file lines and errors in one transaction. Returns false if there was no
+/// file with that ID.
+pub fn delete_file(db_path: &str, file_id: i64) -> Result<bool, CwrDbError> {
+    let mut conn = Connection::open(db_path)?;
+    let tx = conn.transaction()?;
+    let exists = tx.query_row("SELECT 1 FROM file WHERE file_id = ?1", [file_id], |_| Ok(())).optional()?.is_some();
+    if exists {
+        delete_file_rows(&tx, file_id)?;
+    }
+    tx.commit()?;
+    Ok(exists)
+}
+
+/// Rebuilds the database file to reclaim the space left by deleted imports
+pub fn vacuum(db_path: &str) -> Result<(), CwrDbError> {
+    let conn = Connection::open(db_path)?;
+    conn.execute_batch("VACUUM")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DuplicatePolicy, ImportOptions, process_cwr_to_sqlite_with_options};
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_list_delete_and_vacuum() {
+        let temp_dir = tempdir().unwrap();
+        let cwr_file_path = temp_dir.path().join("test.V21");
+        let db_file_path = temp_dir.path().join("test.db");
+        std::fs::write(
+            &cwr_file_path,
+            "HDRPB285606836WARNER CHAPPELL MUSIC PUBLISHING LTD         01.102022122112541120221221\r\nGRHNWR0000102.100000000000  \r\n",
+        )
+        .unwrap();
+        let cwr_path = cwr_file_path.to_str().unwrap();
+        let db_path = db_file_path.to_str().unwrap();
+        let options = ImportOptions { on_duplicate: DuplicatePolicy::NewVersion, ..Default::default() };
+        let (first_id, _, _) = process_cwr_to_sqlite_with_options(cwr_path, db_path, &options).unwrap();
+        let (second_id, _, _) = process_cwr_to_sqlite_with_options(cwr_path, db_path, &options).unwrap();
+
+        let files = list_files(db_path).unwrap();
+        assert_eq!(
+            files.iter().map(|f| (f.file_id, f.import_version)).collect::<Vec<_>>(),
+            [(first_id, 1), (second_id, 2)]
+        );
+        assert_eq!(files[0].file_path.as_deref(), Some(cwr_path));
+        assert_eq!(files[0].record_count, 2);
+        assert_eq!(files[0].content_hash, files[1].content_hash);
+
+        assert!(delete_file(db_path, first_id).unwrap());
+        assert!(!delete_file(db_path, first_id).unwrap());
+        let files = list_files(db_path).unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].file_id, second_id);
+        let conn = Connection::open(db_path).unwrap();
+        let orphans: i64 =
+            conn.query_row("SELECT count(*) FROM cwr_hdr WHERE file_id = ?1", [first_id], |row| row.get(0)).unwrap();
+        assert_eq!(orphans, 0);
+
+        vacuum(db_path).unwrap();
+        assert_eq!(list_files(db_path).unwrap().len(), 1);
+    }
+}
@@ -3,20 +3,31 @@
 //! This crate provides database setup, schema management, and record operations
 //! for storing and querying CWR file data in SQLite databases.
 
+pub mod ack;
+pub mod cli;
 pub mod connection;
 pub mod domain_conversions;
+pub mod duplicates;
 pub mod error;
+pub mod files;
+pub mod normalized;
 pub mod operations;
+pub mod parallel;
 pub mod record_handlers;
+pub mod registration;
 pub mod report;
+pub mod search;
+pub mod sql_dump;
 pub mod statements;
 
 use allegro_cwr::domain_types::{
     Boolean, Flag, LanguageCode, MonetaryValue, MusicalWorkDistributionCategory, Number, SocietyCode,
     TransactionStatus, VersionType,
 };
+use allegro_cwr::parsing::intern;
 use domain_conversions::{
-    CwrFromSqlString, CwrToSqlInt, CwrToSqlString, opt_domain_to_string, opt_string_to_domain, opt_string_to_numeric,
+    CwrFromSqlString, CwrToSqlInt, CwrToSqlString, opt_domain_to_int, opt_domain_to_string, opt_int_to_domain,
+    opt_string_to_domain, opt_string_to_numeric,
 };
 
 /// Trait for inserting CWR records into SQLite
@@ -25,7 +36,7 @@ pub trait SqliteInsertable {
     fn table_name(&self) -> &'static str;
 
     /// Convert record fields to SQL parameters
-    fn to_sql_params(&self, file_id: i64) -> Vec<Box<dyn rusqlite::types::ToSql>>;
+    fn to_sql_params(&self, file_id: i64) -> Result<Vec<Box<dyn rusqlite::types::ToSql>>>;
 
     /// Execute insertion using appropriate prepared statement
     fn execute_insert(
@@ -42,6 +53,21 @@ pub trait SqliteQueryable: Sized {
     fn from_sql_row(row: &rusqlite::Row) -> rusqlite::Result<Self>;
 }
 
+/// Owned SQL parameters, so they can outlive the record fields they are converted from
+macro_rules! sql_params {
+    ($($param:expr),* $(,)?) => {
+        vec![$(Box::new(sql_value(&$param)?) as Box<dyn rusqlite::types::ToSql>),*]
+    };
+}
+
+fn sql_value(param: &dyn rusqlite::types::ToSql) -> rusqlite::Result<rusqlite::types::Value> {
+    match param.to_sql()? {
+        rusqlite::types::ToSqlOutput::Borrowed(value) => Ok(value.into()),
+        rusqlite::types::ToSqlOutput::Owned(value) => Ok(value),
+        _ => Err(rusqlite::Error::ToSqlConversionFailure("Value cannot be stored as a parameter".into())),
+    }
+}
+
 // Implementation of SqliteInsertable for CwrRegistry - this centralizes the 33-case match logic
 impl SqliteInsertable for allegro_cwr::CwrRegistry {
     fn table_name(&self) -> &'static str {
@@ -82,604 +108,512 @@ impl SqliteInsertable for allegro_cwr::CwrRegistry {
         }
     }
 
-    fn to_sql_params(&self, _file_id: i64) -> Vec<Box<dyn rusqlite::types::ToSql>> {
-        // This will be implemented via execute_insert for now
-        // since the parameter structure varies significantly between record types
-        vec![]
+    fn to_sql_params(&self, file_id: i64) -> Result<Vec<Box<dyn rusqlite::types::ToSql>>> {
+        Ok(match self {
+            allegro_cwr::CwrRegistry::Hdr(hdr) => sql_params![
+                file_id,
+                "HDR",
+                hdr.sender_type.as_str(),
+                hdr.sender_id.as_str(),
+                hdr.sender_name.as_str(),
+                hdr.edi_standard_version_number.as_str(),
+                hdr.creation_date.as_str(),
+                hdr.creation_time.as_str(),
+                hdr.transmission_date.as_str(),
+                opt_domain_to_string(&hdr.character_set),
+                hdr.version.as_ref().map(|v| v.as_str()),
+                hdr.revision.as_ref().map(|r| r.as_str()),
+                hdr.software_package,
+                hdr.software_package_version
+            ],
+            allegro_cwr::CwrRegistry::Grh(grh) => sql_params![
+                file_id,
+                "GRH",
+                grh.transaction_type.to_sql_string(),
+                grh.group_id.to_sql_int(),
+                grh.version_number.as_str(),
+                grh.batch_request.as_ref().map(|n| n.to_string()).as_deref(),
+                grh.submission_distribution_type
+            ],
+            allegro_cwr::CwrRegistry::Grt(grt) => sql_params![
+                file_id,
+                "GRT",
+                grt.group_id.to_sql_int(),
+                grt.transaction_count.to_sql_int(),
+                grt.record_count.to_sql_int(),
+                grt.currency_indicator.as_ref().map(|c| c.to_sql_string()),
+                grt.total_monetary_value.as_ref().map(|n| n.to_string()).as_deref()
+            ],
+            allegro_cwr::CwrRegistry::Trl(trl) => sql_params![
+                file_id,
+                "TRL",
+                trl.group_count.to_sql_int(),
+                trl.transaction_count.to_sql_int(),
+                trl.record_count.to_sql_int()
+            ],
+            allegro_cwr::CwrRegistry::Agr(agr) => sql_params![
+                file_id,
+                "AGR",
+                agr.transaction_sequence_num.as_str(),
+                agr.record_sequence_num.as_str(),
+                agr.submitter_agreement_number.as_str(),
+                agr.international_standard_agreement_code.as_deref(),
+                agr.agreement_type.to_sql_string(),
+                agr.agreement_start_date.as_str(),
+                agr.agreement_end_date.as_ref().map(|d| d.as_str()),
+                agr.retention_end_date.as_ref().map(|d| d.as_str()),
+                agr.prior_royalty_status.to_sql_string(),
+                agr.prior_royalty_start_date.as_ref().map(|d| d.as_str()),
+                agr.post_term_collection_status.to_sql_string(),
+                agr.post_term_collection_end_date.as_ref().map(|d| d.as_str()),
+                agr.date_of_signature_of_agreement.as_ref().map(|d| d.as_str()),
+                agr.number_of_works.to_sql_int(),
+                agr.sales_manufacture_clause.as_ref().map(|c| c.as_str()),
+                opt_domain_to_string(&agr.shares_change).as_deref(),
+                opt_domain_to_string(&agr.advance_given).as_deref(),
+                agr.society_assigned_agreement_number.as_deref()
+            ],
+            allegro_cwr::CwrRegistry::Nwr(nwr) => sql_params![
+                file_id,
+                &*nwr.record_type,
+                nwr.transaction_sequence_num.as_str(),
+                nwr.record_sequence_num.as_str(),
+                nwr.work_title.as_str(),
+                &opt_domain_to_string(&nwr.language_code),
+                nwr.submitter_work_num.as_str(),
+                nwr.iswc.as_deref(),
+                opt_domain_to_string(&nwr.copyright_date).as_deref(),
+                nwr.copyright_number.as_deref(),
+                nwr.musical_work_distribution_category.to_sql_string(),
+                opt_domain_to_int(&nwr.duration),
+                nwr.recorded_indicator.to_sql_string(),
+                &opt_domain_to_string(&nwr.text_music_relationship),
+                &opt_domain_to_string(&nwr.composite_type),
+                nwr.version_type.to_sql_string(),
+                &opt_domain_to_string(&nwr.excerpt_type),
+                &opt_domain_to_string(&nwr.music_arrangement),
+                &opt_domain_to_string(&nwr.lyric_adaptation),
+                nwr.contact_name.as_deref(),
+                nwr.contact_id.as_deref(),
+                &opt_domain_to_string(&nwr.cwr_work_type),
+                opt_domain_to_string(&nwr.grand_rights_ind).as_deref(),
+                nwr.composite_component_count.as_ref().map(|c| c.to_sql_int()),
+                opt_domain_to_string(&nwr.date_of_publication_of_printed_edition).as_deref(),
+                opt_domain_to_string(&nwr.exceptional_clause).as_deref(),
+                nwr.opus_number.as_deref(),
+                nwr.catalogue_number.as_deref(),
+                opt_domain_to_string(&nwr.priority_flag).as_deref()
+            ],
+            allegro_cwr::CwrRegistry::Ack(ack) => sql_params![
+                file_id,
+                "ACK",
+                ack.transaction_sequence_num.as_str(),
+                ack.record_sequence_num.as_str(),
+                ack.creation_date.as_str(),
+                ack.creation_time.as_str(),
+                ack.original_group_id.to_sql_int(),
+                ack.original_transaction_sequence_num.as_str(),
+                ack.original_transaction_type.to_sql_string(),
+                ack.creation_title.as_deref(),
+                ack.submitter_creation_num.as_deref(),
+                ack.recipient_creation_num.as_deref(),
+                ack.processing_date.as_str(),
+                ack.transaction_status.to_sql_string()
+            ],
+            allegro_cwr::CwrRegistry::Ter(ter) => sql_params![
+                file_id,
+                "TER",
+                ter.transaction_sequence_num.as_str(),
+                ter.record_sequence_num.as_str(),
+                ter.inclusion_exclusion_indicator.to_sql_string(),
+                ter.tis_numeric_code.to_sql_int()
+            ],
+            allegro_cwr::CwrRegistry::Ipa(ipa) => sql_params![
+                file_id,
+                "IPA",
+                ipa.transaction_sequence_num.as_str(),
+                ipa.record_sequence_num.as_str(),
+                ipa.agreement_role_code.to_sql_string(),
+                ipa.interested_party_ipi_name_num.as_deref(),
+                ipa.ipi_base_number.as_deref(),
+                ipa.interested_party_num.as_str(),
+                ipa.interested_party_last_name.as_str(),
+                ipa.interested_party_writer_first_name.as_deref(),
+                ipa.pr_affiliation_society.as_deref(),
+                ipa.pr_share.as_ref().map(|s| s.to_sql_int()),
+                ipa.mr_affiliation_society.as_deref(),
+                ipa.mr_share.as_ref().map(|s| s.to_sql_int()),
+                ipa.sr_affiliation_society.as_deref(),
+                ipa.sr_share.as_ref().map(|s| s.to_sql_int())
+            ],
+            allegro_cwr::CwrRegistry::Npa(npa) => sql_params![
+                file_id,
+                "NPA",
+                npa.transaction_sequence_num.as_str(),
+                npa.record_sequence_num.as_str(),
+                npa.interested_party_num.as_deref(),
+                npa.interested_party_name.as_str(),
+                npa.interested_party_writer_first_name.as_str(),
+                npa.language_code.as_deref()
+            ],
+            allegro_cwr::CwrRegistry::Spu(spu) => sql_params![
+                file_id,
+                &*spu.record_type,
+                spu.transaction_sequence_num.as_str(),
+                spu.record_sequence_num.as_str(),
+                spu.publisher_sequence_num.to_sql_int(),
+                spu.interested_party_num.as_deref(),
+                spu.publisher_name.as_deref(),
+                opt_domain_to_string(&spu.publisher_unknown_indicator).as_deref(),
+                spu.publisher_type.as_ref().map(|p| p.to_sql_string()).as_deref(),
+                spu.tax_id_num.as_deref(),
+                spu.publisher_ipi_name_num.as_deref(),
+                spu.submitter_agreement_number.as_deref(),
+                spu.pr_affiliation_society_num.as_deref(),
+                spu.pr_ownership_share.as_ref().map(|s| s.to_sql_int()),
+                spu.mr_society.as_deref(),
+                spu.mr_ownership_share.as_ref().map(|s| s.to_sql_int()),
+                spu.sr_society.as_deref(),
+                spu.sr_ownership_share.as_ref().map(|s| s.to_sql_int()),
+                opt_domain_to_string(&spu.special_agreements_indicator).as_deref(),
+                opt_domain_to_string(&spu.first_recording_refusal_ind).as_deref(),
+                spu.filler.as_ref().map(|n| n.to_string()).as_deref(),
+                spu.publisher_ipi_base_number.as_deref(),
+                spu.international_standard_agreement_code.as_deref(),
+                spu.society_assigned_agreement_number.as_deref(),
+                spu.agreement_type.as_ref().map(|x| x.as_str()),
+                opt_domain_to_string(&spu.usa_license_ind).as_deref()
+            ],
+            allegro_cwr::CwrRegistry::Npn(npn) => sql_params![
+                file_id,
+                "NPN",
+                npn.transaction_sequence_num.as_str(),
+                npn.record_sequence_num.as_str(),
+                npn.publisher_sequence_num.to_sql_int(),
+                npn.interested_party_num.as_str(),
+                npn.publisher_name.as_str(),
+                npn.language_code.as_deref()
+            ],
+            allegro_cwr::CwrRegistry::Spt(spt) => sql_params![
+                file_id,
+                &*spt.record_type,
+                spt.transaction_sequence_num.as_str(),
+                spt.record_sequence_num.as_str(),
+                spt.interested_party_num.as_str(),
+                "", // constant_spaces
+                spt.pr_collection_share.as_ref().map(|s| s.to_sql_int()),
+                spt.mr_collection_share.as_ref().map(|s| s.to_sql_int()),
+                spt.sr_collection_share.as_ref().map(|s| s.to_sql_int()),
+                spt.inclusion_exclusion_indicator.to_sql_string(),
+                spt.tis_numeric_code.to_sql_int(),
+                opt_domain_to_string(&spt.shares_change).as_deref(),
+                spt.sequence_num.as_ref().map(|n| n.to_string()).as_deref()
+            ],
+            allegro_cwr::CwrRegistry::Swr(swr) => sql_params![
+                file_id,
+                &*swr.record_type,
+                swr.transaction_sequence_num.as_str(),
+                swr.record_sequence_num.as_str(),
+                swr.interested_party_num.as_deref(),
+                swr.writer_last_name.as_deref(),
+                swr.writer_first_name.as_deref(),
+                opt_domain_to_string(&swr.writer_unknown_indicator).as_deref(),
+                swr.writer_designation_code.as_ref().map(|x| x.as_str()),
+                swr.tax_id_num.as_deref(),
+                swr.writer_ipi_name_num.as_deref(),
+                swr.pr_affiliation_society_num.as_deref(),
+                swr.pr_ownership_share.as_ref().map(|s| s.to_sql_int()),
+                swr.mr_society.as_deref(),
+                swr.mr_ownership_share.as_ref().map(|s| s.to_sql_int()),
+                swr.sr_society.as_deref(),
+                swr.sr_ownership_share.as_ref().map(|s| s.to_sql_int()),
+                opt_domain_to_string(&swr.reversionary_indicator).as_deref(),
+                opt_domain_to_string(&swr.first_recording_refusal_ind).as_deref(),
+                opt_domain_to_string(&swr.work_for_hire_indicator).as_deref(),
+                swr.filler.as_ref().map(|n| n.to_string()).as_deref(),
+                swr.writer_ipi_base_number.as_deref(),
+                swr.personal_number.as_ref().map(|n| n.to_string()).as_deref(),
+                opt_domain_to_string(&swr.usa_license_ind).as_deref()
+            ],
+            allegro_cwr::CwrRegistry::Nwn(nwn) => sql_params![
+                file_id,
+                "NWN",
+                nwn.transaction_sequence_num.as_str(),
+                nwn.record_sequence_num.as_str(),
+                nwn.interested_party_num.as_deref(),
+                nwn.writer_last_name.as_str(),
+                nwn.writer_first_name.as_deref(),
+                nwn.language_code.as_deref()
+            ],
+            allegro_cwr::CwrRegistry::Swt(swt) => sql_params![
+                file_id,
+                &*swt.record_type,
+                swt.transaction_sequence_num.as_str(),
+                swt.record_sequence_num.as_str(),
+                swt.interested_party_num.as_deref(),
+                swt.pr_collection_share.as_ref().map(|s| s.to_sql_int()),
+                swt.mr_collection_share.as_ref().map(|s| s.to_sql_int()),
+                swt.sr_collection_share.as_ref().map(|s| s.to_sql_int()),
+                swt.inclusion_exclusion_indicator.to_sql_string(),
+                swt.tis_numeric_code.to_sql_int(),
+                opt_domain_to_string(&swt.shares_change).as_deref(),
+                swt.sequence_num.as_ref().map(|n| n.to_string()).as_deref()
+            ],
+            allegro_cwr::CwrRegistry::Pwr(pwr) => sql_params![
+                file_id,
+                "PWR",
+                pwr.transaction_sequence_num.as_str(),
+                pwr.record_sequence_num.as_str(),
+                pwr.publisher_ip_num.as_deref(),
+                pwr.publisher_name.as_deref(),
+                pwr.submitter_agreement_number.as_deref(),
+                pwr.society_assigned_agreement_number.as_deref(),
+                pwr.writer_ip_num.as_deref(),
+                pwr.publisher_sequence_num.as_ref().map(|s| s.to_sql_int())
+            ],
+            allegro_cwr::CwrRegistry::Alt(alt) => sql_params![
+                file_id,
+                "ALT",
+                alt.transaction_sequence_num.as_str(),
+                alt.record_sequence_num.as_str(),
+                alt.alternate_title.as_str(),
+                alt.title_type.to_sql_string(),
+                alt.language_code.as_deref()
+            ],
+            allegro_cwr::CwrRegistry::Nat(nat) => sql_params![
+                file_id,
+                "NAT",
+                nat.transaction_sequence_num.as_str(),
+                nat.record_sequence_num.as_str(),
+                nat.title.as_str(),
+                nat.title_type.to_sql_string(),
+                nat.language_code.as_deref()
+            ],
+            allegro_cwr::CwrRegistry::Ewt(ewt) => sql_params![
+                file_id,
+                "EWT",
+                ewt.transaction_sequence_num.as_str(),
+                ewt.record_sequence_num.as_str(),
+                ewt.entire_work_title.as_str(),
+                ewt.iswc_of_entire_work.as_deref(),
+                ewt.language_code.as_deref(),
+                ewt.writer_1_last_name.as_deref(),
+                ewt.writer_1_first_name.as_deref(),
+                ewt.source.as_deref(),
+                ewt.writer_1_ipi_name_num.as_deref(),
+                ewt.writer_1_ipi_base_number.as_deref(),
+                ewt.writer_2_last_name.as_deref(),
+                ewt.writer_2_first_name.as_deref(),
+                ewt.writer_2_ipi_name_num.as_deref(),
+                ewt.writer_2_ipi_base_number.as_deref(),
+                ewt.submitter_work_num.as_deref()
+            ],
+            allegro_cwr::CwrRegistry::Ver(ver) => sql_params![
+                file_id,
+                "VER",
+                ver.transaction_sequence_num.as_str(),
+                ver.record_sequence_num.as_str(),
+                ver.original_work_title.as_str(),
+                ver.iswc_of_original_work.as_deref(),
+                ver.language_code.as_deref(),
+                ver.writer_1_last_name.as_deref(),
+                ver.writer_1_first_name.as_deref(),
+                ver.source.as_deref(),
+                ver.writer_1_ipi_name_num.as_deref(),
+                ver.writer_1_ipi_base_number.as_deref(),
+                ver.writer_2_last_name.as_deref(),
+                ver.writer_2_first_name.as_deref(),
+                ver.writer_2_ipi_name_num.as_deref(),
+                ver.writer_2_ipi_base_number.as_deref(),
+                ver.submitter_work_num.as_deref()
+            ],
+            allegro_cwr::CwrRegistry::Per(per) => sql_params![
+                file_id,
+                "PER",
+                per.transaction_sequence_num.as_str(),
+                per.record_sequence_num.as_str(),
+                per.performing_artist_last_name.as_str(),
+                per.performing_artist_first_name.as_deref(),
+                per.performing_artist_ipi_name_num.as_deref(),
+                per.performing_artist_ipi_base_number.as_deref()
+            ],
+            allegro_cwr::CwrRegistry::Npr(npr) => sql_params![
+                file_id,
+                "NPR",
+                npr.transaction_sequence_num.as_str(),
+                npr.record_sequence_num.as_str(),
+                npr.performing_artist_name.as_deref(),
+                npr.performing_artist_first_name.as_deref(),
+                npr.performing_artist_ipi_name_num.as_deref(),
+                npr.performing_artist_ipi_base_number.as_deref(),
+                npr.language_code.as_deref(),
+                npr.performance_language.as_deref(),
+                npr.performance_dialect.as_ref().map(|d| d.as_str())
+            ],
+            allegro_cwr::CwrRegistry::Rec(rec) => sql_params![
+                file_id,
+                "REC",
+                rec.transaction_sequence_num.as_str(),
+                rec.record_sequence_num.as_str(),
+                rec.release_date.as_ref().map(|d| d.as_str()),
+                "", // constant_blanks_1
+                opt_domain_to_int(&rec.release_duration),
+                "", // constant_blanks_2
+                rec.album_title.as_deref(),
+                rec.album_label.as_deref(),
+                rec.release_catalog_num.as_deref(),
+                rec.ean.as_deref(),
+                rec.isrc.as_deref(),
+                opt_domain_to_string(&rec.recording_format).as_deref(),
+                opt_domain_to_string(&rec.recording_technique).as_deref(),
+                rec.media_type.as_deref(),
+                rec.recording_title.as_deref(),
+                rec.version_title.as_deref(),
+                rec.display_artist.as_deref(),
+                rec.record_label.as_deref(),
+                rec.isrc_validity.as_ref().map(|x| x.as_str()),
+                rec.submitter_recording_identifier.as_deref()
+            ],
+            allegro_cwr::CwrRegistry::Orn(orn) => sql_params![
+                file_id,
+                "ORN",
+                orn.transaction_sequence_num.as_str(),
+                orn.record_sequence_num.as_str(),
+                orn.intended_purpose.to_sql_string(),
+                orn.production_title.as_deref(),
+                orn.cd_identifier.as_deref(),
+                orn.cut_number.as_ref().map(|n| n.to_string()).as_deref(),
+                orn.library.as_deref(),
+                opt_domain_to_string(&orn.bltvr).as_deref(),
+                orn.filler.as_ref().map(|n| n.to_string()).as_deref(),
+                orn.production_num.as_deref(),
+                orn.episode_title.as_deref(),
+                orn.episode_num.as_deref(),
+                orn.year_of_production.as_ref().map(|n| n.to_string()).as_deref(),
+                orn.avi_society_code.as_ref().map(|n| n.to_string()).as_deref(),
+                orn.audio_visual_number.as_deref(),
+                orn.v_isan_isan.as_deref(),
+                orn.v_isan_episode.as_deref(),
+                orn.v_isan_check_digit_1.as_deref(),
+                orn.v_isan_version.as_deref(),
+                orn.v_isan_check_digit_2.as_deref(),
+                orn.eidr.as_deref(),
+                orn.eidr_check_digit.as_deref()
+            ],
+            allegro_cwr::CwrRegistry::Ins(ins) => sql_params![
+                file_id,
+                "INS",
+                ins.transaction_sequence_num.as_str(),
+                ins.record_sequence_num.as_str(),
+                ins.number_of_voices.as_ref().map(|n| n.to_string()).as_deref(),
+                ins.standard_instrumentation_type.as_deref(),
+                ins.instrumentation_description.as_deref()
+            ],
+            allegro_cwr::CwrRegistry::Ind(ind) => sql_params![
+                file_id,
+                "IND",
+                ind.transaction_sequence_num.as_str(),
+                ind.record_sequence_num.as_str(),
+                ind.instrument_code.to_sql_string(),
+                ind.number_of_players.as_ref().map(|n| n.to_string()).as_deref()
+            ],
+            allegro_cwr::CwrRegistry::Com(com) => sql_params![
+                file_id,
+                "COM",
+                com.transaction_sequence_num.as_str(),
+                com.record_sequence_num.as_str(),
+                com.title.as_str(),
+                com.iswc_of_component.as_deref(),
+                com.submitter_work_num.as_deref(),
+                opt_domain_to_int(&com.duration),
+                com.writer_1_last_name.as_str(),
+                com.writer_1_first_name.as_deref(),
+                com.writer_1_ipi_name_num.as_deref(),
+                com.writer_2_last_name.as_deref(),
+                com.writer_2_first_name.as_deref(),
+                com.writer_2_ipi_name_num.as_deref(),
+                com.writer_1_ipi_base_number.as_deref(),
+                com.writer_2_ipi_base_number.as_deref()
+            ],
+            allegro_cwr::CwrRegistry::Msg(msg) => sql_params![
+                file_id,
+                "MSG",
+                msg.transaction_sequence_num.as_str(),
+                msg.record_sequence_num.as_str(),
+                msg.message_type.to_sql_string(),
+                msg.original_record_sequence_num.as_str(),
+                msg.record_type_field.as_str(),
+                msg.message_level.to_sql_string(),
+                msg.validation_number.as_str(),
+                msg.message_text.as_str()
+            ],
+            allegro_cwr::CwrRegistry::Net(net) => sql_params![
+                file_id,
+                &*net.record_type,
+                net.transaction_sequence_num.as_str(),
+                net.record_sequence_num.as_str(),
+                net.title.as_str(),
+                net.language_code.as_deref()
+            ],
+            allegro_cwr::CwrRegistry::Now(now) => sql_params![
+                file_id,
+                "NOW",
+                now.transaction_sequence_num.as_str(),
+                now.record_sequence_num.as_str(),
+                now.writer_name.as_str(),
+                now.writer_first_name.as_str(),
+                now.language_code.as_deref(),
+                now.writer_position.as_ref().map(|p| p.as_str())
+            ],
+            allegro_cwr::CwrRegistry::Ari(ari) => sql_params![
+                file_id,
+                "ARI",
+                ari.transaction_sequence_num.as_str(),
+                ari.record_sequence_num.as_str(),
+                ari.society_num.to_sql_string(),
+                ari.work_num.as_deref(),
+                ari.type_of_right.to_sql_string(),
+                ari.subject_code.as_ref().map(|x| x.as_str()),
+                ari.note.as_deref()
+            ],
+            allegro_cwr::CwrRegistry::Xrf(xrf) => sql_params![
+                file_id,
+                "XRF",
+                xrf.transaction_sequence_num.as_str(),
+                xrf.record_sequence_num.as_str(),
+                xrf.organisation_code.to_sql_string(),
+                xrf.identifier.as_str(),
+                xrf.identifier_type.to_sql_string(),
+                xrf.validity.to_sql_string()
+            ],
+        })
     }
 
     fn execute_insert(
         &self, statements: &mut PreparedStatements, tx: &rusqlite::Transaction, file_id: i64,
     ) -> Result<i64> {
-        use rusqlite::params;
-
-        match self {
-            allegro_cwr::CwrRegistry::Hdr(hdr) => {
-                statements.hdr_stmt.execute(params![
-                    file_id,
-                    "HDR",
-                    hdr.sender_type.as_str(),
-                    hdr.sender_id.as_str(),
-                    hdr.sender_name.as_str(),
-                    hdr.edi_standard_version_number.as_str(),
-                    hdr.creation_date.as_str(),
-                    hdr.creation_time.as_str(),
-                    hdr.transmission_date.as_str(),
-                    opt_domain_to_string(&hdr.character_set),
-                    hdr.version.as_ref().map(|v| v.as_str()),
-                    hdr.revision.as_ref().map(|r| r.as_str()),
-                    hdr.software_package,
-                    hdr.software_package_version
-                ])?;
-                Ok(tx.last_insert_rowid())
-            }
-            allegro_cwr::CwrRegistry::Grh(grh) => {
-                statements.grh_stmt.execute(params![
-                    file_id,
-                    "GRH",
-                    grh.transaction_type.to_sql_string(),
-                    grh.group_id.to_sql_int(),
-                    grh.version_number.as_str(),
-                    grh.batch_request.as_ref().map(|n| n.to_string()).as_deref(),
-                    grh.submission_distribution_type
-                ])?;
-                Ok(tx.last_insert_rowid())
-            }
-            allegro_cwr::CwrRegistry::Grt(grt) => {
-                statements.grt_stmt.execute(params![
-                    file_id,
-                    "GRT",
-                    grt.group_id.to_sql_int(),
-                    grt.transaction_count.to_sql_int(),
-                    grt.record_count.to_sql_int(),
-                    grt.currency_indicator.as_ref().map(|c| c.to_sql_string()),
-                    grt.total_monetary_value.as_ref().map(|n| n.to_string()).as_deref()
-                ])?;
-                Ok(tx.last_insert_rowid())
-            }
-            allegro_cwr::CwrRegistry::Trl(trl) => {
-                statements.trl_stmt.execute(params![
-                    file_id,
-                    "TRL",
-                    trl.group_count.to_sql_int(),
-                    trl.transaction_count.to_sql_int(),
-                    trl.record_count.to_sql_int()
-                ])?;
-                Ok(tx.last_insert_rowid())
-            }
-            allegro_cwr::CwrRegistry::Agr(agr) => {
-                statements.agr_stmt.execute(params![
-                    file_id,
-                    "AGR",
-                    agr.transaction_sequence_num.as_str(),
-                    agr.record_sequence_num.as_str(),
-                    agr.submitter_agreement_number.as_str(),
-                    agr.international_standard_agreement_code.as_deref(),
-                    agr.agreement_type.to_sql_string(),
-                    agr.agreement_start_date.as_str(),
-                    agr.agreement_end_date.as_ref().map(|d| d.as_str()),
-                    agr.retention_end_date.as_ref().map(|d| d.as_str()),
-                    agr.prior_royalty_status.to_sql_string(),
-                    agr.prior_royalty_start_date.as_ref().map(|d| d.as_str()),
-                    agr.post_term_collection_status.to_sql_string(),
-                    agr.post_term_collection_end_date.as_ref().map(|d| d.as_str()),
-                    agr.date_of_signature_of_agreement.as_ref().map(|d| d.as_str()),
-                    agr.number_of_works.to_sql_int(),
-                    agr.sales_manufacture_clause.as_ref().map(|c| c.as_str()),
-                    opt_domain_to_string(&agr.shares_change).as_deref(),
-                    opt_domain_to_string(&agr.advance_given).as_deref(),
-                    agr.society_assigned_agreement_number.as_deref()
-                ])?;
-                Ok(tx.last_insert_rowid())
-            }
-            allegro_cwr::CwrRegistry::Nwr(nwr) => {
-                statements.nwr_stmt.execute(params![
-                    file_id,
-                    "NWR",
-                    nwr.transaction_sequence_num.as_str(),
-                    nwr.record_sequence_num.as_str(),
-                    nwr.work_title.as_str(),
-                    &opt_domain_to_string(&nwr.language_code),
-                    nwr.submitter_work_num.as_str(),
-                    nwr.iswc.as_deref(),
-                    opt_domain_to_string(&nwr.copyright_date).as_deref(),
-                    nwr.copyright_number.as_deref(),
-                    nwr.musical_work_distribution_category.to_sql_string(),
-                    opt_domain_to_string(&nwr.duration).as_deref(),
-                    nwr.recorded_indicator.to_sql_string(),
-                    &opt_domain_to_string(&nwr.text_music_relationship),
-                    &opt_domain_to_string(&nwr.composite_type),
-                    nwr.version_type.to_sql_string(),
-                    &opt_domain_to_string(&nwr.excerpt_type),
-                    &opt_domain_to_string(&nwr.music_arrangement),
-                    &opt_domain_to_string(&nwr.lyric_adaptation),
-                    nwr.contact_name.as_deref(),
-                    nwr.contact_id.as_deref(),
-                    &opt_domain_to_string(&nwr.cwr_work_type),
-                    opt_domain_to_string(&nwr.grand_rights_ind).as_deref(),
-                    nwr.composite_component_count.as_ref().map(|c| c.to_sql_int()),
-                    opt_domain_to_string(&nwr.date_of_publication_of_printed_edition).as_deref(),
-                    opt_domain_to_string(&nwr.exceptional_clause).as_deref(),
-                    nwr.opus_number.as_deref(),
-                    nwr.catalogue_number.as_deref(),
-                    opt_domain_to_string(&nwr.priority_flag).as_deref()
-                ])?;
-                Ok(tx.last_insert_rowid())
-            }
-            allegro_cwr::CwrRegistry::Ack(ack) => {
-                statements.ack_stmt.execute(params![
-                    file_id,
-                    "ACK",
-                    ack.transaction_sequence_num.as_str(),
-                    ack.record_sequence_num.as_str(),
-                    ack.creation_date.as_str(),
-                    ack.creation_time.as_str(),
-                    ack.original_group_id.to_sql_int(),
-                    ack.original_transaction_sequence_num.as_str(),
-                    ack.original_transaction_type.to_sql_string(),
-                    ack.creation_title.as_deref(),
-                    ack.submitter_creation_num.as_deref(),
-                    ack.recipient_creation_num.as_deref(),
-                    ack.processing_date.as_str(),
-                    ack.transaction_status.to_sql_string()
-                ])?;
-                Ok(tx.last_insert_rowid())
-            }
-            allegro_cwr::CwrRegistry::Ter(ter) => {
-                statements.ter_stmt.execute(params![
-                    file_id,
-                    "TER",
-                    ter.transaction_sequence_num.as_str(),
-                    ter.record_sequence_num.as_str(),
-                    ter.inclusion_exclusion_indicator.to_sql_string(),
-                    ter.tis_numeric_code.to_sql_int()
-                ])?;
-                Ok(tx.last_insert_rowid())
-            }
-            allegro_cwr::CwrRegistry::Ipa(ipa) => {
-                statements.ipa_stmt.execute(params![
-                    file_id,
-                    "IPA",
-                    ipa.transaction_sequence_num.as_str(),
-                    ipa.record_sequence_num.as_str(),
-                    ipa.agreement_role_code.to_sql_string(),
-                    ipa.interested_party_ipi_name_num.as_deref(),
-                    ipa.ipi_base_number.as_deref(),
-                    ipa.interested_party_num.as_str(),
-                    ipa.interested_party_last_name.as_str(),
-                    ipa.interested_party_writer_first_name.as_deref(),
-                    ipa.pr_affiliation_society.as_deref(),
-                    ipa.pr_share.as_ref().map(|s| s.to_sql_int()),
-                    ipa.mr_affiliation_society.as_deref(),
-                    ipa.mr_share.as_ref().map(|s| s.to_sql_int()),
-                    ipa.sr_affiliation_society.as_deref(),
-                    ipa.sr_share.as_ref().map(|s| s.to_sql_int())
-                ])?;
-                Ok(tx.last_insert_rowid())
-            }
-            allegro_cwr::CwrRegistry::Npa(npa) => {
-                statements.npa_stmt.execute(params![
-                    file_id,
-                    "NPA",
-                    npa.transaction_sequence_num.as_str(),
-                    npa.record_sequence_num.as_str(),
-                    npa.interested_party_num.as_deref(),
-                    npa.interested_party_name.as_str(),
-                    npa.interested_party_writer_first_name.as_str(),
-                    npa.language_code.as_deref()
-                ])?;
-                Ok(tx.last_insert_rowid())
-            }
-            allegro_cwr::CwrRegistry::Spu(spu) => {
-                statements.spu_stmt.execute(params![
-                    file_id,
-                    "SPU",
-                    spu.transaction_sequence_num.as_str(),
-                    spu.record_sequence_num.as_str(),
-                    spu.publisher_sequence_num.to_sql_int(),
-                    spu.interested_party_num.as_deref(),
-                    spu.publisher_name.as_deref(),
-                    opt_domain_to_string(&spu.publisher_unknown_indicator).as_deref(),
-                    spu.publisher_type.as_ref().map(|p| p.to_sql_string()).as_deref(),
-                    spu.tax_id_num.as_deref(),
-                    spu.publisher_ipi_name_num.as_deref(),
-                    spu.submitter_agreement_number.as_deref(),
-                    spu.pr_affiliation_society_num.as_deref(),
-                    spu.pr_ownership_share.as_ref().map(|s| s.to_sql_int()),
-                    spu.mr_society.as_deref(),
-                    spu.mr_ownership_share.as_ref().map(|s| s.to_sql_int()),
-                    spu.sr_society.as_deref(),
-                    spu.sr_ownership_share.as_ref().map(|s| s.to_sql_int()),
-                    opt_domain_to_string(&spu.special_agreements_indicator).as_deref(),
-                    opt_domain_to_string(&spu.first_recording_refusal_ind).as_deref(),
-                    spu.filler.as_ref().map(|n| n.to_string()).as_deref(),
-                    spu.publisher_ipi_base_number.as_deref(),
-                    spu.international_standard_agreement_code.as_deref(),
-                    spu.society_assigned_agreement_number.as_deref(),
-                    spu.agreement_type.as_ref().map(|x| x.as_str()),
-                    opt_domain_to_string(&spu.usa_license_ind).as_deref()
-                ])?;
-                Ok(tx.last_insert_rowid())
-            }
-            allegro_cwr::CwrRegistry::Npn(npn) => {
-                statements.npn_stmt.execute(params![
-                    file_id,
-                    "NPN",
-                    npn.transaction_sequence_num.as_str(),
-                    npn.record_sequence_num.as_str(),
-                    npn.publisher_sequence_num.to_sql_int(),
-                    npn.interested_party_num.as_str(),
-                    npn.publisher_name.as_str(),
-                    npn.language_code.as_deref()
-                ])?;
-                Ok(tx.last_insert_rowid())
-            }
-            allegro_cwr::CwrRegistry::Spt(spt) => {
-                statements.spt_stmt.execute(params![
-                    file_id,
-                    "SPT",
-                    spt.transaction_sequence_num.as_str(),
-                    spt.record_sequence_num.as_str(),
-                    spt.interested_party_num.as_str(),
-                    "", // constant_spaces
-                    spt.pr_collection_share.as_ref().map(|s| s.to_sql_int()),
-                    spt.mr_collection_share.as_ref().map(|s| s.to_sql_int()),
-                    spt.sr_collection_share.as_ref().map(|s| s.to_sql_int()),
-                    spt.inclusion_exclusion_indicator.to_sql_string(),
-                    spt.tis_numeric_code.to_sql_int(),
-                    opt_domain_to_string(&spt.shares_change).as_deref(),
-                    spt.sequence_num.as_ref().map(|n| n.to_string()).as_deref()
-                ])?;
-                Ok(tx.last_insert_rowid())
-            }
-            allegro_cwr::CwrRegistry::Swr(swr) => {
-                statements.swr_stmt.execute(params![
-                    file_id,
-                    "SWR",
-                    swr.transaction_sequence_num.as_str(),
-                    swr.record_sequence_num.as_str(),
-                    swr.interested_party_num.as_deref(),
-                    swr.writer_last_name.as_deref(),
-                    swr.writer_first_name.as_deref(),
-                    opt_domain_to_string(&swr.writer_unknown_indicator).as_deref(),
-                    swr.writer_designation_code.as_ref().map(|x| x.as_str()),
-                    swr.tax_id_num.as_deref(),
-                    swr.writer_ipi_name_num.as_deref(),
-                    swr.pr_affiliation_society_num.as_deref(),
-                    swr.pr_ownership_share.as_ref().map(|s| s.to_sql_int()),
-                    swr.mr_society.as_deref(),
-                    swr.mr_ownership_share.as_ref().map(|s| s.to_sql_int()),
-                    swr.sr_society.as_deref(),
-                    swr.sr_ownership_share.as_ref().map(|s| s.to_sql_int()),
-                    opt_domain_to_string(&swr.reversionary_indicator).as_deref(),
-                    opt_domain_to_string(&swr.first_recording_refusal_ind).as_deref(),
-                    opt_domain_to_string(&swr.work_for_hire_indicator).as_deref(),
-                    swr.filler.as_ref().map(|n| n.to_string()).as_deref(),
-                    swr.writer_ipi_base_number.as_deref(),
-                    swr.personal_number.as_ref().map(|n| n.to_string()).as_deref(),
-                    opt_domain_to_string(&swr.usa_license_ind).as_deref()
-                ])?;
-                Ok(tx.last_insert_rowid())
-            }
-            allegro_cwr::CwrRegistry::Nwn(nwn) => {
-                statements.nwn_stmt.execute(params![
-                    file_id,
-                    "NWN",
-                    nwn.transaction_sequence_num.as_str(),
-                    nwn.record_sequence_num.as_str(),
-                    nwn.interested_party_num.as_deref(),
-                    nwn.writer_last_name.as_str(),
-                    nwn.writer_first_name.as_deref(),
-                    nwn.language_code.as_deref()
-                ])?;
-                Ok(tx.last_insert_rowid())
-            }
-            allegro_cwr::CwrRegistry::Swt(swt) => {
-                statements.swt_stmt.execute(params![
-                    file_id,
-                    "SWT",
-                    swt.transaction_sequence_num.as_str(),
-                    swt.record_sequence_num.as_str(),
-                    swt.interested_party_num.as_deref(),
-                    swt.pr_collection_share.as_ref().map(|s| s.to_sql_int()),
-                    swt.mr_collection_share.as_ref().map(|s| s.to_sql_int()),
-                    swt.sr_collection_share.as_ref().map(|s| s.to_sql_int()),
-                    swt.inclusion_exclusion_indicator.to_sql_string(),
-                    swt.tis_numeric_code.to_sql_int(),
-                    opt_domain_to_string(&swt.shares_change).as_deref(),
-                    swt.sequence_num.as_ref().map(|n| n.to_string()).as_deref()
-                ])?;
-                Ok(tx.last_insert_rowid())
-            }
-            allegro_cwr::CwrRegistry::Pwr(pwr) => {
-                statements.pwr_stmt.execute(params![
-                    file_id,
-                    "PWR",
-                    pwr.transaction_sequence_num.as_str(),
-                    pwr.record_sequence_num.as_str(),
-                    pwr.publisher_ip_num.as_deref(),
-                    pwr.publisher_name.as_deref(),
-                    pwr.submitter_agreement_number.as_deref(),
-                    pwr.society_assigned_agreement_number.as_deref(),
-                    pwr.writer_ip_num.as_deref(),
-                    pwr.publisher_sequence_num.as_ref().map(|s| s.to_sql_int())
-                ])?;
-                Ok(tx.last_insert_rowid())
-            }
-            allegro_cwr::CwrRegistry::Alt(alt) => {
-                statements.alt_stmt.execute(params![
-                    file_id,
-                    "ALT",
-                    alt.transaction_sequence_num.as_str(),
-                    alt.record_sequence_num.as_str(),
-                    alt.alternate_title.as_str(),
-                    alt.title_type.to_sql_string(),
-                    alt.language_code.as_deref()
-                ])?;
-                Ok(tx.last_insert_rowid())
-            }
-            allegro_cwr::CwrRegistry::Nat(nat) => {
-                statements.nat_stmt.execute(params![
-                    file_id,
-                    "NAT",
-                    nat.transaction_sequence_num.as_str(),
-                    nat.record_sequence_num.as_str(),
-                    nat.title.as_str(),
-                    nat.title_type.to_sql_string(),
-                    nat.language_code.as_deref()
-                ])?;
-                Ok(tx.last_insert_rowid())
-            }
-            allegro_cwr::CwrRegistry::Ewt(ewt) => {
-                statements.ewt_stmt.execute(params![
-                    file_id,
-                    "EWT",
-                    ewt.transaction_sequence_num.as_str(),
-                    ewt.record_sequence_num.as_str(),
-                    ewt.entire_work_title.as_str(),
-                    ewt.iswc_of_entire_work.as_deref(),
-                    ewt.language_code.as_deref(),
-                    ewt.writer_1_last_name.as_deref(),
-                    ewt.writer_1_first_name.as_deref(),
-                    ewt.source.as_deref(),
-                    ewt.writer_1_ipi_name_num.as_deref(),
-                    ewt.writer_1_ipi_base_number.as_deref(),
-                    ewt.writer_2_last_name.as_deref(),
-                    ewt.writer_2_first_name.as_deref(),
-                    ewt.writer_2_ipi_name_num.as_deref(),
-                    ewt.writer_2_ipi_base_number.as_deref(),
-                    ewt.submitter_work_num.as_deref()
-                ])?;
-                Ok(tx.last_insert_rowid())
-            }
-            allegro_cwr::CwrRegistry::Ver(ver) => {
-                statements.ver_stmt.execute(params![
-                    file_id,
-                    "VER",
-                    ver.transaction_sequence_num.as_str(),
-                    ver.record_sequence_num.as_str(),
-                    ver.original_work_title.as_str(),
-                    ver.iswc_of_original_work.as_deref(),
-                    ver.language_code.as_deref(),
-                    ver.writer_1_last_name.as_deref(),
-                    ver.writer_1_first_name.as_deref(),
-                    ver.source.as_deref(),
-                    ver.writer_1_ipi_name_num.as_deref(),
-                    ver.writer_1_ipi_base_number.as_deref(),
-                    ver.writer_2_last_name.as_deref(),
-                    ver.writer_2_first_name.as_deref(),
-                    ver.writer_2_ipi_name_num.as_deref(),
-                    ver.writer_2_ipi_base_number.as_deref(),
-                    ver.submitter_work_num.as_deref()
-                ])?;
-                Ok(tx.last_insert_rowid())
-            }
-            allegro_cwr::CwrRegistry::Per(per) => {
-                statements.per_stmt.execute(params![
-                    file_id,
-                    "PER",
-                    per.transaction_sequence_num.as_str(),
-                    per.record_sequence_num.as_str(),
-                    per.performing_artist_last_name.as_str(),
-                    per.performing_artist_first_name.as_deref(),
-                    per.performing_artist_ipi_name_num.as_deref(),
-                    per.performing_artist_ipi_base_number.as_deref()
-                ])?;
-                Ok(tx.last_insert_rowid())
-            }
-            allegro_cwr::CwrRegistry::Npr(npr) => {
-                statements.npr_stmt.execute(params![
-                    file_id,
-                    "NPR",
-                    npr.transaction_sequence_num.as_str(),
-                    npr.record_sequence_num.as_str(),
-                    npr.performing_artist_name.as_deref(),
-                    npr.performing_artist_first_name.as_deref(),
-                    npr.performing_artist_ipi_name_num.as_deref(),
-                    npr.performing_artist_ipi_base_number.as_deref(),
-                    npr.language_code.as_deref(),
-                    npr.performance_language.as_deref(),
-                    npr.performance_dialect.as_ref().map(|d| d.as_str())
-                ])?;
-                Ok(tx.last_insert_rowid())
-            }
-            allegro_cwr::CwrRegistry::Rec(rec) => {
-                statements.rec_stmt.execute(params![
-                    file_id,
-                    "REC",
-                    rec.transaction_sequence_num.as_str(),
-                    rec.record_sequence_num.as_str(),
-                    rec.release_date.as_ref().map(|d| d.as_str()),
-                    "", // constant_blanks_1
-                    opt_domain_to_string(&rec.release_duration).as_deref(),
-                    "", // constant_blanks_2
-                    rec.album_title.as_deref(),
-                    rec.album_label.as_deref(),
-                    rec.release_catalog_num.as_deref(),
-                    rec.ean.as_deref(),
-                    rec.isrc.as_deref(),
-                    opt_domain_to_string(&rec.recording_format).as_deref(),
-                    opt_domain_to_string(&rec.recording_technique).as_deref(),
-                    rec.media_type.as_deref(),
-                    rec.recording_title.as_deref(),
-                    rec.version_title.as_deref(),
-                    rec.display_artist.as_deref(),
-                    rec.record_label.as_deref(),
-                    rec.isrc_validity.as_ref().map(|x| x.as_str()),
-                    rec.submitter_recording_identifier.as_deref()
-                ])?;
-                Ok(tx.last_insert_rowid())
-            }
-            allegro_cwr::CwrRegistry::Orn(orn) => {
-                statements.orn_stmt.execute(params![
-                    file_id,
-                    "ORN",
-                    orn.transaction_sequence_num.as_str(),
-                    orn.record_sequence_num.as_str(),
-                    orn.intended_purpose.to_sql_string(),
-                    orn.production_title.as_deref(),
-                    orn.cd_identifier.as_deref(),
-                    orn.cut_number.as_ref().map(|n| n.to_string()).as_deref(),
-                    orn.library.as_deref(),
-                    opt_domain_to_string(&orn.bltvr).as_deref(),
-                    orn.filler.as_ref().map(|n| n.to_string()).as_deref(),
-                    orn.production_num.as_deref(),
-                    orn.episode_title.as_deref(),
-                    orn.episode_num.as_deref(),
-                    orn.year_of_production.as_ref().map(|n| n.to_string()).as_deref(),
-                    orn.avi_society_code.as_ref().map(|n| n.to_string()).as_deref(),
-                    orn.audio_visual_number.as_deref(),
-                    orn.v_isan_isan.as_deref(),
-                    orn.v_isan_episode.as_deref(),
-                    orn.v_isan_check_digit_1.as_deref(),
-                    orn.v_isan_version.as_deref(),
-                    orn.v_isan_check_digit_2.as_deref(),
-                    orn.eidr.as_deref(),
-                    orn.eidr_check_digit.as_deref()
-                ])?;
-                Ok(tx.last_insert_rowid())
-            }
-            allegro_cwr::CwrRegistry::Ins(ins) => {
-                statements.ins_stmt.execute(params![
-                    file_id,
-                    "INS",
-                    ins.transaction_sequence_num.as_str(),
-                    ins.record_sequence_num.as_str(),
-                    ins.number_of_voices.as_ref().map(|n| n.to_string()).as_deref(),
-                    ins.standard_instrumentation_type.as_deref(),
-                    ins.instrumentation_description.as_deref()
-                ])?;
-                Ok(tx.last_insert_rowid())
-            }
-            allegro_cwr::CwrRegistry::Ind(ind) => {
-                statements.ind_stmt.execute(params![
-                    file_id,
-                    "IND",
-                    ind.transaction_sequence_num.as_str(),
-                    ind.record_sequence_num.as_str(),
-                    ind.instrument_code.to_sql_string(),
-                    ind.number_of_players.as_ref().map(|n| n.to_string()).as_deref()
-                ])?;
-                Ok(tx.last_insert_rowid())
-            }
-            allegro_cwr::CwrRegistry::Com(com) => {
-                statements.com_stmt.execute(params![
-                    file_id,
-                    "COM",
-                    com.transaction_sequence_num.as_str(),
-                    com.record_sequence_num.as_str(),
-                    com.title.as_str(),
-                    com.iswc_of_component.as_deref(),
-                    com.submitter_work_num.as_deref(),
-                    opt_domain_to_string(&com.duration).as_deref(),
-                    com.writer_1_last_name.as_str(),
-                    com.writer_1_first_name.as_deref(),
-                    com.writer_1_ipi_name_num.as_deref(),
-                    com.writer_2_last_name.as_deref(),
-                    com.writer_2_first_name.as_deref(),
-                    com.writer_2_ipi_name_num.as_deref(),
-                    com.writer_1_ipi_base_number.as_deref(),
-                    com.writer_2_ipi_base_number.as_deref()
-                ])?;
-                Ok(tx.last_insert_rowid())
-            }
-            allegro_cwr::CwrRegistry::Msg(msg) => {
-                statements.msg_stmt.execute(params![
-                    file_id,
-                    "MSG",
-                    msg.transaction_sequence_num.as_str(),
-                    msg.record_sequence_num.as_str(),
-                    msg.message_type.to_sql_string(),
-                    msg.original_record_sequence_num.as_str(),
-                    msg.record_type_field.as_str(),
-                    msg.message_level.to_sql_string(),
-                    msg.validation_number.as_str(),
-                    msg.message_text.as_str()
-                ])?;
-                Ok(tx.last_insert_rowid())
-            }
-            allegro_cwr::CwrRegistry::Net(net) => {
-                statements.net_stmt.execute(params![
-                    file_id,
-                    "NET",
-                    net.transaction_sequence_num.as_str(),
-                    net.record_sequence_num.as_str(),
-                    net.title.as_str(),
-                    net.language_code.as_deref()
-                ])?;
-                Ok(tx.last_insert_rowid())
-            }
-            allegro_cwr::CwrRegistry::Now(now) => {
-                statements.now_stmt.execute(params![
-                    file_id,
-                    "NOW",
-                    now.transaction_sequence_num.as_str(),
-                    now.record_sequence_num.as_str(),
-                    now.writer_name.as_str(),
-                    now.writer_first_name.as_str(),
-                    now.language_code.as_deref(),
-                    now.writer_position.as_ref().map(|p| p.as_str())
-                ])?;
-                Ok(tx.last_insert_rowid())
-            }
-            allegro_cwr::CwrRegistry::Ari(ari) => {
-                statements.ari_stmt.execute(params![
-                    file_id,
-                    "ARI",
-                    ari.transaction_sequence_num.as_str(),
-                    ari.record_sequence_num.as_str(),
-                    ari.society_num.to_sql_string(),
-                    ari.work_num.as_deref(),
-                    ari.type_of_right.to_sql_string(),
-                    ari.subject_code.as_ref().map(|x| x.as_str()),
-                    ari.note.as_deref()
-                ])?;
-                Ok(tx.last_insert_rowid())
-            }
-            allegro_cwr::CwrRegistry::Xrf(xrf) => {
-                statements.xrf_stmt.execute(params![
-                    file_id,
-                    "XRF",
-                    xrf.transaction_sequence_num.as_str(),
-                    xrf.record_sequence_num.as_str(),
-                    xrf.organisation_code.to_sql_string(),
-                    xrf.identifier.as_str(),
-                    xrf.identifier_type.to_sql_string(),
-                    xrf.validity.to_sql_string()
-                ])?;
-                Ok(tx.last_insert_rowid())
-            }
-        }
+        let stmt = statements
+            .record_stmt(self.table_name())
+            .ok_or_else(|| CwrDbError::Setup(format!("No insert statement for table '{}'", self.table_name())))?;
+        stmt.execute(rusqlite::params_from_iter(self.to_sql_params(file_id)?))?;
+        Ok(tx.last_insert_rowid())
     }
 }
 
 // Re-export main types and functions
-pub use connection::{CwrDatabase, determine_db_filename, setup_database};
+pub use ack::{AckCorrelation, WorkAckStatus, correlate_acks, work_ack_statuses};
+pub use connection::{CwrDatabase, SCHEMA_VERSION, check_schema_version, determine_db_filename, setup_database};
+pub use duplicates::{DuplicateWork, WorkConflict, WorkRegistration, find_duplicate_works};
 pub use error::CwrDbError;
+pub use files::{FileSummary, delete_file, list_files, vacuum};
+pub use normalized::{SchemaKind, setup_normalized_schema};
 pub use operations::{
-    CwrRecordInserter, count_errors_by_record_type, count_records_by_type, insert_file_line_record, insert_file_record,
-    log_error,
+    CwrRecordInserter, DuplicatePolicy, content_hash, count_errors_by_record_type, count_records_by_type,
+    delete_file_rows, file_content_hash, insert_file_line_record, insert_file_record, log_error, log_warning,
 };
+pub use registration::{ACCEPTED_STATUSES, is_work_registered, registration_transaction_type};
+pub use search::{TitleMatch, enable_title_search, search_titles};
+pub use sql_dump::{SqlDialect, SqlDumpHandler, SqlDumpOptions};
 pub use statements::PreparedStatements;
 
 /// Result type for database operations
@@ -694,6 +628,10 @@ pub struct SqliteHandler {
     error_count: usize,
     db_filename: String,
     batch_size: usize,
+    commit_per_transaction: bool,
+    store_original_lines: bool,
+    schema: SchemaKind,
+    normalized: normalized::NormalizedWriter,
     statements: Option<statements::PreparedStatements<'static>>,
 }
 
@@ -703,14 +641,58 @@ impl SqliteHandler {
     }
 
     pub fn new_with_batch_size(input_filename: &str, db_filename: &str, batch_size: usize) -> Result<Self> {
-        use statements::get_prepared_statements;
+        Self::new_with_duplicate_policy(input_filename, db_filename, batch_size, DuplicatePolicy::default())
+    }
+
+    /// Like [`SqliteHandler::new_with_batch_size`], applying `duplicate_policy` when a file with the same content
+    /// was imported before
+    pub fn new_with_duplicate_policy(
+        input_filename: &str, db_filename: &str, batch_size: usize, duplicate_policy: DuplicatePolicy,
+    ) -> Result<Self> {
+        let content_hash = file_content_hash(input_filename)?;
+        Self::for_content_hash(input_filename, &content_hash, db_filename, batch_size, duplicate_policy)
+    }
+
+    /// Like [`SqliteHandler::new_with_duplicate_policy`], for content held in memory (e.g. read from stdin) rather
+    /// than a file; `name` is recorded as its file name
+    pub fn for_content(
+        name: &str, content: &[u8], db_filename: &str, batch_size: usize, duplicate_policy: DuplicatePolicy,
+    ) -> Result<Self> {
+        Self::for_content_hash(name, &content_hash(content)?, db_filename, batch_size, duplicate_policy)
+    }
 
-        // Setup database
+    fn for_content_hash(
+        input_filename: &str, content_hash: &str, db_filename: &str, batch_size: usize,
+        duplicate_policy: DuplicatePolicy,
+    ) -> Result<Self> {
         setup_database(db_filename)?;
+        let conn = rusqlite::Connection::open(db_filename)?;
+        conn.pragma_update(None, "journal_mode", "OFF")?;
+        Self::with_connection(conn, input_filename, content_hash, db_filename, batch_size, duplicate_policy)
+    }
 
-        // Open connection and setup transaction
+    /// Like [`SqliteHandler::new_with_duplicate_policy`], for importing while other connections write to the same
+    /// database: the journal is a write-ahead log, each batch takes the write lock when it starts and waits up to
+    /// `busy_timeout` for other importers to commit. The database schema must already be set up.
+    pub fn new_shared(
+        input_filename: &str, db_filename: &str, batch_size: usize, duplicate_policy: DuplicatePolicy,
+        busy_timeout: std::time::Duration,
+    ) -> Result<Self> {
         let mut conn = rusqlite::Connection::open(db_filename)?;
-        conn.pragma_update(None, "journal_mode", "OFF")?;
+        check_schema_version(&conn)?;
+        conn.busy_timeout(busy_timeout)?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.set_transaction_behavior(rusqlite::TransactionBehavior::Immediate);
+        let content_hash = file_content_hash(input_filename)?;
+        Self::with_connection(conn, input_filename, &content_hash, db_filename, batch_size, duplicate_policy)
+    }
+
+    fn with_connection(
+        mut conn: rusqlite::Connection, input_filename: &str, content_hash: &str, db_filename: &str, batch_size: usize,
+        duplicate_policy: DuplicatePolicy,
+    ) -> Result<Self> {
+        use statements::get_prepared_statements;
+
         conn.pragma_update(None, "synchronous", "OFF")?;
         conn.pragma_update(None, "temp_store", "MEMORY")?;
 
@@ -719,7 +701,13 @@ impl SqliteHandler {
         let file_id = {
             let tx = conn.transaction()?;
             let mut prepared_statements = get_prepared_statements(&tx)?;
-            let file_id = insert_file_record(&tx, &mut prepared_statements.file_insert_stmt, input_filename)?;
+            let file_id = insert_file_record(
+                &tx,
+                &mut prepared_statements.file_insert_stmt,
+                input_filename,
+                content_hash,
+                duplicate_policy,
+            )?;
             drop(prepared_statements); // Drop before commit to release borrow
             tx.commit()?;
             file_id
@@ -733,10 +721,37 @@ impl SqliteHandler {
             error_count: 0,
             db_filename: db_filename.to_string(),
             batch_size,
+            commit_per_transaction: false,
+            store_original_lines: false,
+            schema: SchemaKind::Raw,
+            normalized: normalized::NormalizedWriter::default(),
             statements: None,
         })
     }
 
+    /// Fill the tables of `schema` instead of the per-record-type tables, creating them if needed
+    pub fn with_schema(mut self, schema: SchemaKind) -> Result<Self> {
+        if schema == SchemaKind::Normalized {
+            setup_normalized_schema(&self.conn)?;
+        }
+        self.schema = schema;
+        Ok(self)
+    }
+
+    /// Commit at the end of every CWR transaction and group instead of every `batch_size` records, so a
+    /// transaction is never split across database commits
+    pub fn with_commit_per_transaction(mut self, commit_per_transaction: bool) -> Self {
+        self.commit_per_transaction = commit_per_transaction;
+        self
+    }
+
+    /// Store each record's source line in `file_line.original_line`. The records must be parsed with
+    /// [`allegro_cwr::ParseOptions::keep_raw_lines`] for there to be a line to store.
+    pub fn with_original_lines(mut self, store_original_lines: bool) -> Self {
+        self.store_original_lines = store_original_lines;
+        self
+    }
+
     fn start_batch(&mut self) -> Result<()> {
         if self.tx.is_none() {
             // Start transaction
@@ -761,7 +776,7 @@ impl SqliteHandler {
     }
 
     fn should_commit_batch(&self) -> bool {
-        self.processed_count % self.batch_size == 0
+        !self.commit_per_transaction && self.processed_count.is_multiple_of(self.batch_size)
     }
 }
 
@@ -771,20 +786,25 @@ impl allegro_cwr::CwrHandler for SqliteHandler {
     fn process_record(&mut self, parsed_record: allegro_cwr::ParsedRecord) -> std::result::Result<(), Self::Error> {
         self.start_batch()?;
 
-        if let Some(ref tx) = self.tx {
-            if let Some(ref mut statements) = self.statements {
-                // Use the trait method to execute the insertion - replaces 434 lines of match statement!
-                let record_id = parsed_record.record.execute_insert(statements, tx, self.file_id)?;
-
-                // Insert into file_line table for tracking
-                insert_file_line_record(
-                    &mut statements.file_stmt,
-                    self.file_id,
-                    parsed_record.line_number,
-                    parsed_record.record.record_type(),
-                    record_id,
-                )?;
-            }
+        if self.schema == SchemaKind::Normalized
+            && let Some(ref tx) = self.tx
+        {
+            self.normalized.insert(tx, self.file_id, parsed_record.line_number, &parsed_record.record)?;
+        } else if let Some(ref tx) = self.tx
+            && let Some(ref mut statements) = self.statements
+        {
+            // Use the trait method to execute the insertion - replaces 434 lines of match statement!
+            let record_id = parsed_record.record.execute_insert(statements, tx, self.file_id)?;
+
+            // Insert into file_line table for tracking
+            insert_file_line_record(
+                &mut statements.file_stmt,
+                self.file_id,
+                parsed_record.line_number,
+                parsed_record.record.record_type(),
+                record_id,
+                parsed_record.raw_line.as_deref().filter(|_| self.store_original_lines),
+            )?;
         }
 
         self.processed_count += 1;
@@ -814,8 +834,19 @@ impl allegro_cwr::CwrHandler for SqliteHandler {
         Ok(())
     }
 
+    fn end_transaction(&mut self) -> std::result::Result<(), Self::Error> {
+        if self.commit_per_transaction {
+            self.commit_batch()?;
+        }
+        Ok(())
+    }
+
+    fn end_group(&mut self) -> std::result::Result<(), Self::Error> {
+        self.end_transaction()
+    }
+
     fn handle_warnings(
-        &mut self, line_number: usize, record_type: &str, warnings: &[String],
+        &mut self, line_number: usize, record_type: &str, warnings: &[allegro_cwr::parsing::CwrWarning<'static>],
     ) -> std::result::Result<(), Self::Error> {
         if warnings.is_empty() {
             return Ok(());
@@ -825,9 +856,7 @@ impl allegro_cwr::CwrHandler for SqliteHandler {
 
         if let Some(ref mut statements) = self.statements {
             for warning in warnings {
-                // Store warnings in the error table with "WARNING:" prefix to distinguish from errors
-                let warning_description = format!("WARNING [{}]: {}", record_type, warning);
-                log_error(&mut statements.error_stmt, self.file_id, line_number, warning_description)?;
+                log_warning(&mut statements.warning_stmt, self.file_id, line_number, record_type, warning)?;
                 self.error_count += 1;
             }
         }
@@ -851,6 +880,15 @@ impl allegro_cwr::CwrHandler for SqliteHandler {
             self.db_filename, self.processed_count, self.error_count
         )
     }
+
+    fn report(&self) -> allegro_cwr::HandlerReport {
+        allegro_cwr::HandlerReport::Output(allegro_cwr::OutputReport {
+            format: "sqlite".to_string(),
+            destination: Some(self.db_filename.clone()),
+            records: self.processed_count,
+            errors: self.error_count,
+        })
+    }
 }
 
 /// Convenience function to process CWR file with SQLite handler
@@ -864,40 +902,212 @@ pub fn process_cwr_to_sqlite(
 pub fn process_cwr_to_sqlite_with_version(
     input_filename: &str, db_filename: &str, version_hint: Option<f32>,
 ) -> std::result::Result<(i64, usize, String), Box<dyn std::error::Error>> {
-    let handler = SqliteHandler::new(input_filename, db_filename)?;
+    process_cwr_to_sqlite_with_options(
+        input_filename,
+        db_filename,
+        &ImportOptions { version_hint, ..Default::default() },
+    )
+}
+
+/// Options for importing a CWR file into SQLite
+#[derive(Debug, Clone, Default)]
+pub struct ImportOptions {
+    /// CWR version to use instead of auto-detecting it
+    pub version_hint: Option<f32>,
+    /// Keep the verbatim source line of every record in `file_line.original_line`
+    pub store_original_lines: bool,
+    /// What to do when the file's content was imported before
+    pub on_duplicate: DuplicatePolicy,
+    /// Treat the file as an acknowledgement and match its ACK transactions to earlier submissions
+    pub correlate_acks: bool,
+    /// Add the full-text title search index to the database if it does not have it (see [`search_titles`])
+    pub title_search: bool,
+    /// Tables to import into
+    pub schema: SchemaKind,
+}
+
+/// Convenience function to process CWR file with SQLite handler using the given import options
+pub fn process_cwr_to_sqlite_with_options(
+    input_filename: &str, db_filename: &str, options: &ImportOptions,
+) -> std::result::Result<(i64, usize, String), Box<dyn std::error::Error>> {
+    if options.title_search {
+        setup_database(db_filename)?;
+        enable_title_search(db_filename)?;
+    }
+    let handler = SqliteHandler::new_with_duplicate_policy(input_filename, db_filename, 1000, options.on_duplicate);
+    import_with_handler(input_filename, db_filename, options, handler)
+}
+
+/// Imports a JSON file written by `allegro_cwr_json` into SQLite from its JSON fields, without going through CWR
+/// lines, so the rows match those of importing the original CWR file. Original lines are stored when the JSON has
+/// them and `store_original_lines` is set; the version hint does not apply.
+pub fn process_json_to_sqlite_with_options(
+    input_filename: &str, db_filename: &str, options: &ImportOptions,
+) -> std::result::Result<(i64, usize, String), Box<dyn std::error::Error>> {
+    if options.title_search {
+        setup_database(db_filename)?;
+        enable_title_search(db_filename)?;
+    }
+    let handler = SqliteHandler::new_with_duplicate_policy(input_filename, db_filename, 1000, options.on_duplicate);
+    run_import(input_filename, db_filename, options, handler, |handler| {
+        allegro_cwr_json::process_json_with_handler(input_filename, handler)
+    })
+}
+
+/// Like [`process_cwr_to_sqlite_with_options`], for CWR content held in memory (e.g. read from stdin); `name` is
+/// recorded as its file name
+pub fn process_cwr_bytes_to_sqlite_with_options(
+    name: &str, content: &[u8], db_filename: &str, options: &ImportOptions,
+) -> std::result::Result<(i64, usize, String), Box<dyn std::error::Error>> {
+    if options.title_search {
+        setup_database(db_filename)?;
+        enable_title_search(db_filename)?;
+    }
+    let handler = SqliteHandler::for_content(name, content, db_filename, 1000, options.on_duplicate);
+    run_import(name, db_filename, options, handler, |handler| {
+        allegro_cwr::process_cwr_reader_with_handler_and_options(name, content, handler, &parse_options(options))
+    })
+}
+
+/// Like [`process_json_to_sqlite_with_options`], for JSON held in memory (e.g. read from stdin); `name` is recorded
+/// as its file name
+pub fn process_json_bytes_to_sqlite_with_options(
+    name: &str, content: &[u8], db_filename: &str, options: &ImportOptions,
+) -> std::result::Result<(i64, usize, String), Box<dyn std::error::Error>> {
+    if options.title_search {
+        setup_database(db_filename)?;
+        enable_title_search(db_filename)?;
+    }
+    let handler = SqliteHandler::for_content(name, content, db_filename, 1000, options.on_duplicate);
+    run_import(name, db_filename, options, handler, |handler| {
+        allegro_cwr_json::process_json_reader_with_handler(name, content, handler)
+    })
+}
+
+/// Parses `input_filename` into `handler`, the step of an import that follows setting up the database
+pub(crate) fn import_with_handler(
+    input_filename: &str, db_filename: &str, options: &ImportOptions, handler: Result<SqliteHandler>,
+) -> std::result::Result<(i64, usize, String), Box<dyn std::error::Error>> {
+    run_import(input_filename, db_filename, options, handler, |handler| {
+        allegro_cwr::process_cwr_with_handler_and_options(input_filename, handler, &parse_options(options))
+    })
+}
+
+/// How an import with `options` reads its CWR input
+fn parse_options(options: &ImportOptions) -> allegro_cwr::ParseOptions {
+    allegro_cwr::ParseOptions {
+        version_hint: options.version_hint,
+        keep_raw_lines: options.store_original_lines,
+        ..Default::default()
+    }
+}
+
+/// Sets up `handler` for `options` and feeds it the input with `process`, unless the file is a skipped duplicate
+fn run_import(
+    input_filename: &str, db_filename: &str, options: &ImportOptions, handler: Result<SqliteHandler>,
+    process: impl FnOnce(SqliteHandler) -> std::result::Result<allegro_cwr::ProcessingSummary, Box<dyn std::error::Error>>,
+) -> std::result::Result<(i64, usize, String), Box<dyn std::error::Error>> {
+    let handler = match handler {
+        Ok(handler) => handler.with_original_lines(options.store_original_lines).with_schema(options.schema)?,
+        Err(CwrDbError::DuplicateFile { existing_file_id }) => {
+            let report = format!(
+                "SQLite processing skipped:\n  Database: {}\n  '{}' was already imported as file ID {}",
+                db_filename, input_filename, existing_file_id
+            );
+            return Ok((existing_file_id, 0, report));
+        }
+        Err(e) => return Err(e.into()),
+    };
     let file_id = handler.file_id;
-    let report = allegro_cwr::process_cwr_with_handler_and_version(input_filename, handler, version_hint)?;
+    let summary = process(handler)?;
+    let processed = summary.handler_report.output().map_or(0, |output| output.records);
+    let mut report = summary.report;
 
-    // Extract count from report (simple parsing for now)
-    let processed_count = report
-        .lines()
-        .find(|line| line.contains("Records processed:"))
-        .and_then(|line| line.split(':').nth(1))
-        .and_then(|s| s.trim().parse::<usize>().ok())
-        .unwrap_or(0);
+    if options.correlate_acks {
+        let mut conn = rusqlite::Connection::open(db_filename)?;
+        let correlation = correlate_acks(&mut conn, file_id)?;
+        report.push_str(&format!(
+            "\n  ACKs matched to submissions: {}\n  ACKs without a submission: {}",
+            correlation.matched, correlation.unmatched
+        ));
+    }
+
+    Ok((file_id, processed, report))
+}
 
-    Ok((file_id, processed_count, report))
+/// Writes `input_filename` to `writer` as the SQL statements importing it would run, as `dump` describes. Of
+/// `options`, only the version hint and whether to keep original lines apply. Returns the number of records
+/// written and the handler's report.
+pub fn process_cwr_to_sql<W: std::io::Write>(
+    input_filename: &str, writer: W, options: &ImportOptions, dump: &SqlDumpOptions,
+) -> std::result::Result<(usize, String), Box<dyn std::error::Error>> {
+    let content_hash = file_content_hash(input_filename)?;
+    let handler = SqlDumpHandler::new(writer, input_filename, Some(&content_hash), dump)?;
+    let summary = allegro_cwr::process_cwr_with_handler_and_options(input_filename, handler, &parse_options(options))?;
+    Ok((summary.handler_report.output().map_or(0, |output| output.records), summary.report))
+}
+
+/// Like [`process_cwr_to_sql`], for CWR content held in memory (e.g. read from stdin); `name` is recorded as its
+/// file name
+pub fn process_cwr_bytes_to_sql<W: std::io::Write>(
+    name: &str, content: &[u8], writer: W, options: &ImportOptions, dump: &SqlDumpOptions,
+) -> std::result::Result<(usize, String), Box<dyn std::error::Error>> {
+    let handler = SqlDumpHandler::new(writer, name, Some(&content_hash(content)?), dump)?;
+    let summary =
+        allegro_cwr::process_cwr_reader_with_handler_and_options(name, content, handler, &parse_options(options))?;
+    Ok((summary.handler_report.output().map_or(0, |output| output.records), summary.report))
 }
 
 /// Convenience function to process SQLite database and output CWR with optional version hint and output file
 /// This demonstrates the SQLite-to-CWR write pattern, following the same approach as JSON-to-CWR
 pub fn process_sqlite_to_cwr_with_version_and_output(
     db_filename: &str, file_id: i64, version_hint: Option<f32>, output_filename: Option<&str>,
+) -> std::result::Result<usize, Box<dyn std::error::Error>> {
+    process_sqlite_to_cwr_with_options(
+        db_filename,
+        file_id,
+        output_filename,
+        &ExportOptions { version_hint, ..Default::default() },
+    )
+}
+
+/// Options for exporting an imported file back to CWR
+#[derive(Debug, Clone, Default)]
+pub struct ExportOptions {
+    /// CWR version to write (2.2 when not given)
+    pub version_hint: Option<f32>,
+    /// Send works as REV when an ACK has accepted them and as NWR otherwise, regrouping transactions by type
+    pub registration_aware: bool,
+}
+
+/// Exports an imported file to CWR (stdout when no output file is given) using the given export options
+pub fn process_sqlite_to_cwr_with_options(
+    db_filename: &str, file_id: i64, output_filename: Option<&str>, options: &ExportOptions,
 ) -> std::result::Result<usize, Box<dyn std::error::Error>> {
     use std::fs::File;
-    use std::io::{self, Write};
+    use std::io::{self, BufWriter, Write};
+
+    let output: Box<dyn Write> = match output_filename {
+        Some(filename) => Box::new(BufWriter::new(File::create(filename)?)),
+        None => Box::new(BufWriter::new(io::stdout())),
+    };
+    process_sqlite_to_cwr_to_writer(db_filename, file_id, output, options)
+}
 
+/// Exports an imported file as CWR lines written to `writer` (stdout, a pipe, an in-memory buffer, ...) using the
+/// given export options. Returns the number of records written.
+pub fn process_sqlite_to_cwr_to_writer<W: std::io::Write>(
+    db_filename: &str, file_id: i64, output: W, options: &ExportOptions,
+) -> std::result::Result<usize, Box<dyn std::error::Error>> {
     // Open database connection
     let conn = rusqlite::Connection::open(db_filename)?;
 
     // Get CWR version from the database or use hint
-    let _cwr_version = allegro_cwr::domain_types::CwrVersion(version_hint.or(Some(2.2)).expect("Hardcoded?!"));
+    let _cwr_version = allegro_cwr::domain_types::CwrVersion(options.version_hint.unwrap_or(2.2));
 
-    // Create output writer with ASCII validation
-    let output: Box<dyn Write> = match output_filename {
-        Some(filename) => Box::new(File::create(filename)?),
-        None => Box::new(io::stdout()),
-    };
+    if options.registration_aware {
+        return write_registration_aware(&conn, file_id, _cwr_version, output);
+    }
     let mut ascii_writer = allegro_cwr::AsciiWriter::new(output);
 
     // For demonstration, let's implement a simple approach using the stored record lines
@@ -927,19 +1137,95 @@ pub fn process_sqlite_to_cwr_with_version_and_output(
 
         // Query and reconstruct the actual record from database fields
         if let Some(cwr_record) = query_record_by_type(&conn, &record_type, record_id)? {
-            let character_set = allegro_cwr::domain_types::CharacterSet::ASCII;
-            let cwr_bytes = cwr_record.to_cwr_record_bytes(&_cwr_version, &character_set);
-            let cwr_line = String::from_utf8_lossy(&cwr_bytes).to_string();
-            ascii_writer.write_line(&cwr_line)?;
+            ascii_writer.write_record(&cwr_record, &_cwr_version)?;
             count += 1; // Only count successfully reconstructed records
         }
         // Skip records that couldn't be reconstructed (not yet implemented)
     }
 
-    // AsciiWriter doesn't need explicit flush - it writes directly
+    ascii_writer.flush()?;
     Ok(count)
 }
 
+/// Writes the file's transactions with NWR/REV decided by [`registration_transaction_type`]. Transactions are
+/// regrouped so each transaction type gets one group, in order of first appearance, with regenerated sequence
+/// numbers and control records.
+fn write_registration_aware<W: std::io::Write>(
+    conn: &rusqlite::Connection, file_id: i64, version: allegro_cwr::domain_types::CwrVersion, output: W,
+) -> std::result::Result<usize, Box<dyn std::error::Error>> {
+    use allegro_cwr::CwrRegistry;
+    use allegro_cwr::domain_types::{CharacterSet, TransactionType};
+
+    let mut stmt = conn.prepare(
+        "SELECT record_type, record_id FROM file_line WHERE file_id = ?1 ORDER BY line_number, insert_position",
+    )?;
+    let rows: Vec<(String, i64)> =
+        stmt.query_map([file_id], |row| Ok((row.get(0)?, row.get(1)?)))?.collect::<std::result::Result<_, _>>()?;
+
+    let mut hdr = None;
+    let mut grh: Option<allegro_cwr::GrhRecord> = None;
+    // (transaction type, group header, transactions) per output group
+    let mut groups: Vec<(String, allegro_cwr::GrhRecord, Vec<Vec<CwrRegistry>>)> = Vec::new();
+    let mut current_group: Option<usize> = None;
+
+    for (record_type, record_id) in rows {
+        let Some(mut record) = query_record_by_type(conn, &record_type, record_id)? else {
+            continue;
+        };
+        match record {
+            CwrRegistry::Hdr(record) => hdr = Some(record),
+            CwrRegistry::Grh(record) => grh = Some(record),
+            CwrRegistry::Grt(_) | CwrRegistry::Trl(_) => {}
+            _ if matches!(record.sequence_numbers(), Some((_, 0))) => {
+                let Some(group_header) = grh.as_ref() else {
+                    return Err(format!("{} transaction outside of a group", record.record_type()).into());
+                };
+                if let CwrRegistry::Nwr(nwr) = &mut record
+                    && matches!(nwr.transaction_type(), TransactionType::NWR | TransactionType::REV)
+                {
+                    let transaction_type = registration_transaction_type(conn, &nwr.submitter_work_num)?;
+                    nwr.record_type = intern(transaction_type.as_str());
+                }
+                let transaction_type = record.record_type().to_string();
+                let index = match groups.iter().position(|(kind, _, _)| *kind == transaction_type) {
+                    Some(index) => index,
+                    None => {
+                        let mut group_header = group_header.clone();
+                        if let Some(kind) = [TransactionType::NWR, TransactionType::REV]
+                            .into_iter()
+                            .find(|kind| kind.as_str() == transaction_type)
+                        {
+                            group_header.transaction_type = kind;
+                        }
+                        groups.push((transaction_type, group_header, Vec::new()));
+                        groups.len() - 1
+                    }
+                };
+                groups[index].2.push(vec![record]);
+                current_group = Some(index);
+            }
+            record => match current_group.and_then(|index| groups[index].2.last_mut()) {
+                Some(transaction) => transaction.push(record),
+                None => return Err(format!("{} record before the first transaction", record.record_type()).into()),
+            },
+        }
+    }
+
+    let Some(hdr) = hdr else {
+        return Err("File has no HDR record".into());
+    };
+    let mut writer = allegro_cwr::TransmissionWriter::start(output, &hdr, version, CharacterSet::ASCII)?;
+    for (index, (_, group_header, transactions)) in groups.iter().enumerate() {
+        for transaction in transactions {
+            writer.start_transaction(index as u32, group_header)?;
+            for record in transaction {
+                writer.write_detail(&mut record.clone())?;
+            }
+        }
+    }
+    Ok(writer.finish()?.records as usize)
+}
+
 /// Query a specific record by type and reconstruct it from database fields
 /// This demonstrates the key challenge: converting database strings back to domain types
 fn query_record_by_type(
@@ -954,7 +1240,7 @@ fn query_record_by_type(
                 // Reconstruct HDR record from database fields
                 // This is where we need to convert database strings back to domain types
                 let hdr = allegro_cwr::records::HdrRecord {
-                    record_type: row.get::<_, String>("record_type")?,
+                    record_type: intern(&row.get::<_, String>("record_type")?),
                     sender_type: {
                         use allegro_cwr::domain_types::SenderType;
                         SenderType::from_sql_string(&row.get::<_, String>("sender_type")?)
@@ -1031,7 +1317,7 @@ fn query_record_by_type(
             let mut stmt = conn.prepare("SELECT * FROM cwr_grh WHERE cwr_grh_id = ?1")?;
             match stmt.query_row(params![record_id], |row| {
                 let grh = allegro_cwr::records::GrhRecord {
-                    record_type: row.get::<_, String>("record_type")?,
+                    record_type: intern(&row.get::<_, String>("record_type")?),
                     transaction_type: {
                         use allegro_cwr::domain_types::TransactionType;
                         TransactionType::from_sql_string(&row.get::<_, String>("transaction_type")?)
@@ -1066,7 +1352,7 @@ fn query_record_by_type(
             let mut stmt = conn.prepare("SELECT * FROM cwr_grt WHERE cwr_grt_id = ?1")?;
             match stmt.query_row(params![record_id], |row| {
                 let grt = allegro_cwr::records::GrtRecord {
-                    record_type: row.get::<_, String>("record_type")?,
+                    record_type: intern(&row.get::<_, String>("record_type")?),
                     group_id: {
                         use allegro_cwr::domain_types::GroupId;
                         GroupId::from_sql_string(&row.get::<_, String>("group_id")?)
@@ -1115,7 +1401,7 @@ fn query_record_by_type(
             let mut stmt = conn.prepare("SELECT * FROM cwr_trl WHERE cwr_trl_id = ?1")?;
             match stmt.query_row(params![record_id], |row| {
                 let trl = allegro_cwr::records::TrlRecord {
-                    record_type: row.get::<_, String>("record_type")?,
+                    record_type: intern(&row.get::<_, String>("record_type")?),
                     group_count: {
                         use allegro_cwr::domain_types::GroupCount;
                         GroupCount::from_sql_string(&row.get::<_, String>("group_count")?)
@@ -1139,11 +1425,11 @@ fn query_record_by_type(
                 Err(e) => Err(error::CwrDbError::Sqlite(e)),
             }
         }
-        "NWR" => {
+        "NWR" | "REV" | "ISW" | "EXC" => {
             let mut stmt = conn.prepare("SELECT * FROM cwr_nwr WHERE cwr_nwr_id = ?1")?;
             match stmt.query_row(params![record_id], |row| {
                 let nwr = allegro_cwr::records::NwrRecord {
-                    record_type: row.get::<_, String>("record_type")?,
+                    record_type: intern(&row.get::<_, String>("record_type")?),
                     transaction_sequence_num: {
                         use allegro_cwr::domain_types::Number;
                         Number::from_sql_string(&row.get::<_, String>("transaction_sequence_num")?)
@@ -1170,9 +1456,9 @@ fn query_record_by_type(
                     )
                     .map_err(|e| rusqlite::Error::InvalidColumnType(0, e, rusqlite::types::Type::Text))?,
                     duration: {
-                        use allegro_cwr::domain_types::Time;
-                        opt_string_to_domain::<Time>(row.get::<_, Option<String>>("duration")?.as_deref())
-                            .map_err(|e| rusqlite::Error::InvalidColumnType(0, e, rusqlite::types::Type::Text))?
+                        use allegro_cwr::domain_types::Duration;
+                        opt_int_to_domain::<Duration>(row.get::<_, Option<i64>>("duration")?)
+                            .map_err(|e| rusqlite::Error::InvalidColumnType(0, e, rusqlite::types::Type::Integer))?
                     },
                     recorded_indicator: {
                         use allegro_cwr::domain_types::Flag;
@@ -1207,14 +1493,11 @@ fn query_record_by_type(
                             .map_err(|e| rusqlite::Error::InvalidColumnType(0, e, rusqlite::types::Type::Text))?
                     },
                     composite_component_count: {
-                        let opt_int: Option<i64> = row.get::<_, Option<i64>>("composite_component_count")?;
-                        opt_int
-                            .map(|i| {
-                                use allegro_cwr::domain_types::CompositeComponentCount;
-                                CompositeComponentCount::from_sql_string(&i.to_string())
-                            })
-                            .transpose()
-                            .map_err(|e| rusqlite::Error::InvalidColumnType(0, e, rusqlite::types::Type::Text))?
+                        use allegro_cwr::domain_types::CompositeComponentCount;
+                        opt_string_to_numeric::<CompositeComponentCount>(
+                            row.get::<_, Option<String>>("composite_component_count")?.as_deref(),
+                        )
+                        .map_err(|e| rusqlite::Error::InvalidColumnType(0, e, rusqlite::types::Type::Text))?
                     },
                     date_of_publication_of_printed_edition: {
                         use allegro_cwr::domain_types::Date;
@@ -1247,7 +1530,7 @@ fn query_record_by_type(
             let mut stmt = conn.prepare("SELECT * FROM cwr_agr WHERE cwr_agr_id = ?1")?;
             match stmt.query_row(params![record_id], |row| {
                 let agr = allegro_cwr::records::AgrRecord {
-                    record_type: row.get::<_, String>("record_type")?,
+                    record_type: intern(&row.get::<_, String>("record_type")?),
                     transaction_sequence_num: {
                         use allegro_cwr::domain_types::Number;
                         Number::from_sql_string(&row.get::<_, String>("transaction_sequence_num")?)
@@ -1350,7 +1633,7 @@ fn query_record_by_type(
             let mut stmt = conn.prepare("SELECT * FROM cwr_ack WHERE cwr_ack_id = ?1")?;
             match stmt.query_row(params![record_id], |row| {
                 let ack = allegro_cwr::records::AckRecord {
-                    record_type: row.get::<_, String>("record_type")?,
+                    record_type: intern(&row.get::<_, String>("record_type")?),
                     transaction_sequence_num: {
                         use allegro_cwr::domain_types::Number;
                         Number::from_sql_string(&row.get::<_, String>("transaction_sequence_num")?)
@@ -1412,7 +1695,7 @@ fn query_record_by_type(
             let mut stmt = conn.prepare("SELECT * FROM cwr_ter WHERE cwr_ter_id = ?1")?;
             match stmt.query_row(params![record_id], |row| {
                 let ter = allegro_cwr::records::TerRecord {
-                    record_type: row.get::<_, String>("record_type")?,
+                    record_type: intern(&row.get::<_, String>("record_type")?),
                     transaction_sequence_num: {
                         use allegro_cwr::domain_types::Number;
                         Number::from_sql_string(&row.get::<_, String>("transaction_sequence_num")?)
@@ -1447,7 +1730,7 @@ fn query_record_by_type(
             let mut stmt = conn.prepare("SELECT * FROM cwr_ipa WHERE cwr_ipa_id = ?1")?;
             match stmt.query_row(params![record_id], |row| {
                 let ipa = allegro_cwr::records::IpaRecord {
-                    record_type: row.get::<_, String>("record_type")?,
+                    record_type: intern(&row.get::<_, String>("record_type")?),
                     transaction_sequence_num: {
                         use allegro_cwr::domain_types::Number;
                         Number::from_sql_string(&row.get::<_, String>("transaction_sequence_num")?)
@@ -1538,7 +1821,7 @@ fn query_record_by_type(
             let mut stmt = conn.prepare("SELECT * FROM cwr_npa WHERE cwr_npa_id = ?1")?;
             match stmt.query_row(params![record_id], |row| {
                 let npa = allegro_cwr::records::NpaRecord {
-                    record_type: row.get::<_, String>("record_type")?,
+                    record_type: intern(&row.get::<_, String>("record_type")?),
                     transaction_sequence_num: {
                         use allegro_cwr::domain_types::Number;
                         Number::from_sql_string(&row.get::<_, String>("transaction_sequence_num")?)
@@ -1570,11 +1853,11 @@ fn query_record_by_type(
                 Err(e) => Err(error::CwrDbError::Sqlite(e)),
             }
         }
-        "SPU" => {
+        "SPU" | "OPU" => {
             let mut stmt = conn.prepare("SELECT * FROM cwr_spu WHERE cwr_spu_id = ?1")?;
             match stmt.query_row(params![record_id], |row| {
                 let spu = allegro_cwr::records::SpuRecord {
-                    record_type: row.get::<_, String>("record_type")?,
+                    record_type: intern(&row.get::<_, String>("record_type")?),
                     transaction_sequence_num: {
                         use allegro_cwr::domain_types::Number;
                         Number::from_sql_string(&row.get::<_, String>("transaction_sequence_num")?)
@@ -1720,7 +2003,7 @@ fn query_record_by_type(
             let mut stmt = conn.prepare("SELECT * FROM cwr_npn WHERE cwr_npn_id = ?1")?;
             match stmt.query_row(params![record_id], |row| {
                 let npn = allegro_cwr::records::NpnRecord {
-                    record_type: row.get::<_, String>("record_type")?,
+                    record_type: intern(&row.get::<_, String>("record_type")?),
                     transaction_sequence_num: {
                         use allegro_cwr::domain_types::Number;
                         Number::from_sql_string(&row.get::<_, String>("transaction_sequence_num")?)
@@ -1754,11 +2037,11 @@ fn query_record_by_type(
                 Err(e) => Err(error::CwrDbError::Sqlite(e)),
             }
         }
-        "SPT" => {
+        "SPT" | "OPT" => {
             let mut stmt = conn.prepare("SELECT * FROM cwr_spt WHERE cwr_spt_id = ?1")?;
             match stmt.query_row(params![record_id], |row| {
                 let spt = allegro_cwr::records::SptRecord {
-                    record_type: row.get::<_, String>("record_type")?,
+                    record_type: intern(&row.get::<_, String>("record_type")?),
                     transaction_sequence_num: {
                         use allegro_cwr::domain_types::Number;
                         Number::from_sql_string(&row.get::<_, String>("transaction_sequence_num")?)
@@ -1821,11 +2104,11 @@ fn query_record_by_type(
                 Err(e) => Err(error::CwrDbError::Sqlite(e)),
             }
         }
-        "SWR" => {
+        "SWR" | "OWR" => {
             let mut stmt = conn.prepare("SELECT * FROM cwr_swr WHERE cwr_swr_id = ?1")?;
             match stmt.query_row(params![record_id], |row| {
                 let swr = allegro_cwr::records::SwrRecord {
-                    record_type: row.get::<_, String>("record_type")?,
+                    record_type: intern(&row.get::<_, String>("record_type")?),
                     transaction_sequence_num: {
                         use allegro_cwr::domain_types::Number;
                         Number::from_sql_string(&row.get::<_, String>("transaction_sequence_num")?)
@@ -1967,7 +2250,7 @@ fn query_record_by_type(
             let mut stmt = conn.prepare("SELECT * FROM cwr_nwn WHERE cwr_nwn_id = ?1")?;
             match stmt.query_row(params![record_id], |row| {
                 let nwn = allegro_cwr::records::NwnRecord {
-                    record_type: row.get::<_, String>("record_type")?,
+                    record_type: intern(&row.get::<_, String>("record_type")?),
                     transaction_sequence_num: {
                         use allegro_cwr::domain_types::Number;
                         Number::from_sql_string(&row.get::<_, String>("transaction_sequence_num")?)
@@ -2009,11 +2292,11 @@ fn query_record_by_type(
                 Err(e) => Err(error::CwrDbError::Sqlite(e)),
             }
         }
-        "SWT" => {
+        "SWT" | "OWT" => {
             let mut stmt = conn.prepare("SELECT * FROM cwr_swt WHERE cwr_swt_id = ?1")?;
             match stmt.query_row(params![record_id], |row| {
                 let swt = allegro_cwr::records::SwtRecord {
-                    record_type: row.get::<_, String>("record_type")?,
+                    record_type: intern(&row.get::<_, String>("record_type")?),
                     transaction_sequence_num: {
                         use allegro_cwr::domain_types::Number;
                         Number::from_sql_string(&row.get::<_, String>("transaction_sequence_num")?)
@@ -2079,7 +2362,7 @@ fn query_record_by_type(
             let mut stmt = conn.prepare("SELECT * FROM cwr_pwr WHERE cwr_pwr_id = ?1")?;
             match stmt.query_row(params![record_id], |row| {
                 let pwr = allegro_cwr::records::PwrRecord {
-                    record_type: row.get::<_, String>("record_type")?,
+                    record_type: intern(&row.get::<_, String>("record_type")?),
                     transaction_sequence_num: {
                         use allegro_cwr::domain_types::Number;
                         Number::from_sql_string(&row.get::<_, String>("transaction_sequence_num")?)
@@ -2115,7 +2398,7 @@ fn query_record_by_type(
             let mut stmt = conn.prepare("SELECT * FROM cwr_alt WHERE cwr_alt_id = ?1")?;
             match stmt.query_row(params![record_id], |row| {
                 let alt = allegro_cwr::records::AltRecord {
-                    record_type: row.get::<_, String>("record_type")?,
+                    record_type: intern(&row.get::<_, String>("record_type")?),
                     transaction_sequence_num: {
                         use allegro_cwr::domain_types::Number;
                         Number::from_sql_string(&row.get::<_, String>("transaction_sequence_num")?)
@@ -2150,7 +2433,7 @@ fn query_record_by_type(
             let mut stmt = conn.prepare("SELECT * FROM cwr_nat WHERE cwr_nat_id = ?1")?;
             match stmt.query_row(params![record_id], |row| {
                 let nat = allegro_cwr::records::NatRecord {
-                    record_type: row.get::<_, String>("record_type")?,
+                    record_type: intern(&row.get::<_, String>("record_type")?),
                     transaction_sequence_num: {
                         use allegro_cwr::domain_types::Number;
                         Number::from_sql_string(&row.get::<_, String>("transaction_sequence_num")?)
@@ -2185,7 +2468,7 @@ fn query_record_by_type(
             let mut stmt = conn.prepare("SELECT * FROM cwr_ewt WHERE cwr_ewt_id = ?1")?;
             match stmt.query_row(params![record_id], |row| {
                 let ewt = allegro_cwr::records::EwtRecord {
-                    record_type: row.get::<_, String>("record_type")?,
+                    record_type: intern(&row.get::<_, String>("record_type")?),
                     transaction_sequence_num: {
                         use allegro_cwr::domain_types::Number;
                         Number::from_sql_string(&row.get::<_, String>("transaction_sequence_num")?)
@@ -2253,7 +2536,7 @@ fn query_record_by_type(
             let mut stmt = conn.prepare("SELECT * FROM cwr_ver WHERE cwr_ver_id = ?1")?;
             match stmt.query_row(params![record_id], |row| {
                 let ver = allegro_cwr::records::VerRecord {
-                    record_type: row.get::<_, String>("record_type")?,
+                    record_type: intern(&row.get::<_, String>("record_type")?),
                     transaction_sequence_num: {
                         use allegro_cwr::domain_types::Number;
                         Number::from_sql_string(&row.get::<_, String>("transaction_sequence_num")?)
@@ -2328,7 +2611,7 @@ fn query_record_by_type(
             let mut stmt = conn.prepare("SELECT * FROM cwr_per WHERE cwr_per_id = ?1")?;
             match stmt.query_row(params![record_id], |row| {
                 let per = allegro_cwr::records::PerRecord {
-                    record_type: row.get::<_, String>("record_type")?,
+                    record_type: intern(&row.get::<_, String>("record_type")?),
                     transaction_sequence_num: {
                         use allegro_cwr::domain_types::Number;
                         Number::from_sql_string(&row.get::<_, String>("transaction_sequence_num")?)
@@ -2373,7 +2656,7 @@ fn query_record_by_type(
             let mut stmt = conn.prepare("SELECT * FROM cwr_npr WHERE cwr_npr_id = ?1")?;
             match stmt.query_row(params![record_id], |row| {
                 let npr = allegro_cwr::records::NprRecord {
-                    record_type: row.get::<_, String>("record_type")?,
+                    record_type: intern(&row.get::<_, String>("record_type")?),
                     transaction_sequence_num: {
                         use allegro_cwr::domain_types::Number;
                         Number::from_sql_string(&row.get::<_, String>("transaction_sequence_num")?)
@@ -2446,7 +2729,7 @@ fn query_record_by_type(
             let mut stmt = conn.prepare("SELECT * FROM cwr_rec WHERE cwr_rec_id = ?1")?;
             match stmt.query_row(params![record_id], |row| {
                 let rec = allegro_cwr::records::RecRecord {
-                    record_type: row.get::<_, String>("record_type")?,
+                    record_type: intern(&row.get::<_, String>("record_type")?),
                     transaction_sequence_num: {
                         use allegro_cwr::domain_types::Number;
                         Number::from_sql_string(&row.get::<_, String>("transaction_sequence_num")?)
@@ -2464,9 +2747,9 @@ fn query_record_by_type(
                     },
                     constant: row.get::<_, String>("constant_blanks_1")?,
                     release_duration: {
-                        use allegro_cwr::domain_types::Time;
-                        opt_string_to_domain::<Time>(row.get::<_, Option<String>>("release_duration")?.as_deref())
-                            .map_err(|e| rusqlite::Error::InvalidColumnType(0, e, rusqlite::types::Type::Text))?
+                        use allegro_cwr::domain_types::Duration;
+                        opt_int_to_domain::<Duration>(row.get::<_, Option<i64>>("release_duration")?)
+                            .map_err(|e| rusqlite::Error::InvalidColumnType(0, e, rusqlite::types::Type::Integer))?
                     },
                     constant2: row.get::<_, String>("constant_blanks_2")?,
                     album_title: row.get::<_, Option<String>>("album_title")?,
@@ -2537,7 +2820,7 @@ fn query_record_by_type(
             let mut stmt = conn.prepare("SELECT * FROM cwr_orn WHERE cwr_orn_id = ?1")?;
             match stmt.query_row(params![record_id], |row| {
                 let orn = allegro_cwr::records::OrnRecord {
-                    record_type: row.get::<_, String>("record_type")?,
+                    record_type: intern(&row.get::<_, String>("record_type")?),
                     transaction_sequence_num: {
                         use allegro_cwr::domain_types::Number;
                         Number::from_sql_string(&row.get::<_, String>("transaction_sequence_num")?)
@@ -2562,16 +2845,10 @@ fn query_record_by_type(
                         .map_err(|e| rusqlite::Error::InvalidColumnType(0, e, rusqlite::types::Type::Text))?,
                     library: row.get::<_, Option<String>>("library")?,
                     bltvr: row.get::<_, Option<String>>("bltvr")?,
-                    filler: {
-                        match row.get::<_, Option<String>>("filler")? {
-                            Some(filler) => {
-                                Some(Number::from_sql_string(&filler).map_err(|e| {
-                                    rusqlite::Error::InvalidColumnType(0, e, rusqlite::types::Type::Text)
-                                })?)
-                            }
-                            None => None,
-                        }
-                    },
+                    filler: opt_string_to_numeric::<Number>(
+                        row.get::<_, Option<String>>("filler_reserved")?.as_deref(),
+                    )
+                    .map_err(|e| rusqlite::Error::InvalidColumnType(0, e, rusqlite::types::Type::Text))?,
                     production_num: row.get::<_, Option<String>>("production_num")?,
                     episode_title: row.get::<_, Option<String>>("episode_title")?,
                     episode_num: row.get::<_, Option<String>>("episode_num")?,
@@ -2603,7 +2880,7 @@ fn query_record_by_type(
             let mut stmt = conn.prepare("SELECT * FROM cwr_ins WHERE cwr_ins_id = ?1")?;
             match stmt.query_row(params![record_id], |row| {
                 let ins = allegro_cwr::records::InsRecord {
-                    record_type: row.get::<_, String>("record_type")?,
+                    record_type: intern(&row.get::<_, String>("record_type")?),
                     transaction_sequence_num: {
                         use allegro_cwr::domain_types::Number;
                         Number::from_sql_string(&row.get::<_, String>("transaction_sequence_num")?)
@@ -2642,7 +2919,7 @@ fn query_record_by_type(
             let mut stmt = conn.prepare("SELECT * FROM cwr_ind WHERE cwr_ind_id = ?1")?;
             match stmt.query_row(params![record_id], |row| {
                 let ind = allegro_cwr::records::IndRecord {
-                    record_type: row.get::<_, String>("record_type")?,
+                    record_type: intern(&row.get::<_, String>("record_type")?),
                     transaction_sequence_num: {
                         use allegro_cwr::domain_types::Number;
                         Number::from_sql_string(&row.get::<_, String>("transaction_sequence_num")?)
@@ -2678,7 +2955,7 @@ fn query_record_by_type(
             let mut stmt = conn.prepare("SELECT * FROM cwr_com WHERE cwr_com_id = ?1")?;
             match stmt.query_row(params![record_id], |row| {
                 let com = allegro_cwr::records::ComRecord {
-                    record_type: row.get::<_, String>("record_type")?,
+                    record_type: intern(&row.get::<_, String>("record_type")?),
                     transaction_sequence_num: {
                         use allegro_cwr::domain_types::Number;
                         Number::from_sql_string(&row.get::<_, String>("transaction_sequence_num")?)
@@ -2693,16 +2970,9 @@ fn query_record_by_type(
                     iswc_of_component: row.get::<_, Option<String>>("iswc_of_component")?,
                     submitter_work_num: row.get::<_, Option<String>>("submitter_work_num")?,
                     duration: {
-                        use crate::domain_conversions::CwrFromSqlString;
-                        use allegro_cwr::domain_types::Time;
-                        match row.get::<_, Option<String>>("duration")? {
-                            Some(duration_str) if !duration_str.trim().is_empty() => {
-                                Some(Time::from_sql_string(&duration_str).map_err(|e| {
-                                    rusqlite::Error::InvalidColumnType(0, e, rusqlite::types::Type::Text)
-                                })?)
-                            }
-                            _ => None,
-                        }
+                        use allegro_cwr::domain_types::Duration;
+                        opt_int_to_domain::<Duration>(row.get::<_, Option<i64>>("duration")?)
+                            .map_err(|e| rusqlite::Error::InvalidColumnType(0, e, rusqlite::types::Type::Integer))?
                     },
                     writer_1_last_name: row.get::<_, String>("writer_1_last_name")?,
                     writer_1_first_name: row.get::<_, Option<String>>("writer_1_first_name")?,
@@ -2759,7 +3029,7 @@ fn query_record_by_type(
             let mut stmt = conn.prepare("SELECT * FROM cwr_msg WHERE cwr_msg_id = ?1")?;
             match stmt.query_row(params![record_id], |row| {
                 let msg = allegro_cwr::records::MsgRecord {
-                    record_type: row.get::<_, String>("record_type")?,
+                    record_type: intern(&row.get::<_, String>("record_type")?),
                     transaction_sequence_num: {
                         use allegro_cwr::domain_types::Number;
                         Number::from_sql_string(&row.get::<_, String>("transaction_sequence_num")?)
@@ -2783,7 +3053,7 @@ fn query_record_by_type(
                         Number::from_sql_string(&row.get::<_, String>("original_record_sequence_num")?)
                             .map_err(|e| rusqlite::Error::InvalidColumnType(0, e, rusqlite::types::Type::Text))?
                     },
-                    record_type_field: row.get::<_, String>("record_type_field")?,
+                    record_type_field: row.get::<_, String>("msg_record_type")?,
                     message_level: {
                         use crate::domain_conversions::CwrFromSqlString;
 
@@ -2807,7 +3077,7 @@ fn query_record_by_type(
             let mut stmt = conn.prepare("SELECT * FROM cwr_net WHERE cwr_net_id = ?1")?;
             match stmt.query_row(params![record_id], |row| {
                 let net = allegro_cwr::records::NetRecord {
-                    record_type: row.get::<_, String>("record_type")?,
+                    record_type: intern(&row.get::<_, String>("record_type")?),
                     transaction_sequence_num: {
                         use allegro_cwr::domain_types::Number;
                         Number::from_sql_string(&row.get::<_, String>("transaction_sequence_num")?)
@@ -2838,7 +3108,7 @@ fn query_record_by_type(
             let mut stmt = conn.prepare("SELECT * FROM cwr_now WHERE cwr_now_id = ?1")?;
             match stmt.query_row(params![record_id], |row| {
                 let now = allegro_cwr::records::NowRecord {
-                    record_type: row.get::<_, String>("record_type")?,
+                    record_type: intern(&row.get::<_, String>("record_type")?),
                     transaction_sequence_num: {
                         use allegro_cwr::domain_types::Number;
                         Number::from_sql_string(&row.get::<_, String>("transaction_sequence_num")?)
@@ -2888,7 +3158,7 @@ fn query_record_by_type(
             let mut stmt = conn.prepare("SELECT * FROM cwr_ari WHERE cwr_ari_id = ?1")?;
             match stmt.query_row(params![record_id], |row| {
                 let ari = allegro_cwr::records::AriRecord {
-                    record_type: row.get::<_, String>("record_type")?,
+                    record_type: intern(&row.get::<_, String>("record_type")?),
                     transaction_sequence_num: {
                         use allegro_cwr::domain_types::Number;
                         Number::from_sql_string(&row.get::<_, String>("transaction_sequence_num")?)
@@ -2899,7 +3169,7 @@ fn query_record_by_type(
                         Number::from_sql_string(&row.get::<_, String>("record_sequence_num")?)
                             .map_err(|e| rusqlite::Error::InvalidColumnType(0, e, rusqlite::types::Type::Text))?
                     },
-                    society_num: allegro_cwr::domain_types::SocietyCode(row.get::<_, String>("society_num")?),
+                    society_num: SocietyCode(intern(&row.get::<_, String>("society_num")?)),
                     work_num: row.get::<_, Option<String>>("work_num")?,
                     type_of_right: {
                         use crate::domain_conversions::CwrFromSqlString;
@@ -2927,7 +3197,7 @@ fn query_record_by_type(
             let mut stmt = conn.prepare("SELECT * FROM cwr_xrf WHERE cwr_xrf_id = ?1")?;
             match stmt.query_row(params![record_id], |row| {
                 let xrf = allegro_cwr::records::XrfRecord {
-                    record_type: row.get::<_, String>("record_type")?,
+                    record_type: intern(&row.get::<_, String>("record_type")?),
                     transaction_sequence_num: {
                         use allegro_cwr::domain_types::Number;
                         Number::from_sql_string(&row.get::<_, String>("transaction_sequence_num")?)
@@ -2984,7 +3254,7 @@ mod tests {
     fn test_records_are_actually_inserted_into_database() {
         // Create a temporary CWR file with a few records
         let temp_dir = tempdir().unwrap();
-        let cwr_file_path = temp_dir.path().join("test.cwr");
+        let cwr_file_path = temp_dir.path().join("test.V22");
         let db_file_path = temp_dir.path().join("test.db");
 
         let mut file = File::create(&cwr_file_path).unwrap();
@@ -3231,4 +3501,336 @@ mod tests {
         println!("📝 Original CWR → SQLite → CWR conversion completed");
         println!("🔄 This follows the same streaming pattern as JSON ↔ CWR conversion");
     }
+
+    #[test]
+    fn test_original_lines_are_stored_when_enabled() {
+        let temp_dir = tempdir().unwrap();
+        let cwr_file_path = temp_dir.path().join("test.V21");
+        let db_file_path = temp_dir.path().join("test.db");
+        let grh = "GRHNWR0000102.100000000000  ";
+        std::fs::write(
+            &cwr_file_path,
+            format!(
+                "HDRPB285606836WARNER CHAPPELL MUSIC PUBLISHING LTD         01.102022122112541120221221\r\n{}\r\n",
+                grh
+            ),
+        )
+        .unwrap();
+        let cwr_path = cwr_file_path.to_str().unwrap();
+        let db_path = db_file_path.to_str().unwrap();
+
+        let (plain_id, _, _) = process_cwr_to_sqlite(cwr_path, db_path).unwrap();
+        let options = ImportOptions {
+            store_original_lines: true,
+            on_duplicate: DuplicatePolicy::NewVersion,
+            ..Default::default()
+        };
+        let (file_id, _, _) = process_cwr_to_sqlite_with_options(cwr_path, db_path, &options).unwrap();
+
+        let conn = rusqlite::Connection::open(&db_file_path).unwrap();
+        let original_line = |file_id: i64| -> Option<String> {
+            conn.query_row(
+                "SELECT original_line FROM file_line WHERE file_id = ?1 AND record_type = 'GRH'",
+                [file_id],
+                |row| row.get(0),
+            )
+            .unwrap()
+        };
+        assert_eq!(original_line(plain_id), None);
+        assert_eq!(original_line(file_id).as_deref(), Some(grh));
+    }
+
+    #[test]
+    fn test_warnings_are_stored_with_their_details() {
+        let temp_dir = tempdir().unwrap();
+        let cwr_file_path = temp_dir.path().join("test.V21");
+        let db_file_path = temp_dir.path().join("test.db");
+        std::fs::write(
+            &cwr_file_path,
+            "HDRPB285606836WARNER CHAPPELL MUSIC PUBLISHING LTD         01.102022122112541120221221\r\nGRHZZZ0000102.100000000000  \r\n",
+        )
+        .unwrap();
+        let (file_id, _, _) =
+            process_cwr_to_sqlite(cwr_file_path.to_str().unwrap(), db_file_path.to_str().unwrap()).unwrap();
+
+        let conn = rusqlite::Connection::open(&db_file_path).unwrap();
+        let (line_number, record_type, level, source): (i64, String, String, String) = conn
+            .query_row(
+                "SELECT line_number, record_type, level, source FROM error WHERE file_id = ?1 AND field_name = 'transaction_type'",
+                [file_id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+            )
+            .unwrap();
+        assert_eq!((line_number, record_type.as_str(), level.as_str(), source.as_str()), (2, "GRH", "CRITICAL", "ZZZ"));
+    }
+
+    #[test]
+    fn test_json_import_matches_cwr_import() {
+        let temp_dir = tempdir().unwrap();
+        let cwr_file_path = temp_dir.path().join("test.V21");
+        let json_file_path = temp_dir.path().join("test.json");
+        std::fs::write(
+            &cwr_file_path,
+            "HDRPB285606836WARNER CHAPPELL MUSIC PUBLISHING LTD         01.102022122112541120221221\n\
+             GRHNWR0000102.100000000000  \n\
+             NWR0000000000000000Song Title                                                  SW0000000001        SER        Y       ORI\n\
+             XYZ0000000000000001\n\
+             GRHZZZ0000202.100000000000  \n",
+        )
+        .unwrap();
+        let cwr_path = cwr_file_path.to_str().unwrap();
+        let json_path = json_file_path.to_str().unwrap();
+        let json_options =
+            allegro_cwr_json::JsonOptions { include_raw_lines: true, structured_warnings: true, ..Default::default() };
+        allegro_cwr_json::process_cwr_to_json_with_options(cwr_path, None, Some(json_path), json_options).unwrap();
+
+        let options = ImportOptions { store_original_lines: true, ..Default::default() };
+        let cwr_db = temp_dir.path().join("cwr.db");
+        let json_db = temp_dir.path().join("json.db");
+        let (_, cwr_count, _) =
+            process_cwr_to_sqlite_with_options(cwr_path, cwr_db.to_str().unwrap(), &options).unwrap();
+        let (_, json_count, _) =
+            process_json_to_sqlite_with_options(json_path, json_db.to_str().unwrap(), &options).unwrap();
+        assert_eq!(json_count, cwr_count);
+
+        let rows = |db: &std::path::Path, sql: &str| -> Vec<String> {
+            let conn = rusqlite::Connection::open(db).unwrap();
+            let mut stmt = conn.prepare(sql).unwrap();
+            let columns = stmt.column_count();
+            stmt.query_map([], |row| {
+                Ok((0..columns).map(|i| format!("{:?}", row.get_ref(i).unwrap())).collect::<Vec<_>>().join("|"))
+            })
+            .unwrap()
+            .collect::<std::result::Result<_, _>>()
+            .unwrap()
+        };
+        for sql in [
+            "SELECT * FROM cwr_hdr",
+            "SELECT * FROM cwr_grh",
+            "SELECT * FROM cwr_nwr",
+            "SELECT * FROM file_line",
+            "SELECT * FROM error",
+        ] {
+            assert_eq!(rows(&json_db, sql), rows(&cwr_db, sql), "{}", sql);
+        }
+        assert_eq!(rows(&json_db, "SELECT * FROM error WHERE level IS NULL").len(), 1);
+        assert!(!rows(&json_db, "SELECT * FROM error WHERE field_name = 'transaction_type'").is_empty());
+    }
+
+    #[test]
+    fn test_duplicate_import_policies() {
+        let temp_dir = tempdir().unwrap();
+        let cwr_file_path = temp_dir.path().join("test.V21");
+        let db_file_path = temp_dir.path().join("test.db");
+        std::fs::write(
+            &cwr_file_path,
+            "HDRPB285606836WARNER CHAPPELL MUSIC PUBLISHING LTD         01.102022122112541120221221\r\nGRHNWR0000102.100000000000  \r\n",
+        )
+        .unwrap();
+        let cwr_path = cwr_file_path.to_str().unwrap();
+        let db_path = db_file_path.to_str().unwrap();
+        let import = |on_duplicate| {
+            let options = ImportOptions { on_duplicate, ..Default::default() };
+            process_cwr_to_sqlite_with_options(cwr_path, db_path, &options).unwrap()
+        };
+        let file_rows = || -> Vec<(i64, i64)> {
+            let conn = rusqlite::Connection::open(db_path).unwrap();
+            let mut stmt = conn.prepare("SELECT file_id, import_version FROM file ORDER BY file_id").unwrap();
+            stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+                .unwrap()
+                .collect::<std::result::Result<_, _>>()
+                .unwrap()
+        };
+
+        let (first_id, first_count, _) = import(DuplicatePolicy::Skip);
+        assert!(first_count > 0);
+
+        let (skipped_id, skipped_count, _) = import(DuplicatePolicy::Skip);
+        assert_eq!((skipped_id, skipped_count), (first_id, 0));
+        assert_eq!(file_rows(), vec![(first_id, 1)]);
+
+        let (second_id, _, _) = import(DuplicatePolicy::NewVersion);
+        assert_eq!(file_rows(), vec![(first_id, 1), (second_id, 2)]);
+
+        let (replaced_id, _, _) = import(DuplicatePolicy::Replace);
+        assert_eq!(file_rows(), vec![(replaced_id, 3)]);
+        let conn = rusqlite::Connection::open(db_path).unwrap();
+        let stale_rows: i64 = conn
+            .query_row(
+                "SELECT (SELECT count(*) FROM cwr_hdr WHERE file_id != ?1) + (SELECT count(*) FROM file_line WHERE file_id != ?1)",
+                [replaced_id],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(stale_rows, 0);
+
+        // A gzipped copy has the same content, so it is a duplicate too
+        let gz_file_path = temp_dir.path().join("test.V21.gz");
+        let mut encoder = flate2::write::GzEncoder::new(File::create(&gz_file_path).unwrap(), Default::default());
+        encoder.write_all(&std::fs::read(&cwr_file_path).unwrap()).unwrap();
+        encoder.finish().unwrap();
+        let gz_path = gz_file_path.to_str().unwrap();
+        assert_eq!(file_content_hash(gz_path).unwrap(), file_content_hash(cwr_path).unwrap());
+        assert_eq!(content_hash(&std::fs::read(gz_path).unwrap()).unwrap(), file_content_hash(cwr_path).unwrap());
+        let options = ImportOptions { on_duplicate: DuplicatePolicy::Skip, ..Default::default() };
+        assert_eq!(process_cwr_to_sqlite_with_options(gz_path, db_path, &options).unwrap().1, 0);
+    }
+
+    #[test]
+    fn test_setup_database_adds_original_line_column() {
+        let temp_dir = tempdir().unwrap();
+        let db_file_path = temp_dir.path().join("old.db");
+        let db_path = db_file_path.to_str().unwrap();
+        setup_database(db_path).unwrap();
+        let conn = rusqlite::Connection::open(db_path).unwrap();
+        conn.execute_batch("ALTER TABLE file_line DROP COLUMN original_line").unwrap();
+        drop(conn);
+
+        setup_database(db_path).unwrap();
+        let conn = rusqlite::Connection::open(db_path).unwrap();
+        let columns: i64 = conn
+            .query_row("SELECT count(*) FROM pragma_table_info('file_line') WHERE name = 'original_line'", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(columns, 1);
+    }
+
+    #[test]
+    fn test_setup_database_checks_schema_version() {
+        let temp_dir = tempdir().unwrap();
+        let db_file_path = temp_dir.path().join("versioned.db");
+        let db_path = db_file_path.to_str().unwrap();
+        setup_database(db_path).unwrap();
+        let conn = rusqlite::Connection::open(db_path).unwrap();
+        assert_eq!(check_schema_version(&conn).unwrap(), SCHEMA_VERSION);
+
+        conn.pragma_update(None, "user_version", SCHEMA_VERSION + 1).unwrap();
+        drop(conn);
+        let error = setup_database(db_path).unwrap_err();
+        assert!(matches!(error, CwrDbError::IncompatibleSchema { found, .. } if found == SCHEMA_VERSION + 1));
+        assert!(error.to_string().contains("newer than the supported version"), "{}", error);
+        assert!(CwrDatabase::open(db_path).is_err());
+
+        let cwr_path = temp_dir.path().join("input.cwr");
+        std::fs::write(&cwr_path, "").unwrap();
+        assert!(SqliteHandler::new(cwr_path.to_str().unwrap(), db_path).is_err());
+    }
+
+    #[test]
+    fn test_work_views_link_records_to_their_work() {
+        let temp_dir = tempdir().unwrap();
+        let db_file_path = temp_dir.path().join("views.db");
+        let db_path = db_file_path.to_str().unwrap();
+        setup_database(db_path).unwrap();
+        let conn = rusqlite::Connection::open(db_path).unwrap();
+
+        let work = |submitter_work_num: &str, record_type: &str| {
+            conn.execute(
+                "INSERT INTO cwr_nwr (file_id, record_type, transaction_sequence_num, record_sequence_num, work_title, \
+                 submitter_work_num, musical_work_distribution_category, recorded_indicator, version_type) \
+                 VALUES (1, ?2, '0', '0', 'TITLE', ?1, 'SER', 'U', 'ORI')",
+                [submitter_work_num, record_type],
+            )
+            .unwrap();
+            conn.last_insert_rowid()
+        };
+        let writer = |record_type: &str| {
+            conn.execute(
+                "INSERT INTO cwr_swr (file_id, record_type, transaction_sequence_num, record_sequence_num, \
+                 writer_last_name) VALUES (1, ?1, '0', '1', 'WRITER')",
+                [record_type],
+            )
+            .unwrap();
+            conn.last_insert_rowid()
+        };
+        let line = |line_number: i64, record_type: &str, record_id: i64| {
+            conn.execute(
+                "INSERT INTO file_line (file_id, line_number, record_type, record_id) VALUES (1, ?1, ?2, ?3)",
+                rusqlite::params![line_number, record_type, record_id],
+            )
+            .unwrap();
+        };
+
+        line(1, "HDR", 1);
+        line(2, "GRH", 1);
+        line(3, "NWR", work("A", "NWR"));
+        line(4, "SWR", writer("SWR"));
+        conn.execute(
+            "INSERT INTO cwr_spu (file_id, record_type, transaction_sequence_num, record_sequence_num, \
+             publisher_sequence_num, publisher_name) VALUES (1, 'SPU', '0', '2', '01', 'PUBLISHER')",
+            [],
+        )
+        .unwrap();
+        line(5, "SPU", conn.last_insert_rowid());
+        conn.execute(
+            "INSERT INTO cwr_spt (file_id, record_type, transaction_sequence_num, record_sequence_num, \
+             interested_party_num, inclusion_exclusion_indicator, tis_numeric_code) \
+             VALUES (1, 'SPT', '0', '3', 'P1', 'I', '2136')",
+            [],
+        )
+        .unwrap();
+        line(6, "SPT", conn.last_insert_rowid());
+        line(7, "REV", work("B", "REV"));
+        line(8, "OWR", writer("OWR"));
+        line(9, "GRT", 1);
+        line(10, "GRH", 2);
+        line(11, "SWR", writer("SWR"));
+
+        let query = |sql: &str| -> Vec<(String, String)> {
+            let mut stmt = conn.prepare(sql).unwrap();
+            stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?))).unwrap().map(|row| row.unwrap()).collect()
+        };
+        assert_eq!(
+            query("SELECT submitter_work_num, record_type FROM v_work_writers ORDER BY line_number"),
+            [("A".to_string(), "SWR".to_string()), ("B".to_string(), "OWR".to_string())]
+        );
+        assert_eq!(
+            query("SELECT submitter_work_num, publisher_name FROM v_work_publishers"),
+            [("A".to_string(), "PUBLISHER".to_string())]
+        );
+        assert_eq!(
+            query("SELECT submitter_work_num, party_type || ' ' || tis_numeric_code FROM v_work_territories"),
+            [("A".to_string(), "publisher 2136".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_generated_records_round_trip() {
+        use allegro_cwr::domain_types::{CharacterSet, CwrVersion};
+
+        let temp_dir = tempdir().unwrap();
+        let db_file_path = temp_dir.path().join("generated.db");
+        let db_path = db_file_path.to_str().unwrap();
+        // Records are not read from the input, but the handler records its content hash
+        let input_path = temp_dir.path().join("generated.V22");
+        std::fs::write(&input_path, "").unwrap();
+        let input = input_path.to_str().unwrap();
+        let records: Vec<allegro_cwr::CwrRegistry> =
+            (0..300).filter_map(|seed| allegro_cwr::generate::from_seed(seed).ok()).collect();
+        let parsed = records.iter().enumerate().map(|(index, record)| {
+            Ok(allegro_cwr::ParsedRecord {
+                line_number: index + 1,
+                record: record.clone(),
+                context: allegro_cwr::ParsingContext { cwr_version: 2.2, file_id: 0, character_set: None },
+                warnings: Vec::new(),
+                raw_line: None,
+                raw_bytes: None,
+            })
+        });
+        let handler = SqliteHandler::new(input, db_path).unwrap();
+        allegro_cwr::process_records_with_handler(input, parsed, handler).unwrap();
+
+        let conn = rusqlite::Connection::open(db_path).unwrap();
+        let mut stmt = conn.prepare("SELECT record_type, record_id FROM file_line ORDER BY line_number").unwrap();
+        let lines: Vec<(String, i64)> =
+            stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?))).unwrap().map(|row| row.unwrap()).collect();
+        assert_eq!(lines.len(), records.len());
+        let line =
+            |record: &allegro_cwr::CwrRegistry| record.to_cwr_record_bytes(&CwrVersion(2.2), &CharacterSet::ASCII);
+        for (record, (record_type, record_id)) in records.iter().zip(lines) {
+            let read = query_record_by_type(&conn, &record_type, record_id).unwrap().expect(&record_type);
+            assert_eq!(line(&read), line(record), "{}", record_type);
+        }
+    }
 }
@@ -0,0 +1,447 @@
+//! Work-centric ingestion: works, parties, shares and territories instead of one table per record type
+
+use crate::domain_conversions::{CwrToSqlInt, CwrToSqlString, opt_domain_to_int, opt_domain_to_string};
+use crate::error::CwrDbError;
+use allegro_cwr::CwrRegistry;
+use allegro_cwr::domain_types::{InclusionExclusionIndicator, OwnershipShare, SocietyCode};
+use rusqlite::{Connection, params};
+use std::str::FromStr;
+
+const NORMALIZED_SCHEMA_SQL: &str = include_str!("normalized.sql");
+
+/// Which tables [`crate::SqliteHandler`] fills
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SchemaKind {
+    /// One `cwr_*` table per record type plus `file_line`, for archiving files and exporting them back to CWR
+    #[default]
+    Raw,
+    /// `work`, `work_alternate_title`, `party`, `work_party`, `work_party_share` and `work_party_territory`,
+    /// for catalog management. Files imported this way cannot be exported back to CWR.
+    Normalized,
+}
+
+impl FromStr for SchemaKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "raw" => Ok(SchemaKind::Raw),
+            "normalized" => Ok(SchemaKind::Normalized),
+            _ => Err(format!("Invalid schema '{}'. Valid schemas: raw, normalized", s)),
+        }
+    }
+}
+
+/// Creates the normalized tables if they are missing
+pub fn setup_normalized_schema(conn: &Connection) -> Result<(), CwrDbError> {
+    conn.execute_batch(NORMALIZED_SCHEMA_SQL)?;
+    Ok(())
+}
+
+/// Deletes the works of a file with their titles, parties' roles, shares and territories. Parties are kept, as
+/// other files' works may refer to them.
+pub(crate) fn delete_file_works(conn: &Connection, file_id: i64) -> Result<(), CwrDbError> {
+    let has_work_table: i64 =
+        conn.query_row("SELECT count(*) FROM sqlite_master WHERE type = 'table' AND name = 'work'", [], |row| {
+            row.get(0)
+        })?;
+    if has_work_table == 0 {
+        return Ok(());
+    }
+    const WORK_PARTIES: &str =
+        "SELECT work_party_id FROM work_party WHERE work_id IN (SELECT work_id FROM work WHERE file_id = ?1)";
+    conn.execute(&format!("DELETE FROM work_party_share WHERE work_party_id IN ({})", WORK_PARTIES), [file_id])?;
+    conn.execute(&format!("DELETE FROM work_party_territory WHERE work_party_id IN ({})", WORK_PARTIES), [file_id])?;
+    conn.execute("DELETE FROM work_party WHERE work_id IN (SELECT work_id FROM work WHERE file_id = ?1)", [file_id])?;
+    conn.execute(
+        "DELETE FROM work_alternate_title WHERE work_id IN (SELECT work_id FROM work WHERE file_id = ?1)",
+        [file_id],
+    )?;
+    conn.execute("DELETE FROM work WHERE file_id = ?1", [file_id])?;
+    Ok(())
+}
+
+/// Ingests records into the normalized tables, tracking the work the following records belong to
+#[derive(Debug, Default)]
+pub(crate) struct NormalizedWriter {
+    work_id: Option<i64>,
+    /// Work parties of the current work with their interested party number, for linking territories
+    work_parties: Vec<(Option<String>, i64)>,
+}
+
+impl NormalizedWriter {
+    pub(crate) fn insert(
+        &mut self, conn: &Connection, file_id: i64, line_number: usize, record: &CwrRegistry,
+    ) -> Result<(), CwrDbError> {
+        match record {
+            CwrRegistry::Nwr(nwr) => {
+                conn.prepare_cached(
+                    "INSERT INTO work (file_id, line_number, transaction_type, submitter_work_num, title, \
+                     language_code, iswc, musical_work_distribution_category, version_type, duration) \
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                )?
+                .execute(params![
+                    file_id,
+                    line_number as i64,
+                    nwr.record_type.as_ref(),
+                    nwr.submitter_work_num.trim_end(),
+                    nwr.work_title.trim_end(),
+                    opt_domain_to_string(&nwr.language_code),
+                    nwr.iswc.as_deref(),
+                    nwr.musical_work_distribution_category.to_sql_string(),
+                    nwr.version_type.to_sql_string(),
+                    opt_domain_to_int(&nwr.duration),
+                ])?;
+                self.work_id = Some(conn.last_insert_rowid());
+                self.work_parties.clear();
+            }
+            CwrRegistry::Grh(_) | CwrRegistry::Grt(_) | CwrRegistry::Agr(_) | CwrRegistry::Ack(_) => {
+                self.work_id = None;
+                self.work_parties.clear();
+            }
+            CwrRegistry::Alt(alt) => {
+                if let Some(work_id) = self.work_id {
+                    conn.prepare_cached(
+                        "INSERT INTO work_alternate_title (work_id, title, title_type, language_code) \
+                         VALUES (?1, ?2, ?3, ?4)",
+                    )?
+                    .execute(params![
+                        work_id,
+                        alt.alternate_title.trim_end(),
+                        alt.title_type.to_sql_string(),
+                        opt_domain_to_string(&alt.language_code),
+                    ])?;
+                }
+            }
+            CwrRegistry::Spu(spu) => {
+                let Some(work_id) = self.work_id else { return Ok(()) };
+                let party_id = upsert_party(
+                    conn,
+                    "publisher",
+                    spu.interested_party_num.as_deref(),
+                    spu.publisher_name.as_deref(),
+                    None,
+                    opt_domain_to_string(&spu.publisher_ipi_name_num).as_deref(),
+                    opt_domain_to_string(&spu.publisher_ipi_base_number).as_deref(),
+                )?;
+                let work_party_id = self.insert_work_party(
+                    conn,
+                    work_id,
+                    party_id,
+                    &spu.record_type,
+                    opt_domain_to_string(&spu.publisher_type).as_deref(),
+                    Some(spu.publisher_sequence_num.to_sql_int()),
+                    spu.interested_party_num.as_deref(),
+                )?;
+                insert_shares(
+                    conn,
+                    work_party_id,
+                    [
+                        ("PR", &spu.pr_affiliation_society_num, &spu.pr_ownership_share),
+                        ("MR", &spu.mr_society, &spu.mr_ownership_share),
+                        ("SR", &spu.sr_society, &spu.sr_ownership_share),
+                    ],
+                )?;
+            }
+            CwrRegistry::Swr(swr) => {
+                let Some(work_id) = self.work_id else { return Ok(()) };
+                let party_id = upsert_party(
+                    conn,
+                    "writer",
+                    swr.interested_party_num.as_deref(),
+                    swr.writer_last_name.as_deref(),
+                    swr.writer_first_name.as_deref(),
+                    opt_domain_to_string(&swr.writer_ipi_name_num).as_deref(),
+                    opt_domain_to_string(&swr.writer_ipi_base_number).as_deref(),
+                )?;
+                let work_party_id = self.insert_work_party(
+                    conn,
+                    work_id,
+                    party_id,
+                    &swr.record_type,
+                    opt_domain_to_string(&swr.writer_designation_code).as_deref(),
+                    None,
+                    swr.interested_party_num.as_deref(),
+                )?;
+                insert_shares(
+                    conn,
+                    work_party_id,
+                    [
+                        ("PR", &swr.pr_affiliation_society_num, &swr.pr_ownership_share),
+                        ("MR", &swr.mr_society, &swr.mr_ownership_share),
+                        ("SR", &swr.sr_society, &swr.sr_ownership_share),
+                    ],
+                )?;
+            }
+            CwrRegistry::Spt(spt) => {
+                if let Some(work_party_id) = self.work_party_for(Some(&spt.interested_party_num)) {
+                    insert_territory(
+                        conn,
+                        work_party_id,
+                        &spt.tis_numeric_code.as_str(),
+                        &spt.inclusion_exclusion_indicator,
+                        [&spt.pr_collection_share, &spt.mr_collection_share, &spt.sr_collection_share],
+                    )?;
+                }
+            }
+            CwrRegistry::Swt(swt) => {
+                if let Some(work_party_id) = self.work_party_for(swt.interested_party_num.as_ref()) {
+                    insert_territory(
+                        conn,
+                        work_party_id,
+                        &swt.tis_numeric_code.as_str(),
+                        &swt.inclusion_exclusion_indicator,
+                        [&swt.pr_collection_share, &swt.mr_collection_share, &swt.sr_collection_share],
+                    )?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn insert_work_party(
+        &mut self, conn: &Connection, work_id: i64, party_id: i64, record_type: &str, role: Option<&str>,
+        publisher_sequence_num: Option<i64>, interested_party_num: Option<&str>,
+    ) -> Result<i64, CwrDbError> {
+        let controlled = matches!(record_type, "SPU" | "SWR");
+        conn.prepare_cached(
+            "INSERT INTO work_party (work_id, party_id, record_type, controlled, role, publisher_sequence_num) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        )?
+        .execute(params![work_id, party_id, record_type, controlled, role, publisher_sequence_num])?;
+        let work_party_id = conn.last_insert_rowid();
+        self.work_parties.push((interested_party_num.map(|num| num.trim().to_string()), work_party_id));
+        Ok(work_party_id)
+    }
+
+    /// The latest work party of the current work with this interested party number, or the latest one if the
+    /// territory record has none
+    fn work_party_for(&self, interested_party_num: Option<&String>) -> Option<i64> {
+        match interested_party_num.map(|num| num.trim()) {
+            Some(num) if !num.is_empty() => self
+                .work_parties
+                .iter()
+                .rev()
+                .find(|(party_num, _)| party_num.as_deref() == Some(num))
+                .map(|(_, work_party_id)| *work_party_id),
+            _ => self.work_parties.last().map(|(_, work_party_id)| *work_party_id),
+        }
+    }
+}
+
+fn upsert_party(
+    conn: &Connection, party_type: &str, interested_party_num: Option<&str>, name: Option<&str>,
+    first_name: Option<&str>, ipi_name_num: Option<&str>, ipi_base_number: Option<&str>,
+) -> Result<i64, CwrDbError> {
+    let interested_party_num = interested_party_num.map(str::trim).filter(|num| !num.is_empty());
+    let party_id = conn
+        .prepare_cached(
+            "INSERT INTO party (party_type, interested_party_num, name, first_name, ipi_name_num, ipi_base_number) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6) \
+             ON CONFLICT (party_type, interested_party_num) DO UPDATE SET \
+                 name = coalesce(excluded.name, party.name), \
+                 first_name = coalesce(excluded.first_name, party.first_name), \
+                 ipi_name_num = coalesce(excluded.ipi_name_num, party.ipi_name_num), \
+                 ipi_base_number = coalesce(excluded.ipi_base_number, party.ipi_base_number) \
+             RETURNING party_id",
+        )?
+        .query_row(
+            params![
+                party_type,
+                interested_party_num,
+                name.map(str::trim_end),
+                first_name.map(str::trim_end),
+                ipi_name_num,
+                ipi_base_number
+            ],
+            |row| row.get(0),
+        )?;
+    Ok(party_id)
+}
+
+fn insert_shares(
+    conn: &Connection, work_party_id: i64, shares: [(&str, &Option<SocietyCode>, &Option<OwnershipShare>); 3],
+) -> Result<(), CwrDbError> {
+    let mut stmt = conn.prepare_cached(
+        "INSERT INTO work_party_share (work_party_id, right_type, society, ownership_share) VALUES (?1, ?2, ?3, ?4)",
+    )?;
+    for (right_type, society, share) in shares {
+        if society.is_none() && share.is_none() {
+            continue;
+        }
+        stmt.execute(params![
+            work_party_id,
+            right_type,
+            opt_domain_to_string(society),
+            share.as_ref().map(|share| share.as_percentage() as f64)
+        ])?;
+    }
+    Ok(())
+}
+
+fn insert_territory(
+    conn: &Connection, work_party_id: i64, tis_numeric_code: &str, indicator: &InclusionExclusionIndicator,
+    collection_shares: [&Option<OwnershipShare>; 3],
+) -> Result<(), CwrDbError> {
+    let [pr, mr, sr] = collection_shares.map(|share| share.as_ref().map(|share| share.as_percentage() as f64));
+    conn.prepare_cached(
+        "INSERT INTO work_party_territory \
+         (work_party_id, tis_numeric_code, included, pr_collection_share, mr_collection_share, sr_collection_share) \
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+    )?
+    .execute(params![
+        work_party_id,
+        tis_numeric_code,
+        *indicator == InclusionExclusionIndicator::Included,
+        pr,
+        mr,
+        sr
+    ])?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ImportOptions, delete_file, process_cwr_to_sqlite_with_options};
+    use allegro_cwr::records::{AltRecord, FieldSpec, NwrRecord, SptRecord, SpuRecord, SwrRecord};
+    use tempfile::tempdir;
+
+    fn line(specs: &[FieldSpec], values: &[(&str, &str)]) -> String {
+        let len = specs.iter().map(|spec| spec.start + spec.len).max().unwrap();
+        let mut line = vec![b' '; len];
+        for (name, value) in values {
+            let spec = specs.iter().find(|spec| spec.name == *name).unwrap();
+            line[spec.start..spec.start + value.len()].copy_from_slice(value.as_bytes());
+        }
+        String::from_utf8(line).unwrap()
+    }
+
+    fn work(transaction: &str, submitter_work_num: &str, title: &str) -> Vec<String> {
+        let header = [("transaction_sequence_num", transaction), ("record_sequence_num", "00000000")];
+        let record = |record_type: &'static str, sequence: &'static str| {
+            [("record_type", record_type), ("transaction_sequence_num", transaction), ("record_sequence_num", sequence)]
+        };
+        vec![
+            line(
+                NwrRecord::FIELD_SPECS,
+                &[
+                    &[("record_type", "NWR")][..],
+                    &header,
+                    &[
+                        ("work_title", title),
+                        ("submitter_work_num", submitter_work_num),
+                        ("musical_work_distribution_category", "SER"),
+                        ("recorded_indicator", "U"),
+                        ("version_type", "ORI"),
+                    ],
+                ]
+                .concat(),
+            ),
+            line(
+                SpuRecord::FIELD_SPECS,
+                &[
+                    &record("SPU", "00000001")[..],
+                    &[
+                        ("publisher_sequence_num", "01"),
+                        ("interested_party_num", "P1"),
+                        ("publisher_name", "MUSIC PUBLISHING"),
+                        ("publisher_type", "E"),
+                        ("pr_affiliation_society_num", "052"),
+                        ("pr_ownership_share", "05000"),
+                    ],
+                ]
+                .concat(),
+            ),
+            line(
+                SptRecord::FIELD_SPECS,
+                &[
+                    &record("SPT", "00000002")[..],
+                    &[
+                        ("interested_party_num", "P1"),
+                        ("pr_collection_share", "05000"),
+                        ("inclusion_exclusion_indicator", "I"),
+                        ("tis_numeric_code", "2136"),
+                    ],
+                ]
+                .concat(),
+            ),
+            line(
+                SwrRecord::FIELD_SPECS,
+                &[
+                    &record("SWR", "00000003")[..],
+                    &[
+                        ("interested_party_num", "W1"),
+                        ("writer_last_name", "SMITH"),
+                        ("writer_first_name", "JANE"),
+                        ("writer_designation_code", "CA"),
+                        ("pr_affiliation_society_num", "052"),
+                        ("pr_ownership_share", "05000"),
+                    ],
+                ]
+                .concat(),
+            ),
+            line(
+                AltRecord::FIELD_SPECS,
+                &[&record("ALT", "00000004")[..], &[("alternate_title", "OTHER TITLE"), ("title_type", "AT")]].concat(),
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_normalized_import() {
+        let temp_dir = tempdir().unwrap();
+        let cwr_file_path = temp_dir.path().join("test.V21");
+        let db_file_path = temp_dir.path().join("test.db");
+        let mut lines = vec![
+            "HDRPB285606836WARNER CHAPPELL MUSIC PUBLISHING LTD         01.102022122112541120221221".to_string(),
+            "GRHNWR0000102.100000000000  ".to_string(),
+        ];
+        lines.extend(work("00000000", "SW1", "FIRST SONG"));
+        lines.extend(work("00000001", "SW2", "SECOND SONG"));
+        std::fs::write(&cwr_file_path, lines.join("\n") + "\n").unwrap();
+        let cwr_path = cwr_file_path.to_str().unwrap();
+        let db_path = db_file_path.to_str().unwrap();
+
+        let options = ImportOptions { schema: SchemaKind::Normalized, ..Default::default() };
+        let (file_id, _, _) = process_cwr_to_sqlite_with_options(cwr_path, db_path, &options).unwrap();
+
+        let conn = Connection::open(db_path).unwrap();
+        let count = |sql: &str| -> i64 { conn.query_row(sql, [], |row| row.get(0)).unwrap() };
+        assert_eq!(count("SELECT count(*) FROM work"), 2);
+        assert_eq!(count("SELECT count(*) FROM party"), 2);
+        assert_eq!(count("SELECT count(*) FROM work_party"), 4);
+        assert_eq!(count("SELECT count(*) FROM work_alternate_title"), 2);
+        assert_eq!(count("SELECT count(*) FROM cwr_nwr"), 0);
+
+        let (title, name, share, territory): (String, String, f64, String) = conn
+            .query_row(
+                "SELECT w.title, p.name, s.ownership_share, t.tis_numeric_code
+                 FROM work w
+                 JOIN work_party wp ON wp.work_id = w.work_id
+                 JOIN party p ON p.party_id = wp.party_id
+                 JOIN work_party_share s ON s.work_party_id = wp.work_party_id AND s.right_type = 'PR'
+                 JOIN work_party_territory t ON t.work_party_id = wp.work_party_id
+                 WHERE w.submitter_work_num = 'SW2' AND p.party_type = 'publisher'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+            )
+            .unwrap();
+        assert_eq!(
+            (title.as_str(), name.as_str(), share, territory.as_str()),
+            ("SECOND SONG", "MUSIC PUBLISHING", 50.0, "2136")
+        );
+
+        assert!(delete_file(db_path, file_id).unwrap());
+        assert_eq!(count("SELECT count(*) FROM work") + count("SELECT count(*) FROM work_party_share"), 0);
+        assert_eq!(count("SELECT count(*) FROM party"), 2);
+    }
+
+    #[test]
+    fn test_schema_kind_parse() {
+        assert_eq!("Normalized".parse::<SchemaKind>(), Ok(SchemaKind::Normalized));
+        assert!("star".parse::<SchemaKind>().is_err());
+    }
+}
@@ -0,0 +1,82 @@
+-- noinspection SqlNoDataSourceInspectionForFile
+
+-- Work-centric schema for catalog management, filled instead of the cwr_* record tables by SchemaKind::Normalized
+
+-- Work registered by an NWR, REV, ISW or EXC transaction
+CREATE TABLE IF NOT EXISTS work (
+    work_id INTEGER PRIMARY KEY,
+    file_id INTEGER NOT NULL REFERENCES file(file_id),
+    line_number INTEGER NOT NULL,
+    transaction_type VARCHAR(3) NOT NULL,
+    submitter_work_num VARCHAR(14) NOT NULL,
+    title VARCHAR(60) NOT NULL,
+    language_code VARCHAR(2),
+    iswc VARCHAR(11),
+    musical_work_distribution_category VARCHAR(3) NOT NULL,
+    version_type VARCHAR(3) NOT NULL,
+    duration INTEGER
+);
+
+CREATE INDEX IF NOT EXISTS idx_work_file ON work(file_id);
+CREATE INDEX IF NOT EXISTS idx_work_submitter_num ON work(submitter_work_num);
+CREATE INDEX IF NOT EXISTS idx_work_iswc ON work(iswc);
+
+-- Alternate titles (ALT)
+CREATE TABLE IF NOT EXISTS work_alternate_title (
+    work_alternate_title_id INTEGER PRIMARY KEY,
+    work_id INTEGER NOT NULL REFERENCES work(work_id),
+    title VARCHAR(60) NOT NULL,
+    title_type VARCHAR(2) NOT NULL,
+    language_code VARCHAR(2)
+);
+
+-- Publisher or writer, shared by every work they appear on when they have an interested party number
+CREATE TABLE IF NOT EXISTS party (
+    party_id INTEGER PRIMARY KEY,
+    party_type VARCHAR(9) NOT NULL CHECK (party_type IN ('publisher', 'writer')),
+    interested_party_num VARCHAR(9),
+    name VARCHAR(45),
+    first_name VARCHAR(30),
+    ipi_name_num VARCHAR(11),
+    ipi_base_number VARCHAR(13)
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_party_ip_num ON party(party_type, interested_party_num);
+
+-- A party's part in a work (SPU/OPU or SWR/OWR)
+CREATE TABLE IF NOT EXISTS work_party (
+    work_party_id INTEGER PRIMARY KEY,
+    work_id INTEGER NOT NULL REFERENCES work(work_id),
+    party_id INTEGER NOT NULL REFERENCES party(party_id),
+    record_type VARCHAR(3) NOT NULL,
+    controlled INTEGER NOT NULL,
+    role VARCHAR(2),
+    publisher_sequence_num INTEGER
+);
+
+CREATE INDEX IF NOT EXISTS idx_work_party_work ON work_party(work_id);
+CREATE INDEX IF NOT EXISTS idx_work_party_party ON work_party(party_id);
+
+-- Ownership share of a work party per right (PR, MR, SR), in percent
+CREATE TABLE IF NOT EXISTS work_party_share (
+    work_party_share_id INTEGER PRIMARY KEY,
+    work_party_id INTEGER NOT NULL REFERENCES work_party(work_party_id),
+    right_type VARCHAR(2) NOT NULL CHECK (right_type IN ('PR', 'MR', 'SR')),
+    society VARCHAR(3),
+    ownership_share REAL
+);
+
+CREATE INDEX IF NOT EXISTS idx_work_party_share ON work_party_share(work_party_id);
+
+-- Territory of control of a work party (SPT/OPT or SWT/OWT), collection shares in percent
+CREATE TABLE IF NOT EXISTS work_party_territory (
+    work_party_territory_id INTEGER PRIMARY KEY,
+    work_party_id INTEGER NOT NULL REFERENCES work_party(work_party_id),
+    tis_numeric_code VARCHAR(4) NOT NULL,
+    included INTEGER NOT NULL,
+    pr_collection_share REAL,
+    mr_collection_share REAL,
+    sr_collection_share REAL
+);
+
+CREATE INDEX IF NOT EXISTS idx_work_party_territory ON work_party_territory(work_party_id);
@@ -1,6 +1,60 @@
 use crate::{error::CwrDbError, statements::PreparedStatements};
-use rusqlite::{Connection, Statement, Transaction, params};
+use allegro_cwr::parsing::CwrWarning;
+use rusqlite::{Connection, OptionalExtension, Statement, Transaction, params};
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
+use std::io::Read;
+use tracing::{info, warn};
+
+/// What to do when a file with the same content has already been imported
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DuplicatePolicy {
+    /// Leave the database unchanged and fail with [`CwrDbError::DuplicateFile`]
+    #[default]
+    Skip,
+    /// Delete the earlier imports of the content, then import again
+    Replace,
+    /// Import again alongside the earlier imports, with the next `import_version`
+    NewVersion,
+}
+
+impl std::str::FromStr for DuplicatePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "skip" => Ok(DuplicatePolicy::Skip),
+            "replace" => Ok(DuplicatePolicy::Replace),
+            "new-version" => Ok(DuplicatePolicy::NewVersion),
+            _ => Err(format!("Invalid duplicate policy '{}'. Valid values are: skip, replace, new-version", s)),
+        }
+    }
+}
+
+/// SHA-256 of a file's content, as lowercase hex. Gzipped files are hashed decompressed, so a file has the same
+/// hash whether it is imported compressed or not.
+pub fn file_content_hash(file_path: &str) -> Result<String, CwrDbError> {
+    let mut reader = allegro_cwr::compression::open_input(file_path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 64 * 1024];
+    loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(hex_digest(hasher))
+}
+
+/// SHA-256 of content held in memory (e.g. read from stdin), as [`file_content_hash`] computes it for a file
+pub fn content_hash(content: &[u8]) -> Result<String, CwrDbError> {
+    Ok(hex_digest(Sha256::new_with_prefix(allegro_cwr::compression::decompress(content)?)))
+}
+
+fn hex_digest(hasher: Sha256) -> String {
+    hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect()
+}
 
 /// High-level interface for inserting CWR records
 pub struct CwrRecordInserter<'conn> {
@@ -28,19 +82,96 @@ pub fn log_error(
     Ok(())
 }
 
-/// Inserts a record into the 'file' table and returns the file_id
+/// Inserts a warning on a parsed record into the 'error' table, keeping its level, field and source text in their
+/// own columns. The description keeps the `WARNING [<record type>]: ` prefix that reports group on.
+pub fn log_warning(
+    warning_stmt: &mut Statement, file_id: i64, line_number: usize, record_type: &str, warning: &CwrWarning,
+) -> Result<(), CwrDbError> {
+    let field_name = Some(warning.field_name).filter(|name| !name.is_empty());
+    let source = Some(warning.source_str.as_ref()).filter(|source| !source.is_empty());
+    warning_stmt.execute(params![
+        file_id,
+        line_number as i64,
+        format!("WARNING [{}]: {}", record_type, warning),
+        record_type,
+        warning.level.as_str(),
+        field_name,
+        source
+    ])?;
+    Ok(())
+}
+
+/// Inserts a record into the 'file' table and returns the file_id. The file's content hash (see
+/// [`file_content_hash`]) is compared with earlier imports and `duplicate_policy` decides what happens when it matches
+/// one.
 pub fn insert_file_record(
-    tx: &Transaction, file_insert_stmt: &mut Statement, file_path: &str,
+    tx: &Transaction, file_insert_stmt: &mut Statement, file_path: &str, content_hash: &str,
+    duplicate_policy: DuplicatePolicy,
 ) -> Result<i64, CwrDbError> {
-    file_insert_stmt.execute(params![file_path])?;
+    let latest: Option<(i64, i64)> = tx
+        .query_row(
+            "SELECT file_id, import_version FROM file WHERE content_hash = ?1 ORDER BY import_version DESC LIMIT 1",
+            [content_hash],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+
+    let import_version = match (latest, duplicate_policy) {
+        (None, _) => 1,
+        (Some((existing_file_id, _)), DuplicatePolicy::Skip) => {
+            return Err(CwrDbError::DuplicateFile { existing_file_id });
+        }
+        (Some((existing_file_id, version)), DuplicatePolicy::Replace) => {
+            let earlier: Vec<i64> = tx
+                .prepare("SELECT file_id FROM file WHERE content_hash = ?1")?
+                .query_map([content_hash], |row| row.get(0))?
+                .collect::<Result<_, _>>()?;
+            for file_id in earlier {
+                delete_file_rows(tx, file_id)?;
+            }
+            info!("Replacing earlier import of '{}' (file ID {})", file_path, existing_file_id);
+            version + 1
+        }
+        (Some((existing_file_id, version)), DuplicatePolicy::NewVersion) => {
+            warn!(
+                "'{}' has the same content as file ID {}; importing as version {}",
+                file_path,
+                existing_file_id,
+                version + 1
+            );
+            version + 1
+        }
+    };
+
+    file_insert_stmt.execute(params![file_path, content_hash, import_version])?;
     Ok(tx.last_insert_rowid())
 }
 
+/// Deletes a file and every row that belongs to it (records, file lines and errors)
+pub fn delete_file_rows(tx: &Transaction, file_id: i64) -> Result<(), CwrDbError> {
+    let record_tables: Vec<String> = tx
+        .prepare("SELECT name FROM sqlite_master WHERE type='table' AND name LIKE 'cwr_%'")?
+        .query_map([], |row| row.get(0))?
+        .collect::<Result<_, _>>()?;
+    for table in record_tables.iter().map(String::as_str).chain(["file_line", "error"]) {
+        tx.execute(&format!("DELETE FROM {} WHERE file_id = ?1", table), [file_id])?;
+    }
+    crate::normalized::delete_file_works(tx, file_id)?;
+    tx.execute("DELETE FROM ack_status WHERE ack_file_id = ?1", [file_id])?;
+    tx.execute(
+        "UPDATE ack_status SET submission_file_id = NULL, cwr_nwr_id = NULL WHERE submission_file_id = ?1",
+        [file_id],
+    )?;
+    tx.execute("DELETE FROM file WHERE file_id = ?1", [file_id])?;
+    Ok(())
+}
+
 /// Inserts a record into the 'file_line' table using a prepared statement
 pub fn insert_file_line_record(
     file_stmt: &mut Statement, file_id: i64, line_number: usize, record_type: &str, record_id: i64,
+    original_line: Option<&str>,
 ) -> Result<(), CwrDbError> {
-    file_stmt.execute(params![file_id, line_number as i64, record_type, record_id])?;
+    file_stmt.execute(params![file_id, line_number as i64, record_type, record_id, original_line])?;
     Ok(())
 }
 
@@ -88,10 +219,11 @@ pub fn count_errors_by_record_type(db_path: &str) -> Result<HashMap<String, i32>
 
         // Extract record type from error description
         // Examples: "SWR Interested Party # is mandatory", "PWR missing or empty..."
-        if let Some(record_type) = description.split_whitespace().next() {
-            if record_type.len() == 3 && record_type.chars().all(|c| c.is_ascii_uppercase()) {
-                *error_counts.entry(record_type.to_string()).or_insert(0) += count;
-            }
+        if let Some(record_type) = description.split_whitespace().next()
+            && record_type.len() == 3
+            && record_type.chars().all(|c| c.is_ascii_uppercase())
+        {
+            *error_counts.entry(record_type.to_string()).or_insert(0) += count;
         }
     }
 
@@ -0,0 +1,193 @@
+//! Importing several CWR files into one database at the same time
+//!
+//! Each worker thread parses its files and writes them through its own connection. The database runs with a
+//! write-ahead log while the import is running, so parsing carries on in parallel while writes are committed one
+//! batch at a time.
+
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use tracing::info;
+
+use crate::{
+    ImportOptions, SchemaKind, SqliteHandler, correlate_acks, enable_title_search, import_with_handler, setup_database,
+    setup_normalized_schema,
+};
+
+/// How long a worker waits for another to commit before its import fails
+pub const BUSY_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// The outcome of importing one file with [`import_files`]
+#[derive(Debug)]
+pub struct FileImport {
+    pub input_filename: String,
+    /// The file's id, records imported and handler report, or why the import failed
+    pub result: Result<ImportedFile, String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportedFile {
+    pub file_id: i64,
+    pub record_count: usize,
+    pub report: String,
+}
+
+/// The per-file outcomes of [`import_files`], in the order the files were given
+#[derive(Debug, Default)]
+pub struct BatchImport {
+    pub files: Vec<FileImport>,
+}
+
+impl BatchImport {
+    /// The ids of the imported files, by input filename
+    pub fn file_ids(&self) -> Vec<(&str, i64)> {
+        self.imported().map(|(name, imported)| (name, imported.file_id)).collect()
+    }
+
+    pub fn total_records(&self) -> usize {
+        self.imported().map(|(_, imported)| imported.record_count).sum()
+    }
+
+    pub fn files_imported(&self) -> usize {
+        self.imported().count()
+    }
+
+    pub fn failed_files(&self) -> Vec<&str> {
+        self.files.iter().filter(|file| file.result.is_err()).map(|file| file.input_filename.as_str()).collect()
+    }
+
+    fn imported(&self) -> impl Iterator<Item = (&str, &ImportedFile)> {
+        self.files
+            .iter()
+            .filter_map(|file| file.result.as_ref().ok().map(|imported| (file.input_filename.as_str(), imported)))
+    }
+}
+
+/// Imports `input_filenames` into `db_filename` using up to `workers` threads. A file that fails does not stop the
+/// others; its error is kept in the returned [`BatchImport`]. ACK correlation, when asked for, runs once all files
+/// are in, so acknowledgements can match submissions imported in the same batch.
+pub fn import_files(
+    input_filenames: &[String], db_filename: &str, options: &ImportOptions, workers: usize,
+) -> crate::Result<BatchImport> {
+    setup_database(db_filename)?;
+    if options.title_search {
+        enable_title_search(db_filename)?;
+    }
+    if options.schema == SchemaKind::Normalized {
+        setup_normalized_schema(&rusqlite::Connection::open(db_filename)?)?;
+    }
+
+    let file_options = ImportOptions { correlate_acks: false, ..options.clone() };
+    let next_file = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<Result<ImportedFile, String>>>> =
+        Mutex::new(input_filenames.iter().map(|_| None).collect());
+    let workers = workers.clamp(1, input_filenames.len().max(1));
+    info!("Importing {} files into '{}' with {} workers", input_filenames.len(), db_filename, workers);
+
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| {
+                loop {
+                    let index = next_file.fetch_add(1, Ordering::Relaxed);
+                    let Some(input_filename) = input_filenames.get(index) else {
+                        break;
+                    };
+                    let result = import_file(input_filename, db_filename, &file_options);
+                    results.lock().unwrap_or_else(|e| e.into_inner())[index] = Some(result);
+                }
+            });
+        }
+    });
+
+    let mut conn = rusqlite::Connection::open(db_filename)?;
+    conn.pragma_update(None, "journal_mode", "DELETE")?;
+
+    let mut files = Vec::with_capacity(input_filenames.len());
+    let results = results.into_inner().unwrap_or_else(|e| e.into_inner());
+    for (input_filename, result) in input_filenames.iter().zip(results) {
+        let mut result = result.unwrap_or_else(|| Err("File was not imported".to_string()));
+        if options.correlate_acks
+            && let Ok(imported) = &mut result
+        {
+            let correlation = correlate_acks(&mut conn, imported.file_id)?;
+            imported.report.push_str(&format!(
+                "\n  ACKs matched to submissions: {}\n  ACKs without a submission: {}",
+                correlation.matched, correlation.unmatched
+            ));
+        }
+        files.push(FileImport { input_filename: input_filename.clone(), result });
+    }
+    Ok(BatchImport { files })
+}
+
+fn import_file(input_filename: &str, db_filename: &str, options: &ImportOptions) -> Result<ImportedFile, String> {
+    let handler = SqliteHandler::new_shared(input_filename, db_filename, 1000, options.on_duplicate, BUSY_TIMEOUT);
+    import_with_handler(input_filename, db_filename, options, handler)
+        .map(|(file_id, record_count, report)| ImportedFile { file_id, record_count, report })
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DuplicatePolicy;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_import_files_in_parallel() {
+        let temp_dir = tempdir().unwrap();
+        let db_file_path = temp_dir.path().join("batch.db");
+        let db_path = db_file_path.to_str().unwrap();
+        let mut input_filenames: Vec<String> = (0..8)
+            .map(|i| {
+                let path = temp_dir.path().join(format!("works{}.V21", i));
+                let mut content = String::from(
+                    "HDRPB285606836WARNER CHAPPELL MUSIC PUBLISHING LTD         01.102022122112541120221221\n\
+                     GRHNWR0000102.100000000000  \n",
+                );
+                for work in 0..=i {
+                    content.push_str(&format!(
+                        "NWR{:08}00000000{:<60}SW{:<14}SER        Y       ORI\n",
+                        work,
+                        format!("Song {} of file {}", work, i),
+                        format!("{}-{}", i, work)
+                    ));
+                }
+                std::fs::write(&path, content).unwrap();
+                path.to_string_lossy().to_string()
+            })
+            .collect();
+        input_filenames.push(temp_dir.path().join("missing.V21").to_string_lossy().to_string());
+        input_filenames.push(input_filenames[0].clone());
+
+        let options = ImportOptions { on_duplicate: DuplicatePolicy::Skip, ..Default::default() };
+        let batch = import_files(&input_filenames, db_path, &options, 4).unwrap();
+
+        assert_eq!(batch.files.len(), 10);
+        assert_eq!(batch.failed_files(), vec![input_filenames[8].as_str()]);
+        assert_eq!(batch.files_imported(), 9);
+        // HDR, GRH and one NWR per work; the repeated file is skipped
+        assert_eq!(batch.total_records(), (0..8).map(|i| i + 3).sum::<usize>());
+
+        let mut file_ids: Vec<i64> = batch.file_ids().iter().take(8).map(|(_, file_id)| *file_id).collect();
+        file_ids.sort();
+        file_ids.dedup();
+        assert_eq!(file_ids.len(), 8);
+        // Whichever copy of the first file is imported second finds the first and is skipped
+        let copies: Vec<&ImportedFile> = [0, 9].map(|i| batch.files[i].result.as_ref().unwrap()).to_vec();
+        assert_eq!(copies[0].file_id, copies[1].file_id);
+        assert_eq!(copies[0].record_count + copies[1].record_count, 3);
+
+        let conn = rusqlite::Connection::open(db_path).unwrap();
+        let (files, works): (i64, i64) = conn
+            .query_row("SELECT (SELECT count(*) FROM file), (SELECT count(*) FROM cwr_nwr)", [], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .unwrap();
+        assert_eq!((files, works), (8, 36));
+        let journal_mode: String = conn.query_row("PRAGMA journal_mode", [], |row| row.get(0)).unwrap();
+        assert_eq!(journal_mode, "delete");
+    }
+}
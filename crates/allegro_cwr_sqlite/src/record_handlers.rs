@@ -1,7 +1,7 @@
 use crate::domain_conversions::{CwrToSqlInt, CwrToSqlString, opt_domain_to_int, opt_domain_to_string};
 use crate::{PreparedStatements, insert_file_line_record, log_error};
 use allegro_cwr::records::*;
-use allegro_cwr::{CwrParseError, ParsingContext};
+use allegro_cwr::{CwrParseError, ParseErrorCode, ParsingContext, RecordParseError};
 use rusqlite::{Transaction, params};
 
 /// Helper function to reconstruct the full line from safe_slice calls
@@ -13,7 +13,7 @@ fn reconstruct_line_from_safe_slice(
     // Most CWR lines are under 1000 characters, so this should work for all record types
     match safe_slice(0, 2000) {
         Ok(Some(line)) => Ok(line),
-        Ok(None) => Err(CwrParseError::BadFormat("Empty line".to_string())),
+        Ok(None) => Err(RecordParseError::new(ParseErrorCode::EmptyLine, "Empty line").into()),
         Err(e) => Err(e),
     }
 }
@@ -35,7 +35,7 @@ where
         Ok(parse_result) => {
             // Log any warnings
             for warning in &parse_result.warnings {
-                log::warn!("Line {}: {}", line_number, warning);
+                tracing::warn!("Line {}: {}", line_number, warning);
             }
 
             // Insert using the provided function
@@ -44,7 +44,7 @@ where
 
             // Log to file_line table
             let record_type = line.get(0..3).unwrap_or("UNK");
-            insert_file_line_record(&mut stmts.file_stmt, context.file_id, line_number, record_type, record_id)?;
+            insert_file_line_record(&mut stmts.file_stmt, context.file_id, line_number, record_type, record_id, None)?;
 
             Ok(())
         }
@@ -261,7 +261,7 @@ pub fn parse_and_insert_nwr<'a>(
                 &opt_domain_to_string(&record.copyright_date).unwrap_or_default(),
                 record.copyright_number.as_deref().unwrap_or(""),
                 record.musical_work_distribution_category.to_sql_string(),
-                &opt_domain_to_string(&record.duration).unwrap_or_default(),
+                opt_domain_to_int(&record.duration),
                 record.recorded_indicator.to_sql_string(),
                 &opt_domain_to_string(&record.text_music_relationship).unwrap_or_default(),
                 &opt_domain_to_string(&record.composite_type).unwrap_or_default(),
@@ -813,7 +813,7 @@ pub fn parse_and_insert_rec<'a>(
                 record.record_sequence_num.to_sql_int(),
                 &opt_domain_to_string(&record.release_date).unwrap_or_default(),
                 record.constant.as_str(),
-                &opt_domain_to_string(&record.release_duration).unwrap_or_default(),
+                opt_domain_to_int(&record.release_duration),
                 record.constant.as_str(),
                 record.album_title.as_deref().unwrap_or(""),
                 record.album_label.as_deref().unwrap_or(""),
@@ -949,7 +949,7 @@ pub fn parse_and_insert_com<'a>(
                 record.title,
                 record.iswc_of_component.as_deref().unwrap_or(""),
                 record.submitter_work_num.as_deref().unwrap_or(""),
-                &opt_domain_to_string(&record.duration).unwrap_or_default(),
+                opt_domain_to_int(&record.duration),
                 &record.writer_1_last_name,
                 record.writer_1_first_name.as_deref().unwrap_or(""),
                 record.writer_1_ipi_name_num.as_deref().unwrap_or(""),
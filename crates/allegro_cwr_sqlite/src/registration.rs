@@ -0,0 +1,121 @@
+//! NWR or REV: deciding how to send a work from its registration state
+//!
+//! A work counts as registered once an ACK correlated with one of its submissions (see [`crate::ack`]) accepted
+//! it. Registered works are sent as revisions (REV), all others as new registrations (NWR).
+
+use crate::error::CwrDbError;
+use allegro_cwr::domain_types::TransactionType;
+use rusqlite::Connection;
+
+/// ACK transaction statuses that mean the work was registered
+pub const ACCEPTED_STATUSES: &[&str] = &["AS", "AC", "RA"];
+
+/// True if an ACK has accepted a submission of the work with this submitter work number
+pub fn is_work_registered(conn: &Connection, submitter_work_num: &str) -> Result<bool, CwrDbError> {
+    let submitter_work_num = submitter_work_num.trim();
+    if submitter_work_num.is_empty() {
+        return Ok(false);
+    }
+    let statuses = ACCEPTED_STATUSES.iter().map(|s| format!("'{}'", s)).collect::<Vec<_>>().join(", ");
+    let registered = conn.query_row(
+        &format!(
+            "SELECT EXISTS (SELECT 1 FROM ack_status s LEFT JOIN cwr_nwr n ON n.cwr_nwr_id = s.cwr_nwr_id \
+             WHERE s.transaction_status IN ({}) \
+             AND (TRIM(s.submitter_work_num) = ?1 OR TRIM(n.submitter_work_num) = ?1))",
+            statuses
+        ),
+        [submitter_work_num],
+        |row| row.get(0),
+    )?;
+    Ok(registered)
+}
+
+/// The transaction type to send the work with: REV once registered, NWR before
+pub fn registration_transaction_type(
+    conn: &Connection, submitter_work_num: &str,
+) -> Result<TransactionType, CwrDbError> {
+    Ok(if is_work_registered(conn, submitter_work_num)? { TransactionType::REV } else { TransactionType::NWR })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ExportOptions, ImportOptions, process_cwr_to_sqlite_with_options, process_sqlite_to_cwr_with_options};
+    use tempfile::tempdir;
+
+    const HDR: &str = "HDRPB285606836WARNER CHAPPELL MUSIC PUBLISHING LTD         01.102022122112541120221221";
+
+    fn nwr(transaction_seq: u32, submitter_work_num: &str) -> String {
+        format!(
+            "NWR{:08}00000000{:<60}  {:<14}     SER        Y       ORI",
+            transaction_seq, "TEST WORK TITLE", submitter_work_num
+        )
+    }
+
+    fn ack(transaction_seq: u32, original_seq: u32, submitter_work_num: &str, status: &str) -> String {
+        format!(
+            "ACK{:08}0000000020221221125411000010{:07}NWR{:<60}{:<20}{:<20}20221222{}",
+            transaction_seq, original_seq, "TEST WORK TITLE", submitter_work_num, "", status
+        )
+    }
+
+    #[test]
+    fn test_registration_aware_export() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("registration.db");
+        let db_path = db_path.to_str().unwrap();
+        let submission_path = temp_dir.path().join("submission.V21");
+        let ack_path = temp_dir.path().join("ack.V21");
+        let output_path = temp_dir.path().join("output.V21");
+
+        let submission = [
+            HDR.to_string(),
+            "GRHNWR0000102.100000000000  ".to_string(),
+            nwr(0, "SW0000000001"),
+            nwr(1, "SW0000000002"),
+            nwr(2, "SW0000000003"),
+            "GRT000010000000300000005".to_string(),
+            "TRL000000010000000300000007".to_string(),
+        ];
+        std::fs::write(&submission_path, submission.join("\r\n")).unwrap();
+        let acks = [
+            HDR.to_string(),
+            "GRHACK0000102.100000000000  ".to_string(),
+            ack(0, 0, "SW0000000001", "RJ"),
+            ack(1, 1, "SW0000000002", "AS"),
+            "GRT000010000000200000004".to_string(),
+            "TRL000000010000000200000006".to_string(),
+        ];
+        std::fs::write(&ack_path, acks.join("\r\n")).unwrap();
+
+        let (submission_id, _, _) =
+            process_cwr_to_sqlite_with_options(submission_path.to_str().unwrap(), db_path, &ImportOptions::default())
+                .unwrap();
+        let options = ImportOptions { correlate_acks: true, ..Default::default() };
+        process_cwr_to_sqlite_with_options(ack_path.to_str().unwrap(), db_path, &options).unwrap();
+
+        let conn = Connection::open(db_path).unwrap();
+        assert!(is_work_registered(&conn, "SW0000000002").unwrap());
+        assert!(!is_work_registered(&conn, "SW0000000001").unwrap());
+        assert!(!is_work_registered(&conn, "  ").unwrap());
+        assert_eq!(registration_transaction_type(&conn, "SW0000000003").unwrap(), TransactionType::NWR);
+
+        let export = ExportOptions { registration_aware: true, ..Default::default() };
+        let count =
+            process_sqlite_to_cwr_with_options(db_path, submission_id, Some(output_path.to_str().unwrap()), &export)
+                .unwrap();
+        let output = std::fs::read_to_string(&output_path).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+
+        assert_eq!(count, 9);
+        assert_eq!(lines.len(), 9);
+        assert!(lines[1].starts_with("GRHNWR00001"), "{}", lines[1]);
+        assert!(lines[2].starts_with("NWR0000000000000000") && lines[2].contains("SW0000000001"), "{}", lines[2]);
+        assert!(lines[3].starts_with("NWR0000000100000000") && lines[3].contains("SW0000000003"), "{}", lines[3]);
+        assert!(lines[4].starts_with("GRT00001000000020000000"), "{}", lines[4]);
+        assert!(lines[5].starts_with("GRHREV00002"), "{}", lines[5]);
+        assert!(lines[6].starts_with("REV0000000000000000") && lines[6].contains("SW0000000002"), "{}", lines[6]);
+        assert!(lines[7].starts_with("GRT00002000000010000000"), "{}", lines[7]);
+        assert!(lines[8].starts_with("TRL00002"), "{}", lines[8]);
+    }
+}
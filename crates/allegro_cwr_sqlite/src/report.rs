@@ -1,123 +1,330 @@
+use crate::error::CwrDbError;
 use allegro_cwr::{OutputFormat, format_int_with_commas};
 use rusqlite::Connection;
+use std::fmt::Write;
 
-/// Generates and prints summary reports from the database for a specific file import.
-pub fn report_summary(db_filename: &str, file_id: i64, format: OutputFormat) -> Result<(), Box<dyn std::error::Error>> {
+/// Record types that open a transaction
+const TRANSACTION_RECORD_TYPES: &str = "'AGR', 'NWR', 'REV', 'ISW', 'EXC', 'ACK'";
+/// Prefix the SQLite handler gives warnings stored in the `error` table
+const WARNING_PREFIX: &str = "WARNING [";
+
+/// Number of rows for one record or transaction type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeCount {
+    pub record_type: String,
+    pub count: i64,
+}
+
+/// Number of works (NWR, REV, ISW and EXC records) submitted by one sender
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SenderWorkCount {
+    pub sender_id: String,
+    pub sender_name: String,
+    pub work_count: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IssueSeverity {
+    Warning,
+    Error,
+}
+
+/// How often one warning or error description was recorded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IssueCount {
+    pub severity: IssueSeverity,
+    /// Record type the issue was reported on, when the description names one
+    pub record_type: Option<String>,
+    pub description: String,
+    pub count: i64,
+}
+
+/// Sum of the ownership shares of one record type (SPU, OPU, SWR or OWR), in hundredths of a percent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShareTotals {
+    pub record_type: String,
+    pub record_count: i64,
+    pub pr_ownership_share: i64,
+    pub mr_ownership_share: i64,
+    pub sr_ownership_share: i64,
+}
+
+/// Rows per record type, for one file or (with `None`) the whole database
+pub fn record_type_counts(conn: &Connection, file_id: Option<i64>) -> Result<Vec<TypeCount>, CwrDbError> {
+    type_counts(conn, file_id, "")
+}
+
+/// Transactions per transaction type (AGR, NWR, REV, ISW, EXC, ACK)
+pub fn transaction_type_counts(conn: &Connection, file_id: Option<i64>) -> Result<Vec<TypeCount>, CwrDbError> {
+    type_counts(conn, file_id, &format!("AND record_type IN ({})", TRANSACTION_RECORD_TYPES))
+}
+
+fn type_counts(conn: &Connection, file_id: Option<i64>, filter: &str) -> Result<Vec<TypeCount>, CwrDbError> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT record_type, count(*) FROM file_line WHERE (?1 IS NULL OR file_id = ?1) {} \
+         GROUP BY record_type ORDER BY record_type",
+        filter
+    ))?;
+    let counts = stmt
+        .query_map([file_id], |row| Ok(TypeCount { record_type: row.get(0)?, count: row.get(1)? }))?
+        .collect::<Result<_, _>>()?;
+    Ok(counts)
+}
+
+/// Works per sender named in the file headers, most works first
+pub fn works_per_sender(conn: &Connection, file_id: Option<i64>) -> Result<Vec<SenderWorkCount>, CwrDbError> {
+    let mut stmt = conn.prepare(
+        "SELECT h.sender_id, h.sender_name, count(*) FROM cwr_nwr w JOIN cwr_hdr h ON h.file_id = w.file_id \
+         WHERE (?1 IS NULL OR w.file_id = ?1) GROUP BY h.sender_id, h.sender_name ORDER BY count(*) DESC, h.sender_name",
+    )?;
+    let counts = stmt
+        .query_map([file_id], |row| {
+            Ok(SenderWorkCount { sender_id: row.get(0)?, sender_name: row.get(1)?, work_count: row.get(2)? })
+        })?
+        .collect::<Result<_, _>>()?;
+    Ok(counts)
+}
+
+/// Warnings and errors grouped by description, most frequent first
+pub fn issue_counts(conn: &Connection, file_id: Option<i64>) -> Result<Vec<IssueCount>, CwrDbError> {
+    let mut stmt = conn.prepare(
+        "SELECT description, count(*) FROM error WHERE (?1 IS NULL OR file_id = ?1) \
+         GROUP BY description ORDER BY count(*) DESC, description",
+    )?;
+    let counts = stmt
+        .query_map([file_id], |row| {
+            let description: String = row.get(0)?;
+            let count: i64 = row.get(1)?;
+            Ok((description, count))
+        })?
+        .map(|row| row.map(|(description, count)| issue_count(description, count)))
+        .collect::<Result<_, _>>()?;
+    Ok(counts)
+}
+
+fn issue_count(description: String, count: i64) -> IssueCount {
+    if let Some(rest) = description.strip_prefix(WARNING_PREFIX)
+        && let Some((record_type, _)) = rest.split_once(']')
+    {
+        return IssueCount {
+            severity: IssueSeverity::Warning,
+            record_type: Some(record_type.to_string()),
+            description,
+            count,
+        };
+    }
+    let record_type = description
+        .split_whitespace()
+        .next()
+        .filter(|word| word.len() == 3 && word.chars().all(|c| c.is_ascii_uppercase()))
+        .map(str::to_string);
+    IssueCount { severity: IssueSeverity::Error, record_type, description, count }
+}
+
+/// Ownership share totals for publisher (SPU, OPU) and writer (SWR, OWR) records
+pub fn share_totals(conn: &Connection, file_id: Option<i64>) -> Result<Vec<ShareTotals>, CwrDbError> {
+    let mut totals = Vec::new();
+    for table in ["cwr_spu", "cwr_swr"] {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT record_type, count(*), \
+             COALESCE(SUM(CAST(pr_ownership_share AS INTEGER)), 0), \
+             COALESCE(SUM(CAST(mr_ownership_share AS INTEGER)), 0), \
+             COALESCE(SUM(CAST(sr_ownership_share AS INTEGER)), 0) \
+             FROM {} WHERE (?1 IS NULL OR file_id = ?1) GROUP BY record_type ORDER BY record_type",
+            table
+        ))?;
+        let rows = stmt.query_map([file_id], |row| {
+            Ok(ShareTotals {
+                record_type: row.get(0)?,
+                record_count: row.get(1)?,
+                pr_ownership_share: row.get(2)?,
+                mr_ownership_share: row.get(3)?,
+                sr_ownership_share: row.get(4)?,
+            })
+        })?;
+        for row in rows {
+            totals.push(row?);
+        }
+    }
+    Ok(totals)
+}
+
+fn format_share(hundredths: i64) -> String {
+    format!("{}.{:02}", hundredths / 100, hundredths % 100)
+}
+
+/// Generates the summary report of one file import from the database, for the caller to print
+pub fn report_summary(
+    db_filename: &str, file_id: i64, format: OutputFormat,
+) -> Result<String, Box<dyn std::error::Error>> {
     let conn = Connection::open(db_filename)?;
 
+    let mut out = String::new();
     match format {
-        OutputFormat::Default | OutputFormat::Sql => generate_default_report(&conn, file_id),
-        OutputFormat::Json => generate_json_report(&conn, file_id),
+        OutputFormat::Default | OutputFormat::Sql => generate_default_report(&mut out, &conn, file_id)?,
+        OutputFormat::Json => generate_json_report(&mut out, &conn, file_id)?,
     }
+    Ok(out)
 }
 
-fn generate_default_report(conn: &Connection, file_id: i64) -> Result<(), Box<dyn std::error::Error>> {
+fn generate_default_report(
+    out: &mut String, conn: &Connection, file_id: i64,
+) -> Result<(), Box<dyn std::error::Error>> {
     // Record Type Report
-    println!();
-    println!("{:<5} | {:>10}", "Type", "Count"); // Header (Right-align Count)
-    println!("{:-<5}-+-{:-<10}", "", ""); // Separator (No change needed here)
-    let mut stmt_rec = conn.prepare(
-        "SELECT record_type, count(*) FROM file_line WHERE file_id = ?1 GROUP BY record_type ORDER BY record_type",
-    )?;
-    let mut rows_rec = stmt_rec.query([file_id])?;
-    let mut record_found = false;
-    while let Some(row) = rows_rec.next()? {
-        record_found = true;
-        let record_type: String = row.get(0)?;
-        let count: i64 = row.get(1)?;
-        println!("{:<5} | {:>10}", record_type, format_int_with_commas(count)); // Right-align count
+    writeln!(out)?;
+    writeln!(out, "{:<5} | {:>10}", "Type", "Count")?; // Header (Right-align Count)
+    writeln!(out, "{:-<5}-+-{:-<10}", "", "")?;
+    let record_types = record_type_counts(conn, Some(file_id))?;
+    for TypeCount { record_type, count } in &record_types {
+        writeln!(out, "{:<5} | {:>10}", record_type, format_int_with_commas(*count))?; // Right-align count
+    }
+    if record_types.is_empty() {
+        writeln!(out, "  No records loaded into 'file_line' table.")?;
     }
-    if !record_found {
-        println!("  No records loaded into 'file_line' table.");
+
+    // Share Report
+    let shares = share_totals(conn, Some(file_id))?;
+    if !shares.is_empty() {
+        writeln!(out)?;
+        writeln!(
+            out,
+            "{:<5} | {:>10} | {:>10} | {:>10} | {:>10}",
+            "Type", "Records", "PR Share", "MR Share", "SR Share"
+        )?;
+        writeln!(out, "{:-<5}-+-{:-<10}-+-{:-<10}-+-{:-<10}-+-{:-<10}", "", "", "", "", "")?;
+        for totals in &shares {
+            writeln!(
+                out,
+                "{:<5} | {:>10} | {:>10} | {:>10} | {:>10}",
+                totals.record_type,
+                format_int_with_commas(totals.record_count),
+                format_share(totals.pr_ownership_share),
+                format_share(totals.mr_ownership_share),
+                format_share(totals.sr_ownership_share)
+            )?;
+        }
     }
 
     // Error Report
-    println!();
-    println!("{:<60} | {:>10}", "Error", "Count"); // Header (Right-align Count)
-    println!("{:-<60}-+-{:-<10}", "", ""); // Separator (No change needed here)
-    let mut stmt_err = conn.prepare(
-        "SELECT description, count(*) FROM error WHERE file_id = ?1 GROUP BY description ORDER BY count(*) DESC",
-    )?;
-    let mut rows_err = stmt_err.query([file_id])?;
-    let mut error_found = false;
-    while let Some(row) = rows_err.next()? {
-        error_found = true;
-        let description: String = row.get(0)?;
-        let count: i64 = row.get(1)?;
+    writeln!(out)?;
+    writeln!(out, "{:<60} | {:>10}", "Error", "Count")?; // Header (Right-align Count)
+    writeln!(out, "{:-<60}-+-{:-<10}", "", "")?;
+    let issues = issue_counts(conn, Some(file_id))?;
+    for issue in &issues {
         // Truncate description if too long for alignment
-        let desc_display =
-            if description.len() > 60 { description[..57].to_string().to_owned() + "..." } else { description };
-        println!("{:<60} | {:>10}", desc_display, format_int_with_commas(count)); // Right-align count
+        let desc_display: String = if issue.description.chars().count() > 60 {
+            issue.description.chars().take(57).collect::<String>() + "..."
+        } else {
+            issue.description.clone()
+        };
+        writeln!(out, "{:<60} | {:>10}", desc_display, format_int_with_commas(issue.count))?; // Right-align count
     }
-    if !error_found {
-        println!("  No errors recorded.");
+    if issues.is_empty() {
+        writeln!(out, "  No errors recorded.")?;
     }
 
-    println!();
+    writeln!(out)?;
     Ok(())
 }
 
-fn generate_json_report(conn: &Connection, file_id: i64) -> Result<(), Box<dyn std::error::Error>> {
-    use std::collections::HashMap;
-
-    // Collect record type data
-    let mut record_types = HashMap::new();
-    let mut stmt_rec = conn.prepare(
-        "SELECT record_type, count(*) FROM file_line WHERE file_id = ?1 GROUP BY record_type ORDER BY record_type",
-    )?;
-    let mut rows_rec = stmt_rec.query([file_id])?;
-    while let Some(row) = rows_rec.next()? {
-        let record_type: String = row.get(0)?;
-        let count: i64 = row.get(1)?;
-        record_types.insert(record_type, count);
-    }
-
-    // Collect error data
-    let mut errors = HashMap::new();
-    let mut stmt_err = conn.prepare(
-        "SELECT description, count(*) FROM error WHERE file_id = ?1 GROUP BY description ORDER BY count(*) DESC",
-    )?;
-    let mut rows_err = stmt_err.query([file_id])?;
-    while let Some(row) = rows_err.next()? {
-        let description: String = row.get(0)?;
-        let count: i64 = row.get(1)?;
-        errors.insert(description, count);
-    }
+fn generate_json_report(out: &mut String, conn: &Connection, file_id: i64) -> Result<(), Box<dyn std::error::Error>> {
+    let record_types: Vec<(String, i64)> =
+        record_type_counts(conn, Some(file_id))?.into_iter().map(|c| (c.record_type, c.count)).collect();
+    let errors: Vec<(String, i64)> =
+        issue_counts(conn, Some(file_id))?.into_iter().map(|i| (i.description, i.count)).collect();
 
     // Generate JSON manually (simple format)
-    println!("{{");
-    println!("  \"file_id\": {},", file_id);
-    println!("  \"record_types\": {{");
+    writeln!(out, "{{")?;
+    writeln!(out, "  \"file_id\": {},", file_id)?;
+    writeln!(out, "  \"record_types\": {{")?;
 
     let mut first = true;
     for (record_type, count) in &record_types {
         if !first {
-            println!(",");
+            writeln!(out, ",")?;
         }
-        print!("    \"{}\": {}", record_type, count);
+        write!(out, "    \"{}\": {}", record_type, count)?;
         first = false;
     }
     if !record_types.is_empty() {
-        println!();
+        writeln!(out)?;
     }
-    println!("  }},");
+    writeln!(out, "  }},")?;
 
-    println!("  \"errors\": {{");
+    writeln!(out, "  \"errors\": {{")?;
     first = true;
     for (description, count) in &errors {
         if !first {
-            println!(",");
+            writeln!(out, ",")?;
         }
         // Escape quotes in description
         let escaped_description = description.replace("\"", "\\\"");
-        print!("    \"{}\": {}", escaped_description, count);
+        write!(out, "    \"{}\": {}", escaped_description, count)?;
         first = false;
     }
     if !errors.is_empty() {
-        println!();
+        writeln!(out)?;
     }
-    println!("  }}");
-    println!("}}");
+    writeln!(out, "  }}")?;
+    writeln!(out, "}}")?;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process_cwr_to_sqlite;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_aggregate_queries() {
+        let temp_dir = tempdir().unwrap();
+        let cwr_file_path = temp_dir.path().join("test.V21");
+        let db_file_path = temp_dir.path().join("test.db");
+        let lines = [
+            "HDRPB285606836WARNER CHAPPELL MUSIC PUBLISHING LTD         01.102022122112541120221221",
+            "GRHNWR0000102.100000000000  ",
+            "NWR0000000000000000Test Song                                                   SW0000001     T0000000001        ORI",
+            "GRT000010000000100000003",
+            "TRL000000010000000100000005",
+        ];
+        std::fs::write(&cwr_file_path, lines.join("\r\n")).unwrap();
+        let (file_id, _, _) =
+            process_cwr_to_sqlite(cwr_file_path.to_str().unwrap(), db_file_path.to_str().unwrap()).unwrap();
+        let conn = Connection::open(&db_file_path).unwrap();
+
+        let record_types = record_type_counts(&conn, Some(file_id)).unwrap();
+        assert_eq!(
+            record_types.iter().map(|c| c.record_type.as_str()).collect::<Vec<_>>(),
+            ["GRH", "GRT", "HDR", "NWR", "TRL"]
+        );
+        assert_eq!(record_type_counts(&conn, None).unwrap(), record_types);
+        assert!(record_type_counts(&conn, Some(file_id + 1)).unwrap().is_empty());
+
+        let transactions = transaction_type_counts(&conn, Some(file_id)).unwrap();
+        assert_eq!(transactions, [TypeCount { record_type: "NWR".into(), count: 1 }]);
+
+        let senders = works_per_sender(&conn, None).unwrap();
+        assert_eq!(senders.len(), 1);
+        assert_eq!(senders[0].sender_name, "WARNER CHAPPELL MUSIC PUBLISHING LTD");
+        assert_eq!(senders[0].work_count, 1);
+
+        assert!(share_totals(&conn, Some(file_id)).unwrap().is_empty());
+
+        let summary = report_summary(db_file_path.to_str().unwrap(), file_id, OutputFormat::Json).unwrap();
+        assert!(summary.contains("\"NWR\": 1"));
+    }
+
+    #[test]
+    fn test_issue_classification() {
+        let warning = issue_count("WARNING [SWR]: Invalid share".to_string(), 2);
+        assert_eq!(warning.severity, IssueSeverity::Warning);
+        assert_eq!(warning.record_type.as_deref(), Some("SWR"));
+
+        let error = issue_count("PWR missing or empty publisher".to_string(), 1);
+        assert_eq!(error.severity, IssueSeverity::Error);
+        assert_eq!(error.record_type.as_deref(), Some("PWR"));
+        assert_eq!(format_share(5050), "50.50");
+    }
+}
@@ -3,14 +3,23 @@
 CREATE TABLE file (
     file_id INTEGER PRIMARY KEY,
     file_path TEXT,
-    imported_on DATETIME
+    imported_on DATETIME,
+    content_hash TEXT,
+    import_version INTEGER NOT NULL DEFAULT 1
 );
 
+CREATE INDEX idx_file_content_hash ON file(content_hash);
+
 CREATE TABLE error (
     error_id INTEGER PRIMARY KEY,
     file_id INTEGER NOT NULL,
     line_number INTEGER NOT NULL,
-    description TEXT
+    description TEXT,
+    -- Set for warnings on parsed records; NULL for parse errors
+    record_type VARCHAR(3),
+    level VARCHAR(8),
+    field_name TEXT,
+    source TEXT
 );
 
 CREATE TABLE file_line (
@@ -19,7 +28,8 @@ CREATE TABLE file_line (
     line_number INTEGER NOT NULL,
     insert_position INTEGER NOT NULL DEFAULT 0,
     record_type VARCHAR(3) NOT NULL,
-    record_id INTEGER NOT NULL
+    record_id INTEGER NOT NULL,
+    original_line TEXT
 );
 
 CREATE UNIQUE INDEX idx_file_line_pos ON file_line(file_id, line_number, insert_position);
@@ -118,7 +128,7 @@ CREATE TABLE cwr_nwr (
     copyright_date VARCHAR(8),
     copyright_number VARCHAR(12),
     musical_work_distribution_category VARCHAR(3) NOT NULL,
-    duration VARCHAR(6),
+    duration INTEGER,
     recorded_indicator VARCHAR(1) NOT NULL,
     text_music_relationship VARCHAR(3),
     composite_type VARCHAR(3),
@@ -444,7 +454,7 @@ CREATE TABLE cwr_rec (
     record_sequence_num VARCHAR(8) NOT NULL,
     release_date VARCHAR(8),
     constant_blanks_1 VARCHAR(60),
-    release_duration VARCHAR(6),
+    release_duration INTEGER,
     constant_blanks_2 VARCHAR(5),
     album_title VARCHAR(60),
     album_label VARCHAR(60),
@@ -524,7 +534,7 @@ CREATE TABLE cwr_com (
     title VARCHAR(60) NOT NULL,
     iswc_of_component VARCHAR(11),
     submitter_work_num VARCHAR(14),
-    duration VARCHAR(6),
+    duration INTEGER,
     writer_1_last_name VARCHAR(45) NOT NULL,
     writer_1_first_name VARCHAR(30),
     writer_1_ipi_name_num VARCHAR(11),
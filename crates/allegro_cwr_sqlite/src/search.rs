@@ -0,0 +1,175 @@
+//! Optional full-text search over work titles, alternate titles and performer names (SQLite FTS5)
+
+use crate::error::CwrDbError;
+use rusqlite::Connection;
+
+/// FTS5 table and the triggers keeping it in step with `cwr_nwr`, `cwr_alt` and `cwr_per`. The row ID is the
+/// record ID times 4 plus the kind, so deletes can find their entry without scanning.
+const TITLE_SEARCH_SQL: &str = "
+CREATE VIRTUAL TABLE IF NOT EXISTS title_search USING fts5(
+    title, kind UNINDEXED, file_id UNINDEXED, record_type UNINDEXED, record_id UNINDEXED,
+    tokenize = 'unicode61 remove_diacritics 2'
+);
+
+CREATE TRIGGER IF NOT EXISTS title_search_nwr_insert AFTER INSERT ON cwr_nwr BEGIN
+    INSERT INTO title_search (rowid, title, kind, file_id, record_type, record_id)
+    VALUES (new.cwr_nwr_id * 4, new.work_title, 'work', new.file_id, new.record_type, new.cwr_nwr_id);
+END;
+CREATE TRIGGER IF NOT EXISTS title_search_nwr_delete AFTER DELETE ON cwr_nwr BEGIN
+    DELETE FROM title_search WHERE rowid = old.cwr_nwr_id * 4;
+END;
+
+CREATE TRIGGER IF NOT EXISTS title_search_alt_insert AFTER INSERT ON cwr_alt BEGIN
+    INSERT INTO title_search (rowid, title, kind, file_id, record_type, record_id)
+    VALUES (new.cwr_alt_id * 4 + 1, new.alternate_title, 'alternate', new.file_id, new.record_type, new.cwr_alt_id);
+END;
+CREATE TRIGGER IF NOT EXISTS title_search_alt_delete AFTER DELETE ON cwr_alt BEGIN
+    DELETE FROM title_search WHERE rowid = old.cwr_alt_id * 4 + 1;
+END;
+
+CREATE TRIGGER IF NOT EXISTS title_search_per_insert AFTER INSERT ON cwr_per BEGIN
+    INSERT INTO title_search (rowid, title, kind, file_id, record_type, record_id)
+    VALUES (new.cwr_per_id * 4 + 2, trim(trim(coalesce(new.performing_artist_first_name, '')) || ' ' ||
+            trim(new.performing_artist_last_name)), 'performer', new.file_id, new.record_type, new.cwr_per_id);
+END;
+CREATE TRIGGER IF NOT EXISTS title_search_per_delete AFTER DELETE ON cwr_per BEGIN
+    DELETE FROM title_search WHERE rowid = old.cwr_per_id * 4 + 2;
+END;
+";
+
+/// Index rows of records imported before the index existed
+const TITLE_SEARCH_BACKFILL_SQL: &str = "
+INSERT INTO title_search (rowid, title, kind, file_id, record_type, record_id)
+SELECT cwr_nwr_id * 4, work_title, 'work', file_id, record_type, cwr_nwr_id FROM cwr_nwr;
+INSERT INTO title_search (rowid, title, kind, file_id, record_type, record_id)
+SELECT cwr_alt_id * 4 + 1, alternate_title, 'alternate', file_id, record_type, cwr_alt_id FROM cwr_alt;
+INSERT INTO title_search (rowid, title, kind, file_id, record_type, record_id)
+SELECT cwr_per_id * 4 + 2, trim(trim(coalesce(performing_artist_first_name, '')) || ' ' || trim(performing_artist_last_name)),
+       'performer', file_id, record_type, cwr_per_id FROM cwr_per;
+";
+
+/// A title or performer name matching a search, with the work it belongs to
+#[derive(Debug, Clone, PartialEq)]
+pub struct TitleMatch {
+    pub file_id: i64,
+    /// `work`, `alternate` or `performer`
+    pub kind: String,
+    /// The matching work title, alternate title or performer name
+    pub text: String,
+    pub cwr_nwr_id: Option<i64>,
+    pub submitter_work_num: Option<String>,
+    pub work_title: Option<String>,
+}
+
+/// Whether the database has the title search index
+pub fn has_title_search(conn: &Connection) -> Result<bool, CwrDbError> {
+    let count: i64 = conn.query_row(
+        "SELECT count(*) FROM sqlite_master WHERE type = 'table' AND name = 'title_search'",
+        [],
+        |row| row.get(0),
+    )?;
+    Ok(count > 0)
+}
+
+/// Adds the title search index to a database set up with [`crate::setup_database`], indexing the records already
+/// imported. Later imports are indexed as they are inserted. Does nothing if the index exists.
+pub fn enable_title_search(db_path: &str) -> Result<(), CwrDbError> {
+    let mut conn = Connection::open(db_path)?;
+    if has_title_search(&conn)? {
+        return Ok(());
+    }
+    let tx = conn.transaction()?;
+    tx.execute_batch(TITLE_SEARCH_SQL)?;
+    tx.execute_batch(TITLE_SEARCH_BACKFILL_SQL)?;
+    tx.commit()?;
+    Ok(())
+}
+
+/// Searches work titles, alternate titles and performer names with an FTS5 query (e.g. `love`, `"baby can"`,
+/// `lov*`), best matches first
+pub fn search_titles(db_path: &str, query: &str, limit: usize) -> Result<Vec<TitleMatch>, CwrDbError> {
+    let conn = Connection::open(db_path)?;
+    if !has_title_search(&conn)? {
+        return Err(CwrDbError::Setup(format!(
+            "Database '{}' has no title search index; import with --title-search to create it",
+            db_path
+        )));
+    }
+    let mut stmt = conn.prepare(
+        "SELECT s.file_id, s.kind, s.title, w.cwr_nwr_id, n.submitter_work_num, n.work_title
+         FROM title_search s
+         LEFT JOIN v_line_work w ON w.file_id = s.file_id AND w.record_id = s.record_id
+             AND (w.record_type = s.record_type OR (s.kind = 'work' AND w.record_type IN ('NWR', 'REV', 'ISW', 'EXC')))
+         LEFT JOIN cwr_nwr n ON n.cwr_nwr_id = w.cwr_nwr_id
+         WHERE title_search MATCH ?1
+         ORDER BY bm25(title_search)
+         LIMIT ?2",
+    )?;
+    let matches = stmt
+        .query_map(rusqlite::params![query, limit as i64], |row| {
+            Ok(TitleMatch {
+                file_id: row.get(0)?,
+                kind: row.get(1)?,
+                text: row.get(2)?,
+                cwr_nwr_id: row.get(3)?,
+                submitter_work_num: row.get(4)?,
+                work_title: row.get(5)?,
+            })
+        })?
+        .collect::<Result<_, _>>()?;
+    Ok(matches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ImportOptions, delete_file, process_cwr_to_sqlite_with_options};
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_search_titles() {
+        let temp_dir = tempdir().unwrap();
+        let cwr_file_path = temp_dir.path().join("test.V21");
+        let db_file_path = temp_dir.path().join("test.db");
+        let content = format!(
+            "HDRPB285606836WARNER CHAPPELL MUSIC PUBLISHING LTD         01.102022122112541120221221\n\
+             GRHNWR0000102.100000000000  \n\
+             NWR0000000000000000{:<60}SW0000000001        SER        Y       ORI\n\
+             ALT0000000000000001{:<60}AT  \n\
+             PER0000000000000002{:<45}{:<30}\n",
+            "Test Song", "Baby Can't You See", "SMITH", "JANE"
+        );
+        std::fs::write(&cwr_file_path, content).unwrap();
+        let cwr_path = cwr_file_path.to_str().unwrap();
+        let db_path = db_file_path.to_str().unwrap();
+
+        let options = ImportOptions { title_search: true, ..Default::default() };
+        let (file_id, _, _) = process_cwr_to_sqlite_with_options(cwr_path, db_path, &options).unwrap();
+
+        let matches = search_titles(db_path, "baby", 10).unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!((matches[0].kind.as_str(), matches[0].text.trim_end()), ("alternate", "Baby Can't You See"));
+        assert_eq!(matches[0].work_title.as_deref().map(str::trim_end), Some("Test Song"));
+
+        let matches = search_titles(db_path, "jane smith", 10).unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!((matches[0].kind.as_str(), matches[0].text.as_str()), ("performer", "JANE SMITH"));
+        assert_eq!(matches[0].submitter_work_num.as_deref().map(str::trim_end), Some("0000000001"));
+
+        assert!(delete_file(db_path, file_id).unwrap());
+        assert!(search_titles(db_path, "test", 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_search_without_index() {
+        let temp_dir = tempdir().unwrap();
+        let db_file_path = temp_dir.path().join("plain.db");
+        let db_path = db_file_path.to_str().unwrap();
+        crate::setup_database(db_path).unwrap();
+        assert!(matches!(search_titles(db_path, "song", 10), Err(CwrDbError::Setup(_))));
+
+        enable_title_search(db_path).unwrap();
+        enable_title_search(db_path).unwrap();
+        assert!(search_titles(db_path, "song", 10).unwrap().is_empty());
+    }
+}
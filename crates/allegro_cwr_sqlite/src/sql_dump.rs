@@ -0,0 +1,373 @@
+//! Writing a CWR file as SQL text instead of importing it
+//!
+//! [`SqlDumpHandler`] streams the statements an import would run, for piping into SQLite, PostgreSQL or MySQL
+//! (see [`SqlDialect`]) or reading as text. Row ids are assigned by the handler and written out, counting from
+//! [`SqlDumpOptions::first_id`] in every table, so the dump loads the same way in each database; raise it to load
+//! into a database that already holds rows.
+
+use std::collections::HashMap;
+use std::io::Write;
+
+use rusqlite::types::{ToSql, ToSqlOutput, ValueRef};
+
+use crate::connection::{SCHEMA_SQL, SCHEMA_VERSION};
+use crate::statements::record_insert_sql;
+use crate::{CwrDbError, SqliteInsertable};
+
+/// Database a SQL dump is written for
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SqlDialect {
+    #[default]
+    Sqlite,
+    Postgres,
+    MySql,
+}
+
+impl SqlDialect {
+    /// The schema DDL, adjusted where the database differs from SQLite
+    fn schema(self) -> String {
+        match self {
+            SqlDialect::Sqlite => format!("{}\nPRAGMA user_version = {};\n", SCHEMA_SQL, SCHEMA_VERSION),
+            SqlDialect::Postgres => SCHEMA_SQL.replace(" DATETIME", " TIMESTAMP"),
+            // MySQL indexes TEXT columns by prefix only; a SHA-256 hex digest is 64 characters
+            SqlDialect::MySql => SCHEMA_SQL.replace("ON file(content_hash)", "ON file(content_hash(64))"),
+        }
+    }
+
+    fn begin(self) -> &'static str {
+        match self {
+            SqlDialect::Sqlite => "BEGIN;",
+            SqlDialect::Postgres | SqlDialect::MySql => "START TRANSACTION;",
+        }
+    }
+
+    /// `value` as a literal: `NULL`, a number, a quoted string or a hex blob. SQL has no literal for NaN or
+    /// infinity, so those are written as `NULL`, which is also how SQLite stores NaN.
+    fn literal(self, value: ValueRef) -> String {
+        match value {
+            ValueRef::Null => "NULL".to_string(),
+            ValueRef::Integer(i) => i.to_string(),
+            // Debug keeps the decimal point (`5.0`), so the literal stays a REAL
+            ValueRef::Real(f) if f.is_finite() => format!("{:?}", f),
+            ValueRef::Real(_) => "NULL".to_string(),
+            ValueRef::Text(text) => {
+                let text = String::from_utf8_lossy(text).replace('\'', "''");
+                // MySQL treats backslashes in string literals as escapes by default
+                let text = if self == SqlDialect::MySql { text.replace('\\', "\\\\") } else { text };
+                format!("'{}'", text)
+            }
+            ValueRef::Blob(blob) => {
+                let hex: String = blob.iter().map(|b| format!("{:02X}", b)).collect();
+                match self {
+                    SqlDialect::Postgres => format!("decode('{}', 'hex')", hex),
+                    SqlDialect::Sqlite | SqlDialect::MySql => format!("X'{}'", hex),
+                }
+            }
+        }
+    }
+}
+
+impl std::str::FromStr for SqlDialect {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "sqlite" => Ok(SqlDialect::Sqlite),
+            "postgres" | "postgresql" => Ok(SqlDialect::Postgres),
+            "mysql" => Ok(SqlDialect::MySql),
+            _ => Err(format!("Invalid SQL dialect '{}'. Valid values are: sqlite, postgres, mysql", s)),
+        }
+    }
+}
+
+/// How [`SqlDumpHandler`] writes a dump
+#[derive(Debug, Clone)]
+pub struct SqlDumpOptions {
+    pub dialect: SqlDialect,
+    /// Start with the schema DDL
+    pub include_schema: bool,
+    /// Id of the first row written to each table
+    pub first_id: i64,
+}
+
+impl Default for SqlDumpOptions {
+    fn default() -> Self {
+        SqlDumpOptions { dialect: SqlDialect::default(), include_schema: true, first_id: 1 }
+    }
+}
+
+/// A [`allegro_cwr::CwrHandler`] that writes `INSERT` statements for each record, parse error and warning to a
+/// writer, as one file
+pub struct SqlDumpHandler<W: Write> {
+    writer: W,
+    dialect: SqlDialect,
+    file_id: i64,
+    first_id: i64,
+    next_ids: HashMap<&'static str, i64>,
+    processed_count: usize,
+    error_count: usize,
+}
+
+impl<W: Write> SqlDumpHandler<W> {
+    /// Starts the dump with the `file` row for `file_path`, after the schema when the options include it.
+    /// `content_hash` is stored for duplicate detection, as [`crate::file_content_hash`] computes it.
+    pub fn new(
+        mut writer: W, file_path: &str, content_hash: Option<&str>, options: &SqlDumpOptions,
+    ) -> Result<Self, CwrDbError> {
+        let dialect = options.dialect;
+        if options.include_schema {
+            writer.write_all(dialect.schema().as_bytes())?;
+            writeln!(writer)?;
+        }
+        writeln!(writer, "{}", dialect.begin())?;
+        let mut handler = SqlDumpHandler {
+            writer,
+            dialect,
+            file_id: options.first_id,
+            first_id: options.first_id,
+            next_ids: HashMap::new(),
+            processed_count: 0,
+            error_count: 0,
+        };
+        let values = handler.literals(&[&file_path, &content_hash])?;
+        writeln!(
+            handler.writer,
+            "INSERT INTO file (file_id, file_path, imported_on, content_hash, import_version) VALUES ({}, {}, CURRENT_TIMESTAMP, {}, 1);",
+            handler.file_id, values[0], values[1]
+        )?;
+        Ok(handler)
+    }
+
+    /// The writer, once the dump is finished
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    /// Writes a row with the next id of `table` and returns that id. `values` are SQL literals for `columns`.
+    fn write_insert(&mut self, table: &'static str, columns: &str, values: &[String]) -> Result<i64, CwrDbError> {
+        let next_id = self.next_ids.entry(table).or_insert(self.first_id);
+        let id = *next_id;
+        *next_id += 1;
+        writeln!(
+            self.writer,
+            "INSERT INTO {} ({}_id, {}) VALUES ({}, {});",
+            table,
+            table,
+            columns,
+            id,
+            values.join(", ")
+        )?;
+        Ok(id)
+    }
+
+    /// Each of `params` as a literal of the dump's dialect
+    fn literals(&self, params: &[&dyn ToSql]) -> Result<Vec<String>, CwrDbError> {
+        params.iter().map(|param| sql_literal(*param, self.dialect)).collect()
+    }
+}
+
+/// The columns of an `INSERT INTO table (columns) VALUES (...)` statement
+fn insert_columns(sql: &str) -> &str {
+    sql.split_once('(').and_then(|(_, rest)| rest.split_once(')')).map(|(columns, _)| columns).unwrap_or_default()
+}
+
+/// `param` as a SQL literal of `dialect`
+pub fn sql_literal(param: &dyn ToSql, dialect: SqlDialect) -> Result<String, CwrDbError> {
+    Ok(match param.to_sql()? {
+        ToSqlOutput::Borrowed(value) => dialect.literal(value),
+        ToSqlOutput::Owned(ref value) => dialect.literal(value.into()),
+        _ => return Err(CwrDbError::Setup("Value has no SQL literal".to_string())),
+    })
+}
+
+impl<W: Write> allegro_cwr::CwrHandler for SqlDumpHandler<W> {
+    type Error = CwrDbError;
+
+    fn process_record(&mut self, parsed_record: allegro_cwr::ParsedRecord) -> Result<(), Self::Error> {
+        let table = parsed_record.record.table_name();
+        let params = parsed_record.record.to_sql_params(self.file_id)?;
+        let params: Vec<&dyn ToSql> = params.iter().map(|param| param.as_ref()).collect();
+        let values = self.literals(&params)?;
+        let record_id = self.write_insert(table, insert_columns(record_insert_sql(table)), &values)?;
+
+        let values = self.literals(&[
+            &self.file_id,
+            &(parsed_record.line_number as i64),
+            &parsed_record.record.record_type(),
+            &record_id,
+            &parsed_record.raw_line.as_deref(),
+        ])?;
+        self.write_insert("file_line", "file_id, line_number, record_type, record_id, original_line", &values)?;
+        self.processed_count += 1;
+        Ok(())
+    }
+
+    fn handle_parse_error(
+        &mut self, line_number: usize, error: &allegro_cwr::CwrParseError,
+    ) -> Result<(), Self::Error> {
+        let values = self.literals(&[&self.file_id, &(line_number as i64), &error.to_string()])?;
+        self.write_insert("error", "file_id, line_number, description", &values)?;
+        self.error_count += 1;
+        Ok(())
+    }
+
+    fn handle_warnings(
+        &mut self, line_number: usize, record_type: &str, warnings: &[allegro_cwr::parsing::CwrWarning<'static>],
+    ) -> Result<(), Self::Error> {
+        for warning in warnings {
+            let field_name = Some(warning.field_name).filter(|name| !name.is_empty());
+            let source = Some(warning.source_str.as_ref()).filter(|source| !source.is_empty());
+            let values = self.literals(&[
+                &self.file_id,
+                &(line_number as i64),
+                &format!("WARNING [{}]: {}", record_type, warning),
+                &record_type,
+                &warning.level.as_str(),
+                &field_name,
+                &source,
+            ])?;
+            self.write_insert(
+                "error",
+                "file_id, line_number, description, record_type, level, field_name, source",
+                &values,
+            )?;
+            self.error_count += 1;
+        }
+        Ok(())
+    }
+
+    fn finalize(&mut self) -> Result<(), Self::Error> {
+        writeln!(self.writer, "COMMIT;")?;
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    fn get_report(&self) -> String {
+        format!("SQL dump complete:\n  Records processed: {}\n  Errors: {}", self.processed_count, self.error_count)
+    }
+
+    fn report(&self) -> allegro_cwr::HandlerReport {
+        allegro_cwr::HandlerReport::Output(allegro_cwr::OutputReport {
+            format: "sql".to_string(),
+            destination: None,
+            records: self.processed_count,
+            errors: self.error_count,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SqlDialect, sql_literal};
+    use crate::{
+        ImportOptions, SqlDumpOptions, process_cwr_bytes_to_sql, process_cwr_to_sql, process_cwr_to_sqlite_with_options,
+    };
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_sql_literal() {
+        let sqlite = SqlDialect::Sqlite;
+        assert_eq!(sql_literal(&5.0f64, sqlite).unwrap(), "5.0");
+        assert_eq!(sql_literal(&f64::NAN, sqlite).unwrap(), "NULL");
+        assert_eq!(sql_literal(&f64::INFINITY, sqlite).unwrap(), "NULL");
+        assert_eq!(sql_literal(&"it's", sqlite).unwrap(), "'it''s'");
+        assert_eq!(sql_literal(&None::<i64>, sqlite).unwrap(), "NULL");
+        assert_eq!(sql_literal(&vec![0xABu8, 1], sqlite).unwrap(), "X'AB01'");
+
+        assert_eq!(sql_literal(&"C:\\it's", SqlDialect::Postgres).unwrap(), "'C:\\it''s'");
+        assert_eq!(sql_literal(&vec![0xABu8, 1], SqlDialect::Postgres).unwrap(), "decode('AB01', 'hex')");
+        assert_eq!(sql_literal(&"C:\\it's", SqlDialect::MySql).unwrap(), "'C:\\\\it''s'");
+        assert_eq!("postgresql".parse::<SqlDialect>(), Ok(SqlDialect::Postgres));
+        assert!("oracle".parse::<SqlDialect>().is_err());
+    }
+
+    #[test]
+    fn test_sql_dialect_schema() {
+        assert!(SqlDialect::Sqlite.schema().contains("PRAGMA user_version"));
+        let postgres = SqlDialect::Postgres.schema();
+        assert!(!postgres.contains("DATETIME") && !postgres.contains("PRAGMA"));
+        assert!(SqlDialect::MySql.schema().contains("ON file(content_hash(64))"));
+    }
+
+    #[test]
+    fn test_sql_dumps_load_into_one_database() {
+        let content = "HDRPB285606836WARNER CHAPPELL MUSIC PUBLISHING LTD         01.102022122112541120221221\n\
+                       GRHNWR0000102.100000000000  \n";
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        for (name, include_schema, first_id) in [("a.V21", true, 1), ("b.V21", false, 100)] {
+            let mut dump = Vec::new();
+            let options = ImportOptions { store_original_lines: true, ..Default::default() };
+            let dump_options = SqlDumpOptions { include_schema, first_id, ..Default::default() };
+            process_cwr_bytes_to_sql(name, content.as_bytes(), &mut dump, &options, &dump_options).unwrap();
+            conn.execute_batch(&String::from_utf8(dump).unwrap()).unwrap();
+        }
+
+        let count = |sql: &str| conn.query_row(sql, [], |row| row.get::<_, i64>(0)).unwrap();
+        assert_eq!(count("SELECT COUNT(DISTINCT file_id) FROM file"), 2);
+        assert_eq!(count("SELECT COUNT(DISTINCT file_id) FROM cwr_hdr"), 2);
+        assert_eq!(
+            count("SELECT COUNT(*) FROM cwr_grh WHERE file_id = (SELECT file_id FROM file WHERE file_path = 'b.V21')"),
+            1
+        );
+        assert_eq!(
+            count(
+                "SELECT COUNT(*) FROM file_line l JOIN cwr_hdr h ON h.cwr_hdr_id = l.record_id AND h.file_id = l.file_id WHERE l.record_type = 'HDR'"
+            ),
+            2
+        );
+    }
+
+    #[test]
+    fn test_sql_dump_matches_import() {
+        let temp_dir = tempdir().unwrap();
+        let cwr_file_path = temp_dir.path().join("test.V21");
+        let db_file_path = temp_dir.path().join("test.db");
+        let content = format!(
+            "HDRPB285606836WARNER CHAPPELL MUSIC PUBLISHING LTD         01.102022122112541120221221\n\
+             GRHNWR0000102.100000000000  \n\
+             NWR0000000000000000{:<60}SW0000000001        SER        Y       ORI\n\
+             ALT0000000000000001{:<60}AT  \n\
+             GRHZZZ0000202.100000000000  \n",
+            "Don't Stop", "O'Brien's Song"
+        );
+        std::fs::write(&cwr_file_path, content).unwrap();
+        let cwr_path = cwr_file_path.to_str().unwrap();
+        let options = ImportOptions { store_original_lines: true, ..Default::default() };
+
+        let mut dump = Vec::new();
+        let (count, _) = process_cwr_to_sql(cwr_path, &mut dump, &options, &SqlDumpOptions::default()).unwrap();
+        let dump = String::from_utf8(dump).unwrap();
+        assert_eq!(count, 5);
+        assert!(dump.contains("INSERT INTO cwr_alt (cwr_alt_id, file_id, record_type, "));
+        assert!(dump.contains("'O''Brien''s Song"));
+        assert!(dump.trim_end().ends_with("COMMIT;"));
+
+        let dumped = rusqlite::Connection::open_in_memory().unwrap();
+        dumped.execute_batch(&dump).unwrap();
+        process_cwr_to_sqlite_with_options(cwr_path, db_file_path.to_str().unwrap(), &options).unwrap();
+        let imported = rusqlite::Connection::open(&db_file_path).unwrap();
+
+        let rows = |conn: &rusqlite::Connection, sql: &str| -> Vec<String> {
+            let mut stmt = conn.prepare(sql).unwrap();
+            let columns = stmt.column_count();
+            stmt.query_map([], |row| {
+                Ok((0..columns).map(|i| format!("{:?}", row.get_ref(i).unwrap())).collect::<Vec<_>>().join("|"))
+            })
+            .unwrap()
+            .collect::<std::result::Result<_, _>>()
+            .unwrap()
+        };
+        for sql in [
+            "SELECT * FROM cwr_hdr",
+            "SELECT * FROM cwr_grh",
+            "SELECT * FROM cwr_nwr",
+            "SELECT * FROM cwr_alt",
+            "SELECT * FROM file_line",
+            "SELECT file_id, line_number, description, record_type, level, field_name, source FROM error",
+            "SELECT file_id, file_path, content_hash, import_version FROM file",
+        ] {
+            assert_eq!(rows(&dumped, sql), rows(&imported, sql), "{}", sql);
+        }
+        assert!(!rows(&dumped, "SELECT * FROM error").is_empty());
+    }
+}
@@ -0,0 +1,57 @@
+-- noinspection SqlNoDataSourceInspectionForFile
+
+-- Work-centric views: records of a work transaction linked to its NWR/REV/ISW/EXC record (cwr_nwr)
+
+CREATE INDEX IF NOT EXISTS idx_file_line_type ON file_line(file_id, record_type, line_number);
+
+-- Work each line belongs to: the closest preceding transaction or group header, when that header is a work
+CREATE VIEW IF NOT EXISTS v_line_work AS
+SELECT l.file_id, l.file_line_id, l.line_number, l.record_type, l.record_id, h.record_id AS cwr_nwr_id
+FROM file_line l
+JOIN file_line h ON h.file_id = l.file_id AND h.line_number = (
+    SELECT max(t.line_number) FROM file_line t
+    WHERE t.file_id = l.file_id AND t.line_number <= l.line_number
+      AND t.record_type IN ('NWR', 'REV', 'ISW', 'EXC', 'AGR', 'ACK', 'GRH', 'GRT', 'TRL')
+)
+WHERE h.record_type IN ('NWR', 'REV', 'ISW', 'EXC');
+
+-- Writers (SWR controlled, OWR other) of each work
+CREATE VIEW IF NOT EXISTS v_work_writers AS
+SELECT w.file_id, w.cwr_nwr_id, n.submitter_work_num, n.work_title, n.iswc, w.line_number, s.cwr_swr_id,
+       s.record_type, s.interested_party_num, s.writer_last_name, s.writer_first_name, s.writer_designation_code,
+       s.writer_ipi_name_num, s.writer_ipi_base_number, s.pr_affiliation_society_num, s.pr_ownership_share,
+       s.mr_society, s.mr_ownership_share, s.sr_society, s.sr_ownership_share
+FROM v_line_work w
+JOIN cwr_nwr n ON n.cwr_nwr_id = w.cwr_nwr_id
+JOIN cwr_swr s ON s.cwr_swr_id = w.record_id
+WHERE w.record_type IN ('SWR', 'OWR');
+
+-- Publishers (SPU controlled, OPU other) of each work
+CREATE VIEW IF NOT EXISTS v_work_publishers AS
+SELECT w.file_id, w.cwr_nwr_id, n.submitter_work_num, n.work_title, n.iswc, w.line_number, p.cwr_spu_id,
+       p.record_type, p.publisher_sequence_num, p.interested_party_num, p.publisher_name, p.publisher_type,
+       p.publisher_ipi_name_num, p.publisher_ipi_base_number, p.submitter_agreement_number,
+       p.pr_affiliation_society_num, p.pr_ownership_share, p.mr_society, p.mr_ownership_share, p.sr_society,
+       p.sr_ownership_share
+FROM v_line_work w
+JOIN cwr_nwr n ON n.cwr_nwr_id = w.cwr_nwr_id
+JOIN cwr_spu p ON p.cwr_spu_id = w.record_id
+WHERE w.record_type IN ('SPU', 'OPU');
+
+-- Territories of control of each work's publishers (SPT/OPT) and writers (SWT/OWT)
+CREATE VIEW IF NOT EXISTS v_work_territories AS
+SELECT w.file_id, w.cwr_nwr_id, n.submitter_work_num, n.work_title, n.iswc, w.line_number, w.record_type,
+       'publisher' AS party_type, t.interested_party_num, t.inclusion_exclusion_indicator, t.tis_numeric_code,
+       t.pr_collection_share, t.mr_collection_share, t.sr_collection_share, t.shares_change, t.sequence_num
+FROM v_line_work w
+JOIN cwr_nwr n ON n.cwr_nwr_id = w.cwr_nwr_id
+JOIN cwr_spt t ON t.cwr_spt_id = w.record_id
+WHERE w.record_type IN ('SPT', 'OPT')
+UNION ALL
+SELECT w.file_id, w.cwr_nwr_id, n.submitter_work_num, n.work_title, n.iswc, w.line_number, w.record_type,
+       'writer' AS party_type, t.interested_party_num, t.inclusion_exclusion_indicator, t.tis_numeric_code,
+       t.pr_collection_share, t.mr_collection_share, t.sr_collection_share, t.shares_change, t.sequence_num
+FROM v_line_work w
+JOIN cwr_nwr n ON n.cwr_nwr_id = w.cwr_nwr_id
+JOIN cwr_swt t ON t.cwr_swt_id = w.record_id
+WHERE w.record_type IN ('SWT', 'OWT');
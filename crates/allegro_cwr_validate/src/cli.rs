@@ -0,0 +1,474 @@
+use std::ffi::OsString;
+use std::io::Write;
+use std::process;
+use std::time::Instant;
+
+use crate::{CwrInput, FailurePolicy, FileReport, Outcome, ReportFormat};
+
+use allegro_cwr_cli::{
+    check_log_format, create_output, get_output_filename_with_default_extension, get_value,
+    init_report_logging_and_parse_args, read_stdin, status, status_error, BaseConfig, BatchSummary,
+};
+
+/// CWR version of `--fragment` input when `--cwr` is not given
+const FRAGMENT_DEFAULT_VERSION: f32 = 2.2;
+
+#[derive(Default)]
+struct Config {
+    base: BaseConfig,
+    charset_override: Option<String>,
+    output_filename: Option<String>,
+    run_rules: bool,
+    disabled_rules: Vec<crate::RuleId>,
+    format: ReportFormat,
+    fail_on: Option<FailurePolicy>,
+    fragment: bool,
+    #[cfg(feature = "online")]
+    online: Option<crate::online::LookupConfig>,
+}
+
+fn parse_args(args: Vec<OsString>) -> Result<Config, String> {
+    let mut config = Config::default();
+    let mut parser = lexopt::Parser::from_args(args);
+
+    while let Ok(Some(arg)) = parser.next() {
+        match arg {
+            lexopt::Arg::Long("cwr") => {
+                let version_str = get_value(&mut parser, "cwr")?;
+                config.base.set_cwr_version(&version_str)?;
+            }
+            lexopt::Arg::Long("charset") => {
+                let charset_str = get_value(&mut parser, "charset")?;
+                config.charset_override = Some(charset_str);
+            }
+            lexopt::Arg::Short('o') | lexopt::Arg::Long("output") => {
+                let output_filename = get_value(&mut parser, "output")?;
+                config.output_filename = Some(output_filename);
+            }
+            lexopt::Arg::Long("rules") => {
+                config.run_rules = true;
+            }
+            lexopt::Arg::Long("disable-rule") => {
+                let rule_str = get_value(&mut parser, "disable-rule")?;
+                for rule in rule_str.split(',') {
+                    config.disabled_rules.push(rule.parse()?);
+                }
+                config.run_rules = true;
+            }
+            lexopt::Arg::Long("fail-on") => {
+                let categories = get_value(&mut parser, "fail-on")?;
+                config.fail_on = Some(categories.parse()?);
+            }
+            lexopt::Arg::Long("format") => {
+                let format_str = get_value(&mut parser, "format")?;
+                config.format = format_str.parse()?;
+            }
+            lexopt::Arg::Long("fragment") => {
+                config.fragment = true;
+            }
+            #[cfg(feature = "online")]
+            lexopt::Arg::Long("online") => {
+                let config_path = get_value(&mut parser, "online")?;
+                let json = std::fs::read_to_string(&config_path)
+                    .map_err(|e| format!("Error reading lookup config '{}': {}", config_path, e))?;
+                config.online = Some(
+                    serde_json::from_str(&json)
+                        .map_err(|e| format!("Invalid lookup config '{}': {}", config_path, e))?,
+                );
+                config.run_rules = true;
+            }
+            lexopt::Arg::Long("list-rules") => {
+                print_rules();
+                process::exit(0);
+            }
+            lexopt::Arg::Value(val) => {
+                config.base.add_input_file(val.to_string_lossy().to_string())?;
+            }
+            lexopt::Arg::Short('q' | 'v') | lexopt::Arg::Long("quiet" | "verbose") => {}
+            lexopt::Arg::Long("log-format") => check_log_format(&mut parser)?,
+            lexopt::Arg::Short('h') | lexopt::Arg::Long("help") => {
+                print_help();
+                process::exit(0);
+            }
+            _ => {
+                return Err("Unknown argument".to_string());
+            }
+        }
+    }
+
+    config.base.finalize();
+    Ok(config)
+}
+
+/// Runs the command line tool with `args` (excluding the program name)
+pub fn run(args: impl IntoIterator<Item = OsString>) {
+    let args: Vec<OsString> = args.into_iter().collect();
+    let config = init_report_logging_and_parse_args(crate::REPORT_TARGET, || {
+        parse_args(args).inspect_err(|_| {
+            print_help();
+        })
+    });
+
+    let start_time = Instant::now();
+
+    if config.fragment {
+        process_fragments(&config);
+    } else if config.format != ReportFormat::Text {
+        process_machine_report(&config);
+    } else if config.base.read_stdin {
+        process_stdin(&config, start_time);
+    } else {
+        process_files(&config, start_time);
+    }
+}
+
+fn process_stdin(config: &Config, start_time: Instant) {
+    let content = read_stdin();
+    let input = CwrInput::Bytes { name: "stdin", content: &content };
+    if config.run_rules {
+        run_rules(config, input);
+    }
+    let result = crate::check_roundtrip_integrity_with_output(
+        input,
+        config.base.cwr_version,
+        config.charset_override.as_deref(),
+        config.output_filename.as_deref(),
+    );
+    let elapsed_time = start_time.elapsed();
+
+    let count = match result {
+        Ok(c) => c,
+        Err(e) => {
+            status_error!("Error processing stdin after {:.2?}: {}", elapsed_time, e);
+            process::exit(Outcome::Error.exit_code());
+        }
+    };
+    let outcome = check_failure_policy(config, input);
+
+    let action = if config.output_filename.is_some() { "validated" } else { "checked" };
+    status!(
+        "Successfully {} {} CWR records from stdin in {:.2?}",
+        action,
+        allegro_cwr::format_int_with_commas(count as i64),
+        elapsed_time
+    );
+    if outcome != Outcome::Passed {
+        process::exit(outcome.exit_code());
+    }
+}
+
+fn process_files(config: &Config, start_time: Instant) {
+    let mut summary = BatchSummary::default();
+    let mut outcome = Outcome::Passed;
+
+    for filename in &config.base.input_files {
+        status!("Validating CWR file: {}", filename);
+
+        let output_filename = get_output_filename_with_default_extension(
+            config.output_filename.as_deref(),
+            filename,
+            config.base.input_files.len(),
+            summary.files_processed(),
+            "validated",
+        );
+
+        let result = crate::check_roundtrip_integrity_with_output(
+            filename,
+            config.base.cwr_version,
+            config.charset_override.as_deref(),
+            output_filename.as_deref(),
+        );
+
+        if config.run_rules {
+            run_rules(config, filename.into());
+        }
+
+        match result {
+            Ok(count) => {
+                summary.add_success(filename, count);
+                outcome = outcome.max(check_failure_policy(config, filename.into()));
+            }
+            Err(e) => {
+                status_error!("Error processing file '{}': {}", filename, e);
+                summary.add_failure(filename, &e);
+                outcome = outcome.max(Outcome::Error);
+            }
+        }
+
+        println!();
+    }
+
+    let elapsed_time = start_time.elapsed();
+
+    if config.base.input_files.len() == 1 {
+        let failed_files = summary.failed_files();
+        if !failed_files.is_empty() {
+            status_error!("Failed to process {} file(s): {}", failed_files.len(), failed_files.join(", "));
+            process::exit(Outcome::Error.exit_code());
+        }
+
+        status!(
+            "Validated {} CWR records from '{}' in {:.2?}",
+            allegro_cwr::format_int_with_commas(summary.total_records() as i64),
+            &config.base.input_files[0],
+            elapsed_time
+        );
+    } else {
+        summary.print("Validated", elapsed_time);
+        if !summary.failed_files().is_empty() {
+            process::exit(Outcome::Error.exit_code());
+        }
+    }
+    if outcome != Outcome::Passed {
+        process::exit(outcome.exit_code());
+    }
+}
+
+/// With `--fail-on`, lists the findings in `input` the policy treats as fatal; without it, text mode always passes
+fn check_failure_policy(config: &Config, input: CwrInput) -> Outcome {
+    let Some(policy) = &config.fail_on else {
+        return Outcome::Passed;
+    };
+    let display_name = input.name();
+    let mut engine = if config.run_rules { rule_engine(config) } else { crate::RuleEngine::new() };
+    let report =
+        match crate::build_file_report(input, config.base.cwr_version, config.charset_override.as_deref(), &mut engine)
+        {
+            Ok(report) => report,
+            Err(e) => {
+                status_error!("Error checking '{}': {}", display_name, e);
+                return Outcome::Error;
+            }
+        };
+
+    let fatal: Vec<_> = report.fatal_entries(policy).collect();
+    for entry in &fatal {
+        eprintln!(
+            "{}:{}: {} {}: {}",
+            display_name,
+            entry.line,
+            entry.category.as_str(),
+            entry.record_type,
+            entry.message
+        );
+    }
+    if fatal.is_empty() {
+        Outcome::Passed
+    } else {
+        status_error!("'{}' failed validation with {} fatal finding(s)", display_name, fatal.len());
+        Outcome::Failed
+    }
+}
+
+fn process_machine_report(config: &Config) {
+    let policy = config.fail_on.clone().unwrap_or_default();
+    let build_report = |input: CwrInput| {
+        let mut engine = rule_engine(config);
+        match crate::build_file_report(input, config.base.cwr_version, config.charset_override.as_deref(), &mut engine)
+        {
+            Ok(mut report) => {
+                report.apply_policy(&policy);
+                report
+            }
+            Err(e) => {
+                status_error!("Error processing '{}': {}", input.name(), e);
+                FileReport::processing_error(input.name(), e.to_string())
+            }
+        }
+    };
+
+    let reports: Vec<FileReport> = if config.base.read_stdin {
+        let content = read_stdin();
+        vec![build_report(CwrInput::Bytes { name: "-", content: &content })]
+    } else {
+        config.base.input_files.iter().map(|filename| build_report(filename.into())).collect()
+    };
+
+    write_reports(config, &reports);
+
+    let outcome = reports.iter().map(FileReport::outcome).max().unwrap_or(Outcome::Passed);
+    if outcome != Outcome::Passed {
+        process::exit(outcome.exit_code());
+    }
+}
+
+fn write_reports(config: &Config, reports: &[FileReport]) {
+    let mut writer = create_output(config.output_filename.as_deref()).unwrap_or_else(|e| {
+        status_error!("Error creating report file '{}': {}", config.output_filename.as_deref().unwrap_or_default(), e);
+        process::exit(Outcome::Error.exit_code());
+    });
+
+    let result = match config.format {
+        ReportFormat::Sarif => crate::write_sarif_report(reports, &rule_engine(config), &mut writer),
+        _ => crate::write_json_report(reports, &mut writer),
+    };
+    let result = result.and_then(|()| Ok(writer.write_all(b"\n").and_then(|()| writer.flush())?));
+    if let Err(e) = result {
+        status_error!("Error writing report: {}", e);
+        process::exit(Outcome::Error.exit_code());
+    }
+}
+
+/// Records are checked one line at a time, so inputs need no HDR/GRH/GRT/TRL
+fn process_fragments(config: &Config) {
+    let version = config.base.cwr_version.unwrap_or(FRAGMENT_DEFAULT_VERSION);
+    let policy = config.fail_on.clone().unwrap_or_default();
+    let build_report = |name: &str, reader: &mut dyn std::io::Read| match crate::build_fragment_report(
+        name,
+        reader,
+        version,
+        config.charset_override.as_deref(),
+    ) {
+        Ok(mut report) => {
+            report.apply_policy(&policy);
+            report
+        }
+        Err(e) => {
+            status_error!("Error processing '{}': {}", name, e);
+            FileReport::processing_error(name, e.to_string())
+        }
+    };
+
+    let reports: Vec<FileReport> = if config.base.read_stdin {
+        vec![build_report("-", &mut std::io::stdin().lock())]
+    } else {
+        config
+            .base
+            .input_files
+            .iter()
+            .map(|filename| match allegro_cwr::compression::open_input(filename) {
+                Ok(mut file) => build_report(filename, &mut file),
+                Err(e) => {
+                    status_error!("Error opening '{}': {}", filename, e);
+                    FileReport::processing_error(filename, e.to_string())
+                }
+            })
+            .collect()
+    };
+
+    if config.format == ReportFormat::Text {
+        for report in reports.iter().filter(|report| report.error.is_none()) {
+            for entry in &report.entries {
+                let field = entry.field.as_ref().map(|field| format!(" {}", field)).unwrap_or_default();
+                println!(
+                    "{}:{}: {} [{}] {}{}: {}",
+                    report.file,
+                    entry.line,
+                    entry.category.as_str(),
+                    if policy.is_fatal(entry) { "fatal" } else { "ok" },
+                    entry.record_type,
+                    field,
+                    entry.message
+                );
+            }
+            println!(
+                "{}: {} records, {} findings, {}",
+                report.file,
+                allegro_cwr::format_int_with_commas(report.record_count as i64),
+                allegro_cwr::format_int_with_commas(report.entries.len() as i64),
+                if report.passed { "passed" } else { "failed" }
+            );
+        }
+    } else {
+        write_reports(config, &reports);
+    }
+
+    let outcome = reports.iter().map(FileReport::outcome).max().unwrap_or(Outcome::Passed);
+    if outcome != Outcome::Passed {
+        process::exit(outcome.exit_code());
+    }
+}
+
+fn rule_engine(config: &Config) -> crate::RuleEngine {
+    let mut engine = crate::RuleEngine::with_default_rules();
+    #[cfg(feature = "online")]
+    if let Some(lookup_config) = &config.online {
+        match crate::online::LookupClient::new(lookup_config.clone()) {
+            Ok(client) => {
+                for rule in crate::online::online_rules(std::rc::Rc::new(std::cell::RefCell::new(client))) {
+                    engine.add_rule(rule);
+                }
+            }
+            Err(e) => {
+                status_error!("Error reading the lookup cache: {}", e);
+                process::exit(Outcome::Error.exit_code());
+            }
+        }
+    }
+    for rule_id in &config.disabled_rules {
+        engine.disable(*rule_id);
+    }
+    engine
+}
+
+fn run_rules(config: &Config, input: CwrInput) {
+    let mut engine = rule_engine(config);
+    match crate::validate_cwr_file(input, config.base.cwr_version, &mut engine) {
+        Ok(report) => print!("{}", report),
+        Err(e) => status_error!("Error running validation rules on '{}': {}", input.name(), e),
+    }
+}
+
+fn print_rules() {
+    for (rule_id, description, _) in crate::RuleEngine::with_default_rules().rules() {
+        println!("{}  {}", rule_id, description);
+    }
+}
+
+fn print_help() {
+    eprintln!("Usage: cwr-validate [OPTIONS] [FILES...]");
+    eprintln!();
+    eprintln!("Arguments:");
+    eprintln!(
+        "  [FILES...]          CWR files to check for validity. If no files specified or a file is -, reads from stdin"
+    );
+    eprintln!("                      Directories and patterns such as \'*.V21\' expand to the matching CWR files");
+    eprintln!();
+    eprintln!("Options:");
+    eprintln!("  -o, --output <file>      Output file path (defaults to <input>.validated or stdout for stdin)");
+    eprintln!("      --cwr <version>      CWR version (2.0, 2.1, 2.2). Auto-detected from filename (.Vxx) or file content if not specified");
+    eprintln!("      --charset <charset>  Override character set when missing in HDR record (e.g., UTF-8, ASCII)");
+    eprintln!("      --rules              Run CISAC-numbered validation rules");
+    eprintln!("      --disable-rule <id>  Disable a validation rule (e.g., GRT-FLD-003); implies --rules");
+    eprintln!("      --list-rules         List available validation rules");
+    #[cfg(feature = "online")]
+    {
+        eprintln!(
+            "      --online <config>    Also look up ISWCs and IPI name numbers online (rules NWR/SWR/SPU-FLD-901);"
+        );
+        eprintln!("                           <config> is a JSON file with iswc_url, ipi_url, headers and cache_path");
+        eprintln!("                           (implies --rules)");
+    }
+    eprintln!("      --fragment           Check record lines on their own, without HDR/TRL: each line is parsed");
+    eprintln!("                           and round-tripped in --cwr (default 2.2) and --charset (default ASCII)");
+    eprintln!("      --format <format>    Report format: text (default), json or sarif. Machine-readable");
+    eprintln!(
+        "                           reports include rule results and exit non-zero on errors; -o sets the report file"
+    );
+    eprintln!("      --fail-on <list>     Finding categories that fail validation, comma-separated, or all/none:");
+    eprintln!("                           parse-warning, parse-error, roundtrip, length-mismatch, ambiguous,");
+    eprintln!("                           rule-field, rule. Defaults to parse-error,roundtrip,length-mismatch,rule");
+    eprintln!("                           for machine-readable reports; text mode only fails on them when given");
+    eprintln!("  -q, --quiet              Only print errors");
+    eprintln!("  -v, --verbose            Also print info logging");
+    eprintln!("      --log-format <fmt>   Message format on stderr: text (default) or json");
+    eprintln!("  -h, --help               Show this help message");
+    eprintln!();
+    eprintln!("Examples:");
+    eprintln!("  cwr-validate input.cwr                    # Check single CWR file");
+    eprintln!("  cwr-validate *.cwr                        # Check multiple CWR files");
+    eprintln!("  cwr-validate -o normalized.cwr input.cwr  # Validate and write normalized output");
+    eprintln!("  cwr-validate --cwr 2.2 input.cwr          # Force CWR version 2.2");
+    eprintln!("  cwr-validate --rules input.cwr            # Report CISAC validation rule violations");
+    eprintln!("  cwr-validate --format sarif *.cwr > r.sarif # SARIF report for CI");
+    eprintln!("  cwr-validate --fail-on ambiguous,rule *.cwr # Also fail on ambiguous round-trip differences");
+    eprintln!("  cat input.cwr | cwr-validate              # Process CWR data from stdin");
+    eprintln!("  curl -s $URL | cwr-validate -             # Check a file as it is downloaded");
+    eprintln!("  echo 'ALT...' | cwr-validate --fragment --cwr 2.1 # Check a single record line");
+    eprintln!("  find . -name '*.cwr' | xargs cwr-validate # Process all CWR files recursively");
+    eprintln!();
+    eprintln!("Exit codes:");
+    eprintln!("  0  No fatal findings");
+    eprintln!("  1  A file could not be read or processed, or the arguments are invalid");
+    eprintln!("  2  A file has findings that fail validation");
+}
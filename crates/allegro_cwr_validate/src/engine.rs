@@ -0,0 +1,430 @@
+//! Rule engine for CISAC-numbered CWR validation checks
+//!
+//! Each rule carries the record type, validation section and item number from the CWR specification, so
+//! results can be reported the same way societies report them in MSG records.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::str::FromStr;
+
+use allegro_cwr::cwr_registry::CwrRegistry;
+use allegro_cwr::ParsedRecord;
+
+use crate::{CwrInput, RoundtripError};
+
+/// Section of the specification a validation number belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RuleScope {
+    File,
+    Record,
+    Transaction,
+    Field,
+}
+
+impl RuleScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RuleScope::File => "FIL",
+            RuleScope::Record => "REC",
+            RuleScope::Transaction => "TRN",
+            RuleScope::Field => "FLD",
+        }
+    }
+}
+
+/// What the specification says happens when a rule fails
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RejectionLevel {
+    Field,
+    Record,
+    Transaction,
+    Group,
+    EntireFile,
+}
+
+impl RejectionLevel {
+    /// Code used in the Message Level field of MSG records
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RejectionLevel::Field => "F",
+            RejectionLevel::Record => "R",
+            RejectionLevel::Transaction => "T",
+            RejectionLevel::Group => "G",
+            RejectionLevel::EntireFile => "E",
+        }
+    }
+}
+
+/// Identifies a validation rule, e.g. `GRT-FLD-002` for GRT field level validation 2
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RuleId {
+    pub record_type: &'static str,
+    pub scope: RuleScope,
+    pub number: u16,
+}
+
+impl RuleId {
+    pub const fn new(record_type: &'static str, scope: RuleScope, number: u16) -> Self {
+        RuleId { record_type, scope, number }
+    }
+
+    /// Three-digit validation number as used in MSG records
+    pub fn validation_number(&self) -> String {
+        format!("{:03}", self.number)
+    }
+}
+
+impl fmt::Display for RuleId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}-{:03}", self.record_type, self.scope.as_str(), self.number)
+    }
+}
+
+impl FromStr for RuleId {
+    type Err = String;
+
+    /// Parses a rule ID, resolving the record type against the known CWR record types
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.trim().split('-').collect();
+        let [record_type, scope, number] = parts.as_slice() else {
+            return Err(format!("Invalid rule ID '{}', expected e.g. GRT-FLD-002", s));
+        };
+        let record_type = allegro_cwr::get_all_record_type_codes()
+            .into_iter()
+            .find(|code| code.eq_ignore_ascii_case(record_type))
+            .ok_or_else(|| format!("Unknown record type '{}' in rule ID '{}'", record_type, s))?;
+        let scope = match scope.to_ascii_uppercase().as_str() {
+            "FIL" => RuleScope::File,
+            "REC" => RuleScope::Record,
+            "TRN" => RuleScope::Transaction,
+            "FLD" => RuleScope::Field,
+            _ => return Err(format!("Unknown rule scope '{}' in rule ID '{}'", scope, s)),
+        };
+        let number = number.parse::<u16>().map_err(|_| format!("Invalid rule number in rule ID '{}'", s))?;
+        Ok(RuleId { record_type, scope, number })
+    }
+}
+
+/// A single failed check
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuleViolation {
+    pub rule_id: RuleId,
+    pub level: RejectionLevel,
+    pub line_number: usize,
+    /// Record type of the offending record (may differ from the rule's record type)
+    pub record_type: String,
+    pub message: String,
+    /// Field the rule applies to, if it is a field level check
+    pub field: Option<&'static str>,
+    pub value: Option<String>,
+}
+
+impl RuleViolation {
+    pub fn with_field(mut self, field: &'static str, value: impl Into<String>) -> Self {
+        self.field = Some(field);
+        self.value = Some(value.into());
+        self
+    }
+}
+
+/// A record as seen by the rules
+#[derive(Debug, Clone)]
+pub struct RecordRef<'a> {
+    pub line_number: usize,
+    pub record: &'a CwrRegistry,
+    /// Zero-based position of the record in the file
+    pub index: usize,
+    pub previous_record_type: Option<&'a str>,
+    /// True if this record is the header of a new transaction (e.g. NWR inside an ACK group is not)
+    pub starts_transaction: bool,
+    /// Version the file is read as, when known; version 2.1 HDR records do not declare it
+    pub cwr_version: Option<f32>,
+}
+
+/// Records making up one transaction: the header followed by its detail records
+#[derive(Debug, Clone, Default)]
+pub struct Transaction {
+    pub records: Vec<(usize, CwrRegistry)>,
+}
+
+impl Transaction {
+    pub fn header(&self) -> Option<&(usize, CwrRegistry)> {
+        self.records.first()
+    }
+
+    pub fn header_line(&self) -> usize {
+        self.records.first().map_or(0, |(line, _)| *line)
+    }
+
+    pub fn header_type(&self) -> &str {
+        self.records.first().map_or("", |(_, record)| record.record_type())
+    }
+}
+
+/// State available once all records have been seen
+#[derive(Debug, Clone, Default)]
+pub struct FileSummary {
+    pub record_count: usize,
+    pub last_record_type: Option<String>,
+    pub last_line_number: usize,
+}
+
+/// A single numbered validation check
+pub trait ValidationRule {
+    fn id(&self) -> RuleId;
+
+    fn description(&self) -> &'static str;
+
+    fn level(&self) -> RejectionLevel;
+
+    /// Called for every record in file order
+    fn check_record(&mut self, _record: &RecordRef<'_>) -> Vec<RuleViolation> {
+        Vec::new()
+    }
+
+    /// Called for each complete transaction
+    fn check_transaction(&mut self, _transaction: &Transaction) -> Vec<RuleViolation> {
+        Vec::new()
+    }
+
+    /// Called once after the last record
+    fn finish(&mut self, _summary: &FileSummary) -> Vec<RuleViolation> {
+        Vec::new()
+    }
+
+    /// Builds a violation for this rule
+    fn violation(&self, line_number: usize, record_type: &str, message: String) -> RuleViolation {
+        RuleViolation {
+            rule_id: self.id(),
+            level: self.level(),
+            line_number,
+            record_type: record_type.to_string(),
+            message,
+            field: None,
+            value: None,
+        }
+    }
+}
+
+/// Results of a validation run, keyed by rule ID
+#[derive(Debug, Clone, Default)]
+pub struct ValidationReport {
+    pub results: BTreeMap<RuleId, Vec<RuleViolation>>,
+    pub record_count: usize,
+}
+
+impl ValidationReport {
+    pub fn is_empty(&self) -> bool {
+        self.results.is_empty()
+    }
+
+    pub fn violation_count(&self) -> usize {
+        self.results.values().map(Vec::len).sum()
+    }
+
+    pub fn violations(&self) -> impl Iterator<Item = &RuleViolation> {
+        self.results.values().flatten()
+    }
+
+    pub fn for_rule(&self, rule_id: &RuleId) -> &[RuleViolation] {
+        self.results.get(rule_id).map_or(&[], Vec::as_slice)
+    }
+
+    /// Most severe rejection level among all violations
+    pub fn highest_level(&self) -> Option<RejectionLevel> {
+        self.violations().map(|v| v.level).max()
+    }
+
+    fn add(&mut self, violations: Vec<RuleViolation>) {
+        for violation in violations {
+            self.results.entry(violation.rule_id).or_default().push(violation);
+        }
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.results.is_empty() {
+            return writeln!(f, "RULES PASSED: No rule violations in {} records", self.record_count);
+        }
+        writeln!(
+            f,
+            "RULES FAILED: {} violations of {} rules in {} records:",
+            self.violation_count(),
+            self.results.len(),
+            self.record_count
+        )?;
+        for (rule_id, violations) in &self.results {
+            let lines: Vec<String> = violations.iter().take(5).map(|v| v.line_number.to_string()).collect();
+            let more = if violations.len() > 5 { ", ..." } else { "" };
+            writeln!(
+                f,
+                "  {} ({}): {} occurrences (lines {}{})",
+                rule_id,
+                violations[0].level.as_str(),
+                violations.len(),
+                lines.join(", "),
+                more
+            )?;
+            writeln!(f, "    {}", violations[0].message)?;
+        }
+        Ok(())
+    }
+}
+
+/// Runs a set of validation rules over a stream of records
+pub struct RuleEngine {
+    rules: Vec<Box<dyn ValidationRule>>,
+    disabled: BTreeSet<RuleId>,
+    report: ValidationReport,
+    transaction: Transaction,
+    previous_record_type: Option<String>,
+    group_transaction_type: Option<String>,
+    last_line_number: usize,
+    cwr_version: Option<f32>,
+}
+
+impl Default for RuleEngine {
+    fn default() -> Self {
+        Self::with_default_rules()
+    }
+}
+
+impl RuleEngine {
+    /// Creates an engine with no rules
+    pub fn new() -> Self {
+        RuleEngine {
+            rules: Vec::new(),
+            disabled: BTreeSet::new(),
+            report: ValidationReport::default(),
+            transaction: Transaction::default(),
+            previous_record_type: None,
+            group_transaction_type: None,
+            last_line_number: 0,
+            cwr_version: None,
+        }
+    }
+
+    /// Creates an engine with all built-in rules enabled
+    pub fn with_default_rules() -> Self {
+        let mut engine = Self::new();
+        for rule in crate::rules::default_rules() {
+            engine.add_rule(rule);
+        }
+        engine
+    }
+
+    pub fn add_rule(&mut self, rule: Box<dyn ValidationRule>) {
+        self.rules.push(rule);
+    }
+
+    pub fn disable(&mut self, rule_id: RuleId) {
+        self.disabled.insert(rule_id);
+    }
+
+    pub fn enable(&mut self, rule_id: RuleId) {
+        self.disabled.remove(&rule_id);
+    }
+
+    pub fn is_enabled(&self, rule_id: &RuleId) -> bool {
+        !self.disabled.contains(rule_id)
+    }
+
+    /// All registered rules with their description and enabled state
+    pub fn rules(&self) -> Vec<(RuleId, &'static str, bool)> {
+        self.rules.iter().map(|rule| (rule.id(), rule.description(), self.is_enabled(&rule.id()))).collect()
+    }
+
+    /// Sets the version the following records are read as, for rules whose checks depend on it
+    pub fn set_cwr_version(&mut self, cwr_version: Option<f32>) {
+        self.cwr_version = cwr_version;
+    }
+
+    /// Feeds the next record from the parser to the rules, taking the version from its context
+    pub fn check_parsed_record(&mut self, parsed_record: &ParsedRecord) {
+        self.cwr_version = Some(parsed_record.context.cwr_version);
+        self.check_record(parsed_record.line_number, &parsed_record.record);
+    }
+
+    /// Feeds the next record (in file order) to the rules
+    pub fn check_record(&mut self, line_number: usize, record: &CwrRegistry) {
+        let record_type = record.record_type();
+        if let CwrRegistry::Grh(grh) = record {
+            self.group_transaction_type = Some(grh.transaction_type.as_str().to_string());
+        }
+        let starts_transaction = match self.group_transaction_type.as_deref() {
+            Some(group_type) => record_type == group_type,
+            None => matches!(record, CwrRegistry::Agr(_) | CwrRegistry::Nwr(_) | CwrRegistry::Ack(_)),
+        };
+        let ends_transaction = starts_transaction || matches!(record_type, "GRH" | "GRT" | "TRL" | "HDR");
+        if ends_transaction {
+            self.flush_transaction();
+        }
+
+        let record_ref = RecordRef {
+            line_number,
+            record,
+            index: self.report.record_count,
+            previous_record_type: self.previous_record_type.as_deref(),
+            starts_transaction,
+            cwr_version: self.cwr_version,
+        };
+        for rule in self.rules.iter_mut() {
+            if !self.disabled.contains(&rule.id()) {
+                self.report.add(rule.check_record(&record_ref));
+            }
+        }
+
+        if starts_transaction || (!ends_transaction && !self.transaction.records.is_empty()) {
+            self.transaction.records.push((line_number, record.clone()));
+        }
+
+        self.report.record_count += 1;
+        self.previous_record_type = Some(record_type.to_string());
+        self.last_line_number = line_number;
+    }
+
+    /// Completes the run and returns the report
+    pub fn finish(&mut self) -> ValidationReport {
+        self.flush_transaction();
+        let summary = FileSummary {
+            record_count: self.report.record_count,
+            last_record_type: self.previous_record_type.take(),
+            last_line_number: self.last_line_number,
+        };
+        for rule in self.rules.iter_mut() {
+            if !self.disabled.contains(&rule.id()) {
+                self.report.add(rule.finish(&summary));
+            }
+        }
+        self.last_line_number = 0;
+        self.cwr_version = None;
+        std::mem::take(&mut self.report)
+    }
+
+    fn flush_transaction(&mut self) {
+        if self.transaction.records.is_empty() {
+            return;
+        }
+        let transaction = std::mem::take(&mut self.transaction);
+        for rule in self.rules.iter_mut() {
+            if !self.disabled.contains(&rule.id()) {
+                self.report.add(rule.check_transaction(&transaction));
+            }
+        }
+    }
+}
+
+/// Runs the rule engine over a CWR file
+pub fn validate_cwr_file<'a>(
+    input: impl Into<CwrInput<'a>>, cwr_version: Option<f32>, engine: &mut RuleEngine,
+) -> Result<ValidationReport, RoundtripError> {
+    let record_stream = input.into().records_with_charset(cwr_version, None)?;
+
+    for parsed_result in record_stream {
+        let parsed_record = parsed_result.map_err(|e| RoundtripError::CwrParsing(format!("Parse error: {}", e)))?;
+        engine.check_parsed_record(&parsed_record);
+    }
+
+    Ok(engine.finish())
+}
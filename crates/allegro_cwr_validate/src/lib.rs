@@ -1,38 +1,119 @@
 use std::collections::HashMap;
-use std::io::Write;
+use std::io::{BufReader, Cursor, Read, Write};
 
-use allegro_cwr::{cwr_registry::CwrRegistry, domain_types::CharacterSet, process_cwr_stream_with_version_and_charset};
+use allegro_cwr::{
+    cwr_registry::CwrRegistry, domain_types::CharacterSet, process_cwr_bytes_stream, process_cwr_stream_with_options,
+    CwrParseError, ParseOptions, ParsedRecord,
+};
 use thiserror::Error;
 
+pub mod cli;
+pub mod engine;
+#[cfg(feature = "online")]
+pub mod online;
+pub mod report;
+pub mod rules;
+
+pub use engine::{
+    validate_cwr_file, RejectionLevel, RuleEngine, RuleId, RuleScope, RuleViolation, ValidationReport, ValidationRule,
+};
+pub use report::{
+    build_file_report, build_fragment_report, write_json_report, write_sarif_report, FailurePolicy, FileReport,
+    FindingCategory, Outcome, ReportEntry, ReportFormat, Severity,
+};
+
+/// Log target of the round-trip report; `cwr-validate` prints its info events to stdout and warnings to stderr
+pub const REPORT_TARGET: &str = "allegro_cwr_validate::report";
+
 #[derive(Error, Debug)]
 pub enum RoundtripError {
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
     #[error("CWR parsing error: {0}")]
     CwrParsing(String),
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// CWR content to check. The checks read it twice, once parsed and once as the original lines, so content that
+/// can only be read once (such as stdin) is passed in memory.
+#[derive(Debug, Clone, Copy)]
+pub enum CwrInput<'a> {
+    File(&'a str),
+    Bytes {
+        /// Stands in for the file name in messages and reports
+        name: &'a str,
+        content: &'a [u8],
+    },
+}
+
+impl<'a> CwrInput<'a> {
+    /// The file name, or the name given to content held in memory
+    pub fn name(&self) -> &'a str {
+        match self {
+            CwrInput::File(path) => path,
+            CwrInput::Bytes { name, .. } => name,
+        }
+    }
+
+    pub(crate) fn records(
+        &self, options: &ParseOptions,
+    ) -> Result<Box<dyn Iterator<Item = Result<ParsedRecord, CwrParseError>> + 'a>, CwrParseError> {
+        Ok(match *self {
+            CwrInput::File(path) => Box::new(process_cwr_stream_with_options(path, options)?),
+            CwrInput::Bytes { content, .. } => Box::new(process_cwr_bytes_stream(content, options)?),
+        })
+    }
+
+    /// Parses the input with an optional version hint and character set override
+    pub(crate) fn records_with_charset(
+        &self, cwr_version: Option<f32>, charset_override: Option<&str>,
+    ) -> Result<Box<dyn Iterator<Item = Result<ParsedRecord, CwrParseError>> + 'a>, RoundtripError> {
+        let options = ParseOptions {
+            version_hint: cwr_version,
+            charset_override: charset_override.map(str::to_string),
+            ..Default::default()
+        };
+        self.records(&options).map_err(|e| RoundtripError::CwrParsing(format!("Failed to open CWR file: {}", e)))
+    }
+}
+
+impl<'a> From<&'a str> for CwrInput<'a> {
+    fn from(path: &'a str) -> Self {
+        CwrInput::File(path)
+    }
+}
+
+impl<'a> From<&'a String> for CwrInput<'a> {
+    fn from(path: &'a String) -> Self {
+        CwrInput::File(path)
+    }
 }
 
 /// Check round-trip integrity by parsing CWR records and serializing them back
-pub fn check_roundtrip_integrity(input_path: &str, cwr_version: Option<f32>) -> Result<usize, RoundtripError> {
-    check_roundtrip_integrity_with_charset(input_path, cwr_version, None)
+pub fn check_roundtrip_integrity<'a>(
+    input: impl Into<CwrInput<'a>>, cwr_version: Option<f32>,
+) -> Result<usize, RoundtripError> {
+    check_roundtrip_integrity_with_charset(input, cwr_version, None)
 }
 
 /// Check round-trip integrity and optionally write normalized output to a file
-pub fn check_roundtrip_integrity_with_output(
-    input_path: &str, cwr_version: Option<f32>, charset_override: Option<&str>, output_path: Option<&str>,
+pub fn check_roundtrip_integrity_with_output<'a>(
+    input: impl Into<CwrInput<'a>>, cwr_version: Option<f32>, charset_override: Option<&str>, output_path: Option<&str>,
 ) -> Result<usize, RoundtripError> {
     if let Some(output_file) = output_path {
         let file = std::fs::File::create(output_file)?;
-        check_roundtrip_integrity_to_writer(input_path, cwr_version, charset_override, file)
+        check_roundtrip_integrity_to_writer(input, cwr_version, charset_override, std::io::BufWriter::new(file))
     } else {
-        check_roundtrip_integrity_with_charset(input_path, cwr_version, charset_override)
+        check_roundtrip_integrity_with_charset(input, cwr_version, charset_override)
     }
 }
 
 /// Check round-trip integrity and write normalized output to a writer
-pub fn check_roundtrip_integrity_to_writer<W: Write>(
-    input_path: &str, cwr_version: Option<f32>, charset_override: Option<&str>, writer: W,
+pub fn check_roundtrip_integrity_to_writer<'a, W: Write>(
+    input: impl Into<CwrInput<'a>>, cwr_version: Option<f32>, charset_override: Option<&str>, writer: W,
 ) -> Result<usize, RoundtripError> {
+    let input = input.into();
     let mut writer = writer;
     let mut record_count = 0;
     let mut diff_map: HashMap<String, Vec<usize>> = HashMap::new();
@@ -41,11 +122,11 @@ pub fn check_roundtrip_integrity_to_writer<W: Write>(
     let mut detected_version: Option<f32> = None;
     let mut warning_counts: HashMap<String, Vec<usize>> = HashMap::new();
     let mut character_set: Option<CharacterSet> = None;
+    let mut serialized_bytes = Vec::new();
 
-    let original_lines: Vec<String> = std::fs::read_to_string(input_path)?.lines().map(|s| s.to_string()).collect();
+    let mut original_lines = OriginalLines::new(input)?;
 
-    let record_stream = process_cwr_stream_with_version_and_charset(input_path, cwr_version, charset_override)
-        .map_err(|e| RoundtripError::CwrParsing(format!("Failed to open CWR file: {}", e)))?;
+    let record_stream = input.records_with_charset(cwr_version, charset_override)?;
 
     for parsed_result in record_stream {
         match parsed_result {
@@ -53,10 +134,15 @@ pub fn check_roundtrip_integrity_to_writer<W: Write>(
                 if detected_version.is_none() {
                     detected_version = Some(parsed_record.context.cwr_version);
                     character_set = parsed_record.context.character_set.clone();
-                    println!("Detected CWR version: {}", parsed_record.context.cwr_version);
+                    tracing::info!(
+                        target: REPORT_TARGET,
+                        file = input.name(),
+                        version = %parsed_record.context.cwr_version,
+                        "Detected CWR version: {}",
+                        parsed_record.context.cwr_version
+                    );
                 }
 
-                let line_index = parsed_record.line_number - 1;
                 let version = allegro_cwr::domain_types::CwrVersion(parsed_record.context.cwr_version);
 
                 // Check if this is an HDR record and charset override is provided
@@ -74,31 +160,26 @@ pub fn check_roundtrip_integrity_to_writer<W: Write>(
 
                 // Use character set from context, or default to ASCII
                 let charset_for_encoding = character_set.as_ref().unwrap_or(&CharacterSet::ASCII);
-                let serialized_bytes = record_to_write.to_cwr_record_bytes(&version, charset_for_encoding);
-
-                // Convert bytes to string for writing (assuming the file system encoding matches the character set)
-                let serialized_line = match charset_for_encoding {
-                    CharacterSet::ASCII => {
-                        // For ASCII, ensure all bytes are valid ASCII
-                        if serialized_bytes.iter().all(|&b| b <= 127) {
-                            String::from_utf8_lossy(&serialized_bytes).to_string()
-                        } else {
-                            return Err(RoundtripError::CwrParsing("Non-ASCII bytes found in ASCII mode".to_string()));
-                        }
-                    }
-                    _ => {
-                        // For UTF-8 and other character sets, convert bytes to string
-                        String::from_utf8_lossy(&serialized_bytes).to_string()
-                    }
-                };
+                serialized_bytes.clear();
+                record_to_write
+                    .write_cwr_record(&mut serialized_bytes, &version, charset_for_encoding)
+                    .map_err(|e| RoundtripError::CwrParsing(format!("Serialization error: {}", e)))?;
+
+                // For ASCII, ensure all bytes are valid ASCII
+                if *charset_for_encoding == CharacterSet::ASCII && !serialized_bytes.is_ascii() {
+                    return Err(RoundtripError::CwrParsing("Non-ASCII bytes found in ASCII mode".to_string()));
+                }
 
-                writeln!(writer, "{}", serialized_line)?;
+                // Output is written in the file's own character set
+                writer.write_all(&serialized_bytes)?;
+                writer.write_all(b"\n")?;
+                let serialized_line = allegro_cwr::encoding::decode_lossy(&serialized_bytes, charset_for_encoding);
 
-                if line_index < original_lines.len() {
-                    let original_line = &original_lines[line_index];
+                if let Some(original_line) = original_lines.get(parsed_record.line_number)? {
+                    let original_line = allegro_cwr::encoding::decode_lossy(original_line, charset_for_encoding);
 
                     check_character_differences(
-                        original_line,
+                        &original_line,
                         &serialized_line,
                         parsed_record.record.record_type(),
                         parsed_record.line_number,
@@ -121,29 +202,38 @@ pub fn check_roundtrip_integrity_to_writer<W: Write>(
             }
         }
     }
-    println!();
-
-    report_validation_results(&warning_counts, &extra_chars_map, &diff_map, &diff_examples, record_count)?;
+    writer.flush()?;
+    tracing::info!(target: REPORT_TARGET, "");
+
+    report_validation_results(
+        input.name(),
+        &warning_counts,
+        &extra_chars_map,
+        &diff_map,
+        &diff_examples,
+        record_count,
+    )?;
     Ok(record_count)
 }
 
 /// Check round-trip integrity with optional character set override
-pub fn check_roundtrip_integrity_with_charset(
-    input_path: &str, cwr_version: Option<f32>, charset_override: Option<&str>,
+pub fn check_roundtrip_integrity_with_charset<'a>(
+    input: impl Into<CwrInput<'a>>, cwr_version: Option<f32>, charset_override: Option<&str>,
 ) -> Result<usize, RoundtripError> {
+    let input = input.into();
     let mut record_count = 0;
     let mut diff_map: HashMap<String, Vec<usize>> = HashMap::new(); // key: diff description, value: line numbers
     let mut diff_examples: HashMap<String, (String, String, usize)> = HashMap::new(); // key: diff description, value: (original, serialized, line_number)
     let mut extra_chars_map: HashMap<String, Vec<usize>> = HashMap::new(); // key: "record_type:extra_char", value: line numbers
     let mut detected_version: Option<f32> = None;
     let mut warning_counts: HashMap<String, Vec<usize>> = HashMap::new(); // key: warning description, value: line numbers
+    let mut serialized_bytes = Vec::new();
 
-    // Read original lines for comparison
-    let original_lines: Vec<String> = std::fs::read_to_string(input_path)?.lines().map(|s| s.to_string()).collect();
+    // Original lines are read alongside the records for comparison
+    let mut original_lines = OriginalLines::new(input)?;
 
     // Use the allegro_cwr streaming parser with character set override if needed
-    let record_stream = process_cwr_stream_with_version_and_charset(input_path, cwr_version, charset_override)
-        .map_err(|e| RoundtripError::CwrParsing(format!("Failed to open CWR file: {}", e)))?;
+    let record_stream = input.records_with_charset(cwr_version, charset_override)?;
 
     for parsed_result in record_stream {
         match parsed_result {
@@ -151,23 +241,31 @@ pub fn check_roundtrip_integrity_with_charset(
                 // Capture the detected version from the first record
                 if detected_version.is_none() {
                     detected_version = Some(parsed_record.context.cwr_version);
-                    println!("Detected CWR version: {}", parsed_record.context.cwr_version);
+                    tracing::info!(
+                        target: REPORT_TARGET,
+                        file = input.name(),
+                        version = %parsed_record.context.cwr_version,
+                        "Detected CWR version: {}",
+                        parsed_record.context.cwr_version
+                    );
                 }
 
-                let line_index = parsed_record.line_number - 1; // Convert to 0-based index
-                if line_index < original_lines.len() {
-                    let original_line = &original_lines[line_index];
-
+                if let Some(original_line) = original_lines.get(parsed_record.line_number)? {
                     // Serialize the parsed record back to CWR line using byte-based API
                     let version = allegro_cwr::domain_types::CwrVersion(parsed_record.context.cwr_version);
                     let charset_for_encoding =
                         parsed_record.context.character_set.as_ref().unwrap_or(&CharacterSet::ASCII);
-                    let serialized_bytes = parsed_record.record.to_cwr_record_bytes(&version, charset_for_encoding);
-                    let serialized_line = String::from_utf8_lossy(&serialized_bytes).to_string();
+                    serialized_bytes.clear();
+                    parsed_record
+                        .record
+                        .write_cwr_record(&mut serialized_bytes, &version, charset_for_encoding)
+                        .map_err(|e| RoundtripError::CwrParsing(format!("Serialization error: {}", e)))?;
+                    let serialized_line = allegro_cwr::encoding::decode_lossy(&serialized_bytes, charset_for_encoding);
+                    let original_line = allegro_cwr::encoding::decode_lossy(original_line, charset_for_encoding);
 
                     // Check for character differences
                     check_character_differences(
-                        original_line,
+                        &original_line,
                         &serialized_line,
                         parsed_record.record.record_type(),
                         parsed_record.line_number,
@@ -192,20 +290,25 @@ pub fn check_roundtrip_integrity_with_charset(
             }
         }
     }
-    println!();
+    tracing::info!(target: REPORT_TARGET, "");
 
-    report_validation_results(&warning_counts, &extra_chars_map, &diff_map, &diff_examples, record_count)
+    report_validation_results(input.name(), &warning_counts, &extra_chars_map, &diff_map, &diff_examples, record_count)
 }
 
 fn report_validation_results(
-    warning_counts: &HashMap<String, Vec<usize>>, extra_chars_map: &HashMap<String, Vec<usize>>,
+    input_path: &str, warning_counts: &HashMap<String, Vec<usize>>, extra_chars_map: &HashMap<String, Vec<usize>>,
     diff_map: &HashMap<String, Vec<usize>>, diff_examples: &HashMap<String, (String, String, usize)>,
     record_count: usize,
 ) -> Result<usize, RoundtripError> {
     // Report all warnings in a consolidated section
     if !warning_counts.is_empty() || !extra_chars_map.is_empty() {
         let total_issues = warning_counts.len() + extra_chars_map.len();
-        println!("WARNINGS: Found {} distinct types of validation issues:", total_issues);
+        tracing::info!(
+            target: REPORT_TARGET,
+            file = input_path,
+            "WARNINGS: Found {} distinct types of validation issues:",
+            total_issues
+        );
 
         // First show parsing warnings with consistent formatting
         if !warning_counts.is_empty() {
@@ -214,7 +317,7 @@ fn report_validation_results(
             sorted_warnings.sort_by(|a, b| b.1.len().cmp(&a.1.len()).then(a.0.cmp(b.0)));
 
             for (warning, line_numbers) in sorted_warnings {
-                println!("{}: {}", warning, display_incidences(line_numbers));
+                tracing::info!(target: REPORT_TARGET, file = input_path, "{}: {}", warning, display_incidences(line_numbers));
             }
         }
 
@@ -224,7 +327,7 @@ fn report_validation_results(
             sorted_extra.sort_by_key(|(key, lines)| (key.as_str(), lines.len()));
 
             if !sorted_extra.is_empty() {
-                println!("\nAMBIGUOUS:");
+                tracing::info!(target: REPORT_TARGET, "\nAMBIGUOUS:");
             }
 
             for (extra_key, line_numbers) in sorted_extra {
@@ -234,25 +337,43 @@ fn report_validation_results(
                 let display_lines = display_incidences(line_numbers);
 
                 if *extra_info == "missing_optional_fields" {
-                    println!(
+                    tracing::info!(
+                        target: REPORT_TARGET,
+                        file = input_path,
+                        record_type = record_type,
                         "{}: missing optional fields (serializer adds proper padding): {}",
-                        record_type, display_lines
+                        record_type,
+                        display_lines
                     );
                 } else if *extra_info == "date_zero_padding" {
-                    println!(
+                    tracing::info!(
+                        target: REPORT_TARGET,
+                        file = input_path,
+                        record_type = record_type,
                         "{}: date fields with '00000000' treated as None (ambiguous: could be invalid date or empty field): {}",
-                        record_type, display_lines
+                        record_type,
+                        display_lines
                     );
                 } else {
-                    println!("{}: records with extra '{}': {}", record_type, extra_info, display_lines);
+                    tracing::info!(
+                        target: REPORT_TARGET,
+                        file = input_path,
+                        record_type = record_type,
+                        "{}: records with extra '{}': {}",
+                        record_type,
+                        extra_info,
+                        display_lines
+                    );
                 }
             }
         }
-        println!();
+        tracing::info!(target: REPORT_TARGET, "");
     }
 
     if !diff_map.is_empty() {
-        println!(
+        tracing::info!(
+            target: REPORT_TARGET,
+            file = input_path,
             "ROUNDTRIP FAILED: Found {} distinct diff types across {} total errors:",
             diff_map.len(),
             diff_map.values().map(|v| v.len()).sum::<usize>()
@@ -266,13 +387,20 @@ fn report_validation_results(
             } else {
                 format!("[{}, {}, {}, ...]", line_numbers[0], line_numbers[1], line_numbers[2])
             };
-            println!("  {}: {} occurrences on lines {}", diff_key, line_numbers.len(), display_lines);
+            tracing::info!(
+                target: REPORT_TARGET,
+                file = input_path,
+                "  {}: {} occurrences on lines {}",
+                diff_key,
+                line_numbers.len(),
+                display_lines
+            );
 
             // Show visual diff for the first example
             if let Some((original, serialized, line_num)) = diff_examples.get(diff_key) {
-                eprintln!("    Example from line {}:", line_num);
-                eprintln!("    Original:   {}", original);
-                eprintln!("    Serialized: {}", serialized);
+                tracing::warn!(target: REPORT_TARGET, file = input_path, line = *line_num, "    Example from line {}:", line_num);
+                tracing::warn!(target: REPORT_TARGET, "    Original:   {}", original);
+                tracing::warn!(target: REPORT_TARGET, "    Serialized: {}", serialized);
 
                 // Create visual diff indicator
                 let mut diff_indicator = String::new();
@@ -292,8 +420,8 @@ fn report_validation_results(
                     }
                 }
 
-                eprintln!("    Diff:       {}", diff_indicator);
-                eprintln!();
+                tracing::warn!(target: REPORT_TARGET, "    Diff:       {}", diff_indicator);
+                tracing::warn!(target: REPORT_TARGET, "");
             }
         }
         return Err(RoundtripError::CwrParsing(format!(
@@ -302,13 +430,71 @@ fn report_validation_results(
         )));
     }
 
-    println!("ROUNDTRIP PASSED: All {} records maintain round-trip integrity", record_count);
+    tracing::info!(
+        target: REPORT_TARGET,
+        file = input_path,
+        "ROUNDTRIP PASSED: All {} records maintain round-trip integrity",
+        record_count
+    );
     Ok(record_count)
 }
 
-fn parse_charset_override(charset_str: &str) -> CharacterSet {
+/// The raw lines of a file (without line terminators, decompressed when gzipped), read in step with the parsed
+/// records they are compared against so only the current line is held in memory
+pub(crate) struct OriginalLines<'a> {
+    reader: BufReader<Box<dyn Read + 'a>>,
+    line: Vec<u8>,
+    /// Number of the line in `line`; 0 before the first read
+    line_number: usize,
+    at_end: bool,
+}
+
+impl<'a> OriginalLines<'a> {
+    pub(crate) fn new(input: CwrInput<'a>) -> std::io::Result<Self> {
+        let reader: Box<dyn Read + 'a> = match input {
+            CwrInput::File(path) => allegro_cwr::compression::open_input(path)?,
+            CwrInput::Bytes { content, .. } => Box::new(Cursor::new(allegro_cwr::compression::decompress(content)?)),
+        };
+        Ok(OriginalLines { reader: BufReader::new(reader), line: Vec::new(), line_number: 0, at_end: false })
+    }
+
+    /// The line with the given one-based number, or `None` past the end of the file. Line numbers must not
+    /// decrease between calls; asking for the current line again (several records recovered from one line) is
+    /// fine.
+    pub(crate) fn get(&mut self, line_number: usize) -> std::io::Result<Option<&[u8]>> {
+        while self.line_number < line_number && !self.at_end {
+            self.line.clear();
+            if allegro_cwr::read_cwr_line(&mut self.reader, &mut self.line)? == 0 {
+                self.at_end = true;
+                break;
+            }
+            self.line_number += 1;
+            allegro_cwr::trim_line_terminator(&mut self.line);
+        }
+        Ok((self.line_number == line_number && !self.at_end).then_some(self.line.as_slice()))
+    }
+}
+
+/// Splits content into lines the way the parser does (on LF, CRLF or a lone CR), without the terminators
+pub(crate) fn split_lines(mut content: &[u8]) -> Vec<Vec<u8>> {
+    let mut lines = Vec::new();
+    loop {
+        let mut line = Vec::new();
+        // Reading from a slice cannot fail
+        match allegro_cwr::read_cwr_line(&mut content, &mut line) {
+            Ok(0) | Err(_) => return lines,
+            Ok(_) => {
+                allegro_cwr::trim_line_terminator(&mut line);
+                lines.push(line);
+            }
+        }
+    }
+}
+
+pub(crate) fn parse_charset_override(charset_str: &str) -> CharacterSet {
     match charset_str.to_uppercase().as_str() {
         "ASCII" => CharacterSet::ASCII,
+        "LATIN-1" | "LATIN1" | "ISO-8859-1" => CharacterSet::Latin1,
         "UTF-8" | "UTF8" => CharacterSet::UTF8,
         "UNICODE" => CharacterSet::Unicode,
         "TRADITIONAL BIG5" | "BIG5" => CharacterSet::TraditionalBig5,
@@ -334,86 +520,155 @@ fn display_incidences(line_numbers: &[usize]) -> String {
     display_lines
 }
 
-/// Check for character differences between original and round-trip serialized lines
-fn check_character_differences(
-    original: &str, serialized: &str, record_type: &str, line_number: usize,
-    diff_map: &mut HashMap<String, Vec<usize>>, diff_examples: &mut HashMap<String, (String, String, usize)>,
-    extra_chars_map: &mut HashMap<String, Vec<usize>>,
-) {
-    // Check length difference first
+/// How a round-tripped line differs from the original
+#[derive(Debug, Clone, PartialEq)]
+pub enum LineDifference {
+    /// Original has extra characters beyond the CWR specification
+    TrailingCharacters(String),
+    /// Original is missing trailing optional fields (serializer pads them)
+    MissingOptionalFields,
+    /// Date field with '00000000' serialized as spaces
+    DateZeroPadding,
+    LengthMismatch {
+        original_len: usize,
+        serialized_len: usize,
+    },
+    /// Zero-based character indexes that differ
+    Characters(Vec<usize>),
+}
+
+impl LineDifference {
+    /// Ambiguous differences are expected serializer behaviour rather than round-trip failures
+    pub fn is_ambiguous(&self) -> bool {
+        matches!(
+            self,
+            LineDifference::TrailingCharacters(_)
+                | LineDifference::MissingOptionalFields
+                | LineDifference::DateZeroPadding
+        )
+    }
+}
+
+/// Compares an original line with its round-trip serialization
+pub fn classify_line_difference(original: &str, serialized: &str) -> Option<LineDifference> {
     if original.len() != serialized.len() {
         // Special handling for cases where original file is longer than CWR spec allows
         if original.len() > serialized.len() {
-            // Check if the extra characters in the original are just trailing content
-            let common_len = serialized.len();
-            let common_original = &original[..common_len];
-
             // If the common part matches exactly, this is likely just extra trailing characters
-            if common_original == serialized {
-                let extra_chars = &original[common_len..];
-                // Group by record type and extra character content
-                let extra_key = format!("{}:{}", record_type, extra_chars);
-                extra_chars_map.entry(extra_key).or_default().push(line_number);
-                return; // Don't treat this as an error
+            if original.get(..serialized.len()) == Some(serialized) {
+                return Some(LineDifference::TrailingCharacters(original[serialized.len()..].to_string()));
             }
         }
         // Special handling for cases where original file is shorter (missing optional fields)
-        else if original.len() < serialized.len() {
-            // Check if the original matches the beginning of the serialized output
-            if let Some(missing_chars) = serialized.strip_prefix(original) {
-                // If the missing part is just spaces/padding, this is expected behavior
-                if missing_chars.chars().all(|c| c == ' ') {
-                    let missing_key = format!("{}:missing_optional_fields", record_type);
-                    extra_chars_map.entry(missing_key).or_default().push(line_number);
-                    return; // Don't treat this as an error
-                }
+        else if let Some(missing_chars) = serialized.strip_prefix(original) {
+            // If the missing part is just spaces/padding, this is expected behavior
+            if missing_chars.chars().all(|c| c == ' ') {
+                return Some(LineDifference::MissingOptionalFields);
             }
         }
 
-        let explanation = if original.len() > serialized.len() {
-            " - source file may have extra characters beyond CWR specification"
-        } else {
-            " - source file missing version-specific fields, serializer adds required padding"
-        };
-        let diff_key = format!(
-            "record: {}, LENGTH_MISMATCH (original: {}, serialized: {}){}",
-            record_type,
-            original.len(),
-            serialized.len(),
-            explanation
-        );
-        diff_map.entry(diff_key.clone()).or_default().push(line_number);
-        // Store example if this is the first occurrence
-        diff_examples.entry(diff_key).or_insert_with(|| (original.to_string(), serialized.to_string(), line_number));
-        return;
+        return Some(LineDifference::LengthMismatch { original_len: original.len(), serialized_len: serialized.len() });
     }
 
     // Check for date zero-padding ambiguity: "00000000" in original becomes spaces in serialized
-    if original.len() == serialized.len() {
-        let mut i = 0;
-        while i <= original.len().saturating_sub(8) {
-            if let (Some(orig_slice), Some(ser_slice)) = (original.get(i..i + 8), serialized.get(i..i + 8)) {
-                if orig_slice == "00000000" && ser_slice == "        " {
-                    let date_key = format!("{}:date_zero_padding", record_type);
-                    extra_chars_map.entry(date_key).or_default().push(line_number);
-                    return; // Don't treat this as an error, it's expected behavior
-                }
+    let mut i = 0;
+    while i <= original.len().saturating_sub(8) {
+        if let (Some(orig_slice), Some(ser_slice)) = (original.get(i..i + 8), serialized.get(i..i + 8)) {
+            if orig_slice == "00000000" && ser_slice == "        " {
+                return Some(LineDifference::DateZeroPadding);
             }
-            i += 1;
         }
+        i += 1;
+    }
+
+    let indexes: Vec<usize> = original
+        .chars()
+        .zip(serialized.chars())
+        .enumerate()
+        .filter(|(_, (orig_char, ser_char))| orig_char != ser_char)
+        .map(|(index, _)| index)
+        .collect();
+
+    if indexes.is_empty() {
+        None
+    } else {
+        Some(LineDifference::Characters(indexes))
     }
+}
 
-    let original_chars: Vec<char> = original.chars().collect();
-    let serialized_chars: Vec<char> = serialized.chars().collect();
-
-    for (index, (orig_char, ser_char)) in original_chars.iter().zip(serialized_chars.iter()).enumerate() {
-        if orig_char != ser_char {
-            let diff_key = format!("record: {}, index: {}", record_type, index);
-            diff_map.entry(diff_key.clone()).or_default().push(line_number);
-            // Store example if this is the first occurrence
-            diff_examples
-                .entry(diff_key)
-                .or_insert_with(|| (original.to_string(), serialized.to_string(), line_number));
+/// Check for character differences between original and round-trip serialized lines
+fn check_character_differences(
+    original: &str, serialized: &str, record_type: &str, line_number: usize,
+    diff_map: &mut HashMap<String, Vec<usize>>, diff_examples: &mut HashMap<String, (String, String, usize)>,
+    extra_chars_map: &mut HashMap<String, Vec<usize>>,
+) {
+    let mut record_diff = |diff_key: String| {
+        diff_map.entry(diff_key.clone()).or_default().push(line_number);
+        // Store example if this is the first occurrence
+        diff_examples.entry(diff_key).or_insert_with(|| (original.to_string(), serialized.to_string(), line_number));
+    };
+
+    match classify_line_difference(original, serialized) {
+        None => {}
+        Some(LineDifference::TrailingCharacters(extra_chars)) => {
+            // Group by record type and extra character content
+            let extra_key = format!("{}:{}", record_type, extra_chars);
+            extra_chars_map.entry(extra_key).or_default().push(line_number);
+        }
+        Some(LineDifference::MissingOptionalFields) => {
+            let missing_key = format!("{}:missing_optional_fields", record_type);
+            extra_chars_map.entry(missing_key).or_default().push(line_number);
         }
+        Some(LineDifference::DateZeroPadding) => {
+            let date_key = format!("{}:date_zero_padding", record_type);
+            extra_chars_map.entry(date_key).or_default().push(line_number);
+        }
+        Some(LineDifference::LengthMismatch { original_len, serialized_len }) => {
+            let explanation = if original_len > serialized_len {
+                " - source file may have extra characters beyond CWR specification"
+            } else {
+                " - source file missing version-specific fields, serializer adds required padding"
+            };
+            record_diff(format!(
+                "record: {}, LENGTH_MISMATCH (original: {}, serialized: {}){}",
+                record_type, original_len, serialized_len, explanation
+            ));
+        }
+        Some(LineDifference::Characters(indexes)) => {
+            for index in indexes {
+                record_diff(format!("record: {}, index: {}", record_type, index));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_original_lines() {
+        let path = std::env::temp_dir().join(format!("allegro_validate_lines_{}.cwr", std::process::id()));
+        std::fs::write(&path, "HDR\r\nGRH\n\nTRL").unwrap();
+        let mut lines = OriginalLines::new(CwrInput::File(path.to_str().unwrap())).unwrap();
+        assert_eq!(lines.get(1).unwrap(), Some(&b"HDR"[..]));
+        assert_eq!(lines.get(1).unwrap(), Some(&b"HDR"[..]));
+        assert_eq!(lines.get(3).unwrap(), Some(&b""[..]));
+        assert_eq!(lines.get(4).unwrap(), Some(&b"TRL"[..]));
+        assert_eq!(lines.get(5).unwrap(), None);
+
+        // Lines ended by a lone CR, as the parser reads them
+        let content = b"HDR\rGRH\r\nNWR\rTRL";
+        let mut lines = OriginalLines::new(CwrInput::Bytes { name: "-", content }).unwrap();
+        assert_eq!(lines.get(2).unwrap(), Some(&b"GRH"[..]));
+        assert_eq!(lines.get(3).unwrap(), Some(&b"NWR"[..]));
+        assert_eq!(lines.get(4).unwrap(), Some(&b"TRL"[..]));
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_split_lines() {
+        assert_eq!(split_lines(b"HDR\rGRH\r\n\nTRL\n"), [&b"HDR"[..], b"GRH", b"", b"TRL"]);
+        assert!(split_lines(b"").is_empty());
     }
 }
@@ -0,0 +1,510 @@
+//! Verifies ISWCs and IPI name numbers against online lookup services (`online` feature)
+//!
+//! [`LookupClient`] asks the services configured in [`LookupConfig`] whether an identifier exists and remembers the
+//! answer, in memory and optionally in a JSON cache file shared between runs. [`online_rules`] wraps a client in
+//! validation rules numbered 9xx, outside the CISAC numbering, that flag identifiers the services do not know.
+//! Identifiers that cannot be looked up (network or service errors) are logged and not reported as violations;
+//! after the first transport error the client stops sending requests for the rest of the run.
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::time::Duration;
+
+use allegro_cwr::cwr_registry::CwrRegistry;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::engine::{FileSummary, RecordRef, RejectionLevel, RuleId, RuleScope, RuleViolation, ValidationRule};
+
+#[derive(Error, Debug)]
+pub enum LookupError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Lookup request failed: {0}")]
+    Request(String),
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("'{0}' is not a valid identifier to look up")]
+    InvalidIdentifier(String),
+    /// A request failed earlier in the run, so no more are sent
+    #[error("Lookups stopped after an earlier failure: {0}")]
+    Unavailable(String),
+}
+
+/// Where and how identifiers are looked up, usually read from a JSON file
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LookupConfig {
+    /// URL of the ISWC lookup with `{iswc}` in place of the ISWC, e.g. an ISWC Network search by ISWC endpoint
+    pub iswc_url: Option<String>,
+    /// URL of the IPI lookup with `{ipi}` in place of the IPI name number
+    pub ipi_url: Option<String>,
+    /// HTTP headers sent with every request, e.g. the services' subscription key
+    pub headers: BTreeMap<String, String>,
+    /// JSON file the answers are kept in between runs
+    pub cache_path: Option<PathBuf>,
+    /// Request timeout in seconds (default 10)
+    pub timeout_secs: Option<u64>,
+}
+
+/// Fetches a URL, returning `None` when the service answers 404 (unknown identifier)
+pub trait LookupTransport {
+    fn get(
+        &self, url: &str, headers: &BTreeMap<String, String>, timeout_secs: u64,
+    ) -> Result<Option<String>, LookupError>;
+}
+
+/// Fetches URLs over HTTP(S), verifying certificates with rustls and going through the proxy named by the
+/// `HTTPS_PROXY`/`ALL_PROXY` environment variables. Build it from an [`ureq::Agent`] to choose the TLS or proxy
+/// settings.
+#[derive(Debug, Clone)]
+pub struct HttpTransport {
+    agent: ureq::Agent,
+}
+
+impl HttpTransport {
+    /// A transport sending requests through `agent`; non-2xx answers must not be configured as errors
+    pub fn with_agent(agent: ureq::Agent) -> Self {
+        HttpTransport { agent }
+    }
+}
+
+impl Default for HttpTransport {
+    fn default() -> Self {
+        HttpTransport::with_agent(ureq::Agent::new_with_config(
+            ureq::Agent::config_builder().http_status_as_error(false).build(),
+        ))
+    }
+}
+
+impl LookupTransport for HttpTransport {
+    fn get(
+        &self, url: &str, headers: &BTreeMap<String, String>, timeout_secs: u64,
+    ) -> Result<Option<String>, LookupError> {
+        let mut request = self.agent.get(url).config().timeout_global(Some(Duration::from_secs(timeout_secs))).build();
+        for (name, value) in headers {
+            request = request.header(name, value);
+        }
+        let mut response = request.call().map_err(|e| LookupError::Request(e.to_string()))?;
+        match response.status().as_u16() {
+            404 => Ok(None),
+            200..=299 => {
+                response.body_mut().read_to_string().map(Some).map_err(|e| LookupError::Request(e.to_string()))
+            }
+            status => Err(LookupError::Request(format!("HTTP {} from {}", status, url))),
+        }
+    }
+}
+
+/// What a service returned for a known identifier
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IdentifierInfo {
+    /// Work title for an ISWC, party name for an IPI name number, when the response has one
+    pub name: Option<String>,
+}
+
+/// Answers received so far: `None` for identifiers the service does not know
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LookupCache {
+    pub iswcs: BTreeMap<String, Option<IdentifierInfo>>,
+    pub ipis: BTreeMap<String, Option<IdentifierInfo>>,
+}
+
+pub struct LookupClient<T: LookupTransport = HttpTransport> {
+    config: LookupConfig,
+    transport: T,
+    cache: LookupCache,
+    changed: bool,
+    /// URLs whose response could not be read, so they are not asked for again in this run
+    failed_urls: BTreeSet<String>,
+    /// The first transport error, after which no more requests are sent
+    transport_error: Option<String>,
+}
+
+impl LookupClient<HttpTransport> {
+    /// A client using the default [`HttpTransport`], starting from the cache file if there is one
+    pub fn new(config: LookupConfig) -> Result<Self, LookupError> {
+        Self::with_transport(config, HttpTransport::default())
+    }
+}
+
+impl<T: LookupTransport> LookupClient<T> {
+    pub fn with_transport(config: LookupConfig, transport: T) -> Result<Self, LookupError> {
+        let cache = match &config.cache_path {
+            Some(path) if path.exists() => serde_json::from_str(&std::fs::read_to_string(path)?)?,
+            _ => LookupCache::default(),
+        };
+        Ok(LookupClient {
+            config,
+            transport,
+            cache,
+            changed: false,
+            failed_urls: BTreeSet::new(),
+            transport_error: None,
+        })
+    }
+
+    pub fn cache(&self) -> &LookupCache {
+        &self.cache
+    }
+
+    /// Looks up an ISWC (e.g. `T0345246801`); `Ok(None)` if the service does not know it
+    pub fn lookup_iswc(&mut self, iswc: &str) -> Result<Option<IdentifierInfo>, LookupError> {
+        let iswc = iswc.trim().replace(['-', '.'], "");
+        if !is_iswc(&iswc) {
+            return Err(LookupError::InvalidIdentifier(iswc));
+        }
+        if let Some(info) = self.cache.iswcs.get(&iswc) {
+            return Ok(info.clone());
+        }
+        let info = self.fetch(self.config.iswc_url.clone(), "{iswc}", &iswc, &["originalTitle", "title"])?;
+        self.cache.iswcs.insert(iswc, info.clone());
+        self.changed = true;
+        Ok(info)
+    }
+
+    /// Looks up an IPI name number; `Ok(None)` if the service does not know it
+    pub fn lookup_ipi(&mut self, ipi_name_num: &str) -> Result<Option<IdentifierInfo>, LookupError> {
+        let ipi = ipi_name_num.trim().to_string();
+        if ipi.len() != 11 || !ipi.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(LookupError::InvalidIdentifier(ipi));
+        }
+        if let Some(info) = self.cache.ipis.get(&ipi) {
+            return Ok(info.clone());
+        }
+        let info = self.fetch(self.config.ipi_url.clone(), "{ipi}", &ipi, &["name", "lastName"])?;
+        self.cache.ipis.insert(ipi, info.clone());
+        self.changed = true;
+        Ok(info)
+    }
+
+    /// Writes the cache file if anything was looked up since it was read
+    pub fn save_cache(&mut self) -> Result<(), LookupError> {
+        match &self.config.cache_path {
+            Some(path) if self.changed => {
+                std::fs::write(path, serde_json::to_string_pretty(&self.cache)?)?;
+                self.changed = false;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn fetch(
+        &mut self, url: Option<String>, placeholder: &str, identifier: &str, name_keys: &[&str],
+    ) -> Result<Option<IdentifierInfo>, LookupError> {
+        let Some(url) = url else {
+            return Err(LookupError::Request(format!("No lookup URL configured for {}", placeholder)));
+        };
+        if let Some(error) = &self.transport_error {
+            return Err(LookupError::Unavailable(error.clone()));
+        }
+        let url = url.replace(placeholder, &percent_encode(identifier));
+        if self.failed_urls.contains(&url) {
+            return Err(LookupError::Request(format!("Lookup of {} failed earlier", url)));
+        }
+        let body = match self.transport.get(&url, &self.config.headers, self.timeout_secs()) {
+            Ok(Some(body)) => body,
+            Ok(None) => return Ok(None),
+            Err(e) => {
+                self.transport_error = Some(e.to_string());
+                return Err(e);
+            }
+        };
+        let json: serde_json::Value = match serde_json::from_str(&body) {
+            Ok(json) => json,
+            Err(e) => {
+                self.failed_urls.insert(url);
+                return Err(e.into());
+            }
+        };
+        let found = match &json {
+            serde_json::Value::Array(items) => items.first(),
+            serde_json::Value::Null => None,
+            value => Some(value),
+        };
+        Ok(found.map(|value| IdentifierInfo {
+            name: name_keys.iter().find_map(|key| value.get(*key).and_then(|v| v.as_str()).map(str::to_string)),
+        }))
+    }
+
+    fn timeout_secs(&self) -> u64 {
+        self.config.timeout_secs.unwrap_or(10)
+    }
+}
+
+/// `T` followed by ten digits, once separators are removed
+fn is_iswc(iswc: &str) -> bool {
+    iswc.len() == 11 && iswc.starts_with('T') && iswc[1..].bytes().all(|b| b.is_ascii_digit())
+}
+
+/// Percent-encodes everything but unreserved URL characters
+fn percent_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+/// A lookup client shared by the online rules
+pub type SharedLookupClient<T = HttpTransport> = Rc<RefCell<LookupClient<T>>>;
+
+/// Rules checking work ISWCs and writer/publisher IPI name numbers with `client`
+pub fn online_rules<T: LookupTransport + 'static>(client: SharedLookupClient<T>) -> Vec<Box<dyn ValidationRule>> {
+    vec![
+        Box::new(IswcKnown { client: client.clone() }),
+        Box::new(IpiKnown { record_type: "SWR", client: client.clone() }),
+        Box::new(IpiKnown { record_type: "SPU", client }),
+    ]
+}
+
+fn log_lookup_error(identifier: &str, e: &LookupError) {
+    // The failure that stopped the lookups has already been logged
+    if !matches!(e, LookupError::Unavailable(_)) {
+        tracing::warn!("Could not look up '{}': {}", identifier, e);
+    }
+}
+
+struct IswcKnown<T: LookupTransport> {
+    client: SharedLookupClient<T>,
+}
+
+impl<T: LookupTransport> ValidationRule for IswcKnown<T> {
+    fn id(&self) -> RuleId {
+        RuleId::new("NWR", RuleScope::Field, 901)
+    }
+
+    fn description(&self) -> &'static str {
+        "ISWC must be known to the ISWC lookup service (online)"
+    }
+
+    fn level(&self) -> RejectionLevel {
+        RejectionLevel::Field
+    }
+
+    fn check_record(&mut self, record: &RecordRef<'_>) -> Vec<RuleViolation> {
+        let CwrRegistry::Nwr(nwr) = record.record else { return Vec::new() };
+        let Some(iswc) = nwr.iswc.as_deref().map(str::trim).filter(|iswc| !iswc.is_empty()) else {
+            return Vec::new();
+        };
+        match self.client.borrow_mut().lookup_iswc(iswc) {
+            Ok(Some(_)) => Vec::new(),
+            Ok(None) => vec![self
+                .violation(record.line_number, nwr.record_type.as_ref(), format!("Unknown ISWC '{}'", iswc))
+                .with_field("iswc", iswc)],
+            Err(e) => {
+                log_lookup_error(iswc, &e);
+                Vec::new()
+            }
+        }
+    }
+
+    fn finish(&mut self, _summary: &FileSummary) -> Vec<RuleViolation> {
+        if let Err(e) = self.client.borrow_mut().save_cache() {
+            tracing::warn!("Could not save the lookup cache: {}", e);
+        }
+        Vec::new()
+    }
+}
+
+struct IpiKnown<T: LookupTransport> {
+    /// SWR for writers (SWR/OWR), SPU for publishers (SPU/OPU)
+    record_type: &'static str,
+    client: SharedLookupClient<T>,
+}
+
+impl<T: LookupTransport> ValidationRule for IpiKnown<T> {
+    fn id(&self) -> RuleId {
+        RuleId::new(self.record_type, RuleScope::Field, 901)
+    }
+
+    fn description(&self) -> &'static str {
+        "IPI name number must be known to the IPI lookup service (online)"
+    }
+
+    fn level(&self) -> RejectionLevel {
+        RejectionLevel::Field
+    }
+
+    fn check_record(&mut self, record: &RecordRef<'_>) -> Vec<RuleViolation> {
+        let (field, ipi_name_num) = match (self.record_type, record.record) {
+            ("SWR", CwrRegistry::Swr(swr)) => ("writer_ipi_name_num", swr.writer_ipi_name_num.as_ref()),
+            ("SPU", CwrRegistry::Spu(spu)) => ("publisher_ipi_name_num", spu.publisher_ipi_name_num.as_ref()),
+            _ => return Vec::new(),
+        };
+        let Some(ipi) = ipi_name_num.map(|ipi| ipi.as_str().trim()).filter(|ipi| !ipi.is_empty()) else {
+            return Vec::new();
+        };
+        match self.client.borrow_mut().lookup_ipi(ipi) {
+            Ok(Some(_)) => Vec::new(),
+            Ok(None) => vec![self
+                .violation(
+                    record.line_number,
+                    record.record.record_type(),
+                    format!("Unknown IPI name number '{}'", ipi),
+                )
+                .with_field(field, ipi)],
+            Err(e) => {
+                log_lookup_error(ipi, &e);
+                Vec::new()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use allegro_cwr::domain_types::CharacterSet;
+    use std::cell::Cell;
+
+    /// Knows ISWC T0345246801 and IPI 00014107338, answers ISWC T0000000002 with something other than JSON and
+    /// fails for IPI 00000000666; counts requests
+    #[derive(Clone, Default)]
+    struct FakeService {
+        requests: Rc<Cell<usize>>,
+    }
+
+    impl LookupTransport for FakeService {
+        fn get(
+            &self, url: &str, headers: &BTreeMap<String, String>, _timeout_secs: u64,
+        ) -> Result<Option<String>, LookupError> {
+            self.requests.set(self.requests.get() + 1);
+            assert_eq!(headers.get("Key").map(String::as_str), Some("secret"));
+            Ok(match url {
+                "https://ipi.test/00000000666" => return Err(LookupError::Request("HTTP 503".into())),
+                "https://iswc.test/T0000000002" => Some("<html>".into()),
+                "https://iswc.test/T0345246801" => Some(r#"[{"iswc": "T0345246801", "originalTitle": "SONG"}]"#.into()),
+                "https://ipi.test/00014107338" => Some(r#"{"name": "SMITH"}"#.into()),
+                _ => None,
+            })
+        }
+    }
+
+    #[test]
+    fn test_http_transport() {
+        use std::io::{BufRead, BufReader, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let mut keys = Vec::new();
+            for stream in listener.incoming().take(3) {
+                let mut stream = stream.unwrap();
+                let mut request = Vec::new();
+                let mut reader = BufReader::new(&stream);
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                    request.push(line.trim().to_lowercase());
+                }
+                keys.push(request.iter().any(|line| line == "key: secret"));
+                let response = match request[0].split(' ').nth(1) {
+                    Some("/known") => "HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\n{}",
+                    Some("/unknown") => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                    _ => "HTTP/1.1 503 Service Unavailable\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                };
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+            keys
+        });
+
+        let agent =
+            ureq::Agent::new_with_config(ureq::Agent::config_builder().http_status_as_error(false).proxy(None).build());
+        let transport = HttpTransport::with_agent(agent);
+        let headers = BTreeMap::from([("Key".to_string(), "secret".to_string())]);
+        assert_eq!(transport.get(&format!("{}/known", base), &headers, 5).unwrap().as_deref(), Some("{}"));
+        assert_eq!(transport.get(&format!("{}/unknown", base), &headers, 5).unwrap(), None);
+        assert!(
+            matches!(transport.get(&format!("{}/down", base), &headers, 5), Err(LookupError::Request(e)) if e.contains("503"))
+        );
+        assert_eq!(server.join().unwrap(), vec![true; 3]);
+    }
+
+    fn config(cache_path: Option<PathBuf>) -> LookupConfig {
+        LookupConfig {
+            iswc_url: Some("https://iswc.test/{iswc}".to_string()),
+            ipi_url: Some("https://ipi.test/{ipi}".to_string()),
+            headers: BTreeMap::from([("Key".to_string(), "secret".to_string())]),
+            cache_path,
+            timeout_secs: None,
+        }
+    }
+
+    #[test]
+    fn test_lookups_are_cached() {
+        let dir = std::env::temp_dir().join(format!("allegro_lookup_cache_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let cache_path = dir.join("cache.json");
+        let _ = std::fs::remove_file(&cache_path);
+        let service = FakeService::default();
+
+        let mut client = LookupClient::with_transport(config(Some(cache_path.clone())), service.clone()).unwrap();
+        assert_eq!(client.lookup_iswc("T-034.524.680-1").unwrap(), Some(IdentifierInfo { name: Some("SONG".into()) }));
+        assert_eq!(client.lookup_iswc("T0345246801").unwrap().and_then(|info| info.name).as_deref(), Some("SONG"));
+        assert_eq!(client.lookup_ipi("00014107338").unwrap().and_then(|info| info.name).as_deref(), Some("SMITH"));
+        assert_eq!(client.lookup_ipi("00000000001").unwrap(), None);
+        assert_eq!(service.requests.get(), 3);
+        client.save_cache().unwrap();
+
+        let mut client = LookupClient::with_transport(config(Some(cache_path)), service.clone()).unwrap();
+        assert_eq!(client.lookup_ipi("00000000001").unwrap(), None);
+        assert_eq!(service.requests.get(), 3);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_failed_lookups_are_not_repeated() {
+        let service = FakeService::default();
+        let mut client = LookupClient::with_transport(config(None), service.clone()).unwrap();
+        assert!(matches!(client.lookup_iswc("T/../admin?"), Err(LookupError::InvalidIdentifier(_))));
+        assert!(matches!(client.lookup_ipi("1234&x=1"), Err(LookupError::InvalidIdentifier(_))));
+        assert_eq!(service.requests.get(), 0);
+
+        assert!(matches!(client.lookup_iswc("T0000000002"), Err(LookupError::Json(_))));
+        assert!(client.lookup_iswc("T0000000002").is_err());
+        assert_eq!(service.requests.get(), 1);
+
+        assert!(matches!(client.lookup_ipi("00000000666"), Err(LookupError::Request(_))));
+        assert!(matches!(client.lookup_ipi("00014107338"), Err(LookupError::Unavailable(_))));
+        assert_eq!(service.requests.get(), 2);
+    }
+
+    #[test]
+    fn test_percent_encode() {
+        assert_eq!(percent_encode("T0345246801"), "T0345246801");
+        assert_eq!(percent_encode("a b/?&"), "a%20b%2F%3F%26");
+    }
+
+    #[test]
+    fn test_online_rules_report_unknown_identifiers() {
+        let client = Rc::new(RefCell::new(LookupClient::with_transport(config(None), FakeService::default()).unwrap()));
+        let mut engine = crate::RuleEngine::new();
+        for rule in online_rules(client) {
+            engine.add_rule(rule);
+        }
+
+        let nwr = |iswc: &str| format!("NWR0000000000000000{:<60}  {:<14}{:<11}", "SONG", "SW0000000001", iswc);
+        let lines = [nwr("T0345246801"), nwr("T9999999999")];
+        for (index, line) in lines.iter().enumerate() {
+            let (record, _) = allegro_cwr::parse_cwr_line(line, 2.2, &CharacterSet::ASCII).unwrap();
+            engine.check_record(index + 1, &record);
+        }
+        let report = engine.finish();
+        let violations: Vec<_> = report.violations().collect();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].rule_id.to_string(), "NWR-FLD-901");
+        assert_eq!(violations[0].value.as_deref(), Some("T9999999999"));
+    }
+}
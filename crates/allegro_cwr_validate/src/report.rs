@@ -0,0 +1,645 @@
+//! Machine-readable validation reports (JSON and SARIF) for CI pipelines
+
+use std::io::Write;
+use std::str::FromStr;
+
+use allegro_cwr::domain_types::{CharacterSet, CwrVersion};
+use allegro_cwr::RecordParseError;
+use serde::Serialize;
+use serde_json::json;
+
+use crate::engine::{RejectionLevel, RuleEngine, RuleViolation};
+use crate::{classify_line_difference, CwrInput, LineDifference, RoundtripError};
+
+/// Rule name used for parser warnings
+pub const PARSE_RULE: &str = "PARSE";
+/// Rule name used for round-trip differences
+pub const ROUNDTRIP_RULE: &str = "ROUNDTRIP";
+/// Rule name used for expected, ambiguous round-trip differences
+pub const ROUNDTRIP_AMBIGUOUS_RULE: &str = "ROUNDTRIP-AMBIGUOUS";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    fn sarif_level(&self) -> &'static str {
+        match self {
+            Severity::Info => "note",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        }
+    }
+}
+
+/// Output format for validation results
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReportFormat {
+    #[default]
+    Text,
+    Json,
+    Sarif,
+}
+
+impl FromStr for ReportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(ReportFormat::Text),
+            "json" => Ok(ReportFormat::Json),
+            "sarif" => Ok(ReportFormat::Sarif),
+            _ => Err(format!("Invalid report format '{}'. Valid formats: text, json, sarif", s)),
+        }
+    }
+}
+
+/// Kind of finding, used to choose which findings fail validation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FindingCategory {
+    /// Parser warning on a field or record
+    ParseWarning,
+    /// Line that could not be parsed
+    ParseError,
+    /// Characters that change in a parse/serialize round-trip
+    Roundtrip,
+    /// Line whose length changes in a round-trip
+    LengthMismatch,
+    /// Expected round-trip difference: trailing characters, missing optional fields, zero dates
+    Ambiguous,
+    /// Validation rule violation that only rejects the field
+    RuleField,
+    /// Validation rule violation that rejects a record, transaction, group or the file
+    Rule,
+}
+
+impl FindingCategory {
+    pub const ALL: [FindingCategory; 7] = [
+        FindingCategory::ParseWarning,
+        FindingCategory::ParseError,
+        FindingCategory::Roundtrip,
+        FindingCategory::LengthMismatch,
+        FindingCategory::Ambiguous,
+        FindingCategory::RuleField,
+        FindingCategory::Rule,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FindingCategory::ParseWarning => "parse-warning",
+            FindingCategory::ParseError => "parse-error",
+            FindingCategory::Roundtrip => "roundtrip",
+            FindingCategory::LengthMismatch => "length-mismatch",
+            FindingCategory::Ambiguous => "ambiguous",
+            FindingCategory::RuleField => "rule-field",
+            FindingCategory::Rule => "rule",
+        }
+    }
+}
+
+impl FromStr for FindingCategory {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_lowercase();
+        FindingCategory::ALL.into_iter().find(|category| category.as_str() == s).ok_or_else(|| {
+            let valid: Vec<&str> = FindingCategory::ALL.iter().map(FindingCategory::as_str).collect();
+            format!("Invalid finding category '{}'. Valid categories: {}, all, none", s, valid.join(", "))
+        })
+    }
+}
+
+/// Which finding categories fail validation. By default the error-severity ones do: parse errors,
+/// round-trip character and length differences, and rule violations above field level.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FailurePolicy {
+    fatal: Vec<FindingCategory>,
+}
+
+impl Default for FailurePolicy {
+    fn default() -> Self {
+        Self {
+            fatal: vec![
+                FindingCategory::ParseError,
+                FindingCategory::Roundtrip,
+                FindingCategory::LengthMismatch,
+                FindingCategory::Rule,
+            ],
+        }
+    }
+}
+
+impl FailurePolicy {
+    pub fn new(fatal: impl IntoIterator<Item = FindingCategory>) -> Self {
+        Self { fatal: fatal.into_iter().collect() }
+    }
+
+    pub fn is_fatal(&self, entry: &ReportEntry) -> bool {
+        self.fatal.contains(&entry.category)
+    }
+}
+
+/// Comma-separated categories, or `all` / `none`
+impl FromStr for FailurePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "all" => Ok(FailurePolicy::new(FindingCategory::ALL)),
+            "none" => Ok(FailurePolicy::new([])),
+            _ => s.split(',').map(str::parse).collect::<Result<Vec<_>, _>>().map(FailurePolicy::new),
+        }
+    }
+}
+
+/// How a validation run ended, as a process exit code. Outcomes are ordered from best to worst, so the `max` of a
+/// batch is its result: a file that could not be processed outranks one that failed validation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// No fatal findings
+    Passed = 0,
+    /// A file could not be read or processed
+    Error = 1,
+    /// A file has findings the failure policy treats as fatal
+    Failed = 2,
+}
+
+impl Outcome {
+    pub fn exit_code(self) -> i32 {
+        self as i32
+    }
+
+    fn rank(self) -> u8 {
+        match self {
+            Outcome::Passed => 0,
+            Outcome::Failed => 1,
+            Outcome::Error => 2,
+        }
+    }
+}
+
+impl Ord for Outcome {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.rank().cmp(&other.rank())
+    }
+}
+
+impl PartialOrd for Outcome {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// A single structured validation finding
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportEntry {
+    pub rule: String,
+    pub category: FindingCategory,
+    pub severity: Severity,
+    pub line: usize,
+    /// One-based column, when the finding relates to a specific position
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<usize>,
+    pub record_type: String,
+    pub field: Option<String>,
+    pub original_value: Option<String>,
+    pub message: String,
+}
+
+impl From<&RuleViolation> for ReportEntry {
+    fn from(violation: &RuleViolation) -> Self {
+        let (category, severity) = match violation.level {
+            RejectionLevel::Field => (FindingCategory::RuleField, Severity::Warning),
+            _ => (FindingCategory::Rule, Severity::Error),
+        };
+        ReportEntry {
+            rule: violation.rule_id.to_string(),
+            category,
+            severity,
+            line: violation.line_number,
+            column: None,
+            record_type: violation.record_type.clone(),
+            field: violation.field.map(str::to_string),
+            original_value: violation.value.clone(),
+            message: violation.message.clone(),
+        }
+    }
+}
+
+/// Validation findings for one file
+#[derive(Debug, Clone, Serialize)]
+pub struct FileReport {
+    pub file: String,
+    pub cwr_version: Option<String>,
+    pub record_count: usize,
+    pub passed: bool,
+    pub entries: Vec<ReportEntry>,
+    /// Why the file could not be read or processed; its findings are then incomplete
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl FileReport {
+    /// Report for a file that could not be read or processed
+    pub fn processing_error(file: &str, message: impl Into<String>) -> Self {
+        FileReport {
+            file: file.to_string(),
+            cwr_version: None,
+            record_count: 0,
+            passed: false,
+            entries: Vec::new(),
+            error: Some(message.into()),
+        }
+    }
+
+    pub fn outcome(&self) -> Outcome {
+        match (&self.error, self.passed) {
+            (Some(_), _) => Outcome::Error,
+            (None, false) => Outcome::Failed,
+            (None, true) => Outcome::Passed,
+        }
+    }
+
+    pub fn error_count(&self) -> usize {
+        self.entries.iter().filter(|e| e.severity == Severity::Error).count()
+    }
+
+    /// Findings that fail validation under `policy`
+    pub fn fatal_entries<'a>(&'a self, policy: &'a FailurePolicy) -> impl Iterator<Item = &'a ReportEntry> {
+        self.entries.iter().filter(|entry| policy.is_fatal(entry))
+    }
+
+    /// Recomputes `passed` under `policy`
+    pub fn apply_policy(&mut self, policy: &FailurePolicy) {
+        self.passed = self.error.is_none() && !self.entries.iter().any(|entry| policy.is_fatal(entry));
+    }
+}
+
+/// Validates a file (parser warnings, round-trip integrity and validation rules) and collects structured results
+pub fn build_file_report<'a>(
+    input: impl Into<CwrInput<'a>>, cwr_version: Option<f32>, charset_override: Option<&str>, engine: &mut RuleEngine,
+) -> Result<FileReport, RoundtripError> {
+    let input = input.into();
+    let mut entries = Vec::new();
+    let mut detected_version = None;
+    let mut record_count = 0;
+    let mut serialized_bytes = Vec::new();
+
+    let mut original_lines = crate::OriginalLines::new(input)?;
+
+    let record_stream = input.records_with_charset(cwr_version, charset_override)?;
+
+    for parsed_result in record_stream {
+        let parsed_record = match parsed_result {
+            Ok(parsed_record) => parsed_record,
+            Err(e) => match e.record_error() {
+                Some(details) => {
+                    entries.push(parse_error_entry(details));
+                    continue;
+                }
+                None => return Err(RoundtripError::CwrParsing(format!("Parse error: {}", e))),
+            },
+        };
+        let record_type = parsed_record.record.record_type().to_string();
+        let line_number = parsed_record.line_number;
+        detected_version.get_or_insert_with(|| parsed_record.context.cwr_version.to_string());
+
+        for warning in &parsed_record.warnings {
+            let field = warning.is_field_warning().then(|| warning.field_title.to_string());
+            let message = warning.description.to_string();
+            entries.push(ReportEntry {
+                rule: PARSE_RULE.to_string(),
+                category: FindingCategory::ParseWarning,
+                severity: Severity::Warning,
+                line: line_number,
+                column: None,
+                record_type: record_type.clone(),
+                field,
+                original_value: None,
+                message,
+            });
+        }
+
+        if let Some(original_line) = original_lines.get(line_number)? {
+            let version = CwrVersion(parsed_record.context.cwr_version);
+            let charset = parsed_record.context.character_set.as_ref().unwrap_or(&CharacterSet::ASCII);
+            serialized_bytes.clear();
+            parsed_record
+                .record
+                .write_cwr_record(&mut serialized_bytes, &version, charset)
+                .map_err(|e| RoundtripError::CwrParsing(format!("Serialization error: {}", e)))?;
+            let serialized_line = allegro_cwr::encoding::decode_lossy(&serialized_bytes, charset);
+            let original_line = allegro_cwr::encoding::decode_lossy(original_line, charset);
+            if let Some(difference) = classify_line_difference(&original_line, &serialized_line) {
+                entries.push(difference_entry(&difference, &original_line, line_number, &record_type));
+            }
+        }
+
+        engine.check_parsed_record(&parsed_record);
+        record_count += 1;
+    }
+
+    let validation_report = engine.finish();
+    entries.extend(validation_report.violations().map(ReportEntry::from));
+    entries.sort_by_key(|e| e.line);
+
+    let mut report = FileReport {
+        file: input.name().to_string(),
+        cwr_version: detected_version,
+        record_count,
+        passed: true,
+        entries,
+        error: None,
+    };
+    report.apply_policy(&FailurePolicy::default());
+    Ok(report)
+}
+
+/// Checks a fragment of a transmission, down to a single record line, without HDR/TRL or group structure.
+/// Each non-blank line is parsed in `cwr_version` and `charset` (ASCII when `None`) and round-tripped;
+/// validation rules are not run.
+pub fn build_fragment_report<R: std::io::Read>(
+    name: &str, mut reader: R, cwr_version: f32, charset: Option<&str>,
+) -> Result<FileReport, RoundtripError> {
+    let charset = charset.map(crate::parse_charset_override).unwrap_or(CharacterSet::ASCII);
+    let version = CwrVersion(cwr_version);
+    let mut content = Vec::new();
+    reader.read_to_end(&mut content)?;
+
+    let mut entries = Vec::new();
+    let mut record_count = 0;
+    let mut serialized_bytes = Vec::new();
+    for (index, bytes) in crate::split_lines(&content).iter().enumerate() {
+        let line_number = index + 1;
+        let original_line = allegro_cwr::encoding::decode_lossy(bytes, &charset);
+        if original_line.trim().is_empty() {
+            continue;
+        }
+        let (record, warnings) = match allegro_cwr::parse_cwr_line(&original_line, cwr_version, &charset) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                let mut entry = match e.record_error() {
+                    Some(details) => parse_error_entry(details),
+                    None => return Err(RoundtripError::CwrParsing(format!("Parse error: {}", e))),
+                };
+                entry.line = line_number;
+                entries.push(entry);
+                continue;
+            }
+        };
+        let record_type = record.record_type().to_string();
+
+        for warning in &warnings {
+            entries.push(ReportEntry {
+                rule: PARSE_RULE.to_string(),
+                category: FindingCategory::ParseWarning,
+                severity: Severity::Warning,
+                line: line_number,
+                column: None,
+                record_type: record_type.clone(),
+                field: warning.is_field_warning().then(|| warning.field_title.to_string()),
+                original_value: None,
+                message: warning.description.to_string(),
+            });
+        }
+
+        serialized_bytes.clear();
+        record
+            .write_cwr_record(&mut serialized_bytes, &version, &charset)
+            .map_err(|e| RoundtripError::CwrParsing(format!("Serialization error: {}", e)))?;
+        let serialized_line = allegro_cwr::encoding::decode_lossy(&serialized_bytes, &charset);
+        if let Some(difference) = classify_line_difference(&original_line, &serialized_line) {
+            entries.push(difference_entry(&difference, &original_line, line_number, &record_type));
+        }
+        record_count += 1;
+    }
+
+    let mut report = FileReport {
+        file: name.to_string(),
+        cwr_version: Some(cwr_version.to_string()),
+        record_count,
+        passed: true,
+        entries,
+        error: None,
+    };
+    report.apply_policy(&FailurePolicy::default());
+    Ok(report)
+}
+
+/// Lines that could not be parsed are reported under `PARSE/<error code>` rather than aborting the report
+fn parse_error_entry(details: &RecordParseError) -> ReportEntry {
+    ReportEntry {
+        rule: format!("{}/{}", PARSE_RULE, details.code),
+        category: FindingCategory::ParseError,
+        severity: Severity::Error,
+        line: details.line_number.unwrap_or(0),
+        column: details.columns.as_ref().map(|columns| columns.start + 1),
+        record_type: details.record_type.clone().unwrap_or_default(),
+        field: details.field_name.map(str::to_string),
+        original_value: details.value.clone(),
+        message: details.message.clone(),
+    }
+}
+
+fn difference_entry(difference: &LineDifference, original: &str, line: usize, record_type: &str) -> ReportEntry {
+    let (column, original_value, message) = match difference {
+        LineDifference::TrailingCharacters(extra) => {
+            (None, Some(extra.clone()), "Extra characters beyond CWR specification".to_string())
+        }
+        LineDifference::MissingOptionalFields => {
+            (None, None, "Missing optional fields (serializer adds proper padding)".to_string())
+        }
+        LineDifference::DateZeroPadding => (None, None, "Date field with '00000000' treated as None".to_string()),
+        LineDifference::LengthMismatch { original_len, serialized_len } => (
+            None,
+            Some(original.to_string()),
+            format!("Length mismatch (original: {}, serialized: {})", original_len, serialized_len),
+        ),
+        LineDifference::Characters(indexes) => {
+            let first = indexes.first().copied().unwrap_or(0);
+            let last = indexes.last().copied().unwrap_or(first);
+            let value: String = original.chars().skip(first).take(last - first + 1).collect();
+            (Some(first + 1), Some(value), format!("{} characters differ after round-trip", indexes.len()))
+        }
+    };
+
+    let category = match difference {
+        _ if difference.is_ambiguous() => FindingCategory::Ambiguous,
+        LineDifference::LengthMismatch { .. } => FindingCategory::LengthMismatch,
+        _ => FindingCategory::Roundtrip,
+    };
+
+    ReportEntry {
+        rule: if difference.is_ambiguous() { ROUNDTRIP_AMBIGUOUS_RULE } else { ROUNDTRIP_RULE }.to_string(),
+        category,
+        severity: if difference.is_ambiguous() { Severity::Info } else { Severity::Error },
+        line,
+        column,
+        record_type: record_type.to_string(),
+        field: None,
+        original_value,
+        message,
+    }
+}
+
+/// Writes reports as a JSON document
+pub fn write_json_report<W: Write>(reports: &[FileReport], writer: W) -> Result<(), RoundtripError> {
+    let passed = reports.iter().all(|r| r.passed);
+    serde_json::to_writer_pretty(writer, &json!({ "passed": passed, "files": reports }))?;
+    Ok(())
+}
+
+/// Writes reports in SARIF 2.1.0 format
+pub fn write_sarif_report<W: Write>(
+    reports: &[FileReport], engine: &RuleEngine, writer: W,
+) -> Result<(), RoundtripError> {
+    let mut rules = vec![
+        json!({ "id": PARSE_RULE, "shortDescription": { "text": "Field parsing warning" } }),
+        json!({ "id": ROUNDTRIP_RULE, "shortDescription": { "text": "Record does not survive a parse/serialize round-trip" } }),
+        json!({ "id": ROUNDTRIP_AMBIGUOUS_RULE, "shortDescription": { "text": "Ambiguous round-trip difference" } }),
+    ];
+    rules.extend(engine.rules().into_iter().map(
+        |(rule_id, description, _)| json!({ "id": rule_id.to_string(), "shortDescription": { "text": description } }),
+    ));
+
+    let results: Vec<_> = reports
+        .iter()
+        .flat_map(|report| {
+            report.entries.iter().map(move |entry| {
+                let mut region = json!({ "startLine": entry.line });
+                if let Some(column) = entry.column {
+                    region["startColumn"] = json!(column);
+                }
+                let message = match &entry.field {
+                    Some(field) => format!("{} {}: {}", entry.record_type, field, entry.message),
+                    None => format!("{}: {}", entry.record_type, entry.message),
+                };
+                json!({
+                    "ruleId": entry.rule,
+                    "level": entry.severity.sarif_level(),
+                    "message": { "text": message },
+                    "locations": [{
+                        "physicalLocation": {
+                            "artifactLocation": { "uri": report.file },
+                            "region": region,
+                        }
+                    }],
+                })
+            })
+        })
+        .collect();
+
+    // Files that could not be processed are tool execution failures rather than results
+    let notifications: Vec<_> = reports
+        .iter()
+        .filter_map(|report| {
+            report.error.as_ref().map(|error| {
+                json!({
+                    "level": "error",
+                    "message": { "text": error },
+                    "locations": [{ "physicalLocation": { "artifactLocation": { "uri": report.file } } }],
+                })
+            })
+        })
+        .collect();
+
+    let sarif = json!({
+        "version": "2.1.0",
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "runs": [{
+            "tool": { "driver": { "name": "cwr-validate", "version": env!("CARGO_PKG_VERSION"), "rules": rules } },
+            "invocations": [{
+                "executionSuccessful": notifications.is_empty(),
+                "toolExecutionNotifications": notifications,
+            }],
+            "results": results,
+        }],
+    });
+    serde_json::to_writer_pretty(writer, &sarif)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_difference_entries() {
+        let difference = classify_line_difference("TER0000000100000001I2840", "TER0000000100000001E2840").unwrap();
+        let entry = difference_entry(&difference, "TER0000000100000001I2840", 3, "TER");
+        assert_eq!(entry.rule, ROUNDTRIP_RULE);
+        assert_eq!(entry.severity, Severity::Error);
+        assert_eq!(entry.column, Some(20));
+        assert_eq!(entry.original_value.as_deref(), Some("I"));
+
+        let difference = classify_line_difference("TER0000000100000001I2840XX", "TER0000000100000001I2840").unwrap();
+        let entry = difference_entry(&difference, "TER0000000100000001I2840XX", 3, "TER");
+        assert_eq!(entry.rule, ROUNDTRIP_AMBIGUOUS_RULE);
+        assert_eq!(entry.severity, Severity::Info);
+        assert_eq!(entry.original_value.as_deref(), Some("XX"));
+    }
+
+    #[test]
+    fn test_failure_policy() {
+        let difference = classify_line_difference("TER0000000100000001I2840XX", "TER0000000100000001I2840").unwrap();
+        let entry = difference_entry(&difference, "TER0000000100000001I2840XX", 3, "TER");
+        assert_eq!(entry.category, FindingCategory::Ambiguous);
+        assert!(!FailurePolicy::default().is_fatal(&entry));
+
+        let policy: FailurePolicy = "ambiguous, length-mismatch".parse().unwrap();
+        assert!(policy.is_fatal(&entry));
+        assert!(!"none".parse::<FailurePolicy>().unwrap().is_fatal(&entry));
+        assert!("all".parse::<FailurePolicy>().unwrap().is_fatal(&entry));
+        assert!("ambiguous,typo".parse::<FailurePolicy>().unwrap_err().contains("typo"));
+
+        let mut report = FileReport {
+            file: "-".to_string(),
+            cwr_version: None,
+            record_count: 1,
+            passed: true,
+            entries: vec![entry],
+            error: None,
+        };
+        report.apply_policy(&policy);
+        assert!(!report.passed);
+        assert_eq!(report.fatal_entries(&policy).count(), 1);
+        assert_eq!(report.outcome(), Outcome::Failed);
+
+        let mut unreadable = FileReport::processing_error("missing.cwr", "No such file");
+        unreadable.apply_policy(&FailurePolicy::new([]));
+        assert!(!unreadable.passed);
+        assert_eq!(unreadable.outcome().max(report.outcome()), Outcome::Error);
+    }
+
+    #[test]
+    fn test_outcome_order() {
+        assert_eq!(Outcome::Failed.max(Outcome::Error), Outcome::Error);
+        assert_eq!(Outcome::Passed.max(Outcome::Failed), Outcome::Failed);
+        assert_eq!(Outcome::Error.exit_code(), 1);
+    }
+
+    #[test]
+    fn test_fragment_report() {
+        let fragment = "ALT0000000100000002ANOTHER TITLE                                               AT  \n\n\
+                        ALT0000000100000003OTHER TITLE                                                 XX  \n\
+                        ZZZ0000000100000004\n";
+        let report = build_fragment_report("-", fragment.as_bytes(), 2.2, None).unwrap();
+
+        assert_eq!(report.record_count, 2);
+        assert!(report.entries.iter().any(|e| e.line == 3 && e.category == FindingCategory::ParseWarning));
+        assert!(report.entries.iter().any(|e| e.line == 4 && e.category == FindingCategory::ParseError));
+        assert!(report.entries.iter().all(|e| e.line != 1));
+        assert!(!report.passed);
+    }
+
+    #[test]
+    fn test_report_format_parse() {
+        assert_eq!("SARIF".parse::<ReportFormat>(), Ok(ReportFormat::Sarif));
+        assert!("xml".parse::<ReportFormat>().is_err());
+    }
+}
@@ -0,0 +1,1959 @@
+//! Built-in CWR validation rules, numbered as in the CWR 2.2 specification
+
+use std::collections::{BTreeMap, HashSet};
+
+use allegro_cwr::cwr_registry::CwrRegistry;
+use allegro_cwr::domain_types::{
+    AgreementRoleCode, Flag, InclusionExclusionIndicator, OwnershipShare, PublisherType, ShareTotals,
+};
+use allegro_cwr::lookups::tis_codes::territory_exists;
+use allegro_cwr::records::{PwrRecord, SpuRecord};
+use allegro_cwr::territories::{expand_sequence, TerritoryConflict};
+
+use crate::engine::{
+    FileSummary, RecordRef, RejectionLevel, RuleId, RuleScope, RuleViolation, Transaction, ValidationRule,
+};
+
+/// All built-in rules
+pub fn default_rules() -> Vec<Box<dyn ValidationRule>> {
+    vec![
+        Box::new(FirstRecordHdr),
+        Box::new(SecondRecordGrh),
+        Box::new(GrhPrecededByGrt),
+        Box::new(LastRecordTrl),
+        Box::new(GrhFollowedByTransaction),
+        Box::new(GrtFollowedByGrhOrTrl),
+        Box::new(SingleHdrAndTrl::default()),
+        Box::new(GroupIdSequence::default()),
+        Box::new(UniqueGroupTransactionType::default()),
+        Box::new(GroupTrailerCheck::new(GroupTrailerField::GroupId)),
+        Box::new(GroupTrailerCheck::new(GroupTrailerField::TransactionCount)),
+        Box::new(GroupTrailerCheck::new(GroupTrailerField::RecordCount)),
+        Box::new(FileTrailerCheck::new(FileTrailerField::Groups)),
+        Box::new(FileTrailerCheck::new(FileTrailerField::Transactions)),
+        Box::new(FileTrailerCheck::new(FileTrailerField::Records)),
+        Box::new(AgrHasTerritory),
+        Box::new(AgrHasAssignorAndAcquirer),
+        Box::new(WorkHasWriter),
+        Box::new(PublisherChainCheck::new(PublisherChainRule::OpuInControlledChain)),
+        Box::new(PublisherChainCheck::new(PublisherChainRule::SingleOriginalPublisher)),
+        Box::new(PublisherChainCheck::new(PublisherChainRule::StartsWithOriginal)),
+        Box::new(PublisherChainCheck::new(PublisherChainRule::LinkedToOriginal)),
+        Box::new(PublisherChainCheck::new(PublisherChainRule::SequenceOrder)),
+        Box::new(SpuAgreementInFile::default()),
+        Box::new(TerritoryLinkCheck::new(&["SPT"], TerritoryLinkRule::FollowsParent)),
+        Box::new(TerritoryLinkCheck::new(&["OPT"], TerritoryLinkRule::FollowsParent)),
+        Box::new(TerritoryLinkCheck::new(&["SWT"], TerritoryLinkRule::FollowsParent)),
+        Box::new(TerritoryLinkCheck::new(&["OWT"], TerritoryLinkRule::FollowsParent)),
+        Box::new(TerritoryLinkCheck::new(&["SPT", "OPT"], TerritoryLinkRule::SameInterestedParty)),
+        Box::new(TerritoryLinkCheck::new(&["SWT"], TerritoryLinkRule::SameInterestedParty)),
+        Box::new(TerritoryLinkCheck::new(&["OWT"], TerritoryLinkRule::SameInterestedParty)),
+        Box::new(TerritoryLinkCheck::new(&["SPT", "OPT"], TerritoryLinkRule::SequenceNumber)),
+        Box::new(TerritoryLinkCheck::new(&["SWT", "OWT"], TerritoryLinkRule::SequenceNumber)),
+        Box::new(TerritorySequenceCheck::new(TerritoryParty::Publisher)),
+        Box::new(TerritorySequenceCheck::new(TerritoryParty::Writer)),
+        Box::new(CollectionMatchesOwnership),
+        Box::new(SwrHasPwr),
+        Box::new(PwrLinkCheck::new(PwrLinkRule::PublisherSequence)),
+        Box::new(PwrLinkCheck::new(PwrLinkRule::WriterEntered)),
+        Box::new(PwrLinkCheck::new(PwrLinkRule::PublisherEntered)),
+        Box::new(PwrLinkCheck::new(PwrLinkRule::Writer)),
+        Box::new(PwrLinkCheck::new(PwrLinkRule::Publisher)),
+        Box::new(TerTisCodeExists),
+    ]
+}
+
+fn is_work_transaction(record_type: &str) -> bool {
+    matches!(record_type, "NWR" | "REV" | "ISW" | "EXC")
+}
+
+/// Identifies the work a transaction registers, for messages about its detail records
+fn work_label(transaction: &Transaction) -> String {
+    match transaction.header() {
+        Some((_, CwrRegistry::Nwr(nwr))) => {
+            format!("work '{}' ({})", nwr.work_title.trim(), nwr.submitter_work_num.trim())
+        }
+        _ => format!("transaction at line {}", transaction.header_line()),
+    }
+}
+
+/// Tolerance the specification allows on share totals (0.06%)
+const SHARE_TOLERANCE: u16 = 6;
+
+/// A territory of control record (SPT/OPT/SWT/OWT) with the publisher or writer record it belongs to
+struct TerritoryLink<'a> {
+    line: usize,
+    record: &'a CwrRegistry,
+    /// Nearest preceding SPU/OPU/SWR/OWR, skipping name records and other territories
+    parent: Option<(usize, &'a CwrRegistry)>,
+    /// One-based position among the territories following the parent
+    position: u32,
+}
+
+fn territory_links(transaction: &Transaction) -> Vec<TerritoryLink<'_>> {
+    let mut links = Vec::new();
+    let mut parent = None;
+    let mut position = 0;
+    for (line, record) in &transaction.records {
+        match record.record_type() {
+            "SPU" | "OPU" | "SWR" | "OWR" => {
+                parent = Some((*line, record));
+                position = 0;
+            }
+            "SPT" | "OPT" | "SWT" | "OWT" => {
+                position += 1;
+                links.push(TerritoryLink { line: *line, record, parent, position });
+            }
+            "NPN" | "NWN" => {}
+            _ => {
+                parent = None;
+                position = 0;
+            }
+        }
+    }
+    links
+}
+
+/// Record types a territory record may belong to
+fn territory_parent_types(territory_type: &str) -> &'static [&'static str] {
+    match territory_type {
+        "SPT" => &["SPU"],
+        "OPT" => &["SPU", "OPU"],
+        "SWT" => &["SWR"],
+        "OWT" => &["OWR"],
+        _ => &[],
+    }
+}
+
+fn interested_party_num(record: &CwrRegistry) -> &str {
+    let ip_num = match record {
+        CwrRegistry::Spu(spu) => spu.interested_party_num.as_deref(),
+        CwrRegistry::Swr(swr) => swr.interested_party_num.as_deref(),
+        CwrRegistry::Spt(spt) => Some(spt.interested_party_num.as_str()),
+        CwrRegistry::Swt(swt) => swt.interested_party_num.as_deref(),
+        _ => None,
+    };
+    ip_num.unwrap_or_default().trim()
+}
+
+/// A PWR record with the writer record it follows
+struct WriterLink<'a> {
+    line: usize,
+    pwr: &'a PwrRecord,
+    /// Nearest preceding SWR/OWR, skipping name, territory and other PWR records
+    writer: Option<(usize, &'a CwrRegistry)>,
+}
+
+fn writer_links(transaction: &Transaction) -> Vec<WriterLink<'_>> {
+    let mut links = Vec::new();
+    let mut writer = None;
+    for (line, record) in &transaction.records {
+        match record {
+            CwrRegistry::Swr(_) => writer = Some((*line, record)),
+            CwrRegistry::Pwr(pwr) => links.push(WriterLink { line: *line, pwr, writer }),
+            CwrRegistry::Nwn(_) | CwrRegistry::Swt(_) => {}
+            _ => writer = None,
+        }
+    }
+    links
+}
+
+/// Consecutive SPU/OPU records sharing a publisher sequence number
+struct PublisherChain<'a> {
+    sequence: u8,
+    publishers: Vec<(usize, &'a SpuRecord)>,
+}
+
+impl PublisherChain<'_> {
+    fn first_line(&self) -> usize {
+        self.publishers.first().map_or(0, |(line, _)| *line)
+    }
+
+    /// True if the chain was started by a controlled (SPU) publisher
+    fn is_controlled(&self) -> bool {
+        self.publishers.first().is_some_and(|(_, spu)| spu.is_controlled())
+    }
+
+    fn has_original(&self) -> bool {
+        self.publishers.iter().any(|(_, spu)| is_chain_start(spu))
+    }
+}
+
+/// An OPU with a blank publisher type defaults to an original publisher (SPU field level validation 28)
+fn is_chain_start(spu: &SpuRecord) -> bool {
+    match &spu.publisher_type {
+        Some(PublisherType::OriginalPublisher | PublisherType::IncomeParticipant) => true,
+        Some(_) => false,
+        None => !spu.is_controlled(),
+    }
+}
+
+/// Splits a transaction's publisher records into chains, in file order. A sequence number that reappears after
+/// another chain starts a new chain, so out-of-order records show up as extra chains.
+fn publisher_chains(transaction: &Transaction) -> Vec<PublisherChain<'_>> {
+    let mut chains: Vec<PublisherChain<'_>> = Vec::new();
+    for (line, record) in &transaction.records {
+        let CwrRegistry::Spu(spu) = record else {
+            continue;
+        };
+        let sequence = spu.publisher_sequence_num.0;
+        match chains.last_mut() {
+            Some(chain) if chain.sequence == sequence => chain.publishers.push((*line, spu)),
+            _ => chains.push(PublisherChain { sequence, publishers: vec![(*line, spu)] }),
+        }
+    }
+    chains
+}
+
+// File level validation (section 3.4)
+
+struct FirstRecordHdr;
+
+impl ValidationRule for FirstRecordHdr {
+    fn id(&self) -> RuleId {
+        RuleId::new("HDR", RuleScope::File, 2)
+    }
+
+    fn description(&self) -> &'static str {
+        "The first record on the file must be HDR"
+    }
+
+    fn level(&self) -> RejectionLevel {
+        RejectionLevel::EntireFile
+    }
+
+    fn check_record(&mut self, record: &RecordRef<'_>) -> Vec<RuleViolation> {
+        let record_type = record.record.record_type();
+        if record.index == 0 && record_type != "HDR" {
+            return vec![self.violation(
+                record.line_number,
+                record_type,
+                format!("First record is {}, expected HDR", record_type),
+            )];
+        }
+        Vec::new()
+    }
+}
+
+struct SecondRecordGrh;
+
+impl ValidationRule for SecondRecordGrh {
+    fn id(&self) -> RuleId {
+        RuleId::new("HDR", RuleScope::File, 3)
+    }
+
+    fn description(&self) -> &'static str {
+        "The second record on the file must be GRH"
+    }
+
+    fn level(&self) -> RejectionLevel {
+        RejectionLevel::EntireFile
+    }
+
+    fn check_record(&mut self, record: &RecordRef<'_>) -> Vec<RuleViolation> {
+        let record_type = record.record.record_type();
+        if record.index == 1 && record_type != "GRH" {
+            return vec![self.violation(
+                record.line_number,
+                record_type,
+                format!("Second record is {}, expected GRH", record_type),
+            )];
+        }
+        Vec::new()
+    }
+}
+
+struct GrhPrecededByGrt;
+
+impl ValidationRule for GrhPrecededByGrt {
+    fn id(&self) -> RuleId {
+        RuleId::new("HDR", RuleScope::File, 4)
+    }
+
+    fn description(&self) -> &'static str {
+        "Every GRH after the first must be preceded by a GRT"
+    }
+
+    fn level(&self) -> RejectionLevel {
+        RejectionLevel::EntireFile
+    }
+
+    fn check_record(&mut self, record: &RecordRef<'_>) -> Vec<RuleViolation> {
+        if record.record.record_type() != "GRH" {
+            return Vec::new();
+        }
+        match record.previous_record_type {
+            None | Some("HDR") | Some("GRT") => Vec::new(),
+            Some(previous) => vec![self.violation(
+                record.line_number,
+                "GRH",
+                format!("GRH is preceded by {}, expected GRT", previous),
+            )],
+        }
+    }
+}
+
+struct LastRecordTrl;
+
+impl ValidationRule for LastRecordTrl {
+    fn id(&self) -> RuleId {
+        RuleId::new("HDR", RuleScope::File, 5)
+    }
+
+    fn description(&self) -> &'static str {
+        "The last record on the file must be TRL"
+    }
+
+    fn level(&self) -> RejectionLevel {
+        RejectionLevel::EntireFile
+    }
+
+    fn finish(&mut self, summary: &FileSummary) -> Vec<RuleViolation> {
+        match summary.last_record_type.as_deref() {
+            Some("TRL") | None => Vec::new(),
+            Some(last) => {
+                vec![self.violation(summary.last_line_number, last, format!("Last record is {}, expected TRL", last))]
+            }
+        }
+    }
+}
+
+struct GrhFollowedByTransaction;
+
+impl ValidationRule for GrhFollowedByTransaction {
+    fn id(&self) -> RuleId {
+        RuleId::new("HDR", RuleScope::File, 6)
+    }
+
+    fn description(&self) -> &'static str {
+        "GRH must be followed by a transaction header record"
+    }
+
+    fn level(&self) -> RejectionLevel {
+        RejectionLevel::EntireFile
+    }
+
+    fn check_record(&mut self, record: &RecordRef<'_>) -> Vec<RuleViolation> {
+        let record_type = record.record.record_type();
+        if record.previous_record_type == Some("GRH") && !record.starts_transaction {
+            return vec![self.violation(
+                record.line_number,
+                record_type,
+                format!("GRH is followed by {}, expected a transaction header", record_type),
+            )];
+        }
+        Vec::new()
+    }
+}
+
+struct GrtFollowedByGrhOrTrl;
+
+impl ValidationRule for GrtFollowedByGrhOrTrl {
+    fn id(&self) -> RuleId {
+        RuleId::new("HDR", RuleScope::File, 7)
+    }
+
+    fn description(&self) -> &'static str {
+        "GRT must be followed by GRH or TRL"
+    }
+
+    fn level(&self) -> RejectionLevel {
+        RejectionLevel::EntireFile
+    }
+
+    fn check_record(&mut self, record: &RecordRef<'_>) -> Vec<RuleViolation> {
+        let record_type = record.record.record_type();
+        if record.previous_record_type == Some("GRT") && !matches!(record_type, "GRH" | "TRL") {
+            return vec![self.violation(
+                record.line_number,
+                record_type,
+                format!("GRT is followed by {}, expected GRH or TRL", record_type),
+            )];
+        }
+        Vec::new()
+    }
+}
+
+#[derive(Default)]
+struct SingleHdrAndTrl {
+    hdr_seen: bool,
+    trl_seen: bool,
+}
+
+impl ValidationRule for SingleHdrAndTrl {
+    fn id(&self) -> RuleId {
+        RuleId::new("HDR", RuleScope::File, 8)
+    }
+
+    fn description(&self) -> &'static str {
+        "The file may contain only one HDR and one TRL"
+    }
+
+    fn level(&self) -> RejectionLevel {
+        RejectionLevel::EntireFile
+    }
+
+    fn check_record(&mut self, record: &RecordRef<'_>) -> Vec<RuleViolation> {
+        let seen = match record.record.record_type() {
+            "HDR" => &mut self.hdr_seen,
+            "TRL" => &mut self.trl_seen,
+            _ => return Vec::new(),
+        };
+        if std::mem::replace(seen, true) {
+            let record_type = record.record.record_type();
+            return vec![self.violation(
+                record.line_number,
+                record_type,
+                format!("More than one {} record on the file", record_type),
+            )];
+        }
+        Vec::new()
+    }
+}
+
+// GRH field level validation (section 3.6)
+
+#[derive(Default)]
+struct GroupIdSequence {
+    expected: u32,
+}
+
+impl ValidationRule for GroupIdSequence {
+    fn id(&self) -> RuleId {
+        RuleId::new("GRH", RuleScope::Field, 2)
+    }
+
+    fn description(&self) -> &'static str {
+        "Group ID must start at 1 and increment by 1 for each group"
+    }
+
+    fn level(&self) -> RejectionLevel {
+        RejectionLevel::Group
+    }
+
+    fn check_record(&mut self, record: &RecordRef<'_>) -> Vec<RuleViolation> {
+        let CwrRegistry::Grh(grh) = record.record else {
+            return Vec::new();
+        };
+        self.expected += 1;
+        if grh.group_id.0 != self.expected {
+            let message = format!("Group ID is {}, expected {}", grh.group_id.0, self.expected);
+            self.expected = grh.group_id.0;
+            return vec![self
+                .violation(record.line_number, "GRH", message)
+                .with_field("group_id", grh.group_id.0.to_string())];
+        }
+        Vec::new()
+    }
+}
+
+#[derive(Default)]
+struct UniqueGroupTransactionType {
+    seen: HashSet<String>,
+}
+
+impl ValidationRule for UniqueGroupTransactionType {
+    fn id(&self) -> RuleId {
+        RuleId::new("GRH", RuleScope::Field, 5)
+    }
+
+    fn description(&self) -> &'static str {
+        "Each group transaction type can only be used once per file"
+    }
+
+    fn level(&self) -> RejectionLevel {
+        RejectionLevel::Group
+    }
+
+    fn check_record(&mut self, record: &RecordRef<'_>) -> Vec<RuleViolation> {
+        let CwrRegistry::Grh(grh) = record.record else {
+            return Vec::new();
+        };
+        let transaction_type = grh.transaction_type.as_str().to_string();
+        if !self.seen.insert(transaction_type.clone()) {
+            return vec![self
+                .violation(
+                    record.line_number,
+                    "GRH",
+                    format!("Transaction type {} already used by an earlier group", transaction_type),
+                )
+                .with_field("transaction_type", transaction_type)];
+        }
+        Vec::new()
+    }
+}
+
+// GRT field level validation (section 3.7)
+
+#[derive(Clone, Copy)]
+enum GroupTrailerField {
+    GroupId,
+    TransactionCount,
+    RecordCount,
+}
+
+struct GroupTrailerCheck {
+    field: GroupTrailerField,
+    group_id: Option<u32>,
+    transactions: u32,
+    records: u32,
+}
+
+impl GroupTrailerCheck {
+    fn new(field: GroupTrailerField) -> Self {
+        GroupTrailerCheck { field, group_id: None, transactions: 0, records: 0 }
+    }
+}
+
+impl ValidationRule for GroupTrailerCheck {
+    fn id(&self) -> RuleId {
+        let number = match self.field {
+            GroupTrailerField::GroupId => 1,
+            GroupTrailerField::TransactionCount => 2,
+            GroupTrailerField::RecordCount => 3,
+        };
+        RuleId::new("GRT", RuleScope::Field, number)
+    }
+
+    fn description(&self) -> &'static str {
+        match self.field {
+            GroupTrailerField::GroupId => "Group ID must equal the Group ID on the preceding GRH",
+            GroupTrailerField::TransactionCount => {
+                "Transaction count must equal the number of transactions in the group"
+            }
+            GroupTrailerField::RecordCount => "Record count must equal the number of records in the group",
+        }
+    }
+
+    fn level(&self) -> RejectionLevel {
+        RejectionLevel::Group
+    }
+
+    fn check_record(&mut self, record: &RecordRef<'_>) -> Vec<RuleViolation> {
+        match record.record {
+            CwrRegistry::Grh(grh) => {
+                self.group_id = Some(grh.group_id.0);
+                self.transactions = 0;
+                self.records = 1;
+                Vec::new()
+            }
+            CwrRegistry::Grt(grt) => {
+                self.records += 1;
+                let (found, expected) = match self.field {
+                    GroupTrailerField::GroupId => (grt.group_id.0, self.group_id.unwrap_or(0)),
+                    GroupTrailerField::TransactionCount => (grt.transaction_count.0, self.transactions),
+                    GroupTrailerField::RecordCount => (grt.record_count.0, self.records),
+                };
+                self.group_id = None;
+                if found != expected {
+                    return vec![self
+                        .violation(
+                            record.line_number,
+                            "GRT",
+                            format!("{} is {}, expected {}", self.field_name(), found, expected),
+                        )
+                        .with_field(self.field_key(), found.to_string())];
+                }
+                Vec::new()
+            }
+            _ => {
+                self.records += 1;
+                if record.starts_transaction {
+                    self.transactions += 1;
+                }
+                Vec::new()
+            }
+        }
+    }
+}
+
+impl GroupTrailerCheck {
+    fn field_key(&self) -> &'static str {
+        match self.field {
+            GroupTrailerField::GroupId => "group_id",
+            GroupTrailerField::TransactionCount => "transaction_count",
+            GroupTrailerField::RecordCount => "record_count",
+        }
+    }
+
+    fn field_name(&self) -> &'static str {
+        match self.field {
+            GroupTrailerField::GroupId => "Group ID",
+            GroupTrailerField::TransactionCount => "Transaction count",
+            GroupTrailerField::RecordCount => "Record count",
+        }
+    }
+}
+
+// TRL field level validation (section 3.8)
+
+#[derive(Clone, Copy)]
+enum FileTrailerField {
+    Groups,
+    Transactions,
+    Records,
+}
+
+struct FileTrailerCheck {
+    field: FileTrailerField,
+    groups: u32,
+    transactions: u32,
+    records: u32,
+}
+
+impl FileTrailerCheck {
+    fn new(field: FileTrailerField) -> Self {
+        FileTrailerCheck { field, groups: 0, transactions: 0, records: 0 }
+    }
+
+    fn field_key(&self) -> &'static str {
+        match self.field {
+            FileTrailerField::Groups => "group_count",
+            FileTrailerField::Transactions => "transaction_count",
+            FileTrailerField::Records => "record_count",
+        }
+    }
+
+    fn field_name(&self) -> &'static str {
+        match self.field {
+            FileTrailerField::Groups => "Group count",
+            FileTrailerField::Transactions => "Transaction count",
+            FileTrailerField::Records => "Record count",
+        }
+    }
+}
+
+impl ValidationRule for FileTrailerCheck {
+    fn id(&self) -> RuleId {
+        let number = match self.field {
+            FileTrailerField::Groups => 1,
+            FileTrailerField::Transactions => 2,
+            FileTrailerField::Records => 3,
+        };
+        RuleId::new("TRL", RuleScope::Field, number)
+    }
+
+    fn description(&self) -> &'static str {
+        match self.field {
+            FileTrailerField::Groups => "Group count must equal the number of groups in the file",
+            FileTrailerField::Transactions => "Transaction count must equal the number of transactions in the file",
+            FileTrailerField::Records => "Record count must equal the number of records in the file",
+        }
+    }
+
+    fn level(&self) -> RejectionLevel {
+        RejectionLevel::EntireFile
+    }
+
+    fn check_record(&mut self, record: &RecordRef<'_>) -> Vec<RuleViolation> {
+        self.records += 1;
+        match record.record {
+            CwrRegistry::Grh(_) => self.groups += 1,
+            CwrRegistry::Trl(trl) => {
+                let (found, expected) = match self.field {
+                    FileTrailerField::Groups => (trl.group_count.0, self.groups),
+                    FileTrailerField::Transactions => (trl.transaction_count.0, self.transactions),
+                    FileTrailerField::Records => (trl.record_count.0, self.records),
+                };
+                if found != expected {
+                    return vec![self
+                        .violation(
+                            record.line_number,
+                            "TRL",
+                            format!("{} is {}, expected {}", self.field_name(), found, expected),
+                        )
+                        .with_field(self.field_key(), found.to_string())];
+                }
+            }
+            _ if record.starts_transaction => self.transactions += 1,
+            _ => {}
+        }
+        Vec::new()
+    }
+}
+
+// AGR transaction level validation (section 4.1)
+
+struct AgrHasTerritory;
+
+impl ValidationRule for AgrHasTerritory {
+    fn id(&self) -> RuleId {
+        RuleId::new("AGR", RuleScope::Transaction, 2)
+    }
+
+    fn description(&self) -> &'static str {
+        "There must be at least one TER record per AGR transaction"
+    }
+
+    fn level(&self) -> RejectionLevel {
+        RejectionLevel::Transaction
+    }
+
+    fn check_transaction(&mut self, transaction: &Transaction) -> Vec<RuleViolation> {
+        if transaction.header_type() != "AGR" {
+            return Vec::new();
+        }
+        if !transaction.records.iter().any(|(_, record)| matches!(record, CwrRegistry::Ter(_))) {
+            return vec![self.violation(transaction.header_line(), "AGR", "Agreement has no TER record".to_string())];
+        }
+        Vec::new()
+    }
+}
+
+struct AgrHasAssignorAndAcquirer;
+
+impl ValidationRule for AgrHasAssignorAndAcquirer {
+    fn id(&self) -> RuleId {
+        RuleId::new("AGR", RuleScope::Transaction, 3)
+    }
+
+    fn description(&self) -> &'static str {
+        "An AGR transaction must have an assignor IPA and an acquirer IPA"
+    }
+
+    fn level(&self) -> RejectionLevel {
+        RejectionLevel::Transaction
+    }
+
+    fn check_transaction(&mut self, transaction: &Transaction) -> Vec<RuleViolation> {
+        if transaction.header_type() != "AGR" {
+            return Vec::new();
+        }
+        let roles: Vec<&AgreementRoleCode> = transaction
+            .records
+            .iter()
+            .filter_map(|(_, record)| match record {
+                CwrRegistry::Ipa(ipa) => Some(&ipa.agreement_role_code),
+                _ => None,
+            })
+            .collect();
+        let has_assignor = roles.iter().any(|role| matches!(role, AgreementRoleCode::Assignor));
+        let has_acquirer = roles.iter().any(|role| matches!(role, AgreementRoleCode::Acquirer));
+        if !has_assignor || !has_acquirer {
+            return vec![self.violation(
+                transaction.header_line(),
+                "AGR",
+                format!(
+                    "Agreement has {} IPA records (assignor: {}, acquirer: {})",
+                    roles.len(),
+                    if has_assignor { "yes" } else { "no" },
+                    if has_acquirer { "yes" } else { "no" }
+                ),
+            )];
+        }
+        Vec::new()
+    }
+}
+
+// NWR/REV transaction level validation (section 4.2)
+
+struct WorkHasWriter;
+
+impl ValidationRule for WorkHasWriter {
+    fn id(&self) -> RuleId {
+        RuleId::new("NWR", RuleScope::Transaction, 12)
+    }
+
+    fn description(&self) -> &'static str {
+        "A work transaction must contain at least one SWR or OWR record"
+    }
+
+    fn level(&self) -> RejectionLevel {
+        RejectionLevel::Transaction
+    }
+
+    fn check_transaction(&mut self, transaction: &Transaction) -> Vec<RuleViolation> {
+        let header_type = transaction.header_type();
+        if !matches!(header_type, "NWR" | "REV") {
+            return Vec::new();
+        }
+        if !transaction.records.iter().any(|(_, record)| matches!(record, CwrRegistry::Swr(_))) {
+            return vec![self.violation(
+                transaction.header_line(),
+                header_type,
+                "Work has no SWR or OWR record".to_string(),
+            )];
+        }
+        Vec::new()
+    }
+}
+
+// SPU/OPU publisher chain validation (sections 4.2 and 5.4)
+
+#[derive(Clone, Copy)]
+enum PublisherChainRule {
+    OpuInControlledChain,
+    SingleOriginalPublisher,
+    StartsWithOriginal,
+    LinkedToOriginal,
+    SequenceOrder,
+}
+
+struct PublisherChainCheck {
+    rule: PublisherChainRule,
+}
+
+impl PublisherChainCheck {
+    fn new(rule: PublisherChainRule) -> Self {
+        PublisherChainCheck { rule }
+    }
+
+    fn check_chains(&self, chains: &[PublisherChain<'_>], work: &str) -> Vec<RuleViolation> {
+        let mut violations = Vec::new();
+        match self.rule {
+            PublisherChainRule::OpuInControlledChain => {
+                for chain in chains.iter().filter(|chain| chain.is_controlled()) {
+                    for (line, _) in chain.publishers.iter().filter(|(_, spu)| !spu.is_controlled()) {
+                        violations.push(self.violation(
+                            *line,
+                            "OPU",
+                            format!("OPU in controlled publisher chain {} of {}", chain.sequence, work),
+                        ));
+                    }
+                }
+            }
+            PublisherChainRule::SingleOriginalPublisher => {
+                for chain in chains {
+                    let originals = chain
+                        .publishers
+                        .iter()
+                        .filter(|(_, spu)| matches!(spu.publisher_type, Some(PublisherType::OriginalPublisher)));
+                    for (line, spu) in originals.skip(1) {
+                        violations.push(self.violation(
+                            *line,
+                            &spu.record_type,
+                            format!(
+                                "Publisher chain {} of {} has more than one original publisher",
+                                chain.sequence, work
+                            ),
+                        ));
+                    }
+                }
+            }
+            PublisherChainRule::StartsWithOriginal => {
+                for chain in chains {
+                    let Some((line, spu)) = chain.publishers.first() else {
+                        continue;
+                    };
+                    if !is_chain_start(spu) {
+                        let publisher_type = spu.publisher_type.as_ref().map_or("", PublisherType::as_str);
+                        violations.push(
+                            self.violation(
+                                *line,
+                                &spu.record_type,
+                                format!(
+                                    "Publisher chain {} of {} starts with publisher type '{}', expected E or PA",
+                                    chain.sequence, work, publisher_type
+                                ),
+                            )
+                            .with_field("publisher_type", publisher_type),
+                        );
+                    }
+                }
+            }
+            PublisherChainRule::LinkedToOriginal => {
+                let linked: HashSet<u8> =
+                    chains.iter().filter(|chain| chain.has_original()).map(|chain| chain.sequence).collect();
+                for chain in chains.iter().filter(|chain| !linked.contains(&chain.sequence)) {
+                    for (line, spu) in chain.publishers.iter().filter(|(_, spu)| !is_chain_start(spu)) {
+                        violations.push(
+                            self.violation(
+                                *line,
+                                &spu.record_type,
+                                format!(
+                                    "Publisher sequence number {} of {} does not belong to an original publisher or income participant",
+                                    chain.sequence, work
+                                ),
+                            )
+                            .with_field("publisher_sequence_num", spu.publisher_sequence_num.as_str()),
+                        );
+                    }
+                }
+            }
+            PublisherChainRule::SequenceOrder => {
+                let mut expected = 1;
+                for chain in chains {
+                    if chain.sequence != expected {
+                        violations.push(
+                            self.violation(
+                                chain.first_line(),
+                                chain.publishers.first().map_or("SPU", |(_, spu)| &spu.record_type),
+                                format!(
+                                    "Publisher chain of {} has sequence number {}, expected {}",
+                                    work, chain.sequence, expected
+                                ),
+                            )
+                            .with_field("publisher_sequence_num", format!("{:02}", chain.sequence)),
+                        );
+                    }
+                    expected = chain.sequence.saturating_add(1);
+                }
+            }
+        }
+        violations
+    }
+}
+
+impl ValidationRule for PublisherChainCheck {
+    fn id(&self) -> RuleId {
+        match self.rule {
+            PublisherChainRule::OpuInControlledChain => RuleId::new("NWR", RuleScope::Transaction, 16),
+            PublisherChainRule::SingleOriginalPublisher => RuleId::new("NWR", RuleScope::Transaction, 17),
+            PublisherChainRule::StartsWithOriginal => RuleId::new("SPU", RuleScope::Record, 2),
+            PublisherChainRule::LinkedToOriginal => RuleId::new("SPU", RuleScope::Record, 5),
+            PublisherChainRule::SequenceOrder => RuleId::new("SPU", RuleScope::Field, 1),
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        match self.rule {
+            PublisherChainRule::OpuInControlledChain => {
+                "A non-controlled publisher (OPU) can not appear in a chain started with a controlled publisher (SPU)"
+            }
+            PublisherChainRule::SingleOriginalPublisher => {
+                "There can only be one original publisher (Publisher Type E) in a publisher chain"
+            }
+            PublisherChainRule::StartsWithOriginal => {
+                "The first SPU record within a chain must be for an Original Publisher or Income Participant"
+            }
+            PublisherChainRule::LinkedToOriginal => {
+                "Administrators and sub-publishers must have the publisher sequence number of their original publisher"
+            }
+            PublisherChainRule::SequenceOrder => {
+                "The first publisher chain must have sequence number 1, and each following chain the prior one plus 1"
+            }
+        }
+    }
+
+    fn level(&self) -> RejectionLevel {
+        RejectionLevel::Transaction
+    }
+
+    fn check_transaction(&mut self, transaction: &Transaction) -> Vec<RuleViolation> {
+        if !is_work_transaction(transaction.header_type()) {
+            return Vec::new();
+        }
+        self.check_chains(&publisher_chains(transaction), &work_label(transaction))
+    }
+}
+
+/// Submitter agreement numbers can only be checked against agreements on file with a society when the file itself
+/// carries AGR transactions, so this rule stays silent for files without an agreement group
+#[derive(Default)]
+struct SpuAgreementInFile {
+    agreements: HashSet<String>,
+    references: Vec<(usize, String, String)>,
+}
+
+impl ValidationRule for SpuAgreementInFile {
+    fn id(&self) -> RuleId {
+        RuleId::new("SPU", RuleScope::Field, 11)
+    }
+
+    fn description(&self) -> &'static str {
+        "A Submitter Agreement Number must match an agreement on file or an AGR transaction"
+    }
+
+    fn level(&self) -> RejectionLevel {
+        RejectionLevel::Field
+    }
+
+    fn check_record(&mut self, record: &RecordRef<'_>) -> Vec<RuleViolation> {
+        match record.record {
+            CwrRegistry::Agr(agr) => {
+                self.agreements.insert(agr.submitter_agreement_number.trim().to_string());
+            }
+            CwrRegistry::Spu(spu) => {
+                let number = spu.submitter_agreement_number.as_deref().unwrap_or_default().trim();
+                if !number.is_empty() {
+                    self.references.push((record.line_number, spu.record_type.to_string(), number.to_string()));
+                }
+            }
+            _ => {}
+        }
+        Vec::new()
+    }
+
+    fn finish(&mut self, _summary: &FileSummary) -> Vec<RuleViolation> {
+        if self.agreements.is_empty() {
+            return Vec::new();
+        }
+        std::mem::take(&mut self.references)
+            .into_iter()
+            .filter(|(_, _, number)| !self.agreements.contains(number))
+            .map(|(line, record_type, number)| {
+                self.violation(
+                    line,
+                    &record_type,
+                    format!("Submitter agreement number {} does not match any AGR transaction in the file", number),
+                )
+                .with_field("submitter_agreement_number", number)
+            })
+            .collect()
+    }
+}
+
+// SPT/OPT and SWT/OWT territory validation (sections 5.7 and 5.12)
+
+#[derive(Clone, Copy)]
+enum TerritoryLinkRule {
+    FollowsParent,
+    SameInterestedParty,
+    SequenceNumber,
+}
+
+struct TerritoryLinkCheck {
+    territory_types: &'static [&'static str],
+    rule: TerritoryLinkRule,
+}
+
+impl TerritoryLinkCheck {
+    fn new(territory_types: &'static [&'static str], rule: TerritoryLinkRule) -> Self {
+        TerritoryLinkCheck { territory_types, rule }
+    }
+
+    fn check_link(&self, link: &TerritoryLink<'_>, work: &str) -> Option<RuleViolation> {
+        let territory_type = link.record.record_type();
+        let parent_types = territory_parent_types(territory_type);
+        let parent =
+            link.parent.map(|(_, parent)| parent).filter(|parent| parent_types.contains(&parent.record_type()));
+        match self.rule {
+            TerritoryLinkRule::FollowsParent if parent.is_none() => Some(self.violation(
+                link.line,
+                territory_type,
+                format!("{} in {} does not follow a {} record", territory_type, work, parent_types.join(" or ")),
+            )),
+            TerritoryLinkRule::FollowsParent => None,
+            TerritoryLinkRule::SameInterestedParty => {
+                let parent = parent?;
+                let ip_num = interested_party_num(link.record);
+                let parent_ip_num = interested_party_num(parent);
+                // An OWT may leave the interested party blank (SWT field level validation 13)
+                if ip_num == parent_ip_num || (territory_type == "OWT" && ip_num.is_empty()) {
+                    return None;
+                }
+                Some(
+                    self.violation(
+                        link.line,
+                        territory_type,
+                        format!(
+                            "{} interested party '{}' in {} does not match '{}' on the preceding {}",
+                            territory_type,
+                            ip_num,
+                            work,
+                            parent_ip_num,
+                            parent.record_type()
+                        ),
+                    )
+                    .with_field("interested_party_num", ip_num),
+                )
+            }
+            TerritoryLinkRule::SequenceNumber => {
+                let sequence = match link.record {
+                    CwrRegistry::Spt(spt) => spt.sequence_num.as_ref(),
+                    CwrRegistry::Swt(swt) => swt.sequence_num.as_ref(),
+                    _ => None,
+                }?;
+                (sequence.0 != link.position).then(|| {
+                    self.violation(
+                        link.line,
+                        territory_type,
+                        format!(
+                            "{} sequence number in {} is {}, expected {}",
+                            territory_type, work, sequence.0, link.position
+                        ),
+                    )
+                    .with_field("sequence_num", format!("{:03}", sequence.0))
+                })
+            }
+        }
+    }
+}
+
+impl ValidationRule for TerritoryLinkCheck {
+    fn id(&self) -> RuleId {
+        let (record_type, number) = match (self.rule, self.territory_types.first().copied().unwrap_or_default()) {
+            (TerritoryLinkRule::FollowsParent, "SPT") => ("SPT", 1),
+            (TerritoryLinkRule::FollowsParent, "OPT") => ("SPT", 14),
+            (TerritoryLinkRule::FollowsParent, "SWT") => ("SWT", 1),
+            (TerritoryLinkRule::FollowsParent, _) => ("SWT", 11),
+            (TerritoryLinkRule::SameInterestedParty, "SPT") => ("SPT", 2),
+            (TerritoryLinkRule::SameInterestedParty, "SWT") => ("SWT", 2),
+            (TerritoryLinkRule::SameInterestedParty, _) => ("SWT", 13),
+            (TerritoryLinkRule::SequenceNumber, "SPT") => ("SPT", 13),
+            (TerritoryLinkRule::SequenceNumber, _) => ("SWT", 10),
+        };
+        RuleId::new(record_type, RuleScope::Field, number)
+    }
+
+    fn description(&self) -> &'static str {
+        match (self.rule, self.territory_types.first().copied().unwrap_or_default()) {
+            (TerritoryLinkRule::FollowsParent, "SPT") => "SPT records must follow an SPU, NPN or SPT record",
+            (TerritoryLinkRule::FollowsParent, "OPT") => {
+                "OPT records must follow an SPU, NPN, SPT, OPU or OPT record"
+            }
+            (TerritoryLinkRule::FollowsParent, "SWT") => "An SWT record must follow an SWR, NWN or SWT record",
+            (TerritoryLinkRule::FollowsParent, _) => "An OWT must follow an OWR, NWN, or OWT record",
+            (TerritoryLinkRule::SameInterestedParty, "SPT") => {
+                "The Interested Party # must be equal to the Interested Party # on the previous SPU record"
+            }
+            (TerritoryLinkRule::SameInterestedParty, "SWT") => {
+                "For an SWT record the Interested Party # must be equal to the Interested Party # on the previous SWR"
+            }
+            (TerritoryLinkRule::SameInterestedParty, _) => {
+                "For an OWT record the Interested Party # must be equal to the Interested Party # on the previous OWR"
+            }
+            (TerritoryLinkRule::SequenceNumber, "SPT") => {
+                "Sequence # must be 1 for the first SPT/OPT after an SPU/OPU, and increment by 1 for each subsequent SPT"
+            }
+            (TerritoryLinkRule::SequenceNumber, _) => {
+                "Sequence # must be 1 for the first SWT after an SWR and increment by 1 for each subsequent SWT"
+            }
+        }
+    }
+
+    fn level(&self) -> RejectionLevel {
+        match self.rule {
+            TerritoryLinkRule::SequenceNumber => RejectionLevel::Record,
+            _ => RejectionLevel::Transaction,
+        }
+    }
+
+    fn check_transaction(&mut self, transaction: &Transaction) -> Vec<RuleViolation> {
+        if !is_work_transaction(transaction.header_type()) {
+            return Vec::new();
+        }
+        let work = work_label(transaction);
+        territory_links(transaction)
+            .iter()
+            .filter(|link| self.territory_types.contains(&link.record.record_type()))
+            .filter_map(|link| self.check_link(link, &work))
+            .collect()
+    }
+}
+
+#[derive(Clone, Copy)]
+enum TerritoryParty {
+    Publisher,
+    Writer,
+}
+
+/// Each territory may only be covered once by the include/exclude sequence of territories following a publisher or
+/// writer: the sequence is expanded with the TIS hierarchy and its contradictions reported
+struct TerritorySequenceCheck {
+    party: TerritoryParty,
+}
+
+impl TerritorySequenceCheck {
+    fn new(party: TerritoryParty) -> Self {
+        TerritorySequenceCheck { party }
+    }
+
+    fn territory_types(&self) -> &'static [&'static str] {
+        match self.party {
+            TerritoryParty::Publisher => &["SPT", "OPT"],
+            TerritoryParty::Writer => &["SWT"],
+        }
+    }
+}
+
+impl ValidationRule for TerritorySequenceCheck {
+    fn id(&self) -> RuleId {
+        match self.party {
+            TerritoryParty::Publisher => RuleId::new("SPT", RuleScope::Record, 5),
+            TerritoryParty::Writer => RuleId::new("SWT", RuleScope::Record, 2),
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        match self.party {
+            TerritoryParty::Publisher => {
+                "Each Territory (TIS code) included on an SPT/OPT record can only be linked to one SPU/OPU for a given right"
+            }
+            TerritoryParty::Writer => {
+                "Each Territory (TIS code) included on an SWT record can only be linked to one SWR for a given right"
+            }
+        }
+    }
+
+    fn level(&self) -> RejectionLevel {
+        RejectionLevel::Transaction
+    }
+
+    fn check_transaction(&mut self, transaction: &Transaction) -> Vec<RuleViolation> {
+        if !is_work_transaction(transaction.header_type()) {
+            return Vec::new();
+        }
+        // Territory sequences by the line of the publisher or writer they follow
+        let mut sequences: BTreeMap<usize, Vec<(usize, &CwrRegistry)>> = BTreeMap::new();
+        let links = territory_links(transaction);
+        for link in links.iter().filter(|link| self.territory_types().contains(&link.record.record_type())) {
+            if let Some((parent_line, _)) = link.parent {
+                sequences.entry(parent_line).or_default().push((link.line, link.record));
+            }
+        }
+
+        let work = work_label(transaction);
+        let mut violations = Vec::new();
+        for records in sequences.values() {
+            let entries: Vec<(InclusionExclusionIndicator, u16)> = records
+                .iter()
+                .filter_map(|(_, record)| match record {
+                    CwrRegistry::Spt(spt) => Some((spt.inclusion_exclusion_indicator.clone(), spt.tis_numeric_code.0)),
+                    CwrRegistry::Swt(swt) => Some((swt.inclusion_exclusion_indicator.clone(), swt.tis_numeric_code.0)),
+                    _ => None,
+                })
+                .collect();
+            for conflict in expand_sequence(&entries).conflicts {
+                let (index, tis_code) = match conflict {
+                    TerritoryConflict::UnknownTerritory { index, tis_code }
+                    | TerritoryConflict::LeadingExclusion { index, tis_code }
+                    | TerritoryConflict::IneffectiveExclusion { index, tis_code }
+                    | TerritoryConflict::RedundantInclusion { index, tis_code }
+                    | TerritoryConflict::IncludedAfterExclusion { index, tis_code, .. } => (index, tis_code),
+                    // Reported on the exclusion, which comes after the inclusion it contradicts
+                    TerritoryConflict::IncludedAndExcluded { tis_code } => {
+                        let index = entries
+                            .iter()
+                            .rposition(|(indicator, code)| {
+                                *code == tis_code && matches!(indicator, InclusionExclusionIndicator::Excluded)
+                            })
+                            .unwrap_or_default();
+                        (index, tis_code)
+                    }
+                };
+                let (line, record) = records[index];
+                violations.push(
+                    self.violation(
+                        line,
+                        record.record_type(),
+                        format!("{} in {}: {}", record.record_type(), work, conflict),
+                    )
+                    .with_field("tis_numeric_code", format!("{:04}", tis_code)),
+                );
+            }
+        }
+        violations
+    }
+}
+
+/// For performing rights, the collection shares of a chain's SPT records for a territory must equal the ownership of
+/// its original publisher and acquirers, unless the shares change on sub-publication. Income participants receive
+/// shares outside the chain, so works with one are skipped.
+struct CollectionMatchesOwnership;
+
+impl ValidationRule for CollectionMatchesOwnership {
+    fn id(&self) -> RuleId {
+        RuleId::new("NWR", RuleScope::Transaction, 27)
+    }
+
+    fn description(&self) -> &'static str {
+        "For performing rights, within each chain the SPT collection shares for a territory must equal the original publisher and acquirer ownership"
+    }
+
+    fn level(&self) -> RejectionLevel {
+        RejectionLevel::Transaction
+    }
+
+    fn check_transaction(&mut self, transaction: &Transaction) -> Vec<RuleViolation> {
+        if !is_work_transaction(transaction.header_type()) {
+            return Vec::new();
+        }
+        let chains = publisher_chains(transaction);
+        let has_income_participant = chains
+            .iter()
+            .flat_map(|chain| &chain.publishers)
+            .any(|(_, spu)| matches!(spu.publisher_type, Some(PublisherType::IncomeParticipant)))
+            || transaction.records.iter().any(|(_, record)| match record {
+                CwrRegistry::Swr(swr) => swr.writer_designation_code.as_ref().is_some_and(|code| code.0 == "PA"),
+                _ => false,
+            });
+        if has_income_participant {
+            return Vec::new();
+        }
+
+        let mut ownership: BTreeMap<u8, OwnershipShare> = BTreeMap::new();
+        for chain in &chains {
+            let owned: OwnershipShare = chain
+                .publishers
+                .iter()
+                .filter(|(_, spu)| {
+                    matches!(spu.publisher_type, Some(PublisherType::OriginalPublisher | PublisherType::Acquirer))
+                })
+                .map(|(_, spu)| spu.ownership_shares().pr)
+                .sum();
+            *ownership.entry(chain.sequence).or_default() += owned;
+        }
+
+        let mut collection: BTreeMap<(u8, u16), (usize, OwnershipShare)> = BTreeMap::new();
+        for link in territory_links(transaction) {
+            let (CwrRegistry::Spt(spt), Some((_, CwrRegistry::Spu(spu)))) = (link.record, link.parent) else {
+                continue;
+            };
+            if !spt.is_controlled()
+                || !matches!(spt.inclusion_exclusion_indicator, InclusionExclusionIndicator::Included)
+                || matches!(spt.shares_change, Some(Flag::Yes))
+            {
+                continue;
+            }
+            let entry = collection
+                .entry((spu.publisher_sequence_num.0, spt.tis_numeric_code.0))
+                .or_insert((link.line, OwnershipShare::default()));
+            entry.1 += spt.pr_collection_share.unwrap_or_default();
+        }
+
+        let work = work_label(transaction);
+        collection
+            .into_iter()
+            .filter_map(|((sequence, tis_code), (line, collected))| {
+                let owned = ownership.get(&sequence).copied().unwrap_or_default();
+                (collected.0.abs_diff(owned.0) > SHARE_TOLERANCE).then(|| {
+                    self.violation(
+                        line,
+                        "SPT",
+                        format!(
+                            "PR collection share {:.2}% for TIS {:04} in publisher chain {} of {} does not equal ownership share {:.2}%",
+                            collected.as_percentage(),
+                            tis_code,
+                            sequence,
+                            work,
+                            owned.as_percentage()
+                        ),
+                    )
+                })
+            })
+            .collect()
+    }
+}
+
+// SWR and PWR writer to publisher validation (sections 5.9 and 5.14)
+
+struct SwrHasPwr;
+
+impl ValidationRule for SwrHasPwr {
+    fn id(&self) -> RuleId {
+        RuleId::new("SWR", RuleScope::Record, 2)
+    }
+
+    fn description(&self) -> &'static str {
+        "Unless the writers' ownership shares total 100% for each right, each SWR must be followed by a PWR"
+    }
+
+    fn level(&self) -> RejectionLevel {
+        RejectionLevel::Transaction
+    }
+
+    fn check_transaction(&mut self, transaction: &Transaction) -> Vec<RuleViolation> {
+        if !is_work_transaction(transaction.header_type()) {
+            return Vec::new();
+        }
+        let writer_shares: ShareTotals = transaction
+            .records
+            .iter()
+            .filter_map(|(_, record)| match record {
+                CwrRegistry::Swr(swr) => Some(swr.ownership_shares()),
+                _ => None,
+            })
+            .sum();
+        let unpublished = writer_shares
+            .each()
+            .iter()
+            .all(|(_, share)| share.0.saturating_add(SHARE_TOLERANCE) >= OwnershipShare::FULL.0);
+        if unpublished {
+            return Vec::new();
+        }
+
+        let published: HashSet<usize> =
+            writer_links(transaction).iter().filter_map(|link| link.writer.map(|(line, _)| line)).collect();
+        let work = work_label(transaction);
+        transaction
+            .records
+            .iter()
+            .filter(|(line, record)| record.record_type() == "SWR" && !published.contains(line))
+            .map(|(line, record)| {
+                self.violation(
+                    *line,
+                    "SWR",
+                    format!(
+                        "SWR '{}' in {} has no PWR record, but the writers own only PR {:.2}%, MR {:.2}%, SR {:.2}%",
+                        interested_party_num(record),
+                        work,
+                        writer_shares.pr.as_percentage(),
+                        writer_shares.mr.as_percentage(),
+                        writer_shares.sr.as_percentage()
+                    ),
+                )
+            })
+            .collect()
+    }
+}
+
+#[derive(Clone, Copy)]
+enum PwrLinkRule {
+    PublisherSequence,
+    WriterEntered,
+    PublisherEntered,
+    Writer,
+    Publisher,
+}
+
+/// The writer IP # was added to PWR in version 2.1 and the publisher sequence # in 2.2, so whether they must be
+/// entered depends on the version the file is read as
+struct PwrLinkCheck {
+    rule: PwrLinkRule,
+    version: Option<f32>,
+}
+
+impl PwrLinkCheck {
+    fn new(rule: PwrLinkRule) -> Self {
+        PwrLinkCheck { rule, version: None }
+    }
+
+    fn requires(&self, version: f32) -> bool {
+        self.version.is_some_and(|declared| declared >= version)
+    }
+
+    fn check_link(
+        &self, link: &WriterLink<'_>, transaction: &Transaction, originals: &[&SpuRecord], work: &str,
+    ) -> Option<RuleViolation> {
+        let follows_swr = link.writer.is_some_and(|(_, writer)| writer.record_type() == "SWR");
+        match self.rule {
+            PwrLinkRule::PublisherSequence => match &link.pwr.publisher_sequence_num {
+                None if self.requires(2.2) => {
+                    Some(self.violation(link.line, "PWR", format!("PWR in {} has no publisher sequence #", work)))
+                }
+                None => None,
+                Some(sequence) if !originals.iter().any(|spu| spu.publisher_sequence_num == *sequence) => Some(
+                    self.violation(
+                        link.line,
+                        "PWR",
+                        format!(
+                            "PWR publisher sequence # {} in {} does not refer to an original publisher or income participant",
+                            sequence.0, work
+                        ),
+                    )
+                    .with_field("publisher_sequence_num", sequence.as_str()),
+                ),
+                Some(_) => None,
+            },
+            PwrLinkRule::WriterEntered => {
+                let (_, writer) = link.writer?;
+                let ip_num = link.pwr.writer_ip_num.as_deref().unwrap_or_default().trim();
+                // A blank writer IP # is allowed after an OWR, and before version 2.1 the field did not exist
+                (ip_num.is_empty() && follows_swr && self.requires(2.1)).then(|| {
+                    self.violation(
+                        link.line,
+                        "PWR",
+                        format!("PWR in {} has no writer IP #, expected '{}'", work, interested_party_num(writer)),
+                    )
+                })
+            }
+            PwrLinkRule::PublisherEntered => {
+                let ip_num = link.pwr.publisher_ip_num.as_deref().unwrap_or_default().trim();
+                (ip_num.is_empty() && follows_swr)
+                    .then(|| self.violation(link.line, "PWR", format!("PWR in {} has no publisher IP #", work)))
+            }
+            PwrLinkRule::Writer => {
+                let Some((_, writer)) = link.writer else {
+                    return Some(self.violation(
+                        link.line,
+                        "PWR",
+                        format!("PWR in {} does not follow an SWR or OWR record", work),
+                    ));
+                };
+                let ip_num = link.pwr.writer_ip_num.as_deref().unwrap_or_default().trim();
+                let writer_ip_num = interested_party_num(writer);
+                if ip_num.is_empty() || ip_num == writer_ip_num {
+                    return None;
+                }
+                let other_writer = transaction.records.iter().any(|(_, record)| {
+                    matches!(record, CwrRegistry::Swr(_)) && interested_party_num(record) == ip_num
+                });
+                let problem = if other_writer { "refers to another writer, not" } else { "does not match" };
+                Some(
+                    self.violation(
+                        link.line,
+                        "PWR",
+                        format!(
+                            "PWR writer IP # '{}' in {} {} the preceding {} '{}'",
+                            ip_num,
+                            work,
+                            problem,
+                            writer.record_type(),
+                            writer_ip_num
+                        ),
+                    )
+                    .with_field("writer_ip_num", ip_num),
+                )
+            }
+            PwrLinkRule::Publisher => {
+                let ip_num = link.pwr.publisher_ip_num.as_deref().unwrap_or_default().trim();
+                if ip_num.is_empty() {
+                    return None;
+                }
+                let referenced: Vec<&SpuRecord> = originals
+                    .iter()
+                    .copied()
+                    .filter(|spu| {
+                        link.pwr.publisher_sequence_num.as_ref().is_none_or(|sequence| spu.publisher_sequence_num == *sequence)
+                    })
+                    .collect();
+                if referenced.iter().any(|spu| spu.interested_party_num.as_deref().unwrap_or_default().trim() == ip_num) {
+                    return None;
+                }
+                let message = match (&link.pwr.publisher_sequence_num, referenced.first()) {
+                    (Some(sequence), Some(spu)) => format!(
+                        "PWR publisher IP # '{}' in {} does not match '{}', the original publisher of chain {}",
+                        ip_num,
+                        work,
+                        spu.interested_party_num.as_deref().unwrap_or_default().trim(),
+                        sequence.0
+                    ),
+                    _ => format!(
+                        "PWR publisher IP # '{}' in {} does not match any original publisher or income participant",
+                        ip_num, work
+                    ),
+                };
+                Some(self.violation(link.line, "PWR", message).with_field("publisher_ip_num", ip_num))
+            }
+        }
+    }
+}
+
+impl ValidationRule for PwrLinkCheck {
+    fn id(&self) -> RuleId {
+        let number = match self.rule {
+            PwrLinkRule::PublisherSequence => 7,
+            PwrLinkRule::WriterEntered => 8,
+            PwrLinkRule::PublisherEntered => 9,
+            PwrLinkRule::Writer => 11,
+            PwrLinkRule::Publisher => 12,
+        };
+        RuleId::new("PWR", RuleScope::Field, number)
+    }
+
+    fn description(&self) -> &'static str {
+        match self.rule {
+            PwrLinkRule::PublisherSequence => {
+                "Publisher Sequence # must be entered and must match the Publisher Sequence # of the original SPU/OPU"
+            }
+            PwrLinkRule::WriterEntered => {
+                "For controlled chains (where the PWR follows an SWR) writer IP # must be entered"
+            }
+            PwrLinkRule::PublisherEntered => {
+                "For controlled chains (where the PWR follows an SWR) the Publisher IP # must be entered"
+            }
+            PwrLinkRule::Writer => "Writer IP # must match the Interested Party # on the preceding SWR/OWR record",
+            PwrLinkRule::Publisher => {
+                "Publisher IP # must match the original publisher referenced by the publisher sequence #"
+            }
+        }
+    }
+
+    fn level(&self) -> RejectionLevel {
+        RejectionLevel::Transaction
+    }
+
+    fn check_record(&mut self, record: &RecordRef<'_>) -> Vec<RuleViolation> {
+        self.version = record.cwr_version;
+        Vec::new()
+    }
+
+    fn check_transaction(&mut self, transaction: &Transaction) -> Vec<RuleViolation> {
+        if !is_work_transaction(transaction.header_type()) {
+            return Vec::new();
+        }
+        let chains = publisher_chains(transaction);
+        let originals: Vec<&SpuRecord> = chains
+            .iter()
+            .flat_map(|chain| &chain.publishers)
+            .map(|(_, spu)| *spu)
+            .filter(|spu| is_chain_start(spu))
+            .collect();
+        let work = work_label(transaction);
+        writer_links(transaction)
+            .iter()
+            .filter_map(|link| self.check_link(link, transaction, &originals, &work))
+            .collect()
+    }
+}
+
+// TER field level validation (section 5.1)
+
+struct TerTisCodeExists;
+
+impl ValidationRule for TerTisCodeExists {
+    fn id(&self) -> RuleId {
+        RuleId::new("TER", RuleScope::Field, 2)
+    }
+
+    fn description(&self) -> &'static str {
+        "The TIS Numeric Code must match an entry in the TIS"
+    }
+
+    fn level(&self) -> RejectionLevel {
+        RejectionLevel::Transaction
+    }
+
+    fn check_record(&mut self, record: &RecordRef<'_>) -> Vec<RuleViolation> {
+        let CwrRegistry::Ter(ter) = record.record else {
+            return Vec::new();
+        };
+        if !territory_exists(ter.tis_numeric_code.0) {
+            return vec![self
+                .violation(record.line_number, "TER", format!("TIS code {:04} not found", ter.tis_numeric_code.0))
+                .with_field("tis_numeric_code", format!("{:04}", ter.tis_numeric_code.0))];
+        }
+        Vec::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::RuleEngine;
+    use allegro_cwr::cwr_registry::parse_by_record_type;
+
+    fn feed(engine: &mut RuleEngine, lines: &[&str]) {
+        for (index, line) in lines.iter().enumerate() {
+            let (record, _) = parse_by_record_type(&line[0..3], line).unwrap();
+            engine.check_record(index + 1, &record);
+        }
+    }
+
+    #[test]
+    fn test_rules_keyed_by_id() {
+        let mut engine = RuleEngine::with_default_rules();
+        feed(
+            &mut engine,
+            &[
+                "GRHAGR0000102.20            ",
+                "AGR00000001000000011234567890123               AA20231201                N        N                00001                 ",
+                "TER0000000100000001I2840",
+                "GRT000010000000100000009             ",
+            ],
+        );
+        let report = engine.finish();
+
+        let first_hdr = RuleId::new("HDR", RuleScope::File, 2);
+        let record_count = RuleId::new("GRT", RuleScope::Field, 3);
+        let acquirer = RuleId::new("AGR", RuleScope::Transaction, 3);
+        let last_trl = RuleId::new("HDR", RuleScope::File, 5);
+        assert_eq!(report.for_rule(&first_hdr).len(), 1);
+        assert_eq!(report.for_rule(&record_count).len(), 1);
+        assert_eq!(report.for_rule(&acquirer).len(), 1);
+        assert_eq!(report.for_rule(&last_trl)[0].line_number, 4);
+        assert!(report.for_rule(&RuleId::new("AGR", RuleScope::Transaction, 2)).is_empty());
+        assert!(report.for_rule(&RuleId::new("GRT", RuleScope::Field, 2)).is_empty());
+        assert_eq!(report.highest_level(), Some(RejectionLevel::EntireFile));
+    }
+
+    #[test]
+    fn test_disabled_rule_not_reported() {
+        let mut engine = RuleEngine::with_default_rules();
+        let first_hdr: RuleId = "hdr-fil-002".parse().unwrap();
+        engine.disable(first_hdr);
+        feed(&mut engine, &["GRHAGR0000102.20            "]);
+        let report = engine.finish();
+        assert!(report.for_rule(&first_hdr).is_empty());
+        assert!(!engine.is_enabled(&first_hdr));
+    }
+
+    #[test]
+    fn test_ack_group_counts() {
+        let ack = |sequence: u32| {
+            format!(
+                "ACK{sequence:08}00000000200501011200000000100000001NWRTEST WORK TITLE{:<43}SW123456789012345678{:<20}20050102AS   ",
+                "", ""
+            )
+        };
+        let nwr = |sequence: u32| {
+            format!(
+                "NWR{sequence:08}00000001{:<62}{:<14}        SER        Y       ORI{:<141}",
+                "Test Song", "SW0000000001", ""
+            )
+        };
+        let report = |transaction_count: u32| {
+            let mut engine = RuleEngine::with_default_rules();
+            let lines = [
+                "HDRSO000000021BMI                                          01.102022122112541120221221".to_string(),
+                "GRHACK0000102.20            ".to_string(),
+                ack(0),
+                nwr(0),
+                ack(1),
+                nwr(1),
+                format!("GRT00001{transaction_count:08}00000006"),
+                format!("TRL00001{transaction_count:08}00000008"),
+            ];
+            feed(&mut engine, &lines.iter().map(String::as_str).collect::<Vec<_>>());
+            engine.finish()
+        };
+
+        // The NWR records acknowledged inside each ACK transaction are not transactions of their own
+        let valid = report(2);
+        for rule_id in ["HDR-FIL-006", "GRT-FLD-002", "GRT-FLD-003", "TRL-FLD-002", "TRL-FLD-003"] {
+            assert!(valid.for_rule(&rule_id.parse().unwrap()).is_empty(), "{}", rule_id);
+        }
+
+        let overcounted = report(4);
+        assert_eq!(violation_lines(&overcounted, "GRT-FLD-002"), [7]);
+        assert_eq!(violation_lines(&overcounted, "TRL-FLD-002"), [8]);
+    }
+
+    fn spu_line(record_type: &str, sequence: u8, ip_num: &str, publisher_type: &str, agreement: &str) -> String {
+        format!(
+            "{record_type}0000000100000002{sequence:02}{ip_num:<9}{:<45} {publisher_type:<2}{:<20}{agreement:<14}{:<69}",
+            format!("PUBLISHER {ip_num}"),
+            "",
+            "021050000210000002100000"
+        )
+    }
+
+    fn publisher_chain_nwr() -> String {
+        format!("NWR0000000100000001{:<62}{:<14}        SER        Y       ORI{:<141}", "Test Song", "SW0000000001", "")
+    }
+
+    fn publisher_chain_report(lines: &[String]) -> crate::engine::ValidationReport {
+        let mut engine = RuleEngine::new();
+        for rule in default_rules() {
+            engine.add_rule(rule);
+        }
+        let mut records = vec![publisher_chain_nwr()];
+        records.extend_from_slice(lines);
+        let records: Vec<&str> = records.iter().map(String::as_str).collect();
+        feed(&mut engine, &records);
+        engine.finish()
+    }
+
+    #[test]
+    fn test_valid_publisher_chains() {
+        let report = publisher_chain_report(&[
+            spu_line("SPU", 1, "PUB1", "E", ""),
+            spu_line("SPU", 1, "ADM1", "AM", ""),
+            spu_line("SPU", 2, "PUB2", "E", ""),
+            spu_line("SPU", 2, "SUB2", "SE", ""),
+            spu_line("OPU", 3, "OTHER", "", ""),
+        ]);
+        for number in [16, 17] {
+            assert!(report.for_rule(&RuleId::new("NWR", RuleScope::Transaction, number)).is_empty());
+        }
+        assert!(report.for_rule(&RuleId::new("SPU", RuleScope::Record, 2)).is_empty());
+        assert!(report.for_rule(&RuleId::new("SPU", RuleScope::Record, 5)).is_empty());
+        assert!(report.for_rule(&RuleId::new("SPU", RuleScope::Field, 1)).is_empty());
+    }
+
+    #[test]
+    fn test_broken_publisher_chains() {
+        let report = publisher_chain_report(&[
+            spu_line("SPU", 1, "PUB1", "E", ""),
+            spu_line("OPU", 1, "OTHER", "SE", ""),
+            spu_line("SPU", 1, "PUB2", "E", ""),
+            spu_line("SPU", 3, "SUB3", "SE", ""),
+            spu_line("SPU", 1, "ADM1", "AM", ""),
+        ]);
+
+        let opu = report.for_rule(&RuleId::new("NWR", RuleScope::Transaction, 16));
+        assert_eq!(opu.len(), 1);
+        assert_eq!(opu[0].line_number, 3);
+        assert!(opu[0].message.contains("'Test Song' (SW0000000001)"), "{}", opu[0].message);
+        assert_eq!(report.for_rule(&RuleId::new("NWR", RuleScope::Transaction, 17))[0].line_number, 4);
+
+        let first = report.for_rule(&RuleId::new("SPU", RuleScope::Record, 2));
+        assert_eq!(first.iter().map(|v| v.line_number).collect::<Vec<_>>(), vec![5, 6]);
+        let unlinked = report.for_rule(&RuleId::new("SPU", RuleScope::Record, 5));
+        assert_eq!(unlinked.iter().map(|v| v.line_number).collect::<Vec<_>>(), vec![5]);
+
+        // Chain 3 skips 2, and chain 1 reappears after it
+        let order = report.for_rule(&RuleId::new("SPU", RuleScope::Field, 1));
+        assert_eq!(order.iter().map(|v| v.line_number).collect::<Vec<_>>(), vec![5, 6]);
+        assert_eq!(order[0].value.as_deref(), Some("03"));
+    }
+
+    #[test]
+    fn test_spu_agreement_in_file() {
+        let agreement_rule = RuleId::new("SPU", RuleScope::Field, 11);
+        let without_agreements = publisher_chain_report(&[spu_line("SPU", 1, "PUB1", "E", "AGR-404")]);
+        assert!(without_agreements.for_rule(&agreement_rule).is_empty());
+
+        let mut engine = RuleEngine::new();
+        engine.add_rule(Box::new(SpuAgreementInFile::default()));
+        let agr = "AGR00000001000000011234567890123               AA20231201                N        N                00001                 ";
+        let known = spu_line("SPU", 1, "SUB1", "SE", "1234567890123");
+        let unknown = spu_line("SPU", 1, "SUB2", "SE", "AGR-404");
+        feed(&mut engine, &[agr, &known, &unknown]);
+        let report = engine.finish();
+        let violations = report.for_rule(&agreement_rule);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].line_number, 3);
+        assert_eq!(violations[0].value.as_deref(), Some("AGR-404"));
+    }
+
+    fn territory_line(record_type: &str, ip_num: &str, pr_share: u16, shares_change: &str, sequence: u32) -> String {
+        let constant = if matches!(record_type, "SPT" | "OPT") { "      " } else { "" };
+        format!("{record_type}0000000100000003{ip_num:<9}{constant}{pr_share:05}0000000000I2136{shares_change}{sequence:03}")
+    }
+
+    fn swr_line(record_type: &str, ip_num: &str) -> String {
+        format!(
+            "{record_type}0000000100000004{ip_num:<9}WOMACK                                       BOBBY                          CA00000000000033188001021050000990000009900000 N                           B"
+        )
+    }
+
+    fn violation_lines(report: &crate::engine::ValidationReport, rule_id: &str) -> Vec<usize> {
+        report.for_rule(&rule_id.parse().unwrap()).iter().map(|v| v.line_number).collect()
+    }
+
+    #[test]
+    fn test_territory_links() {
+        let report = publisher_chain_report(&[
+            spu_line("SPU", 1, "PUB1", "E", ""),
+            territory_line("SPT", "PUB1", 5000, "N", 1),
+            territory_line("SPT", "OTHER", 0, "N", 3),
+            swr_line("SWR", "WOMA"),
+            territory_line("SWT", "WOMA", 5000, "N", 1),
+            territory_line("SPT", "WOMA", 0, "N", 2),
+            territory_line("OWT", "WOMA", 0, "N", 3),
+        ]);
+
+        assert_eq!(violation_lines(&report, "SPT-FLD-001"), vec![7]);
+        assert_eq!(violation_lines(&report, "SWT-FLD-011"), vec![8]);
+        assert!(violation_lines(&report, "SWT-FLD-001").is_empty());
+        assert_eq!(violation_lines(&report, "SPT-FLD-002"), vec![4]);
+        assert!(violation_lines(&report, "SWT-FLD-002").is_empty());
+        assert_eq!(violation_lines(&report, "SPT-FLD-013"), vec![4]);
+        assert!(violation_lines(&report, "SWT-FLD-010").is_empty());
+        let sequence = &report.for_rule(&"SPT-FLD-013".parse().unwrap())[0];
+        assert_eq!(sequence.level, RejectionLevel::Record);
+        assert_eq!(sequence.value.as_deref(), Some("003"));
+    }
+
+    #[test]
+    fn test_collection_matches_ownership() {
+        let report = publisher_chain_report(&[
+            spu_line("SPU", 1, "PUB1", "E", ""),
+            territory_line("SPT", "PUB1", 5000, "N", 1),
+            spu_line("SPU", 1, "ADM1", "AM", ""),
+            territory_line("SPT", "ADM1", 2500, "N", 1),
+        ]);
+        let publisher = report.for_rule(&"NWR-TRN-027".parse().unwrap());
+        assert_eq!(publisher.len(), 1);
+        assert_eq!(publisher[0].line_number, 3);
+        assert!(publisher[0].message.contains("PR collection share 75.00%"), "{}", publisher[0].message);
+
+        // Collecting less than is owned is as wrong as collecting more
+        let below =
+            publisher_chain_report(&[spu_line("SPU", 1, "PUB1", "E", ""), territory_line("SPT", "PUB1", 2500, "N", 1)]);
+        assert_eq!(violation_lines(&below, "NWR-TRN-027"), vec![3]);
+
+        let equal = publisher_chain_report(&[
+            spu_line("SPU", 1, "PUB1", "E", ""),
+            territory_line("SPT", "PUB1", 4995, "N", 1),
+            territory_line("SPT", "PUB1", 0, "Y", 2).replace("I2136", "I0250"),
+        ]);
+        assert!(violation_lines(&equal, "NWR-TRN-027").is_empty());
+    }
+
+    #[test]
+    fn test_territory_sequences() {
+        let report = publisher_chain_report(&[
+            spu_line("SPU", 1, "PUB1", "E", ""),
+            territory_line("SPT", "PUB1", 5000, "N", 1),
+            territory_line("SPT", "PUB1", 5000, "N", 2).replace("I2136", "I0250"),
+            swr_line("SWR", "WOMA"),
+            territory_line("SWT", "WOMA", 5000, "N", 1),
+            territory_line("SWT", "WOMA", 0, "N", 2).replace("I2136", "E0250"),
+            territory_line("SWT", "WOMA", 5000, "N", 3).replace("I2136", "I0250"),
+        ]);
+
+        let redundant = report.for_rule(&"SPT-REC-005".parse().unwrap());
+        assert_eq!(redundant.len(), 1);
+        assert_eq!(redundant[0].line_number, 4);
+        assert_eq!(redundant[0].value.as_deref(), Some("0250"));
+        assert!(redundant[0].message.contains("already covered"), "{}", redundant[0].message);
+        let mut writer = violation_lines(&report, "SWT-REC-002");
+        writer.sort();
+        assert_eq!(writer, vec![7, 8]);
+
+        let consistent = publisher_chain_report(&[
+            spu_line("SPU", 1, "PUB1", "E", ""),
+            territory_line("SPT", "PUB1", 5000, "N", 1),
+            territory_line("SPT", "PUB1", 0, "N", 2).replace("I2136", "E0250"),
+        ]);
+        assert!(violation_lines(&consistent, "SPT-REC-005").is_empty());
+    }
+
+    fn pwr_line(publisher_ip_num: &str, writer_ip_num: &str, sequence: &str) -> String {
+        format!(
+            "PWR0000000100000005{publisher_ip_num:<9}{:<45}{:<28}{writer_ip_num:<9}{sequence:<2}",
+            format!("PUBLISHER {publisher_ip_num}"),
+            ""
+        )
+    }
+
+    #[test]
+    fn test_pwr_links() {
+        let hdr = "HDRPB123456789BMI MUSIC                                    01.1020050101120000200501010              2.2  1DEV MUSIC SOF";
+        let lines = [
+            spu_line("SPU", 1, "PUB1", "E", ""),
+            spu_line("SPU", 2, "PUB2", "E", ""),
+            swr_line("SWR", "WOMA"),
+            pwr_line("PUB1", "WOMA", "01"),
+            pwr_line("PUB2", "WOMA", "01"),
+            swr_line("SWR", "JONES"),
+            pwr_line("PUB2", "WOMA", "03"),
+            pwr_line("", "", ""),
+            swr_line("SWR", "SMITH"),
+            swr_line("OWR", "OTHER"),
+            pwr_line("", "", "01"),
+        ];
+        let mut records = vec![hdr.to_string(), publisher_chain_nwr()];
+        records.extend(lines);
+        let records: Vec<&str> = records.iter().map(String::as_str).collect();
+        let mut engine = RuleEngine::with_default_rules();
+        engine.set_cwr_version(Some(2.2));
+        feed(&mut engine, &records);
+        let report = engine.finish();
+
+        assert_eq!(violation_lines(&report, "PWR-FLD-007"), vec![9, 10]);
+        assert_eq!(violation_lines(&report, "PWR-FLD-008"), vec![10]);
+        assert_eq!(violation_lines(&report, "PWR-FLD-009"), vec![10]);
+        assert_eq!(violation_lines(&report, "PWR-FLD-011"), vec![9]);
+        assert_eq!(violation_lines(&report, "PWR-FLD-012"), vec![7, 9]);
+        assert_eq!(violation_lines(&report, "SWR-REC-002"), vec![11]);
+        let writer = &report.for_rule(&"PWR-FLD-011".parse().unwrap())[0];
+        assert!(writer.message.contains("refers to another writer"), "{}", writer.message);
+
+        // Version 2.1 requires the writer IP # but not yet the publisher sequence #; its HDR declares no version
+        let blank_writer = [spu_line("SPU", 1, "PUB1", "E", ""), swr_line("SWR", "WOMA"), pwr_line("PUB1", "", "")];
+        let mut engine = RuleEngine::with_default_rules();
+        engine.set_cwr_version(Some(2.1));
+        let mut records = vec![publisher_chain_nwr()];
+        records.extend_from_slice(&blank_writer);
+        feed(&mut engine, &records.iter().map(String::as_str).collect::<Vec<_>>());
+        let report = engine.finish();
+        assert!(violation_lines(&report, "PWR-FLD-007").is_empty());
+        assert_eq!(violation_lines(&report, "PWR-FLD-008"), vec![4]);
+
+        // Without a known version neither is required
+        let report = publisher_chain_report(&blank_writer);
+        assert!(violation_lines(&report, "PWR-FLD-007").is_empty());
+        assert!(violation_lines(&report, "PWR-FLD-008").is_empty());
+    }
+
+    #[test]
+    fn test_unpublished_writer_needs_no_pwr() {
+        let unpublished = swr_line("SWR", "SOLO").replace("021050000990000009900000", "021100000991000009910000");
+        let report = publisher_chain_report(&[unpublished]);
+        assert!(violation_lines(&report, "SWR-REC-002").is_empty());
+        let report = publisher_chain_report(&[swr_line("SWR", "SOLO")]);
+        assert_eq!(violation_lines(&report, "SWR-REC-002"), vec![2]);
+    }
+
+    #[test]
+    fn test_rule_id_round_trip() {
+        let rule_id = RuleId::new("GRT", RuleScope::Field, 2);
+        assert_eq!(rule_id.to_string(), "GRT-FLD-002");
+        assert_eq!("GRT-FLD-002".parse::<RuleId>(), Ok(rule_id));
+        assert!("XYZ-FLD-002".parse::<RuleId>().is_err());
+    }
+}